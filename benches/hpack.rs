@@ -0,0 +1,78 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::header::table::{HeaderTable, StaticTable, STATIC_HEADER_FIELDS_TABLE_CONSTANTS};
+
+/// Header fields of a typical browser request.
+const REQUEST: [(&str, &str); 8] = [
+    (":method", "GET"),
+    (":scheme", "https"),
+    (":authority", "www.example.com"),
+    (":path", "/index.html"),
+    ("accept", "text/html,application/xhtml+xml"),
+    ("accept-encoding", "gzip, deflate"),
+    ("user-agent", "Mozilla/5.0"),
+    ("www-authenticate", "Basic"),
+];
+
+fn header_fields() -> Vec<HeaderField> {
+    REQUEST
+        .iter()
+        .map(|(name, value)| HeaderField::new((*name).into(), (*value).into()))
+        .collect()
+}
+
+/// Baseline: look a header field up by scanning the static table entries.
+fn linear_scan(header_field: &HeaderField) -> Option<usize> {
+    STATIC_HEADER_FIELDS_TABLE_CONSTANTS
+        .iter()
+        .position(|(name, value)| {
+            header_field.name_ref().as_str() == *name && header_field.value_ref().as_str() == *value
+        })
+        .or_else(|| {
+            STATIC_HEADER_FIELDS_TABLE_CONSTANTS
+                .iter()
+                .position(|(name, _)| header_field.name_ref().as_str() == *name)
+        })
+}
+
+fn bench_static_table_lookup(c: &mut Criterion) {
+    let mut group = c.benchmark_group("static_table_lookup");
+    let header_fields = header_fields();
+
+    group.bench_function("linear_scan", |b| {
+        b.iter(|| {
+            for header_field in &header_fields {
+                black_box(linear_scan(black_box(header_field)));
+            }
+        })
+    });
+
+    group.bench_function("match", |b| {
+        let static_table = StaticTable::from(STATIC_HEADER_FIELDS_TABLE_CONSTANTS);
+        b.iter(|| {
+            for header_field in &header_fields {
+                black_box(
+                    static_table
+                        .contains(black_box(header_field))
+                        .or_else(|| static_table.contains_name(header_field)),
+                );
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_header_list_encode(c: &mut Criterion) {
+    let header_list = HeaderList::new(header_fields());
+
+    // Without dynamic table, every header field is looked up in the static table.
+    c.bench_function("header_list_encode", |b| {
+        let mut header_table = HeaderTable::new(0);
+        b.iter(|| black_box(header_list.encode(&mut header_table).unwrap()))
+    });
+}
+
+criterion_group!(benches, bench_static_table_lookup, bench_header_list_encode);
+criterion_main!(benches);
@@ -0,0 +1,86 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use http2::header::huffman::{self, Tree, HPACK_HUFFMAN_CODE};
+
+/// RFC 7541 C.6.1: "Mon, 21 Oct 2013 20:13:21 GMT"
+const ENCODED: [u8; 22] = [
+    0xd0, 0x7a, 0xbe, 0x94, 0x10, 0x54, 0xd4, 0x44,
+    0xa8, 0x20, 0x05, 0x95, 0x04, 0x0b, 0x81, 0x66,
+    0xe0, 0x82, 0xa6, 0x2d, 0x1b, 0xff,
+];
+
+/// Header values of a typical browser request.
+const VALUES: [&str; 4] = [
+    "www.example.com",
+    "text/html,application/xhtml+xml,application/xml;q=0.9,*/*;q=0.8",
+    "Mozilla/5.0 (X11; Linux x86_64; rv:109.0) Gecko/20100101 Firefox/115.0",
+    "Mon, 21 Oct 2013 20:13:21 GMT",
+];
+
+/// Baseline: append the bits of each code one at a time.
+fn bit_pushing_encode(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded = Vec::new();
+    let mut byte = 0u8;
+    let mut bits = 0;
+
+    for symbol in bytes {
+        let (code, _) = HPACK_HUFFMAN_CODE
+            .iter()
+            .find(|(_, code_symbol)| code_symbol == symbol)
+            .unwrap();
+        for bit in code.bytes() {
+            byte = (byte << 1) | (bit - b'0');
+            bits += 1;
+            if bits == 8 {
+                encoded.push(byte);
+                byte = 0;
+                bits = 0;
+            }
+        }
+    }
+
+    if bits > 0 {
+        encoded.push((byte << (8 - bits)) | (0xff >> bits));
+    }
+
+    encoded
+}
+
+fn bench_huffman_encode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("huffman_encode");
+
+    group.bench_function("bit_pushing", |b| {
+        b.iter(|| {
+            for value in VALUES {
+                black_box(bit_pushing_encode(black_box(value.as_bytes())));
+            }
+        })
+    });
+
+    group.bench_function("table", |b| {
+        b.iter(|| {
+            for value in VALUES {
+                black_box(huffman::encode(black_box(value.as_bytes())));
+            }
+        })
+    });
+
+    group.finish();
+}
+
+fn bench_huffman_decode(c: &mut Criterion) {
+    let mut group = c.benchmark_group("huffman_decode");
+
+    group.bench_function("tree", |b| {
+        let tree = Tree::new().unwrap();
+        b.iter(|| tree.decode(&mut black_box(ENCODED.to_vec())).unwrap())
+    });
+
+    group.bench_function("state_machine", |b| {
+        b.iter(|| huffman::decode(black_box(&ENCODED)).unwrap())
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_huffman_encode, bench_huffman_decode);
+criterion_main!(benches);
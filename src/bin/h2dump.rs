@@ -0,0 +1,106 @@
+//! Decode captured HTTP/2 byte streams.
+//!
+//! Usage: h2dump [--hex] [FILE]...
+//!
+//! Every FILE holds the raw bytes sent in one direction of a connection,
+//! optionally preceded by the client connection preface. Each FILE is
+//! decoded with its own HPACK decoding context. With no FILE, or when
+//! FILE is -, the bytes are read from the standard input. With --hex, the
+//! input is read as hexadecimal text, such as "0x00, 0x00, 0x04" or
+//! "000004", instead of raw bytes.
+
+use std::fs;
+use std::io::{self, Read};
+use std::process::ExitCode;
+
+use http2::debug::dump_frames;
+
+const USAGE: &str = "Usage: h2dump [--hex] [FILE]...";
+
+/// Parse hexadecimal text into bytes.
+///
+/// Bytes may be separated by whitespace or commas and prefixed by "0x".
+///
+/// # Arguments
+///
+/// * `text` - The hexadecimal text.
+fn parse_hex(text: &str) -> Result<Vec<u8>, String> {
+    let digits: String = text
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .map(|token| token.trim_start_matches("0x").trim_start_matches("0X"))
+        .collect();
+
+    if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+        return Err(format!("invalid hexadecimal digit {:?}", c));
+    }
+
+    if !digits.len().is_multiple_of(2) {
+        return Err("odd number of hexadecimal digits".to_string());
+    }
+
+    Ok((0..digits.len())
+        .step_by(2)
+        .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap_or_default())
+        .collect())
+}
+
+/// Read the bytes of an input, "-" being the standard input.
+fn read_input(path: &str, hex: bool) -> Result<Vec<u8>, String> {
+    let bytes = if path == "-" {
+        let mut bytes = Vec::new();
+        io::stdin()
+            .read_to_end(&mut bytes)
+            .map_err(|error| format!("stdin: {}", error))?;
+        bytes
+    } else {
+        fs::read(path).map_err(|error| format!("{}: {}", path, error))?
+    };
+
+    if hex {
+        let text = String::from_utf8_lossy(&bytes);
+        parse_hex(&text).map_err(|error| format!("{}: {}", path, error))
+    } else {
+        Ok(bytes)
+    }
+}
+
+fn main() -> ExitCode {
+    let mut hex = false;
+    let mut paths: Vec<String> = Vec::new();
+
+    for argument in std::env::args().skip(1) {
+        match argument.as_str() {
+            "--hex" => hex = true,
+            "-h" | "--help" => {
+                println!("{}", USAGE);
+                return ExitCode::SUCCESS;
+            }
+            option if option.starts_with("--") => {
+                eprintln!("h2dump: unknown option {}\n{}", option, USAGE);
+                return ExitCode::FAILURE;
+            }
+            _ => paths.push(argument),
+        }
+    }
+
+    if paths.is_empty() {
+        paths.push("-".to_string());
+    }
+
+    for path in &paths {
+        let bytes = match read_input(path, hex) {
+            Ok(bytes) => bytes,
+            Err(error) => {
+                eprintln!("h2dump: {}", error);
+                return ExitCode::FAILURE;
+            }
+        };
+
+        if paths.len() > 1 {
+            println!("==> {} <==", path);
+        }
+        print!("{}", dump_frames(&bytes));
+    }
+
+    ExitCode::SUCCESS
+}
@@ -0,0 +1,148 @@
+use bytes::{Buf, BufMut, BytesMut};
+use tokio_util::codec::{Decoder, Encoder};
+
+use crate::error::{ErrorCode, Http2Error};
+use crate::frame::header_block::HeaderBlockGuard;
+use crate::frame::{
+    Frame, FrameHeader, DEFAULT_MAX_FRAME_SIZE, FRAME_HEADER_LENGTH, MAX_FRAME_SIZE_UPPER_BOUND,
+};
+use crate::header::table::HeaderTable;
+use crate::trace;
+
+/// HTTP/2 frame codec.
+///
+/// The codec implements the tokio-util `Decoder` and `Encoder` traits so
+/// that it can be used with `Framed` transports. It owns the HPACK header
+/// tables used to decode received header lists and to encode sent ones.
+///
+/// Frames larger than the advertised SETTINGS_MAX_FRAME_SIZE are rejected
+/// as soon as their header is buffered. Header blocks split over too many
+/// CONTINUATION frames, or too large, are rejected by a
+/// [`HeaderBlockGuard`].
+pub struct FrameCodec {
+    decoder_header_table: HeaderTable,
+    encoder_header_table: HeaderTable,
+    max_frame_size: u32,
+    header_block_guard: HeaderBlockGuard,
+}
+
+impl FrameCodec {
+    /// Create a new frame codec.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table_size` - The maximum size of the HPACK dynamic tables.
+    pub fn new(header_table_size: usize) -> Self {
+        FrameCodec {
+            decoder_header_table: HeaderTable::new(header_table_size),
+            encoder_header_table: HeaderTable::new(header_table_size),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            header_block_guard: HeaderBlockGuard::default(),
+        }
+    }
+
+    /// Get the maximum frame payload size accepted by the codec.
+    pub fn max_frame_size(&self) -> u32 {
+        self.max_frame_size
+    }
+
+    /// Set the maximum frame payload size accepted by the codec.
+    ///
+    /// The value must be the one advertised in SETTINGS_MAX_FRAME_SIZE,
+    /// between 2^14 and 2^24-1 octets.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_frame_size` - The maximum frame payload size.
+    pub fn set_max_frame_size(&mut self, max_frame_size: u32) -> Result<(), Http2Error> {
+        if !(DEFAULT_MAX_FRAME_SIZE..=MAX_FRAME_SIZE_UPPER_BOUND).contains(&max_frame_size) {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!("Invalid maximum frame size {}", max_frame_size),
+            ));
+        }
+
+        self.max_frame_size = max_frame_size;
+
+        Ok(())
+    }
+
+    /// Get the header table used to decode received header lists.
+    pub fn decoder_header_table(&mut self) -> &mut HeaderTable {
+        &mut self.decoder_header_table
+    }
+
+    /// Get the header table used to encode sent header lists.
+    pub fn encoder_header_table(&mut self) -> &mut HeaderTable {
+        &mut self.encoder_header_table
+    }
+
+    /// Get the guard limiting the size of received header blocks.
+    pub fn header_block_guard(&mut self) -> &mut HeaderBlockGuard {
+        &mut self.header_block_guard
+    }
+}
+
+impl Default for FrameCodec {
+    /// Create a frame codec with the default HPACK dynamic table size of 4096.
+    fn default() -> Self {
+        FrameCodec::new(4096)
+    }
+}
+
+impl Decoder for FrameCodec {
+    type Item = Frame;
+    type Error = Http2Error;
+
+    /// Decode a frame from the buffered bytes.
+    ///
+    /// Returns `Ok(None)` while the buffer does not hold a complete frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `src` - The buffered bytes.
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Frame>, Http2Error> {
+        // Wait for a complete frame header.
+        let frame_header = match src.first_chunk() {
+            Some(header_bytes) => FrameHeader::from_bytes(header_bytes),
+            None => return Ok(None),
+        };
+
+        // Reject oversized frames before buffering their payload.
+        frame_header.check_payload_length(self.max_frame_size)?;
+
+        // Wait for a complete frame payload.
+        let frame_length = FRAME_HEADER_LENGTH + frame_header.payload_length() as usize;
+        if src.len() < frame_length {
+            src.reserve(frame_length - src.len());
+            return Ok(None);
+        }
+        self.header_block_guard.check(&frame_header)?;
+
+        // Deserialize the frame and remove it from the buffer.
+        let mut bytes: Vec<u8> = src[..frame_length].to_vec();
+        let frame = Frame::deserialize(&mut bytes, &mut self.decoder_header_table)?;
+        src.advance(frame_length);
+        trace::frame_received(&frame_header);
+
+        Ok(Some(frame))
+    }
+}
+
+impl Encoder<Frame> for FrameCodec {
+    type Error = Http2Error;
+
+    /// Encode a frame into the output buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to encode.
+    /// * `dst` - The output buffer.
+    fn encode(&mut self, frame: Frame, dst: &mut BytesMut) -> Result<(), Http2Error> {
+        let bytes = frame.serialize(&mut self.encoder_header_table)?;
+        trace::frame_sent(&bytes);
+        dst.put_slice(&bytes);
+
+        Ok(())
+    }
+}
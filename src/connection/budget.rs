@@ -0,0 +1,136 @@
+use crate::error::{ErrorCode, Http2Error};
+
+/// Memory budget of a connection.
+///
+/// The budget accounts for the memory a connection holds on behalf of the
+/// peer: the content buffered until the application reads it, the header
+/// block being assembled, the HPACK encoding and decoding tables, and the
+/// control frames queued for the transport, such as the acknowledgements of
+/// the PING and SETTINGS frames of the peer. Multi-tenant servers cap each
+/// connection with a budget, so that a peer cannot exhaust the memory of
+/// the others.
+///
+/// Received content is under flow control: while the budget is exceeded,
+/// the connection flow-control window is no longer replenished, so that the
+/// peer stops sending until the application reads. The rest of the memory
+/// is not under flow control, and exceeding the budget with it alone is a
+/// connection error of type ENHANCE_YOUR_CALM. The content and header
+/// sections the application sends are queued on its own behalf: they are
+/// reported by [`MemoryBudget::send_queue`] but never exceed the budget.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct MemoryBudget {
+    limit: usize,
+    recv_buffers: usize,
+    header_block: usize,
+    header_tables: usize,
+    control_queue: usize,
+    send_queue: usize,
+}
+
+impl MemoryBudget {
+    /// Create a new memory budget.
+    ///
+    /// # Arguments
+    ///
+    /// * `limit` - The number of octets a connection may hold.
+    pub fn new(limit: usize) -> Self {
+        MemoryBudget {
+            limit,
+            recv_buffers: 0,
+            header_block: 0,
+            header_tables: 0,
+            control_queue: 0,
+            send_queue: 0,
+        }
+    }
+
+    /// Record the memory used by the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `recv_buffers` - The number of received octets not read yet.
+    /// * `header_block` - The size of the header block being assembled.
+    /// * `header_tables` - The size of the HPACK dynamic tables.
+    /// * `control_queue` - The number of octets of control frames queued
+    ///   for the transport.
+    /// * `send_queue` - The number of octets queued for the transport.
+    pub(crate) fn record(
+        &mut self,
+        recv_buffers: usize,
+        header_block: usize,
+        header_tables: usize,
+        control_queue: usize,
+        send_queue: usize,
+    ) {
+        self.recv_buffers = recv_buffers;
+        self.header_block = header_block;
+        self.header_tables = header_tables;
+        self.control_queue = control_queue;
+        self.send_queue = send_queue;
+    }
+
+    /// Check that the memory outside of flow control fits in the budget.
+    ///
+    /// Only the control frames of the send queue are accounted for: the
+    /// rest is sent by the application, not induced by the peer.
+    pub(crate) fn check(&self) -> Result<(), Http2Error> {
+        let used = self.header_block + self.header_tables + self.control_queue;
+        if used > self.limit {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::EnhanceYourCalm,
+                format!(
+                    "Connection holds {} octets, exceeding its memory budget of {}",
+                    used, self.limit
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Check if the connection flow-control window may be replenished,
+    /// that is if the memory used fits in the budget.
+    ///
+    /// The bytes queued for the transport are left aside: they are only
+    /// released when the transport is written, not when content is read.
+    pub(crate) fn allows_window_update(&self) -> bool {
+        self.recv_buffers + self.header_block + self.header_tables <= self.limit
+    }
+
+    /// Get the number of octets a connection may hold.
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Get the number of received octets not read by the application yet.
+    pub fn recv_buffers(&self) -> usize {
+        self.recv_buffers
+    }
+
+    /// Get the size of the header block being assembled.
+    pub fn header_block(&self) -> usize {
+        self.header_block
+    }
+
+    /// Get the size of the HPACK encoding and decoding tables.
+    pub fn header_tables(&self) -> usize {
+        self.header_tables
+    }
+
+    /// Get the number of octets of control frames queued for the
+    /// transport.
+    pub fn control_queue(&self) -> usize {
+        self.control_queue
+    }
+
+    /// Get the number of octets queued for the transport, control frames
+    /// included.
+    pub fn send_queue(&self) -> usize {
+        self.send_queue
+    }
+
+    /// Get the number of octets held by the connection.
+    pub fn used(&self) -> usize {
+        self.recv_buffers + self.header_block + self.header_tables + self.send_queue
+    }
+}
@@ -0,0 +1,593 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::connection::budget::MemoryBudget;
+use crate::connection::clock::{Clock, SystemClock};
+use crate::connection::core::{Http2Core, DEFAULT_CONNECTION_RECV_BUFFER_LIMIT};
+use crate::connection::settings::Settings;
+use crate::connection::{Connection, DEFAULT_HEADER_TABLE_SIZE};
+use crate::error::{ErrorCode, Http2Error};
+use crate::flow_control::{BdpEstimator, WindowUpdateStrategy, DEFAULT_INITIAL_WINDOW_SIZE};
+use crate::frame::header_block::{DEFAULT_MAX_CONTINUATION_FRAMES, DEFAULT_MAX_HEADER_BLOCK_SIZE};
+use crate::frame::settings::{SettingsFrame, SettingsParameter};
+use crate::header::lenience::LenienceConfig;
+use crate::header::limits::{DecoderLimits, EncoderLimits};
+use crate::stream::timeout::StreamTimeouts;
+use crate::stream::{Role, DEFAULT_RECV_BUFFER_LIMIT};
+use crate::transport::ByteStream;
+
+/// Default time left to in-flight streams after a GOAWAY frame is sent.
+pub const DEFAULT_GOAWAY_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Default time the peer has to acknowledge a keep-alive PING.
+pub const DEFAULT_PING_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Builder of HTTP/2 connections.
+///
+/// The builder holds the settings advertised to the peer in the SETTINGS
+/// frame of the connection preface, along with the local limits and
+/// timeouts of the connection. Settings left unset are not advertised and
+/// keep their initial value.
+#[derive(Clone, Debug)]
+pub struct ConnectionBuilder {
+    role: Role,
+    header_table_size: Option<u32>,
+    enable_push: Option<bool>,
+    max_concurrent_streams: Option<u32>,
+    initial_window_size: Option<u32>,
+    max_frame_size: Option<u32>,
+    max_header_list_size: Option<u32>,
+    max_header_string_length: Option<usize>,
+    max_header_fields: Option<usize>,
+    enable_connect_protocol: Option<bool>,
+    encoder_header_table_size: usize,
+    encoder_limits: EncoderLimits,
+    max_continuation_frames: usize,
+    max_header_block_size: usize,
+    lenience: LenienceConfig,
+    recv_buffer_limit: usize,
+    connection_recv_buffer_limit: usize,
+    memory_budget: Option<usize>,
+    max_write_batch: Option<usize>,
+    window_update_strategy: WindowUpdateStrategy,
+    clock: Arc<dyn Clock>,
+    settings_timeout: Option<Duration>,
+    ping_interval: Option<Duration>,
+    ping_timeout: Duration,
+    goaway_timeout: Duration,
+    lifecycle_events: bool,
+    deprecate_priority_frames: bool,
+    stream_timeouts: StreamTimeouts,
+}
+
+impl ConnectionBuilder {
+    /// Create a builder of client connections.
+    pub fn client() -> Self {
+        ConnectionBuilder::new(Role::Client)
+    }
+
+    /// Create a builder of server connections.
+    pub fn server() -> Self {
+        ConnectionBuilder::new(Role::Server)
+    }
+
+    /// Create a builder of connections for a role.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The role of the local endpoint.
+    fn new(role: Role) -> Self {
+        ConnectionBuilder {
+            role,
+            header_table_size: None,
+            enable_push: None,
+            max_concurrent_streams: None,
+            initial_window_size: None,
+            max_frame_size: None,
+            max_header_list_size: None,
+            max_header_string_length: None,
+            max_header_fields: None,
+            enable_connect_protocol: None,
+            encoder_header_table_size: DEFAULT_HEADER_TABLE_SIZE,
+            encoder_limits: EncoderLimits::default(),
+            max_continuation_frames: DEFAULT_MAX_CONTINUATION_FRAMES,
+            max_header_block_size: DEFAULT_MAX_HEADER_BLOCK_SIZE,
+            lenience: LenienceConfig::strict(),
+            recv_buffer_limit: DEFAULT_RECV_BUFFER_LIMIT,
+            connection_recv_buffer_limit: DEFAULT_CONNECTION_RECV_BUFFER_LIMIT,
+            memory_budget: None,
+            max_write_batch: None,
+            window_update_strategy: WindowUpdateStrategy::default(),
+            clock: Arc::new(SystemClock),
+            settings_timeout: None,
+            ping_interval: None,
+            ping_timeout: DEFAULT_PING_TIMEOUT,
+            goaway_timeout: DEFAULT_GOAWAY_TIMEOUT,
+            lifecycle_events: false,
+            deprecate_priority_frames: false,
+            stream_timeouts: StreamTimeouts::default(),
+        }
+    }
+
+    /// Set the size of the HPACK decoding table advertised in
+    /// SETTINGS_HEADER_TABLE_SIZE.
+    ///
+    /// The peer may resize the dynamic table up to this size once it has
+    /// acknowledged the SETTINGS frame, up to the initial 4,096 octets
+    /// until then.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table_size` - The maximum size of the decoding table.
+    pub fn header_table_size(mut self, header_table_size: u32) -> Self {
+        self.header_table_size = Some(header_table_size);
+        self
+    }
+
+    /// Set whether the server may push streams, advertised in
+    /// SETTINGS_ENABLE_PUSH.
+    ///
+    /// Only clients may enable server push. Once a client disabling push
+    /// has its SETTINGS frame acknowledged, any PUSH_PROMISE frame it
+    /// receives is a connection error of type PROTOCOL_ERROR, and the
+    /// server refuses to send one.
+    ///
+    /// # Arguments
+    ///
+    /// * `enable_push` - Whether server push is permitted.
+    pub fn enable_push(mut self, enable_push: bool) -> Self {
+        self.enable_push = Some(enable_push);
+        self
+    }
+
+    /// Set the maximum number of streams the peer may open, advertised in
+    /// SETTINGS_MAX_CONCURRENT_STREAMS.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_concurrent_streams` - The maximum number of active streams.
+    pub fn max_concurrent_streams(mut self, max_concurrent_streams: u32) -> Self {
+        self.max_concurrent_streams = Some(max_concurrent_streams);
+        self
+    }
+
+    /// Set the initial flow-control window of the streams, advertised in
+    /// SETTINGS_INITIAL_WINDOW_SIZE.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_window_size` - The initial stream window, at most 2^31-1.
+    pub fn initial_window_size(mut self, initial_window_size: u32) -> Self {
+        self.initial_window_size = Some(initial_window_size);
+        self
+    }
+
+    /// Set the largest frame payload accepted, advertised in
+    /// SETTINGS_MAX_FRAME_SIZE.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_frame_size` - The maximum frame payload size, between 2^14
+    ///   and 2^24-1 octets.
+    pub fn max_frame_size(mut self, max_frame_size: u32) -> Self {
+        self.max_frame_size = Some(max_frame_size);
+        self
+    }
+
+    /// Set the largest header list accepted, advertised in
+    /// SETTINGS_MAX_HEADER_LIST_SIZE and enforced when decoding.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_header_list_size` - The maximum size of a header list.
+    pub fn max_header_list_size(mut self, max_header_list_size: u32) -> Self {
+        self.max_header_list_size = Some(max_header_list_size);
+        self
+    }
+
+    /// Set the longest header field name or value accepted when decoding,
+    /// unbounded by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_header_string_length` - The maximum length in octets of a
+    ///   header field name or value.
+    pub fn max_header_string_length(mut self, max_header_string_length: usize) -> Self {
+        self.max_header_string_length = Some(max_header_string_length);
+        self
+    }
+
+    /// Set the largest number of header fields accepted in a decoded
+    /// header list, unbounded by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_header_fields` - The maximum number of header fields.
+    pub fn max_header_fields(mut self, max_header_fields: usize) -> Self {
+        self.max_header_fields = Some(max_header_fields);
+        self
+    }
+
+    /// Set whether the peer may open extended CONNECT streams, advertised
+    /// in SETTINGS_ENABLE_CONNECT_PROTOCOL.
+    ///
+    /// Received requests carrying a `:protocol` pseudo-header field, such
+    /// as WebSocket handshakes, are refused unless it is enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `enable_connect_protocol` - Whether extended CONNECT is permitted.
+    pub fn enable_connect_protocol(mut self, enable_connect_protocol: bool) -> Self {
+        self.enable_connect_protocol = Some(enable_connect_protocol);
+        self
+    }
+
+    /// Set the maximum size of the HPACK encoding table.
+    ///
+    /// The encoding table never exceeds the SETTINGS_HEADER_TABLE_SIZE
+    /// advertised by the peer, and follows its changes up to this size. A
+    /// smaller table saves memory at the cost of compression.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder_header_table_size` - The maximum size of the encoding table.
+    pub fn encoder_header_table_size(mut self, encoder_header_table_size: usize) -> Self {
+        self.encoder_header_table_size = encoder_header_table_size;
+        self
+    }
+
+    /// Set the limits of the header lists sent, unbounded by default.
+    ///
+    /// Header sections exceeding them are not sent, so that the peer does
+    /// not kill the connection over them.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder_limits` - The encoder limits.
+    pub fn encoder_limits(mut self, encoder_limits: EncoderLimits) -> Self {
+        self.encoder_limits = encoder_limits;
+        self
+    }
+
+    /// Set the maximum number of CONTINUATION frames in a header block.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_continuation_frames` - The maximum number of CONTINUATION frames.
+    pub fn max_continuation_frames(mut self, max_continuation_frames: usize) -> Self {
+        self.max_continuation_frames = max_continuation_frames;
+        self
+    }
+
+    /// Set the maximum size of a header block, in octets of encoded payload.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_header_block_size` - The maximum size of a header block.
+    pub fn max_header_block_size(mut self, max_header_block_size: usize) -> Self {
+        self.max_header_block_size = max_header_block_size;
+        self
+    }
+
+    /// Set whether received header field names with uppercase characters
+    /// are converted to lowercase instead of rejected, `false` by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `lenient_header_names` - Whether uppercase names are accepted.
+    pub fn lenient_header_names(mut self, lenient_header_names: bool) -> Self {
+        self.lenience.uppercase_header_names = lenient_header_names;
+        self
+    }
+
+    /// Set how strictly the frames and header blocks received from the
+    /// peer are checked, [`LenienceConfig::strict`] by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `lenience` - The lenience configuration.
+    pub fn lenience(mut self, lenience: LenienceConfig) -> Self {
+        self.lenience = lenience;
+        self
+    }
+
+    /// Set the high-watermark of the receive buffer of each stream,
+    /// [`DEFAULT_RECV_BUFFER_LIMIT`] by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `recv_buffer_limit` - The number of buffered octets beyond which
+    ///   the stream window is no longer replenished.
+    pub fn recv_buffer_limit(mut self, recv_buffer_limit: usize) -> Self {
+        self.recv_buffer_limit = recv_buffer_limit;
+        self
+    }
+
+    /// Set the high-watermark of all the receive buffers of the connection,
+    /// [`DEFAULT_CONNECTION_RECV_BUFFER_LIMIT`] by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `connection_recv_buffer_limit` - The number of buffered octets
+    ///   beyond which the connection window is no longer replenished.
+    pub fn connection_recv_buffer_limit(mut self, connection_recv_buffer_limit: usize) -> Self {
+        self.connection_recv_buffer_limit = connection_recv_buffer_limit;
+        self
+    }
+
+    /// Cap the memory held by the connection with a [`MemoryBudget`], none
+    /// by default.
+    ///
+    /// While the budget is exceeded, the connection window is no longer
+    /// replenished, and the peer exceeding it with memory outside of flow
+    /// control is a connection error of type ENHANCE_YOUR_CALM.
+    ///
+    /// # Arguments
+    ///
+    /// * `memory_budget` - The number of octets the connection may hold.
+    pub fn memory_budget(mut self, memory_budget: usize) -> Self {
+        self.memory_budget = Some(memory_budget);
+        self
+    }
+
+    /// Batch the writes to the transport, none by default: every frame
+    /// sent is then written right away.
+    ///
+    /// Batched frames, such as SETTINGS acknowledgements, WINDOW_UPDATE
+    /// frames and header blocks, are staged until they fill a batch, and
+    /// written before the connection blocks reading from the transport, on
+    /// [`Connection::flush`] and on [`Connection::shutdown`]. Each write
+    /// carries whole frames, up to the batch size unless a single frame is
+    /// larger, saving a system call per small frame on busy connections.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_write_batch` - The number of octets written at once.
+    pub fn max_write_batch(mut self, max_write_batch: usize) -> Self {
+        self.max_write_batch = Some(max_write_batch);
+        self
+    }
+
+    /// Set the strategy deciding when WINDOW_UPDATE frames replenish the
+    /// receive windows, [`WindowUpdateStrategy::Immediate`] by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `window_update_strategy` - The window update strategy.
+    pub fn window_update_strategy(mut self, window_update_strategy: WindowUpdateStrategy) -> Self {
+        self.window_update_strategy = window_update_strategy;
+        self
+    }
+
+    /// Set the clock of the timers of the connection, the [`SystemClock`]
+    /// by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `clock` - The clock, such as a [`ManualClock`](crate::connection::clock::ManualClock)
+    ///   advanced by tests.
+    pub fn clock(mut self, clock: impl Clock + 'static) -> Self {
+        self.clock = Arc::new(clock);
+        self
+    }
+
+    /// Set the time the peer has to acknowledge a SETTINGS frame before the
+    /// connection fails with SETTINGS_TIMEOUT, `None` by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings_timeout` - The SETTINGS acknowledgement timeout.
+    pub fn settings_timeout(mut self, settings_timeout: Duration) -> Self {
+        self.settings_timeout = Some(settings_timeout);
+        self
+    }
+
+    /// Set the interval between the PING frames keeping the connection
+    /// alive, `None` by default.
+    ///
+    /// A PING frame is sent once no frame was received from the peer for
+    /// the interval.
+    ///
+    /// # Arguments
+    ///
+    /// * `ping_interval` - The keep-alive interval.
+    pub fn ping_interval(mut self, ping_interval: Duration) -> Self {
+        self.ping_interval = Some(ping_interval);
+        self
+    }
+
+    /// Set the time the peer has to acknowledge a keep-alive PING before
+    /// the connection is closed, [`DEFAULT_PING_TIMEOUT`] by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `ping_timeout` - The keep-alive timeout.
+    pub fn ping_timeout(mut self, ping_timeout: Duration) -> Self {
+        self.ping_timeout = ping_timeout;
+        self
+    }
+
+    /// Set the time left to in-flight streams after a GOAWAY frame is sent,
+    /// [`DEFAULT_GOAWAY_TIMEOUT`] by default. The connection fails with a
+    /// connection error of type NO_ERROR once it expires, see
+    /// [`Http2Core::handle_timeouts`].
+    ///
+    /// # Arguments
+    ///
+    /// * `goaway_timeout` - The graceful shutdown timeout.
+    pub fn goaway_timeout(mut self, goaway_timeout: Duration) -> Self {
+        self.goaway_timeout = goaway_timeout;
+        self
+    }
+
+    /// Set whether the connection reports the acknowledgements of its
+    /// SETTINGS and PING frames and the room left in its send windows by
+    /// WINDOW_UPDATE frames as events, `false` by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `lifecycle_events` - Whether lifecycle events are reported.
+    pub fn lifecycle_events(mut self, lifecycle_events: bool) -> Self {
+        self.lifecycle_events = lifecycle_events;
+        self
+    }
+
+    /// Set whether the connection ignores the PRIORITY frames it receives,
+    /// `false` by default.
+    ///
+    /// The priority tree of RFC 7540 is deprecated (RFC 9113 section 5.3.2)
+    /// in favor of the `priority` header field of RFC 9218. Deprecated
+    /// PRIORITY frames are only checked for their size: they neither
+    /// update the priority of their stream nor are reported as events.
+    ///
+    /// # Arguments
+    ///
+    /// * `deprecate_priority_frames` - Whether PRIORITY frames are ignored.
+    pub fn deprecate_priority_frames(mut self, deprecate_priority_frames: bool) -> Self {
+        self.deprecate_priority_frames = deprecate_priority_frames;
+        self
+    }
+
+    /// Set the maximum time between the opening of a stream and the
+    /// reception of the header section of the peer message, `None` by
+    /// default.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_timeout` - The header timeout.
+    pub fn header_timeout(mut self, header_timeout: Duration) -> Self {
+        self.stream_timeouts.header_timeout = Some(header_timeout);
+        self
+    }
+
+    /// Set the maximum time between two frames of the content of the peer
+    /// message, `None` by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `body_idle_timeout` - The body idle timeout.
+    pub fn body_idle_timeout(mut self, body_idle_timeout: Duration) -> Self {
+        self.stream_timeouts.body_idle_timeout = Some(body_idle_timeout);
+        self
+    }
+
+    /// Set the maximum time between the opening and the closing of a
+    /// stream, `None` by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `total_timeout` - The total stream timeout.
+    pub fn stream_timeout(mut self, total_timeout: Duration) -> Self {
+        self.stream_timeouts.total_timeout = Some(total_timeout);
+        self
+    }
+
+    /// Build the SETTINGS frame advertised to the peer.
+    ///
+    /// Fails with the error the peer would report if a setting is illegal,
+    /// and with PROTOCOL_ERROR if a server enables push.
+    pub fn settings_frame(&self) -> Result<SettingsFrame, Http2Error> {
+        if self.role == Role::Server && self.enable_push == Some(true) {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                "Servers cannot enable server push".to_string(),
+            ));
+        }
+
+        let settings_frame = [
+            self.header_table_size
+                .map(SettingsParameter::HeaderTableSize),
+            self.enable_push
+                .map(|enable_push| SettingsParameter::EnablePush(enable_push as u32)),
+            self.max_concurrent_streams
+                .map(SettingsParameter::MaxConcurrentStreams),
+            self.initial_window_size
+                .map(SettingsParameter::InitialWindowSize),
+            self.max_frame_size.map(SettingsParameter::MaxFrameSize),
+            self.max_header_list_size
+                .map(SettingsParameter::MaxHeaderListSize),
+            self.enable_connect_protocol.map(|enable_connect_protocol| {
+                SettingsParameter::EnableConnectProtocol(enable_connect_protocol as u32)
+            }),
+        ]
+        .into_iter()
+        .flatten()
+        .fold(SettingsFrame::new(), SettingsFrame::with_parameter);
+
+        Settings::new().apply(&settings_frame)?;
+
+        Ok(settings_frame)
+    }
+
+    /// Build the sans-io state machine of a connection, with its
+    /// connection preface queued for the transport.
+    pub fn build(&self) -> Result<Http2Core, Http2Error> {
+        let settings_frame = self.settings_frame()?;
+        let mut core = Http2Core::new(self.role, self.clock.clone());
+
+        core.local_settings = settings_frame.settings_parameters().clone();
+        if let Some(max_concurrent_streams) = self.max_concurrent_streams {
+            core.streams
+                .set_local_max_concurrent_streams(Some(max_concurrent_streams));
+        }
+        if let Some(max_frame_size) = self.max_frame_size {
+            core.decoder.set_max_frame_size(max_frame_size)?;
+        }
+
+        // The SETTINGS_HEADER_TABLE_SIZE bound of the decoding table applies
+        // once the peer acknowledges the SETTINGS frame.
+        let header_table = core.decoder.header_table();
+        header_table.set_decoder_limits(DecoderLimits {
+            max_header_list_size: self.max_header_list_size.map(|size| size as usize),
+            max_string_length: self.max_header_string_length,
+            max_header_fields: self.max_header_fields,
+        });
+        header_table.set_lenience(self.lenience);
+
+        let header_block_guard = core.decoder.header_block_guard();
+        header_block_guard.set_max_continuation_frames(self.max_continuation_frames);
+        header_block_guard.set_max_header_block_size(self.max_header_block_size);
+
+        core.streams.set_recv_buffer_limit(self.recv_buffer_limit);
+        core.recv_buffer_limit = self.connection_recv_buffer_limit;
+        core.memory_budget = self.memory_budget.map(MemoryBudget::new);
+        core.window_update_strategy = self.window_update_strategy;
+        if self.window_update_strategy == WindowUpdateStrategy::Bdp {
+            core.bdp_estimator = Some(BdpEstimator::new(DEFAULT_INITIAL_WINDOW_SIZE));
+        }
+        // The encoding table is resized right away, the header blocks sent
+        // before the peer SETTINGS frame are bounded as well.
+        core.set_encoder_header_table_size(self.encoder_header_table_size);
+        core.encoder_header_table
+            .set_encoder_limits(self.encoder_limits);
+        core.ping_interval = self.ping_interval;
+        core.ping_timeout = self.ping_timeout;
+        core.goaway_timeout = self.goaway_timeout;
+        core.lifecycle_events = self.lifecycle_events;
+        core.deprecate_priority_frames = self.deprecate_priority_frames;
+        core.settings_timeout = self.settings_timeout;
+        core.stream_timeouts = self.stream_timeouts;
+
+        core.start()?;
+
+        Ok(core)
+    }
+
+    /// Build a connection with prior knowledge of HTTP/2 support and
+    /// perform its handshake.
+    ///
+    /// A client sends the connection preface, a server reads and checks
+    /// it. Both then exchange their SETTINGS frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The transport, typically a TCP stream.
+    pub fn handshake<T: ByteStream>(&self, io: T) -> Result<Connection<T>, Http2Error> {
+        Connection::handshake(io, self.build()?, self.max_write_batch)
+    }
+
+    /// Get the role of the endpoint the builder configures.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+}
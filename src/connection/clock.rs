@@ -0,0 +1,64 @@
+use std::fmt::Debug;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Source of the current time of a connection.
+///
+/// Every timer of a connection, such as the SETTINGS acknowledgement
+/// timeout, the keep-alive PING or the stream deadlines, reads the time
+/// from its clock. Connections use the [`SystemClock`] by default, tests
+/// inject a [`ManualClock`] to advance virtual time deterministically
+/// instead of sleeping.
+pub trait Clock: Debug + Send + Sync {
+    /// Get the current time.
+    fn now(&self) -> Instant;
+}
+
+/// Clock reading the monotonic time of the system.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// Clock whose time only moves when advanced.
+///
+/// Clones share the same time, so that a test keeps a clone to advance the
+/// clock of the connection it built.
+#[derive(Clone, Debug)]
+pub struct ManualClock {
+    now: Arc<Mutex<Instant>>,
+}
+
+impl ManualClock {
+    /// Create a manual clock, starting at the current time of the system.
+    pub fn new() -> Self {
+        ManualClock {
+            now: Arc::new(Mutex::new(Instant::now())),
+        }
+    }
+
+    /// Move the clock forward.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The time elapsed.
+    pub fn advance(&self, duration: Duration) {
+        *self.now.lock().unwrap() += duration;
+    }
+}
+
+impl Default for ManualClock {
+    fn default() -> Self {
+        ManualClock::new()
+    }
+}
+
+impl Clock for ManualClock {
+    fn now(&self) -> Instant {
+        *self.now.lock().unwrap()
+    }
+}
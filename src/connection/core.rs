@@ -0,0 +1,2016 @@
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::connection::budget::MemoryBudget;
+use crate::connection::builder::{DEFAULT_GOAWAY_TIMEOUT, DEFAULT_PING_TIMEOUT};
+use crate::connection::clock::Clock;
+use crate::connection::send_queue::SendQueue;
+use crate::connection::settings::Settings;
+use crate::connection::stats::ConnectionStats;
+use crate::connection::stream_id::StreamIdGenerator;
+use crate::connection::DEFAULT_HEADER_TABLE_SIZE;
+use crate::error::{ErrorCode, Http2Error};
+use crate::flow_control::{
+    self, BdpEstimator, FlowControlWindow, WindowUpdateCoalescer, WindowUpdateStrategy,
+    BDP_PING_OPAQUE_DATA, DEFAULT_INITIAL_WINDOW_SIZE,
+};
+use crate::frame::data::DataFrame;
+use crate::frame::decoder::FrameDecoder;
+use crate::frame::go_away::GoAwayFrame;
+use crate::frame::header_block::HeaderBlockGuard;
+use crate::frame::headers::HeadersFrame;
+use crate::frame::ping::PingFrame;
+use crate::frame::push_promise::PushPromiseFrame;
+use crate::frame::rst_stream::RstStreamFrame;
+use crate::frame::settings::{SettingsFrame, SettingsParameter};
+use crate::frame::Frame;
+use crate::header::list::HeaderList;
+use crate::header::table::HeaderTable;
+use crate::start::HTTP2_CONNECTION_PREFACE_SEQUENCE;
+use crate::stream::priority::StreamPriority;
+use crate::stream::timeout::{StreamDeadline, StreamTimeout, StreamTimeouts};
+use crate::stream::{manager::StreamManager, Role, Stream, StreamState};
+use crate::trace;
+use crate::websocket;
+
+/// Default high-watermark of the receive buffers of a connection (4 MiB).
+pub const DEFAULT_CONNECTION_RECV_BUFFER_LIMIT: usize = 4_194_304;
+
+/// Opaque data of the PING frames keeping the connection alive.
+const KEEP_ALIVE_PING_OPAQUE_DATA: [u8; 8] = *b"h2keepal";
+
+/// Event received on a HTTP/2 connection.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Event {
+    /// The header section of a request or response.
+    Headers {
+        stream_id: u32,
+        header_list: HeaderList,
+        end_stream: bool,
+    },
+    /// Content of a message was buffered, to be read with
+    /// [`Http2Core::recv_data`].
+    Data {
+        stream_id: u32,
+        length: usize,
+        end_stream: bool,
+    },
+    /// An interim (1xx) response preceding the final response, such as
+    /// 103 Early Hints, received by a client.
+    InterimResponse {
+        stream_id: u32,
+        header_list: HeaderList,
+    },
+    /// The trailer section of a message, ending the stream.
+    Trailers {
+        stream_id: u32,
+        trailers: HeaderList,
+    },
+    /// The stream was reset by the peer.
+    Reset {
+        stream_id: u32,
+        error_code: ErrorCode,
+    },
+    /// The peer is shutting down the connection.
+    ///
+    /// The streams initiated by the local endpoint with an identifier
+    /// greater than `last_stream_id` were not processed by the peer and
+    /// were closed: their requests are safe to retry on a new connection,
+    /// and reading or writing them fails with
+    /// [`Http2Error::StreamNotProcessed`].
+    GoAway {
+        last_stream_id: u32,
+        error_code: ErrorCode,
+        retryable_streams: Vec<u32>,
+    },
+    /// The peer signaled the priority of a stream, in the HEADERS frame
+    /// opening it, its `priority` header field, or a PRIORITY frame.
+    Priority {
+        stream_id: u32,
+        priority: StreamPriority,
+    },
+    /// The peer changed its settings after the connection preface. A
+    /// change of SETTINGS_INITIAL_WINDOW_SIZE was already applied to the
+    /// send windows of the open streams.
+    SettingsChanged {
+        old_settings: Settings,
+        new_settings: Settings,
+    },
+    /// The server promised to push the response to `header_list`, the
+    /// request it associated with `stream_id`, on the reserved stream
+    /// `promised_stream_id`. The pushed response is received on the
+    /// promised stream.
+    PushPromised {
+        stream_id: u32,
+        promised_stream_id: u32,
+        header_list: HeaderList,
+    },
+    /// The peer acknowledged the local settings, which now apply to the
+    /// frames it sends. Only reported with lifecycle events enabled.
+    SettingsAcked { settings: Settings },
+    /// The peer acknowledged a PING frame, sent by the application or by
+    /// the connection to keep it alive or to measure its round-trip time.
+    /// Only reported with lifecycle events enabled.
+    PingAcked { opaque_data: [u8; 8] },
+    /// A WINDOW_UPDATE frame left room in a send window, that of the
+    /// connection for stream 0: content blocked by flow control may be
+    /// sent again. Only reported with lifecycle events enabled.
+    WindowAvailable { stream_id: u32, window_size: i64 },
+}
+
+/// Sans-io HTTP/2 connection state machine.
+///
+/// The core holds the per-connection state, the frame decoder with its
+/// HPACK decoding table, the HPACK encoding table, the streams, the
+/// flow-control windows and the statistics, without owning the transport:
+/// bytes read from the transport are passed to [`Http2Core::handle_input`],
+/// which returns the [`Event`]s they complete, and the bytes to write are
+/// taken with [`Http2Core::poll_output`]. The core can thus be embedded in
+/// any runtime or event loop.
+///
+/// The flow-control windows of a stream start at the
+/// SETTINGS_INITIAL_WINDOW_SIZE of the receiver, and follow its changes:
+/// the send windows as soon as the peer settings are received, the receive
+/// windows once the peer acknowledges the local settings. DATA frames
+/// exceeding the receive window of their stream are a stream error of type
+/// FLOW_CONTROL_ERROR.
+///
+/// Received content is buffered per stream until it is read. WINDOW_UPDATE
+/// frames are sent as decided by the [`WindowUpdateStrategy`], and withheld
+/// while a stream buffer exceeds its limit, or while all the buffers of the
+/// connection exceed the connection limit, applying backpressure on the
+/// peer instead of growing memory. The window credit released is coalesced
+/// into a single WINDOW_UPDATE frame per window until the output is flushed,
+/// at the end of [`Http2Core::handle_input`] or by
+/// [`Http2Core::poll_output`]. A [`MemoryBudget`] further caps the memory
+/// held by the connection, buffers, tables and queued frames included.
+///
+/// Stream errors, such as a malformed message, reset the affected stream
+/// with a RST_STREAM frame and are returned to the caller, while the
+/// connection remains usable: decoding resumes with the next call. The
+/// frames the peer sent on a reset stream before receiving the RST_STREAM
+/// frame are ignored.
+///
+/// Streams exceeding the [`StreamTimeouts`] of the connection are
+/// cancelled by [`Http2Core::handle_timeouts`], which the driver calls once
+/// the deadline returned by [`Http2Core::poll_timeout`] is reached. Timers
+/// also keep the connection alive: a PING frame is sent once the peer was
+/// silent for the keep-alive interval, and the connection is closed if the
+/// peer does not acknowledge it in time.
+/// SETTINGS frames not acknowledged in time fail the connection as well.
+/// Every timer reads the time from the [`Clock`] of the connection, which
+/// tests replace to advance virtual time.
+///
+/// A core is built with a
+/// [`ConnectionBuilder`](crate::connection::builder::ConnectionBuilder),
+/// its connection preface is queued right away.
+pub struct Http2Core {
+    role: Role,
+    pub(super) decoder: FrameDecoder,
+    pub(super) encoder_header_table: HeaderTable,
+    pub(super) streams: StreamManager,
+    stream_ids: StreamIdGenerator,
+    pub(super) local_settings: Vec<SettingsParameter>,
+    remote_settings: Settings,
+    send_window: FlowControlWindow,
+    recv_window: FlowControlWindow,
+    stream_send_windows: HashMap<u32, FlowControlWindow>,
+    stream_recv_windows: HashMap<u32, FlowControlWindow>,
+    acked_local_settings: Settings,
+    pub(super) stream_timeouts: StreamTimeouts,
+    stream_deadlines: HashMap<u32, StreamDeadline>,
+    pub(super) recv_buffer_limit: usize,
+    pending_window_update: u32,
+    window_updates: WindowUpdateCoalescer,
+    pub(super) window_update_strategy: WindowUpdateStrategy,
+    pub(super) bdp_estimator: Option<BdpEstimator>,
+    pub(super) memory_budget: Option<MemoryBudget>,
+    settings_sent: VecDeque<(Instant, SettingsFrame)>,
+    pub(super) settings_timeout: Option<Duration>,
+    ping_sent: Option<([u8; 8], Instant)>,
+    keep_alive_sent: Option<Instant>,
+    go_away_sent: Option<Instant>,
+    last_received: Instant,
+    pub(super) max_encoder_header_table_size: usize,
+    pub(super) ping_interval: Option<Duration>,
+    pub(super) ping_timeout: Duration,
+    pub(super) goaway_timeout: Duration,
+    pub(super) lifecycle_events: bool,
+    pub(super) deprecate_priority_frames: bool,
+    clock: Arc<dyn Clock>,
+    client_preface: Option<Vec<u8>>,
+    settings_received: bool,
+    pending_header_block: Option<Frame>,
+    settings_changed: Option<Settings>,
+    pending_event: Option<Event>,
+    stream_error: Option<Http2Error>,
+    output: SendQueue,
+    stats: ConnectionStats,
+}
+
+impl Http2Core {
+    /// Create a connection state machine, before its connection preface.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The role of the local endpoint.
+    /// * `clock` - The clock of the timers of the connection.
+    pub(super) fn new(role: Role, clock: Arc<dyn Clock>) -> Self {
+        // Header blocks are decoded once complete, see assemble_header_block.
+        let mut decoder = FrameDecoder::new(DEFAULT_HEADER_TABLE_SIZE);
+        decoder.header_table().set_pass_through(true);
+
+        Http2Core {
+            role,
+            decoder,
+            encoder_header_table: HeaderTable::new(DEFAULT_HEADER_TABLE_SIZE),
+            streams: StreamManager::new(role),
+            stream_ids: StreamIdGenerator::new(role),
+            local_settings: Vec::new(),
+            remote_settings: Settings::new(),
+            send_window: FlowControlWindow::new(0, DEFAULT_INITIAL_WINDOW_SIZE),
+            recv_window: FlowControlWindow::new(0, DEFAULT_INITIAL_WINDOW_SIZE),
+            stream_send_windows: HashMap::new(),
+            stream_recv_windows: HashMap::new(),
+            acked_local_settings: Settings::new(),
+            stream_timeouts: StreamTimeouts::default(),
+            stream_deadlines: HashMap::new(),
+            recv_buffer_limit: DEFAULT_CONNECTION_RECV_BUFFER_LIMIT,
+            pending_window_update: 0,
+            window_updates: WindowUpdateCoalescer::new(),
+            window_update_strategy: WindowUpdateStrategy::default(),
+            bdp_estimator: None,
+            memory_budget: None,
+            settings_sent: VecDeque::new(),
+            settings_timeout: None,
+            ping_sent: None,
+            keep_alive_sent: None,
+            go_away_sent: None,
+            last_received: clock.now(),
+            max_encoder_header_table_size: DEFAULT_HEADER_TABLE_SIZE,
+            ping_interval: None,
+            ping_timeout: DEFAULT_PING_TIMEOUT,
+            goaway_timeout: DEFAULT_GOAWAY_TIMEOUT,
+            lifecycle_events: false,
+            deprecate_priority_frames: false,
+            clock,
+            client_preface: None,
+            settings_received: false,
+            pending_header_block: None,
+            settings_changed: None,
+            pending_event: None,
+            stream_error: None,
+            output: SendQueue::new(),
+            stats: ConnectionStats::default(),
+        }
+    }
+
+    /// Queue the connection preface of the local endpoint.
+    ///
+    /// A client sends the connection preface sequence followed by its
+    /// SETTINGS frame. A server sends its SETTINGS frame and expects the
+    /// client connection preface sequence before any frame.
+    pub(super) fn start(&mut self) -> Result<(), Http2Error> {
+        match self.role {
+            Role::Client => {
+                self.output.push_preface(HTTP2_CONNECTION_PREFACE_SEQUENCE);
+                self.stats
+                    .record_bytes_sent(HTTP2_CONNECTION_PREFACE_SEQUENCE.len());
+            }
+            Role::Server => self.client_preface = Some(Vec::new()),
+        }
+
+        self.send_frame(&Frame::Settings(self.local_settings_frame()))
+    }
+
+    /// Build a SETTINGS frame carrying the settings advertised to the peer.
+    fn local_settings_frame(&self) -> SettingsFrame {
+        self.local_settings
+            .iter()
+            .copied()
+            .fold(SettingsFrame::new(), SettingsFrame::with_parameter)
+    }
+
+    /// Process bytes read from the transport.
+    ///
+    /// Returns the events completed by the frames decoded, in order. The
+    /// frames sent in reaction, such as SETTINGS and PING acknowledgements
+    /// or WINDOW_UPDATE frames, are queued for [`Http2Core::poll_output`],
+    /// with at most one WINDOW_UPDATE frame per window.
+    ///
+    /// A stream error following completed events is returned by the next
+    /// call, after these events. A connection error queues a GOAWAY frame,
+    /// see [`Http2Core::next_event`].
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes read from the transport.
+    pub fn handle_input(&mut self, bytes: &[u8]) -> Result<Vec<Event>, Http2Error> {
+        self.feed(bytes);
+
+        let mut events = Vec::new();
+        loop {
+            match self.next_event() {
+                Ok(Some(event)) => events.push(event),
+                Ok(None) => break,
+                Err(error) if error.is_stream_error() && !events.is_empty() => {
+                    self.stream_error = Some(error);
+                    break;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+        self.flush_window_updates();
+
+        Ok(events)
+    }
+
+    /// Take the bytes to write to the transport.
+    ///
+    /// The coalesced WINDOW_UPDATE frames are flushed first. Control frames
+    /// are taken ahead of the DATA and header frames queued before them,
+    /// as ordered by the [`SendQueue`].
+    pub fn poll_output(&mut self) -> Vec<u8> {
+        self.flush_window_updates();
+        self.output.pop_all()
+    }
+
+    /// Take whole frames to write to the transport, up to a number of
+    /// octets, so that a transport applying backpressure only receives
+    /// what it accepts. The frames left are taken by the next calls,
+    /// behind the control frames queued meanwhile.
+    ///
+    /// At least one frame is taken if any is queued.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_len` - The number of octets the transport accepts.
+    pub fn poll_output_limited(&mut self, max_len: usize) -> Vec<u8> {
+        self.flush_window_updates();
+        self.output.pop(max_len)
+    }
+
+    /// Check if bytes are waiting to be written to the transport.
+    pub fn has_output(&self) -> bool {
+        !self.output.is_empty() || !self.window_updates.is_empty()
+    }
+
+    /// Get the number of octets queued for the transport.
+    pub fn output_len(&self) -> usize {
+        self.output.len()
+    }
+
+    /// Queue the coalesced WINDOW_UPDATE frames for the transport.
+    ///
+    /// The connection receive window was increased when their credit was
+    /// released.
+    fn flush_window_updates(&mut self) {
+        for window_update_frame in self.window_updates.flush() {
+            let bytes = window_update_frame.serialize();
+            trace::frame_sent(&bytes);
+
+            self.stats.record_frame_sent(0x8);
+            self.stats.record_bytes_sent(bytes.len());
+            self.output.push(bytes);
+        }
+    }
+
+    /// Buffer bytes read from the transport, without decoding them.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes read from the transport.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.stats.record_bytes_received(bytes.len());
+        match &mut self.client_preface {
+            Some(client_preface) => client_preface.extend_from_slice(bytes),
+            None => self.decoder.feed(bytes),
+        }
+    }
+
+    /// Decode the next buffered event, if complete.
+    ///
+    /// A stream error resets the affected stream with a RST_STREAM frame. A
+    /// connection error is reported to the peer with a GOAWAY frame carrying
+    /// its error code, unless a GOAWAY frame was already sent: the driver
+    /// should write the output, then close the transport.
+    pub fn next_event(&mut self) -> Result<Option<Event>, Http2Error> {
+        match self.recv_next_event() {
+            Err(error) if error.is_connection_error() => {
+                self.go_away_on_connection_error(&error)?;
+                Err(error)
+            }
+            result => result,
+        }
+    }
+
+    /// Decode the next buffered event, resetting the stream of a stream
+    /// error.
+    fn recv_next_event(&mut self) -> Result<Option<Event>, Http2Error> {
+        if let Some(error) = self.stream_error.take() {
+            return Err(error);
+        }
+        if let Some(event) = self.pending_event.take() {
+            return Ok(Some(event));
+        }
+
+        while let Some(frame) = self.next_frame()? {
+            let event = match self.recv_event(frame) {
+                Ok(event) => event,
+                Err(error) => {
+                    self.reset_on_stream_error(&error)?;
+                    return Err(error);
+                }
+            };
+            if let Some(memory_budget) = self.record_memory_usage() {
+                memory_budget.check()?;
+            }
+            if event.is_some() {
+                return Ok(event);
+            }
+        }
+
+        Ok(None)
+    }
+
+    /// Decode the next buffered frame, if complete.
+    ///
+    /// The first frame sent by the peer MUST be a SETTINGS frame, preceded
+    /// by the connection preface sequence if the peer is a client.
+    ///
+    /// CONTINUATION frames are not returned: their header block fragments
+    /// are appended to the HEADERS or PUSH_PROMISE frame starting the header
+    /// block, which is returned with the whole header list once its
+    /// END_HEADERS flag is received.
+    ///
+    /// Frames affecting the state of a stream are applied to the stream
+    /// manager: HEADERS frames open streams and record trailers, DATA
+    /// frames are checked against the `content-length` of their message,
+    /// RST_STREAM frames close their stream, and fail with a connection
+    /// error of type ENHANCE_YOUR_CALM if the peer resets streams faster
+    /// than the stream manager reset guard allows. HEADERS
+    /// frames opening a stream fail with PROTOCOL_ERROR if its identifier
+    /// is not greater than those of the streams the peer previously opened.
+    /// PUSH_PROMISE frames reserve their promised stream, and fail with
+    /// PROTOCOL_ERROR when received by a server or by a client whose
+    /// SETTINGS_ENABLE_PUSH = 0 was acknowledged.
+    ///
+    /// WINDOW_UPDATE and RST_STREAM frames are only tolerated on streams
+    /// that are open or recently closed. Stream errors reset the affected
+    /// stream with a RST_STREAM frame, and the frames received on a stream
+    /// after it was reset are skipped.
+    ///
+    /// DATA frames consume the connection flow-control window, which is
+    /// replenished by the WINDOW_UPDATE frames sent on stream 0. PING
+    /// frames are acknowledged, and PING acknowledgements measure the
+    /// round-trip time of the connection. SETTINGS frames are applied to
+    /// the remote settings and acknowledged.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, Http2Error> {
+        match self.recv_frame() {
+            Err(error) => {
+                self.reset_on_stream_error(&error)?;
+                Err(error)
+            }
+            result => result,
+        }
+    }
+
+    /// Decode the next buffered frame and apply it to the connection state,
+    /// skipping the frames received on streams reset by the local endpoint.
+    fn recv_frame(&mut self) -> Result<Option<Frame>, Http2Error> {
+        if !self.recv_client_preface()? {
+            return Ok(None);
+        }
+
+        let (frame, preface, malformed) = loop {
+            let frame = match self.decoder.next_frame()? {
+                Some(frame) => frame,
+                None => return Ok(None),
+            };
+            self.stats.record_frame_received(frame.frame_type());
+            self.last_received = self.clock.now();
+
+            let preface = !self.settings_received;
+            if preface {
+                match &frame {
+                    Frame::Settings(settings_frame) if !settings_frame.is_ack() => {
+                        self.settings_received = true
+                    }
+                    _ => {
+                        return Err(Http2Error::ConnectionError(
+                            ErrorCode::ProtocolError,
+                            "Connection preface must start with a SETTINGS frame".to_string(),
+                        ))
+                    }
+                }
+            }
+
+            let (frame, malformed) = match self.assemble_header_block(frame)? {
+                Some(header_block) => header_block,
+                None => continue,
+            };
+            if !self.ignore_late_frame(&frame)? {
+                break (frame, preface, malformed);
+            }
+        };
+
+        match &frame {
+            Frame::Data(data_frame) => {
+                let length = data_frame.flow_controlled_length();
+                self.recv_window.consume(length)?;
+                let result = self
+                    .stream_recv_windows
+                    .get_mut(&data_frame.stream_id)
+                    .map_or(Ok(()), |recv_window| recv_window.consume(length))
+                    .and_then(|()| self.streams.recv_data(data_frame));
+                if let Err(error) = result {
+                    self.discard_data(length)?;
+                    return Err(error);
+                }
+            }
+            Frame::Headers(headers_frame) => {
+                if self.streams.get(headers_frame.stream_id()).is_none() {
+                    self.stream_ids.recv_stream_id(headers_frame.stream_id())?;
+                    self.start_deadline(headers_frame.stream_id());
+                    self.open_recv_window(headers_frame.stream_id());
+                }
+                self.streams.recv_headers(headers_frame)?
+            }
+            Frame::PushPromise(push_promise_frame) => {
+                let promised_stream_id = push_promise_frame.promised_stream_id();
+                self.streams.recv_push_promise(push_promise_frame)?;
+                if !self.acked_local_settings.enable_push() {
+                    return Err(Http2Error::ConnectionError(
+                        ErrorCode::ProtocolError,
+                        "PUSH_PROMISE received while SETTINGS_ENABLE_PUSH is 0".to_string(),
+                    ));
+                }
+                self.stream_ids.recv_stream_id(promised_stream_id)?;
+                self.start_deadline(promised_stream_id);
+                self.open_recv_window(promised_stream_id);
+            }
+            Frame::RstStream(rst_stream_frame) => {
+                // The error code is kept for the readers and writers of the
+                // stream, unless it was already closed.
+                let unfinished = self
+                    .streams
+                    .get(rst_stream_frame.stream_id)
+                    .is_some_and(|stream| stream.state() != StreamState::Closed);
+                self.streams
+                    .recv_reset_at(rst_stream_frame.stream_id, self.clock.now())?;
+                if let Some(stream) = self
+                    .streams
+                    .get_mut(rst_stream_frame.stream_id)
+                    .filter(|_| unfinished)
+                {
+                    stream.set_reset_received(ErrorCode::from(rst_stream_frame.error_code));
+                }
+                self.stream_send_windows.remove(&rst_stream_frame.stream_id);
+                self.stream_recv_windows.remove(&rst_stream_frame.stream_id);
+                self.stream_deadlines.remove(&rst_stream_frame.stream_id);
+                self.window_updates.remove(rst_stream_frame.stream_id);
+            }
+            Frame::WindowUpdate(window_update_frame) if window_update_frame.stream_id() == 0 => {
+                self.send_window.apply_window_update(window_update_frame)?
+            }
+            Frame::WindowUpdate(window_update_frame) => {
+                self.streams
+                    .recv_window_update(window_update_frame.stream_id())?;
+                if let Some(send_window) = self
+                    .stream_send_windows
+                    .get_mut(&window_update_frame.stream_id())
+                {
+                    send_window.apply_window_update(window_update_frame)?;
+                }
+            }
+            Frame::Ping(ping_frame) if ping_frame.is_ack() => self.recv_ping_ack(ping_frame)?,
+            Frame::Ping(ping_frame) => {
+                let ack = PingFrame::ack(*ping_frame.opaque_data());
+                self.send_frame(&Frame::Ping(ack))?
+            }
+            // Deprecated PRIORITY frames are only checked for their size.
+            Frame::Priority(_) if self.deprecate_priority_frames => (),
+            Frame::Priority(priority_frame) => self.streams.recv_priority(priority_frame)?,
+            Frame::Settings(settings_frame) if settings_frame.is_ack() => {
+                self.recv_settings_ack()?
+            }
+            Frame::Settings(settings_frame) => {
+                let old_settings = self.remote_settings;
+                self.apply_remote_settings(settings_frame)?;
+                if !preface && self.remote_settings != old_settings {
+                    self.settings_changed = Some(old_settings);
+                }
+            }
+            _ => (),
+        }
+
+        // A malformed header list resets the stream it opened or reserved.
+        if let Some(message) = malformed {
+            let stream_id = match &frame {
+                Frame::Headers(headers_frame) => headers_frame.stream_id(),
+                Frame::PushPromise(push_promise_frame) => push_promise_frame.promised_stream_id(),
+                _ => 0,
+            };
+            return Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::ProtocolError,
+                message,
+            ));
+        }
+
+        let stream_id = match &frame {
+            Frame::Data(data_frame) => Some(data_frame.stream_id),
+            Frame::Headers(headers_frame) => Some(headers_frame.stream_id()),
+            _ => None,
+        };
+        if let Some(stream_deadline) =
+            stream_id.and_then(|stream_id| self.stream_deadlines.get_mut(&stream_id))
+        {
+            stream_deadline.record_received(self.clock.now());
+        }
+        // The receive window is forgotten once the peer ended the stream.
+        if let Some(stream_id) = stream_id {
+            let receiving = self.streams.get(stream_id).is_some_and(|stream| {
+                matches!(
+                    stream.state(),
+                    StreamState::Open | StreamState::HalfClosedLocal
+                )
+            });
+            if !receiving {
+                self.stream_recv_windows.remove(&stream_id);
+            }
+        }
+
+        Ok(Some(frame))
+    }
+
+    /// Assemble the header block of a HEADERS or PUSH_PROMISE frame with
+    /// the CONTINUATION frames following it.
+    ///
+    /// Header block fragments are kept as received until the END_HEADERS
+    /// flag, then the header block is decoded at once, since a header field
+    /// may be split across frames. Returns the frame starting the header
+    /// block once complete, with the decoded header list, and `None` while
+    /// CONTINUATION frames are expected. The decoder guarantees that they
+    /// follow the frame, on its stream.
+    ///
+    /// A malformed header list, such as one with uppercase field names, is
+    /// not a decoding failure: the dynamic table stays in sync with the
+    /// peer. The frame is returned with an empty header list and the reason
+    /// it is malformed, to be reported as a stream error once the frame has
+    /// opened or reserved its stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The received frame.
+    fn assemble_header_block(
+        &mut self,
+        frame: Frame,
+    ) -> Result<Option<(Frame, Option<String>)>, Http2Error> {
+        let mut frame = match (frame, self.pending_header_block.take()) {
+            (Frame::Continuation(continuation_frame), Some(mut frame)) => {
+                match &mut frame {
+                    Frame::Headers(headers_frame) => {
+                        headers_frame.append_continuation(&continuation_frame)?
+                    }
+                    Frame::PushPromise(push_promise_frame) => {
+                        push_promise_frame.append_continuation(&continuation_frame)?
+                    }
+                    _ => (),
+                }
+                frame
+            }
+            (frame, _) => frame,
+        };
+
+        let header_table = self.decoder.header_table();
+        let (end_headers, result) = match &mut frame {
+            Frame::Headers(headers_frame) if headers_frame.end_headers() => {
+                (true, headers_frame.decode_header_block(header_table))
+            }
+            Frame::PushPromise(push_promise_frame) if push_promise_frame.end_headers() => {
+                (true, push_promise_frame.decode_header_block(header_table))
+            }
+            Frame::Headers(_) | Frame::PushPromise(_) => (false, Ok(())),
+            _ => (true, Ok(())),
+        };
+        if !end_headers {
+            self.pending_header_block = Some(frame);
+            return Ok(None);
+        }
+
+        match result {
+            Ok(()) => Ok(Some((frame, None))),
+            Err(Http2Error::HeaderError(message)) => Ok(Some((frame, Some(message)))),
+            Err(error) => Err(error),
+        }
+    }
+
+    /// Ignore a frame received on a stream reset by the local endpoint,
+    /// which the peer may have sent before receiving the RST_STREAM frame.
+    ///
+    /// Header blocks were decoded, keeping the HPACK decoding table in
+    /// sync, and DATA frames count toward the connection flow-control
+    /// window, their credit being released right away.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The received frame.
+    fn ignore_late_frame(&mut self, frame: &Frame) -> Result<bool, Http2Error> {
+        let stream_id = match frame {
+            Frame::Data(data_frame) => data_frame.stream_id,
+            Frame::Headers(headers_frame) => headers_frame.stream_id(),
+            Frame::PushPromise(push_promise_frame) => push_promise_frame.stream_id(),
+            Frame::Priority(priority_frame) => priority_frame.stream_id(),
+            Frame::RstStream(rst_stream_frame) => rst_stream_frame.stream_id,
+            Frame::WindowUpdate(window_update_frame) => window_update_frame.stream_id(),
+            _ => return Ok(false),
+        };
+        if !self.streams.get(stream_id).is_some_and(Stream::reset_sent) {
+            return Ok(false);
+        }
+
+        if let Frame::Data(data_frame) = frame {
+            let length = data_frame.flow_controlled_length();
+            self.recv_window.consume(length)?;
+            self.discard_data(length)?;
+        }
+
+        Ok(true)
+    }
+
+    /// Release the connection window credit of DATA frames whose content is
+    /// discarded.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The length of the discarded content.
+    fn discard_data(&mut self, length: u32) -> Result<(), Http2Error> {
+        self.pending_window_update += length;
+        self.release_windows(0)
+    }
+
+    /// Send a GOAWAY frame reporting a connection error, unless one was
+    /// already sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The connection error.
+    fn go_away_on_connection_error(&mut self, error: &Http2Error) -> Result<(), Http2Error> {
+        if self.go_away_sent.is_some() {
+            return Ok(());
+        }
+
+        let go_away_frame = GoAwayFrame::new(
+            self.stream_ids.last_remote_stream_id(),
+            error.error_code(),
+            error.to_string().into_bytes(),
+        );
+        self.send_frame(&Frame::GoAway(go_away_frame))
+    }
+
+    /// Reset the stream affected by a stream error, keeping the connection.
+    ///
+    /// RST_STREAM frames MUST NOT be sent on idle streams, such as the
+    /// stream of an invalid PRIORITY frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error raised while receiving a frame.
+    fn reset_on_stream_error(&mut self, error: &Http2Error) -> Result<(), Http2Error> {
+        match error.stream_id() {
+            Some(stream_id)
+                if self
+                    .streams
+                    .get(stream_id)
+                    .is_some_and(|stream| stream.state() != StreamState::Idle) =>
+            {
+                self.send_reset(stream_id, error.error_code())
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Check the client connection preface sequence, on a server.
+    ///
+    /// Returns whether frames may be decoded, that is whether the preface
+    /// was received or is not expected.
+    fn recv_client_preface(&mut self) -> Result<bool, Http2Error> {
+        let client_preface = match &self.client_preface {
+            Some(client_preface) => client_preface,
+            None => return Ok(true),
+        };
+
+        let length = HTTP2_CONNECTION_PREFACE_SEQUENCE.len();
+        if client_preface.len() < length {
+            return Ok(false);
+        }
+        if client_preface[..length] != *HTTP2_CONNECTION_PREFACE_SEQUENCE {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                "Invalid client connection preface".to_string(),
+            ));
+        }
+
+        if let Some(client_preface) = self.client_preface.take() {
+            self.decoder.feed(&client_preface[length..]);
+        }
+
+        Ok(true)
+    }
+
+    /// Apply and acknowledge a SETTINGS frame received from the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings_frame` - The received SETTINGS frame.
+    fn apply_remote_settings(&mut self, settings_frame: &SettingsFrame) -> Result<(), Http2Error> {
+        let initial_window_size = self.remote_settings.initial_window_size();
+        self.remote_settings.apply(settings_frame)?;
+        flow_control::update_initial_window_size(
+            self.stream_send_windows.values_mut(),
+            initial_window_size,
+            self.remote_settings.initial_window_size(),
+        )?;
+        self.streams
+            .set_remote_max_concurrent_streams(self.remote_settings.max_concurrent_streams());
+
+        self.resize_encoder_header_table();
+
+        self.send_frame(&Frame::Settings(SettingsFrame::ack()))
+    }
+
+    /// Apply the oldest SETTINGS frame sent once the peer acknowledges it.
+    ///
+    /// A change of SETTINGS_INITIAL_WINDOW_SIZE adjusts the receive windows
+    /// of the open streams: the peer used the previous value until then.
+    /// So does SETTINGS_HEADER_TABLE_SIZE, which bounds the dynamic table
+    /// size updates of the header blocks received afterwards.
+    fn recv_settings_ack(&mut self) -> Result<(), Http2Error> {
+        let settings_frame = match self.settings_sent.pop_front() {
+            Some((_, settings_frame)) => settings_frame,
+            None => return Ok(()),
+        };
+
+        let initial_window_size = self.acked_local_settings.initial_window_size();
+        self.acked_local_settings.apply(&settings_frame)?;
+        self.decoder
+            .header_table()
+            .set_settings_max_size(self.acked_local_settings.header_table_size() as usize);
+        flow_control::update_initial_window_size(
+            self.stream_recv_windows.values_mut(),
+            initial_window_size,
+            self.acked_local_settings.initial_window_size(),
+        )
+    }
+
+    /// Start the receive window of a new stream, at the local
+    /// SETTINGS_INITIAL_WINDOW_SIZE acknowledged by the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    fn open_recv_window(&mut self, stream_id: u32) {
+        let acked_local_settings = &self.acked_local_settings;
+        self.stream_recv_windows
+            .entry(stream_id)
+            .or_insert_with(|| FlowControlWindow::for_stream(stream_id, acked_local_settings));
+    }
+
+    /// Resize the encoding table to the maximum size chosen locally,
+    /// bounded by the SETTINGS_HEADER_TABLE_SIZE of the peer.
+    ///
+    /// A change is signaled with a dynamic table size update at the
+    /// beginning of the next header block.
+    fn resize_encoder_header_table(&mut self) {
+        let encoder_header_table_size = self
+            .max_encoder_header_table_size
+            .min(self.remote_settings.header_table_size() as usize);
+        if encoder_header_table_size != self.encoder_header_table.get_dynamic_table_max_size() {
+            self.encoder_header_table
+                .set_pending_size_update(encoder_header_table_size);
+        }
+    }
+
+    /// Measure the round-trip time of an acknowledged PING, and grow the
+    /// receive windows to the estimated bandwidth-delay product.
+    ///
+    /// The connection window grows with a WINDOW_UPDATE frame, the stream
+    /// windows with a new SETTINGS_INITIAL_WINDOW_SIZE.
+    fn recv_ping_ack(&mut self, ping_frame: &PingFrame) -> Result<(), Http2Error> {
+        if let Some((opaque_data, sent)) = &self.ping_sent {
+            if opaque_data == ping_frame.opaque_data() {
+                self.stats
+                    .record_ping_rtt(self.clock.now().duration_since(*sent));
+                self.ping_sent = None;
+            }
+        }
+        if *ping_frame.opaque_data() == KEEP_ALIVE_PING_OPAQUE_DATA {
+            if let Some(sent) = self.keep_alive_sent.take() {
+                self.stats
+                    .record_ping_rtt(self.clock.now().duration_since(sent));
+            }
+        }
+
+        let previous_window_size = self.recv_window_size();
+        let window_size = match self
+            .bdp_estimator
+            .as_mut()
+            .and_then(|bdp_estimator| bdp_estimator.recv_ping_ack(ping_frame))
+        {
+            Some(window_size) => window_size,
+            None => return Ok(()),
+        };
+
+        let increment = window_size - previous_window_size;
+        self.recv_window.increase(increment)?;
+        self.window_updates.add(0, increment);
+
+        let initial_window_size = SettingsParameter::InitialWindowSize(window_size);
+        self.local_settings.retain(|settings_parameter| {
+            !matches!(settings_parameter, SettingsParameter::InitialWindowSize(_))
+        });
+        self.local_settings.push(initial_window_size);
+        let settings_frame = SettingsFrame::new().with_parameter(initial_window_size);
+        self.send_frame(&Frame::Settings(settings_frame))
+    }
+
+    /// Get the size of the connection receive window when fully
+    /// replenished.
+    fn recv_window_size(&self) -> u32 {
+        self.bdp_estimator
+            .as_ref()
+            .map_or(DEFAULT_INITIAL_WINDOW_SIZE, BdpEstimator::window_size)
+    }
+
+    /// Get the initial size of the stream receive windows advertised to the
+    /// peer.
+    fn local_initial_window_size(&self) -> u32 {
+        self.local_settings().initial_window_size()
+    }
+
+    /// Check whether SETTINGS_ENABLE_CONNECT_PROTOCOL was advertised to the
+    /// peer.
+    fn local_enable_connect_protocol(&self) -> bool {
+        self.local_settings().enable_connect_protocol()
+    }
+
+    /// Convert a received frame into the event it completes, if any.
+    ///
+    /// Header blocks, assembled until their END_HEADERS flag, are reported
+    /// as trailers if the stream manager recorded them as such.
+    /// The content of DATA frames is buffered, and the flow-control windows
+    /// replenished while the buffers are within their limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame received from the peer.
+    fn recv_event(&mut self, frame: Frame) -> Result<Option<Event>, Http2Error> {
+        let event = match frame {
+            Frame::Headers(headers_frame) => Some(self.recv_header_block(headers_frame)?),
+            Frame::PushPromise(push_promise_frame) => Some(Event::PushPromised {
+                stream_id: push_promise_frame.stream_id(),
+                promised_stream_id: push_promise_frame.promised_stream_id(),
+                header_list: push_promise_frame.header_list().clone(),
+            }),
+            Frame::Data(data_frame) => {
+                let length = data_frame.data.len();
+                let flow_controlled_length = data_frame.flow_controlled_length();
+                self.pending_window_update += flow_controlled_length;
+                if let Some(stream) = self.streams.get_mut(data_frame.stream_id) {
+                    stream.buffer_data(
+                        data_frame.data,
+                        flow_controlled_length,
+                        !data_frame.end_stream,
+                    );
+                }
+                self.release_windows(data_frame.stream_id)?;
+
+                if let Some(ping_frame) = self
+                    .bdp_estimator
+                    .as_mut()
+                    .and_then(|bdp_estimator| bdp_estimator.recv_data(flow_controlled_length))
+                {
+                    self.send_frame(&Frame::Ping(ping_frame))?;
+                }
+
+                Some(Event::Data {
+                    stream_id: data_frame.stream_id,
+                    length,
+                    end_stream: data_frame.end_stream,
+                })
+            }
+            Frame::RstStream(rst_stream_frame) => Some(Event::Reset {
+                stream_id: rst_stream_frame.stream_id,
+                error_code: ErrorCode::from(rst_stream_frame.error_code),
+            }),
+            Frame::GoAway(go_away_frame) => {
+                let last_stream_id = go_away_frame.last_stream_id();
+                let retryable_streams = self.streams.recv_go_away(last_stream_id);
+                for stream_id in &retryable_streams {
+                    self.stream_send_windows.remove(stream_id);
+                    self.stream_recv_windows.remove(stream_id);
+                    self.stream_deadlines.remove(stream_id);
+                    self.window_updates.remove(*stream_id);
+                }
+
+                Some(Event::GoAway {
+                    last_stream_id,
+                    error_code: ErrorCode::from(go_away_frame.error_code()),
+                    retryable_streams,
+                })
+            }
+            Frame::Priority(_) if self.deprecate_priority_frames => None,
+            Frame::Priority(priority_frame) => {
+                let stream_id = priority_frame.stream_id();
+                self.streams.get(stream_id).map(|stream| Event::Priority {
+                    stream_id,
+                    priority: stream.priority(),
+                })
+            }
+            Frame::Settings(settings_frame) if settings_frame.is_ack() => {
+                self.lifecycle_events.then_some(Event::SettingsAcked {
+                    settings: self.acked_local_settings,
+                })
+            }
+            Frame::Settings(_) => {
+                self.settings_changed
+                    .take()
+                    .map(|old_settings| Event::SettingsChanged {
+                        old_settings,
+                        new_settings: self.remote_settings,
+                    })
+            }
+            Frame::Ping(ping_frame) if ping_frame.is_ack() => {
+                self.lifecycle_events.then(|| Event::PingAcked {
+                    opaque_data: *ping_frame.opaque_data(),
+                })
+            }
+            Frame::WindowUpdate(window_update_frame) if self.lifecycle_events => {
+                let stream_id = window_update_frame.stream_id();
+                let send_window = match stream_id {
+                    0 => Some(&self.send_window),
+                    stream_id => self.stream_send_windows.get(&stream_id),
+                };
+                send_window
+                    .map(FlowControlWindow::size)
+                    .filter(|window_size| *window_size > 0)
+                    .map(|window_size| Event::WindowAvailable {
+                        stream_id,
+                        window_size,
+                    })
+            }
+            _ => None,
+        };
+
+        Ok(event)
+    }
+
+    /// Convert the header block of a HEADERS frame into its event.
+    ///
+    /// Requests carrying a `:protocol` pseudo-header field fail with a
+    /// stream error of type PROTOCOL_ERROR unless SETTINGS_ENABLE_CONNECT_PROTOCOL
+    /// was advertised to the peer, or if they are not well-formed extended
+    /// CONNECT requests.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers_frame` - The HEADERS frame, with its whole header block.
+    fn recv_header_block(&mut self, headers_frame: HeadersFrame) -> Result<Event, Http2Error> {
+        let stream_id = headers_frame.stream_id();
+        let header_list = headers_frame.header_list();
+
+        let event = match self.streams.take_trailers(stream_id) {
+            Some(trailers) => Event::Trailers {
+                stream_id,
+                trailers,
+            },
+            None => {
+                if self.role == Role::Server && header_list.protocol().is_some() {
+                    if !self.local_enable_connect_protocol() {
+                        return Err(Http2Error::StreamError(
+                            stream_id,
+                            ErrorCode::ProtocolError,
+                            "SETTINGS_ENABLE_CONNECT_PROTOCOL was not advertised".to_string(),
+                        ));
+                    }
+                    websocket::check_extended_connect(stream_id, header_list)?;
+                }
+                if self.role == Role::Client && matches!(header_list.status(), Some(100..=199)) {
+                    return Ok(Event::InterimResponse {
+                        stream_id,
+                        header_list: header_list.clone(),
+                    });
+                }
+
+                // The priority signals follow the header section.
+                if headers_frame.frame_priority().is_some() || header_list.contains("priority") {
+                    self.pending_event =
+                        self.streams.get(stream_id).map(|stream| Event::Priority {
+                            stream_id,
+                            priority: stream.priority(),
+                        });
+                }
+
+                Event::Headers {
+                    stream_id,
+                    header_list: header_list.clone(),
+                    end_stream: headers_frame.end_stream(),
+                }
+            }
+        };
+
+        Ok(event)
+    }
+
+    /// Read the content buffered on a stream.
+    ///
+    /// The flow-control windows withheld because of the content read are
+    /// replenished. The content received on a stream reset by the peer is
+    /// still delivered, then reading fails with a stream error carrying the
+    /// error code of the RST_STREAM frame. Reading a stream the peer did not
+    /// process before sending a GOAWAY frame fails with
+    /// [`Http2Error::StreamNotProcessed`].
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn recv_data(&mut self, stream_id: u32) -> Result<Vec<u8>, Http2Error> {
+        let data = self
+            .streams
+            .get_mut(stream_id)
+            .map(Stream::take_recv_buffer)
+            .unwrap_or_default();
+        if data.is_empty() {
+            if let Some(error) = self.reset_error(stream_id) {
+                return Err(error);
+            }
+        }
+        self.release_windows(stream_id)?;
+
+        Ok(data)
+    }
+
+    /// Get the error of a stream reset by the peer, carrying the error code
+    /// of the RST_STREAM frame, or of a stream the peer did not process
+    /// before sending a GOAWAY frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    fn reset_error(&self, stream_id: u32) -> Option<Http2Error> {
+        let stream = self.streams.get(stream_id)?;
+        if stream.not_processed() {
+            return Some(Http2Error::StreamNotProcessed(stream_id));
+        }
+        let error_code = stream.reset_received()?;
+
+        Some(Http2Error::StreamError(
+            stream_id,
+            error_code,
+            "Stream reset by the peer".to_string(),
+        ))
+    }
+
+    /// Release the window credit of the connection and of a stream, if
+    /// their receive buffers are within their limits and the window update
+    /// strategy calls for it. The connection credit is also withheld while
+    /// the memory budget is exceeded. The credit is coalesced until the
+    /// output is flushed.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    fn release_windows(&mut self, stream_id: u32) -> Result<(), Http2Error> {
+        let strategy = self.window_update_strategy;
+
+        let within_budget = self
+            .record_memory_usage()
+            .is_none_or(|memory_budget| memory_budget.allows_window_update());
+        if self.streams.recv_buffered() <= self.recv_buffer_limit && within_budget {
+            let increment = strategy.increment(self.pending_window_update, self.recv_window_size());
+            if increment > 0 {
+                self.pending_window_update -= increment;
+                self.recv_window.increase(increment)?;
+                self.window_updates.add(0, increment);
+            }
+        }
+
+        let window_size = self.local_initial_window_size();
+        let increment = self
+            .streams
+            .get_mut(stream_id)
+            .map_or(0, |stream| stream.release_window(strategy, window_size));
+        if let Some(recv_window) = self.stream_recv_windows.get_mut(&stream_id) {
+            recv_window.increase(increment)?;
+        }
+        self.window_updates.add(stream_id, increment);
+
+        Ok(())
+    }
+
+    /// Record the memory used by the connection in its memory budget, if
+    /// any, and return the updated budget.
+    fn record_memory_usage(&mut self) -> Option<MemoryBudget> {
+        let memory_budget = self.memory_budget.as_mut()?;
+        memory_budget.record(
+            self.streams.recv_buffered(),
+            self.pending_header_block
+                .as_ref()
+                .and_then(|frame| match frame {
+                    Frame::Headers(headers_frame) => headers_frame.header_block_fragment(),
+                    Frame::PushPromise(push_promise_frame) => {
+                        push_promise_frame.header_block_fragment()
+                    }
+                    _ => None,
+                })
+                .map_or(0, <[u8]>::len),
+            self.encoder_header_table.get_dynamic_table_size()
+                + self.decoder.header_table().get_dynamic_table_size(),
+            self.output.control_len(),
+            self.output.len(),
+        );
+
+        Some(*memory_budget)
+    }
+
+    /// Serialize a frame and queue it for the transport.
+    ///
+    /// DATA frames consume the connection flow-control window of the peer
+    /// and fail with FLOW_CONTROL_ERROR if it is exhausted. The first
+    /// GOAWAY frame starts the time left to in-flight streams. PING frames
+    /// other than the bandwidth-delay product probes are timed to measure
+    /// the round-trip time, the keep-alive PING frames on their own.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to send.
+    pub fn send_frame(&mut self, frame: &Frame) -> Result<(), Http2Error> {
+        match frame {
+            Frame::Data(data_frame) => self
+                .send_window
+                .consume(data_frame.flow_controlled_length())?,
+            Frame::WindowUpdate(window_update_frame) if window_update_frame.stream_id() == 0 => {
+                self.recv_window
+                    .increase(window_update_frame.window_size_increment())?
+            }
+            Frame::WindowUpdate(window_update_frame) => {
+                if let Some(recv_window) = self
+                    .stream_recv_windows
+                    .get_mut(&window_update_frame.stream_id())
+                {
+                    recv_window.increase(window_update_frame.window_size_increment())?
+                }
+            }
+            Frame::Ping(ping_frame)
+                if !ping_frame.is_ack()
+                    && !matches!(
+                        *ping_frame.opaque_data(),
+                        KEEP_ALIVE_PING_OPAQUE_DATA | BDP_PING_OPAQUE_DATA
+                    ) =>
+            {
+                self.ping_sent = Some((*ping_frame.opaque_data(), self.clock.now()))
+            }
+            Frame::Settings(settings_frame) if !settings_frame.is_ack() => self
+                .settings_sent
+                .push_back((self.clock.now(), settings_frame.clone())),
+            Frame::GoAway(_) if self.go_away_sent.is_none() => {
+                self.go_away_sent = Some(self.clock.now())
+            }
+            _ => (),
+        }
+
+        let mut bytes: Vec<u8> = Vec::new();
+        frame.serialize_into(&mut self.encoder_header_table, &mut bytes)?;
+        trace::frame_sent(&bytes);
+
+        self.stats.record_frame_sent(frame.frame_type());
+        self.stats.record_bytes_sent(bytes.len());
+        self.output.push(bytes);
+
+        Ok(())
+    }
+
+    /// Send a HEADERS or PUSH_PROMISE frame, its header block split over
+    /// CONTINUATION frames within the SETTINGS_MAX_FRAME_SIZE of the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The HEADERS or PUSH_PROMISE frame to send.
+    fn send_header_block(&mut self, mut frame: Frame) -> Result<(), Http2Error> {
+        let max_frame_size = self.remote_settings.max_frame_size();
+        let header_table = &mut self.encoder_header_table;
+        let continuation_frames = match &mut frame {
+            Frame::Headers(headers_frame) => {
+                headers_frame.split_header_block(max_frame_size, header_table)?
+            }
+            Frame::PushPromise(push_promise_frame) => {
+                push_promise_frame.split_header_block(max_frame_size, header_table)?
+            }
+            _ => Vec::new(),
+        };
+
+        self.send_frame(&frame)?;
+        for continuation_frame in continuation_frames {
+            self.send_frame(&Frame::Continuation(continuation_frame))?;
+        }
+
+        Ok(())
+    }
+
+    /// Send the header section of a request on a new stream and return the
+    /// stream identifier.
+    ///
+    /// Extended CONNECT requests, carrying a `:protocol` pseudo-header
+    /// field, require the peer to advertise SETTINGS_ENABLE_CONNECT_PROTOCOL.
+    /// Header sections exceeding the SETTINGS_MAX_HEADER_LIST_SIZE of the
+    /// peer, or carrying a `host` header field that disagrees with the
+    /// `:authority` pseudo-header field, are not sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_list` - The request header section.
+    /// * `end_stream` - Whether the request has no content.
+    pub fn send_request(
+        &mut self,
+        header_list: HeaderList,
+        end_stream: bool,
+    ) -> Result<u32, Http2Error> {
+        if self.role != Role::Client {
+            return Err(Http2Error::UsageError(
+                "Only clients can send requests".to_string(),
+            ));
+        }
+        if header_list.protocol().is_some() && !self.remote_settings.enable_connect_protocol() {
+            return Err(Http2Error::UsageError(
+                "Extended CONNECT requires SETTINGS_ENABLE_CONNECT_PROTOCOL".to_string(),
+            ));
+        }
+        if !header_list.host_matches_authority() {
+            return Err(Http2Error::HeaderError(
+                "Request host header field differs from :authority".to_string(),
+            ));
+        }
+        self.check_header_list_size(&header_list)?;
+
+        let stream_id = self.stream_ids.next_stream_id()?;
+        self.streams.open_local(stream_id)?;
+        self.start_deadline(stream_id);
+        self.send_headers(stream_id, header_list, end_stream)?;
+
+        Ok(stream_id)
+    }
+
+    /// Send the header section of the response to a request received on a
+    /// stream.
+    ///
+    /// Responding on a stream reset by the peer is a stream error carrying
+    /// the error code of the RST_STREAM frame. Responding on a promised
+    /// stream sends the pushed response, which opens the stream and fails
+    /// with REFUSED_STREAM beyond the SETTINGS_MAX_CONCURRENT_STREAMS of
+    /// the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream of the request.
+    /// * `header_list` - The response header section.
+    /// * `end_stream` - Whether the response has no content.
+    pub fn send_response(
+        &mut self,
+        stream_id: u32,
+        header_list: HeaderList,
+        end_stream: bool,
+    ) -> Result<(), Http2Error> {
+        if self.role != Role::Server {
+            return Err(Http2Error::UsageError(
+                "Only servers can send responses".to_string(),
+            ));
+        }
+        self.check_header_list_size(&header_list)?;
+        if self
+            .streams
+            .get(stream_id)
+            .is_some_and(|stream| stream.state() == StreamState::ReservedLocal)
+        {
+            self.streams.open_local(stream_id)?;
+        }
+
+        self.send_headers(stream_id, header_list, end_stream)
+    }
+
+    /// Promise to push the response to a request on a new stream and return
+    /// the promised stream identifier.
+    ///
+    /// The promise is associated with a request received on a stream, and
+    /// the pushed response is then sent on the promised stream with
+    /// [`Http2Core::send_response`]. Pushing fails with a
+    /// [`Http2Error::UsageError`] if the peer disabled server push with
+    /// SETTINGS_ENABLE_PUSH = 0, and with a [`Http2Error::HeaderError`] if
+    /// the promised request is not a GET or HEAD request, the only safe and
+    /// cacheable methods without content.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream of the request the push is associated with.
+    /// * `header_list` - The promised request header section.
+    pub fn send_push_promise(
+        &mut self,
+        stream_id: u32,
+        header_list: HeaderList,
+    ) -> Result<u32, Http2Error> {
+        if self.role != Role::Server {
+            return Err(Http2Error::UsageError("Only servers can push".to_string()));
+        }
+        if !self.remote_settings.enable_push() {
+            return Err(Http2Error::UsageError(
+                "Server push is disabled by the peer SETTINGS_ENABLE_PUSH".to_string(),
+            ));
+        }
+        if !matches!(header_list.method(), Some("GET") | Some("HEAD")) {
+            return Err(Http2Error::HeaderError(format!(
+                "Cannot push a request with method {:?}",
+                header_list.method()
+            )));
+        }
+        if let Some(error) = self.reset_error(stream_id) {
+            return Err(error);
+        }
+        self.check_header_list_size(&header_list)?;
+
+        let promised_stream_id = self.stream_ids.next_stream_id()?;
+        self.streams
+            .send_push_promise(stream_id, promised_stream_id)?;
+        let push_promise_frame =
+            PushPromiseFrame::new(stream_id, true, promised_stream_id, header_list);
+        self.send_header_block(Frame::PushPromise(push_promise_frame))?;
+
+        Ok(promised_stream_id)
+    }
+
+    /// Send an interim (1xx) response to a request received on a stream,
+    /// such as 103 Early Hints, before its final response.
+    ///
+    /// Interim responses never end the stream. A status outside of the 1xx
+    /// range, or 101 Switching Protocols which HTTP/2 does not support,
+    /// fails with a [`Http2Error::HeaderError`], and a stream the server
+    /// cannot send on with a [`Http2Error::UsageError`].
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream of the request.
+    /// * `header_list` - The interim response header section.
+    pub fn send_interim_response(
+        &mut self,
+        stream_id: u32,
+        header_list: HeaderList,
+    ) -> Result<(), Http2Error> {
+        if self.role != Role::Server {
+            return Err(Http2Error::UsageError(
+                "Only servers can send responses".to_string(),
+            ));
+        }
+        if let Some(error) = self.reset_error(stream_id) {
+            return Err(error);
+        }
+        let state = self.streams.state(stream_id);
+        if !matches!(state, StreamState::Open | StreamState::HalfClosedRemote) {
+            return Err(Http2Error::UsageError(format!(
+                "Cannot send an interim response on stream {} in state {:?}",
+                stream_id, state
+            )));
+        }
+        let status = header_list.status();
+        if !matches!(status, Some(100..=199)) || status == Some(101) {
+            return Err(Http2Error::HeaderError(format!(
+                "Invalid interim response status {:?}",
+                status
+            )));
+        }
+        self.check_header_list_size(&header_list)?;
+
+        self.send_headers(stream_id, header_list, false)
+    }
+
+    /// Check that a header list does not exceed the
+    /// SETTINGS_MAX_HEADER_LIST_SIZE advertised by the peer, which would
+    /// refuse it, nor the encoder limits of the connection.
+    ///
+    /// Fails fast with a [`Http2Error::HeaderError`] before anything is
+    /// sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_list` - The header list to send.
+    fn check_header_list_size(&self, header_list: &HeaderList) -> Result<(), Http2Error> {
+        self.encoder_header_table
+            .encoder_limits()
+            .check(&header_list.encoded_fields(&self.encoder_header_table))?;
+
+        let max_header_list_size = match self.remote_settings.max_header_list_size() {
+            Some(max_header_list_size) => max_header_list_size as usize,
+            None => return Ok(()),
+        };
+
+        let header_list_size = header_list.size();
+        if header_list_size > max_header_list_size {
+            return Err(Http2Error::HeaderError(format!(
+                "Header list size {} exceeds the SETTINGS_MAX_HEADER_LIST_SIZE of the peer ({})",
+                header_list_size, max_header_list_size
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Send the header section of a message.
+    ///
+    /// Sending on a stream reset by the peer is a stream error carrying the
+    /// error code of the RST_STREAM frame.
+    fn send_headers(
+        &mut self,
+        stream_id: u32,
+        header_list: HeaderList,
+        end_stream: bool,
+    ) -> Result<(), Http2Error> {
+        if let Some(error) = self.reset_error(stream_id) {
+            return Err(error);
+        }
+        let headers_frame = HeadersFrame::new(stream_id, end_stream, true, None, header_list);
+        self.send_header_block(Frame::Headers(headers_frame))?;
+        if self.role.initiates(stream_id) {
+            self.open_recv_window(stream_id);
+        }
+
+        if end_stream {
+            return self.streams.send_end_stream(stream_id);
+        }
+
+        let remote_settings = &self.remote_settings;
+        self.stream_send_windows
+            .entry(stream_id)
+            .or_insert_with(|| FlowControlWindow::for_stream(stream_id, remote_settings));
+
+        Ok(())
+    }
+
+    /// Send the content of a message, within the flow-control windows of
+    /// the peer, and return the number of octets sent.
+    ///
+    /// The content is split into DATA frames within the
+    /// SETTINGS_MAX_FRAME_SIZE of the peer. Octets beyond the windows are
+    /// not sent: the caller should retry once the peer has sent
+    /// WINDOW_UPDATE frames. The stream is only ended once all the content
+    /// is sent. Sending on a stream reset by the peer is a stream error
+    /// carrying the error code of the RST_STREAM frame, sending on a stream
+    /// the peer did not process before sending a GOAWAY frame a
+    /// [`Http2Error::StreamNotProcessed`] error, and sending on another
+    /// closed stream a stream error of type STREAM_CLOSED.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `data` - The content to send.
+    /// * `end_stream` - Whether the content ends the message.
+    pub fn send_data(
+        &mut self,
+        stream_id: u32,
+        data: &[u8],
+        end_stream: bool,
+    ) -> Result<usize, Http2Error> {
+        let mut sent = 0;
+
+        loop {
+            let stream_window_size = match self.stream_send_windows.get(&stream_id) {
+                Some(send_window) => send_window.size(),
+                None => {
+                    return Err(self.reset_error(stream_id).unwrap_or_else(|| {
+                        Http2Error::StreamError(
+                            stream_id,
+                            ErrorCode::StreamClosed,
+                            format!("Cannot send DATA on stream {}", stream_id),
+                        )
+                    }))
+                }
+            };
+            let available = self
+                .send_window
+                .size()
+                .min(stream_window_size)
+                .min(self.remote_settings.max_frame_size() as i64)
+                .max(0) as usize;
+
+            let length = (data.len() - sent).min(available);
+            let end = end_stream && sent + length == data.len();
+            if length == 0 && !end {
+                return Ok(sent);
+            }
+
+            let data_frame = DataFrame::new(stream_id, end, data[sent..sent + length].to_vec());
+            self.send_frame(&Frame::Data(data_frame))?;
+            if let Some(send_window) = self.stream_send_windows.get_mut(&stream_id) {
+                send_window.consume(length as u32)?;
+            }
+            sent += length;
+
+            if end {
+                self.stream_send_windows.remove(&stream_id);
+                self.streams.send_end_stream(stream_id)?;
+                return Ok(sent);
+            }
+        }
+    }
+
+    /// Send the trailers of a message, ending the stream.
+    ///
+    /// Trailers are sent in a HEADERS frame with the END_STREAM flag, after
+    /// the DATA frames of the message, and MUST NOT contain pseudo-header
+    /// fields. Sending on a stream reset by the peer is a stream error
+    /// carrying the error code of the RST_STREAM frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `trailers` - The trailer section.
+    pub fn send_trailers(
+        &mut self,
+        stream_id: u32,
+        trailers: HeaderList,
+    ) -> Result<(), Http2Error> {
+        if let Some(error) = self.reset_error(stream_id) {
+            return Err(error);
+        }
+        self.check_header_list_size(&trailers)?;
+        self.streams.send_trailers(stream_id, &trailers)?;
+        self.stream_send_windows.remove(&stream_id);
+
+        let frame = Frame::Headers(HeadersFrame::new(stream_id, true, true, None, trailers));
+        self.send_header_block(frame)
+    }
+
+    /// Reset a stream with a RST_STREAM frame and close it.
+    ///
+    /// The frames the peer sent on the stream before receiving the
+    /// RST_STREAM frame are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `error_code` - The reason for resetting the stream.
+    pub fn send_reset(&mut self, stream_id: u32, error_code: ErrorCode) -> Result<(), Http2Error> {
+        let rst_stream_frame = RstStreamFrame::new(stream_id, error_code);
+        self.send_frame(&Frame::RstStream(rst_stream_frame))?;
+
+        self.streams.send_reset(stream_id);
+        self.stream_send_windows.remove(&stream_id);
+        self.stream_recv_windows.remove(&stream_id);
+        self.stream_deadlines.remove(&stream_id);
+        self.window_updates.remove(stream_id);
+
+        Ok(())
+    }
+
+    /// Take the trailers received on a stream, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn recv_trailers(&mut self, stream_id: u32) -> Option<HeaderList> {
+        self.streams.take_trailers(stream_id)
+    }
+
+    /// Start the deadlines of a new stream, if stream timeouts are enabled.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    fn start_deadline(&mut self, stream_id: u32) {
+        if self.stream_timeouts.is_enabled() {
+            self.stream_deadlines
+                .insert(stream_id, StreamDeadline::new(self.clock.now()));
+        }
+    }
+
+    /// Get the next deadline of the connection or of its streams, if any.
+    ///
+    /// The driver should call [`Http2Core::handle_timeouts`] once it is
+    /// reached, even if no input was received meanwhile.
+    pub fn poll_timeout(&self) -> Option<Instant> {
+        self.stream_deadlines
+            .iter()
+            .filter_map(|(stream_id, stream_deadline)| {
+                let stream = self.streams.get(*stream_id)?;
+                stream_deadline.next_deadline(&self.stream_timeouts, stream)
+            })
+            .chain(self.settings_deadline())
+            .chain(self.keep_alive_deadline())
+            .chain(self.go_away_deadline())
+            .min()
+    }
+
+    /// Get the time by which the peer must acknowledge the oldest SETTINGS
+    /// frame sent, if a SETTINGS timeout is set.
+    fn settings_deadline(&self) -> Option<Instant> {
+        let (sent, _) = self.settings_sent.front()?;
+        self.settings_timeout
+            .map(|settings_timeout| *sent + settings_timeout)
+    }
+
+    /// Get the deadline of the keep-alive PING: the time to send it, or
+    /// the time to close the connection once it is sent.
+    fn keep_alive_deadline(&self) -> Option<Instant> {
+        match self.keep_alive_sent {
+            Some(sent) => Some(sent + self.ping_timeout),
+            None => self
+                .ping_interval
+                .map(|ping_interval| self.last_received + ping_interval),
+        }
+    }
+
+    /// Get the time by which the in-flight streams must complete once a
+    /// GOAWAY frame is sent, if any is left.
+    fn go_away_deadline(&self) -> Option<Instant> {
+        let in_flight =
+            self.streams.local_active_streams() + self.streams.remote_active_streams() > 0;
+        self.go_away_sent
+            .filter(|_| in_flight)
+            .map(|sent| sent + self.goaway_timeout)
+    }
+
+    /// Handle the expired deadlines of the connection and of its streams.
+    ///
+    /// A SETTINGS frame not acknowledged within the SETTINGS timeout is a
+    /// connection error of type SETTINGS_TIMEOUT, reported to the peer with
+    /// a GOAWAY frame. A keep-alive PING is sent once no frame was received for the
+    /// keep-alive interval. If the peer does not acknowledge it within the
+    /// keep-alive timeout, a GOAWAY frame is sent and the connection fails
+    /// with a connection error of type NO_ERROR: the driver should close
+    /// the transport. So does the connection once a GOAWAY frame was sent,
+    /// if streams are still in flight after the GOAWAY timeout.
+    ///
+    /// Each expired stream is reset with a RST_STREAM frame of type CANCEL
+    /// and closed. Returns a [`Http2Error::StreamTimeout`] per cancelled
+    /// stream, to report to the application. The deadlines of the streams
+    /// closed meanwhile are forgotten.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time.
+    pub fn handle_timeouts(&mut self, now: Instant) -> Result<Vec<Http2Error>, Http2Error> {
+        self.handle_settings_timeout(now)?;
+        self.handle_keep_alive(now)?;
+        self.handle_go_away_timeout(now)?;
+
+        let streams = &self.streams;
+        self.stream_deadlines.retain(|stream_id, _| {
+            streams
+                .get(*stream_id)
+                .is_some_and(|stream| stream.state() != StreamState::Closed)
+        });
+
+        let mut expired: Vec<(u32, StreamTimeout)> = self
+            .stream_deadlines
+            .iter()
+            .filter_map(|(stream_id, stream_deadline)| {
+                let stream = self.streams.get(*stream_id)?;
+                let timeout = stream_deadline.expired(&self.stream_timeouts, stream, now)?;
+                Some((*stream_id, timeout))
+            })
+            .collect();
+        expired.sort_unstable_by_key(|(stream_id, _)| *stream_id);
+
+        let mut errors = Vec::new();
+        for (stream_id, timeout) in expired {
+            self.send_reset(stream_id, ErrorCode::Cancel)?;
+            errors.push(Http2Error::StreamTimeout(stream_id, timeout));
+        }
+
+        Ok(errors)
+    }
+
+    /// Close the connection if the peer did not acknowledge a SETTINGS
+    /// frame in time.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time.
+    fn handle_settings_timeout(&mut self, now: Instant) -> Result<(), Http2Error> {
+        match self.settings_deadline() {
+            Some(deadline) if deadline <= now => (),
+            _ => return Ok(()),
+        }
+
+        let go_away_frame = GoAwayFrame::new(
+            self.stream_ids.last_remote_stream_id(),
+            ErrorCode::SettingsTimeout,
+            b"SETTINGS timeout".to_vec(),
+        );
+        self.send_frame(&Frame::GoAway(go_away_frame))?;
+
+        Err(Http2Error::ConnectionError(
+            ErrorCode::SettingsTimeout,
+            format!(
+                "SETTINGS frame not acknowledged within {:?}",
+                self.settings_timeout.unwrap_or_default()
+            ),
+        ))
+    }
+
+    /// Send a keep-alive PING once the keep-alive interval expired, or
+    /// close the connection if the peer did not acknowledge it in time.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time.
+    fn handle_keep_alive(&mut self, now: Instant) -> Result<(), Http2Error> {
+        match self.keep_alive_deadline() {
+            Some(deadline) if deadline <= now => (),
+            _ => return Ok(()),
+        }
+
+        if self.keep_alive_sent.is_none() {
+            self.send_frame(&Frame::Ping(PingFrame::new(KEEP_ALIVE_PING_OPAQUE_DATA)))?;
+            self.keep_alive_sent = Some(now);
+            return Ok(());
+        }
+
+        let go_away_frame = GoAwayFrame::new(
+            self.stream_ids.last_remote_stream_id(),
+            ErrorCode::NoError,
+            b"keep-alive timeout".to_vec(),
+        );
+        self.send_frame(&Frame::GoAway(go_away_frame))?;
+
+        Err(Http2Error::ConnectionError(
+            ErrorCode::NoError,
+            format!(
+                "Keep-alive PING not acknowledged within {:?}",
+                self.ping_timeout
+            ),
+        ))
+    }
+
+    /// Close the connection if streams are still in flight once the
+    /// GOAWAY timeout expired.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The current time.
+    fn handle_go_away_timeout(&mut self, now: Instant) -> Result<(), Http2Error> {
+        match self.go_away_deadline() {
+            Some(deadline) if deadline <= now => (),
+            _ => return Ok(()),
+        }
+
+        Err(Http2Error::ConnectionError(
+            ErrorCode::NoError,
+            format!(
+                "Streams still in flight {:?} after the GOAWAY frame",
+                self.goaway_timeout
+            ),
+        ))
+    }
+
+    /// Allocate the identifier of a new local stream.
+    ///
+    /// Fails with a connection error of type NO_ERROR once the stream
+    /// identifiers are exhausted and a new connection is required.
+    pub fn next_stream_id(&mut self) -> Result<u32, Http2Error> {
+        self.stream_ids.next_stream_id()
+    }
+
+    /// Check if the SETTINGS frame of the peer connection preface was
+    /// received.
+    pub fn is_established(&self) -> bool {
+        self.settings_received
+    }
+
+    /// Get the connection flow-control window of the peer, available to
+    /// send DATA frames.
+    pub fn send_window(&self) -> &FlowControlWindow {
+        &self.send_window
+    }
+
+    /// Take a snapshot of the connection statistics.
+    pub fn stats(&mut self) -> ConnectionStats {
+        let mut stats = self.stats.clone();
+        stats.set_state(
+            self.streams.local_active_streams(),
+            self.streams.remote_active_streams(),
+            self.encoder_header_table.get_dynamic_table_size(),
+            self.decoder.header_table().get_dynamic_table_size(),
+            self.send_window.size(),
+            self.recv_window.size(),
+        );
+        stats.set_hpack_stats(
+            self.encoder_header_table.stats(),
+            self.decoder.header_table().stats(),
+        );
+
+        stats
+    }
+
+    /// Get the role of the local endpoint.
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    /// Get the streams of the connection, e.g. to inspect their states.
+    pub fn streams(&self) -> &StreamManager {
+        &self.streams
+    }
+
+    /// Get the streams of the connection mutably, for tests driving their
+    /// states by hand.
+    #[cfg(feature = "test-support")]
+    pub fn streams_mut(&mut self) -> &mut StreamManager {
+        &mut self.streams
+    }
+
+    /// Get the guard limiting the size of received header blocks.
+    pub fn header_block_guard(&mut self) -> &mut HeaderBlockGuard {
+        self.decoder.header_block_guard()
+    }
+
+    /// Get the settings advertised to the peer, the parameters never sent
+    /// keeping their initial value.
+    pub fn local_settings(&self) -> Settings {
+        let mut settings = Settings::new();
+        // The parameters were checked when the connection was built.
+        let _ = settings.apply(&self.local_settings_frame());
+        settings
+    }
+
+    /// Get the parameters of the SETTINGS frames sent to the peer, in
+    /// order.
+    pub fn local_settings_parameters(&self) -> &Vec<SettingsParameter> {
+        &self.local_settings
+    }
+
+    /// Get the settings last received from the peer, the parameters never
+    /// received keeping their initial value.
+    pub fn peer_settings(&self) -> Settings {
+        self.remote_settings
+    }
+
+    /// Get the time the peer has to acknowledge a SETTINGS frame, if any.
+    pub fn settings_timeout(&self) -> Option<Duration> {
+        self.settings_timeout
+    }
+
+    /// Get the interval between the PING frames keeping the connection
+    /// alive, if any.
+    pub fn ping_interval(&self) -> Option<Duration> {
+        self.ping_interval
+    }
+
+    /// Get the time the peer has to acknowledge a keep-alive PING.
+    pub fn ping_timeout(&self) -> Duration {
+        self.ping_timeout
+    }
+
+    /// Get the time left to in-flight streams after a GOAWAY frame is sent.
+    pub fn goaway_timeout(&self) -> Duration {
+        self.goaway_timeout
+    }
+
+    /// Get the timeouts applied to the streams.
+    pub fn stream_timeouts(&self) -> StreamTimeouts {
+        self.stream_timeouts
+    }
+
+    /// Get the current time of the clock of the connection.
+    pub fn now(&self) -> Instant {
+        self.clock.now()
+    }
+
+    /// Get the memory budget of the connection, if any, with the memory
+    /// currently used.
+    pub fn memory_budget(&mut self) -> Option<MemoryBudget> {
+        self.record_memory_usage()
+    }
+
+    /// Get the maximum size of the HPACK encoding table chosen locally.
+    ///
+    /// The encoding table uses the SETTINGS_HEADER_TABLE_SIZE of the peer
+    /// if it is smaller.
+    pub fn encoder_header_table_size(&self) -> usize {
+        self.max_encoder_header_table_size
+    }
+
+    /// Set the maximum size of the HPACK encoding table, e.g. to save
+    /// memory on a constrained deployment.
+    ///
+    /// The encoding table never exceeds the SETTINGS_HEADER_TABLE_SIZE of
+    /// the peer. A resize is signaled to the peer decoder with a dynamic
+    /// table size update at the beginning of the next header block.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder_header_table_size` - The maximum size of the encoding table.
+    pub fn set_encoder_header_table_size(&mut self, encoder_header_table_size: usize) {
+        self.max_encoder_header_table_size = encoder_header_table_size;
+        self.resize_encoder_header_table();
+    }
+}
@@ -0,0 +1,328 @@
+use std::io::ErrorKind;
+use std::time::Duration;
+
+use crate::connection::budget::MemoryBudget;
+use crate::connection::builder::ConnectionBuilder;
+use crate::connection::core::Http2Core;
+use crate::connection::settings::Settings;
+use crate::connection::stats::ConnectionStats;
+use crate::error::Http2Error;
+use crate::flow_control::FlowControlWindow;
+use crate::frame::header_block::HeaderBlockGuard;
+use crate::frame::settings::SettingsParameter;
+use crate::frame::Frame;
+use crate::header::list::HeaderList;
+use crate::stream::timeout::StreamTimeouts;
+use crate::stream::{manager::StreamManager, Role};
+use crate::transport::ByteStream;
+
+pub mod budget;
+pub mod builder;
+pub mod clock;
+pub mod core;
+pub mod send_queue;
+pub mod settings;
+pub mod stats;
+pub mod stream_id;
+
+pub use crate::header::table::DEFAULT_HEADER_TABLE_SIZE;
+
+/// Size of the buffer used to read from the transport.
+const READ_BUFFER_SIZE: usize = 16_384;
+
+/// HTTP/2 connection over a blocking transport.
+///
+/// The connection owns the transport, any [`ByteStream`], and drives a
+/// sans-io [`Http2Core`] holding the per-connection state: the frame
+/// decoder with its HPACK decoding table, the HPACK encoding table, the
+/// streams, the connection flow-control windows and the statistics.
+pub struct Connection<T> {
+    io: T,
+    core: Http2Core,
+    max_write_batch: Option<usize>,
+}
+
+impl<T: ByteStream> Connection<T> {
+    /// Start a client connection with prior knowledge of HTTP/2 support.
+    ///
+    /// Cleartext HTTP/2 without upgrade nor TLS: the client sends the
+    /// connection preface followed by its SETTINGS frame, then waits for
+    /// the server SETTINGS frame and acknowledges it. Use a
+    /// [`ConnectionBuilder`] to tune the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The transport, typically a TCP stream.
+    pub fn handshake_prior_knowledge(io: T) -> Result<Self, Http2Error> {
+        ConnectionBuilder::client().handshake(io)
+    }
+
+    /// Accept a server connection with prior knowledge of HTTP/2 support.
+    ///
+    /// The server reads and checks the client connection preface, sends
+    /// its SETTINGS frame, then waits for the client SETTINGS frame and
+    /// acknowledges it. Use a [`ConnectionBuilder`] to tune the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The transport, typically a TCP stream.
+    pub fn accept_prior_knowledge(io: T) -> Result<Self, Http2Error> {
+        ConnectionBuilder::server().handshake(io)
+    }
+
+    /// Perform the handshake of a connection over a transport.
+    ///
+    /// The connection preface queued by the core is written, then frames
+    /// are read until the peer SETTINGS frame, which MUST come first.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The transport.
+    /// * `core` - The connection state machine, with its preface queued.
+    /// * `max_write_batch` - The number of octets written at once, if the
+    ///   writes are batched.
+    fn handshake(
+        io: T,
+        core: Http2Core,
+        max_write_batch: Option<usize>,
+    ) -> Result<Self, Http2Error> {
+        let mut connection = Connection {
+            io,
+            core,
+            max_write_batch,
+        };
+
+        connection.write_output()?;
+        while !connection.core.is_established() {
+            connection.recv_frame()?;
+        }
+        connection.flush()?;
+
+        Ok(connection)
+    }
+
+    /// Serialize and write a frame to the transport.
+    ///
+    /// DATA frames consume the connection flow-control window of the peer
+    /// and fail with FLOW_CONTROL_ERROR if it is exhausted.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame to send.
+    pub fn send_frame(&mut self, frame: &Frame) -> Result<(), Http2Error> {
+        self.core.send_frame(frame)?;
+        self.write_output()
+    }
+
+    /// Read from the transport until a complete frame is decoded.
+    ///
+    /// The frame is applied to the connection state as described in
+    /// [`Http2Core::next_frame`], and the frames sent in reaction are
+    /// written to the transport.
+    pub fn recv_frame(&mut self) -> Result<Frame, Http2Error> {
+        loop {
+            let frame = self.core.next_frame()?;
+            self.write_output()?;
+            if let Some(frame) = frame {
+                return Ok(frame);
+            }
+
+            self.read_input()?;
+        }
+    }
+
+    /// Read once from the transport into the core, after writing the
+    /// batched output the peer may be waiting for.
+    pub(crate) fn read_input(&mut self) -> Result<(), Http2Error> {
+        let mut buffer = [0u8; READ_BUFFER_SIZE];
+
+        self.flush()?;
+        let read = self.io.read(&mut buffer)?;
+        if read == 0 {
+            return Err(Http2Error::IoError(ErrorKind::UnexpectedEof.into()));
+        }
+        self.core.feed(&buffer[..read]);
+
+        Ok(())
+    }
+
+    /// Write the output queued by the core to the transport, unless the
+    /// writes are batched and the batch is not full yet.
+    pub(crate) fn write_output(&mut self) -> Result<(), Http2Error> {
+        match self.max_write_batch {
+            Some(max_write_batch) if self.core.output_len() < max_write_batch => Ok(()),
+            _ => self.flush(),
+        }
+    }
+
+    /// Write all the output queued by the core to the transport, then
+    /// flush the transport.
+    ///
+    /// Batched writes carry whole frames, up to the batch size each.
+    pub fn flush(&mut self) -> Result<(), Http2Error> {
+        if !self.core.has_output() {
+            return Ok(());
+        }
+
+        match self.max_write_batch {
+            Some(max_write_batch) => {
+                while self.core.has_output() {
+                    let bytes = self.core.poll_output_limited(max_write_batch);
+                    self.io.write_all(&bytes)?;
+                }
+            }
+            None => self.io.write_all(&self.core.poll_output())?,
+        }
+        self.io.flush()?;
+
+        Ok(())
+    }
+
+    /// Write the output queued by the core, then shut the writing half of
+    /// the transport down.
+    ///
+    /// The connection is typically shut down once a GOAWAY frame is sent
+    /// and the in-flight streams are complete.
+    pub fn shutdown(&mut self) -> Result<(), Http2Error> {
+        self.flush()?;
+        self.io.shutdown()?;
+
+        Ok(())
+    }
+
+    /// Get the connection flow-control window of the peer, available to
+    /// send DATA frames.
+    pub fn send_window(&self) -> &FlowControlWindow {
+        self.core.send_window()
+    }
+
+    /// Take a snapshot of the connection statistics.
+    pub fn stats(&mut self) -> ConnectionStats {
+        self.core.stats()
+    }
+
+    /// Send the trailers of a message, ending the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `trailers` - The trailer section.
+    pub fn send_trailers(
+        &mut self,
+        stream_id: u32,
+        trailers: HeaderList,
+    ) -> Result<(), Http2Error> {
+        self.core.send_trailers(stream_id, trailers)?;
+        self.write_output()
+    }
+
+    /// Take the trailers received on a stream, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn recv_trailers(&mut self, stream_id: u32) -> Option<HeaderList> {
+        self.core.recv_trailers(stream_id)
+    }
+
+    /// Allocate the identifier of a new local stream.
+    ///
+    /// Fails with a connection error of type NO_ERROR once the stream
+    /// identifiers are exhausted and a new connection is required.
+    pub fn next_stream_id(&mut self) -> Result<u32, Http2Error> {
+        self.core.next_stream_id()
+    }
+
+    /// Get the role of the local endpoint.
+    pub fn role(&self) -> Role {
+        self.core.role()
+    }
+
+    /// Get the streams of the connection, e.g. to inspect their states.
+    pub fn streams(&self) -> &StreamManager {
+        self.core.streams()
+    }
+
+    /// Get the streams of the connection mutably, for tests driving their
+    /// states by hand.
+    #[cfg(feature = "test-support")]
+    pub fn streams_mut(&mut self) -> &mut StreamManager {
+        self.core.streams_mut()
+    }
+
+    /// Get the guard limiting the size of received header blocks.
+    pub fn header_block_guard(&mut self) -> &mut HeaderBlockGuard {
+        self.core.header_block_guard()
+    }
+
+    /// Get the settings advertised to the peer, the parameters never sent
+    /// keeping their initial value.
+    pub fn local_settings(&self) -> Settings {
+        self.core.local_settings()
+    }
+
+    /// Get the parameters of the SETTINGS frames sent to the peer, in
+    /// order.
+    pub fn local_settings_parameters(&self) -> &Vec<SettingsParameter> {
+        self.core.local_settings_parameters()
+    }
+
+    /// Get the settings last received from the peer, the parameters never
+    /// received keeping their initial value.
+    pub fn peer_settings(&self) -> Settings {
+        self.core.peer_settings()
+    }
+
+    /// Get the time the peer has to acknowledge a SETTINGS frame, if any.
+    pub fn settings_timeout(&self) -> Option<Duration> {
+        self.core.settings_timeout()
+    }
+
+    /// Get the interval between the PING frames keeping the connection
+    /// alive, if any.
+    pub fn ping_interval(&self) -> Option<Duration> {
+        self.core.ping_interval()
+    }
+
+    /// Get the time the peer has to acknowledge a keep-alive PING.
+    pub fn ping_timeout(&self) -> Duration {
+        self.core.ping_timeout()
+    }
+
+    /// Get the time left to in-flight streams after a GOAWAY frame is sent.
+    pub fn goaway_timeout(&self) -> Duration {
+        self.core.goaway_timeout()
+    }
+
+    /// Get the timeouts applied to the streams.
+    pub fn stream_timeouts(&self) -> StreamTimeouts {
+        self.core.stream_timeouts()
+    }
+
+    /// Get the number of octets written at once to the transport, if the
+    /// writes are batched.
+    pub fn max_write_batch(&self) -> Option<usize> {
+        self.max_write_batch
+    }
+
+    /// Get the memory budget of the connection, if any, with the memory
+    /// currently used.
+    pub fn memory_budget(&mut self) -> Option<MemoryBudget> {
+        self.core.memory_budget()
+    }
+
+    /// Get the connection state machine.
+    pub fn core(&mut self) -> &mut Http2Core {
+        &mut self.core
+    }
+
+    /// Get the underlying transport.
+    pub fn get_mut(&mut self) -> &mut T {
+        &mut self.io
+    }
+
+    /// Release the underlying transport.
+    pub fn into_inner(self) -> T {
+        self.io
+    }
+}
@@ -0,0 +1,142 @@
+use std::collections::VecDeque;
+
+/// Frame serialized for the transport.
+#[derive(Debug)]
+struct QueuedFrame {
+    stream_id: u32,
+    bytes: Vec<u8>,
+}
+
+/// Outgoing frames of a connection, waiting for the transport.
+///
+/// A large body queued for a slow transport would hold back the frames
+/// keeping the connection alive, such as PING acknowledgements or
+/// WINDOW_UPDATE frames the peer is waiting for. Control frames (SETTINGS,
+/// PING, WINDOW_UPDATE, RST_STREAM and GOAWAY) thus jump ahead of the DATA
+/// and header frames queued before them, with two exceptions:
+///
+/// * the frames of a stream keep their order, so that a RST_STREAM or a
+///   WINDOW_UPDATE frame never overtakes the header block opening its
+///   stream nor the DATA frames it follows,
+/// * a header block is never interleaved with other frames once its first
+///   frame was dequeued.
+///
+/// DATA frames are only queued once their flow-control credit was
+/// consumed, so that every queued frame may be written as soon as the
+/// transport accepts it.
+#[derive(Debug, Default)]
+pub struct SendQueue {
+    control: VecDeque<QueuedFrame>,
+    ordered: VecDeque<QueuedFrame>,
+    len: usize,
+    control_len: usize,
+    header_block_open: bool,
+}
+
+impl SendQueue {
+    /// Create an empty send queue.
+    pub fn new() -> Self {
+        SendQueue::default()
+    }
+
+    /// Queue the connection preface sequence, written before any frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The connection preface sequence.
+    pub fn push_preface(&mut self, bytes: &[u8]) {
+        self.len += bytes.len();
+        self.control_len += bytes.len();
+        self.control.push_front(QueuedFrame {
+            stream_id: 0,
+            bytes: bytes.to_vec(),
+        });
+    }
+
+    /// Queue a serialized frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The serialized frame, its 9-octet header included.
+    pub fn push(&mut self, bytes: Vec<u8>) {
+        let frame_type = bytes[3];
+        let stream_id = u32::from_be_bytes([bytes[5], bytes[6], bytes[7], bytes[8]]) & 0x7fff_ffff;
+        let queued_frame = QueuedFrame { stream_id, bytes };
+        self.len += queued_frame.bytes.len();
+
+        let control = matches!(frame_type, 0x3 | 0x4 | 0x6 | 0x7 | 0x8);
+        let stream_queued = stream_id != 0
+            && self
+                .ordered
+                .iter()
+                .any(|queued_frame| queued_frame.stream_id == stream_id);
+        if control && !stream_queued {
+            self.control_len += queued_frame.bytes.len();
+            self.control.push_back(queued_frame);
+        } else {
+            self.ordered.push_back(queued_frame);
+        }
+    }
+
+    /// Dequeue whole frames up to a number of octets, control frames first.
+    ///
+    /// At least one frame is dequeued if any is queued, even if it is
+    /// larger than the limit.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_len` - The number of octets the transport accepts.
+    pub fn pop(&mut self, max_len: usize) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+
+        loop {
+            let from_control = !self.header_block_open && !self.control.is_empty();
+            let queue = if from_control {
+                &mut self.control
+            } else {
+                &mut self.ordered
+            };
+            let length = match queue.front() {
+                Some(queued_frame) => queued_frame.bytes.len(),
+                None => break,
+            };
+            if !bytes.is_empty() && bytes.len() + length > max_len {
+                break;
+            }
+
+            // A header block stays open until its END_HEADERS flag.
+            let queued_frame = queue.pop_front().unwrap();
+            if from_control {
+                self.control_len -= queued_frame.bytes.len();
+            }
+            if !from_control && matches!(queued_frame.bytes[3], 0x1 | 0x5 | 0x9) {
+                self.header_block_open = queued_frame.bytes[4] & 0x4 == 0;
+            }
+            bytes.extend_from_slice(&queued_frame.bytes);
+        }
+
+        self.len -= bytes.len();
+        bytes
+    }
+
+    /// Dequeue all the queued frames, control frames first.
+    pub fn pop_all(&mut self) -> Vec<u8> {
+        self.pop(usize::MAX)
+    }
+
+    /// Get the number of queued octets.
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Get the number of queued octets jumping ahead as control frames,
+    /// the connection preface included.
+    pub fn control_len(&self) -> usize {
+        self.control_len
+    }
+
+    /// Check if no frame is queued.
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+}
@@ -0,0 +1,167 @@
+use crate::connection::DEFAULT_HEADER_TABLE_SIZE;
+use crate::error::{ErrorCode, Http2Error};
+use crate::flow_control::{DEFAULT_INITIAL_WINDOW_SIZE, MAX_WINDOW_SIZE};
+use crate::frame::settings::{SettingsFrame, SettingsParameter};
+use crate::frame::{DEFAULT_MAX_FRAME_SIZE, MAX_FRAME_SIZE_UPPER_BOUND};
+
+/// Settings of an endpoint.
+///
+/// The settings start with their initial values and are updated by the
+/// SETTINGS frames sent by the endpoint. Limits without an initial value,
+/// SETTINGS_MAX_CONCURRENT_STREAMS and SETTINGS_MAX_HEADER_LIST_SIZE, are
+/// `None` until they are set.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Settings {
+    header_table_size: u32,
+    enable_push: bool,
+    max_concurrent_streams: Option<u32>,
+    initial_window_size: u32,
+    max_frame_size: u32,
+    max_header_list_size: Option<u32>,
+    enable_connect_protocol: bool,
+}
+
+impl Settings {
+    /// Create settings with their initial values.
+    pub fn new() -> Self {
+        Settings {
+            header_table_size: DEFAULT_HEADER_TABLE_SIZE as u32,
+            enable_push: true,
+            max_concurrent_streams: None,
+            initial_window_size: DEFAULT_INITIAL_WINDOW_SIZE,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            max_header_list_size: None,
+            enable_connect_protocol: false,
+        }
+    }
+
+    /// Apply the parameters of a SETTINGS frame.
+    ///
+    /// Parameters are applied in order, so the last value of a parameter
+    /// sent several times wins. The frame is rejected as a whole, leaving
+    /// the settings unchanged, if any value is illegal:
+    ///
+    /// * SETTINGS_ENABLE_PUSH other than 0 or 1 is a PROTOCOL_ERROR,
+    /// * SETTINGS_INITIAL_WINDOW_SIZE above 2^31-1 is a FLOW_CONTROL_ERROR,
+    /// * SETTINGS_MAX_FRAME_SIZE outside of 2^14 to 2^24-1 is a
+    ///   PROTOCOL_ERROR,
+    /// * SETTINGS_ENABLE_CONNECT_PROTOCOL other than 0 or 1, or going back
+    ///   from 1 to 0, is a PROTOCOL_ERROR.
+    ///
+    /// Acknowledgements carry no parameters and leave the settings
+    /// unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings_frame` - The received SETTINGS frame.
+    pub fn apply(&mut self, settings_frame: &SettingsFrame) -> Result<(), Http2Error> {
+        for settings_parameter in settings_frame.settings_parameters() {
+            check(settings_parameter)?;
+            if self.enable_connect_protocol
+                && *settings_parameter == SettingsParameter::EnableConnectProtocol(0)
+            {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::ProtocolError,
+                    "SETTINGS_ENABLE_CONNECT_PROTOCOL cannot be disabled".to_string(),
+                ));
+            }
+        }
+
+        for settings_parameter in settings_frame.settings_parameters() {
+            match *settings_parameter {
+                SettingsParameter::HeaderTableSize(value) => self.header_table_size = value,
+                SettingsParameter::EnablePush(value) => self.enable_push = value == 1,
+                SettingsParameter::MaxConcurrentStreams(value) => {
+                    self.max_concurrent_streams = Some(value)
+                }
+                SettingsParameter::InitialWindowSize(value) => self.initial_window_size = value,
+                SettingsParameter::MaxFrameSize(value) => self.max_frame_size = value,
+                SettingsParameter::MaxHeaderListSize(value) => {
+                    self.max_header_list_size = Some(value)
+                }
+                SettingsParameter::EnableConnectProtocol(value) => {
+                    self.enable_connect_protocol = value == 1
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Get the maximum size of the HPACK decoding table of the endpoint,
+    /// 4,096 octets initially.
+    pub fn header_table_size(&self) -> u32 {
+        self.header_table_size
+    }
+
+    /// Check if server push is permitted, `true` initially.
+    pub fn enable_push(&self) -> bool {
+        self.enable_push
+    }
+
+    /// Get the maximum number of streams the endpoint accepts, unlimited
+    /// (`None`) initially.
+    pub fn max_concurrent_streams(&self) -> Option<u32> {
+        self.max_concurrent_streams
+    }
+
+    /// Get the initial flow-control window of the streams, 65,535 octets
+    /// initially.
+    pub fn initial_window_size(&self) -> u32 {
+        self.initial_window_size
+    }
+
+    /// Get the largest frame payload the endpoint accepts, 16,384 octets
+    /// initially.
+    pub fn max_frame_size(&self) -> u32 {
+        self.max_frame_size
+    }
+
+    /// Get the largest header list the endpoint accepts, unlimited (`None`)
+    /// initially.
+    pub fn max_header_list_size(&self) -> Option<u32> {
+        self.max_header_list_size
+    }
+
+    /// Check if extended CONNECT is permitted, `false` initially.
+    pub fn enable_connect_protocol(&self) -> bool {
+        self.enable_connect_protocol
+    }
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings::new()
+    }
+}
+
+/// Check that the value of a SETTINGS parameter is legal.
+fn check(settings_parameter: &SettingsParameter) -> Result<(), Http2Error> {
+    match *settings_parameter {
+        SettingsParameter::EnablePush(value) if value > 1 => Err(Http2Error::ConnectionError(
+            ErrorCode::ProtocolError,
+            format!("Invalid SETTINGS_ENABLE_PUSH value {}", value),
+        )),
+        SettingsParameter::EnableConnectProtocol(value) if value > 1 => {
+            Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!("Invalid SETTINGS_ENABLE_CONNECT_PROTOCOL value {}", value),
+            ))
+        }
+        SettingsParameter::InitialWindowSize(value) if value > MAX_WINDOW_SIZE => {
+            Err(Http2Error::ConnectionError(
+                ErrorCode::FlowControlError,
+                format!("Invalid SETTINGS_INITIAL_WINDOW_SIZE value {}", value),
+            ))
+        }
+        SettingsParameter::MaxFrameSize(value)
+            if !(DEFAULT_MAX_FRAME_SIZE..=MAX_FRAME_SIZE_UPPER_BOUND).contains(&value) =>
+        {
+            Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!("Invalid SETTINGS_MAX_FRAME_SIZE value {}", value),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
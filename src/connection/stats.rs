@@ -0,0 +1,177 @@
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+use crate::debug::frame_type_name;
+use crate::header::stats::HpackStats;
+
+/// Snapshot of the statistics of a connection.
+///
+/// Counters are accumulated over the lifetime of the connection, other
+/// values reflect the state of the connection when the snapshot was taken.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ConnectionStats {
+    frames_sent: BTreeMap<&'static str, u64>,
+    frames_received: BTreeMap<&'static str, u64>,
+    bytes_sent: u64,
+    bytes_received: u64,
+    local_active_streams: u32,
+    remote_active_streams: u32,
+    encoder_table_size: usize,
+    decoder_table_size: usize,
+    send_window: i64,
+    recv_window: i64,
+    ping_rtt: Option<Duration>,
+    encoder_hpack_stats: HpackStats,
+    decoder_hpack_stats: HpackStats,
+}
+
+impl ConnectionStats {
+    /// Count a frame sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_type` - The type of the frame.
+    pub(crate) fn record_frame_sent(&mut self, frame_type: u8) {
+        *self
+            .frames_sent
+            .entry(frame_type_name(frame_type))
+            .or_default() += 1;
+    }
+
+    /// Count bytes written to the transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The number of bytes written.
+    pub(crate) fn record_bytes_sent(&mut self, length: usize) {
+        self.bytes_sent += length as u64;
+    }
+
+    /// Count a frame received.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_type` - The type of the frame.
+    pub(crate) fn record_frame_received(&mut self, frame_type: u8) {
+        *self
+            .frames_received
+            .entry(frame_type_name(frame_type))
+            .or_default() += 1;
+    }
+
+    /// Count bytes read from the transport.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The number of bytes read.
+    pub(crate) fn record_bytes_received(&mut self, length: usize) {
+        self.bytes_received += length as u64;
+    }
+
+    /// Record the round-trip time measured by a PING acknowledgement.
+    ///
+    /// # Arguments
+    ///
+    /// * `ping_rtt` - The measured round-trip time.
+    pub(crate) fn record_ping_rtt(&mut self, ping_rtt: Duration) {
+        self.ping_rtt = Some(ping_rtt);
+    }
+
+    /// Fill the values describing the current state of the connection.
+    pub(crate) fn set_state(
+        &mut self,
+        local_active_streams: u32,
+        remote_active_streams: u32,
+        encoder_table_size: usize,
+        decoder_table_size: usize,
+        send_window: i64,
+        recv_window: i64,
+    ) {
+        self.local_active_streams = local_active_streams;
+        self.remote_active_streams = remote_active_streams;
+        self.encoder_table_size = encoder_table_size;
+        self.decoder_table_size = decoder_table_size;
+        self.send_window = send_window;
+        self.recv_window = recv_window;
+    }
+
+    /// Fill the HPACK statistics of the header tables.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder_hpack_stats` - The statistics of the encoding table.
+    /// * `decoder_hpack_stats` - The statistics of the decoding table.
+    pub(crate) fn set_hpack_stats(
+        &mut self,
+        encoder_hpack_stats: HpackStats,
+        decoder_hpack_stats: HpackStats,
+    ) {
+        self.encoder_hpack_stats = encoder_hpack_stats;
+        self.decoder_hpack_stats = decoder_hpack_stats;
+    }
+
+    /// Number of frames sent, by frame type name.
+    pub fn frames_sent(&self) -> &BTreeMap<&'static str, u64> {
+        &self.frames_sent
+    }
+
+    /// Number of frames received, by frame type name.
+    pub fn frames_received(&self) -> &BTreeMap<&'static str, u64> {
+        &self.frames_received
+    }
+
+    /// Number of bytes written to the transport.
+    pub fn bytes_sent(&self) -> u64 {
+        self.bytes_sent
+    }
+
+    /// Number of bytes read from the transport.
+    pub fn bytes_received(&self) -> u64 {
+        self.bytes_received
+    }
+
+    /// Number of active streams initiated by the local endpoint.
+    pub fn local_active_streams(&self) -> u32 {
+        self.local_active_streams
+    }
+
+    /// Number of active streams initiated by the peer.
+    pub fn remote_active_streams(&self) -> u32 {
+        self.remote_active_streams
+    }
+
+    /// Size of the HPACK dynamic table used to encode sent header lists.
+    pub fn encoder_table_size(&self) -> usize {
+        self.encoder_table_size
+    }
+
+    /// Size of the HPACK dynamic table used to decode received header lists.
+    pub fn decoder_table_size(&self) -> usize {
+        self.decoder_table_size
+    }
+
+    /// Connection flow-control window for sending DATA.
+    pub fn send_window(&self) -> i64 {
+        self.send_window
+    }
+
+    /// Connection flow-control window for receiving DATA.
+    pub fn recv_window(&self) -> i64 {
+        self.recv_window
+    }
+
+    /// Round-trip time measured by the last acknowledged PING, if any.
+    pub fn ping_rtt(&self) -> Option<Duration> {
+        self.ping_rtt
+    }
+
+    /// HPACK statistics of the header lists sent.
+    pub fn encoder_hpack_stats(&self) -> HpackStats {
+        self.encoder_hpack_stats
+    }
+
+    /// HPACK statistics of the header lists received.
+    pub fn decoder_hpack_stats(&self) -> HpackStats {
+        self.decoder_hpack_stats
+    }
+}
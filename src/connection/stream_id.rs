@@ -0,0 +1,117 @@
+use crate::error::{ErrorCode, Http2Error};
+pub use crate::frame::MAX_STREAM_ID;
+use crate::stream::Role;
+
+/// Stream identifier allocator of a connection.
+///
+/// Streams initiated by the local endpoint are given increasing
+/// identifiers of the parity of its role: odd identifiers for a client,
+/// even identifiers for a server. Streams initiated by the peer MUST use
+/// identifiers greater than all the streams it previously opened.
+///
+/// Stream identifiers cannot be reused: once the local identifiers are
+/// exhausted, a new connection is required to initiate new streams.
+#[derive(Debug)]
+pub struct StreamIdGenerator {
+    role: Role,
+    next_stream_id: Option<u32>,
+    last_remote_stream_id: u32,
+}
+
+impl StreamIdGenerator {
+    /// Create a new stream identifier allocator.
+    ///
+    /// The first local stream is 1 for a client and 2 for a server.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The role of the local endpoint.
+    pub fn new(role: Role) -> Self {
+        let next_stream_id = match role {
+            Role::Client => 1,
+            Role::Server => 2,
+        };
+
+        StreamIdGenerator::with_next_stream_id(role, next_stream_id)
+    }
+
+    /// Create a stream identifier allocator starting at a given identifier.
+    ///
+    /// A client upgrading from HTTP/1.1 starts at stream 3, stream 1 being
+    /// used by the upgrade request.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The role of the local endpoint.
+    /// * `next_stream_id` - The identifier of the next local stream.
+    pub fn with_next_stream_id(role: Role, next_stream_id: u32) -> Self {
+        StreamIdGenerator {
+            role,
+            next_stream_id: (role.initiates(next_stream_id) && next_stream_id <= MAX_STREAM_ID)
+                .then_some(next_stream_id),
+            last_remote_stream_id: 0,
+        }
+    }
+
+    /// Allocate the identifier of a new local stream.
+    ///
+    /// Fails with a connection error of type NO_ERROR once the stream
+    /// identifiers are exhausted: the connection should be gracefully shut
+    /// down with a GOAWAY frame and a new connection established.
+    pub fn next_stream_id(&mut self) -> Result<u32, Http2Error> {
+        let stream_id = self.next_stream_id.ok_or_else(|| {
+            Http2Error::ConnectionError(
+                ErrorCode::NoError,
+                "Stream identifiers exhausted, a new connection is required".to_string(),
+            )
+        })?;
+        self.next_stream_id = stream_id
+            .checked_add(2)
+            .filter(|next_stream_id| *next_stream_id <= MAX_STREAM_ID);
+
+        Ok(stream_id)
+    }
+
+    /// Record a stream initiated by the peer.
+    ///
+    /// Fails with a connection error of type PROTOCOL_ERROR if the stream
+    /// identifier does not have the parity of the peer role, or if it is
+    /// not greater than the identifiers of the streams the peer previously
+    /// opened.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The identifier of the new peer stream.
+    pub fn recv_stream_id(&mut self, stream_id: u32) -> Result<(), Http2Error> {
+        if stream_id == 0 || self.role.initiates(stream_id) {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!("Stream {} cannot be initiated by the peer", stream_id),
+            ));
+        }
+
+        if stream_id <= self.last_remote_stream_id {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!(
+                    "Stream {} opened after stream {}",
+                    stream_id, self.last_remote_stream_id
+                ),
+            ));
+        }
+
+        self.last_remote_stream_id = stream_id;
+        Ok(())
+    }
+
+    /// Check if the local stream identifiers are exhausted.
+    pub fn is_exhausted(&self) -> bool {
+        self.next_stream_id.is_none()
+    }
+
+    /// Get the identifier of the last stream initiated by the peer, 0 if
+    /// none, as reported in a GOAWAY frame.
+    pub fn last_remote_stream_id(&self) -> u32 {
+        self.last_remote_stream_id
+    }
+}
@@ -0,0 +1,325 @@
+use std::fmt::Write;
+
+use crate::error::ErrorCode;
+use crate::frame::settings::SettingsParameter;
+use crate::frame::{Frame, FrameHeader};
+use crate::header::table::{HeaderTable, DEFAULT_HEADER_TABLE_SIZE};
+use crate::start::HTTP2_CONNECTION_PREFACE_SEQUENCE;
+
+/// Size of the HTTP/2 frame header in octets.
+const FRAME_HEADER_LENGTH: usize = 9;
+
+/// Maximum number of payload bytes shown in a preview.
+const PREVIEW_LENGTH: usize = 64;
+
+/// Get the name of a frame type.
+///
+/// # Arguments
+///
+/// * `frame_type` - The frame type.
+pub fn frame_type_name(frame_type: u8) -> &'static str {
+    match frame_type {
+        0x0 => "DATA",
+        0x1 => "HEADERS",
+        0x2 => "PRIORITY",
+        0x3 => "RST_STREAM",
+        0x4 => "SETTINGS",
+        0x5 => "PUSH_PROMISE",
+        0x6 => "PING",
+        0x7 => "GOAWAY",
+        0x8 => "WINDOW_UPDATE",
+        0x9 => "CONTINUATION",
+        _ => "UNKNOWN",
+    }
+}
+
+/// Get the names of the flags set on a frame.
+///
+/// Flags are frame type specific, flags that are not defined for the frame
+/// type are shown as their hexadecimal value.
+///
+/// # Arguments
+///
+/// * `frame_type` - The frame type.
+/// * `frame_flags` - The flags byte of the frame.
+pub fn frame_flag_names(frame_type: u8, frame_flags: u8) -> Vec<String> {
+    let defined: &[(u8, &str)] = match frame_type {
+        0x0 => &[(0x01, "END_STREAM"), (0x08, "PADDED")],
+        0x1 => &[
+            (0x01, "END_STREAM"),
+            (0x04, "END_HEADERS"),
+            (0x08, "PADDED"),
+            (0x20, "PRIORITY"),
+        ],
+        0x4 | 0x6 => &[(0x01, "ACK")],
+        0x5 => &[(0x04, "END_HEADERS"), (0x08, "PADDED")],
+        0x9 => &[(0x04, "END_HEADERS")],
+        _ => &[],
+    };
+
+    (0..8)
+        .map(|bit| 1u8 << bit)
+        .filter(|flag| frame_flags & flag != 0)
+        .map(|flag| {
+            defined
+                .iter()
+                .find(|(value, _)| *value == flag)
+                .map_or_else(|| format!("0x{:02x}", flag), |(_, name)| name.to_string())
+        })
+        .collect()
+}
+
+/// Dump a raw HTTP/2 byte stream in a human readable form.
+///
+/// The stream is parsed as consecutive frames, optionally preceded by the
+/// client connection preface. Every frame is shown with its offset in the
+/// stream, its type, stream identifier, a marker if the reserved bit is
+/// set, its length and flag names, followed by its decoded content: header
+/// lists for HEADERS, PUSH_PROMISE and CONTINUATION frames, parameters for
+/// SETTINGS frames, error codes, and a hexadecimal preview of opaque
+/// payloads.
+///
+/// Header blocks are decoded with a single HPACK decoding context, so the
+/// stream should contain the frames sent in one direction only. Parsing
+/// stops at the first truncated frame.
+///
+/// # Arguments
+///
+/// * `bytes` - The captured byte stream.
+pub fn dump_frames(bytes: &[u8]) -> String {
+    let mut output = String::new();
+    let mut header_table = HeaderTable::new(DEFAULT_HEADER_TABLE_SIZE);
+    let mut offset = 0;
+
+    if bytes.starts_with(HTTP2_CONNECTION_PREFACE_SEQUENCE) {
+        let _ = writeln!(output, "{:08x}  PREFACE", offset);
+        offset += HTTP2_CONNECTION_PREFACE_SEQUENCE.len();
+    }
+
+    while offset < bytes.len() {
+        let _ = dump_frame(&mut output, bytes, &mut offset, &mut header_table);
+    }
+
+    output
+}
+
+/// Dump the frame starting at an offset and move the offset after it.
+///
+/// On a truncated frame, the offset is moved to the end of the bytes.
+fn dump_frame(
+    output: &mut String,
+    bytes: &[u8],
+    offset: &mut usize,
+    header_table: &mut HeaderTable,
+) -> std::fmt::Result {
+    let remaining = &bytes[*offset..];
+
+    if remaining.len() < FRAME_HEADER_LENGTH {
+        writeln!(
+            output,
+            "{:08x}  truncated frame header: {} bytes",
+            offset,
+            remaining.len()
+        )?;
+        *offset = bytes.len();
+        return Ok(());
+    }
+
+    let frame_header =
+        match FrameHeader::deserialize(&mut remaining[..FRAME_HEADER_LENGTH].to_vec()) {
+            Ok(frame_header) => frame_header,
+            Err(error) => {
+                writeln!(output, "{:08x}  invalid frame header: {}", offset, error)?;
+                *offset = bytes.len();
+                return Ok(());
+            }
+        };
+
+    writeln!(
+        output,
+        "{:08x}  {} stream={}{} length={} flags=0x{:02x} [{}]",
+        offset,
+        frame_type_name(frame_header.frame_type()),
+        frame_header.stream_id(),
+        if frame_header.reserved() {
+            " reserved"
+        } else {
+            ""
+        },
+        frame_header.payload_length(),
+        frame_header.frame_flags(),
+        frame_flag_names(frame_header.frame_type(), frame_header.frame_flags()).join(", ")
+    )?;
+
+    let length = frame_header.payload_length() as usize;
+    if remaining.len() < FRAME_HEADER_LENGTH + length {
+        writeln!(
+            output,
+            "          truncated payload: {} of {} bytes",
+            remaining.len() - FRAME_HEADER_LENGTH,
+            length
+        )?;
+        *offset = bytes.len();
+        return Ok(());
+    }
+
+    let payload = &remaining[FRAME_HEADER_LENGTH..FRAME_HEADER_LENGTH + length];
+    dump_payload(output, &frame_header, payload, header_table)?;
+    *offset += FRAME_HEADER_LENGTH + length;
+
+    Ok(())
+}
+
+/// Dump the payload of a frame.
+fn dump_payload(
+    output: &mut String,
+    frame_header: &FrameHeader,
+    payload: &[u8],
+    header_table: &mut HeaderTable,
+) -> std::fmt::Result {
+    match frame_header.frame_type() {
+        // DATA
+        0x0 => {
+            let data = match strip_padding(frame_header, payload) {
+                Some(data) => data,
+                None => return writeln!(output, "          invalid padding"),
+            };
+            dump_preview(output, data)
+        }
+        // HEADERS, PUSH_PROMISE, CONTINUATION
+        0x1 | 0x5 | 0x9 => {
+            let frame =
+                Frame::deserialize_payload(frame_header, &mut payload.to_vec(), header_table);
+            let header_list = match &frame {
+                Ok(Frame::Headers(frame)) => {
+                    if let Some(frame_priority) = frame.frame_priority() {
+                        writeln!(
+                            output,
+                            "          exclusive={} dependency={} weight={}",
+                            frame_priority.exclusive(),
+                            frame_priority.stream_dependency(),
+                            frame_priority.weight()
+                        )?;
+                    }
+                    frame.header_list()
+                }
+                Ok(Frame::PushPromise(frame)) => {
+                    writeln!(
+                        output,
+                        "          promised stream={}",
+                        frame.promised_stream_id()
+                    )?;
+                    frame.header_list()
+                }
+                Ok(Frame::Continuation(frame)) => frame.header_list(),
+                Ok(_) => return Ok(()),
+                Err(error) => return writeln!(output, "          error: {}", error),
+            };
+
+            for line in header_list.to_string().lines() {
+                writeln!(output, "          {}", line)?;
+            }
+
+            Ok(())
+        }
+        // PRIORITY
+        0x2 if payload.len() == 5 => writeln!(
+            output,
+            "          exclusive={} dependency={} weight={}",
+            payload[0] & 0x80 != 0,
+            u32::from_be_bytes([payload[0] & 0x7F, payload[1], payload[2], payload[3]]),
+            payload[4]
+        ),
+        // RST_STREAM
+        0x3 if payload.len() == 4 => {
+            let error_code = u32::from_be_bytes([payload[0], payload[1], payload[2], payload[3]]);
+            writeln!(output, "          error={}", ErrorCode::from(error_code))
+        }
+        // SETTINGS
+        0x4 if payload.len().is_multiple_of(6) => {
+            for parameter in payload.chunks(6) {
+                let parameter_id = u16::from_be_bytes([parameter[0], parameter[1]]);
+                let parameter_value =
+                    u32::from_be_bytes([parameter[2], parameter[3], parameter[4], parameter[5]]);
+                match SettingsParameter::deserialize(parameter_id, parameter_value) {
+                    Ok(settings_parameter) => writeln!(output, "          {}", settings_parameter)?,
+                    Err(_) => writeln!(
+                        output,
+                        "          Unknown (0x{:04x}): {}",
+                        parameter_id, parameter_value
+                    )?,
+                }
+            }
+            Ok(())
+        }
+        // PING
+        0x6 if payload.len() == 8 => dump_preview(output, payload),
+        // GOAWAY
+        0x7 if payload.len() >= 8 => {
+            writeln!(
+                output,
+                "          last stream={} error={}",
+                u32::from_be_bytes([payload[0] & 0x7F, payload[1], payload[2], payload[3]]),
+                ErrorCode::from(u32::from_be_bytes([
+                    payload[4], payload[5], payload[6], payload[7]
+                ]))
+            )?;
+            dump_preview(output, &payload[8..])
+        }
+        // WINDOW_UPDATE
+        0x8 if payload.len() == 4 => writeln!(
+            output,
+            "          increment={}",
+            u32::from_be_bytes([payload[0] & 0x7F, payload[1], payload[2], payload[3]])
+        ),
+        // Unknown frame types are ignored by receivers, show their payload.
+        frame_type if frame_type > 0x9 => dump_preview(output, payload),
+        _ => writeln!(output, "          invalid payload length"),
+    }
+}
+
+/// Remove the padding of a padded DATA frame payload.
+fn strip_padding<'a>(frame_header: &FrameHeader, payload: &'a [u8]) -> Option<&'a [u8]> {
+    if frame_header.frame_flags() & 0x08 == 0 {
+        return Some(payload);
+    }
+
+    let (pad_length, data) = payload.split_first()?;
+    data.len()
+        .checked_sub(*pad_length as usize)
+        .map(|length| &data[..length])
+}
+
+/// Dump a hexadecimal and ASCII preview of some bytes.
+fn dump_preview(output: &mut String, bytes: &[u8]) -> std::fmt::Result {
+    for (line, chunk) in bytes.chunks(16).take(PREVIEW_LENGTH / 16).enumerate() {
+        let hex: Vec<String> = chunk.iter().map(|byte| format!("{:02x}", byte)).collect();
+        let ascii: String = chunk
+            .iter()
+            .map(|&byte| {
+                if byte.is_ascii_graphic() || byte == b' ' {
+                    byte as char
+                } else {
+                    '.'
+                }
+            })
+            .collect();
+        writeln!(
+            output,
+            "          {:04x}  {:<47}  |{}|",
+            line * 16,
+            hex.join(" "),
+            ascii
+        )?;
+    }
+
+    if bytes.len() > PREVIEW_LENGTH {
+        writeln!(
+            output,
+            "          ... {} more bytes",
+            bytes.len() - PREVIEW_LENGTH
+        )?;
+    }
+
+    Ok(())
+}
@@ -1,14 +1,97 @@
 use std::fmt;
 
 /// An Error type for the HTTP2 library.
+///
+/// Every error maps to a HTTP/2 error code and is classified either as a
+/// stream error, which only affects one stream and is reported with a
+/// RST_STREAM frame, or as a connection error, which is reported with a
+/// GOAWAY frame before closing the connection. Usage errors, raised when
+/// the application misuses the API, are neither: nothing is reported to
+/// the peer.
 #[derive(Debug)]
+#[non_exhaustive]
 pub enum Http2Error {
+    /// A frame or a HPACK primitive is truncated (FRAME_SIZE_ERROR).
     NotEnoughBytes(String),
+    /// A frame is malformed (PROTOCOL_ERROR).
     FrameError(String),
-    HpackError(String),
-    HuffmanDecodingError(String),
+    /// A header field is malformed (PROTOCOL_ERROR). The connection reports
+    /// received malformed header lists as stream errors.
     HeaderError(String),
-    IndexationError(String),
+    /// A HTTP/1.1 upgrade to h2c failed (PROTOCOL_ERROR).
+    UpgradeError(String),
+    /// The TLS layer failed (INTERNAL_ERROR).
+    TlsError(String),
+    /// A connection error with its error code.
+    ConnectionError(ErrorCode, String),
+    /// A stream error with the stream identifier and its error code.
+    StreamError(u32, ErrorCode, String),
+    /// A header block cannot be encoded or decoded (COMPRESSION_ERROR).
+    CompressionError(CompressionError),
+    /// A flow-control failure, scoped to a stream or to the connection
+    /// (FLOW_CONTROL_ERROR).
+    FlowControlError(FlowControlError),
+    /// A timeout expired on a stream, which was cancelled (CANCEL).
+    StreamTimeout(u32, StreamTimeout),
+    /// A stream was not processed by the peer, which is shutting down the
+    /// connection with a GOAWAY frame: its request can safely be retried
+    /// on a new connection (REFUSED_STREAM).
+    StreamNotProcessed(u32),
+    /// The transport failed (INTERNAL_ERROR).
+    IoError(std::io::Error),
+    /// The application misused the API, such as a server sending a
+    /// request (INTERNAL_ERROR). Nothing is sent to the peer and the
+    /// connection remains usable.
+    UsageError(String),
+}
+
+impl Http2Error {
+    /// Get the HTTP/2 error code to report to the peer.
+    pub fn error_code(&self) -> ErrorCode {
+        match self {
+            Http2Error::NotEnoughBytes(_) => ErrorCode::FrameSizeError,
+            Http2Error::FrameError(_)
+            | Http2Error::HeaderError(_)
+            | Http2Error::UpgradeError(_) => ErrorCode::ProtocolError,
+            Http2Error::CompressionError(_) => ErrorCode::CompressionError,
+            Http2Error::TlsError(_) | Http2Error::IoError(_) | Http2Error::UsageError(_) => {
+                ErrorCode::InternalError
+            }
+            Http2Error::ConnectionError(error_code, _)
+            | Http2Error::StreamError(_, error_code, _) => *error_code,
+            Http2Error::FlowControlError(_) => ErrorCode::FlowControlError,
+            Http2Error::StreamTimeout(_, _) => ErrorCode::Cancel,
+            Http2Error::StreamNotProcessed(_) => ErrorCode::RefusedStream,
+        }
+    }
+
+    /// Get the identifier of the stream affected by a stream error.
+    pub fn stream_id(&self) -> Option<u32> {
+        match self {
+            Http2Error::StreamError(stream_id, _, _)
+            | Http2Error::StreamTimeout(stream_id, _)
+            | Http2Error::StreamNotProcessed(stream_id) => Some(*stream_id),
+            Http2Error::FlowControlError(error) => match error.scope() {
+                FlowControlScope::Stream(stream_id) => Some(stream_id),
+                FlowControlScope::Connection => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// Check if the error must terminate the connection.
+    ///
+    /// Any error that is not explicitly scoped to a stream is a connection
+    /// error: decoding failures leave the HPACK or framing state out of
+    /// sync with the peer. Usage errors leave the connection untouched.
+    pub fn is_connection_error(&self) -> bool {
+        self.stream_id().is_none() && !matches!(self, Http2Error::UsageError(_))
+    }
+
+    /// Check if the error only affects one stream.
+    pub fn is_stream_error(&self) -> bool {
+        self.stream_id().is_some()
+    }
 }
 
 impl fmt::Display for Http2Error {
@@ -17,18 +100,360 @@ impl fmt::Display for Http2Error {
         match self {
             Http2Error::NotEnoughBytes(message) => write!(f, "Not Enough Bytes: {}", message),
             Http2Error::FrameError(message) => write!(f, "Frame Error: {}", message),
-            Http2Error::HpackError(message) => write!(f, "Hpack Error: {}", message),
-            Http2Error::HuffmanDecodingError(message) => {
-                write!(f, "Huffman Decoding Error: {}", message)
-            }
             Http2Error::HeaderError(message) => {
                 write!(f, "Invalid Header Error: {}", message)
             }
-            Http2Error::IndexationError(message) => {
-                write!(f, "Indexation Error: {}", message)
+            Http2Error::UpgradeError(message) => write!(f, "Upgrade Error: {}", message),
+            Http2Error::TlsError(message) => write!(f, "TLS Error: {}", message),
+            Http2Error::ConnectionError(error_code, message) => {
+                write!(f, "Connection Error ({}): {}", error_code, message)
+            }
+            Http2Error::StreamError(stream_id, error_code, message) => {
+                write!(
+                    f,
+                    "Stream Error ({}) on stream {}: {}",
+                    error_code, stream_id, message
+                )
+            }
+            Http2Error::CompressionError(error) => {
+                write!(f, "Compression Error: {}", error)
+            }
+            Http2Error::FlowControlError(error) => write!(f, "Flow Control Error: {}", error),
+            Http2Error::StreamTimeout(stream_id, timeout) => {
+                write!(f, "Stream Timeout on stream {}: {}", stream_id, timeout)
             }
+            Http2Error::StreamNotProcessed(stream_id) => {
+                write!(
+                    f,
+                    "Stream Not Processed on stream {}: safe to retry",
+                    stream_id
+                )
+            }
+            Http2Error::IoError(error) => write!(f, "IO Error: {}", error),
+            Http2Error::UsageError(message) => write!(f, "Usage Error: {}", message),
         }
     }
 }
 
-impl std::error::Error for Http2Error {}
+impl std::error::Error for Http2Error {
+    /// Get the underlying error, if any.
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Http2Error::IoError(error) => Some(error),
+            Http2Error::CompressionError(error) => Some(error),
+            Http2Error::FlowControlError(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Http2Error {
+    /// Wrap an IO error into a Http2Error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The IO error to wrap.
+    fn from(error: std::io::Error) -> Self {
+        Http2Error::IoError(error)
+    }
+}
+
+impl From<CompressionError> for Http2Error {
+    /// Wrap a compression error into a Http2Error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The compression error to wrap.
+    fn from(error: CompressionError) -> Self {
+        Http2Error::CompressionError(error)
+    }
+}
+
+impl From<FlowControlError> for Http2Error {
+    /// Wrap a flow-control error into a Http2Error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The flow-control error to wrap.
+    fn from(error: FlowControlError) -> Self {
+        Http2Error::FlowControlError(error)
+    }
+}
+
+/// HPACK failures, decoding failures MUST be treated as a connection error
+/// of type COMPRESSION_ERROR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CompressionError {
+    /// A Huffman encoded string literal contains the EOS symbol.
+    HuffmanEos,
+    /// A Huffman encoded string literal is padded with more than 7 bits.
+    HuffmanPaddingTooLong,
+    /// A Huffman encoded string literal is padded with bits that do not
+    /// correspond to the most significant bits of the EOS code.
+    HuffmanInvalidPadding,
+    /// A code of the Huffman code table is empty or is the prefix of
+    /// another code.
+    HuffmanInvalidCode,
+    /// A header field representation references index 0, which is not
+    /// used by HPACK.
+    ZeroIndex,
+    /// A header field representation references an index beyond the
+    /// static and dynamic tables.
+    InvalidIndex(usize),
+    /// A HPACK integer prefix is not between 1 and 8 bits.
+    InvalidPrefixSize(u8),
+    /// A HPACK integer is truncated.
+    TruncatedInteger,
+    /// A HPACK integer overflows its representation.
+    IntegerOverflow,
+    /// A HPACK integer spans too many continuation octets.
+    IntegerTooLong,
+    /// A string literal is truncated.
+    TruncatedString,
+    /// The first octet of a header block does not start a header field
+    /// representation.
+    InvalidRepresentation,
+}
+
+impl fmt::Display for CompressionError {
+    /// Display a CompressionError.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CompressionError::HuffmanEos => {
+                write!(f, "Huffman encoded string contains the EOS symbol")
+            }
+            CompressionError::HuffmanPaddingTooLong => {
+                write!(f, "Huffman padding is longer than 7 bits")
+            }
+            CompressionError::HuffmanInvalidPadding => {
+                write!(f, "Huffman padding does not match the EOS prefix")
+            }
+            CompressionError::HuffmanInvalidCode => write!(f, "Huffman code table is invalid"),
+            CompressionError::ZeroIndex => write!(f, "Header table index 0 is invalid"),
+            CompressionError::InvalidIndex(index) => {
+                write!(f, "Header table index {} is out of bounds", index)
+            }
+            CompressionError::InvalidPrefixSize(n) => {
+                write!(f, "Invalid integer prefix size {}", n)
+            }
+            CompressionError::TruncatedInteger => write!(f, "Truncated integer"),
+            CompressionError::IntegerOverflow => write!(f, "Integer overflow"),
+            CompressionError::IntegerTooLong => write!(f, "Integer too long"),
+            CompressionError::TruncatedString => write!(f, "Truncated string literal"),
+            CompressionError::InvalidRepresentation => {
+                write!(f, "Invalid header field representation")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CompressionError {}
+
+/// Scope of a flow-control window, and of its failures.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FlowControlScope {
+    /// The connection window, failures are connection errors reported
+    /// with a GOAWAY frame.
+    Connection,
+    /// The window of a stream, failures are stream errors reported with a
+    /// RST_STREAM frame.
+    Stream(u32),
+}
+
+impl FlowControlScope {
+    /// Get the scope of the window of a stream, 0 for the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn of(stream_id: u32) -> Self {
+        match stream_id {
+            0 => FlowControlScope::Connection,
+            stream_id => FlowControlScope::Stream(stream_id),
+        }
+    }
+}
+
+/// Flow-control failures, of type FLOW_CONTROL_ERROR.
+///
+/// The scope of the failure decides how it is reported: failures of the
+/// connection window are connection errors, failures of a stream window
+/// are stream errors, except for the changes of SETTINGS_INITIAL_WINDOW_SIZE
+/// which are always connection errors.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FlowControlError {
+    /// A WINDOW_UPDATE increment makes the window exceed 2^31-1 octets,
+    /// with the window size and the increment.
+    WindowOverflow(FlowControlScope, i64, u32),
+    /// A DATA frame exceeds the window, with its length and the window
+    /// size.
+    WindowExceeded(FlowControlScope, u32, i64),
+    /// A change of SETTINGS_INITIAL_WINDOW_SIZE makes the window of a
+    /// stream exceed 2^31-1 octets.
+    InitialWindowSizeOverflow(u32),
+}
+
+impl FlowControlError {
+    /// Get the scope of the failure.
+    pub fn scope(&self) -> FlowControlScope {
+        match self {
+            FlowControlError::WindowOverflow(scope, _, _)
+            | FlowControlError::WindowExceeded(scope, _, _) => *scope,
+            FlowControlError::InitialWindowSizeOverflow(_) => FlowControlScope::Connection,
+        }
+    }
+}
+
+impl fmt::Display for FlowControlError {
+    /// Display a FlowControlError.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            FlowControlError::WindowOverflow(_, size, increment) => write!(
+                f,
+                "Flow-control window overflow: {} + {} exceeds {}",
+                size,
+                increment,
+                i32::MAX
+            ),
+            FlowControlError::WindowExceeded(_, length, size) => write!(
+                f,
+                "Flow-control window exceeded: {} octets for a window of {}",
+                length, size
+            ),
+            FlowControlError::InitialWindowSizeOverflow(stream_id) => write!(
+                f,
+                "SETTINGS_INITIAL_WINDOW_SIZE change overflows the window of stream {}",
+                stream_id
+            ),
+        }
+    }
+}
+
+impl std::error::Error for FlowControlError {}
+
+/// Timeout expired on a stream.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum StreamTimeout {
+    /// The header section of the peer message was not received in time.
+    Headers,
+    /// The peer sent no content for too long.
+    BodyIdle,
+    /// The stream was not closed in time.
+    Total,
+}
+
+impl fmt::Display for StreamTimeout {
+    /// Display a StreamTimeout.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StreamTimeout::Headers => write!(f, "header timeout"),
+            StreamTimeout::BodyIdle => write!(f, "body idle timeout"),
+            StreamTimeout::Total => write!(f, "stream timeout"),
+        }
+    }
+}
+
+/// HTTP/2 error codes.
+///
+/// Error codes are 32-bit fields that are used in RST_STREAM and GOAWAY
+/// frames to convey the reasons for the stream or connection error.
+///
+/// Unknown or unsupported error codes MUST NOT trigger any special
+/// behavior. These MAY be treated by an implementation as being
+/// equivalent to INTERNAL_ERROR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum ErrorCode {
+    NoError,
+    ProtocolError,
+    InternalError,
+    FlowControlError,
+    SettingsTimeout,
+    StreamClosed,
+    FrameSizeError,
+    RefusedStream,
+    Cancel,
+    CompressionError,
+    ConnectError,
+    EnhanceYourCalm,
+    InadequateSecurity,
+    Http11Required,
+}
+
+impl From<u32> for ErrorCode {
+    /// Build an error code from its 32-bit representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The 32-bit error code.
+    fn from(code: u32) -> Self {
+        match code {
+            0x0 => ErrorCode::NoError,
+            0x1 => ErrorCode::ProtocolError,
+            0x3 => ErrorCode::FlowControlError,
+            0x4 => ErrorCode::SettingsTimeout,
+            0x5 => ErrorCode::StreamClosed,
+            0x6 => ErrorCode::FrameSizeError,
+            0x7 => ErrorCode::RefusedStream,
+            0x8 => ErrorCode::Cancel,
+            0x9 => ErrorCode::CompressionError,
+            0xa => ErrorCode::ConnectError,
+            0xb => ErrorCode::EnhanceYourCalm,
+            0xc => ErrorCode::InadequateSecurity,
+            0xd => ErrorCode::Http11Required,
+            _ => ErrorCode::InternalError,
+        }
+    }
+}
+
+impl From<ErrorCode> for u32 {
+    /// Convert an error code into its 32-bit representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `error_code` - The error code to convert.
+    fn from(error_code: ErrorCode) -> Self {
+        match error_code {
+            ErrorCode::NoError => 0x0,
+            ErrorCode::ProtocolError => 0x1,
+            ErrorCode::InternalError => 0x2,
+            ErrorCode::FlowControlError => 0x3,
+            ErrorCode::SettingsTimeout => 0x4,
+            ErrorCode::StreamClosed => 0x5,
+            ErrorCode::FrameSizeError => 0x6,
+            ErrorCode::RefusedStream => 0x7,
+            ErrorCode::Cancel => 0x8,
+            ErrorCode::CompressionError => 0x9,
+            ErrorCode::ConnectError => 0xa,
+            ErrorCode::EnhanceYourCalm => 0xb,
+            ErrorCode::InadequateSecurity => 0xc,
+            ErrorCode::Http11Required => 0xd,
+        }
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    /// Format an error code using its RFC name.
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let name = match self {
+            ErrorCode::NoError => "NO_ERROR",
+            ErrorCode::ProtocolError => "PROTOCOL_ERROR",
+            ErrorCode::InternalError => "INTERNAL_ERROR",
+            ErrorCode::FlowControlError => "FLOW_CONTROL_ERROR",
+            ErrorCode::SettingsTimeout => "SETTINGS_TIMEOUT",
+            ErrorCode::StreamClosed => "STREAM_CLOSED",
+            ErrorCode::FrameSizeError => "FRAME_SIZE_ERROR",
+            ErrorCode::RefusedStream => "REFUSED_STREAM",
+            ErrorCode::Cancel => "CANCEL",
+            ErrorCode::CompressionError => "COMPRESSION_ERROR",
+            ErrorCode::ConnectError => "CONNECT_ERROR",
+            ErrorCode::EnhanceYourCalm => "ENHANCE_YOUR_CALM",
+            ErrorCode::InadequateSecurity => "INADEQUATE_SECURITY",
+            ErrorCode::Http11Required => "HTTP_1_1_REQUIRED",
+        };
+        write!(f, "{}", name)
+    }
+}
@@ -0,0 +1,398 @@
+use crate::connection::settings::Settings;
+use crate::error::{ErrorCode, FlowControlError, FlowControlScope, Http2Error};
+use crate::frame::ping::PingFrame;
+use crate::frame::window_update::WindowUpdateFrame;
+
+/// Initial value of SETTINGS_INITIAL_WINDOW_SIZE (2^16-1 octets).
+pub const DEFAULT_INITIAL_WINDOW_SIZE: u32 = 65_535;
+
+/// Maximum size of a flow-control window (2^31-1 octets).
+pub const MAX_WINDOW_SIZE: u32 = 2_147_483_647;
+
+/// Largest receive window reached by bandwidth-delay product estimation
+/// (16 MiB).
+pub const MAX_BDP_WINDOW_SIZE: u32 = 16_777_216;
+
+/// Opaque data of the PING frames probing the bandwidth-delay product.
+pub(crate) const BDP_PING_OPAQUE_DATA: [u8; 8] = *b"h2bdpest";
+
+/// HTTP/2 flow-control window.
+///
+/// A flow-control window is maintained for the connection (stream 0) and
+/// for each stream. The window is decreased when DATA frames are sent or
+/// received and increased by WINDOW_UPDATE frames.
+///
+/// The window of a stream starts at the SETTINGS_INITIAL_WINDOW_SIZE of the
+/// receiver, see [`FlowControlWindow::for_stream`], and follows its changes
+/// for the lifetime of the stream, see [`update_initial_window_size`]. A
+/// window can become negative when SETTINGS_INITIAL_WINDOW_SIZE is
+/// reduced, hence the signed size.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct FlowControlWindow {
+    stream_id: u32,
+    size: i64,
+}
+
+impl FlowControlWindow {
+    /// Create a new flow-control window.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier, 0 for the connection.
+    /// * `initial_size` - The initial size of the window.
+    pub fn new(stream_id: u32, initial_size: u32) -> Self {
+        FlowControlWindow {
+            stream_id,
+            size: initial_size as i64,
+        }
+    }
+
+    /// Create the flow-control window of a new stream.
+    ///
+    /// The window starts at the SETTINGS_INITIAL_WINDOW_SIZE of the
+    /// receiver of the DATA frames: the settings of the peer for a send
+    /// window, the local settings acknowledged by the peer for a receive
+    /// window, 65,535 octets if the parameter was never sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `receiver_settings` - The settings of the receiver.
+    pub fn for_stream(stream_id: u32, receiver_settings: &Settings) -> Self {
+        FlowControlWindow::new(stream_id, receiver_settings.initial_window_size())
+    }
+
+    /// Increase the window by a given increment.
+    ///
+    /// A sender MUST NOT allow a flow-control window to exceed 2^31-1
+    /// octets. Overflowing the connection window is a connection error of
+    /// type FLOW_CONTROL_ERROR, overflowing a stream window is a stream
+    /// error of the same type.
+    ///
+    /// # Arguments
+    ///
+    /// * `increment` - The number of octets to add to the window.
+    pub fn increase(&mut self, increment: u32) -> Result<(), Http2Error> {
+        let size = self.size + increment as i64;
+
+        if size > MAX_WINDOW_SIZE as i64 {
+            return Err(Http2Error::FlowControlError(
+                FlowControlError::WindowOverflow(self.scope(), self.size, increment),
+            ));
+        }
+
+        self.size = size;
+        Ok(())
+    }
+
+    /// Decrease the window by the flow-controlled length of a DATA frame.
+    ///
+    /// Receiving more data than the window allows is a FLOW_CONTROL_ERROR.
+    /// Empty frames, such as a DATA frame only carrying END_STREAM, are
+    /// allowed even when the window is exhausted or negative.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The number of octets to remove from the window.
+    pub fn consume(&mut self, length: u32) -> Result<(), Http2Error> {
+        if length > 0 && length as i64 > self.size {
+            return Err(Http2Error::FlowControlError(
+                FlowControlError::WindowExceeded(self.scope(), length, self.size),
+            ));
+        }
+
+        self.size -= length as i64;
+        Ok(())
+    }
+
+    /// Apply a change of SETTINGS_INITIAL_WINDOW_SIZE to a stream window.
+    ///
+    /// The window is adjusted by the difference between the new and the
+    /// old initial size, and may become negative. Exceeding 2^31-1 octets
+    /// is a connection error of type FLOW_CONTROL_ERROR.
+    ///
+    /// # Arguments
+    ///
+    /// * `old_initial_size` - The previous initial window size.
+    /// * `new_initial_size` - The new initial window size.
+    pub fn update_initial_size(
+        &mut self,
+        old_initial_size: u32,
+        new_initial_size: u32,
+    ) -> Result<(), Http2Error> {
+        let size = self.size + new_initial_size as i64 - old_initial_size as i64;
+
+        if size > MAX_WINDOW_SIZE as i64 {
+            return Err(Http2Error::FlowControlError(
+                FlowControlError::InitialWindowSizeOverflow(self.stream_id),
+            ));
+        }
+
+        self.size = size;
+        Ok(())
+    }
+
+    /// Validate and apply a WINDOW_UPDATE frame to the window.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - A reference to the WINDOW_UPDATE frame.
+    pub fn apply_window_update(&mut self, frame: &WindowUpdateFrame) -> Result<(), Http2Error> {
+        if frame.stream_id() != self.stream_id {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::InternalError,
+                format!(
+                    "WINDOW_UPDATE for stream {} applied to the window of stream {}",
+                    frame.stream_id(),
+                    self.stream_id
+                ),
+            ));
+        }
+
+        frame.validate()?;
+        self.increase(frame.window_size_increment())
+    }
+
+    /// Get the scope of the window, the connection or a stream.
+    pub fn scope(&self) -> FlowControlScope {
+        FlowControlScope::of(self.stream_id)
+    }
+
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    pub fn size(&self) -> i64 {
+        self.size
+    }
+}
+
+/// Apply a change of SETTINGS_INITIAL_WINDOW_SIZE to the windows of the
+/// open streams.
+///
+/// Each window is adjusted by the difference between the new and the old
+/// initial size, and may become negative: the sender then waits for
+/// WINDOW_UPDATE frames until the window is positive again. The send
+/// windows follow the settings of the peer as soon as they are received,
+/// the receive windows follow the local settings once the peer
+/// acknowledges them, the peer having used the previous value until then.
+/// The connection window is left unchanged.
+///
+/// # Arguments
+///
+/// * `windows` - The stream flow-control windows.
+/// * `old_initial_size` - The previous initial window size.
+/// * `new_initial_size` - The new initial window size.
+pub fn update_initial_window_size<'a>(
+    windows: impl IntoIterator<Item = &'a mut FlowControlWindow>,
+    old_initial_size: u32,
+    new_initial_size: u32,
+) -> Result<(), Http2Error> {
+    if old_initial_size == new_initial_size {
+        return Ok(());
+    }
+
+    for window in windows {
+        window.update_initial_size(old_initial_size, new_initial_size)?;
+    }
+
+    Ok(())
+}
+
+/// Strategy deciding when WINDOW_UPDATE frames replenish a receive window.
+///
+/// Octets consumed from a window are replenished once the application has
+/// read them, in a single WINDOW_UPDATE frame or in several. Fewer, larger
+/// WINDOW_UPDATE frames save bandwidth and processing, at the risk of
+/// stalling the peer if the window is replenished too late.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum WindowUpdateStrategy {
+    /// The window is replenished as soon as octets are consumed.
+    #[default]
+    Immediate,
+    /// The window is replenished once the given percentage of its size is
+    /// consumed, e.g. 50 for half of the window, at most 100.
+    Threshold(u8),
+    /// The window is replenished by multiples of a fixed increment.
+    FixedIncrement(u32),
+    /// The window is replenished once half of it is consumed, and grown
+    /// to the bandwidth-delay product of the connection, estimated with
+    /// PING frames, up to [`MAX_BDP_WINDOW_SIZE`].
+    Bdp,
+}
+
+impl WindowUpdateStrategy {
+    /// Get the increment of the WINDOW_UPDATE frame to send, 0 to wait
+    /// for more octets to be consumed.
+    ///
+    /// # Arguments
+    ///
+    /// * `consumed` - The number of octets consumed and not replenished.
+    /// * `window_size` - The size of the window when fully replenished.
+    pub fn increment(&self, consumed: u32, window_size: u32) -> u32 {
+        let threshold = match *self {
+            WindowUpdateStrategy::Immediate => return consumed,
+            WindowUpdateStrategy::Threshold(percent) => percent.min(100),
+            WindowUpdateStrategy::FixedIncrement(0) => return consumed,
+            WindowUpdateStrategy::FixedIncrement(increment) => {
+                return consumed - consumed % increment
+            }
+            WindowUpdateStrategy::Bdp => 50,
+        };
+
+        if consumed as u64 * 100 >= window_size as u64 * threshold as u64 {
+            consumed
+        } else {
+            0
+        }
+    }
+}
+
+/// WINDOW_UPDATE frames waiting to be sent, coalesced per window.
+///
+/// A receiver consuming many small DATA frames would send a WINDOW_UPDATE
+/// frame for each of them. The increments are instead accumulated for the
+/// connection (stream 0) and for each stream until the output is flushed,
+/// which sends a single WINDOW_UPDATE frame per window.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct WindowUpdateCoalescer {
+    increments: Vec<(u32, u32)>,
+}
+
+impl WindowUpdateCoalescer {
+    /// Create a new coalescer without pending increments.
+    pub fn new() -> Self {
+        WindowUpdateCoalescer::default()
+    }
+
+    /// Add an increment to the pending WINDOW_UPDATE frame of a window.
+    ///
+    /// The pending increment is capped to 2^31-1 octets.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier, 0 for the connection.
+    /// * `increment` - The number of octets released.
+    pub fn add(&mut self, stream_id: u32, increment: u32) {
+        if increment == 0 {
+            return;
+        }
+
+        match self.increments.iter_mut().find(|(id, _)| *id == stream_id) {
+            Some((_, pending)) => *pending = pending.saturating_add(increment).min(MAX_WINDOW_SIZE),
+            None => self
+                .increments
+                .push((stream_id, increment.min(MAX_WINDOW_SIZE))),
+        }
+    }
+
+    /// Get the pending increment of a window.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier, 0 for the connection.
+    pub fn pending(&self, stream_id: u32) -> u32 {
+        self.increments
+            .iter()
+            .find_map(|&(id, increment)| (id == stream_id).then_some(increment))
+            .unwrap_or(0)
+    }
+
+    /// Discard the pending increment of a stream the peer can no longer
+    /// send on, e.g. once it is reset.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn remove(&mut self, stream_id: u32) {
+        self.increments.retain(|&(id, _)| id != stream_id);
+    }
+
+    /// Check if no WINDOW_UPDATE frame is pending.
+    pub fn is_empty(&self) -> bool {
+        self.increments.is_empty()
+    }
+
+    /// Take the pending WINDOW_UPDATE frames, one per window, in the order
+    /// of their first increment.
+    pub fn flush(&mut self) -> Vec<WindowUpdateFrame> {
+        self.increments
+            .drain(..)
+            .map(|(stream_id, increment)| WindowUpdateFrame::new(stream_id, increment))
+            .collect()
+    }
+}
+
+/// Bandwidth-delay product estimator of a connection.
+///
+/// A PING frame is sent along the first DATA frame received, and the
+/// octets received until it is acknowledged sample the bandwidth-delay
+/// product. When a sample nears the receive window, the window is too
+/// small to keep the link busy and is doubled.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BdpEstimator {
+    window_size: u32,
+    sample: Option<u32>,
+}
+
+impl BdpEstimator {
+    /// Create a new estimator.
+    ///
+    /// # Arguments
+    ///
+    /// * `window_size` - The initial size of the receive window.
+    pub fn new(window_size: u32) -> Self {
+        BdpEstimator {
+            window_size,
+            sample: None,
+        }
+    }
+
+    /// Account for a received DATA frame.
+    ///
+    /// Returns the PING frame to send if no sample is in progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The length of the DATA frame.
+    pub fn recv_data(&mut self, length: u32) -> Option<PingFrame> {
+        match &mut self.sample {
+            Some(sample) => {
+                *sample = sample.saturating_add(length);
+                None
+            }
+            None => {
+                self.sample = Some(length);
+                Some(PingFrame::new(BDP_PING_OPAQUE_DATA))
+            }
+        }
+    }
+
+    /// Complete a sample with a PING acknowledgement.
+    ///
+    /// Returns the new size of the receive window if it grows.
+    ///
+    /// # Arguments
+    ///
+    /// * `ping_frame` - The received PING acknowledgement.
+    pub fn recv_ping_ack(&mut self, ping_frame: &PingFrame) -> Option<u32> {
+        if *ping_frame.opaque_data() != BDP_PING_OPAQUE_DATA {
+            return None;
+        }
+
+        let sample = self.sample.take()?;
+        if (sample as u64) * 3 < (self.window_size as u64) * 2
+            || self.window_size >= MAX_BDP_WINDOW_SIZE
+        {
+            return None;
+        }
+
+        self.window_size = sample.saturating_mul(2).min(MAX_BDP_WINDOW_SIZE);
+        Some(self.window_size)
+    }
+
+    pub fn window_size(&self) -> u32 {
+        self.window_size
+    }
+}
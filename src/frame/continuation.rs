@@ -16,13 +16,112 @@ use crate::header::table::HeaderTable;
 /// +---------------------------------------------------------------+
 /// |                   Header Block Fragment (*)                 ...
 /// +---------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ContinuationFrame {
+    stream_id: u32,
     end_headers: bool,
     header_list: HeaderList,
+    header_block_fragment: Option<Vec<u8>>,
 }
 
 impl ContinuationFrame {
+    /// Create a new CONTINUATION frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `end_headers` - Whether the frame ends the header block.
+    /// * `header_list` - The header list to send.
+    pub fn new(stream_id: u32, end_headers: bool, header_list: HeaderList) -> Self {
+        ContinuationFrame {
+            stream_id,
+            end_headers,
+            header_list,
+            header_block_fragment: None,
+        }
+    }
+
+    /// Create the CONTINUATION frames carrying the rest of an encoded
+    /// header block, each within a maximum payload size.
+    ///
+    /// Only the last frame ends the header block. An empty header block
+    /// needs no CONTINUATION frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `header_block` - The rest of the encoded header block.
+    /// * `max_frame_size` - The maximum frame payload size of the peer.
+    pub fn from_header_block(
+        stream_id: u32,
+        header_block: &[u8],
+        max_frame_size: u32,
+    ) -> Vec<Self> {
+        let fragments: Vec<&[u8]> = header_block
+            .chunks((max_frame_size as usize).max(1))
+            .collect();
+        let count = fragments.len();
+
+        fragments
+            .into_iter()
+            .enumerate()
+            .map(|(index, fragment)| ContinuationFrame {
+                stream_id,
+                end_headers: index + 1 == count,
+                header_list: HeaderList::default(),
+                header_block_fragment: Some(fragment.to_vec()),
+            })
+            .collect()
+    }
+
+    /// Serialize a CONTINUATION frame.
+    ///
+    /// The header table is updated with the encoded header fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    pub fn serialize(&self, header_table: &mut HeaderTable) -> Result<Vec<u8>, Http2Error> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(header_table, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Serialize a CONTINUATION frame at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(
+        &self,
+        header_table: &mut HeaderTable,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Http2Error> {
+        let start = bytes.len();
+
+        // Build the flags bit.
+        let mut frame_flags: u8 = 0x0;
+        if self.end_headers {
+            frame_flags |= 0x04;
+        }
+
+        // Serialize the header, its payload length is written once the
+        // payload is.
+        FrameHeader::new(0, 0x9, frame_flags, false, self.stream_id).serialize_into(bytes);
+
+        // Serialize the payload.
+        match &self.header_block_fragment {
+            Some(header_block_fragment) => bytes.extend_from_slice(header_block_fragment),
+            None => self.header_list.encode_into(header_table, bytes)?,
+        }
+
+        FrameHeader::write_payload_length(&mut bytes[start..]);
+
+        Ok(())
+    }
+
     /// Deserialize the flags from a byte.
     /// 
     /// # Arguments
@@ -67,27 +166,87 @@ impl ContinuationFrame {
 
         // Retrieve the header list from the payload.
         *bytes = bytes[0..frame_header.payload_length() as usize].to_vec();
-        let header_list = HeaderList::decode(bytes, header_tables)?;
+        let (header_list, header_block_fragment) = match header_tables.pass_through() {
+            true => (HeaderList::default(), Some(bytes.clone())),
+            false => (HeaderList::decode(bytes, header_tables)?, None),
+        };
 
         Ok(ContinuationFrame {
+            stream_id: frame_header.stream_id(),
             end_headers: flags.contains(&FrameFlag::EndHeaders),
             header_list,
+            header_block_fragment,
         })
     }
+
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    pub fn end_headers(&self) -> bool {
+        self.end_headers
+    }
+
+    pub fn header_list(&self) -> &HeaderList {
+        &self.header_list
+    }
+
+    /// Get the header block fragment of a frame decoded with a
+    /// pass-through header table, until it is decoded.
+    pub fn header_block_fragment(&self) -> Option<&[u8]> {
+        self.header_block_fragment.as_deref()
+    }
+
+    /// Decode the header block fragment kept by a pass-through header
+    /// table into the header list of the frame.
+    ///
+    /// Header blocks MUST be decoded in the order they were received, with
+    /// the decoding table of the connection. The frame is left unchanged
+    /// if its header list is already decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    pub fn decode_header_block(
+        &mut self,
+        header_table: &mut HeaderTable,
+    ) -> Result<(), Http2Error> {
+        // The header block fragment is kept if it fails to decode.
+        if let Some(header_block_fragment) = &mut self.header_block_fragment {
+            self.header_list = HeaderList::decode(header_block_fragment, header_table)?;
+            self.header_block_fragment = None;
+        }
+
+        Ok(())
+    }
+
+    /// Get the flags of the frame, as they are serialized.
+    ///
+    /// The flags of a decoded frame are those it was received with.
+    pub fn flags(&self) -> Vec<FrameFlag> {
+        let mut frame_flags = Vec::new();
+
+        if self.end_headers {
+            frame_flags.push(FrameFlag::EndHeaders);
+        }
+
+        frame_flags
+    }
 }
 
 impl fmt::Display for ContinuationFrame {
-    /// Format a PING frame.
+    /// Format a CONTINUATION frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "CONTINUATION\n")?;
-        write!(f, "End Headers: {}\n", self.end_headers)?;
+        writeln!(f, "CONTINUATION")?;
+        writeln!(f, "Stream Identifier: {}", self.stream_id)?;
+        writeln!(f, "End Headers: {}", self.end_headers)?;
         write!(f, "Header List:\n{}", self.header_list)
     }
 }
 
-impl Into<Frame> for ContinuationFrame {
+impl From<ContinuationFrame> for Frame {
     /// Convert the CONTINUATION frame into a generic frame.
-    fn into(self) -> Frame {
-        Frame::Continuation(self)
+    fn from(frame: ContinuationFrame) -> Self {
+        Frame::Continuation(frame)
     }
 }
\ No newline at end of file
@@ -1,6 +1,7 @@
 use std::fmt;
 
 use crate::error::Http2Error;
+use crate::frame::padding::{self, Padding};
 use crate::frame::{FrameFlag, FrameHeader};
 
 /// DATA Frame.
@@ -20,11 +21,12 @@ use crate::frame::{FrameFlag, FrameHeader};
 /// +---------------------------------------------------------------+
 /// |                           Padding (*)                       ...
 /// +---------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct DataFrame {
     pub stream_id: u32,
     pub end_stream: bool,
     pub data: Vec<u8>,
+    pub pad_length: Option<u8>,
 }
 
 impl DataFrame {
@@ -40,59 +42,80 @@ impl DataFrame {
             stream_id,
             end_stream,
             data,
+            pad_length: None,
         }
     }
 
+    /// Create an empty DATA frame ending a stream.
+    ///
+    /// An empty DATA frame carries no content and only signals END_STREAM,
+    /// once the content of a message was sent in previous frames. It
+    /// consumes no flow-control window, so that it may be sent even when
+    /// the windows are exhausted.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn end_of_stream(stream_id: u32) -> Self {
+        Self::new(stream_id, true, Vec::new())
+    }
+
+    /// Get the flow-controlled length of the frame.
+    ///
+    /// The entire payload counts toward flow control: the Pad Length octet
+    /// and the padding of a padded frame count as well as its data. The
+    /// length is that of the frame as received, or as serialized without a
+    /// padding policy.
+    pub fn flow_controlled_length(&self) -> u32 {
+        let padding = self.pad_length.map_or(0, |pad_length| pad_length as u32 + 1);
+
+        self.data.len() as u32 + padding
+    }
+
     /// Serialize a DATA frame.
     /// 
-    /// Panic if the optional padding length is greater than 255.
-    /// 
     /// # Arguments
     /// 
-    /// * `padding` - An optional bytes padding with max length of 255.
-    pub fn serialize(&self, padding: Option<Vec<u8>>) -> Vec<u8> {
-        // Build the payload.
-        let mut payload: Vec<u8> = Vec::new();
-        match padding.clone() {
-            Some(padding) => {
-                // Panic if the padding length is greater than 255.
-                if padding.len() > 255 {
-                    panic!("Padding length greater than 255");
-                }
-
-                payload.push(padding.len() as u8);
-                payload.append(&mut self.data.clone());
-                payload.append(&mut padding.clone());
-            },
-            None => {
-                payload.append(&mut self.data.clone());
-            }
-        }
+    /// * `padding` - The padding policy of the frame.
+    pub fn serialize(&self, padding: Padding) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(padding, &mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a DATA frame at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `padding` - The padding policy of the frame.
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, padding: Padding, bytes: &mut Vec<u8>) {
+        let start = bytes.len();
+        let pad_length = match padding {
+            Padding::None => self.pad_length,
+            padding => padding.pad_length(),
+        };
 
         // Build the flags bit.
         let mut frame_flags: u8 = 0x0;
         if self.end_stream {
             frame_flags |= 0x01;
         }
-        if padding.clone().is_some() {
+        if pad_length.is_some() {
             frame_flags |= 0x08;
         }
 
-        // Build the header.
-        let header = FrameHeader::new(
-            payload.len() as u32,
-            0x0, 
-            frame_flags,
-            false,
-            self.stream_id, 
-        );
+        // Serialize the header, its payload length is written once the
+        // payload is.
+        FrameHeader::new(0, 0x0, frame_flags, false, self.stream_id).serialize_into(bytes);
 
-        // Serialize the frame.
-        let mut bytes: Vec<u8> = Vec::new();
-        bytes.append(&mut header.serialize());
-        bytes.append(&mut payload);
+        // Serialize the payload.
+        padding::serialize_pad_length(bytes, pad_length);
+        bytes.extend_from_slice(&self.data);
+        padding::serialize_padding(bytes, pad_length);
 
-        bytes
+        FrameHeader::write_payload_length(&mut bytes[start..]);
     }
 
     /// Deserialize the flags from a byte.
@@ -139,32 +162,44 @@ impl DataFrame {
         let frame_flags: Vec<FrameFlag> = DataFrame::deserialize_flags(frame_header.frame_flags());
 
         // Handle the padding if needed.
+        let mut pad_length: Option<u8> = None;
         if frame_flags.contains(&FrameFlag::Padded) {
-            let pad_length = bytes[0] as usize;
-
-            // Check that the padding length is not 0.
-            if pad_length == 0 {
-                return Err(Http2Error::FrameError(
-                    "Padding length invalid: found 0".to_string(),
-                ));
-            }
-            *bytes = bytes[1..frame_header.payload_length() as usize - pad_length].to_vec();
+            pad_length = bytes.first().copied();
+            padding::unpad(bytes, 0)?;
         }
 
         Ok(Self {
             stream_id: frame_header.stream_id(),
             end_stream: frame_flags.contains(&FrameFlag::EndStream),
             data: bytes.clone(),
+            pad_length,
         })
     }
+
+    /// Get the flags of the frame, as they are serialized.
+    ///
+    /// The flags of a decoded frame are those it was received with.
+    pub fn flags(&self) -> Vec<FrameFlag> {
+        let mut frame_flags = Vec::new();
+
+        if self.end_stream {
+            frame_flags.push(FrameFlag::EndStream);
+        }
+
+        if self.pad_length.is_some() {
+            frame_flags.push(FrameFlag::Padded);
+        }
+
+        frame_flags
+    }
 }
 
 impl fmt::Display for DataFrame {
     /// Format a DATA frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "DATA\n")?;
-        write!(f, "Stream Identifier: {}\n", self.stream_id)?;
-        write!(f, "End Stream: {}\n", self.end_stream)?;
-        write!(f, "Data: {}\n", String::from_utf8_lossy(&self.data))
+        writeln!(f, "DATA")?;
+        writeln!(f, "Stream Identifier: {}", self.stream_id)?;
+        writeln!(f, "End Stream: {}", self.end_stream)?;
+        writeln!(f, "Data: {}", String::from_utf8_lossy(&self.data))
     }
 }
@@ -0,0 +1,185 @@
+use crate::error::{ErrorCode, Http2Error};
+use crate::frame::header_block::HeaderBlockGuard;
+use crate::frame::{
+    DecodeStatus, Frame, FrameHeader, DEFAULT_MAX_FRAME_SIZE, FRAME_HEADER_LENGTH,
+    MAX_FRAME_SIZE_UPPER_BOUND,
+};
+use crate::header::table::HeaderTable;
+use crate::trace;
+
+/// State of the frame decoder.
+#[derive(Debug, PartialEq)]
+enum DecoderState {
+    /// Waiting for the 9 octets of a frame header.
+    Header,
+    /// Waiting for the payload of a frame whose header was read.
+    Payload(FrameHeader),
+}
+
+/// HTTP/2 incremental frame decoder.
+///
+/// The decoder accepts arbitrary chunks of bytes, as read from a socket,
+/// and yields frames as soon as they are complete. Partially received
+/// frames are kept in the decoder until the rest of their bytes is fed,
+/// whatever the read boundaries: a frame header or payload split across
+/// any number of chunks is decoded as if it was fed at once.
+///
+/// Frames larger than the advertised SETTINGS_MAX_FRAME_SIZE are rejected
+/// as soon as their header is read, before their payload is buffered.
+/// Header blocks split over too many CONTINUATION frames, or too large,
+/// are rejected by a [`HeaderBlockGuard`].
+pub struct FrameDecoder {
+    buffer: Vec<u8>,
+    state: DecoderState,
+    header_table: HeaderTable,
+    max_frame_size: u32,
+    header_block_guard: HeaderBlockGuard,
+}
+
+impl FrameDecoder {
+    /// Create a new frame decoder.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table_size` - The maximum size of the HPACK dynamic table.
+    pub fn new(header_table_size: usize) -> Self {
+        FrameDecoder {
+            buffer: Vec::new(),
+            state: DecoderState::Header,
+            header_table: HeaderTable::new(header_table_size),
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            header_block_guard: HeaderBlockGuard::default(),
+        }
+    }
+
+    /// Get the maximum frame payload size accepted by the decoder.
+    pub fn max_frame_size(&self) -> u32 {
+        self.max_frame_size
+    }
+
+    /// Set the maximum frame payload size accepted by the decoder.
+    ///
+    /// The value must be the one advertised in SETTINGS_MAX_FRAME_SIZE,
+    /// between 2^14 and 2^24-1 octets.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_frame_size` - The maximum frame payload size.
+    pub fn set_max_frame_size(&mut self, max_frame_size: u32) -> Result<(), Http2Error> {
+        if !(DEFAULT_MAX_FRAME_SIZE..=MAX_FRAME_SIZE_UPPER_BOUND).contains(&max_frame_size) {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!("Invalid maximum frame size {}", max_frame_size),
+            ));
+        }
+
+        self.max_frame_size = max_frame_size;
+
+        Ok(())
+    }
+
+    /// Feed a chunk of bytes to the decoder.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The received bytes.
+    pub fn feed(&mut self, bytes: &[u8]) {
+        self.buffer.extend_from_slice(bytes);
+    }
+
+    /// Decode the next complete frame.
+    ///
+    /// Returns `Ok(None)` when more bytes need to be fed to complete the
+    /// next frame.
+    pub fn next_frame(&mut self) -> Result<Option<Frame>, Http2Error> {
+        Ok(self.next_frame_with_header()?.map(|(_, frame)| frame))
+    }
+
+    /// Decode the next complete frame along with its header as received.
+    ///
+    /// Decoded frames ignore the flags that are not defined for their type
+    /// and the reserved bit, the frame header surfaces them for diagnostic
+    /// tools through [`FrameHeader::unknown_flags`] and
+    /// [`FrameHeader::reserved`].
+    pub fn next_frame_with_header(&mut self) -> Result<Option<(FrameHeader, Frame)>, Http2Error> {
+        match self.decode()? {
+            DecodeStatus::Complete(decoded) => Ok(Some(decoded)),
+            DecodeStatus::NeedMoreData(_) => Ok(None),
+        }
+    }
+
+    /// Decode the next complete frame along with its header as received.
+    ///
+    /// Returns [`DecodeStatus::NeedMoreData`] with the number of bytes
+    /// missing to complete the frame header, or the frame payload once its
+    /// header was read. Errors are fatal to the connection.
+    pub fn decode(&mut self) -> Result<DecodeStatus<(FrameHeader, Frame)>, Http2Error> {
+        // Read the frame header if needed.
+        if self.state == DecoderState::Header {
+            let frame_header = match self.buffer.first_chunk() {
+                Some(header_bytes) => FrameHeader::from_bytes(header_bytes),
+                None => {
+                    return Ok(DecodeStatus::NeedMoreData(
+                        FRAME_HEADER_LENGTH - self.buffer.len(),
+                    ))
+                }
+            };
+            self.buffer.drain(..FRAME_HEADER_LENGTH);
+
+            // Reject oversized frames before buffering their payload.
+            frame_header.check_payload_length(self.max_frame_size)?;
+            self.header_block_guard.check(&frame_header)?;
+
+            self.state = DecoderState::Payload(frame_header);
+        }
+
+        // Wait for the frame payload.
+        let frame_header = match self.state {
+            DecoderState::Payload(frame_header) => frame_header,
+            DecoderState::Header => return Ok(DecodeStatus::NeedMoreData(FRAME_HEADER_LENGTH)),
+        };
+        let payload_length = frame_header.payload_length() as usize;
+        if self.buffer.len() < payload_length {
+            return Ok(DecodeStatus::NeedMoreData(
+                payload_length - self.buffer.len(),
+            ));
+        }
+
+        // Deserialize the frame and get ready for the next one.
+        let mut payload: Vec<u8> = self.buffer.drain(..payload_length).collect();
+        self.state = DecoderState::Header;
+
+        trace::frame_received(&frame_header);
+        let frame =
+            Frame::deserialize_payload(&frame_header, &mut payload, &mut self.header_table)?;
+
+        Ok(DecodeStatus::Complete((frame_header, frame)))
+    }
+
+    /// Get the number of bytes fed but not yet decoded.
+    pub fn buffered(&self) -> usize {
+        match &self.state {
+            DecoderState::Header => self.buffer.len(),
+            DecoderState::Payload(_) => FRAME_HEADER_LENGTH + self.buffer.len(),
+        }
+    }
+
+    /// Get the header table used to decode header lists.
+    pub fn header_table(&mut self) -> &mut HeaderTable {
+        &mut self.header_table
+    }
+
+    /// Get the guard limiting the size of received header blocks.
+    pub fn header_block_guard(&mut self) -> &mut HeaderBlockGuard {
+        &mut self.header_block_guard
+    }
+}
+
+impl Iterator for FrameDecoder {
+    type Item = Result<Frame, Http2Error>;
+
+    /// Yield the next complete frame, if any.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_frame().transpose()
+    }
+}
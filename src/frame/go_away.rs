@@ -1,8 +1,14 @@
 use std::fmt;
 
-use crate::error::Http2Error;
+use crate::error::{ErrorCode, Http2Error};
 use crate::frame::FrameHeader;
 
+/// Maximum length of the debug data kept from a received GOAWAY frame.
+///
+/// Debug data is only meant for diagnostics, longer debug data is
+/// truncated on decode rather than kept in memory.
+pub const MAX_DEBUG_DATA_LENGTH: usize = 1_024;
+
 /// GO_AWAY Frame payload.
 ///
 /// The GO_AWAY frame (type=0x7) is used to initiate shutdown of a
@@ -18,18 +24,67 @@ use crate::frame::FrameHeader;
 /// +---------------------------------------------------------------+
 /// |                  Additional Debug Data (*)                    |
 /// +---------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct GoAwayFrame {
     reserved: bool,
     last_stream_id: u32,
     error_code: u32,
-    debug_data: Option<Vec<u8>>,
+    debug_data: Vec<u8>,
 }
 
 impl GoAwayFrame {
+    /// Create a new GO_AWAY frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `last_stream_id` - The last peer-initiated stream processed (31 bits).
+    /// * `error_code` - The reason for closing the connection.
+    /// * `debug_data` - Additional diagnostic data, possibly empty.
+    pub fn new(last_stream_id: u32, error_code: ErrorCode, debug_data: Vec<u8>) -> Self {
+        GoAwayFrame {
+            reserved: false,
+            last_stream_id: last_stream_id & 0x7FFF_FFFF,
+            error_code: error_code.into(),
+            debug_data,
+        }
+    }
+
+    /// Serialize a GO_AWAY frame.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(&mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a GO_AWAY frame at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        let start = bytes.len();
+
+        // Serialize the header, its payload length is written once the
+        // payload is.
+        FrameHeader::new(0, 0x7, 0x0, false, 0).serialize_into(bytes);
+
+        // Serialize the payload.
+        let mut last_stream_id = self.last_stream_id.to_be_bytes();
+        if self.reserved {
+            last_stream_id[0] |= 0x80;
+        }
+        bytes.extend_from_slice(&last_stream_id);
+        bytes.extend_from_slice(&self.error_code.to_be_bytes());
+        bytes.extend_from_slice(&self.debug_data);
+
+        FrameHeader::write_payload_length(&mut bytes[start..]);
+    }
+
     /// Deserialize a GO_AWAY frame.
     /// 
-    /// The operation is destructive for the bytes vector.
+    /// Debug data longer than [`MAX_DEBUG_DATA_LENGTH`] is truncated. The
+    /// operation is destructive for the bytes vector.
     /// 
     /// # Arguments
     /// 
@@ -37,7 +92,7 @@ impl GoAwayFrame {
     /// * `bytes` - A mutable reference to a bytes vector.
     pub fn deserialize(
         frame_header: &FrameHeader,
-        bytes: &mut Vec<u8>,
+        bytes: &mut [u8],
     ) -> Result<Self, Http2Error> {
         // Check if the bytes has the right length.
         if bytes.len() != frame_header.payload_length() as usize {
@@ -48,16 +103,24 @@ impl GoAwayFrame {
             )));
         }
 
+        // A GO_AWAY frame carries at least a last stream identifier and an
+        // error code.
+        if bytes.len() < 8 {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::FrameSizeError,
+                format!(
+                    "GOAWAY frame payload must be at least 8 bytes, found {}",
+                    bytes.len()
+                ),
+            ));
+        }
+
         // Retrieve the frame fields.
         let reserved: bool = (bytes[0] >> 7) != 0;
         let last_stream_id: u32 =
             u32::from_be_bytes([bytes[0] & 0x7F, bytes[1], bytes[2], bytes[3]]);
         let error_code: u32 = u32::from_be_bytes([bytes[4], bytes[5], bytes[6], bytes[7]]);
-        let debug_data: Option<Vec<u8>> = if frame_header.payload_length() > 8 {
-            Some(bytes[8..frame_header.payload_length() as usize].to_vec())
-        } else {
-            None
-        };
+        let debug_data: Vec<u8> = bytes[8..bytes.len().min(8 + MAX_DEBUG_DATA_LENGTH)].to_vec();
 
         Ok(GoAwayFrame {
             reserved,
@@ -66,20 +129,37 @@ impl GoAwayFrame {
             debug_data,
         })
     }
+
+    pub fn last_stream_id(&self) -> u32 {
+        self.last_stream_id
+    }
+
+    pub fn error_code(&self) -> u32 {
+        self.error_code
+    }
+
+    /// Get the additional debug data, empty if there is none.
+    pub fn debug_data(&self) -> &[u8] {
+        &self.debug_data
+    }
 }
 
 impl fmt::Display for GoAwayFrame {
     /// Format a GO_AWAY frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "GO_AWAY\n")?;
-        write!(f, "Reserved: {}\n", self.reserved)?;
-        write!(f, "Last Stream ID: {}\n", self.last_stream_id)?;
-        write!(f, "Error Code: {}\n", self.error_code)?;
-        match self.debug_data {
-            Some(ref debug_data) => {
-                write!(f, "Debug Data: {}\n", String::from_utf8_lossy(debug_data))
-            }
-            None => write!(f, "Debug Data: None\n"),
+        writeln!(f, "GO_AWAY")?;
+        writeln!(f, "Reserved: {}", self.reserved)?;
+        writeln!(f, "Last Stream ID: {}", self.last_stream_id)?;
+        writeln!(f, "Error Code: {}", self.error_code)?;
+        if self.debug_data.is_empty() {
+            return writeln!(f, "Debug Data: None");
+        }
+
+        // Debug data is opaque, show it in hexadecimal to keep every byte.
+        write!(f, "Debug Data: ")?;
+        for byte in &self.debug_data {
+            write!(f, "{:02x}", byte)?;
         }
+        writeln!(f)
     }
 }
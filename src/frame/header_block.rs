@@ -0,0 +1,167 @@
+use crate::debug::frame_type_name;
+use crate::error::{ErrorCode, Http2Error};
+use crate::frame::FrameHeader;
+
+/// Default maximum number of CONTINUATION frames in a header block.
+pub const DEFAULT_MAX_CONTINUATION_FRAMES: usize = 32;
+
+/// Default maximum size of a header block, in octets of encoded payload.
+pub const DEFAULT_MAX_HEADER_BLOCK_SIZE: usize = 65_536;
+
+/// Header block in progress.
+#[derive(Debug, PartialEq)]
+struct HeaderBlock {
+    stream_id: u32,
+    size: usize,
+    continuation_frames: usize,
+}
+
+/// Header block guard.
+///
+/// A header block starts with a HEADERS or PUSH_PROMISE frame and
+/// continues with CONTINUATION frames on the same stream until one of
+/// them carries the END_HEADERS flag. No other frame may be interleaved.
+/// Nothing bounds the number of CONTINUATION frames a peer may send, so
+/// the guard caps both their count and the total payload size of the
+/// block, failing with a connection error of type ENHANCE_YOUR_CALM when
+/// a limit is exceeded.
+///
+/// Frames are checked from their header, before their payload is read.
+#[derive(Debug)]
+pub struct HeaderBlockGuard {
+    max_continuation_frames: usize,
+    max_header_block_size: usize,
+    header_block: Option<HeaderBlock>,
+}
+
+impl HeaderBlockGuard {
+    /// Create a new header block guard.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_continuation_frames` - The maximum number of CONTINUATION frames.
+    /// * `max_header_block_size` - The maximum size of a header block.
+    pub fn new(max_continuation_frames: usize, max_header_block_size: usize) -> Self {
+        HeaderBlockGuard {
+            max_continuation_frames,
+            max_header_block_size,
+            header_block: None,
+        }
+    }
+
+    /// Check a received frame against the header block in progress.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_header` - The header of the received frame.
+    pub fn check(&mut self, frame_header: &FrameHeader) -> Result<(), Http2Error> {
+        let size = frame_header.payload_length() as usize;
+        let end_headers = frame_header.frame_flags() & 0x4 != 0;
+
+        let header_block = match (self.header_block.take(), frame_header.frame_type()) {
+            // CONTINUATION of the header block in progress.
+            (Some(mut header_block), 0x9) if header_block.stream_id == frame_header.stream_id() => {
+                header_block.size += size;
+                header_block.continuation_frames += 1;
+
+                if header_block.continuation_frames > self.max_continuation_frames {
+                    return Err(Http2Error::ConnectionError(
+                        ErrorCode::EnhanceYourCalm,
+                        format!(
+                            "Header block on stream {} exceeds {} CONTINUATION frames",
+                            header_block.stream_id, self.max_continuation_frames
+                        ),
+                    ));
+                }
+                header_block
+            }
+            (Some(header_block), _) => {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::ProtocolError,
+                    format!(
+                        "Expected CONTINUATION frame on stream {}, found {} on stream {}",
+                        header_block.stream_id,
+                        frame_type_name(frame_header.frame_type()),
+                        frame_header.stream_id()
+                    ),
+                ))
+            }
+            (None, 0x9) => {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::ProtocolError,
+                    format!(
+                        "CONTINUATION frame on stream {} without a header block",
+                        frame_header.stream_id()
+                    ),
+                ))
+            }
+            // HEADERS or PUSH_PROMISE start a new header block.
+            (None, 0x1 | 0x5) => HeaderBlock {
+                stream_id: frame_header.stream_id(),
+                size,
+                continuation_frames: 0,
+            },
+            (None, _) => return Ok(()),
+        };
+
+        if header_block.size > self.max_header_block_size {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::EnhanceYourCalm,
+                format!(
+                    "Header block on stream {} exceeds {} bytes",
+                    header_block.stream_id, self.max_header_block_size
+                ),
+            ));
+        }
+
+        if !end_headers {
+            self.header_block = Some(header_block);
+        }
+
+        Ok(())
+    }
+
+    /// Check if a header block is in progress.
+    pub fn in_progress(&self) -> bool {
+        self.header_block.is_some()
+    }
+
+    /// Get the maximum number of CONTINUATION frames in a header block.
+    pub fn max_continuation_frames(&self) -> usize {
+        self.max_continuation_frames
+    }
+
+    /// Set the maximum number of CONTINUATION frames in a header block.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_continuation_frames` - The maximum number of CONTINUATION frames.
+    pub fn set_max_continuation_frames(&mut self, max_continuation_frames: usize) {
+        self.max_continuation_frames = max_continuation_frames;
+    }
+
+    /// Get the maximum size of a header block, in octets.
+    pub fn max_header_block_size(&self) -> usize {
+        self.max_header_block_size
+    }
+
+    /// Set the maximum size of a header block.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_header_block_size` - The maximum size of a header block, in octets.
+    pub fn set_max_header_block_size(&mut self, max_header_block_size: usize) {
+        self.max_header_block_size = max_header_block_size;
+    }
+}
+
+impl Default for HeaderBlockGuard {
+    /// Create a header block guard with the default limits,
+    /// [`DEFAULT_MAX_CONTINUATION_FRAMES`] and [`DEFAULT_MAX_HEADER_BLOCK_SIZE`].
+    fn default() -> Self {
+        HeaderBlockGuard::new(
+            DEFAULT_MAX_CONTINUATION_FRAMES,
+            DEFAULT_MAX_HEADER_BLOCK_SIZE,
+        )
+    }
+}
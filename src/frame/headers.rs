@@ -1,7 +1,9 @@
 use std::fmt;
 
-use crate::error::Http2Error;
-use crate::frame::{FrameFlag, FrameHeader, FramePriority};
+use crate::error::{ErrorCode, Http2Error};
+use crate::frame::continuation::ContinuationFrame;
+use crate::frame::padding::{self, Padding};
+use crate::frame::{FrameFlag, FrameHeader, FramePriority, FRAME_PRIORITY_LENGTH};
 use crate::header::list::HeaderList;
 use crate::header::table::HeaderTable;
 
@@ -23,7 +25,7 @@ use crate::header::table::HeaderTable;
 /// +---------------------------------------------------------------+
 /// |                           Padding (*)                       ...
 /// +---------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 
 pub struct HeadersFrame {
     stream_id: u32,
@@ -31,9 +33,160 @@ pub struct HeadersFrame {
     end_headers: bool,
     frame_priority: Option<FramePriority>,
     header_list: HeaderList,
+    header_block_fragment: Option<Vec<u8>>,
+    pad_length: Option<u8>,
 }
 
 impl HeadersFrame {
+    /// Create a new HEADERS frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `end_stream` - Whether the header block is the last one of the stream.
+    /// * `end_headers` - Whether the frame contains the entire header block.
+    /// * `frame_priority` - The optional priority of the stream.
+    /// * `header_list` - The header list to send.
+    pub fn new(
+        stream_id: u32,
+        end_stream: bool,
+        end_headers: bool,
+        frame_priority: Option<FramePriority>,
+        header_list: HeaderList,
+    ) -> Self {
+        HeadersFrame {
+            stream_id,
+            end_stream,
+            end_headers,
+            frame_priority,
+            header_list,
+            header_block_fragment: None,
+            pad_length: None,
+        }
+    }
+
+    /// Create a HEADERS frame carrying a whole header list, its header
+    /// block encoded right away.
+    ///
+    /// The header list is encoded with the header table, which is updated,
+    /// so that the frame MUST be sent before any other header block encoded
+    /// with the same table. Serializing the frame then writes the encoded
+    /// header block as is, without touching the header table passed in.
+    /// The header list is kept for inspection.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `header_list` - The header list to send.
+    /// * `end_stream` - Whether the header block is the last one of the stream.
+    /// * `frame_priority` - The optional priority of the stream.
+    /// * `header_table` - The encoding table of the connection.
+    pub fn from_header_list(
+        stream_id: u32,
+        header_list: HeaderList,
+        end_stream: bool,
+        frame_priority: Option<FramePriority>,
+        header_table: &mut HeaderTable,
+    ) -> Result<Self, Http2Error> {
+        let header_block_fragment = header_list.encode(header_table)?;
+
+        Ok(HeadersFrame {
+            stream_id,
+            end_stream,
+            end_headers: true,
+            frame_priority,
+            header_list,
+            header_block_fragment: Some(header_block_fragment),
+            pad_length: None,
+        })
+    }
+
+    /// Serialize a HEADERS frame.
+    ///
+    /// The header table is updated with the encoded header fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    pub fn serialize(&self, header_table: &mut HeaderTable) -> Result<Vec<u8>, Http2Error> {
+        self.serialize_padded(header_table, Padding::None)
+    }
+
+    /// Serialize a HEADERS frame with padding.
+    ///
+    /// The header table is updated with the encoded header fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    /// * `padding` - The padding policy of the frame.
+    pub fn serialize_padded(
+        &self,
+        header_table: &mut HeaderTable,
+        padding: Padding,
+    ) -> Result<Vec<u8>, Http2Error> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_padded_into(header_table, padding, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Serialize a HEADERS frame with padding at the end of a byte vector.
+    ///
+    /// The header table is updated with the encoded header fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    /// * `padding` - The padding policy of the frame.
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_padded_into(
+        &self,
+        header_table: &mut HeaderTable,
+        padding: Padding,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Http2Error> {
+        let start = bytes.len();
+        let pad_length = match padding {
+            Padding::None => self.pad_length,
+            padding => padding.pad_length(),
+        };
+
+        // Build the flags bit.
+        let mut frame_flags: u8 = 0x0;
+        if self.end_stream {
+            frame_flags |= 0x01;
+        }
+        if self.end_headers {
+            frame_flags |= 0x04;
+        }
+        if self.frame_priority.is_some() {
+            frame_flags |= 0x20;
+        }
+        if pad_length.is_some() {
+            frame_flags |= 0x08;
+        }
+
+        // Serialize the header, its payload length is written once the
+        // payload is.
+        FrameHeader::new(0, 0x1, frame_flags, false, self.stream_id).serialize_into(bytes);
+
+        // Serialize the payload.
+        padding::serialize_pad_length(bytes, pad_length);
+        if let Some(frame_priority) = &self.frame_priority {
+            frame_priority.serialize_into(bytes);
+        }
+        match &self.header_block_fragment {
+            Some(header_block_fragment) => bytes.extend_from_slice(header_block_fragment),
+            None => self.header_list.encode_into(header_table, bytes)?,
+        }
+        padding::serialize_padding(bytes, pad_length);
+
+        FrameHeader::write_payload_length(&mut bytes[start..]);
+
+        Ok(())
+    }
+
     /// Deserialize the flags from a byte.
     /// 
     /// # Arguments
@@ -88,17 +241,20 @@ impl HeadersFrame {
         let frame_flags: Vec<FrameFlag> =
             HeadersFrame::deserialize_flags(frame_header.frame_flags());
 
-        // Handle the padding if needed.
-        if frame_flags.contains(&FrameFlag::Padded) {
-            let pad_length = bytes[0] as usize;
+        // The payload holds the Pad Length and the priority, if present,
+        // before the header block fragment.
+        let padded = frame_flags.contains(&FrameFlag::Padded);
+        let fields_length = match frame_flags.contains(&FrameFlag::Priority) {
+            true => FRAME_PRIORITY_LENGTH,
+            false => 0,
+        };
+        padding::check_fields_length(bytes, padded, fields_length, "HEADERS")?;
 
-            // Check that the padding length is not 0.
-            if pad_length == 0 {
-                return Err(Http2Error::FrameError(
-                    "Padding length invalid: found 0".to_string(),
-                ));
-            }
-            *bytes = bytes[1..frame_header.payload_length() as usize - pad_length].to_vec();
+        // Handle the padding if needed, it may not overlap the priority.
+        let mut pad_length: Option<u8> = None;
+        if padded {
+            pad_length = bytes.first().copied();
+            padding::unpad(bytes, fields_length)?;
         }
 
         // Handle the priority if needed.
@@ -108,7 +264,10 @@ impl HeadersFrame {
         }
 
         // Decode the header list (the header table is updated).
-        let header_list = HeaderList::decode(bytes, header_table)?;
+        let (header_list, header_block_fragment) = match header_table.pass_through() {
+            true => (HeaderList::default(), Some(bytes.clone())),
+            false => (HeaderList::decode(bytes, header_table)?, None),
+        };
 
         Ok(Self {
             stream_id: frame_header.stream_id(),
@@ -116,20 +275,198 @@ impl HeadersFrame {
             end_headers: frame_flags.contains(&FrameFlag::EndHeaders),
             frame_priority,
             header_list,
+            header_block_fragment,
+            pad_length,
         })
     }
+
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    pub fn end_stream(&self) -> bool {
+        self.end_stream
+    }
+
+    pub fn end_headers(&self) -> bool {
+        self.end_headers
+    }
+
+    pub fn frame_priority(&self) -> Option<&FramePriority> {
+        self.frame_priority.as_ref()
+    }
+
+    pub fn header_list(&self) -> &HeaderList {
+        &self.header_list
+    }
+
+    /// Get the header block fragment of a frame decoded with a
+    /// pass-through header table, until it is decoded, or of a frame
+    /// created with [`HeadersFrame::from_header_list`].
+    pub fn header_block_fragment(&self) -> Option<&[u8]> {
+        self.header_block_fragment.as_deref()
+    }
+
+    /// Decode the header block fragment kept by a pass-through header
+    /// table into the header list of the frame.
+    ///
+    /// Header blocks MUST be decoded in the order they were received, with
+    /// the decoding table of the connection. The frame is left unchanged
+    /// if its header list is already decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    pub fn decode_header_block(
+        &mut self,
+        header_table: &mut HeaderTable,
+    ) -> Result<(), Http2Error> {
+        // The header block fragment is kept if it fails to decode.
+        if let Some(header_block_fragment) = &mut self.header_block_fragment {
+            self.header_list = HeaderList::decode(header_block_fragment, header_table)?;
+            self.header_block_fragment = None;
+        }
+
+        Ok(())
+    }
+
+    /// Append the header block fragment of the CONTINUATION frame following
+    /// the frame, both decoded with a pass-through header table.
+    ///
+    /// The frame ends its header block if the CONTINUATION frame does, so
+    /// that a header block split over several frames is decoded at once
+    /// with [`HeadersFrame::decode_header_block`]: a header field may be split
+    /// anywhere across the frames. A CONTINUATION frame on another stream,
+    /// or following the end of the header block, fails with PROTOCOL_ERROR.
+    ///
+    /// # Arguments
+    ///
+    /// * `continuation_frame` - The CONTINUATION frame following the frame.
+    pub fn append_continuation(
+        &mut self,
+        continuation_frame: &ContinuationFrame,
+    ) -> Result<(), Http2Error> {
+        match (
+            &mut self.header_block_fragment,
+            continuation_frame.header_block_fragment(),
+        ) {
+            (Some(header_block_fragment), Some(fragment))
+                if !self.end_headers && continuation_frame.stream_id() == self.stream_id =>
+            {
+                header_block_fragment.extend_from_slice(fragment);
+                self.end_headers = continuation_frame.end_headers();
+                Ok(())
+            }
+            _ => Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!(
+                    "CONTINUATION frame does not continue the header block of stream {}",
+                    self.stream_id
+                ),
+            )),
+        }
+    }
+
+    /// Split the header block of the frame so that no frame exceeds a
+    /// maximum payload size, returning the CONTINUATION frames to send
+    /// right after it.
+    ///
+    /// The header list is encoded with the header table, unless the frame
+    /// already carries its header block, so that the frames MUST be sent
+    /// before any other header block encoded with the same table. The frame
+    /// keeps the beginning of the header block, and only the last frame
+    /// has the END_HEADERS flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_frame_size` - The maximum frame payload size of the peer.
+    /// * `header_table` - The encoding table of the connection.
+    pub fn split_header_block(
+        &mut self,
+        max_frame_size: u32,
+        header_table: &mut HeaderTable,
+    ) -> Result<Vec<ContinuationFrame>, Http2Error> {
+        let mut header_block = match self.header_block_fragment.take() {
+            Some(header_block_fragment) => header_block_fragment,
+            None => self.header_list.encode(header_table)?,
+        };
+
+        // The fields preceding the header block share the frame payload.
+        let mut fields_length = self
+            .pad_length
+            .map_or(0, |pad_length| 1 + pad_length as usize);
+        if self.frame_priority.is_some() {
+            fields_length += FRAME_PRIORITY_LENGTH;
+        }
+        let length = (max_frame_size as usize)
+            .saturating_sub(fields_length)
+            .min(header_block.len());
+        let continuation_frames = ContinuationFrame::from_header_block(
+            self.stream_id,
+            &header_block.split_off(length),
+            max_frame_size,
+        );
+
+        self.end_headers = continuation_frames.is_empty();
+        self.header_block_fragment = Some(header_block);
+
+        Ok(continuation_frames)
+    }
+
+    /// Get the number of padding octets the frame was received with, if it
+    /// was padded.
+    ///
+    /// A frame serialized without a padding policy keeps this padding.
+    pub fn pad_length(&self) -> Option<u8> {
+        self.pad_length
+    }
+
+    /// Set the number of padding octets of the frame, `None` if it is not
+    /// padded.
+    ///
+    /// # Arguments
+    ///
+    /// * `pad_length` - The number of padding octets.
+    pub fn set_pad_length(&mut self, pad_length: Option<u8>) {
+        self.pad_length = pad_length;
+    }
+
+    /// Get the flags of the frame, as they are serialized.
+    ///
+    /// The flags of a decoded frame are those it was received with.
+    pub fn flags(&self) -> Vec<FrameFlag> {
+        let mut frame_flags = Vec::new();
+
+        if self.end_stream {
+            frame_flags.push(FrameFlag::EndStream);
+        }
+
+        if self.end_headers {
+            frame_flags.push(FrameFlag::EndHeaders);
+        }
+
+        if self.pad_length.is_some() {
+            frame_flags.push(FrameFlag::Padded);
+        }
+
+        if self.frame_priority.is_some() {
+            frame_flags.push(FrameFlag::Priority);
+        }
+
+        frame_flags
+    }
 }
 
 impl fmt::Display for HeadersFrame {
     /// Format a HEADERS frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "HEADERS\n")?;
-        write!(f, "Stream Identifier: {}\n", self.stream_id)?;
-        write!(f, "End Stream: {}\n", self.end_stream)?;
-        write!(f, "End Headers: {}\n", self.end_headers)?;
+        writeln!(f, "HEADERS")?;
+        writeln!(f, "Stream Identifier: {}", self.stream_id)?;
+        writeln!(f, "End Stream: {}", self.end_stream)?;
+        writeln!(f, "End Headers: {}", self.end_headers)?;
         if let Some(frame_priority) = &self.frame_priority {
             write!(f, "{}", frame_priority)?;
         }
-        write!(f, "Header List:\n{}\n", self.header_list)
+        writeln!(f, "Header List:\n{}", self.header_list)
     }
 }
@@ -1,24 +1,140 @@
 pub mod continuation;
 pub mod data;
+pub mod decoder;
 pub mod go_away;
+pub mod header_block;
 pub mod headers;
+pub mod padding;
 pub mod ping;
 pub mod priority;
 pub mod push_promise;
+pub mod raw;
 pub mod rst_stream;
 pub mod settings;
 pub mod window_update;
 
 use std::fmt;
 
-use crate::error::Http2Error;
+use crate::error::{ErrorCode, Http2Error};
 use crate::frame::{
     continuation::ContinuationFrame, data::DataFrame, go_away::GoAwayFrame, headers::HeadersFrame,
-    ping::PingFrame, priority::PriorityFrame, push_promise::PushPromiseFrame,
-    rst_stream::RstStreamFrame, settings::SettingsFrame, window_update::WindowUpdateFrame,
+    padding::Padding, ping::PingFrame, priority::PriorityFrame, push_promise::PushPromiseFrame,
+    raw::RawFrame, rst_stream::RstStreamFrame, settings::SettingsFrame, window_update::WindowUpdateFrame,
 };
 use crate::header::table::HeaderTable;
 
+/// Initial value of SETTINGS_MAX_FRAME_SIZE (2^14 octets).
+pub const DEFAULT_MAX_FRAME_SIZE: u32 = 16_384;
+
+/// Maximum allowed value of SETTINGS_MAX_FRAME_SIZE (2^24-1 octets).
+pub const MAX_FRAME_SIZE_UPPER_BOUND: u32 = 16_777_215;
+
+/// Size of the HTTP/2 frame header in octets.
+pub const FRAME_HEADER_LENGTH: usize = 9;
+
+/// Size of the priority fields of HEADERS and PRIORITY frames in octets.
+pub const FRAME_PRIORITY_LENGTH: usize = 5;
+
+/// Largest stream identifier (2^31-1).
+pub const MAX_STREAM_ID: u32 = 0x7FFF_FFFF;
+
+/// Status of an attempt to decode a frame from partially received bytes.
+///
+/// Running out of bytes is not an error: a frame split across reads
+/// yields [`DecodeStatus::NeedMoreData`] until its last byte is received,
+/// while errors are reserved to invalid frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeStatus<T> {
+    /// The item was decoded and its bytes consumed.
+    Complete(T),
+    /// The number of bytes missing to decode the next item, at least.
+    NeedMoreData(usize),
+}
+
+/// Get the mask of the flags defined for a frame type.
+///
+/// Frame types without flags and unknown frame types define no flag.
+///
+/// # Arguments
+///
+/// * `frame_type` - The frame type.
+pub fn defined_flags(frame_type: u8) -> u8 {
+    match frame_type {
+        0x0 => 0x01 | 0x08,
+        0x1 => 0x01 | 0x04 | 0x08 | 0x20,
+        0x4 | 0x6 => 0x01,
+        0x5 => 0x04 | 0x08,
+        0x9 => 0x04,
+        _ => 0x00,
+    }
+}
+
+/// Check if the frames of a type carry a header block fragment, which is
+/// decoded with the HPACK decoding table of the connection: HEADERS,
+/// PUSH_PROMISE and CONTINUATION frames.
+///
+/// # Arguments
+///
+/// * `frame_type` - The frame type.
+pub fn has_header_block(frame_type: u8) -> bool {
+    matches!(frame_type, 0x1 | 0x5 | 0x9)
+}
+
+/// Iterate over the frames of a captured bytes stream.
+///
+/// The frames are decoded in order with the header table, which must be
+/// the decoding table of the connection the bytes were captured from. The
+/// iterator ends after the first error: a truncated last frame is reported
+/// as a [`Http2Error::NotEnoughBytes`] error.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes stream, starting at a frame header.
+/// * `header_table` - A mutable reference to a HeaderTable.
+pub fn iter_frames<'a>(
+    bytes: &'a [u8],
+    header_table: &'a mut HeaderTable,
+) -> impl Iterator<Item = Result<Frame, Http2Error>> + 'a {
+    let mut remaining = bytes;
+
+    std::iter::from_fn(move || {
+        if remaining.is_empty() {
+            return None;
+        }
+
+        let frame_header = match remaining.first_chunk() {
+            Some(header_bytes) => FrameHeader::from_bytes(header_bytes),
+            None => {
+                let missing = FRAME_HEADER_LENGTH - remaining.len();
+                remaining = &[];
+                return Some(Err(Http2Error::NotEnoughBytes(format!(
+                    "Frame header needs {} more bytes",
+                    missing
+                ))));
+            }
+        };
+
+        let frame_length = FRAME_HEADER_LENGTH + frame_header.payload_length() as usize;
+        if remaining.len() < frame_length {
+            let missing = frame_length - remaining.len();
+            remaining = &[];
+            return Some(Err(Http2Error::NotEnoughBytes(format!(
+                "Frame needs {} more bytes",
+                missing
+            ))));
+        }
+
+        let mut payload = remaining[FRAME_HEADER_LENGTH..frame_length].to_vec();
+        remaining = &remaining[frame_length..];
+        let frame = Frame::deserialize_payload(&frame_header, &mut payload, header_table);
+        if frame.is_err() {
+            remaining = &[];
+        }
+
+        Some(frame)
+    })
+}
+
 /// HTTP/2 frame.
 /// 
 /// +-----------------------------------------------+
@@ -30,7 +146,8 @@ use crate::header::table::HeaderTable;
 /// +=+=============================================================+
 /// |                   Frame Payload (0...)                      ...
 /// +---------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum Frame {
     Data(DataFrame),
     Headers(HeadersFrame),
@@ -46,6 +163,11 @@ pub enum Frame {
 
 impl Frame {
     /// Deserialize a Frame.
+    ///
+    /// Missing bytes are reported as a [`Http2Error::NotEnoughBytes`] error,
+    /// use [`Frame::decode`] to tell them apart from invalid frames. Frames
+    /// without a header block can be decoded without a header table with
+    /// [`RawFrame`].
     /// 
     /// # Arguments
     /// 
@@ -55,60 +177,271 @@ impl Frame {
         stream: &mut Vec<u8>,
         header_table: &mut HeaderTable,
     ) -> Result<Frame, Http2Error> {
-        // Make a copy of the bytes vector.
-        let mut bytes: Vec<u8> = stream.clone();
-
-        // Try to extract the frame header from the bytes stream.
-        let frame_header = FrameHeader::deserialize(&mut bytes)?;
+        match Frame::decode(stream, header_table)? {
+            DecodeStatus::Complete(frame) => Ok(frame),
+            DecodeStatus::NeedMoreData(missing) => Err(Http2Error::NotEnoughBytes(format!(
+                "Frame needs {} more bytes, found {}",
+                missing,
+                stream.len(),
+            ))),
+        }
+    }
 
-        // Verify that there is enough bytes to deserialize the payload.
-        if bytes.len() < frame_header.payload_length() as usize {
-            return Err(Http2Error::NotEnoughBytes(format!(
-                "Frame payload needs at least {} bytes, found {}",
-                frame_header.payload_length(),
-                bytes.len(),
-            )));
+    /// Decode the first frame of a bytes stream.
+    ///
+    /// The frame is removed from the stream once decoded. When the stream
+    /// does not hold a complete frame, it is left untouched and the number
+    /// of missing bytes is returned, so that the caller can read more bytes
+    /// and try again. An error means the frame is invalid.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A mutable reference to a bytes vector.
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    pub fn decode(
+        stream: &mut Vec<u8>,
+        header_table: &mut HeaderTable,
+    ) -> Result<DecodeStatus<Frame>, Http2Error> {
+        match RawFrame::decode(stream)? {
+            DecodeStatus::Complete(raw_frame) => Ok(DecodeStatus::Complete(
+                raw_frame.decode_header_block(header_table)?,
+            )),
+            DecodeStatus::NeedMoreData(missing) => Ok(DecodeStatus::NeedMoreData(missing)),
         }
+    }
+
+    /// Deserialize a Frame payload once its header has been read.
+    ///
+    /// The stream identifier of the frame header is checked against the
+    /// frame type first, unless the lenience of the header table accepts
+    /// any stream. Flags that are not defined for the frame type and
+    /// the reserved bit have no semantics and are ignored. The operation is
+    /// destructive for the payload bytes vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_header` - A reference to the FrameHeader of the frame.
+    /// * `bytes` - A mutable reference to the payload bytes vector.
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    pub fn deserialize_payload(
+        frame_header: &FrameHeader,
+        bytes: &mut Vec<u8>,
+        header_table: &mut HeaderTable,
+    ) -> Result<Frame, Http2Error> {
+        Frame::deserialize_frame(frame_header, bytes, Some(header_table))
+    }
 
-        // Retrieve only the payload bytes.
-        bytes = bytes[..frame_header.payload_length() as usize].to_vec();
+    /// Deserialize a Frame payload, decoding its header block fragment with
+    /// the header table, if any.
+    ///
+    /// Frames carrying a header block cannot be decoded without a header
+    /// table.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_header` - A reference to the FrameHeader of the frame.
+    /// * `bytes` - A mutable reference to the payload bytes vector.
+    /// * `header_table` - The HeaderTable decoding header blocks, if any.
+    pub(crate) fn deserialize_frame(
+        frame_header: &FrameHeader,
+        bytes: &mut Vec<u8>,
+        header_table: Option<&mut HeaderTable>,
+    ) -> Result<Frame, Http2Error> {
+        let lenient_stream_ids = header_table
+            .as_deref()
+            .is_some_and(|header_table| header_table.lenience().frame_stream_ids);
+        if !lenient_stream_ids {
+            frame_header.check_stream_id()?;
+        }
+        let frame_header = &frame_header.without_unknown_bits();
 
-        // Deserialize the frame.
-        let frame = match frame_header.frame_type() {
-            0x00 => Frame::Data(DataFrame::deserialize(&frame_header, &mut bytes)?),
-            0x01 => Frame::Headers(HeadersFrame::deserialize(
-                &frame_header,
-                &mut bytes,
+        match (frame_header.frame_type(), header_table) {
+            (0x00, _) => Ok(Frame::Data(DataFrame::deserialize(frame_header, bytes)?)),
+            (0x01, Some(header_table)) => Ok(Frame::Headers(HeadersFrame::deserialize(
+                frame_header,
+                bytes,
                 header_table,
-            )?),
-            0x02 => Frame::Priority(PriorityFrame::deserialize(&frame_header, &mut bytes)?),
-            0x03 => Frame::RstStream(RstStreamFrame::deserialize(&frame_header, &mut bytes)?),
-            0x04 => Frame::Settings(SettingsFrame::deserialize(&frame_header, &mut bytes)?),
-            0x05 => Frame::PushPromise(PushPromiseFrame::deserialize(
-                &frame_header,
-                &mut bytes,
+            )?)),
+            (0x02, _) => Ok(Frame::Priority(PriorityFrame::deserialize(
+                frame_header,
+                bytes,
+            )?)),
+            (0x03, _) => Ok(Frame::RstStream(RstStreamFrame::deserialize(
+                frame_header,
+                bytes,
+            )?)),
+            (0x04, _) => Ok(Frame::Settings(SettingsFrame::deserialize(
+                frame_header,
+                bytes,
+            )?)),
+            (0x05, Some(header_table)) => Ok(Frame::PushPromise(PushPromiseFrame::deserialize(
+                frame_header,
+                bytes,
                 header_table,
-            )?),
-            0x06 => Frame::Ping(PingFrame::deserialize(&frame_header, &mut bytes)?),
-            0x07 => Frame::GoAway(GoAwayFrame::deserialize(&frame_header, &mut bytes)?),
-            0x08 => Frame::WindowUpdate(WindowUpdateFrame::deserialize(&frame_header, &mut bytes)?),
-            0x09 => Frame::Continuation(ContinuationFrame::deserialize(
-                &frame_header,
-                &mut bytes,
+            )?)),
+            (0x06, _) => Ok(Frame::Ping(PingFrame::deserialize(frame_header, bytes)?)),
+            (0x07, _) => Ok(Frame::GoAway(GoAwayFrame::deserialize(
+                frame_header,
+                bytes,
+            )?)),
+            (0x08, _) => Ok(Frame::WindowUpdate(WindowUpdateFrame::deserialize(
+                frame_header,
+                bytes,
+            )?)),
+            (0x09, Some(header_table)) => Ok(Frame::Continuation(ContinuationFrame::deserialize(
+                frame_header,
+                bytes,
                 header_table,
-            )?),
-            _ => {
-                return Err(Http2Error::FrameError(format!(
-                    "Could not deserialize Frame: unknown frame type {}",
-                    frame_header.frame_type()
+            )?)),
+            (frame_type, None) if has_header_block(frame_type) => {
+                Err(Http2Error::FrameError(format!(
+                    "Could not deserialize Frame: frame type {} needs a header table",
+                    frame_type
                 )))
             }
+            _ => Err(Http2Error::FrameError(format!(
+                "Could not deserialize Frame: unknown frame type {}",
+                frame_header.frame_type()
+            ))),
+        }
+    }
+
+    /// Serialize a Frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to the HeaderTable used to encode header lists.
+    pub fn serialize(&self, header_table: &mut HeaderTable) -> Result<Vec<u8>, Http2Error> {
+        self.serialize_padded(header_table, Padding::None)
+    }
+
+    /// Serialize a Frame with padding.
+    ///
+    /// The padding policy applies to DATA, HEADERS and PUSH_PROMISE frames,
+    /// the only frames that can be padded, and is ignored for the others.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to the HeaderTable used to encode header lists.
+    /// * `padding` - The padding policy of the frame.
+    pub fn serialize_padded(
+        &self,
+        header_table: &mut HeaderTable,
+        padding: Padding,
+    ) -> Result<Vec<u8>, Http2Error> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_padded_into(header_table, padding, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Serialize a Frame at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to the HeaderTable used to encode header lists.
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(
+        &self,
+        header_table: &mut HeaderTable,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Http2Error> {
+        self.serialize_padded_into(header_table, Padding::None, bytes)
+    }
+
+    /// Serialize a Frame with padding at the end of a byte vector.
+    ///
+    /// Several frames can be serialized in the same byte vector, e.g. to
+    /// build an outgoing TCP segment. If the serialization fails, the byte
+    /// vector is left as it was.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to the HeaderTable used to encode header lists.
+    /// * `padding` - The padding policy of the frame.
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_padded_into(
+        &self,
+        header_table: &mut HeaderTable,
+        padding: Padding,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Http2Error> {
+        let start = bytes.len();
+
+        let result = match self {
+            Frame::Data(frame) => {
+                frame.serialize_into(padding, bytes);
+                Ok(())
+            }
+            Frame::Headers(frame) => frame.serialize_padded_into(header_table, padding, bytes),
+            Frame::Priority(frame) => {
+                frame.serialize_into(bytes);
+                Ok(())
+            }
+            Frame::RstStream(frame) => {
+                frame.serialize_into(bytes);
+                Ok(())
+            }
+            Frame::Settings(frame) => {
+                frame.serialize_into(bytes);
+                Ok(())
+            }
+            Frame::PushPromise(frame) => frame.serialize_padded_into(header_table, padding, bytes),
+            Frame::Ping(frame) => {
+                frame.serialize_into(bytes);
+                Ok(())
+            }
+            Frame::GoAway(frame) => {
+                frame.serialize_into(bytes);
+                Ok(())
+            }
+            Frame::WindowUpdate(frame) => {
+                frame.serialize_into(bytes);
+                Ok(())
+            }
+            Frame::Continuation(frame) => frame.serialize_into(header_table, bytes),
         };
 
-        // Remove the frame from the bytes stream.
-        *stream = stream[9 + frame_header.payload_length() as usize..].to_vec();
+        if result.is_err() {
+            bytes.truncate(start);
+        }
 
-        Ok(frame)
+        result
+    }
+
+    /// Get the frame type.
+    pub fn frame_type(&self) -> u8 {
+        match self {
+            Frame::Data(_) => 0x0,
+            Frame::Headers(_) => 0x1,
+            Frame::Priority(_) => 0x2,
+            Frame::RstStream(_) => 0x3,
+            Frame::Settings(_) => 0x4,
+            Frame::PushPromise(_) => 0x5,
+            Frame::Ping(_) => 0x6,
+            Frame::GoAway(_) => 0x7,
+            Frame::WindowUpdate(_) => 0x8,
+            Frame::Continuation(_) => 0x9,
+        }
+    }
+
+    /// Get the flags of the frame, as they are serialized.
+    ///
+    /// PRIORITY, RST_STREAM, GOAWAY and WINDOW_UPDATE frames define no
+    /// flags.
+    pub fn flags(&self) -> Vec<FrameFlag> {
+        match self {
+            Frame::Data(frame) => frame.flags(),
+            Frame::Headers(frame) => frame.flags(),
+            Frame::Settings(frame) => frame.flags(),
+            Frame::PushPromise(frame) => frame.flags(),
+            Frame::Ping(frame) => frame.flags(),
+            Frame::Continuation(frame) => frame.flags(),
+            Frame::Priority(_)
+            | Frame::RstStream(_)
+            | Frame::GoAway(_)
+            | Frame::WindowUpdate(_) => Vec::new(),
+        }
     }
 }
 
@@ -139,7 +472,10 @@ impl fmt::Display for Frame {
 /// +-+-------------+---------------+-------------------------------+
 /// |R|                 Stream Identifier (31)                      |
 /// +-+-------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+///
+/// [`FrameHeader::new`] does not validate its arguments, a frame header
+/// built with [`FrameHeader::builder`] is checked to fit in the wire format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct FrameHeader {
     payload_length: u32,
     frame_type: u8,
@@ -168,29 +504,85 @@ impl FrameHeader {
         }
     }
 
+    /// Build a new FrameHeader, the other fields being 0 until set.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_type` - The type of the frame.
+    pub fn builder(frame_type: u8) -> FrameHeaderBuilder {
+        FrameHeaderBuilder {
+            frame_header: FrameHeader::new(0, frame_type, 0, false, 0),
+        }
+    }
+
     /// Serialize a FrameHeader.
     pub fn serialize(&self) -> Vec<u8> {
         let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(&mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a FrameHeader at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        bytes.extend_from_slice(&self.to_bytes());
+    }
+
+    /// Get the wire representation of a FrameHeader.
+    ///
+    /// Only the 24 least significant bits of the payload length and the
+    /// 31 least significant bits of the stream identifier are serialized.
+    pub fn to_bytes(&self) -> [u8; FRAME_HEADER_LENGTH] {
+        let mut bytes = [0; FRAME_HEADER_LENGTH];
 
         // Serialize the payload length.
-        bytes.extend_from_slice(&self.payload_length.to_be_bytes()[1..]);
+        bytes[..3].copy_from_slice(&self.payload_length.to_be_bytes()[1..]);
 
         // Serialize the frame type.
-        bytes.push(self.frame_type);
+        bytes[3] = self.frame_type;
 
         // Serialize the frame flags.
-        bytes.push(self.frame_flags);
+        bytes[4] = self.frame_flags;
 
         // Serialize the stream identifier with reserved bit.
-        let mut stream_id: Vec<u8> = self.stream_id.to_be_bytes().to_vec();
+        bytes[5..].copy_from_slice(&(self.stream_id & MAX_STREAM_ID).to_be_bytes());
         if self.reserved {
-            stream_id[0] = stream_id[0] | 0x80;
+            bytes[5] |= 0x80;
         }
-        bytes.extend_from_slice(&stream_id);
 
         bytes
     }
 
+    /// Build a FrameHeader from its wire representation.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The serialized frame header.
+    pub fn from_bytes(bytes: &[u8; FRAME_HEADER_LENGTH]) -> Self {
+        FrameHeader {
+            payload_length: u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]),
+            frame_type: bytes[3],
+            frame_flags: bytes[4],
+            reserved: (bytes[5] >> 7) != 0,
+            stream_id: u32::from_be_bytes([bytes[5] & 0x7F, bytes[6], bytes[7], bytes[8]]),
+        }
+    }
+
+    /// Write the payload length in the header of a serialized frame, for
+    /// frames whose payload is serialized after their header.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The serialized frame, header included.
+    pub(crate) fn write_payload_length(frame: &mut [u8]) {
+        let payload_length = (frame.len() - 9) as u32;
+        frame[..3].copy_from_slice(&payload_length.to_be_bytes()[1..]);
+    }
+
     /// Deserialize a FrameHeader.
     /// 
     /// If the deserialization is successful, the FrameHeader is removed from the bytes vector.
@@ -199,31 +591,90 @@ impl FrameHeader {
     /// 
     /// * `bytes` - A mutable reference to a bytes vector.
     pub fn deserialize(bytes: &mut Vec<u8>) -> Result<Self, Http2Error> {
-        // Check if the bytes stream has at least 9 bytes.
-        if bytes.len() < 9 {
-            return Err(Http2Error::NotEnoughBytes(format!(
-                "Frame header needs at least 9 bytes, found {}",
-                bytes.len()
-            )));
-        }
-
         // Retrieve the frame header fields.
-        let payload_length = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]);
-        let frame_type = bytes[3];
-        let frame_flags = bytes[4];
-        let reserved = (bytes[5] >> 7) != 0;
-        let stream_id: u32 = u32::from_be_bytes([bytes[5] & 0x7F, bytes[6], bytes[7], bytes[8]]);
+        let frame_header = match bytes.first_chunk::<FRAME_HEADER_LENGTH>() {
+            Some(header_bytes) => FrameHeader::from_bytes(header_bytes),
+            None => {
+                return Err(Http2Error::NotEnoughBytes(format!(
+                    "Frame header needs at least 9 bytes, found {}",
+                    bytes.len()
+                )))
+            }
+        };
 
         // Remove the frame header from the bytes stream.
-        *bytes = bytes[9..].to_vec();
+        bytes.drain(..FRAME_HEADER_LENGTH);
 
-        Ok(FrameHeader {
-            payload_length,
-            frame_type,
-            frame_flags,
-            reserved,
-            stream_id,
-        })
+        Ok(frame_header)
+    }
+
+    /// Check that the frame payload does not exceed the maximum frame size.
+    ///
+    /// An endpoint MUST send an error code of FRAME_SIZE_ERROR if a frame
+    /// exceeds the size defined in SETTINGS_MAX_FRAME_SIZE.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_frame_size` - The maximum frame payload size advertised.
+    pub fn check_payload_length(&self, max_frame_size: u32) -> Result<(), Http2Error> {
+        if self.payload_length > max_frame_size {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::FrameSizeError,
+                format!(
+                    "Frame payload of {} bytes exceeds the maximum frame size of {}",
+                    self.payload_length, max_frame_size
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Check that the stream identifier is allowed for the frame type.
+    ///
+    /// DATA, HEADERS, PRIORITY, RST_STREAM and CONTINUATION frames are
+    /// always associated with a stream, and PUSH_PROMISE frames with a
+    /// stream initiated by the client. SETTINGS, PING and GOAWAY frames
+    /// apply to the whole connection and MUST be sent on stream 0.
+    /// WINDOW_UPDATE frames and frames of unknown types may be sent on any
+    /// stream. A violation is a connection error of type PROTOCOL_ERROR.
+    pub fn check_stream_id(&self) -> Result<(), Http2Error> {
+        let valid = match self.frame_type {
+            0x0 | 0x1 | 0x2 | 0x3 | 0x9 => self.stream_id != 0,
+            0x4 | 0x6 | 0x7 => self.stream_id == 0,
+            0x5 => self.stream_id % 2 == 1,
+            _ => true,
+        };
+
+        if !valid {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!(
+                    "Frame of type {} received on invalid stream {}",
+                    self.frame_type, self.stream_id
+                ),
+            ));
+        }
+
+        Ok(())
+    }
+
+    /// Get the flags set on the frame that are not defined for its type.
+    ///
+    /// They MUST be ignored on receipt, but may be reported by diagnostic
+    /// tools.
+    pub fn unknown_flags(&self) -> u8 {
+        self.frame_flags & !defined_flags(self.frame_type)
+    }
+
+    /// Get a copy of the frame header without the unknown flags and with
+    /// the reserved bit unset.
+    fn without_unknown_bits(&self) -> FrameHeader {
+        FrameHeader {
+            frame_flags: self.frame_flags & defined_flags(self.frame_type),
+            reserved: false,
+            ..*self
+        }
     }
 
     pub fn payload_length(&self) -> u32 {
@@ -247,8 +698,81 @@ impl FrameHeader {
     }
 }
 
+/// Builder of a validated HTTP/2 frame header.
+#[derive(Clone, Copy, Debug)]
+pub struct FrameHeaderBuilder {
+    frame_header: FrameHeader,
+}
+
+impl FrameHeaderBuilder {
+    /// Set the length of the frame payload, at most 2^24-1 octets.
+    ///
+    /// # Arguments
+    ///
+    /// * `payload_length` - The length of the frame payload.
+    pub fn payload_length(mut self, payload_length: u32) -> Self {
+        self.frame_header.payload_length = payload_length;
+        self
+    }
+
+    /// Set the flags of the frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_flags` - The flags of the frame.
+    pub fn frame_flags(mut self, frame_flags: u8) -> Self {
+        self.frame_header.frame_flags = frame_flags;
+        self
+    }
+
+    /// Set the reserved bit.
+    ///
+    /// # Arguments
+    ///
+    /// * `reserved` - Reserved bit.
+    pub fn reserved(mut self, reserved: bool) -> Self {
+        self.frame_header.reserved = reserved;
+        self
+    }
+
+    /// Set the stream identifier, at most 2^31-1.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn stream_id(mut self, stream_id: u32) -> Self {
+        self.frame_header.stream_id = stream_id;
+        self
+    }
+
+    /// Build the frame header.
+    ///
+    /// # Errors
+    ///
+    /// * `Http2Error::FrameError` if the payload length or the stream
+    ///   identifier do not fit in the frame header.
+    pub fn build(self) -> Result<FrameHeader, Http2Error> {
+        if self.frame_header.payload_length > MAX_FRAME_SIZE_UPPER_BOUND {
+            return Err(Http2Error::FrameError(format!(
+                "Frame payload length {} exceeds 2^24-1",
+                self.frame_header.payload_length
+            )));
+        }
+
+        if self.frame_header.stream_id > MAX_STREAM_ID {
+            return Err(Http2Error::FrameError(format!(
+                "Stream identifier {} exceeds 2^31-1",
+                self.frame_header.stream_id
+            )));
+        }
+
+        Ok(self.frame_header)
+    }
+}
+
 /// HTTP/2 frame flags.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum FrameFlag {
     EndStream,
     Padded,
@@ -258,7 +782,7 @@ pub enum FrameFlag {
 }
 
 /// HTTP/2 frame priority.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub struct FramePriority {
     exclusive: bool,
     stream_dependency: u32,
@@ -266,6 +790,47 @@ pub struct FramePriority {
 }
 
 impl FramePriority {
+    /// Create a new FramePriority.
+    ///
+    /// # Arguments
+    ///
+    /// * `exclusive` - Whether the stream dependency is exclusive.
+    /// * `stream_dependency` - The identifier of the stream this stream depends on.
+    /// * `weight` - The priority weight of the stream, minus one.
+    pub fn new(exclusive: bool, stream_dependency: u32, weight: u8) -> Self {
+        FramePriority {
+            exclusive,
+            stream_dependency: stream_dependency & 0x7FFF_FFFF,
+            weight,
+        }
+    }
+
+    /// Serialize a FramePriority.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(&mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a FramePriority at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        let mut stream_dependency = self.stream_dependency.to_be_bytes();
+
+        // Set the exclusive bit.
+        if self.exclusive {
+            stream_dependency[0] |= 0x80;
+        }
+        bytes.extend_from_slice(&stream_dependency);
+
+        // Serialize the weight.
+        bytes.push(self.weight);
+    }
+
     /// Deserialize a FramePriority.
     /// 
     /// If the deserialization is successful, the FramePriority is removed from the bytes vector.
@@ -275,7 +840,7 @@ impl FramePriority {
     /// * `bytes` - A mutable reference to a bytes vector.
     pub fn deserialize(bytes: &mut Vec<u8>) -> Result<Self, Http2Error> {
         // Check if the bytes stream has at least 5 bytes.
-        if bytes.len() < 5 {
+        if bytes.len() < FRAME_PRIORITY_LENGTH {
             return Err(Http2Error::NotEnoughBytes(format!(
                 "Frame priority needs at least 5 bytes, found {}",
                 bytes.len()
@@ -288,7 +853,7 @@ impl FramePriority {
         let weight = bytes[4];
 
         // Remove the frame priority from the bytes stream.
-        *bytes = bytes[5..].to_vec();
+        *bytes = bytes[FRAME_PRIORITY_LENGTH..].to_vec();
 
         Ok(FramePriority {
             exclusive,
@@ -313,8 +878,8 @@ impl FramePriority {
 impl fmt::Display for FramePriority {
     /// Format a Frame priority.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "Exclusive: {}\n", self.exclusive)?;
-        write!(f, "Stream Dependency: {}\n", self.stream_dependency)?;
-        write!(f, "Weight: {}\n", self.weight)
+        writeln!(f, "Exclusive: {}", self.exclusive)?;
+        writeln!(f, "Stream Dependency: {}", self.stream_dependency)?;
+        writeln!(f, "Weight: {}", self.weight)
     }
 }
@@ -0,0 +1,140 @@
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::error::{ErrorCode, Http2Error};
+
+/// Padding policy of the DATA, HEADERS and PUSH_PROMISE frames sent.
+///
+/// Padding obscures the size of frame payloads to mitigate traffic
+/// analysis. A padded frame carries a Pad Length octet followed by the
+/// payload and the given number of zero octets, all of them counting
+/// toward the frame size and, for DATA frames, toward flow control.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Padding {
+    /// Frames are not padded.
+    #[default]
+    None,
+    /// Frames are padded with a fixed number of octets.
+    Fixed(u8),
+    /// Frames are padded with a random number of octets, from 0 up to the
+    /// given maximum.
+    Random(u8),
+}
+
+impl Padding {
+    /// Get the pad length of the next frame, `None` if it is not padded.
+    ///
+    /// Random pad lengths are drawn from the per-process random keys of the
+    /// standard library hasher. They are unpredictable enough to hide
+    /// payload sizes but must not be used for cryptographic purposes.
+    pub fn pad_length(&self) -> Option<u8> {
+        match *self {
+            Padding::None => None,
+            Padding::Fixed(pad_length) => Some(pad_length),
+            Padding::Random(max_pad_length) => {
+                let random = RandomState::new().build_hasher().finish();
+                Some((random % (max_pad_length as u64 + 1)) as u8)
+            }
+        }
+    }
+}
+
+/// Serialize the Pad Length octet starting a frame payload, if the frame
+/// is padded.
+///
+/// # Arguments
+///
+/// * `bytes` - The byte vector to serialize into.
+/// * `pad_length` - The number of padding octets, `None` if not padded.
+pub(crate) fn serialize_pad_length(bytes: &mut Vec<u8>, pad_length: Option<u8>) {
+    if let Some(pad_length) = pad_length {
+        bytes.push(pad_length);
+    }
+}
+
+/// Serialize the padding ending a frame payload, if the frame is padded.
+///
+/// # Arguments
+///
+/// * `bytes` - The byte vector to serialize into.
+/// * `pad_length` - The number of padding octets, `None` if not padded.
+pub(crate) fn serialize_padding(bytes: &mut Vec<u8>, pad_length: Option<u8>) {
+    if let Some(pad_length) = pad_length {
+        bytes.resize(bytes.len() + pad_length as usize, 0);
+    }
+}
+
+/// Check that a frame payload is long enough for its Pad Length octet, if
+/// the frame is padded, and for the fields preceding its content.
+///
+/// A shorter payload is a connection error of type FRAME_SIZE_ERROR.
+///
+/// # Arguments
+///
+/// * `payload` - The frame payload.
+/// * `padded` - Whether the frame has the PADDED flag.
+/// * `fields_length` - The length of the fields preceding the content.
+/// * `frame_type` - The name of the frame type, for the error message.
+pub(crate) fn check_fields_length(
+    payload: &[u8],
+    padded: bool,
+    fields_length: usize,
+    frame_type: &str,
+) -> Result<(), Http2Error> {
+    let min_length = padded as usize + fields_length;
+    if payload.len() < min_length {
+        return Err(Http2Error::ConnectionError(
+            ErrorCode::FrameSizeError,
+            format!(
+                "{} frame payload must be at least {} bytes, found {}",
+                frame_type,
+                min_length,
+                payload.len()
+            ),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Remove the padding of a frame payload.
+///
+/// The payload starts with the Pad Length octet, followed by the fields of
+/// the frame, its content and the padding. The fields are kept at the
+/// start of the payload. A pad length of 0 is legal, while padding
+/// overlapping the Pad Length octet or the fields is a connection error of
+/// type PROTOCOL_ERROR.
+///
+/// # Arguments
+///
+/// * `payload` - The padded payload, replaced by its fields and content.
+/// * `fields_length` - The length of the fields preceding the content.
+pub(crate) fn unpad(payload: &mut Vec<u8>, fields_length: usize) -> Result<(), Http2Error> {
+    let pad_length = match payload.first() {
+        Some(&pad_length) => pad_length as usize,
+        None => {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                "Padded frame without a Pad Length".to_string(),
+            ))
+        }
+    };
+
+    if pad_length + fields_length >= payload.len() {
+        return Err(Http2Error::ConnectionError(
+            ErrorCode::ProtocolError,
+            format!(
+                "Padding length {} exceeds a payload of {} bytes with {} bytes of fields",
+                pad_length,
+                payload.len(),
+                fields_length
+            ),
+        ));
+    }
+
+    payload.truncate(payload.len() - pad_length);
+    payload.remove(0);
+
+    Ok(())
+}
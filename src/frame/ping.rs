@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::error::Http2Error;
+use crate::error::{ErrorCode, Http2Error};
 use crate::frame::{FrameFlag, FrameHeader};
 
 /// PING Frame.
@@ -15,13 +15,63 @@ use crate::frame::{FrameFlag, FrameHeader};
 /// |                      Opaque Data (64)                         |
 /// |                                                               |
 /// +---------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PingFrame {
     ack: bool,
-    opaque_data: Vec<u8>,
+    opaque_data: [u8; 8],
 }
 
 impl PingFrame {
+    /// Create a new PING frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `opaque_data` - The data to be echoed by the peer.
+    pub fn new(opaque_data: [u8; 8]) -> Self {
+        PingFrame {
+            ack: false,
+            opaque_data,
+        }
+    }
+
+    /// Create a PING frame acknowledging a received PING frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `opaque_data` - The opaque data of the received PING frame.
+    pub fn ack(opaque_data: [u8; 8]) -> Self {
+        PingFrame {
+            ack: true,
+            opaque_data,
+        }
+    }
+
+    /// Serialize a PING frame.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(&mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a PING frame at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        // Build the flags bit.
+        let mut frame_flags: u8 = 0x0;
+        if self.ack {
+            frame_flags |= 0x01;
+        }
+
+        // Serialize the frame.
+        FrameHeader::new(self.opaque_data.len() as u32, 0x6, frame_flags, false, 0)
+            .serialize_into(bytes);
+        bytes.extend_from_slice(&self.opaque_data);
+    }
+
     /// Deserialize the flags from a byte.
     /// 
     /// # Arguments
@@ -47,7 +97,7 @@ impl PingFrame {
     /// * `bytes` - A mutable reference to a bytes vector.
     pub fn deserialize(
         frame_header: &FrameHeader,
-        bytes: &mut Vec<u8>,
+        bytes: &mut [u8],
     ) -> Result<Self, Http2Error> {
         // Check if the bytes has the right length.
         if bytes.len() != frame_header.payload_length() as usize {
@@ -58,21 +108,54 @@ impl PingFrame {
             )));
         }
 
+        // A PING frame carries exactly 8 octets of opaque data.
+        let opaque_data: [u8; 8] = match bytes.try_into() {
+            Ok(opaque_data) => opaque_data,
+            Err(_) => {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::FrameSizeError,
+                    format!("PING frame payload must be 8 bytes, found {}", bytes.len()),
+                ))
+            }
+        };
+
         // Deserialize the flags from the header.
         let flags: Vec<FrameFlag> = PingFrame::deserialize_flags(frame_header.frame_flags());
 
         Ok(PingFrame {
             ack: flags.contains(&FrameFlag::Ack),
-            opaque_data: bytes[0..8].to_vec(),
+            opaque_data,
         })
     }
+
+    /// Check if the frame acknowledges a PING frame.
+    pub fn is_ack(&self) -> bool {
+        self.ack
+    }
+
+    /// Get the flags of the frame, as they are serialized.
+    ///
+    /// The flags of a decoded frame are those it was received with.
+    pub fn flags(&self) -> Vec<FrameFlag> {
+        let mut frame_flags = Vec::new();
+
+        if self.ack {
+            frame_flags.push(FrameFlag::Ack);
+        }
+
+        frame_flags
+    }
+
+    pub fn opaque_data(&self) -> &[u8; 8] {
+        &self.opaque_data
+    }
 }
 
 impl fmt::Display for PingFrame {
     /// Format a PING frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "PING\n")?;
-        write!(f, "Ack: {}\n", self.ack)?;
-        write!(f, "Opaque Data: {:?}\n", self.opaque_data)
+        writeln!(f, "PING")?;
+        writeln!(f, "Ack: {}", self.ack)?;
+        writeln!(f, "Opaque Data: {:?}", self.opaque_data)
     }
 }
@@ -14,7 +14,7 @@ use crate::frame::{FrameHeader, FramePriority};
 /// +-+-------------+-----------------------------------------------+
 /// |   Weight (8)  |
 /// +-+-------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 
 pub struct PriorityFrame {
     stream_id: u32,
@@ -22,6 +22,47 @@ pub struct PriorityFrame {
 }
 
 impl PriorityFrame {
+    /// Create a new PRIORITY frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The identifier of the prioritized stream.
+    /// * `frame_priority` - The priority block of the stream.
+    pub fn new(stream_id: u32, frame_priority: FramePriority) -> Self {
+        PriorityFrame {
+            stream_id,
+            frame_priority,
+        }
+    }
+
+    /// Serialize a PRIORITY frame.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(&mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a PRIORITY frame at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        // Serialize the frame.
+        FrameHeader::new(5, 0x2, 0x0, false, self.stream_id).serialize_into(bytes);
+        self.frame_priority.serialize_into(bytes);
+    }
+
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    /// Get the priority block of the frame.
+    pub fn frame_priority(&self) -> &FramePriority {
+        &self.frame_priority
+    }
+
     /// Deserialize a PRIORITY frame.
     /// 
     /// The operation is destructive for the bytes vector.
@@ -53,14 +94,14 @@ impl PriorityFrame {
 impl fmt::Display for PriorityFrame {
     /// Format a PRIORITY frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "PRIORITY\n")?;
-        write!(f, "Stream Identifier: {}\n", self.stream_id)?;
-        write!(f, "Exclusive: {}\n", self.frame_priority.exclusive())?;
-        write!(
+        writeln!(f, "PRIORITY")?;
+        writeln!(f, "Stream Identifier: {}", self.stream_id)?;
+        writeln!(f, "Exclusive: {}", self.frame_priority.exclusive())?;
+        writeln!(
             f,
-            "Stream Dependency: {}\n",
+            "Stream Dependency: {}",
             self.frame_priority.stream_dependency()
         )?;
-        write!(f, "Weight: {}\n", self.frame_priority.weight())
+        writeln!(f, "Weight: {}", self.frame_priority.weight())
     }
 }
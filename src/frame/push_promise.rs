@@ -1,10 +1,15 @@
 use std::fmt;
 
-use crate::error::Http2Error;
+use crate::error::{ErrorCode, Http2Error};
+use crate::frame::continuation::ContinuationFrame;
+use crate::frame::padding::{self, Padding};
 use crate::frame::{FrameFlag, FrameHeader};
 use crate::header::list::HeaderList;
 use crate::header::table::HeaderTable;
 
+/// Size of the Promised Stream ID field in octets.
+const PROMISED_STREAM_ID_LENGTH: usize = 4;
+
 /// PUSH_PROMISE Frame.
 ///
 /// The PUSH_PROMISE frame (type=0x5) is used to notify the peer endpoint
@@ -22,16 +27,125 @@ use crate::header::table::HeaderTable;
 /// +---------------------------------------------------------------+
 /// |                           Padding (*)                       ...
 /// +---------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PushPromiseFrame {
     stream_id: u32,
     end_headers: bool,
     reserved: bool,
     promised_stream_id: u32,
     header_list: HeaderList,
+    header_block_fragment: Option<Vec<u8>>,
+    pad_length: Option<u8>,
 }
 
 impl PushPromiseFrame {
+    /// Create a new PUSH_PROMISE frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The identifier of the stream the promise is associated with.
+    /// * `end_headers` - Whether the frame contains the entire header block.
+    /// * `promised_stream_id` - The identifier of the reserved stream.
+    /// * `header_list` - The request header list of the promised stream.
+    pub fn new(
+        stream_id: u32,
+        end_headers: bool,
+        promised_stream_id: u32,
+        header_list: HeaderList,
+    ) -> Self {
+        PushPromiseFrame {
+            stream_id,
+            end_headers,
+            reserved: false,
+            promised_stream_id,
+            header_list,
+            header_block_fragment: None,
+            pad_length: None,
+        }
+    }
+
+    /// Serialize a PUSH_PROMISE frame.
+    ///
+    /// The header table is updated with the encoded header fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    pub fn serialize(&self, header_table: &mut HeaderTable) -> Result<Vec<u8>, Http2Error> {
+        self.serialize_padded(header_table, Padding::None)
+    }
+
+    /// Serialize a PUSH_PROMISE frame with padding.
+    ///
+    /// The header table is updated with the encoded header fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    /// * `padding` - The padding policy of the frame.
+    pub fn serialize_padded(
+        &self,
+        header_table: &mut HeaderTable,
+        padding: Padding,
+    ) -> Result<Vec<u8>, Http2Error> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_padded_into(header_table, padding, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Serialize a PUSH_PROMISE frame with padding at the end of a byte vector.
+    ///
+    /// The header table is updated with the encoded header fields.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    /// * `padding` - The padding policy of the frame.
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_padded_into(
+        &self,
+        header_table: &mut HeaderTable,
+        padding: Padding,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Http2Error> {
+        let start = bytes.len();
+        let pad_length = match padding {
+            Padding::None => self.pad_length,
+            padding => padding.pad_length(),
+        };
+
+        // Build the flags bit.
+        let mut frame_flags: u8 = 0x0;
+        if self.end_headers {
+            frame_flags |= 0x04;
+        }
+        if pad_length.is_some() {
+            frame_flags |= 0x08;
+        }
+
+        // Serialize the header, its payload length is written once the
+        // payload is.
+        FrameHeader::new(0, 0x5, frame_flags, false, self.stream_id).serialize_into(bytes);
+
+        // Serialize the payload.
+        padding::serialize_pad_length(bytes, pad_length);
+        let mut promised_stream_id = self.promised_stream_id.to_be_bytes();
+        if self.reserved {
+            promised_stream_id[0] |= 0x80;
+        }
+        bytes.extend_from_slice(&promised_stream_id);
+        match &self.header_block_fragment {
+            Some(header_block_fragment) => bytes.extend_from_slice(header_block_fragment),
+            None => self.header_list.encode_into(header_table, bytes)?,
+        }
+        padding::serialize_padding(bytes, pad_length);
+
+        FrameHeader::write_payload_length(&mut bytes[start..]);
+
+        Ok(())
+    }
+
     /// Deserialize the flags from a byte.
     /// 
     /// # Arguments
@@ -78,24 +192,36 @@ impl PushPromiseFrame {
         let frame_flags: Vec<FrameFlag> =
             PushPromiseFrame::deserialize_flags(frame_header.frame_flags());
 
-        // Handle the padding if needed.
-        if frame_flags.contains(&FrameFlag::Padded) {
-            let pad_length = bytes[0] as usize;
+        // The payload holds the Pad Length, if present, and the promised
+        // stream identifier before the header block fragment.
+        let padded = frame_flags.contains(&FrameFlag::Padded);
+        padding::check_fields_length(bytes, padded, PROMISED_STREAM_ID_LENGTH, "PUSH_PROMISE")?;
 
-            // Check that the padding length is not 0.
-            if pad_length == 0 {
-                return Err(Http2Error::FrameError(
-                    "Padding length invalid: found 0".to_string(),
-                ));
-            }
-            *bytes = bytes[1..frame_header.payload_length() as usize - pad_length].to_vec();
+        // Handle the padding if needed, it may not overlap the promised
+        // stream identifier.
+        let mut pad_length: Option<u8> = None;
+        if padded {
+            pad_length = bytes.first().copied();
+            padding::unpad(bytes, PROMISED_STREAM_ID_LENGTH)?;
         }
 
         // Deserialize the promise parameters.
         let reserved: bool = (bytes[0] >> 7) != 0;
         let promised_stream_id: u32 =
             u32::from_be_bytes([bytes[0] & 0x7F, bytes[1], bytes[2], bytes[3]]);
-        let header_list: HeaderList = HeaderList::decode(&mut bytes[4..].to_vec(), header_table)?;
+        let (header_list, header_block_fragment) = match header_table.pass_through() {
+            true => (
+                HeaderList::default(),
+                Some(bytes[PROMISED_STREAM_ID_LENGTH..].to_vec()),
+            ),
+            false => (
+                HeaderList::decode(
+                    &mut bytes[PROMISED_STREAM_ID_LENGTH..].to_vec(),
+                    header_table,
+                )?,
+                None,
+            ),
+        };
 
         Ok(Self {
             stream_id: frame_header.stream_id(),
@@ -103,20 +229,190 @@ impl PushPromiseFrame {
             reserved,
             promised_stream_id,
             header_list,
+            header_block_fragment,
+            pad_length,
         })
     }
+
+    /// Get the identifier of the stream the promise is associated with.
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    /// Check if the frame ends its header block, that is if its
+    /// END_HEADERS flag is set or once the CONTINUATION frame ending the
+    /// header block was appended.
+    pub fn end_headers(&self) -> bool {
+        self.end_headers
+    }
+
+    /// Get the identifier of the stream reserved for the pushed response.
+    pub fn promised_stream_id(&self) -> u32 {
+        self.promised_stream_id
+    }
+
+    /// Get the header list of the promised request, empty until the header
+    /// block is decoded.
+    pub fn header_list(&self) -> &HeaderList {
+        &self.header_list
+    }
+
+    /// Get the header block fragment of a frame decoded with a
+    /// pass-through header table, until it is decoded.
+    pub fn header_block_fragment(&self) -> Option<&[u8]> {
+        self.header_block_fragment.as_deref()
+    }
+
+    /// Decode the header block fragment kept by a pass-through header
+    /// table into the header list of the frame.
+    ///
+    /// Header blocks MUST be decoded in the order they were received, with
+    /// the decoding table of the connection. The frame is left unchanged
+    /// if its header list is already decoded.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    pub fn decode_header_block(
+        &mut self,
+        header_table: &mut HeaderTable,
+    ) -> Result<(), Http2Error> {
+        // The header block fragment is kept if it fails to decode.
+        if let Some(header_block_fragment) = &mut self.header_block_fragment {
+            self.header_list = HeaderList::decode(header_block_fragment, header_table)?;
+            self.header_block_fragment = None;
+        }
+
+        Ok(())
+    }
+
+    /// Append the header block fragment of the CONTINUATION frame following
+    /// the frame, both decoded with a pass-through header table.
+    ///
+    /// The frame ends its header block if the CONTINUATION frame does, so
+    /// that a header block split over several frames is decoded at once
+    /// with [`PushPromiseFrame::decode_header_block`]: a header field may be split
+    /// anywhere across the frames. A CONTINUATION frame on another stream,
+    /// or following the end of the header block, fails with PROTOCOL_ERROR.
+    ///
+    /// # Arguments
+    ///
+    /// * `continuation_frame` - The CONTINUATION frame following the frame.
+    pub fn append_continuation(
+        &mut self,
+        continuation_frame: &ContinuationFrame,
+    ) -> Result<(), Http2Error> {
+        match (
+            &mut self.header_block_fragment,
+            continuation_frame.header_block_fragment(),
+        ) {
+            (Some(header_block_fragment), Some(fragment))
+                if !self.end_headers && continuation_frame.stream_id() == self.stream_id =>
+            {
+                header_block_fragment.extend_from_slice(fragment);
+                self.end_headers = continuation_frame.end_headers();
+                Ok(())
+            }
+            _ => Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!(
+                    "CONTINUATION frame does not continue the header block of stream {}",
+                    self.stream_id
+                ),
+            )),
+        }
+    }
+
+    /// Split the header block of the frame so that no frame exceeds a
+    /// maximum payload size, returning the CONTINUATION frames to send
+    /// right after it.
+    ///
+    /// The header list is encoded with the header table, unless the frame
+    /// already carries its header block, so that the frames MUST be sent
+    /// before any other header block encoded with the same table. The frame
+    /// keeps the beginning of the header block, and only the last frame
+    /// has the END_HEADERS flag.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_frame_size` - The maximum frame payload size of the peer.
+    /// * `header_table` - The encoding table of the connection.
+    pub fn split_header_block(
+        &mut self,
+        max_frame_size: u32,
+        header_table: &mut HeaderTable,
+    ) -> Result<Vec<ContinuationFrame>, Http2Error> {
+        let mut header_block = match self.header_block_fragment.take() {
+            Some(header_block_fragment) => header_block_fragment,
+            None => self.header_list.encode(header_table)?,
+        };
+
+        // The fields preceding the header block share the frame payload.
+        let fields_length = PROMISED_STREAM_ID_LENGTH
+            + self
+                .pad_length
+                .map_or(0, |pad_length| 1 + pad_length as usize);
+        let length = (max_frame_size as usize)
+            .saturating_sub(fields_length)
+            .min(header_block.len());
+        let continuation_frames = ContinuationFrame::from_header_block(
+            self.stream_id,
+            &header_block.split_off(length),
+            max_frame_size,
+        );
+
+        self.end_headers = continuation_frames.is_empty();
+        self.header_block_fragment = Some(header_block);
+
+        Ok(continuation_frames)
+    }
+
+    /// Get the number of padding octets the frame was received with, if it
+    /// was padded.
+    ///
+    /// A frame serialized without a padding policy keeps this padding.
+    pub fn pad_length(&self) -> Option<u8> {
+        self.pad_length
+    }
+
+    /// Set the number of padding octets of the frame, `None` if it is not
+    /// padded.
+    ///
+    /// # Arguments
+    ///
+    /// * `pad_length` - The number of padding octets.
+    pub fn set_pad_length(&mut self, pad_length: Option<u8>) {
+        self.pad_length = pad_length;
+    }
+
+    /// Get the flags of the frame, as they are serialized.
+    ///
+    /// The flags of a decoded frame are those it was received with.
+    pub fn flags(&self) -> Vec<FrameFlag> {
+        let mut frame_flags = Vec::new();
+
+        if self.end_headers {
+            frame_flags.push(FrameFlag::EndHeaders);
+        }
+
+        if self.pad_length.is_some() {
+            frame_flags.push(FrameFlag::Padded);
+        }
+
+        frame_flags
+    }
 }
 
 impl fmt::Display for PushPromiseFrame {
     /// Format a  PUSH_PROMISE frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "PUSH_PROMISE\n")?;
-        write!(f, "Stream Identifier: {}\n", self.stream_id)?;
-        write!(f, "End Headers: {}\n", self.end_headers)?;
-        write!(f, "Reserved: {}\n", self.reserved)?;
-        write!(
+        writeln!(f, "PUSH_PROMISE")?;
+        writeln!(f, "Stream Identifier: {}", self.stream_id)?;
+        writeln!(f, "End Headers: {}", self.end_headers)?;
+        writeln!(f, "Reserved: {}", self.reserved)?;
+        writeln!(
             f,
-            "Promised Stream Identifier: {}\n",
+            "Promised Stream Identifier: {}",
             self.promised_stream_id
         )?;
         write!(f, "Header List:\n{}", self.header_list)
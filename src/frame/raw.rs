@@ -0,0 +1,109 @@
+use crate::error::Http2Error;
+use crate::frame::{has_header_block, DecodeStatus, Frame, FrameHeader, FRAME_HEADER_LENGTH};
+use crate::header::table::HeaderTable;
+
+/// Frame whose payload is not decoded yet.
+///
+/// Decoding a frame takes two phases: the frame is first split from the
+/// bytes stream with [`RawFrame::decode`], without any HPACK state, then its
+/// payload is decoded. Frames without a header block, such as PING or
+/// SETTINGS frames, are decoded with [`RawFrame::into_frame`], while
+/// HEADERS, PUSH_PROMISE and CONTINUATION frames need the HPACK decoding
+/// table of the connection, passed to [`RawFrame::decode_header_block`].
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct RawFrame {
+    frame_header: FrameHeader,
+    payload: Vec<u8>,
+}
+
+impl RawFrame {
+    /// Split the first frame of a bytes stream.
+    ///
+    /// The frame is removed from the stream. When the stream does not hold
+    /// a complete frame, it is left untouched and the number of missing
+    /// bytes is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A mutable reference to a bytes vector.
+    pub fn decode(stream: &mut Vec<u8>) -> Result<DecodeStatus<RawFrame>, Http2Error> {
+        // Wait for a complete frame header.
+        let frame_header = match stream.first_chunk() {
+            Some(header_bytes) => FrameHeader::from_bytes(header_bytes),
+            None => {
+                return Ok(DecodeStatus::NeedMoreData(
+                    FRAME_HEADER_LENGTH - stream.len(),
+                ))
+            }
+        };
+
+        // Wait for a complete frame payload.
+        let frame_length = FRAME_HEADER_LENGTH + frame_header.payload_length() as usize;
+        if stream.len() < frame_length {
+            return Ok(DecodeStatus::NeedMoreData(frame_length - stream.len()));
+        }
+
+        let payload = stream[FRAME_HEADER_LENGTH..frame_length].to_vec();
+        stream.drain(..frame_length);
+
+        Ok(DecodeStatus::Complete(RawFrame {
+            frame_header,
+            payload,
+        }))
+    }
+
+    /// Split the first frame of a bytes stream.
+    ///
+    /// Missing bytes are reported as a [`Http2Error::NotEnoughBytes`] error.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - A mutable reference to a bytes vector.
+    pub fn deserialize(stream: &mut Vec<u8>) -> Result<RawFrame, Http2Error> {
+        match RawFrame::decode(stream)? {
+            DecodeStatus::Complete(raw_frame) => Ok(raw_frame),
+            DecodeStatus::NeedMoreData(missing) => Err(Http2Error::NotEnoughBytes(format!(
+                "Frame needs {} more bytes, found {}",
+                missing,
+                stream.len(),
+            ))),
+        }
+    }
+
+    /// Get the header of the frame.
+    pub fn frame_header(&self) -> &FrameHeader {
+        &self.frame_header
+    }
+
+    /// Get the payload of the frame.
+    pub fn payload(&self) -> &[u8] {
+        &self.payload
+    }
+
+    /// Check if the frame carries a header block fragment.
+    pub fn has_header_block(&self) -> bool {
+        has_header_block(self.frame_header.frame_type())
+    }
+
+    /// Decode a frame without a header block.
+    ///
+    /// HEADERS, PUSH_PROMISE and CONTINUATION frames fail with a
+    /// [`Http2Error::FrameError`], they are decoded with
+    /// [`RawFrame::decode_header_block`].
+    pub fn into_frame(mut self) -> Result<Frame, Http2Error> {
+        Frame::deserialize_frame(&self.frame_header, &mut self.payload, None)
+    }
+
+    /// Decode a frame, its header block fragment, if any, being decoded with
+    /// the HPACK decoding table of the connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - A mutable reference to a HeaderTable.
+    pub fn decode_header_block(
+        mut self,
+        header_table: &mut HeaderTable,
+    ) -> Result<Frame, Http2Error> {
+        Frame::deserialize_frame(&self.frame_header, &mut self.payload, Some(header_table))
+    }
+}
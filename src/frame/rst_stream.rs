@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::error::Http2Error;
+use crate::error::{ErrorCode, Http2Error};
 use crate::frame::FrameHeader;
 
 /// RST_STREAM Frame.
@@ -12,13 +12,45 @@ use crate::frame::FrameHeader;
 /// +---------------------------------------------------------------+
 /// |                        Error Code (32)                        |
 /// +---------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RstStreamFrame {
     pub stream_id: u32,
     pub error_code: u32,
 }
 
 impl RstStreamFrame {
+    /// Create a new RST_STREAM frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The identifier of the stream to terminate.
+    /// * `error_code` - The reason for terminating the stream.
+    pub fn new(stream_id: u32, error_code: ErrorCode) -> Self {
+        RstStreamFrame {
+            stream_id,
+            error_code: error_code.into(),
+        }
+    }
+
+    /// Serialize a RST_STREAM frame.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(&mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a RST_STREAM frame at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        // Serialize the frame.
+        FrameHeader::new(4, 0x3, 0x0, false, self.stream_id).serialize_into(bytes);
+        bytes.extend_from_slice(&self.error_code.to_be_bytes());
+    }
+
     /// Deserialize a RST_STREAM frame.
     /// 
     /// The operation is destructive for the bytes vector.
@@ -55,8 +87,8 @@ impl RstStreamFrame {
 impl fmt::Display for RstStreamFrame {
     /// Format a RST_STREAM frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "RST_STREAM\n")?;
-        write!(f, "Stream Identifier: {}\n", self.stream_id)?;
-        write!(f, "Error Code: {}\n", self.error_code)
+        writeln!(f, "RST_STREAM")?;
+        writeln!(f, "Stream Identifier: {}", self.stream_id)?;
+        writeln!(f, "Error Code: {}", self.error_code)
     }
 }
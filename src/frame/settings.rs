@@ -1,10 +1,11 @@
 use std::fmt;
 
-use crate::error::Http2Error;
+use crate::error::{ErrorCode, Http2Error};
 use crate::frame::{FrameFlag, FrameHeader};
 
 /// SETTINGS Frame parameters.
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
 pub enum SettingsParameter {
     HeaderTableSize(u32),
     EnablePush(u32),
@@ -12,9 +13,38 @@ pub enum SettingsParameter {
     InitialWindowSize(u32),
     MaxFrameSize(u32),
     MaxHeaderListSize(u32),
+    EnableConnectProtocol(u32),
 }
 
 impl SettingsParameter {
+    /// Serialize a SETTINGS Frame parameter.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(&mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a SETTINGS Frame parameter at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        let (parameter_id, parameter_value): (u16, u32) = match self {
+            Self::HeaderTableSize(value) => (0x1, *value),
+            Self::EnablePush(value) => (0x2, *value),
+            Self::MaxConcurrentStreams(value) => (0x3, *value),
+            Self::InitialWindowSize(value) => (0x4, *value),
+            Self::MaxFrameSize(value) => (0x5, *value),
+            Self::MaxHeaderListSize(value) => (0x6, *value),
+            Self::EnableConnectProtocol(value) => (0x8, *value),
+        };
+
+        bytes.extend_from_slice(&parameter_id.to_be_bytes());
+        bytes.extend_from_slice(&parameter_value.to_be_bytes());
+    }
+
     /// Deserialize a SETTINGS Frame parameter.
     /// 
     /// # Arguments
@@ -29,6 +59,7 @@ impl SettingsParameter {
             0x4 => Ok(Self::InitialWindowSize(parameter_value)),
             0x5 => Ok(Self::MaxFrameSize(parameter_value)),
             0x6 => Ok(Self::MaxHeaderListSize(parameter_value)),
+            0x8 => Ok(Self::EnableConnectProtocol(parameter_value)),
             _ => Err(Http2Error::FrameError(format!(
                 "Invalid SETTINGS parameter: {}",
                 parameter_id
@@ -53,6 +84,9 @@ impl fmt::Display for SettingsParameter {
             SettingsParameter::MaxHeaderListSize(value) => {
                 write!(f, "Max Header List Size: {}", value)
             }
+            SettingsParameter::EnableConnectProtocol(value) => {
+                write!(f, "Enable Connect Protocol: {}", value)
+            }
         }
     }
 }
@@ -68,13 +102,71 @@ impl fmt::Display for SettingsParameter {
 /// +-------------------------------+-------------------------------+
 /// |                        Value (32)                             |
 /// +---------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct SettingsFrame {
     ack: bool,
     settings_parameters: Vec<SettingsParameter>,
 }
 
 impl SettingsFrame {
+    /// Create a new SETTINGS frame without parameters.
+    ///
+    /// Parameters are added with [`SettingsFrame::with_parameter`].
+    pub fn new() -> Self {
+        SettingsFrame {
+            ack: false,
+            settings_parameters: Vec::new(),
+        }
+    }
+
+    /// Create a SETTINGS frame acknowledging the peer SETTINGS.
+    pub fn ack() -> Self {
+        SettingsFrame {
+            ack: true,
+            settings_parameters: Vec::new(),
+        }
+    }
+
+    /// Add a parameter to the frame.
+    ///
+    /// Parameters are processed by the peer in the order they are added.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings_parameter` - The parameter to add.
+    pub fn with_parameter(mut self, settings_parameter: SettingsParameter) -> Self {
+        self.settings_parameters.push(settings_parameter);
+        self
+    }
+
+    /// Serialize a SETTINGS frame.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(&mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a SETTINGS frame at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        // Build the flags bit.
+        let mut frame_flags: u8 = 0x0;
+        if self.ack {
+            frame_flags |= 0x01;
+        }
+
+        // Serialize the frame.
+        let payload_length = 6 * self.settings_parameters.len() as u32;
+        FrameHeader::new(payload_length, 0x4, frame_flags, false, 0).serialize_into(bytes);
+        for settings_parameter in &self.settings_parameters {
+            settings_parameter.serialize_into(bytes);
+        }
+    }
+
     /// Deserialize the flags from a byte.
     /// 
     /// # Arguments
@@ -112,7 +204,7 @@ impl SettingsFrame {
         }
 
         // Check that the payload length is valid.
-        if frame_header.payload_length() % 6 != 0 {
+        if !frame_header.payload_length().is_multiple_of(6) {
             return Err(Http2Error::FrameError(format!(
                 "Invalid length for SETTINGS parameter: {}",
                 bytes.len()
@@ -122,35 +214,74 @@ impl SettingsFrame {
         // Deserialize the flags from the header.
         let flags: Vec<FrameFlag> = SettingsFrame::deserialize_flags(frame_header.frame_flags());
 
+        // An acknowledgement carries no parameters.
+        if flags.contains(&FrameFlag::Ack) && frame_header.payload_length() != 0 {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::FrameSizeError,
+                format!(
+                    "SETTINGS acknowledgement with a payload of {} bytes",
+                    frame_header.payload_length()
+                ),
+            ));
+        }
+
         // Deserialize the parameters.
         let mut settings_parameters: Vec<SettingsParameter> = Vec::new();
 
-        while settings_parameters.len() != (frame_header.payload_length() / 6) as usize {
-            let parameter_id = u16::from_be_bytes([bytes[0], bytes[1]]);
-            let parameter_value = u32::from_be_bytes([bytes[2], bytes[3], bytes[4], bytes[5]]);
-
-            // Remove the parameter from the bytes stream.
-            *bytes = bytes[6..].to_vec();
+        for parameter in bytes.chunks_exact(6) {
+            let parameter_id = u16::from_be_bytes([parameter[0], parameter[1]]);
+            let parameter_value =
+                u32::from_be_bytes([parameter[2], parameter[3], parameter[4], parameter[5]]);
 
-            // Check that the parameter is valid.
-            settings_parameters.push(SettingsParameter::deserialize(
-                parameter_id,
-                parameter_value,
-            )?);
+            // Parameters with an unknown identifier MUST be ignored.
+            if let Ok(settings_parameter) =
+                SettingsParameter::deserialize(parameter_id, parameter_value)
+            {
+                settings_parameters.push(settings_parameter);
+            }
         }
+        bytes.clear();
 
         Ok(Self {
             ack: flags.contains(&FrameFlag::Ack),
             settings_parameters,
         })
     }
+
+    /// Check if the frame acknowledges the peer SETTINGS.
+    pub fn is_ack(&self) -> bool {
+        self.ack
+    }
+
+    /// Get the flags of the frame, as they are serialized.
+    ///
+    /// The flags of a decoded frame are those it was received with.
+    pub fn flags(&self) -> Vec<FrameFlag> {
+        let mut frame_flags = Vec::new();
+
+        if self.ack {
+            frame_flags.push(FrameFlag::Ack);
+        }
+
+        frame_flags
+    }
+
+    pub fn settings_parameters(&self) -> &Vec<SettingsParameter> {
+        &self.settings_parameters
+    }
+}
+
+impl Default for SettingsFrame {
+    fn default() -> Self {
+        SettingsFrame::new()
+    }
 }
 
 impl fmt::Display for SettingsFrame {
     /// Format a SETTINGS frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "SETTINGS\n")?;
-        write!(f, "Ack: {}\n", self.ack)?;
+        writeln!(f, "SETTINGS")?;
+        writeln!(f, "Ack: {}", self.ack)?;
         write!(f, "Parameters: {:?}", self.settings_parameters)
     }
 }
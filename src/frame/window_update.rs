@@ -1,6 +1,6 @@
 use std::fmt;
 
-use crate::error::Http2Error;
+use crate::error::{ErrorCode, Http2Error};
 use crate::frame::FrameHeader;
 
 /// WINDOW_UPDATE Frame.
@@ -10,7 +10,7 @@ use crate::frame::FrameHeader;
 /// +-+-------------------------------------------------------------+
 /// |R|              Window Size Increment (31)                     |
 /// +-+-------------------------------------------------------------+
-#[derive(Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct WindowUpdateFrame {
     stream_id: u32,
     reserved: bool,
@@ -18,6 +18,71 @@ pub struct WindowUpdateFrame {
 }
 
 impl WindowUpdateFrame {
+    /// Create a new WINDOW_UPDATE frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier, 0 for the connection.
+    /// * `window_size_increment` - The window size increment (31 bits).
+    pub fn new(stream_id: u32, window_size_increment: u32) -> Self {
+        WindowUpdateFrame {
+            stream_id,
+            reserved: false,
+            window_size_increment: window_size_increment & 0x7FFF_FFFF,
+        }
+    }
+
+    /// Validate a WINDOW_UPDATE frame.
+    ///
+    /// A receiver MUST treat the receipt of a WINDOW_UPDATE frame with a
+    /// flow-control window increment of 0 as a stream error of type
+    /// PROTOCOL_ERROR; errors on the connection flow-control window MUST
+    /// be treated as a connection error.
+    pub fn validate(&self) -> Result<(), Http2Error> {
+        if self.window_size_increment != 0 {
+            return Ok(());
+        }
+
+        let message = "WINDOW_UPDATE with a 0 increment".to_string();
+        if self.stream_id == 0 {
+            Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                message,
+            ))
+        } else {
+            Err(Http2Error::StreamError(
+                self.stream_id,
+                ErrorCode::ProtocolError,
+                message,
+            ))
+        }
+    }
+
+    /// Serialize a WINDOW_UPDATE frame.
+    pub fn serialize(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.serialize_into(&mut bytes);
+
+        bytes
+    }
+
+    /// Serialize a WINDOW_UPDATE frame at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The byte vector to serialize into.
+    pub fn serialize_into(&self, bytes: &mut Vec<u8>) {
+        // Build the payload.
+        let mut window_size_increment = self.window_size_increment.to_be_bytes();
+        if self.reserved {
+            window_size_increment[0] |= 0x80;
+        }
+
+        // Serialize the frame.
+        FrameHeader::new(4, 0x8, 0x0, false, self.stream_id).serialize_into(bytes);
+        bytes.extend_from_slice(&window_size_increment);
+    }
+
     /// Deserialize a WINDOW_UPDATE frame.
     /// 
     /// The operation is destructive for the bytes vector.
@@ -28,7 +93,7 @@ impl WindowUpdateFrame {
     /// * `bytes` - A mutable reference to a bytes vector.
     pub fn deserialize(
         frame_header: &FrameHeader,
-        bytes: &mut Vec<u8>,
+        bytes: &mut [u8],
     ) -> Result<Self, Http2Error> {
         // Check if the bytes has the right length.
         if bytes.len() != frame_header.payload_length() as usize {
@@ -50,14 +115,22 @@ impl WindowUpdateFrame {
             ]),
         })
     }
+
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    pub fn window_size_increment(&self) -> u32 {
+        self.window_size_increment
+    }
 }
 
 impl fmt::Display for WindowUpdateFrame {
     /// Format a WINDOW_UPDATE frame.
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "WINDOW_UPDATE\n")?;
-        write!(f, "Stream Identifier: {}\n", self.stream_id)?;
-        write!(f, "Reserved: {}\n", self.reserved)?;
-        write!(f, "Window Size Increment: {}\n", self.window_size_increment)
+        writeln!(f, "WINDOW_UPDATE")?;
+        writeln!(f, "Stream Identifier: {}", self.stream_id)?;
+        writeln!(f, "Reserved: {}", self.reserved)?;
+        writeln!(f, "Window Size Increment: {}", self.window_size_increment)
     }
 }
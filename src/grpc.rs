@@ -0,0 +1,409 @@
+use crate::error::{ErrorCode, Http2Error};
+use crate::frame::data::DataFrame;
+use crate::frame::headers::HeadersFrame;
+use crate::header::field::HeaderField;
+use crate::header::list::HeaderList;
+
+/// Content type of gRPC requests and responses.
+pub const CONTENT_TYPE: &str = "application/grpc";
+
+/// Default maximum size of a received message (4 MiB).
+pub const DEFAULT_MAX_MESSAGE_SIZE: usize = 4 * 1024 * 1024;
+
+/// Size of the message prefix: compressed flag and message length.
+const MESSAGE_PREFIX_LENGTH: usize = 5;
+
+/// Length-prefixed gRPC message.
+///
+/// +---------------+-----------------------------------------------+
+/// |Compressed (8) |            Message Length (32)                |
+/// +---------------+-----------------------------------------------+
+/// |                          Message (*)                        ...
+/// +---------------------------------------------------------------+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    compressed: bool,
+    data: Vec<u8>,
+}
+
+impl Message {
+    /// Create a new message.
+    ///
+    /// # Arguments
+    ///
+    /// * `compressed` - Whether the message is compressed with the `grpc-encoding`.
+    /// * `data` - The serialized message.
+    pub fn new(compressed: bool, data: Vec<u8>) -> Self {
+        Message { compressed, data }
+    }
+
+    /// Encode the message with its prefix.
+    pub fn encode(&self) -> Vec<u8> {
+        let mut bytes: Vec<u8> = Vec::with_capacity(MESSAGE_PREFIX_LENGTH + self.data.len());
+        bytes.push(self.compressed as u8);
+        bytes.extend_from_slice(&(self.data.len() as u32).to_be_bytes());
+        bytes.extend_from_slice(&self.data);
+
+        bytes
+    }
+
+    pub fn compressed(&self) -> bool {
+        self.compressed
+    }
+
+    pub fn data(&self) -> &Vec<u8> {
+        &self.data
+    }
+
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+/// Frame messages into DATA frames.
+///
+/// Messages are concatenated and split into DATA frames of at most
+/// `max_frame_size` octets, regardless of message boundaries.
+///
+/// # Arguments
+///
+/// * `stream_id` - The stream identifier.
+/// * `messages` - The messages to send.
+/// * `max_frame_size` - The SETTINGS_MAX_FRAME_SIZE advertised by the peer.
+/// * `end_stream` - Whether the last DATA frame ends the stream.
+pub fn data_frames(
+    stream_id: u32,
+    messages: &[Message],
+    max_frame_size: u32,
+    end_stream: bool,
+) -> Vec<DataFrame> {
+    let bytes: Vec<u8> = messages.iter().flat_map(Message::encode).collect();
+
+    let mut data_frames: Vec<DataFrame> = bytes
+        .chunks(max_frame_size as usize)
+        .map(|chunk| DataFrame::new(stream_id, false, chunk.to_vec()))
+        .collect();
+
+    if end_stream {
+        match data_frames.last_mut() {
+            Some(data_frame) => data_frame.end_stream = true,
+            None => data_frames.push(DataFrame::new(stream_id, true, Vec::new())),
+        }
+    }
+
+    data_frames
+}
+
+/// Incremental decoder of the messages received on a stream.
+///
+/// Messages may span several DATA frames and a DATA frame may carry
+/// several messages: the payloads of the DATA frames are fed to the
+/// decoder, which yields messages as soon as they are complete.
+#[derive(Debug)]
+pub struct MessageDecoder {
+    stream_id: u32,
+    buffer: Vec<u8>,
+    max_message_size: usize,
+}
+
+impl MessageDecoder {
+    /// Create a new message decoder.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream carrying the messages.
+    /// * `max_message_size` - The maximum size of a received message.
+    pub fn new(stream_id: u32, max_message_size: usize) -> Self {
+        MessageDecoder {
+            stream_id,
+            buffer: Vec::new(),
+            max_message_size,
+        }
+    }
+
+    /// Feed the payload of a DATA frame to the decoder.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_frame` - The received DATA frame.
+    pub fn feed(&mut self, data_frame: &DataFrame) {
+        self.buffer.extend_from_slice(&data_frame.data);
+    }
+
+    /// Decode the next complete message.
+    ///
+    /// Returns `Ok(None)` when more DATA frames need to be fed to complete
+    /// the next message. Messages larger than the maximum message size are
+    /// rejected from their prefix, before they are buffered.
+    pub fn next_message(&mut self) -> Result<Option<Message>, Http2Error> {
+        if self.buffer.len() < MESSAGE_PREFIX_LENGTH {
+            return Ok(None);
+        }
+
+        let compressed = match self.buffer[0] {
+            0 => false,
+            1 => true,
+            flag => {
+                return Err(Http2Error::StreamError(
+                    self.stream_id,
+                    ErrorCode::ProtocolError,
+                    format!("Invalid gRPC compressed flag {}", flag),
+                ))
+            }
+        };
+
+        let length = u32::from_be_bytes([
+            self.buffer[1],
+            self.buffer[2],
+            self.buffer[3],
+            self.buffer[4],
+        ]) as usize;
+        if length > self.max_message_size {
+            return Err(Http2Error::StreamError(
+                self.stream_id,
+                ErrorCode::EnhanceYourCalm,
+                format!(
+                    "gRPC message of {} bytes exceeds {} bytes",
+                    length, self.max_message_size
+                ),
+            ));
+        }
+
+        if self.buffer.len() < MESSAGE_PREFIX_LENGTH + length {
+            return Ok(None);
+        }
+
+        let data: Vec<u8> =
+            self.buffer[MESSAGE_PREFIX_LENGTH..MESSAGE_PREFIX_LENGTH + length].to_vec();
+        self.buffer.drain(..MESSAGE_PREFIX_LENGTH + length);
+
+        Ok(Some(Message::new(compressed, data)))
+    }
+
+    /// Check that no partial message is left once the stream ended.
+    pub fn finish(&self) -> Result<(), Http2Error> {
+        if self.buffer.is_empty() {
+            Ok(())
+        } else {
+            Err(Http2Error::StreamError(
+                self.stream_id,
+                ErrorCode::ProtocolError,
+                format!(
+                    "Stream ended with {} bytes of partial gRPC message",
+                    self.buffer.len()
+                ),
+            ))
+        }
+    }
+
+    /// Get the number of bytes fed but not yet decoded.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+}
+
+impl Iterator for MessageDecoder {
+    type Item = Result<Message, Http2Error>;
+
+    /// Yield the next complete message, if any.
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_message().transpose()
+    }
+}
+
+/// Status of a gRPC call, carried by the `grpc-status` and `grpc-message`
+/// header fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Status {
+    code: u32,
+    message: Option<String>,
+}
+
+impl Status {
+    /// Create a new status.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The gRPC status code, 0 for OK.
+    /// * `message` - An optional description of the status.
+    pub fn new(code: u32, message: Option<&str>) -> Self {
+        Status {
+            code,
+            message: message.map(str::to_string),
+        }
+    }
+
+    /// Read the status from trailers or from a trailers-only response.
+    ///
+    /// Returns `None` if the header list has no valid `grpc-status`.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_list` - The header list carrying the status.
+    pub fn from_header_list(header_list: &HeaderList) -> Option<Self> {
+        let code = header_list.get("grpc-status")?.parse::<u32>().ok()?;
+        let message = header_list.get("grpc-message").map(percent_decode);
+
+        Some(Status { code, message })
+    }
+
+    /// Get the header fields carrying the status.
+    pub fn header_fields(&self) -> Vec<HeaderField> {
+        let mut header_fields = vec![HeaderField::new(
+            "grpc-status".into(),
+            self.code.to_string().into(),
+        )];
+        if let Some(message) = &self.message {
+            header_fields.push(HeaderField::new(
+                "grpc-message".into(),
+                percent_encode(message).into(),
+            ));
+        }
+
+        header_fields
+    }
+
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    pub fn message(&self) -> Option<&str> {
+        self.message.as_deref()
+    }
+}
+
+/// Check the header section of a gRPC request.
+///
+/// A gRPC request MUST use the POST method, a `content-type` starting with
+/// `application/grpc` and carry `te: trailers`, which tells intermediaries
+/// that the client accepts the trailers the status is sent in.
+///
+/// # Arguments
+///
+/// * `stream_id` - The stream carrying the request.
+/// * `header_list` - The header section of the request.
+pub fn check_request(stream_id: u32, header_list: &HeaderList) -> Result<(), Http2Error> {
+    let error = |message: &str| {
+        Err(Http2Error::StreamError(
+            stream_id,
+            ErrorCode::ProtocolError,
+            message.to_string(),
+        ))
+    };
+
+    if header_list.method() != Some("POST") {
+        return error("gRPC requests must use the POST method");
+    }
+
+    match header_list.get("content-type") {
+        Some(content_type) if is_grpc_content_type(content_type) => (),
+        _ => return error("gRPC requests must have an application/grpc content-type"),
+    }
+
+    if header_list.get("te") != Some("trailers") {
+        return error("gRPC requests must carry te: trailers");
+    }
+
+    Ok(())
+}
+
+/// Build the header section of a gRPC response.
+pub fn response_headers() -> HeaderList {
+    HeaderList::new(vec![
+        HeaderField::new(":status".into(), "200".into()),
+        HeaderField::new("content-type".into(), CONTENT_TYPE.into()),
+    ])
+}
+
+/// Build the trailers of a gRPC response.
+///
+/// # Arguments
+///
+/// * `status` - The status of the call.
+pub fn trailers(status: &Status) -> HeaderList {
+    HeaderList::new(status.header_fields())
+}
+
+/// Build a trailers-only gRPC response.
+///
+/// Calls failing before any message is sent are answered with a single
+/// HEADERS frame ending the stream, carrying both the response header
+/// fields and the status.
+///
+/// # Arguments
+///
+/// * `stream_id` - The stream carrying the request.
+/// * `status` - The status of the call.
+pub fn trailers_only_response(stream_id: u32, status: &Status) -> HeadersFrame {
+    let header_list = response_headers()
+        .into_iter()
+        .chain(status.header_fields())
+        .collect();
+
+    HeadersFrame::new(stream_id, true, true, None, header_list)
+}
+
+/// Check if a received HEADERS frame is a trailers-only response.
+///
+/// # Arguments
+///
+/// * `headers_frame` - The HEADERS frame of the response.
+pub fn is_trailers_only(headers_frame: &HeadersFrame) -> bool {
+    headers_frame.end_stream() && headers_frame.header_list().contains("grpc-status")
+}
+
+/// Check if a `content-type` is a gRPC content type, such as
+/// `application/grpc` or `application/grpc+proto`.
+fn is_grpc_content_type(content_type: &str) -> bool {
+    match content_type.strip_prefix(CONTENT_TYPE) {
+        Some(suffix) => suffix.is_empty() || suffix.starts_with('+') || suffix.starts_with(';'),
+        None => false,
+    }
+}
+
+/// Percent-encode a `grpc-message` value.
+///
+/// Bytes outside of the printable ASCII range and '%' are encoded.
+fn percent_encode(message: &str) -> String {
+    let mut encoded = String::with_capacity(message.len());
+    for byte in message.bytes() {
+        if (0x20..=0x7E).contains(&byte) && byte != b'%' {
+            encoded.push(byte as char);
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+
+    encoded
+}
+
+/// Percent-decode a `grpc-message` value.
+///
+/// Invalid percent-encoded sequences are kept as is.
+fn percent_decode(message: &str) -> String {
+    let bytes = message.as_bytes();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let byte = match (bytes[i], bytes.get(i + 1..i + 3)) {
+            (b'%', Some(hex)) if hex.iter().all(u8::is_ascii_hexdigit) => std::str::from_utf8(hex)
+                .ok()
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+
+        match byte {
+            Some(byte) => {
+                decoded.push(byte);
+                i += 3;
+            }
+            None => {
+                decoded.push(bytes[i]);
+                i += 1;
+            }
+        }
+    }
+
+    String::from_utf8_lossy(&decoded).into_owned()
+}
@@ -1,15 +1,22 @@
 use std::fmt;
+use std::hash::{Hash, Hasher};
 
-use crate::error::Http2Error;
+use crate::error::{ErrorCode, Http2Error};
 use crate::header::primitive::HpackString;
 use crate::header::representation::HeaderRepresentation;
 use crate::header::table::HeaderTable;
 
 /// A HTTP/2 header field.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// A sensitive header field is always encoded as a literal never indexed,
+/// so that intermediaries re-encoding it preserve its protection. Header
+/// fields decoded from a never indexed representation are sensitive. The
+/// sensitivity of header fields is not considered when comparing them.
+#[derive(Clone, Debug)]
 pub struct HeaderField {
     name: HeaderName,
     value: HeaderValue,
+    sensitive: bool,
 }
 
 impl HeaderField {
@@ -20,7 +27,11 @@ impl HeaderField {
     /// * `name` - The name of the header field.
     /// * `value` - The value of the header field.
     pub fn new(name: HeaderName, value: HeaderValue) -> Self {
-        HeaderField { name, value }
+        HeaderField {
+            name,
+            value,
+            sensitive: false,
+        }
     }
 
     /// Get the name of the header field.
@@ -33,13 +44,42 @@ impl HeaderField {
         self.value.clone()
     }
 
+    /// Get a reference to the name of the header field.
+    pub fn name_ref(&self) -> &HeaderName {
+        &self.name
+    }
+
+    /// Get a reference to the value of the header field.
+    pub fn value_ref(&self) -> &HeaderValue {
+        &self.value
+    }
+
+    /// Convert the name of the header field to lowercase.
+    pub fn normalize(&mut self) {
+        self.name.normalize();
+    }
+
+    /// Check if the header field is sensitive.
+    pub fn sensitive(&self) -> bool {
+        self.sensitive
+    }
+
+    /// Set whether the header field is sensitive.
+    ///
+    /// # Arguments
+    ///
+    /// * `sensitive` - Whether the header field must never be indexed.
+    pub fn set_sensitive(&mut self, sensitive: bool) {
+        self.sensitive = sensitive;
+    }
+
     /// Calculate the size of the header field in octets.
     ///
     /// The size of an entry is the sum of its name's length in octets,
     /// its value's length in octets, and 32.
     pub fn size(&self) -> usize {
-        let name_size = self.name.to_string().as_bytes().len();
-        let value_size = self.value.to_string().as_bytes().len();
+        let name_size = self.name.to_string().len();
+        let value_size = self.value.to_string().len();
 
         name_size + value_size + 32
     }
@@ -123,13 +163,15 @@ impl HeaderField {
                 let name = header_table.get(index)?.name();
 
                 // Build the header field.
-                let header_field = HeaderField::new(name, value.into());
+                let mut header_field = HeaderField::new(name, value.into());
+                header_field.set_sensitive(true);
 
                 Ok(Some(header_field))
             }
             HeaderRepresentation::NeverIndexedNewName(name, value) => {
                 // Build the header field.
-                let header_field = HeaderField::new(name.into(), value.into());
+                let mut header_field = HeaderField::new(name.into(), value.into());
+                header_field.set_sensitive(true);
 
                 Ok(Some(header_field))
             }
@@ -137,6 +179,18 @@ impl HeaderField {
                 // Parse the maximum size.
                 let max_size: usize = max_size.try_into()?;
 
+                // The new maximum size cannot exceed the protocol-negotiated limit.
+                if max_size > header_table.settings_max_size() {
+                    return Err(Http2Error::ConnectionError(
+                        ErrorCode::CompressionError,
+                        format!(
+                            "Dynamic table size update to {} exceeds the limit of {}",
+                            max_size,
+                            header_table.settings_max_size()
+                        ),
+                    ));
+                }
+
                 // Update the maximum size of the header table.
                 header_table.set_max_size(max_size);
 
@@ -148,14 +202,26 @@ impl HeaderField {
     /// Build a representation from a header field and a header table updating
     /// the header table when possible.
     ///
+    /// A header field larger than the dynamic table is not indexed, as its
+    /// insertion would only empty the table. A sensitive header field is
+    /// never indexed.
+    ///
     /// # Arguments
     ///
     /// * `header_table` - The header table to use.
     pub fn into_representation(&self, header_table: &mut HeaderTable) -> HeaderRepresentation {
+        if self.sensitive {
+            return self.into_representation_never_index(header_table);
+        }
+
         if let Some(index) = header_table.contains(self) {
             return HeaderRepresentation::Indexed(index.into());
         }
 
+        if self.size() > header_table.get_dynamic_table_max_size() {
+            return self.into_representation_without_indexing(header_table);
+        }
+
         if let Some(index) = header_table.contains_name(self) {
             // Add a new entry to the header table.
             header_table.add_entry(self.clone());
@@ -173,6 +239,8 @@ impl HeaderField {
     /// Build a representation from a header field and a header table without
     /// indexing the header field.
     ///
+    /// A sensitive header field is never indexed.
+    ///
     /// # Arguments
     ///
     /// * `header_table` - The header table to use.
@@ -180,6 +248,10 @@ impl HeaderField {
         &self,
         header_table: &mut HeaderTable,
     ) -> HeaderRepresentation {
+        if self.sensitive {
+            return self.into_representation_never_index(header_table);
+        }
+
         if let Some(index) = header_table.contains_name(self) {
             return HeaderRepresentation::WithoutIndexingIndexedName(
                 index.into(),
@@ -219,7 +291,24 @@ impl From<(HeaderName, HeaderValue)> for HeaderField {
     /// * `name` - The name of the header field.
     /// * `value` - The value of the header field.
     fn from((name, value): (HeaderName, HeaderValue)) -> Self {
-        HeaderField { name, value }
+        HeaderField::new(name, value)
+    }
+}
+
+impl PartialEq for HeaderField {
+    /// Compare the names and values of two header fields.
+    fn eq(&self, other: &Self) -> bool {
+        self.name == other.name && self.value == other.value
+    }
+}
+
+impl Eq for HeaderField {}
+
+impl Hash for HeaderField {
+    /// Hash the name and value of a header field.
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.name.hash(state);
+        self.value.hash(state);
     }
 }
 
@@ -231,11 +320,41 @@ impl fmt::Display for HeaderField {
 }
 
 /// A HTTP/2 header field name.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct HeaderName {
     name: String,
 }
 
+impl HeaderName {
+    /// Get the header field name as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.name
+    }
+
+    /// Check if the header field name is a pseudo-header field name.
+    ///
+    /// Pseudo-header fields start with the ':' character.
+    pub fn is_pseudo_header(&self) -> bool {
+        self.name.starts_with(':')
+    }
+
+    /// Check if the header field name has no uppercase character.
+    ///
+    /// Header field names MUST be lowercase on the wire (RFC 9113 section
+    /// 8.2.1).
+    pub fn is_lowercase(&self) -> bool {
+        !self.name.bytes().any(|byte| byte.is_ascii_uppercase())
+    }
+
+    /// Convert the header field name to lowercase.
+    ///
+    /// Names are stored as given and compared as such, header lists are
+    /// normalized when they are encoded.
+    pub fn normalize(&mut self) {
+        self.name.make_ascii_lowercase();
+    }
+}
+
 impl From<&str> for HeaderName {
     /// Create a new HTTP/2 header field name.
     ///
@@ -282,25 +401,25 @@ impl From<&HpackString> for HeaderName {
     }
 }
 
-impl Into<String> for HeaderName {
+impl From<HeaderName> for String {
     /// Convert a header field name into a String.
     ///
     /// # Returns
     ///
     /// A String containing the header field name.
-    fn into(self) -> String {
-        self.name
+    fn from(name: HeaderName) -> Self {
+        name.name
     }
 }
 
-impl Into<HpackString> for HeaderName {
+impl From<HeaderName> for HpackString {
     /// Convert a header field value into a HpackString.
     ///
     /// # Returns
     ///
     /// A HpackString containing the header field value.
-    fn into(self) -> HpackString {
-        self.name.into()
+    fn from(name: HeaderName) -> Self {
+        name.name.into()
     }
 }
 
@@ -312,11 +431,18 @@ impl fmt::Display for HeaderName {
 }
 
 /// A HTTP/2 header field value.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
 pub struct HeaderValue {
     value: String,
 }
 
+impl HeaderValue {
+    /// Get the header field value as a string slice.
+    pub fn as_str(&self) -> &str {
+        &self.value
+    }
+}
+
 impl From<&str> for HeaderValue {
     /// Create a new HTTP/2 header field value.
     ///
@@ -367,25 +493,25 @@ impl From<&HpackString> for HeaderValue {
     }
 }
 
-impl Into<String> for HeaderValue {
+impl From<HeaderValue> for String {
     /// Convert a header field value into a String.
     ///
     /// # Returns
     ///
     /// A String containing the header field value.
-    fn into(self) -> String {
-        self.value
+    fn from(value: HeaderValue) -> Self {
+        value.value
     }
 }
 
-impl Into<HpackString> for HeaderValue {
+impl From<HeaderValue> for HpackString {
     /// Convert a header field value into a HpackString.
     ///
     /// # Returns
     ///
     /// A HpackString containing the header field value.
-    fn into(self) -> HpackString {
-        self.value.into()
+    fn from(value: HeaderValue) -> Self {
+        value.value.into()
     }
 }
 
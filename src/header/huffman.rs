@@ -1,4 +1,6 @@
-use crate::error::Http2Error;
+use std::sync::OnceLock;
+
+use crate::error::{CompressionError, Http2Error};
 use crate::header::huffman::Direction::{Left, Right};
 
 pub enum Node {
@@ -9,17 +11,13 @@ pub enum Node {
 impl Node {
     pub fn update(&mut self, code: &mut Code) -> Result<(), Http2Error> {
         // Exit condition.
-        if code.directions.len() == 0 {
+        if code.directions.is_empty() {
             match self {
                 Node::Branch(_, _) => {
                     *self = Node::Leaf(code.symbol);
                     return Ok(());
                 }
-                Node::Leaf(_) => {
-                    return Err(Http2Error::HuffmanDecodingError(
-                        "Huffman Tree building error".to_string(),
-                    ))
-                }
+                Node::Leaf(_) => return Err(CompressionError::HuffmanInvalidCode.into()),
             }
         }
 
@@ -30,12 +28,10 @@ impl Node {
                 let node = match self {
                     Node::Branch(left, _) => left.get_or_insert(Box::new(Node::Branch(None, None))),
                     Node::Leaf(_) => {
-                        return Err(Http2Error::HuffmanDecodingError(
-                            "Huffman Tree building error".to_string(),
-                        ))
+                        return Err(CompressionError::HuffmanInvalidCode.into());
                     } // Unexpected leaf
                 };
-                return node.update(code);
+                node.update(code)
             }
             Right => {
                 let node = match self {
@@ -43,18 +39,16 @@ impl Node {
                         right.get_or_insert(Box::new(Node::Branch(None, None)))
                     }
                     Node::Leaf(_) => {
-                        return Err(Http2Error::HuffmanDecodingError(
-                            "Huffman Tree building error".to_string(),
-                        ));
+                        return Err(CompressionError::HuffmanInvalidCode.into());
                     } // Unexpected leaf
                 };
-                return node.update(code);
+                node.update(code)
             }
         }
     }
 
     pub fn find(&self, directions: &mut Vec<Direction>) -> Option<char> {
-        if directions.len() == 0 {
+        if directions.is_empty() {
             match self {
                 Node::Branch(_, _) => return None,
                 Node::Leaf(c) => {
@@ -109,7 +103,7 @@ impl Direction {
         let mut directions: Vec<Direction> = Vec::with_capacity(bytes.len() * 8);
 
         for byte in bytes {
-            let mut byte_directions = Direction::from_byte(byte.clone());
+            let mut byte_directions = Direction::from_byte(*byte);
             directions.append(&mut byte_directions);
         }
 
@@ -134,8 +128,8 @@ impl Code {
             }
         }
 
-        if directions.len() == 0 {
-            return Err(Http2Error::HuffmanDecodingError("Empty code".to_string()));
+        if directions.is_empty() {
+            return Err(CompressionError::HuffmanInvalidCode.into());
         }
 
         Ok(Code {
@@ -170,9 +164,8 @@ impl Tree {
         let mut directions = Direction::from_bytes(bytes);
 
         while !directions.is_empty() {
-            match self.root.find(&mut directions) {
-                Some(symbol) => decoded.push(symbol),
-                None => (),
+            if let Some(symbol) = self.root.find(&mut directions) {
+                decoded.push(symbol);
             }
         }
 
@@ -180,6 +173,288 @@ impl Tree {
     }
 }
 
+/// HPACK Huffman code of the EOS symbol.
+pub const HPACK_HUFFMAN_EOS_CODE: &str = "111111111111111111111111111111";
+
+/// Value of the EOS symbol in the decoding state machine.
+const EOS_SYMBOL: u16 = 256;
+
+/// The transition emitted a symbol.
+const FLAG_SYMBOL: u8 = 0b001;
+/// The bits consumed since the last symbol are a valid padding.
+const FLAG_ACCEPTED: u8 = 0b010;
+/// The transition reached the EOS symbol.
+const FLAG_EOS: u8 = 0b100;
+/// The bits consumed since the last symbol are more than 7 bits set to 1.
+const FLAG_PADDING_TOO_LONG: u8 = 0b1000;
+
+/// A transition of the Huffman decoding state machine.
+///
+/// Each state of the machine is an internal node of the Huffman tree.
+/// Consuming 4 bits (a nibble) from a state leads to another state and
+/// emits at most one symbol since the shortest code is 5 bits long.
+#[derive(Clone, Copy, Debug, Default)]
+struct Transition {
+    state: u8,
+    symbol: u8,
+    flags: u8,
+}
+
+/// Huffman tree node used to build the decoding state machine.
+#[derive(Clone, Copy, Default)]
+struct ArenaNode {
+    children: [Option<usize>; 2],
+    symbol: Option<u16>,
+}
+
+/// Build the table-driven Huffman decoding state machine.
+///
+/// The table holds, for each of the 256 internal nodes of the Huffman tree
+/// and each of the 16 possible nibbles, the resulting transition.
+fn build_decoding_table() -> Vec<[Transition; 16]> {
+    // Build the Huffman tree in an arena, the root being node 0.
+    let mut nodes: Vec<ArenaNode> = vec![ArenaNode::default()];
+    let codes = HPACK_HUFFMAN_CODE
+        .iter()
+        .map(|&(code, symbol)| (code, symbol as u16))
+        .chain(std::iter::once((HPACK_HUFFMAN_EOS_CODE, EOS_SYMBOL)));
+    for (code, symbol) in codes {
+        let mut node = 0;
+        for bit in code.bytes().map(|c| (c - b'0') as usize) {
+            node = match nodes[node].children[bit] {
+                Some(child) => child,
+                None => {
+                    nodes.push(ArenaNode::default());
+                    let child = nodes.len() - 1;
+                    nodes[node].children[bit] = Some(child);
+                    child
+                }
+            };
+        }
+        nodes[node].symbol = Some(symbol);
+    }
+
+    // Number the internal nodes, they are the states of the machine.
+    let mut states: Vec<usize> = Vec::with_capacity(256);
+    let mut state_of: Vec<usize> = vec![0; nodes.len()];
+    for (node, arena_node) in nodes.iter().enumerate() {
+        if arena_node.symbol.is_none() {
+            state_of[node] = states.len();
+            states.push(node);
+        }
+    }
+
+    // Classify the internal nodes reached from the root by bits set to 1:
+    // ending a string there means the string is padded with the EOS prefix,
+    // which is only valid if the padding is at most 7 bits long.
+    let mut accepted: Vec<bool> = vec![false; nodes.len()];
+    let mut too_long: Vec<bool> = vec![false; nodes.len()];
+    let mut node = 0;
+    let mut depth = 0;
+    while nodes[node].symbol.is_none() {
+        if depth <= 7 {
+            accepted[node] = true;
+        } else {
+            too_long[node] = true;
+        }
+        node = match nodes[node].children[1] {
+            Some(child) => child,
+            None => break,
+        };
+        depth += 1;
+    }
+
+    // Compute the transitions of every state for every nibble.
+    let mut table: Vec<[Transition; 16]> = Vec::with_capacity(states.len());
+    for &state_node in &states {
+        let mut transitions = [Transition::default(); 16];
+
+        for (nibble, transition) in transitions.iter_mut().enumerate() {
+            let mut node = state_node;
+            let mut flags: u8 = 0;
+            let mut symbol: u8 = 0;
+
+            // The Huffman tree is complete: every path leads to a symbol.
+            for shift in (0..4).rev() {
+                let bit = (nibble >> shift) & 1;
+                node = nodes[node].children[bit].expect("incomplete Huffman tree");
+
+                match nodes[node].symbol {
+                    Some(EOS_SYMBOL) => {
+                        flags |= FLAG_EOS;
+                        break;
+                    }
+                    Some(decoded) => {
+                        flags |= FLAG_SYMBOL;
+                        symbol = decoded as u8;
+                        node = 0;
+                    }
+                    None => (),
+                }
+            }
+
+            if flags & FLAG_EOS == 0 {
+                if accepted[node] {
+                    flags |= FLAG_ACCEPTED;
+                } else if too_long[node] {
+                    flags |= FLAG_PADDING_TOO_LONG;
+                }
+            }
+
+            *transition = Transition {
+                state: state_of[node] as u8,
+                symbol,
+                flags,
+            };
+        }
+
+        table.push(transitions);
+    }
+
+    table
+}
+
+/// Get the Huffman decoding state machine, built on first use.
+fn decoding_table() -> &'static [[Transition; 16]] {
+    static DECODING_TABLE: OnceLock<Vec<[Transition; 16]>> = OnceLock::new();
+    DECODING_TABLE.get_or_init(build_decoding_table)
+}
+
+/// Decode a Huffman encoded string.
+///
+/// The string is decoded 4 bits at a time using a precomputed state
+/// machine. As required by RFC 7541 section 5.2, decoding fails with a
+/// compression error if the EOS symbol is encountered, if the padding is
+/// longer than 7 bits or if the padding does not correspond to the most
+/// significant bits of the EOS code.
+///
+/// # Arguments
+///
+/// * `bytes` - The Huffman encoded bytes.
+pub fn decode(bytes: &[u8]) -> Result<Vec<u8>, Http2Error> {
+    let table = decoding_table();
+    let mut decoded: Vec<u8> = Vec::with_capacity(bytes.len() * 8 / 5);
+    let mut state: usize = 0;
+    let mut flags: u8 = FLAG_ACCEPTED;
+
+    for byte in bytes {
+        for nibble in [byte >> 4, byte & 0x0f] {
+            let transition = table[state][nibble as usize];
+
+            if transition.flags & FLAG_EOS != 0 {
+                return Err(CompressionError::HuffmanEos.into());
+            }
+
+            if transition.flags & FLAG_SYMBOL != 0 {
+                decoded.push(transition.symbol);
+            }
+
+            state = transition.state as usize;
+            flags = transition.flags;
+        }
+    }
+
+    if flags & FLAG_PADDING_TOO_LONG != 0 {
+        return Err(CompressionError::HuffmanPaddingTooLong.into());
+    }
+
+    if flags & FLAG_ACCEPTED == 0 {
+        return Err(CompressionError::HuffmanInvalidPadding.into());
+    }
+
+    Ok(decoded)
+}
+
+/// Huffman codes of the symbols, as values and bit lengths, indexed by
+/// symbol.
+const ENCODING_TABLE: [(u32, u8); 256] = build_encoding_table();
+
+/// Longest Huffman code of a symbol, in bits.
+const MAX_CODE_LENGTH: u32 = 30;
+
+/// Build the encoding table from [`HPACK_HUFFMAN_CODE`], at compile time.
+const fn build_encoding_table() -> [(u32, u8); 256] {
+    let mut table = [(0, 0); 256];
+
+    let mut i = 0;
+    while i < HPACK_HUFFMAN_CODE.len() {
+        let (code, symbol) = HPACK_HUFFMAN_CODE[i];
+        let code = code.as_bytes();
+
+        let mut value = 0;
+        let mut j = 0;
+        while j < code.len() {
+            value = (value << 1) | (code[j] - b'0') as u32;
+            j += 1;
+        }
+        table[symbol as usize] = (value, code.len() as u8);
+        i += 1;
+    }
+
+    table
+}
+
+/// Get the length in octets of a string once Huffman encoded.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+pub fn encoded_len(bytes: &[u8]) -> usize {
+    let bits: usize = bytes
+        .iter()
+        .map(|byte| ENCODING_TABLE[*byte as usize].1 as usize)
+        .sum();
+
+    bits.div_ceil(8)
+}
+
+/// Encode a string with the HPACK Huffman code.
+///
+/// The last octet is padded with the most significant bits of the EOS code.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+pub fn encode(bytes: &[u8]) -> Vec<u8> {
+    let mut encoded: Vec<u8> = Vec::with_capacity(encoded_len(bytes));
+    encode_into(bytes, &mut encoded);
+
+    encoded
+}
+
+/// Encode a string with the HPACK Huffman code at the end of a byte vector.
+///
+/// The codes are accumulated in a 64-bit buffer, flushed 32 bits at a time
+/// so that the longest code always fits in the bits left.
+///
+/// # Arguments
+///
+/// * `bytes` - The bytes to encode.
+/// * `encoded` - The byte vector to encode into.
+pub fn encode_into(bytes: &[u8], encoded: &mut Vec<u8>) {
+    let mut buffer: u64 = 0;
+    let mut bits: u32 = 0;
+
+    for byte in bytes {
+        let (code, length) = ENCODING_TABLE[*byte as usize];
+        buffer = (buffer << length) | code as u64;
+        bits += length as u32;
+
+        if bits >= 64 - MAX_CODE_LENGTH {
+            bits -= 32;
+            encoded.extend_from_slice(&((buffer >> bits) as u32).to_be_bytes());
+        }
+    }
+
+    while bits >= 8 {
+        bits -= 8;
+        encoded.push((buffer >> bits) as u8);
+    }
+    if bits > 0 {
+        encoded.push(((buffer << (8 - bits)) as u8) | (0xff >> bits));
+    }
+}
+
 /// HPACK Huffman code table.
 pub const HPACK_HUFFMAN_CODE: [(&str, u8); 256] = [
     ("1111111111000", 0),
@@ -0,0 +1,36 @@
+/// How strictly the frames and header blocks received from a peer are
+/// checked.
+///
+/// Real-world peers sometimes violate the protocol in ways that do not
+/// prevent understanding them. A strict configuration, the default, treats
+/// every violation as an error, as servers exposed to untrusted peers
+/// should. A tolerant configuration accepts them instead, for debugging
+/// proxies and packet analysis tools that need to keep going.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LenienceConfig {
+    /// Convert header field names with uppercase characters to lowercase
+    /// instead of rejecting them (RFC 9113 section 8.2.1).
+    pub uppercase_header_names: bool,
+    /// Accept pseudo-header fields following regular header fields
+    /// (RFC 9113 section 8.3).
+    pub pseudo_header_order: bool,
+    /// Accept frames on a stream their type does not allow, such as a
+    /// SETTINGS frame on a stream other than 0 (RFC 9113 section 6).
+    pub frame_stream_ids: bool,
+}
+
+impl LenienceConfig {
+    /// Create a configuration rejecting every violation.
+    pub fn strict() -> Self {
+        LenienceConfig::default()
+    }
+
+    /// Create a configuration accepting every violation it covers.
+    pub fn tolerant() -> Self {
+        LenienceConfig {
+            uppercase_header_names: true,
+            pseudo_header_order: true,
+            frame_stream_ids: true,
+        }
+    }
+}
@@ -0,0 +1,200 @@
+use crate::error::{ErrorCode, Http2Error};
+use crate::header::field::HeaderField;
+
+/// Limits applied when decoding a header list.
+///
+/// A peer can send arbitrarily large literals or an unbounded number of
+/// header fields in a header block. These limits bound the amount of
+/// memory the decoder accepts to allocate for a single header list.
+/// A limit set to `None` is not enforced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct DecoderLimits {
+    /// Maximum size of a decoded header list (SETTINGS_MAX_HEADER_LIST_SIZE),
+    /// computed as the sum of the header field sizes.
+    pub max_header_list_size: Option<usize>,
+    /// Maximum length in octets of a single header field name or value.
+    pub max_string_length: Option<usize>,
+    /// Maximum number of header fields in a decoded header list.
+    pub max_header_fields: Option<usize>,
+}
+
+impl DecoderLimits {
+    /// Check a decoded header field against the limits.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_field` - The decoded header field.
+    /// * `header_list_size` - The size of the header list including the field.
+    /// * `header_fields` - The number of header fields including the field.
+    pub fn check(
+        &self,
+        header_field: &HeaderField,
+        header_list_size: usize,
+        header_fields: usize,
+    ) -> Result<(), Http2Error> {
+        if let Some(max_string_length) = self.max_string_length {
+            let name_length = header_field.name().to_string().len();
+            let value_length = header_field.value().to_string().len();
+            if name_length > max_string_length || value_length > max_string_length {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::EnhanceYourCalm,
+                    format!(
+                        "Header field string exceeds the maximum length of {}",
+                        max_string_length
+                    ),
+                ));
+            }
+        }
+
+        if let Some(max_header_list_size) = self.max_header_list_size {
+            if header_list_size > max_header_list_size {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::EnhanceYourCalm,
+                    format!(
+                        "Header list size exceeds the maximum size of {}",
+                        max_header_list_size
+                    ),
+                ));
+            }
+        }
+
+        if let Some(max_header_fields) = self.max_header_fields {
+            if header_fields > max_header_fields {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::EnhanceYourCalm,
+                    format!(
+                        "Header list exceeds the maximum number of {} header fields",
+                        max_header_fields
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Running check of the string literals of a header block against the
+/// decoder limits.
+///
+/// String literals are checked on their length prefix, before their
+/// octets are copied or Huffman decoded, so that an oversized literal is
+/// rejected without being allocated. The lengths of the literals of a
+/// header block add up toward the maximum size of the header list. The
+/// default budget enforces no limit.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct LiteralBudget {
+    max_string_length: Option<usize>,
+    max_header_list_size: Option<usize>,
+    header_list_size: usize,
+}
+
+impl LiteralBudget {
+    /// Create the budget of a header block.
+    ///
+    /// # Arguments
+    ///
+    /// * `decoder_limits` - The limits of the decoder.
+    pub fn new(decoder_limits: &DecoderLimits) -> Self {
+        LiteralBudget {
+            max_string_length: decoder_limits.max_string_length,
+            max_header_list_size: decoder_limits.max_header_list_size,
+            header_list_size: 0,
+        }
+    }
+
+    /// Check the length of a string literal, as encoded, and add it to the
+    /// size of the header list.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The length prefix of the string literal.
+    pub fn check(&mut self, length: usize) -> Result<(), Http2Error> {
+        if let Some(max_string_length) = self.max_string_length {
+            if length > max_string_length {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::EnhanceYourCalm,
+                    format!(
+                        "Header field string exceeds the maximum length of {}",
+                        max_string_length
+                    ),
+                ));
+            }
+        }
+
+        self.header_list_size = self.header_list_size.saturating_add(length);
+        if let Some(max_header_list_size) = self.max_header_list_size {
+            if self.header_list_size > max_header_list_size {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::EnhanceYourCalm,
+                    format!(
+                        "Header list size exceeds the maximum size of {}",
+                        max_header_list_size
+                    ),
+                ));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Limits applied when encoding a header list.
+///
+/// A peer receiving a header field or a header list larger than it accepts
+/// may treat it as a connection error, killing every stream of the
+/// connection. These limits catch such header lists before they are
+/// encoded, so that nothing is sent and the encoding table is left as is.
+/// Sizes are computed as in SETTINGS_MAX_HEADER_LIST_SIZE: the length of
+/// the name and the value of a header field plus an overhead of 32 octets.
+/// A limit set to `None` is not enforced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct EncoderLimits {
+    /// Maximum size of a single header field.
+    pub max_field_size: Option<usize>,
+    /// Maximum size of a header list, computed as the sum of the header
+    /// field sizes.
+    pub max_list_size: Option<usize>,
+}
+
+impl EncoderLimits {
+    /// Check the header fields of a header list against the limits,
+    /// failing with a HeaderError naming the first offending field.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_fields` - The header fields to encode.
+    pub fn check(&self, header_fields: &[HeaderField]) -> Result<(), Http2Error> {
+        let mut header_list_size: usize = 0;
+
+        for (position, header_field) in header_fields.iter().enumerate() {
+            let field_size = header_field.size();
+            if let Some(max_field_size) = self.max_field_size {
+                if field_size > max_field_size {
+                    return Err(Http2Error::HeaderError(format!(
+                        "Header field {} at position {} has a size of {}, exceeding the maximum field size of {}",
+                        header_field.name_ref(),
+                        position,
+                        field_size,
+                        max_field_size
+                    )));
+                }
+            }
+
+            header_list_size += field_size;
+            if let Some(max_list_size) = self.max_list_size {
+                if header_list_size > max_list_size {
+                    return Err(Http2Error::HeaderError(format!(
+                        "Header field {} at position {} brings the header list size to {}, exceeding the maximum list size of {}",
+                        header_field.name_ref(),
+                        position,
+                        header_list_size,
+                        max_list_size
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
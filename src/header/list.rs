@@ -1,12 +1,28 @@
+use std::collections::HashMap;
 use std::fmt;
 
-use crate::error::Http2Error;
-use crate::header::field::HeaderField;
+use crate::error::{ErrorCode, Http2Error};
+use crate::header::field::{HeaderField, HeaderValue};
+use crate::header::limits::LiteralBudget;
 use crate::header::representation::HeaderRepresentation;
-use crate::header::table::HeaderTable;
+use crate::header::request::UriParts;
+use crate::header::table::{HeaderTable, STATIC_HEADER_FIELDS_TABLE_CONSTANTS};
+
+/// Separator of the cookie pairs of a `cookie` header field.
+const COOKIE_SEPARATOR: &str = "; ";
 
 /// A list of HPACK header fields.
-#[derive(Clone, Debug, PartialEq)]
+///
+/// The order of the header fields is preserved and a name may appear
+/// several times. Lookups by name ignore ASCII case, names are stored as
+/// given and lowercased when encoded, as required by HTTP/2.
+///
+/// Header fields sharing a name keep their relative order from decoding to
+/// encoding, so that a proxy forwards repeated header fields, such as
+/// `set-cookie`, as it received them. Repeated header fields are never
+/// combined, except for `cookie` header fields when cookie crumbling is
+/// enabled on the header table, whose pairs keep their order.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
 pub struct HeaderList {
     header_fields: Vec<HeaderField>,
 }
@@ -17,35 +33,338 @@ impl HeaderList {
         HeaderList { header_fields }
     }
 
+    /// Get the header fields of the header list.
+    pub fn header_fields(&self) -> &Vec<HeaderField> {
+        &self.header_fields
+    }
+
+    /// Get the number of header fields.
+    pub fn len(&self) -> usize {
+        self.header_fields.len()
+    }
+
+    /// Get the uncompressed size of the header list, as limited by
+    /// SETTINGS_MAX_HEADER_LIST_SIZE: the sum of the sizes of its header
+    /// fields, each the length of its name and value plus 32 octets.
+    pub fn size(&self) -> usize {
+        self.header_fields.iter().map(HeaderField::size).sum()
+    }
+
+    /// Check if the header list has no header field.
+    pub fn is_empty(&self) -> bool {
+        self.header_fields.is_empty()
+    }
+
+    /// Iterate over the header fields, in order.
+    pub fn iter(&self) -> std::slice::Iter<'_, HeaderField> {
+        self.header_fields.iter()
+    }
+
+    /// Check if the header list contains a header field.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header field.
+    pub fn contains(&self, name: &str) -> bool {
+        self.get(name).is_some()
+    }
+
+    /// Get the first value of a header field.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header field.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        self.position(name)
+            .map(|position| self.header_fields[position].value_ref().as_str())
+    }
+
+    /// Get all the values of a header field, in order.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header field.
+    pub fn get_all<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> + 'a {
+        self.header_fields
+            .iter()
+            .filter(move |header_field| header_field.name_ref().as_str().eq_ignore_ascii_case(name))
+            .map(|header_field| header_field.value_ref().as_str())
+    }
+
+    /// Set the value of a header field, replacing all of its values.
+    ///
+    /// The header field keeps the position of its first occurrence, or is
+    /// added at the end of the list.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header field.
+    /// * `value` - The value of the header field.
+    pub fn insert(&mut self, name: &str, value: &str) {
+        let header_field = HeaderField::new(name.into(), value.into());
+        let position = self.position(name);
+
+        self.remove(name);
+        let position = position.unwrap_or(self.header_fields.len());
+        self.header_fields.insert(position, header_field);
+    }
+
+    /// Add a value to a header field, keeping its other values.
+    ///
+    /// The header field is added at the end of the list, after the values
+    /// already present, and is encoded in that order.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header field.
+    /// * `value` - The value of the header field.
+    pub fn append(&mut self, name: &str, value: &str) {
+        self.header_fields
+            .push(HeaderField::new(name.into(), value.into()));
+    }
+
+    /// Remove all the values of a header field.
+    ///
+    /// Returns the first removed value, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header field.
+    pub fn remove(&mut self, name: &str) -> Option<HeaderValue> {
+        let value = self
+            .position(name)
+            .map(|position| self.header_fields[position].value());
+        self.header_fields
+            .retain(|header_field| !header_field.name_ref().as_str().eq_ignore_ascii_case(name));
+
+        value
+    }
+
+    /// Get the `:method` pseudo-header field of a request.
+    pub fn method(&self) -> Option<&str> {
+        self.get(":method")
+    }
+
+    /// Get the `:scheme` pseudo-header field of a request.
+    pub fn scheme(&self) -> Option<&str> {
+        self.get(":scheme")
+    }
+
+    /// Get the `:authority` pseudo-header field of a request.
+    pub fn authority(&self) -> Option<&str> {
+        self.get(":authority")
+    }
+
+    /// Set the `:authority` pseudo-header field of a request from the
+    /// authority of an absolute URI, such as `https://example.com:8443/`.
+    ///
+    /// The user information of the URI and its default port are dropped,
+    /// and a `host` header field is removed since `:authority` supersedes
+    /// it. A URI without an authority fails with a
+    /// [`Http2Error::HeaderError`].
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The absolute URI of the request.
+    pub fn set_authority_from_uri(&mut self, uri: &str) -> Result<(), Http2Error> {
+        let authority = UriParts::parse(uri)?.authority();
+
+        self.insert(":authority", &authority);
+        self.remove("host");
+        Ok(())
+    }
+
+    /// Check that the `host` header field of a request identifies the
+    /// same host as its `:authority` pseudo-header field, when both are
+    /// present. Hosts are compared case-insensitively.
+    pub fn host_matches_authority(&self) -> bool {
+        match (self.get("host"), self.authority()) {
+            (Some(host), Some(authority)) => host.eq_ignore_ascii_case(authority),
+            _ => true,
+        }
+    }
+
+    /// Get the `:path` pseudo-header field of a request.
+    pub fn path(&self) -> Option<&str> {
+        self.get(":path")
+    }
+
+    /// Get the `:protocol` pseudo-header field of an extended CONNECT
+    /// request.
+    pub fn protocol(&self) -> Option<&str> {
+        self.get(":protocol")
+    }
+
+    /// Get the `:status` pseudo-header field of a response.
+    ///
+    /// Returns `None` if the status is missing or is not a number.
+    pub fn status(&self) -> Option<u16> {
+        self.get(":status")?.parse().ok()
+    }
+
+    /// Get the position of the first occurrence of a header field.
+    fn position(&self, name: &str) -> Option<usize> {
+        self.header_fields
+            .iter()
+            .position(|header_field| header_field.name_ref().as_str().eq_ignore_ascii_case(name))
+    }
+
     /// Decode a header list from a byte vector and a header table.
     ///
+    /// The decoder limits of the header table are enforced on every
+    /// decoded header field. Dynamic table size updates are only accepted
+    /// at the beginning of the header block. If cookie crumbling is enabled
+    /// on the header table, the `cookie` header fields are reassembled into
+    /// one.
+    ///
+    /// Header field names with uppercase characters are malformed (RFC 9113
+    /// section 8.2.1) and fail with a HeaderError, unless the header table
+    /// is lenient, in which case they are converted to lowercase. So are
+    /// pseudo-header fields following regular header fields (RFC 9113
+    /// section 8.3), unless the lenience of the header table accepts them.
+    ///
+    /// The header block is parsed in place before the header table is
+    /// touched, so that a truncated or invalid header block leaves both the
+    /// byte vector and the header table as they were. A header field failing
+    /// to decode afterwards, such as one referencing an invalid index, leaves
+    /// the dynamic table partially updated: such errors are connection
+    /// errors, which close the connection. The byte vector is consumed once
+    /// the header block is decoded, a malformed header list included, as the
+    /// dynamic table was updated to stay in sync with the peer encoder.
+    ///
     /// # Arguments
     ///
     /// * `bytes` - The byte vector to decode from.
     /// * `header_table` - The header table to use.
     pub fn decode(bytes: &mut Vec<u8>, header_table: &mut HeaderTable) -> Result<Self, Http2Error> {
+        header_table.stats_mut().record_header_block(bytes.len());
+
+        // Parse the header representations, checking the string literals
+        // before they are allocated.
+        let mut remaining: &[u8] = bytes;
+        let mut header_representations: Vec<HeaderRepresentation> = Vec::new();
+        let mut literal_budget = LiteralBudget::new(&header_table.decoder_limits());
+        while !remaining.is_empty() {
+            header_representations.push(HeaderRepresentation::decode(
+                &mut remaining,
+                &mut literal_budget,
+            )?);
+        }
+
+        let (mut headers, malformed) =
+            Self::decode_header_fields(header_representations, header_table)?;
+        bytes.clear();
+
+        if let Some(message) = malformed {
+            return Err(Http2Error::HeaderError(message));
+        }
+
+        if header_table.cookie_crumbling() {
+            headers = reassemble_cookies(headers);
+        }
+
+        Ok(Self {
+            header_fields: headers,
+        })
+    }
+
+    /// Decode the header fields of parsed header representations, updating
+    /// the header table.
+    ///
+    /// Returns the header fields, along with the reason the header list is
+    /// malformed, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_representations` - The header representations of the header block.
+    /// * `header_table` - The header table to use.
+    fn decode_header_fields(
+        header_representations: Vec<HeaderRepresentation>,
+        header_table: &mut HeaderTable,
+    ) -> Result<(Vec<HeaderField>, Option<String>), Http2Error> {
         let mut headers: Vec<HeaderField> = Vec::new();
+        let mut header_list_size: usize = 0;
+        let mut malformed: Option<String> = None;
+        let mut regular_header_field = false;
+        let decoder_limits = header_table.decoder_limits();
+        let lenience = header_table.lenience();
 
-        // While the provided byte vector is not empty.
-        while !bytes.is_empty() {
-            // Decode the header representation.
-            let header_representation = HeaderRepresentation::decode(bytes)?;
+        for header_representation in header_representations {
+            let dynamic_table_hit = is_dynamic_table_hit(&header_representation);
+
+            // A dynamic table size update must occur at the beginning of the header block.
+            if let HeaderRepresentation::SizeUpdate(_) = header_representation {
+                if !headers.is_empty() {
+                    return Err(Http2Error::ConnectionError(
+                        ErrorCode::CompressionError,
+                        "Dynamic table size update after the beginning of the header block"
+                            .to_string(),
+                    ));
+                }
+            }
 
             // Try to build a header field from the header representation.
             // Do nothing if the header representation was not a header field.
-            match HeaderField::from_representation(header_representation, header_table)? {
-                Some(header_field) => headers.push(header_field),
-                None => (),
+            if let Some(mut header_field) =
+                HeaderField::from_representation(header_representation, header_table)?
+            {
+                // Uppercase names are malformed, the rest of the header block
+                // is still decoded to keep the dynamic table in sync.
+                if !header_field.name_ref().is_lowercase() {
+                    if header_table.lenient_header_names() {
+                        header_field.normalize();
+                    } else if malformed.is_none() {
+                        malformed = Some(format!(
+                            "Header field name {} is not lowercase",
+                            header_field.name_ref()
+                        ));
+                    }
+                }
+
+                // Pseudo-header fields precede the regular header fields.
+                if !header_field.name_ref().is_pseudo_header() {
+                    regular_header_field = true;
+                } else if regular_header_field
+                    && !lenience.pseudo_header_order
+                    && malformed.is_none()
+                {
+                    malformed = Some(format!(
+                        "Pseudo-header field {} follows a regular header field",
+                        header_field.name_ref()
+                    ));
+                }
+
+                // Check the decoder limits before accepting the header field.
+                header_list_size += header_field.size();
+                decoder_limits.check(&header_field, header_list_size, headers.len() + 1)?;
+
+                header_table
+                    .stats_mut()
+                    .record_header_field(field_length(&header_field), dynamic_table_hit);
+                headers.push(header_field);
             }
         }
 
-        Ok(Self {
-            header_fields: headers,
-        })
+        Ok((headers, malformed))
     }
 
     /// Encode a header list into a byte vector.
     ///
+    /// Pending dynamic table size updates of the header table are emitted
+    /// at the beginning of the header block. If cookie crumbling is enabled
+    /// on the header table, `cookie` header fields are split into one header
+    /// field per cookie pair. Header field names are sent lowercase.
+    ///
+    /// The encoder limits of the header table are enforced on the header
+    /// fields as sent, crumbled cookies included: a header list exceeding
+    /// them fails with a HeaderError naming the offending field, before
+    /// anything is encoded.
+    ///
+    /// The header block is encoded in a single pass knowing the header
+    /// fields still to come: a header field is not added to the dynamic
+    /// table if its insertion would evict an entry referenced by one of
+    /// them, so that a header field repeated in the list is indexed once.
+    ///
     /// # Arguments
     ///
     /// * `header_table` - The header table to use.
@@ -55,18 +374,169 @@ impl HeaderList {
     /// A byte vector containing the encoded header list.
     pub fn encode(&self, header_table: &mut HeaderTable) -> Result<Vec<u8>, Http2Error> {
         let mut bytes: Vec<u8> = Vec::new();
+        self.encode_into(header_table, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Encode a header list at the end of a byte vector.
+    ///
+    /// The header list is encoded as with [`HeaderList::encode`].
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - The header table to use.
+    /// * `bytes` - The byte vector to encode into.
+    pub fn encode_into(
+        &self,
+        header_table: &mut HeaderTable,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Http2Error> {
+        let start = bytes.len();
+
+        // Gather the header fields of the header block.
+        let header_fields = self.encoded_fields(header_table);
+
+        // Check the encoder limits before touching the header table.
+        header_table.encoder_limits().check(&header_fields)?;
+
+        // Signal the dynamic table size changes first.
+        for max_size in header_table.take_pending_size_updates() {
+            HeaderRepresentation::SizeUpdate(max_size.into()).encode_into(false, false, bytes)?;
+        }
+
+        // Count the occurrences of each header field.
+        let mut ahead: HashMap<&HeaderField, usize> = HashMap::new();
+        for header_field in &header_fields {
+            *ahead.entry(header_field).or_default() += 1;
+        }
+
+        // For each header field in the header block.
+        let huffman_encoding = header_table.huffman_encoding();
+        for header_field in &header_fields {
+            if let Some(count) = ahead.get_mut(header_field) {
+                *count -= 1;
+            }
+
+            // Builds a header representation from the header field, keeping
+            // the entries the following header fields reference.
+            let evicts_ahead = header_table.contains(header_field).is_none()
+                && header_table
+                    .evictions(header_field)
+                    .any(|evicted| ahead.get(evicted).is_some_and(|count| *count > 0));
+            let header_representation = if evicts_ahead {
+                header_field.into_representation_without_indexing(header_table)
+            } else {
+                header_field.into_representation(header_table)
+            };
+
+            // Encode the header representation.
+            header_representation.encode_into(huffman_encoding, huffman_encoding, bytes)?;
 
-        // For each header field in the header list.
+            let stats = header_table.stats_mut();
+            stats.record_header_field(
+                field_length(header_field),
+                is_dynamic_table_hit(&header_representation),
+            );
+            if huffman_encoding {
+                stats.record_huffman_savings(header_representation.huffman_savings());
+            }
+        }
+        header_table
+            .stats_mut()
+            .record_header_block(bytes.len() - start);
+
+        Ok(())
+    }
+
+    /// Get the header fields of the header block encoding the header list
+    /// with a header table: names are lowercase and `cookie` header fields
+    /// are crumbled if cookie crumbling is enabled on the header table.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_table` - The header table to use.
+    pub(crate) fn encoded_fields(&self, header_table: &HeaderTable) -> Vec<HeaderField> {
+        let mut header_fields: Vec<HeaderField> = Vec::new();
         for header_field in &self.header_fields {
-            // Builds a header representation from the header field.
-            let header_representation = header_field.into_representation(header_table);
+            let mut header_field = header_field.clone();
+            header_field.normalize();
+            if header_table.cookie_crumbling() && is_cookie(&header_field) {
+                header_fields.extend(crumble_cookie(&header_field));
+            } else {
+                header_fields.push(header_field);
+            }
+        }
+
+        header_fields
+    }
+}
 
-            // Encode the header representation. TODO: Manage Huffman encoding.
-            bytes.append(&mut header_representation.encode(false, false));
+/// Check if a header representation indexes an entry of the dynamic table.
+fn is_dynamic_table_hit(header_representation: &HeaderRepresentation) -> bool {
+    match header_representation {
+        HeaderRepresentation::Indexed(index) => {
+            u128::from(index) > STATIC_HEADER_FIELDS_TABLE_CONSTANTS.len() as u128
         }
+        _ => false,
+    }
+}
 
-        Ok(bytes)
+/// Get the length of the name and value of a header field.
+fn field_length(header_field: &HeaderField) -> usize {
+    header_field.name_ref().as_str().len() + header_field.value_ref().as_str().len()
+}
+
+/// Check if a header field is a `cookie` header field.
+fn is_cookie(header_field: &HeaderField) -> bool {
+    header_field.name_ref().as_str() == "cookie"
+}
+
+/// Split a `cookie` header field into one header field per cookie pair.
+fn crumble_cookie(header_field: &HeaderField) -> Vec<HeaderField> {
+    header_field
+        .value_ref()
+        .as_str()
+        .split(COOKIE_SEPARATOR)
+        .map(|crumb| {
+            let mut crumb = HeaderField::new(header_field.name(), crumb.into());
+            crumb.set_sensitive(header_field.sensitive());
+            crumb
+        })
+        .collect()
+}
+
+/// Join the `cookie` header fields of a header list into one, at the
+/// position of the first of them.
+fn reassemble_cookies(header_fields: Vec<HeaderField>) -> Vec<HeaderField> {
+    let crumbs: Vec<&str> = header_fields
+        .iter()
+        .filter(|header_field| is_cookie(header_field))
+        .map(|header_field| header_field.value_ref().as_str())
+        .collect();
+    if crumbs.len() < 2 {
+        return header_fields;
     }
+
+    // The cookie is sensitive if any of its crumbs is.
+    let mut cookie = HeaderField::new("cookie".into(), crumbs.join(COOKIE_SEPARATOR).into());
+    cookie.set_sensitive(
+        header_fields
+            .iter()
+            .any(|header_field| is_cookie(header_field) && header_field.sensitive()),
+    );
+    let mut cookie = Some(cookie);
+
+    header_fields
+        .into_iter()
+        .filter_map(|header_field| {
+            if is_cookie(&header_field) {
+                cookie.take()
+            } else {
+                Some(header_field)
+            }
+        })
+        .collect()
 }
 
 impl From<Vec<HeaderField>> for HeaderList {
@@ -80,11 +550,38 @@ impl From<Vec<HeaderField>> for HeaderList {
     }
 }
 
+impl FromIterator<HeaderField> for HeaderList {
+    /// Create a header list from an iterator of header fields.
+    fn from_iter<I: IntoIterator<Item = HeaderField>>(iter: I) -> Self {
+        Self::new(iter.into_iter().collect())
+    }
+}
+
+impl IntoIterator for HeaderList {
+    type Item = HeaderField;
+    type IntoIter = std::vec::IntoIter<HeaderField>;
+
+    /// Iterate over the header fields, in order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.header_fields.into_iter()
+    }
+}
+
+impl<'a> IntoIterator for &'a HeaderList {
+    type Item = &'a HeaderField;
+    type IntoIter = std::slice::Iter<'a, HeaderField>;
+
+    /// Iterate over the header fields, in order.
+    fn into_iter(self) -> Self::IntoIter {
+        self.header_fields.iter()
+    }
+}
+
 impl fmt::Display for HeaderList {
     /// Format a header list.
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         for header_field in &self.header_fields {
-            write!(f, "{}: {}\n", header_field.name(), header_field.value())?;
+            writeln!(f, "{}: {}", header_field.name(), header_field.value())?;
         }
 
         Ok(())
@@ -1,6 +1,10 @@
 pub mod field;
 pub mod huffman;
+pub mod lenience;
+pub mod limits;
 pub mod list;
 pub mod primitive;
 pub mod representation;
+pub mod request;
+pub mod stats;
 pub mod table;
@@ -1,7 +1,15 @@
 use std::fmt;
 
-use crate::error::Http2Error;
-use crate::header::huffman::Tree;
+use crate::error::{CompressionError, Http2Error};
+use crate::header::huffman;
+use crate::header::limits::LiteralBudget;
+
+/// Maximum number of continuation octets of a decoded HPACK Integer.
+///
+/// Six octets carry 42 bits on top of the prefix, more than any index,
+/// string length or table size. A peer sending longer integers is
+/// rejected instead of forcing the decoder into long loops.
+pub const MAX_INTEGER_CONTINUATION_OCTETS: usize = 6;
 
 /// HTTP/2 HPACK Integer Primitive.
 ///
@@ -41,7 +49,7 @@ use crate::header::huffman::Tree;
 /// +---+---------------------------+
 /// | 0 |    Value-(2^N-1) MSB      |
 /// +---+---------------------------+
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct HpackInteger {
     value: u128,
 }
@@ -54,12 +62,21 @@ impl HpackInteger {
     /// * `n` - The number of bits of the prefix.
     pub fn encode(&self, n: u8) -> Result<Vec<u8>, Http2Error> {
         let mut result: Vec<u8> = Vec::new();
+        self.encode_into(n, &mut result)?;
+
+        Ok(result)
+    }
 
+    /// Encode a HPACK Integer at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `n` - The number of bits of the prefix.
+    /// * `bytes` - The byte vector to encode into.
+    pub fn encode_into(&self, n: u8, bytes: &mut Vec<u8>) -> Result<(), Http2Error> {
         // Verify that n <= 8 and n != 0.
         if n > 8 || n == 0 {
-            return Err(Http2Error::HpackError(
-                "Invalid integer prefix size".to_string(),
-            ));
+            return Err(CompressionError::InvalidPrefixSize(n).into());
         }
 
         // Compute the max_prefix_value.
@@ -69,79 +86,85 @@ impl HpackInteger {
         let mut integer: u128 = self.value;
 
         // If the value is smaller than max_prefix_value, encode it on n bits.
-        if (integer as u8) < max_prefix_value {
-            result.push(integer as u8);
-            return Ok(result);
+        if integer < max_prefix_value as u128 {
+            bytes.push(integer as u8);
+            return Ok(());
         }
 
         // Encode the max_prefix_value.
-        result.push(max_prefix_value);
+        bytes.push(max_prefix_value);
 
         // Substract the max_prefix_value from the value.
         integer -= max_prefix_value as u128;
 
         // Encode the integer on the required number of octets.
         while integer >= 128 {
-            result.push((integer % 128 + 128) as u8);
+            bytes.push((integer % 128 + 128) as u8);
             integer /= 128;
         }
 
-        result.push(integer as u8);
+        bytes.push(integer as u8);
 
-        Ok(result)
+        Ok(())
     }
 
     /// Decode a HPACK Integer.
     ///
+    /// The decoded octets are consumed by advancing the input slice. Fails
+    /// if the integer is truncated or spans more than
+    /// [`MAX_INTEGER_CONTINUATION_OCTETS`] continuation octets.
+    ///
     /// # Arguments
     ///
     /// * `n` - The number of bits of the prefix.
     /// * `bytes` - The bytes to decode.
-    pub fn decode(n: u8, bytes: &mut Vec<u8>) -> Result<HpackInteger, Http2Error> {
+    pub fn decode(n: u8, bytes: &mut &[u8]) -> Result<HpackInteger, Http2Error> {
         // Verify that n <= 8 and n != 0.
         if n > 8 || n == 0 {
-            return Err(Http2Error::HpackError(
-                "Invalid integer prefix size".to_string(),
-            ));
+            return Err(CompressionError::InvalidPrefixSize(n).into());
         }
 
         // Compute the maximum prefix value.
         let max_prefix_value = (2u16.pow(n as u32) - 1) as u8;
 
         // If the first byte is smaller than max_prefix_value, decode it on n bits.
-        let masked_prefix = bytes[0] & max_prefix_value;
-        if masked_prefix < max_prefix_value {
-            let result = HpackInteger::from(masked_prefix);
-            match bytes.len() {
-                1 => *bytes = Vec::new(),
-                _ => *bytes = bytes[1..].to_vec(),
+        let masked_prefix = match bytes.split_first() {
+            Some((first, rest)) => {
+                *bytes = rest;
+                first & max_prefix_value
             }
-            return Ok(result);
+            None => return Err(CompressionError::TruncatedInteger.into()),
+        };
+        if masked_prefix < max_prefix_value {
+            return Ok(HpackInteger::from(masked_prefix));
         }
 
         // Decode the integer on the required number of octets.
         let mut integer: u128 = max_prefix_value as u128;
         let mut multiplier: u8 = 0;
 
-        // Skip the first byte.
-        *bytes = bytes[1..].to_vec();
+        for _ in 0..MAX_INTEGER_CONTINUATION_OCTETS {
+            let byte = match bytes.split_first() {
+                Some((first, rest)) => {
+                    *bytes = rest;
+                    *first
+                }
+                None => return Err(CompressionError::TruncatedInteger.into()),
+            };
 
-        loop {
-            integer = match integer
-                .checked_add((bytes[0] & 127) as u128 * 2u128.pow(multiplier as u32))
+            integer = match integer.checked_add((byte & 127) as u128 * 2u128.pow(multiplier as u32))
             {
                 Some(integer) => integer,
-                None => return Err(Http2Error::HpackError("Integer overflow".to_string())),
+                None => return Err(CompressionError::IntegerOverflow.into()),
             };
 
-            if bytes[0] & 128 != 128 {
-                *bytes = bytes[1..].to_vec();
+            if byte & 128 != 128 {
                 return Ok(HpackInteger::from(integer));
-            } else {
-                *bytes = bytes[1..].to_vec();
-                multiplier += 7;
             }
+            multiplier += 7;
         }
+
+        Err(CompressionError::IntegerTooLong.into())
     }
 }
 
@@ -221,113 +244,113 @@ impl From<u8> for HpackInteger {
     }
 }
 
-impl Into<u128> for HpackInteger {
+impl From<HpackInteger> for u128 {
     /// Convert a HPACK Integer into a u128.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u128.
-    fn into(self) -> u128 {
-        self.value
+    fn from(integer: HpackInteger) -> Self {
+        integer.value
     }
 }
 
-impl Into<u128> for &HpackInteger {
+impl From<&HpackInteger> for u128 {
     /// Convert a reference to a HPACK Integer into a u128.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u128.
-    fn into(self) -> u128 {
-        self.value
+    fn from(integer: &HpackInteger) -> Self {
+        integer.value
     }
 }
 
-impl Into<u64> for HpackInteger {
+impl From<HpackInteger> for u64 {
     /// Convert a HPACK Integer into a u64.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u64.
-    fn into(self) -> u64 {
-        self.value as u64
+    fn from(integer: HpackInteger) -> Self {
+        integer.value as u64
     }
 }
 
-impl Into<u64> for &HpackInteger {
+impl From<&HpackInteger> for u64 {
     /// Convert a reference to a HPACK Integer into a u64.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u64.
-    fn into(self) -> u64 {
-        self.value as u64
+    fn from(integer: &HpackInteger) -> Self {
+        integer.value as u64
     }
 }
 
-impl Into<u32> for HpackInteger {
+impl From<HpackInteger> for u32 {
     /// Convert a HPACK Integer into a u32.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u32.
-    fn into(self) -> u32 {
-        self.value as u32
+    fn from(integer: HpackInteger) -> Self {
+        integer.value as u32
     }
 }
 
-impl Into<u32> for &HpackInteger {
+impl From<&HpackInteger> for u32 {
     /// Convert a reference to a HPACK Integer into a u32.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u32.
-    fn into(self) -> u32 {
-        self.value as u32
+    fn from(integer: &HpackInteger) -> Self {
+        integer.value as u32
     }
 }
 
-impl Into<u16> for HpackInteger {
+impl From<HpackInteger> for u16 {
     /// Convert a HPACK Integer into a u16.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u16.
-    fn into(self) -> u16 {
-        self.value as u16
+    fn from(integer: HpackInteger) -> Self {
+        integer.value as u16
     }
 }
 
-impl Into<u16> for &HpackInteger {
+impl From<&HpackInteger> for u16 {
     /// Convert a reference to a HPACK Integer into a u16.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u16.
-    fn into(self) -> u16 {
-        self.value as u16
+    fn from(integer: &HpackInteger) -> Self {
+        integer.value as u16
     }
 }
 
-impl Into<u8> for HpackInteger {
+impl From<HpackInteger> for u8 {
     /// Convert a HPACK Integer into a u8.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u8.
-    fn into(self) -> u8 {
-        self.value as u8
+    fn from(integer: HpackInteger) -> Self {
+        integer.value as u8
     }
 }
 
-impl Into<u8> for &HpackInteger {
+impl From<&HpackInteger> for u8 {
     /// Convert a reference to a HPACK Integer into a u8.
     ///
     /// # Returns
     ///
     /// The HPACK Integer as a u8.
-    fn into(self) -> u8 {
-        self.value as u8
+    fn from(integer: &HpackInteger) -> Self {
+        integer.value as u8
     }
 }
 
@@ -342,7 +365,7 @@ impl TryInto<usize> for HpackInteger {
     fn try_into(self) -> Result<usize, Self::Error> {
         match self.value.try_into() {
             Ok(value) => Ok(value),
-            Err(_) => Err(Http2Error::HpackError("HPACK Integer overflow".to_string())),
+            Err(_) => Err(CompressionError::IntegerOverflow.into()),
         }
     }
 }
@@ -358,7 +381,7 @@ impl TryInto<usize> for &HpackInteger {
     fn try_into(self) -> Result<usize, Self::Error> {
         match self.value.try_into() {
             Ok(value) => Ok(value),
-            Err(_) => Err(Http2Error::HpackError("HPACK Integer overflow".to_string())),
+            Err(_) => Err(CompressionError::IntegerOverflow.into()),
         }
     }
 }
@@ -396,7 +419,7 @@ impl fmt::Display for HpackInteger {
 ///     then the encoded data is the raw octets of the string literal. If
 ///     H is '1', then the encoded data is the Huffman encoding of the
 ///     string literal.
-#[derive(Clone, Debug, PartialEq)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct HpackString {
     s: String,
 }
@@ -406,44 +429,74 @@ impl HpackString {
     ///
     /// # Arguments
     ///
-    /// * `huffman_encode` - Whether or not the string should be Huffman encoded.
+    /// * `huffman_encode` - Whether or not the string should be Huffman
+    ///   encoded, which is not done if it makes the string longer.
     pub fn encode(&self, huffman_encode: bool) -> Result<Vec<u8>, Http2Error> {
         let mut result: Vec<u8> = Vec::new();
+        self.encode_into(huffman_encode, &mut result)?;
 
+        Ok(result)
+    }
+
+    /// Encode a HPACK String at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `huffman_encode` - Whether or not the string should be Huffman
+    ///   encoded, which is not done if it makes the string longer.
+    /// * `bytes` - The byte vector to encode into.
+    pub fn encode_into(&self, huffman_encode: bool, bytes: &mut Vec<u8>) -> Result<(), Http2Error> {
         // Gather the string's octets.
         let string_octets = self.s.as_bytes();
-
-        // Encode the string if Huffman encoding is required. TODO
-        if huffman_encode {}
+        let encoded_len = huffman::encoded_len(string_octets);
+        let huffman_encode = huffman_encode && encoded_len <= string_octets.len();
 
         // Encode the length of the string.
-        let length = HpackInteger::from(string_octets.len() as u128);
-        let length_encoded = length.encode(7)?;
-        result.extend(length_encoded);
-
-        // Encode the string.
-        result.extend(self.s.as_bytes());
+        let start = bytes.len();
+        if huffman_encode {
+            HpackInteger::from(encoded_len as u128).encode_into(7, bytes)?;
+        } else {
+            HpackInteger::from(string_octets.len() as u128).encode_into(7, bytes)?;
+        }
 
-        // Add the H bit if the string is Huffman encoded.
+        // Add the H bit and encode the string.
         if huffman_encode {
-            result[0] |= 0b10000000;
+            bytes[start] |= 0b10000000;
+            huffman::encode_into(string_octets, bytes);
+        } else {
+            bytes.extend_from_slice(string_octets);
         }
 
-        Ok(result)
+        Ok(())
+    }
+
+    /// Get the number of octets Huffman encoding saves on the string, 0 if
+    /// it would make the string longer.
+    pub fn huffman_savings(&self) -> usize {
+        let string_octets = self.s.as_bytes();
+        string_octets
+            .len()
+            .saturating_sub(huffman::encoded_len(string_octets))
     }
 
     /// Decode a HPACK String.
     ///
-    /// The function will delete the bytes that were decoded from the
-    /// input bytes.
+    /// The decoded octets are consumed by advancing the input slice. The
+    /// length of the string is checked against the budget
+    /// of the header block before its octets are copied or Huffman decoded,
+    /// failing with ENHANCE_YOUR_CALM.
     ///
     /// # Arguments
     ///
     /// * `bytes` - The bytes to decode.
-    pub fn decode(bytes: &mut Vec<u8>) -> Result<HpackString, Http2Error> {
+    /// * `literal_budget` - The budget of the string literals of the header block.
+    pub fn decode(
+        bytes: &mut &[u8],
+        literal_budget: &mut LiteralBudget,
+    ) -> Result<HpackString, Http2Error> {
         // Verify that the string is not empty.
-        if bytes.len() == 0 {
-            return Err(Http2Error::HpackError("Invalid string length".to_string()));
+        if bytes.is_empty() {
+            return Err(CompressionError::TruncatedString.into());
         }
 
         // Decode the H bit.
@@ -452,35 +505,24 @@ impl HpackString {
         // Decode the length of the string.
         let length = HpackInteger::decode(7, bytes)?;
         let length = length.value as usize;
-
-        // Verify that the string is not empty.
-        if length == 0 {
-            return Err(Http2Error::HpackError("Invalid string length".to_string()));
-        }
+        literal_budget.check(length)?;
 
         // Verify that the string is not too long.
         if bytes.len() < length {
-            return Err(Http2Error::HpackError("Invalid string length".to_string()));
+            return Err(CompressionError::TruncatedString.into());
         }
 
-        // Gather the string octets.
-        let mut string_octets: Vec<u8> = Vec::new();
-        for i in 0..length {
-            string_octets.push(bytes[i]);
-        }
+        // Gather the string octets and consume them.
+        let (string_octets, rest) = bytes.split_at(length);
+        *bytes = rest;
 
-        // Delete the bytes that were decoded.
-        *bytes = bytes[length..].to_vec();
+        // Decode the string if Huffman encoded.
+        let string = match huffman_encode {
+            true => String::from_utf8_lossy(&huffman::decode(string_octets)?).to_string(),
+            false => String::from_utf8_lossy(string_octets).to_string(),
+        };
 
-        // Decode the string if Huffman encoded. TODO
-        if huffman_encode {
-            let tree: Tree = Tree::new().unwrap();
-            Ok(HpackString::from(tree.decode(&mut string_octets)?))
-        } else {
-            Ok(HpackString::from(
-                String::from_utf8_lossy(&string_octets).to_string(),
-            ))
-        }
+        Ok(HpackString::from(string))
     }
 }
 
@@ -506,25 +548,25 @@ impl From<String> for HpackString {
     }
 }
 
-impl Into<String> for HpackString {
+impl From<HpackString> for String {
     /// Convert a HPACK String into a String.
     ///
     /// # Returns
     ///
     /// The HPACK String as a String.
-    fn into(self) -> String {
-        self.s
+    fn from(string: HpackString) -> Self {
+        string.s
     }
 }
 
-impl Into<String> for &HpackString {
+impl From<&HpackString> for String {
     /// Convert a HPACK String into a String.
     ///
     /// # Returns
     ///
     /// The HPACK String as a String.
-    fn into(self) -> String {
-        self.s.clone()
+    fn from(string: &HpackString) -> Self {
+        string.s.clone()
     }
 }
 
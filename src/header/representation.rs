@@ -1,4 +1,5 @@
-use crate::error::Http2Error;
+use crate::error::{CompressionError, Http2Error};
+use crate::header::limits::LiteralBudget;
 use crate::header::primitive::{HpackInteger, HpackString};
 
 /// HTTP/2 HPACK header field representation.
@@ -133,7 +134,18 @@ pub enum HeaderRepresentation {
 }
 
 impl HeaderRepresentation {
-    pub fn decode(bytes: &mut Vec<u8>) -> Result<HeaderRepresentation, Http2Error> {
+    /// Decode a header field representation.
+    ///
+    /// The decoded octets are consumed by advancing the input slice.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes to decode.
+    /// * `literal_budget` - The budget of the string literals of the header block.
+    pub fn decode(
+        bytes: &mut &[u8],
+        literal_budget: &mut LiteralBudget,
+    ) -> Result<HeaderRepresentation, Http2Error> {
         // Check if it is Indexed Header Field Representation.
         if bytes[0] & 0b1000_0000 == 0b1000_0000 {
             let index = HpackInteger::decode(7, bytes)?;
@@ -145,14 +157,14 @@ impl HeaderRepresentation {
             // Check if it is Literal Header Field with Incremental Indexing -- Indexed Name.
             if bytes[0] & 0b0011_1111 != 0 {
                 let index = HpackInteger::decode(6, bytes)?;
-                let value = HpackString::decode(bytes)?;
+                let value = HpackString::decode(bytes, literal_budget)?;
                 return Ok(HeaderRepresentation::IncrementalIndexingIndexedName(
                     index, value,
                 ));
             } else {
-                *bytes = bytes[1..].to_vec();
-                let name = HpackString::decode(bytes)?;
-                let value = HpackString::decode(bytes)?;
+                *bytes = &bytes[1..];
+                let name = HpackString::decode(bytes, literal_budget)?;
+                let value = HpackString::decode(bytes, literal_budget)?;
                 return Ok(HeaderRepresentation::IncrementalIndexingNewName(
                     name, value,
                 ));
@@ -164,14 +176,14 @@ impl HeaderRepresentation {
             // Check if it is Literal Header Field without Indexing -- Indexed Name.
             if bytes[0] & 0b0000_1111 != 0 {
                 let index = HpackInteger::decode(4, bytes)?;
-                let value = HpackString::decode(bytes)?;
+                let value = HpackString::decode(bytes, literal_budget)?;
                 return Ok(HeaderRepresentation::WithoutIndexingIndexedName(
                     index, value,
                 ));
             } else {
-                *bytes = bytes[1..].to_vec();
-                let name = HpackString::decode(bytes)?;
-                let value = HpackString::decode(bytes)?;
+                *bytes = &bytes[1..];
+                let name = HpackString::decode(bytes, literal_budget)?;
+                let value = HpackString::decode(bytes, literal_budget)?;
                 return Ok(HeaderRepresentation::WithoutIndexingNewName(name, value));
             }
         }
@@ -181,12 +193,12 @@ impl HeaderRepresentation {
             // Check if it is Literal Header Field Never Indexed -- Indexed Name.
             if bytes[0] & 0b0000_1111 != 0 {
                 let index = HpackInteger::decode(4, bytes)?;
-                let value = HpackString::decode(bytes)?;
+                let value = HpackString::decode(bytes, literal_budget)?;
                 return Ok(HeaderRepresentation::NeverIndexedIndexedName(index, value));
             } else {
-                *bytes = bytes[1..].to_vec();
-                let name = HpackString::decode(bytes)?;
-                let value = HpackString::decode(bytes)?;
+                *bytes = &bytes[1..];
+                let name = HpackString::decode(bytes, literal_budget)?;
+                let value = HpackString::decode(bytes, literal_budget)?;
                 return Ok(HeaderRepresentation::NeverIndexedNewName(name, value));
             }
         }
@@ -197,63 +209,97 @@ impl HeaderRepresentation {
             return Ok(HeaderRepresentation::SizeUpdate(max_size));
         }
 
-        Err(Http2Error::HpackError(
-            "Invalid header field representation".to_string(),
-        ))
+        Err(CompressionError::InvalidRepresentation.into())
     }
 
     /// Encodes the header field representation into a byte vector.
-    pub fn encode(&self, huffman_encode_name: bool, huffman_encode_value: bool) -> Vec<u8> {
+    ///
+    /// # Arguments
+    ///
+    /// * `huffman_encode_name` - Whether the literal name may be Huffman encoded.
+    /// * `huffman_encode_value` - Whether the literal value may be Huffman encoded.
+    pub fn encode(
+        &self,
+        huffman_encode_name: bool,
+        huffman_encode_value: bool,
+    ) -> Result<Vec<u8>, Http2Error> {
+        let mut bytes: Vec<u8> = Vec::new();
+        self.encode_into(huffman_encode_name, huffman_encode_value, &mut bytes)?;
+
+        Ok(bytes)
+    }
+
+    /// Encodes the header field representation at the end of a byte vector.
+    ///
+    /// # Arguments
+    ///
+    /// * `huffman_encode_name` - Whether the literal name may be Huffman encoded.
+    /// * `huffman_encode_value` - Whether the literal value may be Huffman encoded.
+    /// * `bytes` - The byte vector to encode into.
+    pub fn encode_into(
+        &self,
+        huffman_encode_name: bool,
+        huffman_encode_value: bool,
+        bytes: &mut Vec<u8>,
+    ) -> Result<(), Http2Error> {
+        let start = bytes.len();
+
         match self {
             HeaderRepresentation::Indexed(index) => {
-                let mut bytes = index.encode(7).unwrap();
-                bytes[0] |= 0b1000_0000;
-                bytes
+                index.encode_into(7, bytes)?;
+                bytes[start] |= 0b1000_0000;
             }
             HeaderRepresentation::IncrementalIndexingIndexedName(index, value) => {
-                let mut bytes = index.encode(6).unwrap();
-                bytes[0] |= 0b0100_0000;
-                bytes.append(&mut value.encode(huffman_encode_value).unwrap());
-                bytes
+                index.encode_into(6, bytes)?;
+                bytes[start] |= 0b0100_0000;
+                value.encode_into(huffman_encode_value, bytes)?;
             }
             HeaderRepresentation::IncrementalIndexingNewName(name, value) => {
-                let mut bytes: Vec<u8> = Vec::new();
                 bytes.push(0b0100_0000);
-                bytes.append(&mut name.encode(huffman_encode_name).unwrap());
-                bytes.append(&mut value.encode(huffman_encode_value).unwrap());
-                bytes
+                name.encode_into(huffman_encode_name, bytes)?;
+                value.encode_into(huffman_encode_value, bytes)?;
             }
             HeaderRepresentation::WithoutIndexingIndexedName(index, value) => {
-                let mut bytes = index.encode(4).unwrap();
-                bytes[0] |= 0b0000_0000;
-                bytes.append(&mut value.encode(huffman_encode_value).unwrap());
-                bytes
+                index.encode_into(4, bytes)?;
+                bytes[start] |= 0b0000_0000;
+                value.encode_into(huffman_encode_value, bytes)?;
             }
             HeaderRepresentation::WithoutIndexingNewName(name, value) => {
-                let mut bytes: Vec<u8> = Vec::new();
                 bytes.push(0b0000_0000);
-                bytes.append(&mut name.encode(huffman_encode_name).unwrap());
-                bytes.append(&mut value.encode(huffman_encode_value).unwrap());
-                bytes
+                name.encode_into(huffman_encode_name, bytes)?;
+                value.encode_into(huffman_encode_value, bytes)?;
             }
             HeaderRepresentation::NeverIndexedIndexedName(index, value) => {
-                let mut bytes = index.encode(4).unwrap();
-                bytes[0] |= 0b0001_0000;
-                bytes.append(&mut value.encode(huffman_encode_value).unwrap());
-                bytes
+                index.encode_into(4, bytes)?;
+                bytes[start] |= 0b0001_0000;
+                value.encode_into(huffman_encode_value, bytes)?;
             }
             HeaderRepresentation::NeverIndexedNewName(name, value) => {
-                let mut bytes: Vec<u8> = Vec::new();
                 bytes.push(0b0001_0000);
-                bytes.append(&mut name.encode(huffman_encode_name).unwrap());
-                bytes.append(&mut value.encode(huffman_encode_value).unwrap());
-                bytes
+                name.encode_into(huffman_encode_name, bytes)?;
+                value.encode_into(huffman_encode_value, bytes)?;
             }
             HeaderRepresentation::SizeUpdate(max_size) => {
-                let mut bytes = max_size.encode(5).unwrap();
-                bytes[0] |= 0b0010_0000;
-                bytes
+                max_size.encode_into(5, bytes)?;
+                bytes[start] |= 0b0010_0000;
+            }
+        }
+
+        Ok(())
+    }
+    /// Get the number of octets Huffman encoding saves on the string
+    /// literals of the representation.
+    pub fn huffman_savings(&self) -> usize {
+        match self {
+            HeaderRepresentation::IncrementalIndexingIndexedName(_, value)
+            | HeaderRepresentation::WithoutIndexingIndexedName(_, value)
+            | HeaderRepresentation::NeverIndexedIndexedName(_, value) => value.huffman_savings(),
+            HeaderRepresentation::IncrementalIndexingNewName(name, value)
+            | HeaderRepresentation::WithoutIndexingNewName(name, value)
+            | HeaderRepresentation::NeverIndexedNewName(name, value) => {
+                name.huffman_savings() + value.huffman_savings()
             }
+            HeaderRepresentation::Indexed(_) | HeaderRepresentation::SizeUpdate(_) => 0,
         }
     }
 }
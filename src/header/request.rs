@@ -0,0 +1,246 @@
+use crate::error::Http2Error;
+use crate::header::list::HeaderList;
+
+/// Components of an absolute URI, as carried by request pseudo-header
+/// fields.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) struct UriParts<'a> {
+    pub scheme: String,
+    pub host: String,
+    pub port: Option<&'a str>,
+    pub path: &'a str,
+}
+
+impl<'a> UriParts<'a> {
+    /// Split an absolute URI into its scheme, authority and path.
+    ///
+    /// The scheme and host are lowercased, the user information and the
+    /// fragment are dropped. The path and query are kept as written, so that
+    /// percent-encoded octets are preserved.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The absolute URI to split.
+    pub fn parse(uri: &'a str) -> Result<Self, Http2Error> {
+        if uri.bytes().any(|byte| !byte.is_ascii_graphic()) {
+            return Err(Http2Error::HeaderError(format!(
+                "URI {} contains invalid characters",
+                uri
+            )));
+        }
+
+        let (scheme, rest) = uri
+            .split_once("://")
+            .filter(|(scheme, _)| is_scheme(scheme))
+            .ok_or_else(|| Http2Error::HeaderError(format!("URI {} has no scheme", uri)))?;
+        let scheme = scheme.to_ascii_lowercase();
+
+        let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+        let (authority, path) = rest.split_at(end);
+        let authority = authority
+            .rsplit_once('@')
+            .map_or(authority, |(_, host)| host);
+
+        // The port follows the last colon, outside of an IPv6 literal.
+        let (host, port) = match authority.rfind(':') {
+            Some(colon) if !authority[colon..].contains(']') => {
+                (&authority[..colon], Some(&authority[colon + 1..]))
+            }
+            _ => (authority, None),
+        };
+        if host.is_empty() {
+            return Err(Http2Error::HeaderError(format!(
+                "URI {} has no authority",
+                uri
+            )));
+        }
+        if port.is_some_and(|port| !port.bytes().all(|byte| byte.is_ascii_digit())) {
+            return Err(Http2Error::HeaderError(format!(
+                "URI {} has an invalid port",
+                uri
+            )));
+        }
+
+        let path = path.split('#').next().unwrap_or_default();
+
+        Ok(UriParts {
+            scheme,
+            host: host.to_ascii_lowercase(),
+            port: port.filter(|port| !port.is_empty()),
+            path,
+        })
+    }
+
+    /// Get the authority of the URI, omitting the default port of the
+    /// scheme.
+    pub fn authority(&self) -> String {
+        match self.port {
+            Some(port) if Some(port) != default_port(&self.scheme) => {
+                format!("{}:{}", self.host, port)
+            }
+            _ => self.host.clone(),
+        }
+    }
+
+    /// Get the authority of the URI with an explicit port, as required by
+    /// CONNECT requests. The default port of the scheme is used if the URI
+    /// has none.
+    pub fn authority_with_port(&self) -> String {
+        match self.port.or_else(|| default_port(&self.scheme)) {
+            Some(port) => format!("{}:{}", self.host, port),
+            None => self.host.clone(),
+        }
+    }
+}
+
+/// Check that a string is a URI scheme (RFC 3986 section 3.1).
+fn is_scheme(scheme: &str) -> bool {
+    scheme.starts_with(|c: char| c.is_ascii_alphabetic())
+        && scheme
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"+-.".contains(&byte))
+}
+
+/// Get the default port of a scheme, if known.
+fn default_port(scheme: &str) -> Option<&'static str> {
+    match scheme {
+        "http" => Some("80"),
+        "https" => Some("443"),
+        _ => None,
+    }
+}
+
+/// Check that a string is a token, as required of methods (RFC 9110
+/// section 5.6.2).
+fn is_token(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .bytes()
+            .all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte))
+}
+
+/// Entry point for building request header sections.
+#[derive(Clone, Copy, Debug)]
+pub struct Request;
+
+impl Request {
+    /// Create a builder of request header sections.
+    pub fn builder() -> RequestBuilder {
+        RequestBuilder::new()
+    }
+}
+
+/// Builder of request header sections.
+///
+/// The pseudo-header fields are built from the method and URI of the
+/// request: `:method`, then `:scheme`, `:authority` and `:path`, followed by
+/// the header fields of the request. The default port of the scheme is
+/// omitted from `:authority`, and the path and query are kept as written.
+/// CONNECT requests only carry `:method` and `:authority`, which then always
+/// has a port.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RequestBuilder {
+    method: String,
+    uri: Option<String>,
+    header_list: HeaderList,
+}
+
+impl RequestBuilder {
+    /// Create a builder of GET requests.
+    pub fn new() -> Self {
+        RequestBuilder {
+            method: "GET".to_string(),
+            uri: None,
+            header_list: HeaderList::default(),
+        }
+    }
+
+    /// Set the method of the request, GET by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method of the request.
+    pub fn method(mut self, method: &str) -> Self {
+        self.method = method.to_string();
+        self
+    }
+
+    /// Set the absolute URI of the request, such as
+    /// `https://example.com/path?x=1`.
+    ///
+    /// # Arguments
+    ///
+    /// * `uri` - The URI of the request.
+    pub fn uri(mut self, uri: &str) -> Self {
+        self.uri = Some(uri.to_string());
+        self
+    }
+
+    /// Add a header field to the request.
+    ///
+    /// The name is lowercased, as required by HTTP/2.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the header field.
+    /// * `value` - The value of the header field.
+    pub fn header(mut self, name: &str, value: &str) -> Self {
+        self.header_list.append(&name.to_ascii_lowercase(), value);
+        self
+    }
+
+    /// Build the header section of the request.
+    ///
+    /// An invalid method, a missing or invalid URI, or a `host` header
+    /// field disagreeing with the authority of the URI fails with a
+    /// [`Http2Error::HeaderError`]. An empty path is sent as `/`, or as `*`
+    /// for OPTIONS requests.
+    pub fn build(&self) -> Result<HeaderList, Http2Error> {
+        if !is_token(&self.method) {
+            return Err(Http2Error::HeaderError(format!(
+                "Invalid request method {}",
+                self.method
+            )));
+        }
+        let uri = self
+            .uri
+            .as_deref()
+            .ok_or_else(|| Http2Error::HeaderError("Request has no URI".to_string()))?;
+        let uri_parts = UriParts::parse(uri)?;
+
+        let mut header_list = HeaderList::default();
+        header_list.append(":method", &self.method);
+        if self.method == "CONNECT" {
+            header_list.append(":authority", &uri_parts.authority_with_port());
+        } else {
+            header_list.append(":scheme", &uri_parts.scheme);
+            header_list.append(":authority", &uri_parts.authority());
+            match uri_parts.path {
+                "" if self.method == "OPTIONS" => header_list.append(":path", "*"),
+                path if !path.starts_with('/') => {
+                    header_list.append(":path", &format!("/{}", path))
+                }
+                path => header_list.append(":path", path),
+            }
+        }
+
+        let header_list: HeaderList = header_list
+            .into_iter()
+            .chain(self.header_list.clone())
+            .collect();
+        if !header_list.host_matches_authority() {
+            return Err(Http2Error::HeaderError(format!(
+                "Host header field differs from the authority of {}",
+                uri
+            )));
+        }
+
+        Ok(header_list)
+    }
+}
+
+impl Default for RequestBuilder {
+    fn default() -> Self {
+        RequestBuilder::new()
+    }
+}
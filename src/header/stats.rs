@@ -0,0 +1,119 @@
+/// HPACK statistics of a header table.
+///
+/// The counters are accumulated over the header blocks encoded or decoded
+/// with a header table, e.g. to tune SETTINGS_HEADER_TABLE_SIZE from real
+/// workloads: a low dynamic table hit rate along with many evictions calls
+/// for a larger dynamic table.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct HpackStats {
+    header_blocks: u64,
+    header_fields: u64,
+    dynamic_table_hits: u64,
+    uncompressed_bytes: u64,
+    compressed_bytes: u64,
+    evictions: u64,
+    huffman_savings: u64,
+}
+
+impl HpackStats {
+    /// Count a header field of a header block.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The length of the name and value of the header field.
+    /// * `dynamic_table_hit` - Whether the header field was represented by
+    ///   an index in the dynamic table.
+    pub(crate) fn record_header_field(&mut self, length: usize, dynamic_table_hit: bool) {
+        self.header_fields += 1;
+        self.uncompressed_bytes += length as u64;
+        if dynamic_table_hit {
+            self.dynamic_table_hits += 1;
+        }
+    }
+
+    /// Count a header block.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The length of the encoded header block.
+    pub(crate) fn record_header_block(&mut self, length: usize) {
+        self.header_blocks += 1;
+        self.compressed_bytes += length as u64;
+    }
+
+    /// Count the octets saved by Huffman encoding string literals.
+    ///
+    /// # Arguments
+    ///
+    /// * `length` - The number of octets saved.
+    pub(crate) fn record_huffman_savings(&mut self, length: usize) {
+        self.huffman_savings += length as u64;
+    }
+
+    /// Set the number of entries evicted from the dynamic table.
+    ///
+    /// # Arguments
+    ///
+    /// * `evictions` - The number of entries evicted.
+    pub(crate) fn set_evictions(&mut self, evictions: u64) {
+        self.evictions = evictions;
+    }
+
+    /// Number of header blocks encoded or decoded.
+    pub fn header_blocks(&self) -> u64 {
+        self.header_blocks
+    }
+
+    /// Number of header fields encoded or decoded.
+    pub fn header_fields(&self) -> u64 {
+        self.header_fields
+    }
+
+    /// Number of header fields represented by an index in the dynamic
+    /// table.
+    pub fn dynamic_table_hits(&self) -> u64 {
+        self.dynamic_table_hits
+    }
+
+    /// Share of the header fields represented by an index in the dynamic
+    /// table, between 0 and 1.
+    pub fn dynamic_table_hit_rate(&self) -> f64 {
+        if self.header_fields == 0 {
+            return 0.0;
+        }
+
+        self.dynamic_table_hits as f64 / self.header_fields as f64
+    }
+
+    /// Length of the names and values of the header fields, before
+    /// compression.
+    pub fn uncompressed_bytes(&self) -> u64 {
+        self.uncompressed_bytes
+    }
+
+    /// Length of the header blocks, after compression.
+    pub fn compressed_bytes(&self) -> u64 {
+        self.compressed_bytes
+    }
+
+    /// Ratio of the length of the header blocks to the length of the header
+    /// fields they carry, 1 if no header field was compressed.
+    pub fn compression_ratio(&self) -> f64 {
+        if self.uncompressed_bytes == 0 {
+            return 1.0;
+        }
+
+        self.compressed_bytes as f64 / self.uncompressed_bytes as f64
+    }
+
+    /// Number of entries evicted from the dynamic table.
+    pub fn evictions(&self) -> u64 {
+        self.evictions
+    }
+
+    /// Number of octets saved by Huffman encoding string literals, only
+    /// counted when encoding.
+    pub fn huffman_savings(&self) -> u64 {
+        self.huffman_savings
+    }
+}
@@ -1,6 +1,16 @@
-use crate::error::Http2Error;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+
+use crate::error::{CompressionError, Http2Error};
 use crate::header::field::HeaderField;
 use crate::header::field::{HeaderName, HeaderValue};
+use crate::header::lenience::LenienceConfig;
+use crate::header::limits::{DecoderLimits, EncoderLimits};
+use crate::header::stats::HpackStats;
+use crate::trace;
+
+/// Initial value of SETTINGS_HEADER_TABLE_SIZE (4,096 octets).
+pub const DEFAULT_HEADER_TABLE_SIZE: usize = 4_096;
 
 /// HTTP/2 HPACK header table.
 ///
@@ -17,11 +27,33 @@ use crate::header::field::{HeaderName, HeaderValue};
 pub struct HeaderTable {
     static_table: StaticTable,
     dynamic_table: DynamicTable,
+    decoder_limits: DecoderLimits,
+    encoder_limits: EncoderLimits,
+    settings_max_size: usize,
+    pending_size_update: Option<PendingSizeUpdate>,
+    cookie_crumbling: bool,
+    huffman_encoding: bool,
+    lenience: LenienceConfig,
+    pass_through: bool,
+    stats: HpackStats,
+}
+
+/// Dynamic table size changes not yet signaled to the peer decoder.
+///
+/// If the maximum size was changed several times between two header
+/// blocks, the smallest value reached must be signaled before the final one.
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct PendingSizeUpdate {
+    smallest: usize,
+    last: usize,
 }
 
 impl HeaderTable {
     /// Create a new header table.
     ///
+    /// The maximum size is also used as the SETTINGS_HEADER_TABLE_SIZE bound
+    /// that dynamic table size updates cannot exceed.
+    ///
     /// # Arguments
     ///
     /// * `dynamic_table_max_size` - The maximum size of the dynamic table.
@@ -29,20 +61,227 @@ impl HeaderTable {
         HeaderTable {
             static_table: StaticTable::from(STATIC_HEADER_FIELDS_TABLE_CONSTANTS),
             dynamic_table: DynamicTable::new(dynamic_table_max_size),
+            decoder_limits: DecoderLimits::default(),
+            encoder_limits: EncoderLimits::default(),
+            settings_max_size: dynamic_table_max_size,
+            pending_size_update: None,
+            cookie_crumbling: true,
+            huffman_encoding: false,
+            lenience: LenienceConfig::strict(),
+            pass_through: false,
+            stats: HpackStats::default(),
         }
     }
 
+    /// Change the maximum size of the dynamic table on the encoder side.
+    ///
+    /// The change is applied immediately and a dynamic table size update
+    /// will be emitted at the beginning of the next encoded header block.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_size` - The new maximum size of the dynamic table.
+    pub fn set_pending_size_update(&mut self, max_size: usize) {
+        self.set_max_size(max_size);
+
+        self.pending_size_update = match self.pending_size_update {
+            Some(pending) => Some(PendingSizeUpdate {
+                smallest: pending.smallest.min(max_size),
+                last: max_size,
+            }),
+            None => Some(PendingSizeUpdate {
+                smallest: max_size,
+                last: max_size,
+            }),
+        };
+    }
+
+    /// Take the dynamic table size updates to emit at the beginning of the
+    /// next header block.
+    pub fn take_pending_size_updates(&mut self) -> Vec<usize> {
+        match self.pending_size_update.take() {
+            Some(pending) if pending.smallest < pending.last => {
+                vec![pending.smallest, pending.last]
+            }
+            Some(pending) => vec![pending.last],
+            None => Vec::new(),
+        }
+    }
+
+    /// Get the SETTINGS_HEADER_TABLE_SIZE bound of the dynamic table.
+    pub fn settings_max_size(&self) -> usize {
+        self.settings_max_size
+    }
+
+    /// Set the SETTINGS_HEADER_TABLE_SIZE bound of the dynamic table.
+    ///
+    /// Dynamic table size updates received afterwards cannot exceed it.
+    ///
+    /// # Arguments
+    ///
+    /// * `settings_max_size` - The value of SETTINGS_HEADER_TABLE_SIZE.
+    pub fn set_settings_max_size(&mut self, settings_max_size: usize) {
+        self.settings_max_size = settings_max_size;
+    }
+
+    /// Get the limits applied when decoding header lists with this table.
+    pub fn decoder_limits(&self) -> DecoderLimits {
+        self.decoder_limits
+    }
+
+    /// Set the limits applied when decoding header lists with this table.
+    ///
+    /// # Arguments
+    ///
+    /// * `decoder_limits` - The decoder limits.
+    pub fn set_decoder_limits(&mut self, decoder_limits: DecoderLimits) {
+        self.decoder_limits = decoder_limits;
+    }
+
+    /// Get the limits applied when encoding header lists with this table.
+    pub fn encoder_limits(&self) -> EncoderLimits {
+        self.encoder_limits
+    }
+
+    /// Set the limits applied when encoding header lists with this table.
+    ///
+    /// # Arguments
+    ///
+    /// * `encoder_limits` - The encoder limits.
+    pub fn set_encoder_limits(&mut self, encoder_limits: EncoderLimits) {
+        self.encoder_limits = encoder_limits;
+    }
+
+    /// Check if `cookie` header fields are crumbled by the header lists
+    /// encoded with this table, and reassembled by the ones decoded with it.
+    pub fn cookie_crumbling(&self) -> bool {
+        self.cookie_crumbling
+    }
+
+    /// Enable or disable `cookie` header field crumbling, enabled by default.
+    ///
+    /// Splitting a `cookie` header field into one header field per cookie
+    /// pair lets the pairs that do not change between requests be indexed
+    /// in the dynamic table. The crumbs are joined back with "; " after
+    /// decoding, as they must be before being passed to a non-HTTP/2
+    /// context.
+    ///
+    /// # Arguments
+    ///
+    /// * `cookie_crumbling` - Whether `cookie` header fields are crumbled.
+    pub fn set_cookie_crumbling(&mut self, cookie_crumbling: bool) {
+        self.cookie_crumbling = cookie_crumbling;
+    }
+
+    /// Check if the string literals of the header lists encoded with this
+    /// table are Huffman encoded.
+    pub fn huffman_encoding(&self) -> bool {
+        self.huffman_encoding
+    }
+
+    /// Enable or disable the Huffman encoding of string literals, disabled
+    /// by default.
+    ///
+    /// A string literal is not Huffman encoded if it makes it longer.
+    ///
+    /// # Arguments
+    ///
+    /// * `huffman_encoding` - Whether string literals are Huffman encoded.
+    pub fn set_huffman_encoding(&mut self, huffman_encoding: bool) {
+        self.huffman_encoding = huffman_encoding;
+    }
+
+    /// Check if the header lists decoded with this table accept header
+    /// field names with uppercase characters.
+    pub fn lenient_header_names(&self) -> bool {
+        self.lenience.uppercase_header_names
+    }
+
+    /// Accept or reject header field names with uppercase characters when
+    /// decoding, rejected by default.
+    ///
+    /// Uppercase names are malformed in HTTP/2. A lenient table converts
+    /// them to lowercase instead, to interoperate with misbehaving peers.
+    ///
+    /// # Arguments
+    ///
+    /// * `lenient_header_names` - Whether uppercase names are accepted.
+    pub fn set_lenient_header_names(&mut self, lenient_header_names: bool) {
+        self.lenience.uppercase_header_names = lenient_header_names;
+    }
+
+    /// Get how strictly the frames and header blocks decoded with this
+    /// table are checked.
+    pub fn lenience(&self) -> LenienceConfig {
+        self.lenience
+    }
+
+    /// Set how strictly the frames and header blocks decoded with this
+    /// table are checked, strictly by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `lenience` - The lenience configuration.
+    pub fn set_lenience(&mut self, lenience: LenienceConfig) {
+        self.lenience = lenience;
+    }
+
+    /// Check if the frames decoded with this table keep their header block
+    /// fragments opaque instead of decoding them.
+    pub fn pass_through(&self) -> bool {
+        self.pass_through
+    }
+
+    /// Enable or disable the pass-through of header blocks, disabled by
+    /// default.
+    ///
+    /// A proxy relaying frames between two connections does not need the
+    /// header lists it forwards. With pass-through enabled, HEADERS,
+    /// PUSH_PROMISE and CONTINUATION frames keep their header block
+    /// fragments as received, serialize them unchanged and leave the
+    /// dynamic table untouched. The fragments can still be decoded later,
+    /// in the order they were received, with `decode_header_block`.
+    ///
+    /// # Arguments
+    ///
+    /// * `pass_through` - Whether header blocks are kept opaque.
+    pub fn set_pass_through(&mut self, pass_through: bool) {
+        self.pass_through = pass_through;
+    }
+
+    /// Get the HPACK statistics of the header blocks encoded or decoded
+    /// with the header table.
+    pub fn stats(&self) -> HpackStats {
+        let mut stats = self.stats;
+        stats.set_evictions(self.dynamic_table.eviction_count());
+
+        stats
+    }
+
+    /// Get the HPACK statistics to update.
+    pub(crate) fn stats_mut(&mut self) -> &mut HpackStats {
+        &mut self.stats
+    }
+
     /// Get a header field from the header table.
     ///
+    /// Indices start at 1 with the static table, followed by the dynamic
+    /// table. Index 0 and indices beyond the dynamic table are compression
+    /// errors.
+    ///
     /// # Arguments
     ///
     /// * `index` - The index of the header field to get.
     pub fn get(&self, index: usize) -> Result<HeaderField, Http2Error> {
-        if index <= self.static_table.len() {
-            self.static_table.get(index - 1)
-        } else {
-            self.dynamic_table.get(index - self.static_table.len() - 1)
-        }
+        let header_field = match index.checked_sub(1) {
+            None => return Err(CompressionError::ZeroIndex.into()),
+            Some(index) => match index.checked_sub(self.static_table.len()) {
+                None => self.static_table.get(index),
+                Some(index) => self.dynamic_table.get(index),
+            },
+        };
+
+        header_field.map_err(|_| CompressionError::InvalidIndex(index).into())
     }
 
     /// Insert a header field into the header table.
@@ -52,6 +291,17 @@ impl HeaderTable {
     /// * `header_field` - The header field to insert.
     pub fn add_entry(&mut self, header_field: HeaderField) {
         self.dynamic_table.add_entry(header_field);
+        trace::header_table_size(self.dynamic_table.size(), self.dynamic_table.max_size());
+    }
+
+    /// Get the entries of the dynamic table that adding a header field
+    /// would evict, the oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_field` - The header field to add.
+    pub fn evictions(&self, header_field: &HeaderField) -> impl Iterator<Item = &HeaderField> {
+        self.dynamic_table.evictions(header_field.size())
     }
 
     /// Get the index of a header field in the header table.
@@ -101,19 +351,68 @@ impl HeaderTable {
     /// * `max_size` - The maximum size of the dynamic table.
     pub fn set_max_size(&mut self, max_size: usize) {
         self.dynamic_table.set_max_size(max_size);
+        trace::header_table_size(self.dynamic_table.size(), self.dynamic_table.max_size());
     }
 
     /// Get the current size of the dynamic table.
     pub fn get_dynamic_table_size(&self) -> usize {
         self.dynamic_table.size()
     }
+
+    /// Get the maximum size of the dynamic table.
+    pub fn get_dynamic_table_max_size(&self) -> usize {
+        self.dynamic_table.max_size()
+    }
+
+    /// Get the entries of the dynamic table, the most recent first.
+    ///
+    /// Entries are numbered from 1 within the dynamic table, as in the
+    /// examples of RFC 7541 appendix C. The index of an entry in the header
+    /// table is offset by the length of the static table.
+    pub fn dynamic_entries(&self) -> impl Iterator<Item = (usize, &HeaderField)> {
+        self.dynamic_table
+            .entries
+            .iter()
+            .enumerate()
+            .map(|(index, (_, header_field))| (index + 1, header_field))
+    }
+}
+
+impl fmt::Display for HeaderTable {
+    /// Format the dynamic table as in the examples of RFC 7541 appendix C.
+    ///
+    /// ```text
+    /// [  1] (s =  57) :authority: www.example.com
+    ///       Table size:  57
+    /// ```
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (index, header_field) in self.dynamic_entries() {
+            writeln!(
+                f,
+                "[{:>3}] (s = {:>3}) {}",
+                index,
+                header_field.size(),
+                header_field
+            )?;
+        }
+        write!(f, "      Table size: {:>3}", self.dynamic_table.size())
+    }
 }
 
 /// HTTP/2 HPACK dynamic table.
+///
+/// Entries are stored in a ring buffer, the most recent entry first, and
+/// the table size is maintained incrementally. Every entry is stamped with
+/// an insertion sequence number so that the index of an entry can be
+/// computed from the hash maps used to look up header fields and names.
 pub struct DynamicTable {
-    entries: Vec<HeaderField>,
+    entries: VecDeque<(u64, HeaderField)>,
+    fields: HashMap<HeaderField, u64>,
+    names: HashMap<HeaderName, u64>,
+    next_sequence: u64,
     size: usize,
     max_size: usize,
+    eviction_count: u64,
 }
 
 impl DynamicTable {
@@ -124,9 +423,13 @@ impl DynamicTable {
     /// * `max_size` - The maximum size of the dynamic table.
     pub fn new(max_size: usize) -> DynamicTable {
         DynamicTable {
-            entries: Vec::new(),
+            entries: VecDeque::new(),
+            fields: HashMap::new(),
+            names: HashMap::new(),
+            next_sequence: 0,
             max_size,
             size: 0,
+            eviction_count: 0,
         }
     }
 
@@ -135,6 +438,11 @@ impl DynamicTable {
         self.entries.len()
     }
 
+    /// Check if the dynamic table has no entries.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
     /// Get the size of the dynamic table.
     pub fn size(&self) -> usize {
         self.size
@@ -145,26 +453,24 @@ impl DynamicTable {
         self.max_size
     }
 
+    /// Get the number of entries evicted since the dynamic table was
+    /// created.
+    pub fn eviction_count(&self) -> u64 {
+        self.eviction_count
+    }
+
     /// Get a header field from the dynamic table.
     ///
+    /// Indices start at 0 with the most recent entry. Indices beyond the
+    /// dynamic table fail with [`CompressionError::InvalidIndex`].
+    ///
     /// # Arguments
     ///
     /// * `index` - The index of the header field to get.
     pub fn get(&self, index: usize) -> Result<HeaderField, Http2Error> {
         match self.entries.get(index) {
-            Some(header_field) => Ok(header_field.clone()),
-            None => Err(Http2Error::IndexationError(format!(
-                "Index {} is out of bounds.",
-                index
-            ))),
-        }
-    }
-
-    /// Update the size of the dynamic table.
-    pub fn update_size(&mut self) {
-        self.size = 0;
-        for entry in &self.entries {
-            self.size += entry.size();
+            Some((_, header_field)) => Ok(header_field.clone()),
+            None => Err(CompressionError::InvalidIndex(index).into()),
         }
     }
 
@@ -179,12 +485,9 @@ impl DynamicTable {
     /// * `Some(index)` - The index of the header field in the dynamic table.
     /// * `None` - The header field is not in the dynamic table.
     pub fn contains(&self, header_field: &HeaderField) -> Option<usize> {
-        for (index, entry) in self.entries.iter().enumerate() {
-            if entry == header_field {
-                return Some(index);
-            }
-        }
-        None
+        self.fields
+            .get(header_field)
+            .map(|sequence| self.index_of(*sequence))
     }
 
     /// Check if the dynamic table contains a header field that has the same name as
@@ -199,12 +502,9 @@ impl DynamicTable {
     /// * `Some(index)` - The index of the header field name in the dynamic table.
     /// * `None` - The header field name is not in the dynamic table.
     pub fn contains_name(&self, header_field: &HeaderField) -> Option<usize> {
-        for (index, entry) in self.entries.iter().enumerate() {
-            if entry.name() == header_field.name() {
-                return Some(index);
-            }
-        }
-        None
+        self.names
+            .get(&header_field.name())
+            .map(|sequence| self.index_of(*sequence))
     }
 
     /// Add a header field to the dynamic table.
@@ -213,17 +513,41 @@ impl DynamicTable {
     ///
     /// * `entry` - The header field to add to the HPACK dynamic table.
     pub fn add_entry(&mut self, entry: HeaderField) {
-        // Add the entry at the beginning of the dynamic table.
-        self.entries.insert(0, entry);
+        // Index the entry with its sequence number, newer entries shadow older ones.
+        let sequence = self.next_sequence;
+        self.next_sequence += 1;
+        self.fields.insert(entry.clone(), sequence);
+        self.names.insert(entry.name(), sequence);
 
-        // Update the size of the dynamic table.
-        self.update_size();
+        // Add the entry at the beginning of the dynamic table.
+        self.size += entry.size();
+        self.entries.push_front((sequence, entry));
 
         // Evict entries if the size of the dynamic table is greater than the maximum size.
-        while self.size > self.max_size {
-            self.entries.pop();
-            self.update_size();
-        }
+        self.evict();
+    }
+
+    /// Get the entries that adding an entry of the given size would evict,
+    /// the oldest first.
+    ///
+    /// # Arguments
+    ///
+    /// * `entry_size` - The size of the entry to add.
+    pub fn evictions(&self, entry_size: usize) -> impl Iterator<Item = &HeaderField> {
+        let max_size = self.max_size;
+        let mut size = self.size + entry_size;
+
+        self.entries
+            .iter()
+            .rev()
+            .map(|(_, entry)| entry)
+            .take_while(move |entry| {
+                if size <= max_size {
+                    return false;
+                }
+                size -= entry.size();
+                true
+            })
     }
 
     /// Set the maximum size of the dynamic table.
@@ -236,11 +560,38 @@ impl DynamicTable {
         self.max_size = max_size;
 
         // Evict entries if the size of the dynamic table is greater than the maximum size.
+        self.evict();
+    }
+
+    /// Evict the oldest entries until the dynamic table fits in its maximum size.
+    fn evict(&mut self) {
         while self.size > self.max_size {
-            self.entries.pop();
-            self.update_size();
+            let (sequence, entry) = match self.entries.pop_back() {
+                Some(evicted) => evicted,
+                None => break,
+            };
+            self.size -= entry.size();
+            self.eviction_count += 1;
+
+            // Only drop the lookups still pointing to the evicted entry.
+            if self.fields.get(&entry) == Some(&sequence) {
+                self.fields.remove(&entry);
+            }
+            let name = entry.name();
+            if self.names.get(&name) == Some(&sequence) {
+                self.names.remove(&name);
+            }
         }
     }
+
+    /// Compute the index of an entry from its sequence number.
+    ///
+    /// # Arguments
+    ///
+    /// * `sequence` - The sequence number of the entry.
+    fn index_of(&self, sequence: u64) -> usize {
+        (self.next_sequence - 1 - sequence) as usize
+    }
 }
 
 /// HPACK static table constants.
@@ -308,7 +659,74 @@ pub const STATIC_HEADER_FIELDS_TABLE_CONSTANTS: [(&str, &str); 61] = [
     ("www-authenticate", ""),
 ];
 
+/// Get the index of the first static table entry with a name.
+///
+/// # Arguments
+///
+/// * `name` - The header field name.
+fn static_name_index(name: &str) -> Option<usize> {
+    match name {
+        ":authority" => Some(0),
+        ":method" => Some(1),
+        ":path" => Some(3),
+        ":scheme" => Some(5),
+        ":status" => Some(7),
+        "accept-charset" => Some(14),
+        "accept-encoding" => Some(15),
+        "accept-language" => Some(16),
+        "accept-ranges" => Some(17),
+        "accept" => Some(18),
+        "access-control-allow-origin" => Some(19),
+        "age" => Some(20),
+        "allow" => Some(21),
+        "authorization" => Some(22),
+        "cache-control" => Some(23),
+        "content-disposition" => Some(24),
+        "content-encoding" => Some(25),
+        "content-language" => Some(26),
+        "content-length" => Some(27),
+        "content-location" => Some(28),
+        "content-range" => Some(29),
+        "content-type" => Some(30),
+        "cookie" => Some(31),
+        "date" => Some(32),
+        "etag" => Some(33),
+        "expect" => Some(34),
+        "expires" => Some(35),
+        "from" => Some(36),
+        "host" => Some(37),
+        "if-match" => Some(38),
+        "if-modified-since" => Some(39),
+        "if-none-match" => Some(40),
+        "if-range" => Some(41),
+        "if-unmodified-since" => Some(42),
+        "last-modified" => Some(43),
+        "link" => Some(44),
+        "location" => Some(45),
+        "max-forwards" => Some(46),
+        "proxy-authenticate" => Some(47),
+        "proxy-authorization" => Some(48),
+        "range" => Some(49),
+        "referer" => Some(50),
+        "refresh" => Some(51),
+        "retry-after" => Some(52),
+        "server" => Some(53),
+        "set-cookie" => Some(54),
+        "strict-transport-security" => Some(55),
+        "transfer-encoding" => Some(56),
+        "user-agent" => Some(57),
+        "vary" => Some(58),
+        "via" => Some(59),
+        "www-authenticate" => Some(60),
+        _ => None,
+    }
+}
+
 /// HTTP/2 HPACK static header fields table.
+///
+/// Header fields are looked up with a compile-time match on their name,
+/// the entries sharing a name being contiguous in the table, instead of
+/// scanning the 61 entries for every encoded header field.
 pub struct StaticTable {
     table: Vec<HeaderField>,
 }
@@ -316,16 +734,16 @@ pub struct StaticTable {
 impl StaticTable {
     /// Get a header field from the static table.
     ///
+    /// Indices start at 0. Indices beyond the static table fail with
+    /// [`CompressionError::InvalidIndex`].
+    ///
     /// # Arguments
     ///
     /// * `index` - The index of the header field to get.
     pub fn get(&self, index: usize) -> Result<HeaderField, Http2Error> {
         match self.table.get(index) {
             Some(header_field) => Ok(header_field.clone()),
-            None => Err(Http2Error::IndexationError(format!(
-                "Index {} is out of bounds.",
-                index
-            ))),
+            None => Err(CompressionError::InvalidIndex(index).into()),
         }
     }
 
@@ -340,12 +758,12 @@ impl StaticTable {
     /// * `Some(index)` - The index of the header field in the static table.
     /// * `None` - The header field is not in the static table.
     pub fn contains(&self, header_field: &HeaderField) -> Option<usize> {
-        for (index, entry) in self.table.iter().enumerate() {
-            if entry == header_field {
-                return Some(index);
-            }
-        }
-        None
+        let name = header_field.name_ref().as_str();
+        let value = header_field.value_ref().as_str();
+
+        (static_name_index(name)?..STATIC_HEADER_FIELDS_TABLE_CONSTANTS.len())
+            .take_while(|index| STATIC_HEADER_FIELDS_TABLE_CONSTANTS[*index].0 == name)
+            .find(|index| STATIC_HEADER_FIELDS_TABLE_CONSTANTS[*index].1 == value)
     }
 
     /// Check if the static table contains a header field name.
@@ -356,21 +774,22 @@ impl StaticTable {
     ///
     /// # Returns
     ///
-    /// * `Some(index)` - The index of the header field name in the static table.
+    /// * `Some(index)` - The index of the first header field with this name
+    ///   in the static table.
     /// * `None` - The header field name is not in the static table.
     pub fn contains_name(&self, header_field: &HeaderField) -> Option<usize> {
-        for (index, entry) in self.table.iter().enumerate() {
-            if entry.name() == header_field.name() {
-                return Some(index);
-            }
-        }
-        None
+        static_name_index(header_field.name_ref().as_str())
     }
 
     /// Get the number of header fields of static table.
     pub fn len(&self) -> usize {
         self.table.len()
     }
+
+    /// Check if the static table has no header fields.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
 }
 
 impl From<[(&str, &str); 61]> for StaticTable {
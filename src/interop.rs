@@ -0,0 +1,221 @@
+use http::header::{HeaderMap, HeaderName, HeaderValue};
+use http::{Method, Request, Response, StatusCode, Uri};
+
+use crate::error::Http2Error;
+use crate::header::field::HeaderField;
+use crate::header::list::HeaderList;
+
+/// Connection-specific header fields, which MUST NOT be sent in HTTP/2.
+const CONNECTION_SPECIFIC_HEADERS: [&str; 5] = [
+    "connection",
+    "keep-alive",
+    "proxy-connection",
+    "transfer-encoding",
+    "upgrade",
+];
+
+impl From<&HeaderMap> for HeaderList {
+    /// Convert the header fields of a `http` header map into a header list.
+    ///
+    /// Connection-specific header fields are dropped, as well as a `te`
+    /// header field with a value other than `trailers`. Values that are
+    /// not valid UTF-8 are converted lossily. The values of a header field
+    /// keep their order, but are grouped since the header map groups them.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_map` - The header map to convert.
+    fn from(header_map: &HeaderMap) -> Self {
+        header_map
+            .iter()
+            .filter(|(name, value)| {
+                !CONNECTION_SPECIFIC_HEADERS.contains(&name.as_str())
+                    && (*name != http::header::TE || value.as_bytes() == b"trailers")
+            })
+            .map(|(name, value)| {
+                HeaderField::new(
+                    name.as_str().into(),
+                    String::from_utf8_lossy(value.as_bytes())
+                        .into_owned()
+                        .into(),
+                )
+            })
+            .collect()
+    }
+}
+
+impl TryFrom<&HeaderList> for HeaderMap {
+    type Error = Http2Error;
+
+    /// Convert the regular header fields of a header list into a `http`
+    /// header map.
+    ///
+    /// Pseudo-header fields are skipped, repeated header fields are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_list` - The header list to convert.
+    fn try_from(header_list: &HeaderList) -> Result<Self, Self::Error> {
+        let mut header_map = HeaderMap::with_capacity(header_list.len());
+
+        for header_field in header_list
+            .iter()
+            .filter(|header_field| !header_field.name_ref().is_pseudo_header())
+        {
+            let name = HeaderName::from_bytes(header_field.name_ref().as_str().as_bytes())
+                .map_err(|error| Http2Error::HeaderError(error.to_string()))?;
+            let value = HeaderValue::from_str(header_field.value_ref().as_str())
+                .map_err(|error| Http2Error::HeaderError(error.to_string()))?;
+            header_map.append(name, value);
+        }
+
+        Ok(header_map)
+    }
+}
+
+impl<B> TryFrom<&Request<B>> for HeaderList {
+    type Error = Http2Error;
+
+    /// Convert the head of a `http` request into a request header section.
+    ///
+    /// The `:method`, `:scheme`, `:authority` and `:path` pseudo-header
+    /// fields are built from the method and URI of the request, followed
+    /// by its header fields. CONNECT requests only carry `:method` and
+    /// `:authority`. A `host` header field disagreeing with the authority
+    /// of the URI is rejected. The body of the request is not converted.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to convert.
+    fn try_from(request: &Request<B>) -> Result<Self, Self::Error> {
+        let uri = request.uri();
+        let mut header_list = HeaderList::default();
+        header_list.append(":method", request.method().as_str());
+
+        if request.method() == Method::CONNECT {
+            match uri.authority() {
+                Some(authority) => header_list.append(":authority", authority.as_str()),
+                None => {
+                    return Err(Http2Error::HeaderError(
+                        "CONNECT requests must have an authority".to_string(),
+                    ))
+                }
+            }
+        } else {
+            match uri.scheme_str() {
+                Some(scheme) => header_list.append(":scheme", scheme),
+                None => {
+                    return Err(Http2Error::HeaderError(format!(
+                        "Request URI {} has no scheme",
+                        uri
+                    )))
+                }
+            }
+            if let Some(authority) = uri.authority() {
+                header_list.append(":authority", authority.as_str());
+            }
+            header_list.append(
+                ":path",
+                uri.path_and_query().map_or("/", |path| path.as_str()),
+            );
+        }
+
+        let header_list: HeaderList = header_list
+            .into_iter()
+            .chain(HeaderList::from(request.headers()))
+            .collect();
+        if !header_list.host_matches_authority() {
+            return Err(Http2Error::HeaderError(format!(
+                "Host header field differs from the authority of {}",
+                uri
+            )));
+        }
+
+        Ok(header_list)
+    }
+}
+
+impl TryFrom<&HeaderList> for Request<()> {
+    type Error = Http2Error;
+
+    /// Convert a request header section into the head of a `http` request.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_list` - The header section of the request.
+    fn try_from(header_list: &HeaderList) -> Result<Self, Self::Error> {
+        let method = header_list
+            .method()
+            .ok_or_else(|| Http2Error::HeaderError("Request has no :method".to_string()))?;
+        let method = Method::from_bytes(method.as_bytes())
+            .map_err(|error| Http2Error::HeaderError(error.to_string()))?;
+
+        let mut uri = Uri::builder();
+        if let Some(scheme) = header_list.scheme() {
+            uri = uri.scheme(scheme);
+        }
+        if let Some(authority) = header_list.authority() {
+            uri = uri.authority(authority);
+        }
+        if let Some(path) = header_list.path() {
+            uri = uri.path_and_query(path);
+        }
+        let uri = uri
+            .build()
+            .map_err(|error| Http2Error::HeaderError(error.to_string()))?;
+
+        let mut request = Request::new(());
+        *request.method_mut() = method;
+        *request.uri_mut() = uri;
+        *request.version_mut() = http::Version::HTTP_2;
+        *request.headers_mut() = HeaderMap::try_from(header_list)?;
+
+        Ok(request)
+    }
+}
+
+impl<B> From<&Response<B>> for HeaderList {
+    /// Convert the head of a `http` response into a response header
+    /// section.
+    ///
+    /// The `:status` pseudo-header field is followed by the header fields
+    /// of the response. The body of the response is not converted.
+    ///
+    /// # Arguments
+    ///
+    /// * `response` - The response to convert.
+    fn from(response: &Response<B>) -> Self {
+        let mut header_list = HeaderList::default();
+        header_list.append(":status", response.status().as_str());
+
+        header_list
+            .into_iter()
+            .chain(HeaderList::from(response.headers()))
+            .collect()
+    }
+}
+
+impl TryFrom<&HeaderList> for Response<()> {
+    type Error = Http2Error;
+
+    /// Convert a response header section into the head of a `http`
+    /// response.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_list` - The header section of the response.
+    fn try_from(header_list: &HeaderList) -> Result<Self, Self::Error> {
+        let status = header_list
+            .status()
+            .ok_or_else(|| Http2Error::HeaderError("Response has no valid :status".to_string()))?;
+        let status = StatusCode::from_u16(status)
+            .map_err(|error| Http2Error::HeaderError(error.to_string()))?;
+
+        let mut response = Response::new(());
+        *response.status_mut() = status;
+        *response.version_mut() = http::Version::HTTP_2;
+        *response.headers_mut() = HeaderMap::try_from(header_list)?;
+
+        Ok(response)
+    }
+}
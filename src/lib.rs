@@ -1,4 +1,50 @@
+//! HTTP/2 (RFC 9113) and HPACK (RFC 7541) implementation.
+//!
+//! The crate is split into layers, each gated behind a cargo feature so
+//! that users only needing to parse frames or header blocks do not compile
+//! the connection runtime:
+//!
+//! * the HPACK layer, `header` and `error`, is always available,
+//! * `codec` adds the `frame` codec, the connection preface (`start`) and
+//!   the frame dumps of `debug`,
+//! * `sync`, enabled by default, adds the sans-I/O `connection` core, its
+//!   `stream` and `flow_control` state, the blocking `sync` driver,
+//!   its `transport`s and the HTTP/1.1 `upgrade`,
+//! * `async-tokio` adds a `tokio-util` frame codec,
+//! * `tls-rustls` adds TLS transports based on `rustls`.
+//!
+//! The `grpc`, `http`, `test-support`, `tracing` and `cli` features add
+//! optional integrations on top of these layers.
+
+#[cfg(feature = "async-tokio")]
+pub mod codec;
+#[cfg(feature = "sync")]
+pub mod connection;
+#[cfg(feature = "codec")]
+pub mod debug;
 pub mod error;
+#[cfg(feature = "sync")]
+pub mod flow_control;
+#[cfg(feature = "codec")]
 pub mod frame;
+#[cfg(feature = "grpc")]
+pub mod grpc;
 pub mod header;
+#[cfg(feature = "http")]
+pub mod interop;
+#[cfg(feature = "codec")]
 pub mod start;
+#[cfg(feature = "sync")]
+pub mod stream;
+#[cfg(feature = "sync")]
+pub mod sync;
+#[cfg(feature = "test-support")]
+pub mod test_support;
+#[cfg(feature = "tls-rustls")]
+pub mod tls;
+mod trace;
+#[cfg(feature = "sync")]
+pub mod transport;
+#[cfg(feature = "sync")]
+pub mod upgrade;
+pub mod websocket;
@@ -1,4 +0,0 @@
-pub struct Stream {
-    stream_id: u32,
-    state: StreamState,
-}
\ No newline at end of file
@@ -0,0 +1,1018 @@
+use std::collections::{HashMap, VecDeque};
+use std::time::Instant;
+
+use crate::error::{ErrorCode, Http2Error};
+use crate::frame::continuation::ContinuationFrame;
+use crate::frame::data::DataFrame;
+use crate::frame::headers::HeadersFrame;
+use crate::frame::priority::PriorityFrame;
+use crate::frame::push_promise::PushPromiseFrame;
+use crate::frame::FramePriority;
+use crate::header::list::HeaderList;
+use crate::stream::{reset::ResetGuard, Role, Stream, StreamState, DEFAULT_RECV_BUFFER_LIMIT};
+use crate::trace;
+
+/// Default number of closed streams remembered by a stream manager.
+pub const DEFAULT_MAX_CLOSED_STREAMS: usize = 100;
+
+/// HTTP/2 stream manager.
+///
+/// Keeps track of the streams of a connection and enforces the
+/// SETTINGS_MAX_CONCURRENT_STREAMS limits advertised by both endpoints:
+///
+/// * the limit advertised by the local endpoint bounds the number of
+///   active streams the peer is permitted to initiate,
+/// * the limit advertised by the peer bounds the number of active streams
+///   the local endpoint is permitted to initiate.
+///
+/// Only streams in the "open" or "half-closed" states are counted.
+///
+/// Opening a stream implicitly closes the idle streams with a lower
+/// identifier initiated by the same endpoint. Closed streams are kept for
+/// a while, so that the WINDOW_UPDATE and RST_STREAM frames the peer sent
+/// before it learnt that a stream was closed are tolerated: only the most
+/// recently closed streams are remembered, older ones are forgotten.
+///
+/// Streams reset by the peer before the local endpoint finished them are
+/// counted by a [`ResetGuard`] to mitigate Rapid Reset attacks.
+///
+/// The length of received messages is checked against their
+/// `content-length` header field, unless content length enforcement is
+/// disabled.
+#[derive(Debug)]
+pub struct StreamManager {
+    role: Role,
+    streams: HashMap<u32, Stream>,
+    local_max_concurrent_streams: Option<u32>,
+    remote_max_concurrent_streams: Option<u32>,
+    local_active_streams: u32,
+    remote_active_streams: u32,
+    last_local_stream_id: u32,
+    last_remote_stream_id: u32,
+    closed_streams: VecDeque<u32>,
+    max_closed_streams: usize,
+    reset_guard: Option<ResetGuard>,
+    enforce_content_length: bool,
+    recv_buffer_limit: usize,
+}
+
+impl StreamManager {
+    /// Create a new stream manager.
+    ///
+    /// Both limits are initially unbounded, peer resets are limited by
+    /// the default [`ResetGuard`] and the last
+    /// [`DEFAULT_MAX_CLOSED_STREAMS`] closed streams are remembered.
+    ///
+    /// # Arguments
+    ///
+    /// * `role` - The role of the local endpoint.
+    pub fn new(role: Role) -> Self {
+        StreamManager {
+            role,
+            streams: HashMap::new(),
+            local_max_concurrent_streams: None,
+            remote_max_concurrent_streams: None,
+            local_active_streams: 0,
+            remote_active_streams: 0,
+            last_local_stream_id: 0,
+            last_remote_stream_id: 0,
+            closed_streams: VecDeque::new(),
+            max_closed_streams: DEFAULT_MAX_CLOSED_STREAMS,
+            reset_guard: Some(ResetGuard::default()),
+            enforce_content_length: true,
+            recv_buffer_limit: DEFAULT_RECV_BUFFER_LIMIT,
+        }
+    }
+
+    /// Open a stream initiated by the local endpoint.
+    ///
+    /// Fails with REFUSED_STREAM if opening the stream would exceed the
+    /// SETTINGS_MAX_CONCURRENT_STREAMS advertised by the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The identifier of the stream to open.
+    pub fn open_local(&mut self, stream_id: u32) -> Result<&Stream, Http2Error> {
+        if !self.role.initiates(stream_id) {
+            return Err(Http2Error::UsageError(format!(
+                "Stream {} cannot be initiated locally",
+                stream_id
+            )));
+        }
+
+        if !self.can_open_local() {
+            return Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::RefusedStream,
+                format!(
+                    "Peer SETTINGS_MAX_CONCURRENT_STREAMS reached ({} active streams)",
+                    self.local_active_streams
+                ),
+            ));
+        }
+
+        self.open(stream_id)
+    }
+
+    /// Open a stream initiated by the peer.
+    ///
+    /// Fails with REFUSED_STREAM if opening the stream would exceed the
+    /// SETTINGS_MAX_CONCURRENT_STREAMS advertised by the local endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The identifier of the stream to open.
+    pub fn open_remote(&mut self, stream_id: u32) -> Result<&Stream, Http2Error> {
+        if stream_id == 0 || self.role.initiates(stream_id) {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!("Stream {} cannot be initiated by the peer", stream_id),
+            ));
+        }
+
+        if let Some(max) = self.local_max_concurrent_streams {
+            if self.remote_active_streams >= max {
+                return Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::RefusedStream,
+                    format!(
+                        "SETTINGS_MAX_CONCURRENT_STREAMS reached ({} active streams)",
+                        self.remote_active_streams
+                    ),
+                ));
+            }
+        }
+
+        self.open(stream_id)
+    }
+
+    /// Move an idle or reserved stream to its active state.
+    fn open(&mut self, stream_id: u32) -> Result<&Stream, Http2Error> {
+        let state = self.state(stream_id);
+
+        let next_state = match state {
+            StreamState::Idle => StreamState::Open,
+            StreamState::ReservedLocal => StreamState::HalfClosedRemote,
+            StreamState::ReservedRemote => StreamState::HalfClosedLocal,
+            _ => {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::ProtocolError,
+                    format!("Stream {} is already open or closed", stream_id),
+                ))
+            }
+        };
+
+        self.set_state(stream_id, next_state);
+        Ok(&self.streams[&stream_id])
+    }
+
+    /// Change the state of a stream, updating the active stream counts.
+    ///
+    /// Streams leaving the idle state implicitly close the idle streams
+    /// with a lower identifier, closed streams are remembered until they
+    /// are among the oldest beyond the maximum number of closed streams.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `state` - The new state of the stream.
+    pub fn set_state(&mut self, stream_id: u32, state: StreamState) {
+        let stream = self.stream_mut(stream_id);
+        let previous_state = stream.state;
+        trace::stream_state(stream_id, previous_state, state);
+        stream.state = state;
+
+        let (count, last_stream_id) = if self.role.initiates(stream_id) {
+            (
+                &mut self.local_active_streams,
+                &mut self.last_local_stream_id,
+            )
+        } else {
+            (
+                &mut self.remote_active_streams,
+                &mut self.last_remote_stream_id,
+            )
+        };
+
+        match (previous_state.is_active(), state.is_active()) {
+            (false, true) => *count += 1,
+            (true, false) => *count -= 1,
+            _ => (),
+        }
+
+        if state != StreamState::Idle {
+            *last_stream_id = stream_id.max(*last_stream_id);
+        }
+
+        if state == StreamState::Closed && previous_state != StreamState::Closed {
+            self.closed_streams.push_back(stream_id);
+            self.forget_closed_streams();
+        }
+    }
+
+    /// Forget the oldest closed streams beyond the maximum number of closed
+    /// streams.
+    fn forget_closed_streams(&mut self) {
+        while self.closed_streams.len() > self.max_closed_streams {
+            if let Some(stream_id) = self.closed_streams.pop_front() {
+                self.streams.remove(&stream_id);
+            }
+        }
+    }
+
+    /// Close a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn close(&mut self, stream_id: u32) {
+        self.set_state(stream_id, StreamState::Closed);
+    }
+
+    /// Handle a HEADERS frame received from the peer.
+    ///
+    /// Opens the stream if it was idle or reserved. HEADERS frames on a
+    /// stream that is not remembered with an identifier lower than a stream
+    /// previously opened by the peer are a connection error of type
+    /// PROTOCOL_ERROR, on a remembered closed stream a stream error of type
+    /// STREAM_CLOSED. The first header block
+    /// that is not an informational (1xx) response is the header section
+    /// of the message, any later header block is its trailer section: it
+    /// MUST end the stream and MUST NOT contain pseudo-header fields.
+    /// Informational responses MUST NOT end the stream either, and requests
+    /// carrying both a `host` header field and an `:authority`
+    /// pseudo-header field that disagree are malformed.
+    ///
+    /// The `content-length` of the header section is recorded to check
+    /// the length of the DATA frames of the message, and its priority
+    /// signals, the priority block of the frame and the `priority` header
+    /// field, are applied to the stream. A stream depending on itself is a
+    /// stream error of type PROTOCOL_ERROR.
+    ///
+    /// # Arguments
+    ///
+    /// * `headers_frame` - The received HEADERS frame.
+    pub fn recv_headers(&mut self, headers_frame: &HeadersFrame) -> Result<(), Http2Error> {
+        let stream_id = headers_frame.stream_id();
+        let header_list = headers_frame.header_list();
+
+        match self.state(stream_id) {
+            StreamState::Idle | StreamState::ReservedRemote => {
+                self.open_remote(stream_id)?;
+            }
+            StreamState::Open | StreamState::HalfClosedLocal => (),
+            StreamState::Closed if !self.streams.contains_key(&stream_id) => {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::ProtocolError,
+                    format!(
+                        "HEADERS received on stream {}, lower than stream {}",
+                        stream_id, self.last_remote_stream_id
+                    ),
+                ))
+            }
+            state => {
+                return Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::StreamClosed,
+                    format!(
+                        "HEADERS received on stream {} in state {:?}",
+                        stream_id, state
+                    ),
+                ))
+            }
+        }
+
+        let content_length = if self.enforce_content_length {
+            content_length(stream_id, header_list, self.role)?
+        } else {
+            None
+        };
+        let enforce_content_length = self.enforce_content_length;
+        let role = self.role;
+        if let Some(frame_priority) = headers_frame.frame_priority() {
+            check_stream_dependency(stream_id, frame_priority)?;
+        }
+
+        let stream = self.stream_mut(stream_id);
+        if let Some(frame_priority) = headers_frame.frame_priority() {
+            stream.priority.apply_frame_priority(frame_priority);
+        }
+        if stream.headers_received {
+            if !headers_frame.end_stream() {
+                return Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::ProtocolError,
+                    "Trailers must end the stream".to_string(),
+                ));
+            }
+            check_trailers(stream_id, header_list)?;
+            stream.trailers = Some(header_list.clone());
+        } else if is_informational(header_list) {
+            if headers_frame.end_stream() {
+                return Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::ProtocolError,
+                    "Informational responses must not end the stream".to_string(),
+                ));
+            }
+        } else {
+            if role == Role::Server && !header_list.host_matches_authority() {
+                return Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::ProtocolError,
+                    "Request host header field differs from :authority".to_string(),
+                ));
+            }
+            stream.headers_received = true;
+            stream.content_length = content_length;
+            if let Some(priority) = header_list.get("priority") {
+                stream.priority.apply_priority_field(priority);
+            }
+        }
+
+        if enforce_content_length {
+            check_content_length(stream, headers_frame.end_stream())?;
+        }
+
+        if headers_frame.end_stream() {
+            self.recv_end_stream(stream_id);
+        }
+
+        Ok(())
+    }
+
+    /// Handle a DATA frame received from the peer.
+    ///
+    /// DATA frames are only accepted on streams the peer has not ended.
+    /// The total length of the DATA frames of a message must match its
+    /// `content-length`, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `data_frame` - The received DATA frame.
+    pub fn recv_data(&mut self, data_frame: &DataFrame) -> Result<(), Http2Error> {
+        let stream_id = data_frame.stream_id;
+
+        match self.state(stream_id) {
+            StreamState::Open | StreamState::HalfClosedLocal => (),
+            StreamState::Idle => {
+                return Err(Http2Error::ConnectionError(
+                    ErrorCode::ProtocolError,
+                    format!("DATA received on idle stream {}", stream_id),
+                ))
+            }
+            state => {
+                return Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::StreamClosed,
+                    format!("DATA received on stream {} in state {:?}", stream_id, state),
+                ))
+            }
+        }
+
+        let enforce_content_length = self.enforce_content_length;
+        let stream = self.stream_mut(stream_id);
+        stream.data_received += data_frame.data.len() as u64;
+
+        if enforce_content_length {
+            check_content_length(stream, data_frame.end_stream)?;
+        }
+
+        if data_frame.end_stream {
+            self.recv_end_stream(stream_id);
+        }
+
+        Ok(())
+    }
+
+    /// Handle a PUSH_PROMISE frame received from the peer.
+    ///
+    /// The promised stream is reserved by the peer until it sends the
+    /// pushed response, which opens it half-closed for the local endpoint.
+    /// Only servers push, on a stream they may still send on, and promise
+    /// idle streams they initiate: any other PUSH_PROMISE frame is a
+    /// connection error of type PROTOCOL_ERROR.
+    ///
+    /// # Arguments
+    ///
+    /// * `push_promise_frame` - The received PUSH_PROMISE frame.
+    pub fn recv_push_promise(
+        &mut self,
+        push_promise_frame: &PushPromiseFrame,
+    ) -> Result<(), Http2Error> {
+        let stream_id = push_promise_frame.stream_id();
+        let promised_stream_id = push_promise_frame.promised_stream_id();
+
+        if self.role != Role::Client {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                "PUSH_PROMISE received from a client".to_string(),
+            ));
+        }
+
+        let state = self.state(stream_id);
+        if !matches!(state, StreamState::Open | StreamState::HalfClosedLocal) {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!(
+                    "PUSH_PROMISE received on stream {} in state {:?}",
+                    stream_id, state
+                ),
+            ));
+        }
+
+        if promised_stream_id == 0
+            || self.role.initiates(promised_stream_id)
+            || self.state(promised_stream_id) != StreamState::Idle
+        {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!("Stream {} cannot be promised", promised_stream_id),
+            ));
+        }
+
+        self.set_state(promised_stream_id, StreamState::ReservedRemote);
+
+        Ok(())
+    }
+
+    /// Reserve a stream promised by the local endpoint in a PUSH_PROMISE
+    /// frame.
+    ///
+    /// The promised stream is reserved until the pushed response is sent,
+    /// which opens it half-closed for the peer. Only servers push, on a
+    /// stream they may still send on, and promise idle streams they
+    /// initiate.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream the promise is associated with.
+    /// * `promised_stream_id` - The stream to reserve.
+    pub fn send_push_promise(
+        &mut self,
+        stream_id: u32,
+        promised_stream_id: u32,
+    ) -> Result<(), Http2Error> {
+        if self.role != Role::Server {
+            return Err(Http2Error::UsageError("Only servers can push".to_string()));
+        }
+
+        let state = self.state(stream_id);
+        if !matches!(state, StreamState::Open | StreamState::HalfClosedRemote) {
+            return Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::StreamClosed,
+                format!("Cannot push on stream {} in state {:?}", stream_id, state),
+            ));
+        }
+
+        if !self.role.initiates(promised_stream_id)
+            || self.state(promised_stream_id) != StreamState::Idle
+        {
+            return Err(Http2Error::UsageError(format!(
+                "Stream {} cannot be promised",
+                promised_stream_id
+            )));
+        }
+
+        self.set_state(promised_stream_id, StreamState::ReservedLocal);
+
+        Ok(())
+    }
+
+    /// Handle a CONTINUATION frame received from the peer.
+    ///
+    /// Header fields of a trailer section split over CONTINUATION frames
+    /// are appended to the trailers of the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `continuation_frame` - The received CONTINUATION frame.
+    pub fn recv_continuation(
+        &mut self,
+        continuation_frame: &ContinuationFrame,
+    ) -> Result<(), Http2Error> {
+        let stream_id = continuation_frame.stream_id();
+        let header_list = continuation_frame.header_list();
+
+        if let Some(trailers) = self
+            .streams
+            .get_mut(&stream_id)
+            .and_then(|stream| stream.trailers.as_mut())
+        {
+            check_trailers(stream_id, header_list)?;
+            *trailers = trailers.iter().chain(header_list.iter()).cloned().collect();
+        }
+
+        Ok(())
+    }
+
+    /// Check and account for trailers sent by the local endpoint.
+    ///
+    /// Trailers end the stream, so they may only be sent on a stream the
+    /// local endpoint has not closed yet.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `trailers` - The trailers to send.
+    pub fn send_trailers(
+        &mut self,
+        stream_id: u32,
+        trailers: &HeaderList,
+    ) -> Result<(), Http2Error> {
+        let next_state = match self.state(stream_id) {
+            StreamState::Open => StreamState::HalfClosedLocal,
+            StreamState::HalfClosedRemote => StreamState::Closed,
+            state => {
+                return Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::StreamClosed,
+                    format!(
+                        "Cannot send trailers on stream {} in state {:?}",
+                        stream_id, state
+                    ),
+                ))
+            }
+        };
+        check_trailers(stream_id, trailers)?;
+
+        self.set_state(stream_id, next_state);
+        Ok(())
+    }
+
+    /// Account for the end of a stream sent by the local endpoint, with the
+    /// END_STREAM flag of a HEADERS or DATA frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn send_end_stream(&mut self, stream_id: u32) -> Result<(), Http2Error> {
+        let next_state = match self.state(stream_id) {
+            StreamState::Open => StreamState::HalfClosedLocal,
+            StreamState::HalfClosedRemote => StreamState::Closed,
+            state => {
+                return Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::StreamClosed,
+                    format!("Cannot end stream {} in state {:?}", stream_id, state),
+                ))
+            }
+        };
+
+        self.set_state(stream_id, next_state);
+        Ok(())
+    }
+
+    /// Take the trailers received on a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn take_trailers(&mut self, stream_id: u32) -> Option<HeaderList> {
+        self.streams
+            .get_mut(&stream_id)
+            .and_then(|stream| stream.trailers.take())
+    }
+
+    /// Move a stream to its state after the peer ended it.
+    fn recv_end_stream(&mut self, stream_id: u32) {
+        match self.state(stream_id) {
+            StreamState::Open => self.set_state(stream_id, StreamState::HalfClosedRemote),
+            StreamState::HalfClosedLocal => self.close(stream_id),
+            _ => (),
+        }
+    }
+
+    /// Get the state of a stream.
+    ///
+    /// Streams that are not remembered are closed if their identifier is
+    /// lower than a stream opened by the same endpoint, idle otherwise.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn state(&self, stream_id: u32) -> StreamState {
+        let last_stream_id = if self.role.initiates(stream_id) {
+            self.last_local_stream_id
+        } else {
+            self.last_remote_stream_id
+        };
+
+        match self.streams.get(&stream_id) {
+            Some(stream) => stream.state,
+            None if stream_id <= last_stream_id => StreamState::Closed,
+            None => StreamState::Idle,
+        }
+    }
+
+    /// Check a WINDOW_UPDATE or RST_STREAM frame received on a stream.
+    ///
+    /// These frames are a connection error of type PROTOCOL_ERROR on idle
+    /// streams. They are tolerated on recently closed streams, which the
+    /// peer may not know are closed yet, but not on forgotten ones.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `frame_type` - The name of the received frame type.
+    fn check_recv_frame(&self, stream_id: u32, frame_type: &str) -> Result<(), Http2Error> {
+        match self.state(stream_id) {
+            StreamState::Idle => Err(Http2Error::ConnectionError(
+                ErrorCode::ProtocolError,
+                format!("{} received on idle stream {}", frame_type, stream_id),
+            )),
+            StreamState::Closed if !self.streams.contains_key(&stream_id) => {
+                Err(Http2Error::ConnectionError(
+                    ErrorCode::ProtocolError,
+                    format!(
+                        "{} received on stream {} closed long ago",
+                        frame_type, stream_id
+                    ),
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Get a stream, creating it in the idle state if needed.
+    fn stream_mut(&mut self, stream_id: u32) -> &mut Stream {
+        let recv_buffer_limit = self.recv_buffer_limit;
+        self.streams.entry(stream_id).or_insert_with(|| Stream {
+            recv_buffer_limit,
+            ..Stream::new(stream_id)
+        })
+    }
+
+    /// Handle a PRIORITY frame received from the peer.
+    ///
+    /// The priority of a known stream is updated. PRIORITY frames can be
+    /// sent in any stream state, those on idle or forgotten streams are
+    /// ignored. A stream depending on itself is a stream error of type
+    /// PROTOCOL_ERROR.
+    ///
+    /// # Arguments
+    ///
+    /// * `priority_frame` - The received PRIORITY frame.
+    pub fn recv_priority(&mut self, priority_frame: &PriorityFrame) -> Result<(), Http2Error> {
+        let stream_id = priority_frame.stream_id();
+        check_stream_dependency(stream_id, priority_frame.frame_priority())?;
+
+        if let Some(stream) = self.streams.get_mut(&stream_id) {
+            stream
+                .priority
+                .apply_frame_priority(priority_frame.frame_priority());
+        }
+
+        Ok(())
+    }
+
+    /// Handle a RST_STREAM frame received from the peer.
+    ///
+    /// Closes the stream, RST_STREAM frames on idle or forgotten streams are
+    /// a connection error of type PROTOCOL_ERROR. If the stream was
+    /// initiated by the peer and the
+    /// local endpoint had not finished sending on it, the reset is
+    /// recorded by the reset guard, which fails with ENHANCE_YOUR_CALM
+    /// when the peer resets streams too quickly.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The identifier of the reset stream.
+    pub fn recv_reset(&mut self, stream_id: u32) -> Result<(), Http2Error> {
+        self.recv_reset_at(stream_id, Instant::now())
+    }
+
+    /// Handle a RST_STREAM frame received from the peer at a given time.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The identifier of the reset stream.
+    /// * `now` - The time at which the frame was received.
+    pub fn recv_reset_at(&mut self, stream_id: u32, now: Instant) -> Result<(), Http2Error> {
+        self.check_recv_frame(stream_id, "RST_STREAM")?;
+
+        let state = self.state(stream_id);
+        let unfinished = matches!(state, StreamState::Open | StreamState::HalfClosedRemote);
+        self.close(stream_id);
+
+        match &mut self.reset_guard {
+            Some(reset_guard) if unfinished && !self.role.initiates(stream_id) => {
+                reset_guard.record(now)
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Account for a RST_STREAM frame sent by the local endpoint.
+    ///
+    /// Closes the stream and remembers that it was reset, so that the
+    /// frames the peer sent before receiving the RST_STREAM frame can be
+    /// ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The identifier of the reset stream.
+    pub fn send_reset(&mut self, stream_id: u32) {
+        self.stream_mut(stream_id).reset_sent = true;
+        self.close(stream_id);
+    }
+
+    /// Handle a GOAWAY frame received from the peer.
+    ///
+    /// The streams initiated by the local endpoint with an identifier
+    /// greater than the last stream identifier of the frame were not
+    /// processed by the peer: they are closed and marked as such, their
+    /// requests being safe to retry on a new connection. Returns their
+    /// identifiers, in ascending order.
+    ///
+    /// # Arguments
+    ///
+    /// * `last_stream_id` - The last stream identifier of the GOAWAY frame.
+    pub fn recv_go_away(&mut self, last_stream_id: u32) -> Vec<u32> {
+        let mut stream_ids: Vec<u32> = self
+            .streams
+            .values()
+            .filter(|stream| {
+                stream.stream_id > last_stream_id
+                    && self.role.initiates(stream.stream_id)
+                    && !matches!(stream.state, StreamState::Idle | StreamState::Closed)
+            })
+            .map(|stream| stream.stream_id)
+            .collect();
+        stream_ids.sort_unstable();
+
+        for &stream_id in &stream_ids {
+            self.stream_mut(stream_id).not_processed = true;
+            self.close(stream_id);
+        }
+
+        stream_ids
+    }
+
+    /// Handle a WINDOW_UPDATE frame received from the peer on a stream.
+    ///
+    /// WINDOW_UPDATE frames on idle or forgotten streams are a connection
+    /// error of type PROTOCOL_ERROR.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn recv_window_update(&mut self, stream_id: u32) -> Result<(), Http2Error> {
+        self.check_recv_frame(stream_id, "WINDOW_UPDATE")
+    }
+
+    /// Check if the local endpoint may open a new stream.
+    pub fn can_open_local(&self) -> bool {
+        match self.remote_max_concurrent_streams {
+            Some(max) => self.local_active_streams < max,
+            None => true,
+        }
+    }
+
+    /// Get a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn get(&self, stream_id: u32) -> Option<&Stream> {
+        self.streams.get(&stream_id)
+    }
+
+    /// Get a stream, e.g. to change the limit of its receive buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn get_mut(&mut self, stream_id: u32) -> Option<&mut Stream> {
+        self.streams.get_mut(&stream_id)
+    }
+
+    pub fn role(&self) -> Role {
+        self.role
+    }
+
+    pub fn local_max_concurrent_streams(&self) -> Option<u32> {
+        self.local_max_concurrent_streams
+    }
+
+    /// Set the SETTINGS_MAX_CONCURRENT_STREAMS advertised by the local endpoint.
+    ///
+    /// Reducing the limit below the current number of active streams does
+    /// not close any stream, new streams are refused until enough streams
+    /// have closed.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum number of streams the peer may initiate.
+    pub fn set_local_max_concurrent_streams(&mut self, max: Option<u32>) {
+        self.local_max_concurrent_streams = max;
+    }
+
+    pub fn remote_max_concurrent_streams(&self) -> Option<u32> {
+        self.remote_max_concurrent_streams
+    }
+
+    /// Set the SETTINGS_MAX_CONCURRENT_STREAMS advertised by the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `max` - The maximum number of streams the local endpoint may initiate.
+    pub fn set_remote_max_concurrent_streams(&mut self, max: Option<u32>) {
+        self.remote_max_concurrent_streams = max;
+    }
+
+    pub fn reset_guard(&self) -> Option<&ResetGuard> {
+        self.reset_guard.as_ref()
+    }
+
+    /// Set the guard limiting the streams reset by the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `reset_guard` - The reset guard, `None` disables the protection.
+    pub fn set_reset_guard(&mut self, reset_guard: Option<ResetGuard>) {
+        self.reset_guard = reset_guard;
+    }
+
+    pub fn max_closed_streams(&self) -> usize {
+        self.max_closed_streams
+    }
+
+    /// Set the number of closed streams remembered.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_closed_streams` - The number of most recently closed streams
+    ///   on which WINDOW_UPDATE and RST_STREAM frames are tolerated.
+    pub fn set_max_closed_streams(&mut self, max_closed_streams: usize) {
+        self.max_closed_streams = max_closed_streams;
+        self.forget_closed_streams();
+    }
+
+    pub fn enforce_content_length(&self) -> bool {
+        self.enforce_content_length
+    }
+
+    /// Enable or disable the `content-length` checks of received messages.
+    ///
+    /// Lenient mode is needed by clients sending HEAD requests, whose
+    /// responses carry the `content-length` of a body that is not sent.
+    ///
+    /// # Arguments
+    ///
+    /// * `enforce_content_length` - Whether mismatches are stream errors.
+    pub fn set_enforce_content_length(&mut self, enforce_content_length: bool) {
+        self.enforce_content_length = enforce_content_length;
+    }
+
+    pub fn recv_buffer_limit(&self) -> usize {
+        self.recv_buffer_limit
+    }
+
+    /// Set the high-watermark of the receive buffer of new streams,
+    /// [`DEFAULT_RECV_BUFFER_LIMIT`] by default.
+    ///
+    /// # Arguments
+    ///
+    /// * `recv_buffer_limit` - The number of buffered octets beyond which
+    ///   the stream window is no longer replenished.
+    pub fn set_recv_buffer_limit(&mut self, recv_buffer_limit: usize) {
+        self.recv_buffer_limit = recv_buffer_limit;
+    }
+
+    /// Number of received octets not read by the application yet, on all
+    /// the streams.
+    pub fn recv_buffered(&self) -> usize {
+        self.streams.values().map(Stream::recv_buffered).sum()
+    }
+
+    /// Number of active streams initiated by the local endpoint.
+    pub fn local_active_streams(&self) -> u32 {
+        self.local_active_streams
+    }
+
+    /// Number of active streams initiated by the peer.
+    pub fn remote_active_streams(&self) -> u32 {
+        self.remote_active_streams
+    }
+}
+
+/// Check that a trailer section does not contain pseudo-header fields.
+///
+/// # Arguments
+///
+/// * `stream_id` - The stream carrying the trailers.
+/// * `trailers` - The trailer section.
+fn check_trailers(stream_id: u32, trailers: &HeaderList) -> Result<(), Http2Error> {
+    match trailers
+        .iter()
+        .find(|header_field| header_field.name_ref().is_pseudo_header())
+    {
+        Some(header_field) => Err(Http2Error::StreamError(
+            stream_id,
+            ErrorCode::ProtocolError,
+            format!("Pseudo-header field {} in trailers", header_field.name()),
+        )),
+        None => Ok(()),
+    }
+}
+
+/// Check if a header section is an informational (1xx) response.
+fn is_informational(header_list: &HeaderList) -> bool {
+    header_list
+        .status()
+        .is_some_and(|status| (100..200).contains(&status))
+}
+
+/// Parse the `content-length` of a header section.
+///
+/// Several `content-length` header fields are accepted as long as they
+/// have the same value. A `304 Not Modified` response carries the length
+/// of a body that is not sent, so its `content-length` is ignored.
+///
+/// # Arguments
+///
+/// * `stream_id` - The stream carrying the header section.
+/// * `header_list` - The header section.
+/// * `role` - The role of the local endpoint.
+fn content_length(
+    stream_id: u32,
+    header_list: &HeaderList,
+    role: Role,
+) -> Result<Option<u64>, Http2Error> {
+    if role == Role::Client && header_list.status() == Some(304) {
+        return Ok(None);
+    }
+
+    let mut content_length: Option<u64> = None;
+    for value in header_list.get_all("content-length") {
+        let length = match value.parse::<u64>() {
+            Ok(length) if value.bytes().all(|byte| byte.is_ascii_digit()) => length,
+            _ => {
+                return Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::ProtocolError,
+                    format!("Invalid content-length {:?}", value),
+                ))
+            }
+        };
+
+        if content_length.is_some_and(|content_length| content_length != length) {
+            return Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::ProtocolError,
+                "Conflicting content-length header fields".to_string(),
+            ));
+        }
+        content_length = Some(length);
+    }
+
+    Ok(content_length)
+}
+
+/// Check that a stream does not depend on itself.
+///
+/// # Arguments
+///
+/// * `stream_id` - The stream identifier.
+/// * `frame_priority` - The priority block received for the stream.
+fn check_stream_dependency(
+    stream_id: u32,
+    frame_priority: &FramePriority,
+) -> Result<(), Http2Error> {
+    if frame_priority.stream_dependency() == stream_id {
+        return Err(Http2Error::StreamError(
+            stream_id,
+            ErrorCode::ProtocolError,
+            format!("Stream {} cannot depend on itself", stream_id),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Check the DATA received on a stream against its `content-length`.
+///
+/// # Arguments
+///
+/// * `stream` - The stream receiving the message.
+/// * `end_stream` - Whether the message is complete.
+fn check_content_length(stream: &Stream, end_stream: bool) -> Result<(), Http2Error> {
+    match stream.content_length {
+        Some(content_length)
+            if stream.data_received > content_length
+                || (end_stream && stream.data_received != content_length) =>
+        {
+            Err(Http2Error::StreamError(
+                stream.stream_id,
+                ErrorCode::ProtocolError,
+                format!(
+                    "Received {} bytes of DATA, content-length is {}",
+                    stream.data_received, content_length
+                ),
+            ))
+        }
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,269 @@
+use crate::error::{ErrorCode, Http2Error};
+use crate::frame::Frame;
+use crate::header::list::HeaderList;
+
+/// Default maximum size of the body of an assembled message (16 MiB).
+pub const DEFAULT_MAX_BODY_SIZE: usize = 16_777_216;
+
+/// Complete HTTP message received on a stream, a request or a response.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Message {
+    stream_id: u32,
+    header_list: HeaderList,
+    body: Vec<u8>,
+    trailers: Option<HeaderList>,
+}
+
+impl Message {
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    /// Get the header section of the message.
+    pub fn header_list(&self) -> &HeaderList {
+        &self.header_list
+    }
+
+    /// Get the content of the message.
+    pub fn body(&self) -> &[u8] {
+        &self.body
+    }
+
+    /// Get the trailer section of the message, if any.
+    pub fn trailers(&self) -> Option<&HeaderList> {
+        self.trailers.as_ref()
+    }
+
+    /// Check if the message is a response, carrying a `:status`
+    /// pseudo-header field.
+    pub fn is_response(&self) -> bool {
+        self.header_list.contains(":status")
+    }
+
+    /// Split the message into its header section, content and trailers.
+    pub fn into_parts(self) -> (HeaderList, Vec<u8>, Option<HeaderList>) {
+        (self.header_list, self.body, self.trailers)
+    }
+}
+
+/// Assembler of the message received on a stream.
+///
+/// The assembler consumes the frames of a single stream and produces the
+/// complete message once the stream is ended, for applications that do not
+/// need to process content as it arrives: the header section, assembled
+/// from a HEADERS frame and its CONTINUATION frames, the content of the
+/// DATA frames, and the trailers carried by a last header block.
+///
+/// Interim responses (1xx) preceding the final response are discarded.
+/// Frames that do not carry the message, such as WINDOW_UPDATE or
+/// PRIORITY frames, are ignored.
+#[derive(Debug)]
+pub struct MessageAssembler {
+    stream_id: u32,
+    header_list: Option<HeaderList>,
+    body: Vec<u8>,
+    pending_block: Option<(HeaderList, bool)>,
+    max_body_size: usize,
+    complete: bool,
+}
+
+impl MessageAssembler {
+    /// Create the assembler of the message of a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn new(stream_id: u32) -> Self {
+        MessageAssembler {
+            stream_id,
+            header_list: None,
+            body: Vec::new(),
+            pending_block: None,
+            max_body_size: DEFAULT_MAX_BODY_SIZE,
+            complete: false,
+        }
+    }
+
+    /// Consume a frame of the stream, returning the message it completes.
+    ///
+    /// A stream reset by the peer fails with a stream error carrying the
+    /// error code of the RST_STREAM frame. Content received before the
+    /// header section, or a trailer section without the END_STREAM flag,
+    /// is a stream error of type PROTOCOL_ERROR, and content exceeding the
+    /// maximum body size a stream error of type ENHANCE_YOUR_CALM.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The frame received on the stream.
+    pub fn push(&mut self, frame: &Frame) -> Result<Option<Message>, Http2Error> {
+        let stream_id = match frame {
+            Frame::Data(data_frame) => data_frame.stream_id,
+            Frame::Headers(headers_frame) => headers_frame.stream_id(),
+            Frame::Continuation(continuation_frame) => continuation_frame.stream_id(),
+            Frame::RstStream(rst_stream_frame) => rst_stream_frame.stream_id,
+            _ => return Ok(None),
+        };
+        if stream_id != self.stream_id {
+            return Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::ProtocolError,
+                format!("Frame of stream {} is not part of the message", stream_id),
+            ));
+        }
+        if self.complete {
+            return Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::StreamClosed,
+                "Frame received after the end of the message".to_string(),
+            ));
+        }
+
+        match frame {
+            Frame::Headers(_) if self.pending_block.is_some() => Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::ProtocolError,
+                "HEADERS frame received within a header block".to_string(),
+            )),
+            Frame::Headers(headers_frame) => {
+                let header_list = headers_frame.header_list().clone();
+                self.recv_header_block(
+                    header_list,
+                    headers_frame.end_stream(),
+                    headers_frame.end_headers(),
+                )
+            }
+            Frame::Continuation(continuation_frame) => match self.pending_block.take() {
+                Some((header_list, end_stream)) => {
+                    let header_list = header_list
+                        .into_iter()
+                        .chain(continuation_frame.header_list().clone())
+                        .collect();
+                    self.recv_header_block(
+                        header_list,
+                        end_stream,
+                        continuation_frame.end_headers(),
+                    )
+                }
+                None => Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::ProtocolError,
+                    "CONTINUATION frame without a header block".to_string(),
+                )),
+            },
+            Frame::Data(data_frame) => {
+                if self.header_list.is_none() || self.pending_block.is_some() {
+                    return Err(Http2Error::StreamError(
+                        stream_id,
+                        ErrorCode::ProtocolError,
+                        "DATA frame received before the header section".to_string(),
+                    ));
+                }
+                if self.body.len() + data_frame.data.len() > self.max_body_size {
+                    return Err(Http2Error::StreamError(
+                        stream_id,
+                        ErrorCode::EnhanceYourCalm,
+                        format!(
+                            "Message body exceeds the maximum size of {}",
+                            self.max_body_size
+                        ),
+                    ));
+                }
+
+                self.body.extend_from_slice(&data_frame.data);
+                match data_frame.end_stream {
+                    true => Ok(Some(self.complete(None))),
+                    false => Ok(None),
+                }
+            }
+            Frame::RstStream(rst_stream_frame) => {
+                self.complete = true;
+                Err(Http2Error::StreamError(
+                    stream_id,
+                    ErrorCode::from(rst_stream_frame.error_code),
+                    "Stream reset by the peer".to_string(),
+                ))
+            }
+            _ => Ok(None),
+        }
+    }
+
+    /// Complete a header block, or keep it pending until its END_HEADERS
+    /// flag.
+    ///
+    /// The first complete header block is the header section of the
+    /// message, unless it is an interim response, the next one its
+    /// trailers.
+    fn recv_header_block(
+        &mut self,
+        header_list: HeaderList,
+        end_stream: bool,
+        end_headers: bool,
+    ) -> Result<Option<Message>, Http2Error> {
+        if !end_headers {
+            self.pending_block = Some((header_list, end_stream));
+            return Ok(None);
+        }
+
+        if self.header_list.is_some() {
+            if !end_stream {
+                return Err(Http2Error::StreamError(
+                    self.stream_id,
+                    ErrorCode::ProtocolError,
+                    "Trailers must end the stream".to_string(),
+                ));
+            }
+            return Ok(Some(self.complete(Some(header_list))));
+        }
+
+        if matches!(header_list.status(), Some(100..=199)) && !end_stream {
+            return Ok(None);
+        }
+
+        self.header_list = Some(header_list);
+        match end_stream {
+            true => Ok(Some(self.complete(None))),
+            false => Ok(None),
+        }
+    }
+
+    /// Produce the message, once the stream is ended.
+    fn complete(&mut self, trailers: Option<HeaderList>) -> Message {
+        self.complete = true;
+
+        Message {
+            stream_id: self.stream_id,
+            header_list: self.header_list.take().unwrap_or_default(),
+            body: std::mem::take(&mut self.body),
+            trailers,
+        }
+    }
+
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    /// Get the size of the content received so far.
+    pub fn body_len(&self) -> usize {
+        self.body.len()
+    }
+
+    /// Check if the message was completed, or the stream reset.
+    pub fn is_complete(&self) -> bool {
+        self.complete
+    }
+
+    /// Get the maximum size of the body, [`DEFAULT_MAX_BODY_SIZE`] by
+    /// default.
+    pub fn max_body_size(&self) -> usize {
+        self.max_body_size
+    }
+
+    /// Set the maximum size of the body.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_body_size` - The maximum size of the body, in octets.
+    pub fn set_max_body_size(&mut self, max_body_size: usize) {
+        self.max_body_size = max_body_size;
+    }
+}
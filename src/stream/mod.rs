@@ -0,0 +1,277 @@
+use crate::error::ErrorCode;
+use crate::flow_control::WindowUpdateStrategy;
+use crate::header::list::HeaderList;
+use crate::stream::priority::StreamPriority;
+
+pub mod manager;
+pub mod message;
+pub mod priority;
+pub mod reset;
+pub mod scheduler;
+pub mod timeout;
+
+/// Default high-watermark of the receive buffer of a stream (1 MiB).
+pub const DEFAULT_RECV_BUFFER_LIMIT: usize = 1_048_576;
+
+/// Role of an endpoint on a HTTP/2 connection.
+///
+/// Streams initiated by a client use odd-numbered stream identifiers,
+/// streams initiated by a server use even-numbered stream identifiers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Role {
+    Client,
+    Server,
+}
+
+impl Role {
+    /// Check if a stream identifier belongs to a stream initiated by this role.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn initiates(&self, stream_id: u32) -> bool {
+        match self {
+            Role::Client => !stream_id.is_multiple_of(2),
+            Role::Server => stream_id != 0 && stream_id.is_multiple_of(2),
+        }
+    }
+}
+
+/// HTTP/2 stream states.
+///
+/// ```text
+///                              +--------+
+///                      send PP |        | recv PP
+///                     ,--------+  idle  +--------.
+///                    /         |        |         \
+///                   v          +--------+          v
+///            +----------+          |           +----------+
+///            |          |          | send H /  |          |
+///     ,------+ reserved |          | recv H    | reserved +------.
+///     |      | (local)  |          |           | (remote) |      |
+///     |      +---+------+          v           +------+---+      |
+///     |          |             +--------+             |          |
+///     |          |     recv ES |        | send ES     |          |
+///     |   send H |     ,-------+  open  +-------.     | recv H   |
+///     |          |    /        |        |        \    |          |
+///     |          v   v         +---+----+         v   v          |
+///     |      +----------+          |           +----------+      |
+///     |      |   half-  |          |           |   half-  |      |
+///     |      |  closed  |          | send R /  |  closed  |      |
+///     |      | (remote) |          | recv R    | (local)  |      |
+///     |      +----+-----+          |           +-----+----+      |
+///     |           |                |                 |           |
+///     |           | send ES /      |       recv ES / |           |
+///     |           |  send R /      v        send R / |           |
+///     |           |  recv R    +--------+   recv R   |           |
+///     | send R /  `----------->|        |<-----------'  send R / |
+///     | recv R                 | closed |               recv R   |
+///     `----------------------->|        |<-----------------------'
+///                              +--------+
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum StreamState {
+    Idle,
+    ReservedLocal,
+    ReservedRemote,
+    Open,
+    HalfClosedLocal,
+    HalfClosedRemote,
+    Closed,
+}
+
+impl StreamState {
+    /// Check if the state counts toward SETTINGS_MAX_CONCURRENT_STREAMS.
+    ///
+    /// Streams that are in the "open" state or in either of the
+    /// "half-closed" states count toward the maximum number of streams
+    /// that an endpoint is permitted to open.
+    pub fn is_active(&self) -> bool {
+        matches!(
+            self,
+            StreamState::Open | StreamState::HalfClosedLocal | StreamState::HalfClosedRemote
+        )
+    }
+}
+
+/// HTTP/2 stream.
+///
+/// Besides its state, a stream keeps track of the phase of the received
+/// message: a HEADERS frame received after the final header block of the
+/// message carries its trailers.
+///
+/// Received content is buffered until the application reads it. While the
+/// buffer exceeds its limit, the stream flow-control window is no longer
+/// replenished, so that the peer stops sending.
+#[derive(Debug, PartialEq)]
+pub struct Stream {
+    stream_id: u32,
+    state: StreamState,
+    headers_received: bool,
+    trailers: Option<HeaderList>,
+    content_length: Option<u64>,
+    data_received: u64,
+    recv_buffer: Vec<u8>,
+    recv_buffer_limit: usize,
+    pending_window_update: u32,
+    reset_sent: bool,
+    reset_received: Option<ErrorCode>,
+    not_processed: bool,
+    priority: StreamPriority,
+}
+
+impl Stream {
+    /// Create a new idle stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn new(stream_id: u32) -> Self {
+        Stream {
+            stream_id,
+            state: StreamState::Idle,
+            headers_received: false,
+            trailers: None,
+            content_length: None,
+            data_received: 0,
+            recv_buffer: Vec::new(),
+            recv_buffer_limit: DEFAULT_RECV_BUFFER_LIMIT,
+            pending_window_update: 0,
+            reset_sent: false,
+            reset_received: None,
+            not_processed: false,
+            priority: StreamPriority::new(),
+        }
+    }
+
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    pub fn state(&self) -> StreamState {
+        self.state
+    }
+
+    /// Check if the final header block of the received message was received.
+    pub fn headers_received(&self) -> bool {
+        self.headers_received
+    }
+
+    /// Get the trailers of the received message, if any.
+    pub fn trailers(&self) -> Option<&HeaderList> {
+        self.trailers.as_ref()
+    }
+
+    /// Get the `content-length` of the received message, if any.
+    pub fn content_length(&self) -> Option<u64> {
+        self.content_length
+    }
+
+    /// Get the number of DATA octets received, padding excluded.
+    pub fn data_received(&self) -> u64 {
+        self.data_received
+    }
+
+    /// Get the number of received octets not read by the application yet.
+    pub fn recv_buffered(&self) -> usize {
+        self.recv_buffer.len()
+    }
+
+    pub fn recv_buffer_limit(&self) -> usize {
+        self.recv_buffer_limit
+    }
+
+    /// Check if the local endpoint reset the stream with a RST_STREAM
+    /// frame.
+    pub fn reset_sent(&self) -> bool {
+        self.reset_sent
+    }
+
+    /// Get the error code of the RST_STREAM frame the peer reset the
+    /// stream with, if it did before the stream was closed.
+    pub fn reset_received(&self) -> Option<ErrorCode> {
+        self.reset_received
+    }
+
+    /// Check if the stream was closed unprocessed by a GOAWAY frame of the
+    /// peer, its request being safe to retry.
+    pub fn not_processed(&self) -> bool {
+        self.not_processed
+    }
+
+    /// Get the priority signaled by the peer.
+    pub fn priority(&self) -> StreamPriority {
+        self.priority
+    }
+
+    /// Set the high-watermark of the receive buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `recv_buffer_limit` - The number of buffered octets beyond which
+    ///   WINDOW_UPDATE frames are withheld.
+    pub fn set_recv_buffer_limit(&mut self, recv_buffer_limit: usize) {
+        self.recv_buffer_limit = recv_buffer_limit;
+    }
+
+    /// Buffer received content.
+    ///
+    /// # Arguments
+    ///
+    /// * `data` - The content of a DATA frame.
+    /// * `flow_controlled_length` - The flow-controlled length of the DATA
+    ///   frame, its padding included.
+    /// * `replenish` - Whether the stream window must be replenished, that
+    ///   is whether the peer may send more DATA frames.
+    pub(crate) fn buffer_data(
+        &mut self,
+        mut data: Vec<u8>,
+        flow_controlled_length: u32,
+        replenish: bool,
+    ) {
+        if replenish {
+            self.pending_window_update += flow_controlled_length;
+        }
+        self.recv_buffer.append(&mut data);
+    }
+
+    /// Remember the error code the peer reset the stream with.
+    ///
+    /// # Arguments
+    ///
+    /// * `error_code` - The error code of the RST_STREAM frame.
+    pub(crate) fn set_reset_received(&mut self, error_code: ErrorCode) {
+        self.reset_received = Some(error_code);
+    }
+
+    /// Take the buffered content.
+    pub(crate) fn take_recv_buffer(&mut self) -> Vec<u8> {
+        std::mem::take(&mut self.recv_buffer)
+    }
+
+    /// Take the increment of the WINDOW_UPDATE frame to send, 0 while the
+    /// buffer exceeds its limit, while the strategy waits for more octets
+    /// to be consumed or once the peer can no longer send.
+    ///
+    /// # Arguments
+    ///
+    /// * `strategy` - The window update strategy of the connection.
+    /// * `window_size` - The initial size of the stream windows.
+    pub(crate) fn release_window(
+        &mut self,
+        strategy: WindowUpdateStrategy,
+        window_size: u32,
+    ) -> u32 {
+        if !matches!(self.state, StreamState::Open | StreamState::HalfClosedLocal) {
+            self.pending_window_update = 0;
+        }
+
+        if self.recv_buffer.len() > self.recv_buffer_limit {
+            return 0;
+        }
+
+        let increment = strategy.increment(self.pending_window_update, window_size);
+        self.pending_window_update -= increment;
+        increment
+    }
+}
@@ -0,0 +1,104 @@
+use crate::frame::FramePriority;
+use crate::stream::scheduler::{DEFAULT_URGENCY, DEFAULT_WEIGHT};
+
+/// Maximum urgency of a stream (RFC 9218), the least urgent.
+pub const MAX_URGENCY: u8 = 7;
+
+/// Priority of a stream, as signaled by the peer.
+///
+/// Two schemes are surfaced: the deprecated priority tree of RFC 7540,
+/// carried by the priority block of HEADERS frames and by PRIORITY frames,
+/// which sets the weight and the stream dependency, and the extensible
+/// priorities of RFC 9218, carried by the `priority` header field, which
+/// sets the urgency and the incremental flag. Signals absent from a
+/// message leave the defaults.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct StreamPriority {
+    urgency: u8,
+    incremental: bool,
+    weight: u16,
+    stream_dependency: u32,
+}
+
+impl StreamPriority {
+    /// Create the default priority of a stream.
+    pub fn new() -> Self {
+        StreamPriority {
+            urgency: DEFAULT_URGENCY,
+            incremental: false,
+            weight: DEFAULT_WEIGHT,
+            stream_dependency: 0,
+        }
+    }
+
+    /// Apply the priority block of a HEADERS or PRIORITY frame.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame_priority` - The received frame priority.
+    pub fn apply_frame_priority(&mut self, frame_priority: &FramePriority) {
+        self.weight = frame_priority.weight() as u16 + 1;
+        self.stream_dependency = frame_priority.stream_dependency();
+    }
+
+    /// Apply the value of a `priority` header field (RFC 9218).
+    ///
+    /// The value is a structured field dictionary: `u` is the urgency,
+    /// an integer from 0 to 7, and `i` the incremental flag, a boolean.
+    /// Unknown parameters and invalid values are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value of the header field.
+    pub fn apply_priority_field(&mut self, value: &str) {
+        for member in value.split(',') {
+            let (key, value) = match member.split_once('=') {
+                Some((key, value)) => (key.trim(), Some(value.trim())),
+                None => (member.trim(), None),
+            };
+            // Parameters of the member are ignored.
+            let value = value.map(|value| value.split(';').next().unwrap_or_default());
+
+            match (key, value) {
+                ("u", Some(urgency)) => {
+                    if let Some(urgency) = urgency
+                        .parse::<u8>()
+                        .ok()
+                        .filter(|urgency| *urgency <= MAX_URGENCY)
+                    {
+                        self.urgency = urgency;
+                    }
+                }
+                ("i", None) | ("i", Some("?1")) => self.incremental = true,
+                ("i", Some("?0")) => self.incremental = false,
+                _ => (),
+            }
+        }
+    }
+
+    /// Get the urgency, from 0 (most urgent) to 7.
+    pub fn urgency(&self) -> u8 {
+        self.urgency
+    }
+
+    /// Check if the response can be processed incrementally.
+    pub fn incremental(&self) -> bool {
+        self.incremental
+    }
+
+    /// Get the weight, from 1 to 256.
+    pub fn weight(&self) -> u16 {
+        self.weight
+    }
+
+    /// Get the identifier of the stream this stream depends on, 0 for none.
+    pub fn stream_dependency(&self) -> u32 {
+        self.stream_dependency
+    }
+}
+
+impl Default for StreamPriority {
+    fn default() -> Self {
+        StreamPriority::new()
+    }
+}
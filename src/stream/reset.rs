@@ -0,0 +1,82 @@
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+
+use crate::error::{ErrorCode, Http2Error};
+
+/// Default maximum number of streams the peer may reset per interval.
+pub const DEFAULT_MAX_RESETS: usize = 100;
+
+/// Default interval over which peer resets are counted.
+pub const DEFAULT_RESET_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Rapid Reset (CVE-2023-44487) guard.
+///
+/// A peer can open a stream and immediately cancel it with RST_STREAM:
+/// the stream no longer counts against SETTINGS_MAX_CONCURRENT_STREAMS
+/// while the server still does the work of handling the request. The
+/// guard counts the streams reset by the peer before the local endpoint
+/// finished them over a sliding interval and fails with a connection
+/// error of type ENHANCE_YOUR_CALM once the limit is exceeded.
+#[derive(Debug)]
+pub struct ResetGuard {
+    max_resets: usize,
+    interval: Duration,
+    resets: VecDeque<Instant>,
+}
+
+impl ResetGuard {
+    /// Create a new reset guard.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_resets` - The maximum number of resets accepted per interval.
+    /// * `interval` - The length of the sliding interval.
+    pub fn new(max_resets: usize, interval: Duration) -> Self {
+        ResetGuard {
+            max_resets,
+            interval,
+            resets: VecDeque::new(),
+        }
+    }
+
+    /// Record a stream reset by the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time at which the RST_STREAM frame was received.
+    pub fn record(&mut self, now: Instant) -> Result<(), Http2Error> {
+        while let Some(reset) = self.resets.front() {
+            if now.duration_since(*reset) < self.interval {
+                break;
+            }
+            self.resets.pop_front();
+        }
+
+        if self.resets.len() >= self.max_resets {
+            return Err(Http2Error::ConnectionError(
+                ErrorCode::EnhanceYourCalm,
+                format!(
+                    "Peer reset more than {} streams in {:?}",
+                    self.max_resets, self.interval
+                ),
+            ));
+        }
+
+        self.resets.push_back(now);
+        Ok(())
+    }
+
+    pub fn max_resets(&self) -> usize {
+        self.max_resets
+    }
+
+    pub fn interval(&self) -> Duration {
+        self.interval
+    }
+}
+
+impl Default for ResetGuard {
+    fn default() -> Self {
+        ResetGuard::new(DEFAULT_MAX_RESETS, DEFAULT_RESET_INTERVAL)
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::VecDeque;
+
+use crate::error::Http2Error;
+use crate::flow_control::{FlowControlWindow, DEFAULT_INITIAL_WINDOW_SIZE};
+use crate::frame::data::DataFrame;
+use crate::frame::window_update::WindowUpdateFrame;
+use crate::frame::DEFAULT_MAX_FRAME_SIZE;
+use crate::stream::priority::StreamPriority;
+
+/// Default urgency of a stream (RFC 9218).
+pub const DEFAULT_URGENCY: u8 = 3;
+
+/// Default weight of a stream.
+pub const DEFAULT_WEIGHT: u16 = 16;
+
+/// Virtual time a stream of weight 1 spends to send one octet.
+const STRIDE: u64 = 256;
+
+/// Stream with queued body data.
+#[derive(Debug)]
+struct ScheduledStream {
+    window: FlowControlWindow,
+    urgency: u8,
+    weight: u16,
+    data: VecDeque<u8>,
+    end_stream: bool,
+    pass: u64,
+}
+
+impl ScheduledStream {
+    /// Number of octets the stream may send now.
+    fn sendable(&self, connection_window: i64, max_frame_size: u32) -> usize {
+        let window = self.window.size().min(connection_window).max(0) as usize;
+        self.data.len().min(window).min(max_frame_size as usize)
+    }
+}
+
+/// Sender-side DATA frame scheduler.
+///
+/// The application queues the body of every stream in the scheduler,
+/// which decides the order in which DATA frames are sent:
+///
+/// * streams with a lower urgency are served first,
+/// * streams of the same urgency share the connection in proportion to
+///   their weight, streams of equal weight are served round-robin,
+/// * a stream is only served when both its flow-control window and the
+///   connection flow-control window allow it, so a blocked stream does
+///   not hold back the others.
+///
+/// Weighted sharing uses stride scheduling: every stream advances a
+/// virtual time by the number of octets sent divided by its weight, and
+/// the stream with the earliest virtual time is served next.
+#[derive(Debug)]
+pub struct Scheduler {
+    connection_window: FlowControlWindow,
+    initial_window_size: u32,
+    max_frame_size: u32,
+    streams: Vec<(u32, ScheduledStream)>,
+    virtual_time: u64,
+}
+
+impl Scheduler {
+    /// Create a new scheduler with the initial flow-control windows and
+    /// frame size of a new connection.
+    pub fn new() -> Self {
+        Scheduler {
+            connection_window: FlowControlWindow::new(0, DEFAULT_INITIAL_WINDOW_SIZE),
+            initial_window_size: DEFAULT_INITIAL_WINDOW_SIZE,
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            streams: Vec::new(),
+            virtual_time: 0,
+        }
+    }
+
+    /// Queue body data on a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `data` - The data to send.
+    /// * `end_stream` - Whether the data ends the stream.
+    pub fn push(&mut self, stream_id: u32, data: &[u8], end_stream: bool) {
+        let stream = self.stream_mut(stream_id);
+        stream.data.extend(data);
+        stream.end_stream |= end_stream;
+    }
+
+    /// Set the priority of a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `urgency` - The urgency, from 0 (most urgent) to 7.
+    /// * `weight` - The weight, from 1 to 256.
+    pub fn set_priority(&mut self, stream_id: u32, urgency: u8, weight: u16) {
+        let stream = self.stream_mut(stream_id);
+        stream.urgency = urgency.min(7);
+        stream.weight = weight.clamp(1, 256);
+    }
+
+    /// Set the priority of a stream from the priority signaled by the peer,
+    /// as reported by [`Event::Priority`](crate::connection::core::Event::Priority).
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    /// * `priority` - The priority of the stream.
+    pub fn apply_priority(&mut self, stream_id: u32, priority: StreamPriority) {
+        self.set_priority(stream_id, priority.urgency(), priority.weight());
+    }
+
+    /// Remove a stream and discard its queued data, after a RST_STREAM.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn remove(&mut self, stream_id: u32) {
+        self.streams.retain(|(id, _)| *id != stream_id);
+    }
+
+    /// Apply a WINDOW_UPDATE frame received from the peer.
+    ///
+    /// Updates for streams without queued data are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `frame` - The WINDOW_UPDATE frame.
+    pub fn apply_window_update(&mut self, frame: &WindowUpdateFrame) -> Result<(), Http2Error> {
+        if frame.stream_id() == 0 {
+            return self.connection_window.apply_window_update(frame);
+        }
+
+        match self
+            .streams
+            .iter_mut()
+            .find(|(id, _)| *id == frame.stream_id())
+        {
+            Some((_, stream)) => stream.window.apply_window_update(frame),
+            None => Ok(()),
+        }
+    }
+
+    /// Build the next DATA frame to send.
+    ///
+    /// Returns `None` when no stream has data it is allowed to send. A
+    /// stream is removed from the scheduler once its last DATA frame,
+    /// carrying the END_STREAM flag, was built.
+    pub fn next_frame(&mut self) -> Option<DataFrame> {
+        let connection_window = self.connection_window.size();
+        let max_frame_size = self.max_frame_size;
+
+        let (index, length) = self
+            .streams
+            .iter()
+            .enumerate()
+            .filter_map(|(index, (_, stream))| {
+                let length = stream.sendable(connection_window, max_frame_size);
+                let end_only = stream.data.is_empty() && stream.end_stream;
+                (length > 0 || end_only).then_some((index, length))
+            })
+            .min_by_key(|(index, _)| {
+                let stream = &self.streams[*index].1;
+                (stream.urgency, stream.pass)
+            })?;
+
+        let (stream_id, stream) = &mut self.streams[index];
+        let stream_id = *stream_id;
+
+        // Both windows were checked above.
+        stream.window.consume(length as u32).ok()?;
+        self.connection_window.consume(length as u32).ok()?;
+
+        let data: Vec<u8> = stream.data.drain(..length).collect();
+        let end_stream = stream.end_stream && stream.data.is_empty();
+
+        self.virtual_time = stream.pass;
+        stream.pass += (length.max(1) as u64) * STRIDE / stream.weight as u64;
+
+        if end_stream {
+            self.streams.remove(index);
+        }
+
+        Some(DataFrame::new(stream_id, end_stream, data))
+    }
+
+    /// Check if no stream has queued data.
+    pub fn is_empty(&self) -> bool {
+        self.streams.is_empty()
+    }
+
+    /// Get the number of octets queued on a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn buffered(&self, stream_id: u32) -> usize {
+        self.streams
+            .iter()
+            .find(|(id, _)| *id == stream_id)
+            .map_or(0, |(_, stream)| stream.data.len())
+    }
+
+    pub fn connection_window(&self) -> &FlowControlWindow {
+        &self.connection_window
+    }
+
+    /// Set the initial flow-control window size of the streams.
+    ///
+    /// The value must be the SETTINGS_INITIAL_WINDOW_SIZE advertised by the
+    /// peer, it applies to the streams queued afterwards.
+    ///
+    /// # Arguments
+    ///
+    /// * `initial_window_size` - The initial window size in octets.
+    pub fn set_initial_window_size(&mut self, initial_window_size: u32) {
+        self.initial_window_size = initial_window_size;
+    }
+
+    /// Set the maximum size of the DATA frames.
+    ///
+    /// # Arguments
+    ///
+    /// * `max_frame_size` - The SETTINGS_MAX_FRAME_SIZE advertised by the peer.
+    pub fn set_max_frame_size(&mut self, max_frame_size: u32) {
+        self.max_frame_size = max_frame_size;
+    }
+
+    /// Get a scheduled stream, adding it if needed.
+    ///
+    /// New streams start at the current virtual time, so that they do not
+    /// get ahead of, nor fall behind, the streams already scheduled.
+    fn stream_mut(&mut self, stream_id: u32) -> &mut ScheduledStream {
+        let index = match self.streams.iter().position(|(id, _)| *id == stream_id) {
+            Some(index) => index,
+            None => {
+                self.streams.push((
+                    stream_id,
+                    ScheduledStream {
+                        window: FlowControlWindow::new(stream_id, self.initial_window_size),
+                        urgency: DEFAULT_URGENCY,
+                        weight: DEFAULT_WEIGHT,
+                        data: VecDeque::new(),
+                        end_stream: false,
+                        pass: self.virtual_time,
+                    },
+                ));
+                self.streams.len() - 1
+            }
+        };
+
+        &mut self.streams[index].1
+    }
+}
+
+impl Default for Scheduler {
+    fn default() -> Self {
+        Scheduler::new()
+    }
+}
@@ -0,0 +1,122 @@
+use std::time::{Duration, Instant};
+
+pub use crate::error::StreamTimeout;
+use crate::stream::{Stream, StreamState};
+
+/// Timeouts applied to the streams of a connection.
+///
+/// A slow or stalled peer can keep a stream, and the resources allocated
+/// to it, open indefinitely. Streams exceeding one of these timeouts are
+/// cancelled. A timeout set to `None` is not enforced.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct StreamTimeouts {
+    /// Maximum time between the opening of a stream and the reception of
+    /// the header section of the peer message.
+    pub header_timeout: Option<Duration>,
+    /// Maximum time between two frames of the peer message, once its
+    /// header section is received and until the peer ends the stream.
+    pub body_idle_timeout: Option<Duration>,
+    /// Maximum time between the opening and the closing of a stream.
+    pub total_timeout: Option<Duration>,
+}
+
+impl StreamTimeouts {
+    /// Check if any timeout is enforced.
+    pub fn is_enabled(&self) -> bool {
+        self.header_timeout.is_some()
+            || self.body_idle_timeout.is_some()
+            || self.total_timeout.is_some()
+    }
+}
+
+/// Deadlines of a stream.
+///
+/// The deadlines are computed from the time the stream was opened and the
+/// time its last frame was received, according to the state of the stream.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct StreamDeadline {
+    opened: Instant,
+    last_received: Instant,
+}
+
+impl StreamDeadline {
+    /// Start the deadlines of a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time at which the stream was opened.
+    pub fn new(now: Instant) -> Self {
+        StreamDeadline {
+            opened: now,
+            last_received: now,
+        }
+    }
+
+    /// Record a frame of the peer message.
+    ///
+    /// # Arguments
+    ///
+    /// * `now` - The time at which the frame was received.
+    pub fn record_received(&mut self, now: Instant) {
+        self.last_received = now;
+    }
+
+    /// Get the next deadline of the stream, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeouts` - The timeouts of the connection.
+    /// * `stream` - The stream.
+    pub fn next_deadline(&self, timeouts: &StreamTimeouts, stream: &Stream) -> Option<Instant> {
+        self.deadlines(timeouts, stream)
+            .map(|(_, deadline)| deadline)
+            .min()
+    }
+
+    /// Get the timeout expired on the stream, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `timeouts` - The timeouts of the connection.
+    /// * `stream` - The stream.
+    /// * `now` - The current time.
+    pub fn expired(
+        &self,
+        timeouts: &StreamTimeouts,
+        stream: &Stream,
+        now: Instant,
+    ) -> Option<StreamTimeout> {
+        self.deadlines(timeouts, stream)
+            .filter(|(_, deadline)| *deadline <= now)
+            .min_by_key(|(_, deadline)| *deadline)
+            .map(|(timeout, _)| timeout)
+    }
+
+    /// Get the deadlines applying to the stream in its current state.
+    fn deadlines(
+        &self,
+        timeouts: &StreamTimeouts,
+        stream: &Stream,
+    ) -> impl Iterator<Item = (StreamTimeout, Instant)> {
+        let receiving = matches!(
+            stream.state(),
+            StreamState::Open | StreamState::HalfClosedLocal
+        );
+        let header_timeout = timeouts
+            .header_timeout
+            .filter(|_| receiving && !stream.headers_received())
+            .map(|timeout| (StreamTimeout::Headers, self.opened + timeout));
+        let body_idle_timeout = timeouts
+            .body_idle_timeout
+            .filter(|_| receiving && stream.headers_received())
+            .map(|timeout| (StreamTimeout::BodyIdle, self.last_received + timeout));
+        let total_timeout = timeouts
+            .total_timeout
+            .filter(|_| stream.state() != StreamState::Closed)
+            .map(|timeout| (StreamTimeout::Total, self.opened + timeout));
+
+        [header_timeout, body_idle_timeout, total_timeout]
+            .into_iter()
+            .flatten()
+    }
+}
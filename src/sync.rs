@@ -0,0 +1,637 @@
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind, Read, Write};
+
+use crate::connection;
+use crate::error::{ErrorCode, Http2Error};
+use crate::header::list::HeaderList;
+use crate::transport::ByteStream;
+use crate::websocket;
+
+pub use crate::connection::core::Event;
+
+/// Synchronous HTTP/2 connection driver.
+///
+/// The driver wraps a [`connection::Connection`] over a blocking
+/// [`ByteStream`], such as a `std::net::TcpStream`, a TLS stream or an
+/// in-memory [`DuplexStream`](crate::transport::DuplexStream), and
+/// exchanges whole messages with blocking calls:
+/// [`Connection::send_request`] and [`Connection::send_response`] send a
+/// header section and its content, [`Connection::next_event`] reads frames
+/// until the next [`Event`].
+///
+/// Content is split into DATA frames within the SETTINGS_MAX_FRAME_SIZE of
+/// the peer and its flow-control windows. While the windows are exhausted,
+/// the driver reads frames from the peer, queueing the events they carry
+/// until the next call to [`Connection::next_event`]. Received content is
+/// buffered until it is read with [`Connection::recv_data`].
+///
+/// Stream errors reset the affected stream and are returned by the next
+/// call waiting on the stream or by [`Connection::next_event`], the
+/// connection remaining usable. Connection errors found in the frames of
+/// the peer are reported to it with a GOAWAY frame, written before the
+/// error is returned: the connection should then be dropped.
+/// Once the peer resets a stream, sending on it fails with a stream error
+/// carrying the error code of the peer, as does reading its content once
+/// the content received before the reset is read. Once the peer sends a
+/// GOAWAY frame, the requests it did not process fail with a
+/// [`Http2Error::StreamNotProcessed`] and are safe to retry on a new
+/// connection.
+///
+/// The deadlines of the connection and of its streams are checked whenever
+/// the driver reads from the transport. Streams exceeding their timeouts
+/// are cancelled and fail with a [`Http2Error::StreamTimeout`], returned
+/// the same way.
+/// Keep-alive PING frames are sent while the peer is silent, and calls fail
+/// with a connection error once a PING or a SETTINGS frame is not
+/// acknowledged in time. Deadlines follow the clock of the connection. To
+/// enforce deadlines while blocked on a silent peer, the transport should
+/// time out its reads, e.g. with `TcpStream::set_read_timeout`.
+pub struct Connection<T> {
+    inner: connection::Connection<T>,
+    events: VecDeque<Event>,
+    errors: VecDeque<Http2Error>,
+}
+
+impl<T: ByteStream> Connection<T> {
+    /// Start a client connection with prior knowledge of HTTP/2 support.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The transport, typically a TCP stream.
+    pub fn connect(io: T) -> Result<Self, Http2Error> {
+        Ok(Connection::from_connection(
+            connection::Connection::handshake_prior_knowledge(io)?,
+        ))
+    }
+
+    /// Accept a server connection with prior knowledge of HTTP/2 support.
+    ///
+    /// # Arguments
+    ///
+    /// * `io` - The transport, typically a TCP stream.
+    pub fn accept(io: T) -> Result<Self, Http2Error> {
+        Ok(Connection::from_connection(
+            connection::Connection::accept_prior_knowledge(io)?,
+        ))
+    }
+
+    /// Drive a connection which completed its handshake, e.g. built with a
+    /// [`ConnectionBuilder`](crate::connection::builder::ConnectionBuilder).
+    ///
+    /// # Arguments
+    ///
+    /// * `inner` - The connection to drive.
+    pub fn from_connection(inner: connection::Connection<T>) -> Self {
+        Connection {
+            inner,
+            events: VecDeque::new(),
+            errors: VecDeque::new(),
+        }
+    }
+
+    /// Send a request on a new stream and return the stream identifier.
+    ///
+    /// The stream is ended by the last DATA frame of the body, or by the
+    /// HEADERS frame if there is no body.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_list` - The request header section.
+    /// * `body` - The request content, if any.
+    pub fn send_request(
+        &mut self,
+        header_list: HeaderList,
+        body: Option<&[u8]>,
+    ) -> Result<u32, Http2Error> {
+        let body = body.filter(|body| !body.is_empty());
+
+        let stream_id = self
+            .inner
+            .core()
+            .send_request(header_list, body.is_none())?;
+        self.inner.write_output()?;
+        if let Some(body) = body {
+            self.send_data(stream_id, body, true)?;
+        }
+
+        Ok(stream_id)
+    }
+
+    /// Send the response to a request received on a stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream of the request.
+    /// * `header_list` - The response header section.
+    /// * `body` - The response content, if any.
+    pub fn send_response(
+        &mut self,
+        stream_id: u32,
+        header_list: HeaderList,
+        body: Option<&[u8]>,
+    ) -> Result<(), Http2Error> {
+        let body = body.filter(|body| !body.is_empty());
+
+        self.inner
+            .core()
+            .send_response(stream_id, header_list, body.is_none())?;
+        self.inner.write_output()?;
+        if let Some(body) = body {
+            self.send_data(stream_id, body, true)?;
+        }
+
+        Ok(())
+    }
+
+    /// Promise to push the response to a request received on a stream, and
+    /// return the promised stream identifier.
+    ///
+    /// The pushed response is sent on the promised stream with
+    /// [`Connection::send_response`]. Fails with PROTOCOL_ERROR if the
+    /// client disabled server push.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream of the request the push is associated with.
+    /// * `header_list` - The promised request header section.
+    pub fn send_push_promise(
+        &mut self,
+        stream_id: u32,
+        header_list: HeaderList,
+    ) -> Result<u32, Http2Error> {
+        let promised_stream_id = self
+            .inner
+            .core()
+            .send_push_promise(stream_id, header_list)?;
+        self.inner.write_output()?;
+
+        Ok(promised_stream_id)
+    }
+
+    /// Cancel a push promised by the server, with a RST_STREAM frame of type
+    /// CANCEL on the promised stream.
+    ///
+    /// Pushes are reported by [`Event::PushPromised`], and accepted by
+    /// reading the pushed response on the promised stream. The frames of the
+    /// pushed response the server sent before receiving the RST_STREAM frame
+    /// are ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * `promised_stream_id` - The promised stream identifier.
+    pub fn cancel_push(&mut self, promised_stream_id: u32) -> Result<(), Http2Error> {
+        self.inner
+            .core()
+            .send_reset(promised_stream_id, ErrorCode::Cancel)?;
+        self.inner.write_output()
+    }
+
+    /// Send an interim (1xx) response to a request received on a stream,
+    /// before its final response.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream of the request.
+    /// * `header_list` - The interim response header section.
+    pub fn send_interim_response(
+        &mut self,
+        stream_id: u32,
+        header_list: HeaderList,
+    ) -> Result<(), Http2Error> {
+        self.inner
+            .core()
+            .send_interim_response(stream_id, header_list)?;
+        self.inner.write_output()
+    }
+
+    /// Open a WebSocket with an extended CONNECT request (RFC 9220) and
+    /// return the stream carrying it.
+    ///
+    /// Blocks until the server responds, queueing the events of other
+    /// streams. Fails with PROTOCOL_ERROR if the server did not advertise
+    /// SETTINGS_ENABLE_CONNECT_PROTOCOL, and with a stream error of type
+    /// CONNECT_ERROR if the server refuses the handshake.
+    ///
+    /// # Arguments
+    ///
+    /// * `header_list` - The handshake request, built with
+    ///   [`websocket::request`].
+    pub fn open_websocket(
+        &mut self,
+        header_list: HeaderList,
+    ) -> Result<StreamChannel<'_, T>, Http2Error> {
+        let stream_id = self.inner.core().send_request(header_list, false)?;
+        self.inner.write_output()?;
+
+        let mut event = self.next_stream_event(stream_id)?;
+        while let Event::InterimResponse { .. } = event {
+            event = self.next_stream_event(stream_id)?;
+        }
+
+        match event {
+            Event::Headers {
+                header_list,
+                end_stream,
+                ..
+            } if websocket::is_accepted(&header_list) => Ok(StreamChannel {
+                connection: self,
+                stream_id,
+                buffer: Vec::new(),
+                end_stream,
+            }),
+            Event::Headers { header_list, .. } => Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::ConnectError,
+                format!(
+                    "WebSocket handshake refused with status {}",
+                    header_list.get(":status").unwrap_or_default()
+                ),
+            )),
+            Event::Reset { error_code, .. } => Err(Http2Error::StreamError(
+                stream_id,
+                error_code,
+                "WebSocket handshake reset by the server".to_string(),
+            )),
+            event => Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::ProtocolError,
+                format!("Unexpected event {:?} during WebSocket handshake", event),
+            )),
+        }
+    }
+
+    /// Accept a WebSocket handshake received on a stream and return the
+    /// stream carrying it.
+    ///
+    /// Fails with a stream error of type PROTOCOL_ERROR if the request is
+    /// not a WebSocket handshake.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream of the handshake.
+    /// * `header_list` - The handshake request.
+    pub fn accept_websocket(
+        &mut self,
+        stream_id: u32,
+        header_list: &HeaderList,
+    ) -> Result<StreamChannel<'_, T>, Http2Error> {
+        websocket::check_request(stream_id, header_list)?;
+        self.inner
+            .core()
+            .send_response(stream_id, websocket::response(), false)?;
+        self.inner.write_output()?;
+
+        Ok(StreamChannel {
+            connection: self,
+            stream_id,
+            buffer: Vec::new(),
+            end_stream: false,
+        })
+    }
+
+    /// Send content in DATA frames.
+    ///
+    /// Blocks reading frames from the peer while the flow-control windows
+    /// are exhausted.
+    fn send_data(
+        &mut self,
+        stream_id: u32,
+        mut body: &[u8],
+        end_stream: bool,
+    ) -> Result<(), Http2Error> {
+        loop {
+            while let Some(event) = self.poll_stream_event()? {
+                self.events.push_back(event);
+            }
+            if let Some(error) = self.take_stream_error(stream_id) {
+                return Err(error);
+            }
+
+            let sent = self.inner.core().send_data(stream_id, body, end_stream)?;
+            self.inner.write_output()?;
+            body = &body[sent..];
+            if body.is_empty() {
+                return Ok(());
+            }
+
+            if sent == 0 {
+                self.read_input()?;
+            }
+        }
+    }
+
+    /// Read frames until the next event.
+    ///
+    /// Blocks until the peer sends a header section, some content, the
+    /// trailers of a message, or resets a stream or the connection. Fails
+    /// with a stream error, such as a [`Http2Error::StreamTimeout`], once a
+    /// stream is reset by the local endpoint.
+    pub fn next_event(&mut self) -> Result<Event, Http2Error> {
+        loop {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(event);
+            }
+            if let Some(error) = self.errors.pop_front() {
+                return Err(error);
+            }
+
+            if let Some(event) = self.poll_event()? {
+                return Ok(event);
+            }
+
+            self.read_input()?;
+        }
+    }
+
+    /// Read frames until the next event of a stream, queueing the events
+    /// of the other streams.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    fn next_stream_event(&mut self, stream_id: u32) -> Result<Event, Http2Error> {
+        if let Some(position) = self
+            .events
+            .iter()
+            .position(|event| event_stream_id(event) == Some(stream_id))
+        {
+            return Ok(self.events.remove(position).unwrap());
+        }
+
+        loop {
+            match self.poll_stream_event()? {
+                Some(event) if event_stream_id(&event) == Some(stream_id) => return Ok(event),
+                Some(event) => self.events.push_back(event),
+                None => {
+                    if let Some(error) = self.take_stream_error(stream_id) {
+                        return Err(error);
+                    }
+                    self.read_input()?
+                }
+            }
+        }
+    }
+
+    /// Decode the next buffered event, writing the frames sent in
+    /// response, such as a RST_STREAM frame after a stream error.
+    ///
+    /// The GOAWAY frame reporting a connection error is flushed even if
+    /// writes are batched, a failing transport not hiding the error. The
+    /// streams a GOAWAY frame left unprocessed fail with a
+    /// [`Http2Error::StreamNotProcessed`], queued as the stream errors.
+    fn poll_event(&mut self) -> Result<Option<Event>, Http2Error> {
+        let event = self.inner.core().next_event();
+        match &event {
+            Err(error) if error.is_connection_error() => {
+                let _ = self.inner.flush();
+            }
+            _ => self.inner.write_output()?,
+        }
+
+        if let Ok(Some(Event::GoAway {
+            retryable_streams, ..
+        })) = &event
+        {
+            self.errors.extend(
+                retryable_streams
+                    .iter()
+                    .map(|stream_id| Http2Error::StreamNotProcessed(*stream_id)),
+            );
+        }
+
+        event
+    }
+
+    /// Decode the next buffered event while waiting on a stream, queueing
+    /// the stream errors until they are taken by the calls waiting on their
+    /// stream or by [`Connection::next_event`].
+    fn poll_stream_event(&mut self) -> Result<Option<Event>, Http2Error> {
+        loop {
+            match self.poll_event() {
+                Err(error) if error.is_stream_error() => self.errors.push_back(error),
+                result => return result,
+            }
+        }
+    }
+
+    /// Read once from the transport, unless streams were cancelled because
+    /// their deadline expired.
+    ///
+    /// A read timed out by the transport is not an error while the
+    /// connection or its streams have a deadline: the deadlines are checked
+    /// again instead.
+    fn read_input(&mut self) -> Result<(), Http2Error> {
+        if self.handle_timeouts()? {
+            return Ok(());
+        }
+
+        match self.inner.read_input() {
+            Err(Http2Error::IoError(error))
+                if matches!(error.kind(), ErrorKind::WouldBlock | ErrorKind::TimedOut)
+                    && self.inner.core().poll_timeout().is_some() =>
+            {
+                self.handle_timeouts().map(|_| ())
+            }
+            result => result,
+        }
+    }
+
+    /// Handle the expired deadlines, queueing the errors of the cancelled
+    /// streams.
+    ///
+    /// Returns whether any stream was cancelled, and fails if the peer did
+    /// not acknowledge a SETTINGS frame or a keep-alive PING in time.
+    fn handle_timeouts(&mut self) -> Result<bool, Http2Error> {
+        let core = self.inner.core();
+        let errors = core.handle_timeouts(core.now())?;
+        self.inner.write_output()?;
+
+        let cancelled = !errors.is_empty();
+        self.errors.extend(errors);
+
+        Ok(cancelled)
+    }
+
+    /// Take the error of a stream reset by the local endpoint.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    fn take_stream_error(&mut self, stream_id: u32) -> Option<Http2Error> {
+        let position = self
+            .errors
+            .iter()
+            .position(|error| error.stream_id() == Some(stream_id))?;
+
+        self.errors.remove(position)
+    }
+
+    /// Read the content buffered on a stream.
+    ///
+    /// Once the peer reset the stream, the content received before the
+    /// reset is returned, then reading fails with a stream error carrying
+    /// the error code of the peer.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_id` - The stream identifier.
+    pub fn recv_data(&mut self, stream_id: u32) -> Result<Vec<u8>, Http2Error> {
+        let data = self.inner.core().recv_data(stream_id)?;
+        self.inner.write_output()?;
+
+        Ok(data)
+    }
+
+    /// Write the frames batched for the transport, if the writes are
+    /// batched with [`connection::builder::ConnectionBuilder::max_write_batch`].
+    pub fn flush(&mut self) -> Result<(), Http2Error> {
+        self.inner.flush()
+    }
+
+    /// Get the underlying connection.
+    pub fn get_mut(&mut self) -> &mut connection::Connection<T> {
+        &mut self.inner
+    }
+
+    /// Release the underlying connection.
+    pub fn into_inner(self) -> connection::Connection<T> {
+        self.inner
+    }
+}
+
+/// Stream of an established tunnel, such as a WebSocket, used as a
+/// bidirectional byte channel.
+///
+/// Reads return the content received on the stream, and 0 once the peer
+/// ended it. Writes send DATA frames without ending the stream, which is
+/// ended with [`StreamChannel::close`]. Once the peer resets the stream,
+/// reads return the content received before the reset, then reads and
+/// writes fail with an IO error of kind `ConnectionReset` wrapping the
+/// stream error, which carries the error code of the peer. Events of other streams received
+/// meanwhile are queued until the next call to [`Connection::next_event`].
+pub struct StreamChannel<'a, T> {
+    connection: &'a mut Connection<T>,
+    stream_id: u32,
+    buffer: Vec<u8>,
+    end_stream: bool,
+}
+
+impl<T: ByteStream> StreamChannel<'_, T> {
+    /// Get the identifier of the stream.
+    pub fn stream_id(&self) -> u32 {
+        self.stream_id
+    }
+
+    /// End the stream in the local direction.
+    ///
+    /// Content sent by the peer can still be read until it ends the stream.
+    pub fn close(&mut self) -> Result<(), Http2Error> {
+        self.connection.send_data(self.stream_id, &[], true)
+    }
+
+    /// Convert an error of the connection into an IO error, of kind
+    /// `ConnectionReset` once the peer reset the stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The error to convert.
+    fn stream_io_error(&mut self, error: Http2Error) -> io::Error {
+        let reset = self
+            .connection
+            .inner
+            .core()
+            .streams()
+            .get(self.stream_id)
+            .is_some_and(|stream| stream.reset_received().is_some());
+
+        match error {
+            error if reset && error.stream_id() == Some(self.stream_id) => {
+                io::Error::new(io::ErrorKind::ConnectionReset, error)
+            }
+            error => into_io_error(error),
+        }
+    }
+}
+
+impl<T: ByteStream> Read for StreamChannel<'_, T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        while self.buffer.is_empty() && !self.end_stream {
+            match self
+                .connection
+                .next_stream_event(self.stream_id)
+                .map_err(into_io_error)?
+            {
+                Event::Data { end_stream, .. } => {
+                    self.buffer = self
+                        .connection
+                        .recv_data(self.stream_id)
+                        .map_err(into_io_error)?;
+                    self.end_stream = end_stream;
+                }
+                Event::Trailers { .. } => self.end_stream = true,
+                // The content received before the reset was read with its
+                // DATA events.
+                Event::Reset { error_code, .. } => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::ConnectionReset,
+                        Http2Error::StreamError(
+                            self.stream_id,
+                            error_code,
+                            "Stream reset by the peer".to_string(),
+                        ),
+                    ))
+                }
+                _ => (),
+            }
+        }
+
+        let read = buf.len().min(self.buffer.len());
+        buf[..read].copy_from_slice(&self.buffer[..read]);
+        self.buffer.drain(..read);
+
+        Ok(read)
+    }
+}
+
+impl<T: ByteStream> Write for StreamChannel<'_, T> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if !buf.is_empty() {
+            self.connection
+                .send_data(self.stream_id, buf, false)
+                .map_err(|error| self.stream_io_error(error))?;
+        }
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.connection.inner.flush().map_err(into_io_error)
+    }
+}
+
+/// Get the stream an event was received on, if any.
+fn event_stream_id(event: &Event) -> Option<u32> {
+    match *event {
+        Event::Headers { stream_id, .. }
+        | Event::Data { stream_id, .. }
+        | Event::InterimResponse { stream_id, .. }
+        | Event::Trailers { stream_id, .. }
+        | Event::Reset { stream_id, .. }
+        | Event::Priority { stream_id, .. }
+        | Event::PushPromised { stream_id, .. } => Some(stream_id),
+        Event::WindowAvailable { stream_id, .. } if stream_id != 0 => Some(stream_id),
+        Event::GoAway { .. }
+        | Event::SettingsChanged { .. }
+        | Event::SettingsAcked { .. }
+        | Event::PingAcked { .. }
+        | Event::WindowAvailable { .. } => None,
+    }
+}
+
+/// Convert an error of the connection into an IO error.
+fn into_io_error(error: Http2Error) -> io::Error {
+    match error {
+        Http2Error::IoError(error) => error,
+        error => io::Error::other(error),
+    }
+}
@@ -0,0 +1,49 @@
+//! End-to-end connections over an in-memory transport.
+//!
+//! A client and a server [`Connection`] are connected in the same process
+//! over a pair of [`DuplexStream`]s, without sockets:
+//!
+//! ```no_run
+//! # use http2::test_support::duplex;
+//! let (client, server) = duplex().unwrap();
+//! ```
+//!
+//! Each end blocks while waiting on the other, such as for a WINDOW_UPDATE
+//! frame once the flow-control windows are exhausted: exchanges larger
+//! than the windows need the server to run in another thread.
+
+use std::thread;
+
+use crate::connection::builder::ConnectionBuilder;
+use crate::error::Http2Error;
+use crate::sync::Connection;
+use crate::transport::{self, DuplexStream};
+
+/// Connect a client and a server with the default settings.
+pub fn duplex() -> Result<(Connection<DuplexStream>, Connection<DuplexStream>), Http2Error> {
+    duplex_with(ConnectionBuilder::client(), ConnectionBuilder::server())
+}
+
+/// Connect a client and a server built by the given builders.
+///
+/// The handshakes run concurrently, the server one in another thread.
+///
+/// # Arguments
+///
+/// * `client_builder` - The builder of the client connection.
+/// * `server_builder` - The builder of the server connection.
+pub fn duplex_with(
+    client_builder: ConnectionBuilder,
+    server_builder: ConnectionBuilder,
+) -> Result<(Connection<DuplexStream>, Connection<DuplexStream>), Http2Error> {
+    let (client_io, server_io) = transport::duplex();
+
+    let server = thread::spawn(move || server_builder.handshake(server_io));
+    let client = client_builder.handshake(client_io);
+    let server = server.join().expect("server handshake panicked");
+
+    Ok((
+        Connection::from_connection(client?),
+        Connection::from_connection(server?),
+    ))
+}
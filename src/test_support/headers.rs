@@ -0,0 +1,16 @@
+//! Header lists written inline in tests.
+
+use crate::header::field::HeaderField;
+use crate::header::list::HeaderList;
+
+/// Build a header list from name and value pairs.
+///
+/// # Arguments
+///
+/// * `header_fields` - The names and values of the header fields, in order.
+pub fn header_list(header_fields: &[(&str, &str)]) -> HeaderList {
+    header_fields
+        .iter()
+        .map(|(name, value)| HeaderField::new((*name).into(), (*value).into()))
+        .collect()
+}
@@ -0,0 +1,89 @@
+//! Hex dump fixtures.
+//!
+//! Test vectors are written as the hex dumps of the RFCs, such as the
+//! examples of RFC 7541 appendix C:
+//!
+//! ```text
+//! # C.4.1 First Request
+//! 8286 8441 8cf1 e3c2 e5f2 3a6b a0ab 90f4 | ..A......:k....
+//! ff                                      | .
+//! ```
+//!
+//! Each line holds hexadecimal digits, grouped as desired, optionally
+//! followed by an ASCII gutter after a `|`. Lines starting with `#` are
+//! comments. Fixtures are stored as `tests/fixtures/<name>.hex` files.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Parse a hex dump into bytes.
+///
+/// The ASCII gutter of every line, following a `|`, and the comment lines,
+/// starting with `#`, are ignored, as well as whitespace between digits.
+///
+/// # Arguments
+///
+/// * `dump` - The hex dump text.
+pub fn parse(dump: &str) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+
+    for (index, line) in dump.lines().enumerate() {
+        let line = line.split('|').next().unwrap_or_default().trim();
+        if line.starts_with('#') {
+            continue;
+        }
+
+        let digits: String = line.split_whitespace().collect();
+        if let Some(c) = digits.chars().find(|c| !c.is_ascii_hexdigit()) {
+            return Err(format!(
+                "line {}: invalid hexadecimal digit {:?}",
+                index + 1,
+                c
+            ));
+        }
+        if !digits.len().is_multiple_of(2) {
+            return Err(format!(
+                "line {}: odd number of hexadecimal digits",
+                index + 1
+            ));
+        }
+
+        bytes.extend(
+            (0..digits.len())
+                .step_by(2)
+                .map(|i| u8::from_str_radix(&digits[i..i + 2], 16).unwrap_or_default()),
+        );
+    }
+
+    Ok(bytes)
+}
+
+/// Load a hex dump file.
+///
+/// # Arguments
+///
+/// * `path` - The path of the file.
+pub fn load(path: impl AsRef<Path>) -> Result<Vec<u8>, String> {
+    let path = path.as_ref();
+    let dump =
+        fs::read_to_string(path).map_err(|error| format!("{}: {}", path.display(), error))?;
+
+    parse(&dump).map_err(|error| format!("{}: {}", path.display(), error))
+}
+
+/// Load the hex dump fixture `tests/fixtures/<name>.hex` of the crate
+/// being tested.
+///
+/// # Arguments
+///
+/// * `name` - The name of the fixture, without its extension.
+pub fn fixture(name: &str) -> Result<Vec<u8>, String> {
+    let mut path = std::env::var_os("CARGO_MANIFEST_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_default();
+    path.push("tests");
+    path.push("fixtures");
+    path.push(format!("{}.hex", name));
+
+    load(path)
+}
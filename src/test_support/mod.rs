@@ -0,0 +1,261 @@
+//! Property-based testing support.
+//!
+//! Proptest strategies generating valid frames and header lists, to be
+//! used in round-trip tests of this crate or of crates built on top of it,
+//! a loader of hex dump fixtures in [`hex`], client and server connections
+//! linked in memory for end-to-end tests in [`duplex`](mod@duplex), client
+//! and server cores driven by hand in [`pair`], and header lists written
+//! inline in [`headers`].
+
+pub mod duplex;
+pub mod headers;
+pub mod hex;
+pub mod pair;
+
+pub use duplex::{duplex, duplex_with};
+pub use pair::{establish, transfer};
+
+use proptest::collection::vec;
+use proptest::option;
+use proptest::prelude::*;
+
+use crate::connection::stream_id::MAX_STREAM_ID;
+use crate::frame::continuation::ContinuationFrame;
+use crate::frame::data::DataFrame;
+use crate::frame::headers::HeadersFrame;
+use crate::frame::ping::PingFrame;
+use crate::frame::push_promise::PushPromiseFrame;
+use crate::frame::raw::RawFrame;
+use crate::frame::rst_stream::RstStreamFrame;
+use crate::frame::settings::{SettingsFrame, SettingsParameter};
+use crate::frame::window_update::WindowUpdateFrame;
+use crate::frame::{Frame, FrameHeader, FramePriority, MAX_FRAME_SIZE_UPPER_BOUND};
+use crate::header::field::{HeaderField, HeaderName, HeaderValue};
+use crate::header::list::HeaderList;
+
+/// Build a frame that has no constructor from its wire representation.
+///
+/// # Arguments
+///
+/// * `frame_type` - The frame type.
+/// * `frame_flags` - The frame flags.
+/// * `stream_id` - The stream identifier.
+/// * `payload` - The frame payload.
+fn from_wire(frame_type: u8, frame_flags: u8, stream_id: u32, payload: Vec<u8>) -> Frame {
+    let mut bytes = FrameHeader::new(
+        payload.len() as u32,
+        frame_type,
+        frame_flags,
+        false,
+        stream_id,
+    )
+    .serialize();
+    bytes.extend(payload);
+
+    RawFrame::deserialize(&mut bytes)
+        .and_then(RawFrame::into_frame)
+        .expect("valid generated frame")
+}
+
+/// Generate a frame header, of any type and with any flags.
+pub fn frame_header() -> impl Strategy<Value = FrameHeader> {
+    (
+        0..=MAX_FRAME_SIZE_UPPER_BOUND,
+        any::<u8>(),
+        any::<u8>(),
+        any::<bool>(),
+        0..=MAX_STREAM_ID,
+    )
+        .prop_map(
+            |(payload_length, frame_type, frame_flags, reserved, stream_id)| {
+                FrameHeader::new(payload_length, frame_type, frame_flags, reserved, stream_id)
+            },
+        )
+}
+
+/// Generate a stream identifier other than 0.
+pub fn stream_id() -> impl Strategy<Value = u32> {
+    1..=MAX_STREAM_ID
+}
+
+/// Generate the identifier of a stream initiated by a client.
+pub fn client_stream_id() -> impl Strategy<Value = u32> {
+    (0..=MAX_STREAM_ID / 2).prop_map(|index| index * 2 + 1)
+}
+
+/// Generate the identifier of a stream initiated by a server.
+pub fn server_stream_id() -> impl Strategy<Value = u32> {
+    (1..=MAX_STREAM_ID / 2).prop_map(|index| index * 2)
+}
+
+/// Generate a header field with a lowercase token name and a visible
+/// ASCII value.
+pub fn header_field() -> impl Strategy<Value = HeaderField> {
+    ("[a-z][a-z0-9-]{0,15}", "[ -~]{0,32}").prop_map(|(name, value)| {
+        HeaderField::new(
+            HeaderName::from(name.as_str()),
+            HeaderValue::from(value.as_str()),
+        )
+    })
+}
+
+/// Generate a header list of up to 8 header fields.
+pub fn header_list() -> impl Strategy<Value = HeaderList> {
+    vec(header_field(), 0..8).prop_map(HeaderList::new)
+}
+
+/// Generate a frame priority.
+pub fn frame_priority() -> impl Strategy<Value = FramePriority> {
+    (any::<bool>(), 0..=MAX_STREAM_ID, any::<u8>()).prop_map(
+        |(exclusive, stream_dependency, weight)| {
+            FramePriority::new(exclusive, stream_dependency, weight)
+        },
+    )
+}
+
+/// Generate a SETTINGS parameter with a valid value.
+pub fn settings_parameter() -> impl Strategy<Value = SettingsParameter> {
+    prop_oneof![
+        any::<u32>().prop_map(SettingsParameter::HeaderTableSize),
+        (0..=1u32).prop_map(SettingsParameter::EnablePush),
+        any::<u32>().prop_map(SettingsParameter::MaxConcurrentStreams),
+        (0..=MAX_STREAM_ID).prop_map(SettingsParameter::InitialWindowSize),
+        (16_384..=16_777_215u32).prop_map(SettingsParameter::MaxFrameSize),
+        any::<u32>().prop_map(SettingsParameter::MaxHeaderListSize),
+        (0..=1u32).prop_map(SettingsParameter::EnableConnectProtocol),
+    ]
+}
+
+/// Generate a DATA frame.
+pub fn data_frame() -> impl Strategy<Value = Frame> {
+    (stream_id(), any::<bool>(), vec(any::<u8>(), 0..64)).prop_map(
+        |(stream_id, end_stream, data)| Frame::Data(DataFrame::new(stream_id, end_stream, data)),
+    )
+}
+
+/// Generate a HEADERS frame.
+pub fn headers_frame() -> impl Strategy<Value = Frame> {
+    (
+        stream_id(),
+        any::<bool>(),
+        any::<bool>(),
+        option::of(frame_priority()),
+        header_list(),
+    )
+        .prop_map(
+            |(stream_id, end_stream, end_headers, frame_priority, header_list)| {
+                Frame::Headers(HeadersFrame::new(
+                    stream_id,
+                    end_stream,
+                    end_headers,
+                    frame_priority,
+                    header_list,
+                ))
+            },
+        )
+}
+
+/// Generate a PRIORITY frame.
+pub fn priority_frame() -> impl Strategy<Value = Frame> {
+    (stream_id(), frame_priority()).prop_map(|(stream_id, frame_priority)| {
+        from_wire(0x2, 0x0, stream_id, frame_priority.serialize())
+    })
+}
+
+/// Generate a RST_STREAM frame.
+pub fn rst_stream_frame() -> impl Strategy<Value = Frame> {
+    (stream_id(), any::<u32>()).prop_map(|(stream_id, error_code)| {
+        Frame::RstStream(RstStreamFrame {
+            stream_id,
+            error_code,
+        })
+    })
+}
+
+/// Generate a SETTINGS frame, either an acknowledgement or a list of parameters.
+pub fn settings_frame() -> impl Strategy<Value = Frame> {
+    (any::<bool>(), vec(settings_parameter(), 0..6)).prop_map(|(ack, parameters)| {
+        if ack {
+            Frame::Settings(SettingsFrame::ack())
+        } else {
+            Frame::Settings(
+                parameters
+                    .into_iter()
+                    .fold(SettingsFrame::new(), SettingsFrame::with_parameter),
+            )
+        }
+    })
+}
+
+/// Generate a PUSH_PROMISE frame.
+pub fn push_promise_frame() -> impl Strategy<Value = Frame> {
+    (
+        client_stream_id(),
+        any::<bool>(),
+        server_stream_id(),
+        header_list(),
+    )
+        .prop_map(
+            |(stream_id, end_headers, promised_stream_id, header_list)| {
+                Frame::PushPromise(PushPromiseFrame::new(
+                    stream_id,
+                    end_headers,
+                    promised_stream_id,
+                    header_list,
+                ))
+            },
+        )
+}
+
+/// Generate a PING frame.
+pub fn ping_frame() -> impl Strategy<Value = Frame> {
+    (any::<bool>(), any::<[u8; 8]>()).prop_map(|(ack, opaque_data)| {
+        if ack {
+            Frame::Ping(PingFrame::ack(opaque_data))
+        } else {
+            Frame::Ping(PingFrame::new(opaque_data))
+        }
+    })
+}
+
+/// Generate a GOAWAY frame.
+pub fn go_away_frame() -> impl Strategy<Value = Frame> {
+    (0..=MAX_STREAM_ID, any::<u32>(), vec(any::<u8>(), 0..32)).prop_map(
+        |(last_stream_id, error_code, debug_data)| {
+            let mut payload = last_stream_id.to_be_bytes().to_vec();
+            payload.extend_from_slice(&error_code.to_be_bytes());
+            payload.extend(debug_data);
+            from_wire(0x7, 0x0, 0, payload)
+        },
+    )
+}
+
+/// Generate a WINDOW_UPDATE frame with a valid increment.
+pub fn window_update_frame() -> impl Strategy<Value = Frame> {
+    (0..=MAX_STREAM_ID, 1..=MAX_STREAM_ID).prop_map(|(stream_id, window_size_increment)| {
+        Frame::WindowUpdate(WindowUpdateFrame::new(stream_id, window_size_increment))
+    })
+}
+
+/// Generate a CONTINUATION frame.
+pub fn continuation_frame() -> impl Strategy<Value = Frame> {
+    (stream_id(), any::<bool>(), header_list()).prop_map(|(stream_id, end_headers, header_list)| {
+        Frame::Continuation(ContinuationFrame::new(stream_id, end_headers, header_list))
+    })
+}
+
+/// Generate a frame of any type.
+pub fn frame() -> impl Strategy<Value = Frame> {
+    prop_oneof![
+        data_frame(),
+        headers_frame(),
+        priority_frame(),
+        rst_stream_frame(),
+        settings_frame(),
+        push_promise_frame(),
+        ping_frame(),
+        go_away_frame(),
+        window_update_frame(),
+        continuation_frame(),
+    ]
+}
@@ -0,0 +1,41 @@
+//! Client and server cores exchanging their output in memory.
+//!
+//! Sans-I/O tests drive an [`Http2Core`] pair by hand, passing the output
+//! of one end to the other:
+//!
+//! ```
+//! # use http2::connection::builder::ConnectionBuilder;
+//! # use http2::test_support::{establish, transfer};
+//! let mut client = ConnectionBuilder::client().build().unwrap();
+//! let mut server = ConnectionBuilder::server().build().unwrap();
+//! establish(&mut client, &mut server);
+//! assert_eq!(transfer(&mut client, &mut server), vec![]);
+//! ```
+
+use crate::connection::core::{Event, Http2Core};
+
+/// Pass the output of a core to its peer, returning the events received.
+///
+/// Panics if the peer fails to handle the output.
+///
+/// # Arguments
+///
+/// * `from` - The core whose output is sent.
+/// * `to` - The core receiving the output.
+pub fn transfer(from: &mut Http2Core, to: &mut Http2Core) -> Vec<Event> {
+    to.handle_input(&from.poll_output())
+        .expect("peer output handled")
+}
+
+/// Establish a client and a server core, exchanging their prefaces and
+/// SETTINGS acknowledgements.
+///
+/// # Arguments
+///
+/// * `client` - The client core.
+/// * `server` - The server core.
+pub fn establish(client: &mut Http2Core, server: &mut Http2Core) {
+    transfer(client, server);
+    transfer(server, client);
+    transfer(client, server);
+}
@@ -0,0 +1,147 @@
+use std::io::{self, Read, Write};
+use std::ops::{Deref, DerefMut};
+use std::sync::Arc;
+
+use rustls::pki_types::ServerName;
+use rustls::{
+    ClientConfig, ClientConnection, ConnectionCommon, ServerConfig, ServerConnection, SideData,
+    StreamOwned,
+};
+
+use crate::connection::Connection;
+use crate::error::Http2Error;
+use crate::transport::ByteStream;
+
+/// ALPN protocol identifier of HTTP/2 over TLS.
+pub const ALPN_H2: &[u8] = b"h2";
+
+/// ALPN protocol identifier of HTTP/1.1.
+pub const ALPN_HTTP11: &[u8] = b"http/1.1";
+
+/// HTTP/2 connection over a rustls client stream.
+pub type ClientTlsConnection<T> = Connection<StreamOwned<ClientConnection, T>>;
+
+/// HTTP/2 connection over a rustls server stream.
+pub type ServerTlsConnection<T> = Connection<StreamOwned<ServerConnection, T>>;
+
+impl<C, S, T> ByteStream for StreamOwned<C, T>
+where
+    C: DerefMut + Deref<Target = ConnectionCommon<S>>,
+    S: SideData,
+    T: Read + Write,
+{
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        Read::read(self, buf)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        Write::write(self, buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Write::flush(self)
+    }
+
+    /// Send the TLS close_notify alert, the underlying transport is left
+    /// open.
+    fn shutdown(&mut self) -> io::Result<()> {
+        self.conn.send_close_notify();
+        Write::flush(self)
+    }
+}
+
+impl From<rustls::Error> for Http2Error {
+    /// Wrap a rustls error into a Http2Error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error` - The rustls error to wrap.
+    fn from(error: rustls::Error) -> Self {
+        Http2Error::TlsError(error.to_string())
+    }
+}
+
+/// Check that a list of ALPN protocols offers HTTP/2.
+///
+/// # Arguments
+///
+/// * `alpn_protocols` - The ALPN protocols of a rustls configuration.
+fn check_alpn_offered(alpn_protocols: &[Vec<u8>]) -> Result<(), Http2Error> {
+    if alpn_protocols.iter().any(|protocol| protocol == ALPN_H2) {
+        Ok(())
+    } else {
+        Err(Http2Error::TlsError(
+            "TLS configuration does not offer the h2 ALPN protocol".to_string(),
+        ))
+    }
+}
+
+/// Check that the negotiated ALPN protocol is HTTP/2.
+///
+/// # Arguments
+///
+/// * `alpn_protocol` - The protocol negotiated during the TLS handshake.
+fn check_alpn_negotiated(alpn_protocol: Option<&[u8]>) -> Result<(), Http2Error> {
+    match alpn_protocol {
+        Some(ALPN_H2) => Ok(()),
+        Some(protocol) => Err(Http2Error::TlsError(format!(
+            "ALPN negotiated {} instead of h2",
+            String::from_utf8_lossy(protocol)
+        ))),
+        None => Err(Http2Error::TlsError(
+            "No ALPN protocol negotiated".to_string(),
+        )),
+    }
+}
+
+/// Open a HTTP/2 connection over TLS as a client.
+///
+/// Performs the TLS handshake offering the `h2` ALPN protocol, checks that
+/// the server selected it, then performs the HTTP/2 connection handshake.
+/// Connections where the server negotiated another protocol, such as
+/// `http/1.1`, or no protocol at all are rejected.
+///
+/// # Arguments
+///
+/// * `config` - The rustls client configuration, offering `h2` in ALPN.
+/// * `server_name` - The name of the server to verify its certificate.
+/// * `io` - The transport, typically a TCP stream.
+pub fn connect<T: Read + Write>(
+    config: Arc<ClientConfig>,
+    server_name: ServerName<'static>,
+    mut io: T,
+) -> Result<ClientTlsConnection<T>, Http2Error> {
+    check_alpn_offered(&config.alpn_protocols)?;
+
+    let mut tls = ClientConnection::new(config, server_name)?;
+    while tls.is_handshaking() {
+        tls.complete_io(&mut io)?;
+    }
+    check_alpn_negotiated(tls.alpn_protocol())?;
+
+    Connection::handshake_prior_knowledge(StreamOwned::new(tls, io))
+}
+
+/// Accept a HTTP/2 connection over TLS as a server.
+///
+/// Performs the TLS handshake, checks that the `h2` ALPN protocol was
+/// negotiated, then performs the HTTP/2 connection handshake.
+///
+/// # Arguments
+///
+/// * `config` - The rustls server configuration, accepting `h2` in ALPN.
+/// * `io` - The transport, typically a TCP stream.
+pub fn accept<T: Read + Write>(
+    config: Arc<ServerConfig>,
+    mut io: T,
+) -> Result<ServerTlsConnection<T>, Http2Error> {
+    check_alpn_offered(&config.alpn_protocols)?;
+
+    let mut tls = ServerConnection::new(config)?;
+    while tls.is_handshaking() {
+        tls.complete_io(&mut io)?;
+    }
+    check_alpn_negotiated(tls.alpn_protocol())?;
+
+    Connection::accept_prior_knowledge(StreamOwned::new(tls, io))
+}
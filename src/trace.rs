@@ -0,0 +1,84 @@
+//! Tracing instrumentation.
+//!
+//! With the `tracing` feature enabled, the library reports every frame
+//! sent and received, HPACK dynamic table size changes and stream state
+//! transitions as `tracing` events under the `http2` target. Without the
+//! feature, these hooks compile to nothing.
+#![cfg_attr(not(feature = "tracing"), allow(unused_variables))]
+
+#[cfg(all(feature = "codec", feature = "tracing"))]
+use crate::debug::frame_type_name;
+#[cfg(feature = "codec")]
+use crate::frame::FrameHeader;
+#[cfg(feature = "sync")]
+use crate::stream::StreamState;
+
+/// Report a frame sent to the peer.
+///
+/// # Arguments
+///
+/// * `bytes` - The serialized frame.
+#[cfg(any(feature = "sync", feature = "async-tokio"))]
+pub(crate) fn frame_sent(bytes: &[u8]) {
+    #[cfg(feature = "tracing")]
+    if let Some(header_bytes) = bytes.first_chunk() {
+        frame("send", &FrameHeader::from_bytes(header_bytes));
+    }
+}
+
+/// Report a frame received from the peer.
+///
+/// # Arguments
+///
+/// * `frame_header` - The header of the received frame.
+#[cfg(feature = "codec")]
+pub(crate) fn frame_received(frame_header: &FrameHeader) {
+    #[cfg(feature = "tracing")]
+    frame("recv", frame_header);
+}
+
+/// Report a frame.
+#[cfg(all(feature = "codec", feature = "tracing"))]
+fn frame(direction: &'static str, frame_header: &FrameHeader) {
+    tracing::debug!(
+        target: "http2::frame",
+        direction,
+        frame_type = frame_type_name(frame_header.frame_type()),
+        stream_id = frame_header.stream_id(),
+        flags = frame_header.frame_flags(),
+        length = frame_header.payload_length(),
+        "frame"
+    );
+}
+
+/// Report a change of the HPACK dynamic table size.
+///
+/// # Arguments
+///
+/// * `size` - The size of the dynamic table.
+/// * `max_size` - The maximum size of the dynamic table.
+pub(crate) fn header_table_size(size: usize, max_size: usize) {
+    #[cfg(feature = "tracing")]
+    tracing::trace!(target: "http2::hpack", size, max_size, "dynamic table size");
+}
+
+/// Report a stream state transition.
+///
+/// # Arguments
+///
+/// * `stream_id` - The stream identifier.
+/// * `from` - The previous state of the stream.
+/// * `to` - The new state of the stream.
+#[cfg(feature = "sync")]
+pub(crate) fn stream_state(stream_id: u32, from: StreamState, to: StreamState) {
+    #[cfg(feature = "tracing")]
+    if from != to {
+        tracing::debug!(
+            target: "http2::stream",
+            stream_id,
+            from = ?from,
+            to = ?to,
+            "stream state"
+        );
+    }
+}
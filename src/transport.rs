@@ -0,0 +1,253 @@
+use std::collections::VecDeque;
+use std::io::{self, ErrorKind};
+use std::net::{Shutdown, TcpStream};
+#[cfg(unix)]
+use std::os::unix::net::UnixStream;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+/// Transport carrying the bytes of a connection.
+///
+/// The connection drivers only read, write, flush and shut down their
+/// transport, so that the same code runs over TCP streams, TLS streams,
+/// Unix sockets, in-memory [`DuplexStream`]s or tunnels. Reads block until
+/// bytes are available, and return 0 once the peer shut the stream down.
+pub trait ByteStream {
+    /// Read bytes into a buffer, returning the number of bytes read.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - The buffer to fill.
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize>;
+
+    /// Write bytes from a buffer, returning the number of bytes written.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - The bytes to write.
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize>;
+
+    /// Flush the bytes buffered by the transport.
+    fn flush(&mut self) -> io::Result<()>;
+
+    /// Shut the writing half of the transport down, the peer reading the
+    /// end of the stream once the bytes written are received.
+    fn shutdown(&mut self) -> io::Result<()>;
+
+    /// Write all the bytes of a buffer.
+    ///
+    /// # Arguments
+    ///
+    /// * `buf` - The bytes to write.
+    fn write_all(&mut self, mut buf: &[u8]) -> io::Result<()> {
+        while !buf.is_empty() {
+            match self.write(buf) {
+                Ok(0) => return Err(ErrorKind::WriteZero.into()),
+                Ok(written) => buf = &buf[written..],
+                Err(error) if error.kind() == ErrorKind::Interrupted => (),
+                Err(error) => return Err(error),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl ByteStream for TcpStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        io::Read::read(self, buf)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::Write::write(self, buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::Write::flush(self)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        TcpStream::shutdown(self, Shutdown::Write)
+    }
+}
+
+#[cfg(unix)]
+impl ByteStream for UnixStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        io::Read::read(self, buf)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        io::Write::write(self, buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        io::Write::flush(self)
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        UnixStream::shutdown(self, Shutdown::Write)
+    }
+}
+
+impl<T: ByteStream + ?Sized> ByteStream for &mut T {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        (**self).shutdown()
+    }
+}
+
+impl<T: ByteStream + ?Sized> ByteStream for Box<T> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        (**self).read(buf)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        (**self).write(buf)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        (**self).flush()
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        (**self).shutdown()
+    }
+}
+
+/// One direction of a [`DuplexStream`] pair.
+#[derive(Debug, Default)]
+struct Pipe {
+    buffer: VecDeque<u8>,
+    writer_closed: bool,
+    reader_closed: bool,
+}
+
+/// Shared state of one direction of a [`DuplexStream`] pair.
+type SharedPipe = Arc<(Mutex<Pipe>, Condvar)>;
+
+/// In-memory transport, one end of a pair created by [`duplex`].
+///
+/// The bytes written to an end are read from the other. Reads block until
+/// bytes are written, or time out after the read timeout, if any, with an
+/// error of kind `WouldBlock`. Once an end is shut down or dropped, the
+/// other end reads the end of the stream, and writing to a dropped end
+/// fails with an error of kind `BrokenPipe`.
+#[derive(Debug)]
+pub struct DuplexStream {
+    read_pipe: SharedPipe,
+    write_pipe: SharedPipe,
+    read_timeout: Option<Duration>,
+}
+
+/// Create a pair of connected in-memory transports.
+pub fn duplex() -> (DuplexStream, DuplexStream) {
+    let forward = SharedPipe::default();
+    let backward = SharedPipe::default();
+
+    (
+        DuplexStream {
+            read_pipe: backward.clone(),
+            write_pipe: forward.clone(),
+            read_timeout: None,
+        },
+        DuplexStream {
+            read_pipe: forward,
+            write_pipe: backward,
+            read_timeout: None,
+        },
+    )
+}
+
+impl DuplexStream {
+    /// Set the time a read waits for bytes, `None` to wait forever.
+    ///
+    /// # Arguments
+    ///
+    /// * `read_timeout` - The read timeout.
+    pub fn set_read_timeout(&mut self, read_timeout: Option<Duration>) {
+        self.read_timeout = read_timeout;
+    }
+
+    /// Get the time a read waits for bytes, if any.
+    pub fn read_timeout(&self) -> Option<Duration> {
+        self.read_timeout
+    }
+}
+
+impl ByteStream for DuplexStream {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        let (pipe, readable) = &*self.read_pipe;
+        let deadline = self.read_timeout.map(|timeout| Instant::now() + timeout);
+
+        let mut pipe = pipe.lock().unwrap();
+        while pipe.buffer.is_empty() && !pipe.writer_closed && !buf.is_empty() {
+            pipe = match deadline {
+                Some(deadline) => {
+                    let timeout = deadline.saturating_duration_since(Instant::now());
+                    if timeout.is_zero() {
+                        return Err(ErrorKind::WouldBlock.into());
+                    }
+                    readable.wait_timeout(pipe, timeout).unwrap().0
+                }
+                None => readable.wait(pipe).unwrap(),
+            };
+        }
+
+        let read = buf.len().min(pipe.buffer.len());
+        for (byte, buffered) in buf.iter_mut().zip(pipe.buffer.drain(..read)) {
+            *byte = buffered;
+        }
+
+        Ok(read)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let (pipe, readable) = &*self.write_pipe;
+
+        let mut pipe = pipe.lock().unwrap();
+        if pipe.writer_closed || pipe.reader_closed {
+            return Err(ErrorKind::BrokenPipe.into());
+        }
+        pipe.buffer.extend(buf);
+        readable.notify_all();
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        Ok(())
+    }
+
+    fn shutdown(&mut self) -> io::Result<()> {
+        let (pipe, readable) = &*self.write_pipe;
+        pipe.lock().unwrap().writer_closed = true;
+        readable.notify_all();
+
+        Ok(())
+    }
+}
+
+impl Drop for DuplexStream {
+    fn drop(&mut self) {
+        // A poisoned pipe is left as is, the other end panicked already.
+        if let Ok(mut pipe) = self.write_pipe.0.lock() {
+            pipe.writer_closed = true;
+            self.write_pipe.1.notify_all();
+        }
+        if let Ok(mut pipe) = self.read_pipe.0.lock() {
+            pipe.reader_closed = true;
+        }
+    }
+}
@@ -0,0 +1,365 @@
+use crate::error::Http2Error;
+use crate::frame::settings::SettingsParameter;
+use crate::stream::{manager::StreamManager, StreamState};
+
+/// Alphabet of the base64url encoding (RFC 4648 section 5).
+const BASE64URL_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+/// Response sent by a server accepting an upgrade to HTTP/2 over cleartext TCP.
+pub const SWITCHING_PROTOCOLS_RESPONSE: &[u8] =
+    b"HTTP/1.1 101 Switching Protocols\r\nConnection: Upgrade\r\nUpgrade: h2c\r\n\r\n";
+
+/// Serialize SETTINGS parameters into a `HTTP2-Settings` header value.
+///
+/// The value is the SETTINGS frame payload encoded with base64url,
+/// trailing '=' characters omitted.
+///
+/// # Arguments
+///
+/// * `settings_parameters` - The SETTINGS parameters to advertise.
+pub fn encode_http2_settings(settings_parameters: &[SettingsParameter]) -> String {
+    let mut payload: Vec<u8> = Vec::new();
+    for settings_parameter in settings_parameters {
+        settings_parameter.serialize_into(&mut payload);
+    }
+
+    let mut encoded = String::with_capacity(payload.len().div_ceil(3) * 4);
+    for chunk in payload.chunks(3) {
+        let mut group: u32 = 0;
+        for (i, byte) in chunk.iter().enumerate() {
+            group |= (*byte as u32) << (16 - 8 * i);
+        }
+
+        // A chunk of n bytes produces n + 1 characters.
+        for i in 0..=chunk.len() {
+            let index = (group >> (18 - 6 * i)) & 0x3F;
+            encoded.push(BASE64URL_ALPHABET[index as usize] as char);
+        }
+    }
+
+    encoded
+}
+
+/// Parse a `HTTP2-Settings` header value into SETTINGS parameters.
+///
+/// # Arguments
+///
+/// * `value` - The base64url encoded SETTINGS payload.
+pub fn decode_http2_settings(value: &str) -> Result<Vec<SettingsParameter>, Http2Error> {
+    // Padding is not expected but tolerated.
+    let value = value.trim().trim_end_matches('=');
+
+    let mut payload: Vec<u8> = Vec::with_capacity(value.len() * 3 / 4);
+    let mut group: u32 = 0;
+    let mut bits: u32 = 0;
+
+    for character in value.bytes() {
+        let sextet = match BASE64URL_ALPHABET.iter().position(|&c| c == character) {
+            Some(sextet) => sextet as u32,
+            None => {
+                return Err(Http2Error::UpgradeError(format!(
+                    "Invalid base64url character in HTTP2-Settings: {:?}",
+                    character as char
+                )))
+            }
+        };
+
+        group = (group << 6) | sextet;
+        bits += 6;
+
+        if bits >= 8 {
+            bits -= 8;
+            payload.push((group >> bits) as u8);
+            group &= (1 << bits) - 1;
+        }
+    }
+
+    if bits >= 6 || !payload.len().is_multiple_of(6) {
+        return Err(Http2Error::UpgradeError(format!(
+            "Invalid HTTP2-Settings length: {} bytes",
+            payload.len()
+        )));
+    }
+
+    let mut settings_parameters: Vec<SettingsParameter> = Vec::new();
+    for parameter in payload.chunks(6) {
+        let parameter_id = u16::from_be_bytes([parameter[0], parameter[1]]);
+        let parameter_value =
+            u32::from_be_bytes([parameter[2], parameter[3], parameter[4], parameter[5]]);
+        settings_parameters.push(SettingsParameter::deserialize(
+            parameter_id,
+            parameter_value,
+        )?);
+    }
+
+    Ok(settings_parameters)
+}
+
+/// Head of a HTTP/1.1 message: start line and header fields.
+#[derive(Debug, PartialEq)]
+struct MessageHead {
+    start_line: String,
+    headers: Vec<(String, String)>,
+    length: usize,
+}
+
+impl MessageHead {
+    /// Parse the head of a HTTP/1.1 message.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes starting with the message head.
+    fn parse(bytes: &[u8]) -> Result<Self, Http2Error> {
+        let length = match bytes.windows(4).position(|window| window == b"\r\n\r\n") {
+            Some(position) => position + 4,
+            None => {
+                return Err(Http2Error::NotEnoughBytes(
+                    "Incomplete HTTP/1.1 message head".to_string(),
+                ))
+            }
+        };
+
+        let head = std::str::from_utf8(&bytes[..length - 4]).map_err(|_| {
+            Http2Error::UpgradeError("HTTP/1.1 message head is not valid UTF-8".to_string())
+        })?;
+
+        let mut lines = head.split("\r\n");
+        let start_line = lines.next().unwrap_or_default().to_string();
+
+        let mut headers: Vec<(String, String)> = Vec::new();
+        for line in lines {
+            match line.split_once(':') {
+                Some((name, value)) => {
+                    headers.push((name.trim().to_ascii_lowercase(), value.trim().to_string()))
+                }
+                None => {
+                    return Err(Http2Error::UpgradeError(format!(
+                        "Invalid HTTP/1.1 header line: {:?}",
+                        line
+                    )))
+                }
+            }
+        }
+
+        Ok(MessageHead {
+            start_line,
+            headers,
+            length,
+        })
+    }
+
+    /// Get all the values of a header field.
+    fn values<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a str> {
+        self.headers
+            .iter()
+            .filter(move |(header_name, _)| header_name.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+
+    /// Check if a comma separated header field contains a token.
+    fn has_token(&self, name: &str, token: &str) -> bool {
+        self.values(name)
+            .flat_map(|value| value.split(','))
+            .any(|value| value.trim().eq_ignore_ascii_case(token))
+    }
+}
+
+/// Server side of a HTTP/1.1 `Upgrade: h2c` request.
+///
+/// The request that contained the upgrade is assigned stream 1 and is
+/// half-closed (remote) once the upgrade is accepted: the response to
+/// that request is sent as HTTP/2 frames.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ServerUpgrade {
+    method: String,
+    target: String,
+    headers: Vec<(String, String)>,
+    settings_parameters: Vec<SettingsParameter>,
+    length: usize,
+}
+
+impl ServerUpgrade {
+    /// Parse a HTTP/1.1 request asking for an upgrade to h2c.
+    ///
+    /// The request MUST contain exactly one `HTTP2-Settings` header field,
+    /// and both `Upgrade` and `HTTP2-Settings` as connection options.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes starting with the HTTP/1.1 request head.
+    pub fn from_request(bytes: &[u8]) -> Result<Self, Http2Error> {
+        let head = MessageHead::parse(bytes)?;
+
+        let mut start_line = head.start_line.split(' ');
+        let (method, target) = match (start_line.next(), start_line.next(), start_line.next()) {
+            (Some(method), Some(target), Some("HTTP/1.1")) => {
+                (method.to_string(), target.to_string())
+            }
+            _ => {
+                return Err(Http2Error::UpgradeError(format!(
+                    "Invalid HTTP/1.1 request line: {:?}",
+                    head.start_line
+                )))
+            }
+        };
+
+        if !head.has_token("upgrade", "h2c") {
+            return Err(Http2Error::UpgradeError(
+                "Request does not ask for an upgrade to h2c".to_string(),
+            ));
+        }
+
+        if !head.has_token("connection", "upgrade")
+            || !head.has_token("connection", "http2-settings")
+        {
+            return Err(Http2Error::UpgradeError(
+                "Connection header must contain Upgrade and HTTP2-Settings".to_string(),
+            ));
+        }
+
+        let values: Vec<&str> = head.values("http2-settings").collect();
+        let settings_parameters = match values[..] {
+            [value] => decode_http2_settings(value)?,
+            _ => {
+                return Err(Http2Error::UpgradeError(
+                    "Request must contain exactly one HTTP2-Settings header".to_string(),
+                ))
+            }
+        };
+
+        Ok(ServerUpgrade {
+            method,
+            target,
+            headers: head.headers,
+            settings_parameters,
+            length: head.length,
+        })
+    }
+
+    /// Accept the upgrade.
+    ///
+    /// Opens stream 1 in the half-closed (remote) state and returns the
+    /// `101 Switching Protocols` response to write before the server
+    /// connection preface.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream_manager` - The stream manager of the new HTTP/2 connection.
+    pub fn accept(&self, stream_manager: &mut StreamManager) -> &'static [u8] {
+        stream_manager.set_state(1, StreamState::HalfClosedRemote);
+        SWITCHING_PROTOCOLS_RESPONSE
+    }
+
+    pub fn method(&self) -> &str {
+        &self.method
+    }
+
+    pub fn target(&self) -> &str {
+        &self.target
+    }
+
+    pub fn headers(&self) -> &Vec<(String, String)> {
+        &self.headers
+    }
+
+    /// SETTINGS parameters sent by the client in the `HTTP2-Settings` header.
+    pub fn settings_parameters(&self) -> &Vec<SettingsParameter> {
+        &self.settings_parameters
+    }
+
+    /// Length of the request head, bytes after it belong to the request body.
+    pub fn length(&self) -> usize {
+        self.length
+    }
+}
+
+/// Client side of a HTTP/1.1 `Upgrade: h2c` request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientUpgrade {
+    method: String,
+    target: String,
+    host: String,
+    settings_parameters: Vec<SettingsParameter>,
+}
+
+impl ClientUpgrade {
+    /// Create a new upgrade request.
+    ///
+    /// # Arguments
+    ///
+    /// * `method` - The method of the request, typically GET or OPTIONS.
+    /// * `target` - The request target.
+    /// * `host` - The value of the Host header.
+    /// * `settings_parameters` - The SETTINGS parameters to advertise.
+    pub fn new(
+        method: &str,
+        target: &str,
+        host: &str,
+        settings_parameters: Vec<SettingsParameter>,
+    ) -> Self {
+        ClientUpgrade {
+            method: method.to_string(),
+            target: target.to_string(),
+            host: host.to_string(),
+            settings_parameters,
+        }
+    }
+
+    /// Serialize the HTTP/1.1 upgrade request.
+    pub fn request(&self) -> Vec<u8> {
+        format!(
+            "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: {}\r\n\r\n",
+            self.method,
+            self.target,
+            self.host,
+            encode_http2_settings(&self.settings_parameters)
+        )
+        .into_bytes()
+    }
+
+    /// Handle the HTTP/1.1 response to the upgrade request.
+    ///
+    /// Returns the length of the response head if the server switched to
+    /// h2c: the bytes following it are the server connection preface.
+    /// Stream 1 is then opened in the half-closed (local) state. Returns
+    /// `None` if the server ignored the upgrade, in which case the
+    /// response is a regular HTTP/1.1 response.
+    ///
+    /// # Arguments
+    ///
+    /// * `bytes` - The bytes starting with the HTTP/1.1 response head.
+    /// * `stream_manager` - The stream manager of the new HTTP/2 connection.
+    pub fn handle_response(
+        &self,
+        bytes: &[u8],
+        stream_manager: &mut StreamManager,
+    ) -> Result<Option<usize>, Http2Error> {
+        let head = MessageHead::parse(bytes)?;
+
+        let mut status_line = head.start_line.split(' ');
+        match (status_line.next(), status_line.next()) {
+            (Some("HTTP/1.1"), Some("101")) => (),
+            (Some("HTTP/1.1"), Some(_)) => return Ok(None),
+            _ => {
+                return Err(Http2Error::UpgradeError(format!(
+                    "Invalid HTTP/1.1 status line: {:?}",
+                    head.start_line
+                )))
+            }
+        }
+
+        if !head.has_token("upgrade", "h2c") {
+            return Err(Http2Error::UpgradeError(
+                "Server switched to a protocol other than h2c".to_string(),
+            ));
+        }
+
+        stream_manager.set_state(1, StreamState::HalfClosedLocal);
+        Ok(Some(head.length))
+    }
+
+    pub fn settings_parameters(&self) -> &Vec<SettingsParameter> {
+        &self.settings_parameters
+    }
+}
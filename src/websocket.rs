@@ -0,0 +1,119 @@
+use crate::error::{ErrorCode, Http2Error};
+use crate::header::list::HeaderList;
+
+/// Value of the `:protocol` pseudo-header field of a WebSocket handshake.
+pub const PROTOCOL: &str = "websocket";
+
+/// Version of the WebSocket protocol requested by a handshake.
+pub const VERSION: &str = "13";
+
+/// Build the extended CONNECT request opening a WebSocket (RFC 9220).
+///
+/// The request may only be sent once the server advertised
+/// SETTINGS_ENABLE_CONNECT_PROTOCOL. Subprotocols and extensions can be
+/// requested by appending `sec-websocket-protocol` and
+/// `sec-websocket-extensions` header fields.
+///
+/// # Arguments
+///
+/// * `scheme` - The scheme of the WebSocket URI, "https" for wss or "http"
+///   for ws.
+/// * `authority` - The authority of the WebSocket URI.
+/// * `path` - The path and query of the WebSocket URI.
+pub fn request(scheme: &str, authority: &str, path: &str) -> HeaderList {
+    let mut header_list = HeaderList::default();
+    header_list.append(":method", "CONNECT");
+    header_list.append(":protocol", PROTOCOL);
+    header_list.append(":scheme", scheme);
+    header_list.append(":authority", authority);
+    header_list.append(":path", path);
+    header_list.append("sec-websocket-version", VERSION);
+
+    header_list
+}
+
+/// Build the response accepting a WebSocket handshake.
+///
+/// The response must not end the stream, which then carries the
+/// WebSocket frames in both directions.
+pub fn response() -> HeaderList {
+    let mut header_list = HeaderList::default();
+    header_list.append(":status", "200");
+
+    header_list
+}
+
+/// Check whether a response accepts an extended CONNECT request.
+///
+/// Any 2xx status establishes the tunnel.
+///
+/// # Arguments
+///
+/// * `header_list` - The response header section.
+pub fn is_accepted(header_list: &HeaderList) -> bool {
+    matches!(header_list.status(), Some(200..=299))
+}
+
+/// Check that a request carrying a `:protocol` pseudo-header field is a
+/// well-formed extended CONNECT request (RFC 8441 section 4).
+///
+/// The `:method` must be CONNECT, and the `:scheme`, `:path` and
+/// `:authority` pseudo-header fields must be present. Requests without a
+/// `:protocol` pseudo-header field are not checked.
+///
+/// # Arguments
+///
+/// * `stream_id` - The stream of the request.
+/// * `header_list` - The request header section.
+pub fn check_extended_connect(stream_id: u32, header_list: &HeaderList) -> Result<(), Http2Error> {
+    if header_list.protocol().is_none() {
+        return Ok(());
+    }
+
+    if header_list.method() != Some("CONNECT") {
+        return Err(Http2Error::StreamError(
+            stream_id,
+            ErrorCode::ProtocolError,
+            "The :protocol pseudo-header field requires the CONNECT method".to_string(),
+        ));
+    }
+
+    for name in [":scheme", ":path", ":authority"] {
+        if !header_list.contains(name) {
+            return Err(Http2Error::StreamError(
+                stream_id,
+                ErrorCode::ProtocolError,
+                format!("Extended CONNECT request without {}", name),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Check that a request is a WebSocket handshake (RFC 9220 section 3).
+///
+/// # Arguments
+///
+/// * `stream_id` - The stream of the request.
+/// * `header_list` - The request header section.
+pub fn check_request(stream_id: u32, header_list: &HeaderList) -> Result<(), Http2Error> {
+    if header_list.protocol() != Some(PROTOCOL) {
+        return Err(Http2Error::StreamError(
+            stream_id,
+            ErrorCode::ProtocolError,
+            "Request is not a WebSocket handshake".to_string(),
+        ));
+    }
+    check_extended_connect(stream_id, header_list)?;
+
+    if header_list.get("sec-websocket-version") != Some(VERSION) {
+        return Err(Http2Error::StreamError(
+            stream_id,
+            ErrorCode::ProtocolError,
+            format!("Unsupported WebSocket version, expected {}", VERSION),
+        ));
+    }
+
+    Ok(())
+}
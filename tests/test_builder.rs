@@ -0,0 +1,257 @@
+#![cfg(feature = "sync")]
+
+use std::io::{Cursor, Read, Write};
+use std::time::Duration;
+
+use http2::connection::builder::{ConnectionBuilder, DEFAULT_GOAWAY_TIMEOUT, DEFAULT_PING_TIMEOUT};
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::headers::HeadersFrame;
+use http2::frame::ping::PingFrame;
+use http2::frame::settings::{SettingsFrame, SettingsParameter};
+use http2::frame::Frame;
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::start::HTTP2_CONNECTION_PREFACE_SEQUENCE;
+use http2::stream::Role;
+use http2::transport::ByteStream;
+
+/// In-memory transport replaying scripted input and recording output.
+struct MockIo {
+    input: Cursor<Vec<u8>>,
+    output: Vec<u8>,
+    writes: usize,
+}
+
+impl ByteStream for MockIo {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.input.read(buf)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.writes += 1;
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Empty SETTINGS frame sent by the peer.
+const EMPTY_SETTINGS: [u8; 9] = [0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00];
+
+#[test]
+pub fn test_builder_settings_frame() {
+    assert_eq!(ConnectionBuilder::client().role(), Role::Client);
+    assert_eq!(
+        ConnectionBuilder::client().settings_frame().unwrap(),
+        SettingsFrame::new()
+    );
+
+    let settings_frame = ConnectionBuilder::client()
+        .enable_push(false)
+        .max_concurrent_streams(100)
+        .initial_window_size(1 << 20)
+        .max_header_list_size(16_384)
+        .enable_connect_protocol(true)
+        .settings_frame()
+        .unwrap();
+    assert_eq!(
+        settings_frame,
+        SettingsFrame::new()
+            .with_parameter(SettingsParameter::EnablePush(0))
+            .with_parameter(SettingsParameter::MaxConcurrentStreams(100))
+            .with_parameter(SettingsParameter::InitialWindowSize(1 << 20))
+            .with_parameter(SettingsParameter::MaxHeaderListSize(16_384))
+            .with_parameter(SettingsParameter::EnableConnectProtocol(1))
+    );
+}
+
+#[test]
+pub fn test_builder_settings_frame_invalid() {
+    let cases = [
+        (
+            ConnectionBuilder::client().max_frame_size(1_000),
+            ErrorCode::ProtocolError,
+        ),
+        (
+            ConnectionBuilder::client().initial_window_size(1 << 31),
+            ErrorCode::FlowControlError,
+        ),
+        (
+            ConnectionBuilder::server().enable_push(true),
+            ErrorCode::ProtocolError,
+        ),
+    ];
+
+    for (builder, error_code) in cases {
+        match builder.settings_frame() {
+            Err(Http2Error::ConnectionError(code, _)) if code == error_code => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+
+        let io = MockIo {
+            input: Cursor::new(EMPTY_SETTINGS.to_vec()),
+            output: Vec::new(),
+            writes: 0,
+        };
+        assert!(builder.handshake(io).is_err());
+    }
+}
+
+#[test]
+pub fn test_builder_handshake_server() {
+    let mut input: Vec<u8> = HTTP2_CONNECTION_PREFACE_SEQUENCE.to_vec();
+    input.extend_from_slice(&EMPTY_SETTINGS);
+    let io = MockIo {
+        input: Cursor::new(input),
+        output: Vec::new(),
+        writes: 0,
+    };
+
+    let connection = ConnectionBuilder::server()
+        .max_concurrent_streams(1)
+        .max_frame_size(32_768)
+        .ping_interval(Duration::from_secs(30))
+        .handshake(io)
+        .unwrap();
+    assert_eq!(connection.role(), Role::Server);
+    assert_eq!(
+        connection.local_settings_parameters(),
+        &vec![
+            SettingsParameter::MaxConcurrentStreams(1),
+            SettingsParameter::MaxFrameSize(32_768),
+        ]
+    );
+    let local_settings = connection.local_settings();
+    assert_eq!(local_settings.max_concurrent_streams(), Some(1));
+    assert_eq!(local_settings.max_frame_size(), 32_768);
+    assert_eq!(local_settings.initial_window_size(), 65_535);
+    assert_eq!(local_settings.max_header_list_size(), None);
+    assert_eq!(connection.streams().local_max_concurrent_streams(), Some(1));
+    assert_eq!(connection.ping_interval(), Some(Duration::from_secs(30)));
+    assert_eq!(connection.ping_timeout(), DEFAULT_PING_TIMEOUT);
+    assert_eq!(connection.goaway_timeout(), DEFAULT_GOAWAY_TIMEOUT);
+
+    // SETTINGS, SETTINGS acknowledgement.
+    assert_eq!(
+        connection.into_inner().output,
+        vec![
+            0x00, 0x00, 0x0c, // Length = 12
+            0x04, // Frame Type = SETTINGS
+            0x00, // Flags = None
+            0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+            0x00, 0x03, 0x00, 0x00, 0x00, 0x01, // MAX_CONCURRENT_STREAMS = 1
+            0x00, 0x05, 0x00, 0x00, 0x80, 0x00, // MAX_FRAME_SIZE = 32768
+            0x00, 0x00, 0x00, // Length = 0
+            0x04, // Frame Type = SETTINGS
+            0x01, // Flags = ACK
+            0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        ]
+    );
+}
+
+#[test]
+pub fn test_builder_encoder_header_table_size() {
+    let io = MockIo {
+        input: Cursor::new(EMPTY_SETTINGS.to_vec()),
+        output: Vec::new(),
+        writes: 0,
+    };
+
+    let mut connection = ConnectionBuilder::client()
+        .encoder_header_table_size(0)
+        .handshake(io)
+        .unwrap();
+    let handshake_length = connection.get_mut().output.len();
+
+    let request = HeaderList::new(vec![HeaderField::new(":method".into(), "GET".into())]);
+    connection
+        .send_frame(&Frame::Headers(HeadersFrame::new(
+            1, true, true, None, request,
+        )))
+        .unwrap();
+
+    // The first header block shrinks the peer decoding table to 0.
+    assert_eq!(
+        connection.get_mut().output[handshake_length + 9..],
+        [
+            0x20, // Dynamic Table Size Update = 0
+            0x82, // :method: GET
+        ]
+    );
+}
+
+#[test]
+pub fn test_builder_max_write_batch() {
+    let io = MockIo {
+        input: Cursor::new(EMPTY_SETTINGS.to_vec()),
+        output: Vec::new(),
+        writes: 0,
+    };
+
+    let mut connection = ConnectionBuilder::client()
+        .max_write_batch(64)
+        .handshake(io)
+        .unwrap();
+    assert_eq!(connection.max_write_batch(), Some(64));
+
+    // The preface is written before reading, the SETTINGS acknowledgement
+    // once the handshake is complete.
+    assert_eq!(connection.get_mut().writes, 2);
+    let handshake_length = connection.get_mut().output.len();
+
+    // PING frames are 17 octets long: the fourth one fills the batch, and
+    // at most 3 frames fit in a write.
+    for opaque_data in 0..3 {
+        connection
+            .send_frame(&Frame::Ping(PingFrame::new([opaque_data; 8])))
+            .unwrap();
+    }
+    assert_eq!(connection.get_mut().output.len(), handshake_length);
+    connection
+        .send_frame(&Frame::Ping(PingFrame::new([3; 8])))
+        .unwrap();
+    assert_eq!(connection.get_mut().writes, 4);
+    assert_eq!(connection.get_mut().output.len(), handshake_length + 4 * 17);
+
+    // Frames left in a batch are written on flush.
+    connection
+        .send_frame(&Frame::Ping(PingFrame::new([4; 8])))
+        .unwrap();
+    assert_eq!(connection.get_mut().writes, 4);
+    connection.flush().unwrap();
+    assert_eq!(connection.get_mut().writes, 5);
+    assert_eq!(connection.get_mut().output.len(), handshake_length + 5 * 17);
+}
+
+#[test]
+pub fn test_builder_decoder_limits() {
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new("x-custom".into(), "a".repeat(100).into()),
+    ]);
+
+    for server in [
+        ConnectionBuilder::server().max_header_string_length(64),
+        ConnectionBuilder::server().max_header_fields(3),
+    ] {
+        let mut client = ConnectionBuilder::client().build().unwrap();
+        let mut server = server.build().unwrap();
+        server.handle_input(&client.poll_output()).unwrap();
+        client.handle_input(&server.poll_output()).unwrap();
+        server.handle_input(&client.poll_output()).unwrap();
+
+        client.send_request(request.clone(), true).unwrap();
+        match server.handle_input(&client.poll_output()) {
+            Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+}
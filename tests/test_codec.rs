@@ -0,0 +1,49 @@
+#![cfg(feature = "async-tokio")]
+
+use bytes::BytesMut;
+use http2::codec::FrameCodec;
+use tokio_util::codec::{Decoder, Encoder};
+
+#[test]
+pub fn test_frame_codec_partial_frames() {
+    let bytes: Vec<u8> = vec![
+        0x00, 0x00, 0x14, // Length = 20
+        0x01, // Frame Type = HEADERS
+        0x05, // Flags = [EndStream, EndHeaders]
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+        0x82, 0x86, 0x84, 0x41, 0x0f, 0x77, 0x77, 0x77, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c,
+        0x65, 0x2e, 0x63, 0x6f, 0x6d,
+        // Payload =
+        // :method: GET
+        // :scheme: http
+        // :path: /
+        // :authority: www.example.com
+        0x00, 0x00, 0x08, // Length = 8
+        0x06, // Frame Type = PING
+        0x00, // Flags = []
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, // Opaque Data
+    ];
+
+    let mut decoder = FrameCodec::default();
+    let mut encoder = FrameCodec::default();
+    let mut src = BytesMut::new();
+    let mut dst = BytesMut::new();
+
+    // Feed the bytes one at a time: frames are only produced when complete.
+    let mut frames = Vec::new();
+    for byte in &bytes {
+        src.extend_from_slice(&[*byte]);
+        while let Some(frame) = decoder.decode(&mut src).unwrap() {
+            frames.push(frame);
+        }
+    }
+    assert_eq!(frames.len(), 2);
+    assert!(src.is_empty());
+
+    // Encoding the decoded frames gives back the original bytes.
+    for frame in frames {
+        encoder.encode(frame, &mut dst).unwrap();
+    }
+    assert_eq!(dst.to_vec(), bytes);
+}
@@ -0,0 +1,359 @@
+#![cfg(feature = "test-support")]
+
+use std::io::{Cursor, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::Duration;
+
+use http2::connection::Connection;
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::data::DataFrame;
+use http2::frame::headers::HeadersFrame;
+use http2::frame::ping::PingFrame;
+use http2::frame::Frame;
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::header::table::HeaderTable;
+use http2::start::HTTP2_CONNECTION_PREFACE_SEQUENCE;
+use http2::stream::reset::ResetGuard;
+use http2::stream::{Role, StreamState};
+use http2::transport::ByteStream;
+
+/// In-memory transport replaying scripted input and recording output.
+struct MockIo {
+    input: Cursor<Vec<u8>>,
+    output: Vec<u8>,
+}
+
+impl ByteStream for MockIo {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        self.input.read(buf)
+    }
+
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.output.write(buf)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+
+    fn shutdown(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+pub fn test_handshake_prior_knowledge() {
+    let io = MockIo {
+        input: Cursor::new(vec![
+            0x00, 0x00, 0x06, // Length = 6
+            0x04, // Frame Type = SETTINGS
+            0x00, // Flags = None
+            0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+            0x00, 0x03, 0x00, 0x00, 0x00, 0x64, // MAX_CONCURRENT_STREAMS = 100
+        ]),
+        output: Vec::new(),
+    };
+
+    let connection = Connection::handshake_prior_knowledge(io).unwrap();
+    assert_eq!(connection.role(), Role::Client);
+    assert_eq!(
+        connection.peer_settings().max_concurrent_streams(),
+        Some(100)
+    );
+    assert_eq!(
+        connection.streams().remote_max_concurrent_streams(),
+        Some(100)
+    );
+
+    // Preface, empty SETTINGS, SETTINGS acknowledgement.
+    let mut expected: Vec<u8> = HTTP2_CONNECTION_PREFACE_SEQUENCE.to_vec();
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    expected.extend_from_slice(&[0x00, 0x00, 0x00, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00]);
+    assert_eq!(connection.into_inner().output, expected);
+}
+
+#[test]
+pub fn test_handshake_prior_knowledge_invalid() {
+    // The server starts with a PING frame.
+    let io = MockIo {
+        input: Cursor::new(vec![
+            0x00, 0x00, 0x08, // Length = 8
+            0x06, // Frame Type = PING
+            0x00, // Flags = None
+            0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+            0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, // Opaque Data
+        ]),
+        output: Vec::new(),
+    };
+    assert!(matches!(
+        Connection::handshake_prior_knowledge(io),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+
+    // The server SETTINGS carry an illegal value.
+    let io = MockIo {
+        input: Cursor::new(vec![
+            0x00, 0x00, 0x06, // Length = 6
+            0x04, // Frame Type = SETTINGS
+            0x00, // Flags = None
+            0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+            0x00, 0x02, 0x00, 0x00, 0x00, 0x02, // ENABLE_PUSH = 2
+        ]),
+        output: Vec::new(),
+    };
+    assert!(matches!(
+        Connection::handshake_prior_knowledge(io),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+
+    // The server closes the connection.
+    let io = MockIo {
+        input: Cursor::new(vec![]),
+        output: Vec::new(),
+    };
+    assert!(matches!(
+        Connection::handshake_prior_knowledge(io),
+        Err(Http2Error::IoError(_))
+    ));
+}
+
+#[test]
+pub fn test_prior_knowledge_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let connection = Connection::accept_prior_knowledge(io).unwrap();
+        assert_eq!(connection.role(), Role::Server);
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let connection = Connection::handshake_prior_knowledge(io).unwrap();
+    assert_eq!(connection.role(), Role::Client);
+
+    server.join().unwrap();
+}
+
+#[test]
+pub fn test_recv_frame_rapid_reset() {
+    let mut input: Vec<u8> = HTTP2_CONNECTION_PREFACE_SEQUENCE.to_vec();
+    input.extend_from_slice(&[0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    for stream_id in [1u8, 3] {
+        input.extend_from_slice(&[
+            0x00, 0x00, 0x04, // Length = 4
+            0x03, // Frame Type = RST_STREAM
+            0x00, // Flags = None
+            0x00, 0x00, 0x00, stream_id, // Stream Identifier
+            0x00, 0x00, 0x00, 0x08, // Error Code = CANCEL
+        ]);
+    }
+    let io = MockIo {
+        input: Cursor::new(input),
+        output: Vec::new(),
+    };
+
+    let mut connection = Connection::accept_prior_knowledge(io).unwrap();
+    connection
+        .streams_mut()
+        .set_reset_guard(Some(ResetGuard::new(1, Duration::from_secs(60))));
+
+    connection.streams_mut().open_remote(1).unwrap();
+    assert!(matches!(connection.recv_frame(), Ok(Frame::RstStream(_))));
+    assert_eq!(
+        connection.streams().get(1).unwrap().state(),
+        StreamState::Closed
+    );
+
+    connection.streams_mut().open_remote(3).unwrap();
+    assert!(matches!(
+        connection.recv_frame(),
+        Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _))
+    ));
+}
+
+#[test]
+pub fn test_trailers_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let trailers = HeaderList::new(vec![HeaderField::new("grpc-status".into(), "0".into())]);
+
+    let expected = trailers.clone();
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let mut connection = Connection::accept_prior_knowledge(io).unwrap();
+
+        // SETTINGS acknowledgement, request headers, then trailers.
+        assert!(matches!(connection.recv_frame(), Ok(Frame::Settings(_))));
+        assert!(matches!(connection.recv_frame(), Ok(Frame::Headers(_))));
+        assert_eq!(connection.recv_trailers(1), None);
+        assert!(matches!(connection.recv_frame(), Ok(Frame::Headers(_))));
+        assert_eq!(connection.recv_trailers(1), Some(expected));
+        assert_eq!(
+            connection.streams().get(1).unwrap().state(),
+            StreamState::HalfClosedRemote
+        );
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let mut connection = Connection::handshake_prior_knowledge(io).unwrap();
+    connection.streams_mut().open_local(1).unwrap();
+    let request = HeaderList::new(vec![HeaderField::new(":method".into(), "POST".into())]);
+    connection
+        .send_frame(&Frame::Headers(HeadersFrame::new(
+            1, false, true, None, request,
+        )))
+        .unwrap();
+    connection.send_trailers(1, trailers).unwrap();
+    assert_eq!(
+        connection.streams().get(1).unwrap().state(),
+        StreamState::HalfClosedLocal
+    );
+
+    server.join().unwrap();
+}
+
+#[test]
+pub fn test_connection_stats() {
+    let mut input: Vec<u8> = vec![
+        0x00, 0x00, 0x00, // Length = 0
+        0x04, // Frame Type = SETTINGS
+        0x00, // Flags = None
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+    ];
+    input.extend_from_slice(&[
+        0x00, 0x00, 0x08, // Length = 8
+        0x06, // Frame Type = PING
+        0x01, // Flags = ACK
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, // Opaque Data
+    ]);
+    input.extend_from_slice(&[
+        0x00, 0x00, 0x04, // Length = 4
+        0x08, // Frame Type = WINDOW_UPDATE
+        0x00, // Flags = None
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x00, 0x00, 0x03, 0xe8, // Window Size Increment = 1000
+    ]);
+    let io = MockIo {
+        input: Cursor::new(input),
+        output: Vec::new(),
+    };
+
+    let mut connection = Connection::handshake_prior_knowledge(io).unwrap();
+    let stats = connection.stats();
+    assert_eq!(stats.bytes_sent(), 24 + 9 + 9);
+    assert_eq!(stats.frames_sent().get("SETTINGS"), Some(&2));
+    assert_eq!(stats.frames_received().get("SETTINGS"), Some(&1));
+    assert_eq!(stats.send_window(), 65_535);
+    assert_eq!(stats.recv_window(), 65_535);
+    assert_eq!(stats.ping_rtt(), None);
+
+    // A PING acknowledgement measures the round-trip time.
+    let ping = PingFrame::new([0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08]);
+    connection.send_frame(&Frame::Ping(ping)).unwrap();
+    assert!(matches!(connection.recv_frame(), Ok(Frame::Ping(_))));
+    assert!(connection.stats().ping_rtt().is_some());
+
+    // DATA consumes the send window, WINDOW_UPDATE replenishes it.
+    connection.streams_mut().open_local(1).unwrap();
+    connection
+        .send_frame(&Frame::Data(DataFrame::new(1, false, vec![0; 500])))
+        .unwrap();
+    assert_eq!(connection.stats().send_window(), 65_035);
+    assert!(matches!(
+        connection.recv_frame(),
+        Ok(Frame::WindowUpdate(_))
+    ));
+
+    let stats = connection.stats();
+    assert_eq!(stats.send_window(), 66_035);
+    assert_eq!(stats.local_active_streams(), 1);
+    assert_eq!(stats.frames_sent().get("DATA"), Some(&1));
+    assert_eq!(stats.frames_received().get("PING"), Some(&1));
+    assert_eq!(stats.frames_received().get("WINDOW_UPDATE"), Some(&1));
+    assert_eq!(stats.bytes_received(), 9 + 17 + 13);
+    assert_eq!(stats.bytes_sent(), 24 + 9 + 9 + 17 + 509);
+}
+
+#[test]
+pub fn test_connection_ping() {
+    let mut input: Vec<u8> = vec![
+        0x00, 0x00, 0x00, // Length = 0
+        0x04, // Frame Type = SETTINGS
+        0x00, // Flags = None
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+    ];
+    let ping: Vec<u8> = vec![
+        0x00, 0x00, 0x08, // Length = 8
+        0x06, // Frame Type = PING
+        0x00, // Flags = None
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, // Opaque Data
+    ];
+    let mut ping_ack = ping.clone();
+    ping_ack[4] = 0x01; // Flags = ACK
+    input.extend_from_slice(&ping);
+    input.extend_from_slice(&ping_ack);
+    let io = MockIo {
+        input: Cursor::new(input),
+        output: Vec::new(),
+    };
+
+    let mut connection = Connection::handshake_prior_knowledge(io).unwrap();
+    let handshake_length = connection.get_mut().output.len();
+
+    // A PING is acknowledged with the same opaque data.
+    assert!(matches!(
+        connection.recv_frame(),
+        Ok(Frame::Ping(ping_frame)) if !ping_frame.is_ack()
+    ));
+    assert_eq!(connection.get_mut().output[handshake_length..], ping_ack);
+
+    // A PING acknowledgement is not acknowledged.
+    assert!(matches!(
+        connection.recv_frame(),
+        Ok(Frame::Ping(ping_frame)) if ping_frame.is_ack()
+    ));
+    assert_eq!(
+        connection.get_mut().output.len(),
+        handshake_length + ping_ack.len()
+    );
+}
+
+#[test]
+pub fn test_connection_stream_ids() {
+    let mut input: Vec<u8> = HTTP2_CONNECTION_PREFACE_SEQUENCE.to_vec();
+    input.extend_from_slice(&[0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    let mut header_table = HeaderTable::new(4096);
+    let request = HeaderList::new(vec![HeaderField::new(":method".into(), "POST".into())]);
+    let trailers = HeaderList::new(vec![HeaderField::new("grpc-status".into(), "0".into())]);
+    for (stream_id, end_stream, header_list) in [
+        (3, false, &request),
+        (3, true, &trailers),
+        (1, false, &request),
+    ] {
+        let frame = HeadersFrame::new(stream_id, end_stream, true, None, header_list.clone());
+        input.extend(Frame::Headers(frame).serialize(&mut header_table).unwrap());
+    }
+    let io = MockIo {
+        input: Cursor::new(input),
+        output: Vec::new(),
+    };
+
+    let mut connection = Connection::accept_prior_knowledge(io).unwrap();
+    assert_eq!(connection.next_stream_id().unwrap(), 2);
+    assert_eq!(connection.next_stream_id().unwrap(), 4);
+
+    // Stream 3 is opened, then receives its trailers.
+    assert!(matches!(connection.recv_frame(), Ok(Frame::Headers(_))));
+    assert!(matches!(connection.recv_frame(), Ok(Frame::Headers(_))));
+
+    // Stream 1 cannot be opened after stream 3.
+    assert!(matches!(
+        connection.recv_frame(),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+}
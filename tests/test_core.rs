@@ -0,0 +1,1656 @@
+#![cfg(feature = "test-support")]
+
+use std::time::Duration;
+
+use http2::connection::builder::ConnectionBuilder;
+use http2::connection::clock::{Clock, ManualClock};
+use http2::connection::core::Event;
+use http2::connection::settings::Settings;
+use http2::error::{ErrorCode, FlowControlError, FlowControlScope, Http2Error};
+use http2::flow_control::WindowUpdateStrategy;
+use http2::frame::data::DataFrame;
+use http2::frame::go_away::GoAwayFrame;
+use http2::frame::headers::HeadersFrame;
+use http2::frame::ping::PingFrame;
+use http2::frame::push_promise::PushPromiseFrame;
+use http2::frame::settings::{SettingsFrame, SettingsParameter};
+use http2::frame::{Frame, FrameHeader, FRAME_HEADER_LENGTH};
+use http2::header::field::HeaderField;
+use http2::header::limits::EncoderLimits;
+use http2::header::list::HeaderList;
+use http2::header::table::{HeaderTable, DEFAULT_HEADER_TABLE_SIZE};
+use http2::start::HTTP2_CONNECTION_PREFACE_SEQUENCE;
+use http2::stream::{StreamState, DEFAULT_RECV_BUFFER_LIMIT};
+use http2::test_support::{establish, transfer};
+
+#[test]
+pub fn test_core_handshake() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .max_concurrent_streams(10)
+        .build()
+        .unwrap();
+    assert!(!client.is_established());
+    assert!(!server.is_established());
+    assert_eq!(client.peer_settings(), Settings::new());
+    assert_eq!(server.local_settings().max_concurrent_streams(), Some(10));
+
+    // The client preface starts with the connection preface sequence.
+    let output = client.poll_output();
+    assert_eq!(output[..24], *HTTP2_CONNECTION_PREFACE_SEQUENCE);
+    assert!(!client.has_output());
+
+    // The preface may be split anywhere.
+    assert_eq!(server.handle_input(&output[..10]).unwrap(), vec![]);
+    assert_eq!(server.handle_input(&output[10..]).unwrap(), vec![]);
+    assert!(server.is_established());
+
+    // Server SETTINGS and acknowledgement of the client SETTINGS.
+    assert_eq!(transfer(&mut server, &mut client), vec![]);
+    assert!(client.is_established());
+    assert_eq!(client.peer_settings().max_concurrent_streams(), Some(10));
+    // Parameters never sent keep their initial value.
+    assert_eq!(client.peer_settings().max_frame_size(), 16_384);
+    assert_eq!(client.peer_settings().max_header_list_size(), None);
+    assert!(client.has_output());
+
+    // Acknowledgement of the server SETTINGS.
+    assert_eq!(transfer(&mut client, &mut server), vec![]);
+    assert!(!server.has_output());
+}
+
+#[test]
+pub fn test_core_exchange() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let body = vec![0x2a; 70_000];
+
+    // The body exceeds the initial flow-control windows.
+    let stream_id = client.send_request(request.clone(), false).unwrap();
+    assert_eq!(stream_id, 1);
+    assert_eq!(client.send_data(stream_id, &body, true).unwrap(), 65_535);
+    let events = transfer(&mut client, &mut server);
+    assert_eq!(
+        events[0],
+        Event::Headers {
+            stream_id: 1,
+            header_list: request,
+            end_stream: false,
+        }
+    );
+    assert_eq!(events.len(), 5);
+    assert!(events[1..].iter().all(|event| matches!(
+        event,
+        Event::Data {
+            end_stream: false,
+            ..
+        }
+    )));
+    assert_eq!(server.recv_data(1).unwrap(), body[..65_535]);
+
+    // The WINDOW_UPDATE frames of the server let the client finish.
+    assert_eq!(transfer(&mut server, &mut client), vec![]);
+    assert_eq!(
+        client.send_data(stream_id, &body[65_535..], true).unwrap(),
+        4_465
+    );
+    assert_eq!(
+        transfer(&mut client, &mut server),
+        vec![Event::Data {
+            stream_id: 1,
+            length: 4_465,
+            end_stream: true,
+        }]
+    );
+    assert_eq!(server.recv_data(1).unwrap(), body[65_535..]);
+    assert_eq!(server.recv_data(1).unwrap(), vec![]);
+    assert_eq!(client.stats().encoder_hpack_stats().header_fields(), 3);
+    assert_eq!(server.stats().decoder_hpack_stats().header_fields(), 3);
+
+    // Sending on an ended stream fails.
+    assert!(matches!(
+        client.send_data(stream_id, &body, true),
+        Err(Http2Error::StreamError(1, ErrorCode::StreamClosed, _))
+    ));
+
+    let response = HeaderList::new(vec![HeaderField::new(":status".into(), "204".into())]);
+    server.send_response(1, response.clone(), true).unwrap();
+    let events = transfer(&mut server, &mut client);
+    assert_eq!(
+        events,
+        vec![Event::Headers {
+            stream_id: 1,
+            header_list: response,
+            end_stream: true,
+        }]
+    );
+}
+
+#[test]
+pub fn test_core_recv_buffer_limit() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .recv_buffer_limit(10_000)
+        .connection_recv_buffer_limit(40_000)
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+    assert_eq!(server.streams().recv_buffer_limit(), 10_000);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let body = vec![0x2a; 100_000];
+    let stream_id = client.send_request(request, false).unwrap();
+    assert_eq!(client.send_data(stream_id, &body, true).unwrap(), 65_535);
+    transfer(&mut client, &mut server);
+
+    // The stream buffer exceeds its limit from the first DATA frame, the
+    // connection buffers from the third one. The credit of the first two
+    // frames is sent in a single WINDOW_UPDATE frame.
+    let stream = server.streams().get(1).unwrap();
+    assert_eq!(stream.recv_buffered(), 65_535);
+    assert_eq!(stream.recv_buffer_limit(), 10_000);
+    assert_eq!(server.stats().frames_sent().get("WINDOW_UPDATE"), Some(&1));
+    transfer(&mut server, &mut client);
+    assert_eq!(
+        client.send_data(stream_id, &body[65_535..], true).unwrap(),
+        0
+    );
+
+    // Reading the buffer releases the withheld windows.
+    assert_eq!(server.recv_data(1).unwrap().len(), 65_535);
+    transfer(&mut server, &mut client);
+    assert_eq!(
+        client.send_data(stream_id, &body[65_535..], true).unwrap(),
+        34_465
+    );
+
+    // The limit of a stream can be raised.
+    let stream = server.streams_mut().get_mut(1).unwrap();
+    stream.set_recv_buffer_limit(DEFAULT_RECV_BUFFER_LIMIT);
+    assert_eq!(stream.recv_buffer_limit(), DEFAULT_RECV_BUFFER_LIMIT);
+}
+
+#[test]
+pub fn test_core_stream_recv_window() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    // The buffered content exceeds the limit, no WINDOW_UPDATE is sent.
+    let mut server = ConnectionBuilder::server()
+        .initial_window_size(1_000)
+        .recv_buffer_limit(1_000)
+        .build()
+        .unwrap();
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request, false).unwrap();
+    assert_eq!(
+        client.send_data(stream_id, &[0x2a; 2_000], false).unwrap(),
+        2_000
+    );
+
+    // Until the server SETTINGS frame is acknowledged, the stream window
+    // keeps the initial value.
+    let events = transfer(&mut client, &mut server);
+    assert!(matches!(
+        events[..],
+        [
+            Event::Headers { stream_id: 1, .. },
+            Event::Data {
+                stream_id: 1,
+                length: 2_000,
+                ..
+            }
+        ]
+    ));
+
+    // The send window of the client is reduced right away.
+    transfer(&mut server, &mut client);
+    assert_eq!(client.send_data(stream_id, b"late", false).unwrap(), 0);
+
+    // The receive window of the server is reduced by the acknowledgement,
+    // the DATA frames beyond it are a stream error.
+    transfer(&mut client, &mut server);
+    client
+        .send_frame(&Frame::Data(DataFrame::new(
+            stream_id,
+            false,
+            b"late".to_vec(),
+        )))
+        .unwrap();
+    match server.handle_input(&client.poll_output()) {
+        Err(Http2Error::FlowControlError(FlowControlError::WindowExceeded(
+            FlowControlScope::Stream(1),
+            4,
+            -1_000,
+        ))) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // The scope of the error resets the stream, keeping the connection.
+    let events = transfer(&mut server, &mut client);
+    assert_eq!(
+        events,
+        vec![Event::Reset {
+            stream_id: 1,
+            error_code: ErrorCode::FlowControlError,
+        }]
+    );
+}
+
+#[test]
+pub fn test_core_padded_data() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request, false).unwrap();
+
+    // The Pad Length octet and the padding count toward flow control.
+    let mut data_frame = DataFrame::new(stream_id, false, b"Hello".to_vec());
+    data_frame.pad_length = Some(10);
+    client.send_frame(&Frame::Data(data_frame)).unwrap();
+    assert_eq!(client.send_window().size(), 65_535 - 16);
+
+    let events = transfer(&mut client, &mut server);
+    assert!(matches!(
+        events[..],
+        [
+            Event::Headers { stream_id: 1, .. },
+            Event::Data {
+                stream_id: 1,
+                length: 5,
+                end_stream: false,
+            }
+        ]
+    ));
+
+    // The whole frame payload is replenished.
+    transfer(&mut server, &mut client);
+    assert_eq!(client.send_window().size(), 65_535);
+}
+
+#[test]
+pub fn test_core_empty_end_stream_data() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    // The buffered content exceeds the limit, the stream window is not
+    // replenished.
+    let mut server = ConnectionBuilder::server()
+        .recv_buffer_limit(1_000)
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request, false).unwrap();
+    assert_eq!(
+        client.send_data(stream_id, &[0x2a; 65_535], false).unwrap(),
+        65_535
+    );
+    transfer(&mut client, &mut server);
+    transfer(&mut server, &mut client);
+    assert_eq!(client.send_data(stream_id, b"late", false).unwrap(), 0);
+
+    // An empty DATA frame ends the stream although the windows are
+    // exhausted.
+    assert_eq!(client.send_data(stream_id, &[], true).unwrap(), 0);
+    let events = transfer(&mut client, &mut server);
+    assert_eq!(
+        events,
+        vec![Event::Data {
+            stream_id: 1,
+            length: 0,
+            end_stream: true,
+        }]
+    );
+}
+
+#[test]
+pub fn test_core_window_update_strategy() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .window_update_strategy(WindowUpdateStrategy::Threshold(50))
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let body = vec![0x2a; 16_384];
+    let stream_id = client.send_request(request, false).unwrap();
+
+    // The windows are replenished once half of them is consumed.
+    client.send_data(stream_id, &body, false).unwrap();
+    transfer(&mut client, &mut server);
+    server.recv_data(stream_id).unwrap();
+    assert_eq!(server.stats().frames_sent().get("WINDOW_UPDATE"), None);
+
+    client.send_data(stream_id, &body, false).unwrap();
+    transfer(&mut client, &mut server);
+    assert_eq!(server.stats().frames_sent().get("WINDOW_UPDATE"), Some(&2));
+    transfer(&mut server, &mut client);
+    assert_eq!(client.send_window().size(), 65_535);
+}
+
+#[test]
+pub fn test_core_window_update_coalescing() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request, false).unwrap();
+
+    // Many small DATA frames received at once.
+    for _ in 0..50 {
+        client.send_data(stream_id, &[0x2a; 100], false).unwrap();
+    }
+    assert_eq!(transfer(&mut client, &mut server).len(), 51);
+    assert_eq!(server.stats().frames_sent().get("WINDOW_UPDATE"), Some(&2));
+
+    // DATA frames read one event at a time are coalesced until the output
+    // is polled.
+    for _ in 0..50 {
+        client.send_data(stream_id, &[0x2a; 100], false).unwrap();
+    }
+    server.feed(&client.poll_output());
+    while server.next_event().unwrap().is_some() {}
+    assert!(server.has_output());
+    assert_eq!(server.stats().frames_sent().get("WINDOW_UPDATE"), Some(&2));
+    assert_eq!(transfer(&mut server, &mut client), vec![]);
+    assert_eq!(server.stats().frames_sent().get("WINDOW_UPDATE"), Some(&4));
+    assert_eq!(client.send_window().size(), 65_535);
+}
+
+#[test]
+pub fn test_core_window_update_bdp() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .window_update_strategy(WindowUpdateStrategy::Bdp)
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let body = vec![0x2a; 100_000];
+    let stream_id = client.send_request(request, false).unwrap();
+    assert_eq!(client.send_data(stream_id, &body, true).unwrap(), 65_535);
+
+    // The first DATA frame is sent along a PING, acknowledged by the
+    // client after the whole window is received.
+    transfer(&mut client, &mut server);
+    assert_eq!(server.stats().frames_sent().get("PING"), Some(&1));
+    transfer(&mut server, &mut client);
+    transfer(&mut client, &mut server);
+
+    // The windows are doubled.
+    assert!(server
+        .local_settings_parameters()
+        .contains(&SettingsParameter::InitialWindowSize(131_070)));
+    assert_eq!(server.local_settings().initial_window_size(), 131_070);
+    assert_eq!(server.stats().recv_window(), 131_070 - 32_767);
+    transfer(&mut server, &mut client);
+    assert_eq!(client.peer_settings().initial_window_size(), 131_070);
+    assert_eq!(
+        client.send_data(stream_id, &body[65_535..], true).unwrap(),
+        34_465
+    );
+}
+
+#[test]
+pub fn test_core_invalid_preface() {
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    let mut input = b"GET / HTTP/1.1\r\nHost: a\r\n\r\n".to_vec();
+    input.extend_from_slice(&[0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]);
+
+    match server.handle_input(&input) {
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_core_keep_alive() {
+    let clock = ManualClock::new();
+    let mut client = ConnectionBuilder::client()
+        .clock(clock.clone())
+        .ping_interval(Duration::from_secs(10))
+        .ping_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+    let start = clock.now();
+    assert_eq!(client.now(), start);
+    assert_eq!(client.ping_timeout(), Duration::from_secs(5));
+    assert_eq!(server.poll_timeout(), None);
+
+    // A PING is sent once the server was silent for the interval.
+    assert_eq!(client.poll_timeout(), Some(start + Duration::from_secs(10)));
+    client.handle_timeouts(client.now()).unwrap();
+    assert!(!client.has_output());
+    clock.advance(Duration::from_secs(10));
+    client.handle_timeouts(client.now()).unwrap();
+    assert_eq!(client.stats().frames_sent().get("PING"), Some(&1));
+    assert_eq!(client.poll_timeout(), Some(start + Duration::from_secs(15)));
+
+    // The acknowledgement postpones the next PING.
+    clock.advance(Duration::from_secs(1));
+    transfer(&mut client, &mut server);
+    transfer(&mut server, &mut client);
+    assert_eq!(client.poll_timeout(), Some(start + Duration::from_secs(21)));
+    assert_eq!(client.stats().ping_rtt(), Some(Duration::from_secs(1)));
+
+    // The connection is closed if the next PING is not acknowledged.
+    clock.advance(Duration::from_secs(10));
+    client.handle_timeouts(client.now()).unwrap();
+    assert_eq!(client.stats().frames_sent().get("PING"), Some(&2));
+    clock.advance(Duration::from_secs(5));
+    match client.handle_timeouts(client.now()) {
+        Err(Http2Error::ConnectionError(ErrorCode::NoError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    let events = transfer(&mut client, &mut server);
+    assert_eq!(
+        events.last(),
+        Some(&Event::GoAway {
+            last_stream_id: 0,
+            error_code: ErrorCode::NoError,
+            retryable_streams: Vec::new(),
+        })
+    );
+}
+
+#[test]
+pub fn test_core_ping_rtt() {
+    let clock = ManualClock::new();
+    let mut client = ConnectionBuilder::client()
+        .clock(clock.clone())
+        .ping_interval(Duration::from_secs(10))
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    client
+        .send_frame(&Frame::Ping(PingFrame::new([1, 2, 3, 4, 5, 6, 7, 8])))
+        .unwrap();
+    transfer(&mut client, &mut server);
+    let ping_ack = server.poll_output();
+
+    // A keep-alive PING sent before the acknowledgement does not replace
+    // the PING of the application.
+    clock.advance(Duration::from_secs(10));
+    client.handle_timeouts(client.now()).unwrap();
+    assert_eq!(client.stats().frames_sent().get("PING"), Some(&2));
+    clock.advance(Duration::from_secs(1));
+    client.handle_input(&ping_ack).unwrap();
+    assert_eq!(client.stats().ping_rtt(), Some(Duration::from_secs(11)));
+
+    // The keep-alive PING is timed on its own.
+    transfer(&mut client, &mut server);
+    clock.advance(Duration::from_secs(2));
+    transfer(&mut server, &mut client);
+    assert_eq!(client.stats().ping_rtt(), Some(Duration::from_secs(3)));
+}
+
+#[test]
+pub fn test_core_settings_timeout() {
+    let clock = ManualClock::new();
+    let mut client = ConnectionBuilder::client()
+        .clock(clock.clone())
+        .settings_timeout(Duration::from_secs(10))
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    assert_eq!(client.settings_timeout(), Some(Duration::from_secs(10)));
+    assert_eq!(server.settings_timeout(), None);
+    assert_eq!(
+        client.poll_timeout(),
+        Some(clock.now() + Duration::from_secs(10))
+    );
+
+    // The acknowledgement of the preface SETTINGS frame clears the deadline.
+    establish(&mut client, &mut server);
+    assert_eq!(client.poll_timeout(), None);
+
+    // A later SETTINGS frame must be acknowledged in time as well.
+    client
+        .send_frame(&Frame::Settings(
+            SettingsFrame::new().with_parameter(SettingsParameter::MaxConcurrentStreams(10)),
+        ))
+        .unwrap();
+    client.poll_output();
+    clock.advance(Duration::from_secs(9));
+    assert!(client.handle_timeouts(client.now()).unwrap().is_empty());
+    clock.advance(Duration::from_secs(1));
+    match client.handle_timeouts(client.now()) {
+        Err(Http2Error::ConnectionError(ErrorCode::SettingsTimeout, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    let events = transfer(&mut client, &mut server);
+    assert_eq!(
+        events.last(),
+        Some(&Event::GoAway {
+            last_stream_id: 0,
+            error_code: ErrorCode::SettingsTimeout,
+            retryable_streams: Vec::new(),
+        })
+    );
+}
+
+#[test]
+pub fn test_core_go_away_timeout() {
+    let clock = ManualClock::new();
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .clock(clock.clone())
+        .goaway_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    client.send_request(request, false).unwrap();
+    transfer(&mut client, &mut server);
+    assert_eq!(server.poll_timeout(), None);
+
+    // The request in flight has the GOAWAY timeout to complete.
+    let start = clock.now();
+    server
+        .send_frame(&Frame::GoAway(GoAwayFrame::new(
+            1,
+            ErrorCode::NoError,
+            Vec::new(),
+        )))
+        .unwrap();
+    assert_eq!(server.poll_timeout(), Some(start + Duration::from_secs(5)));
+    clock.advance(Duration::from_secs(4));
+    assert!(server.handle_timeouts(server.now()).unwrap().is_empty());
+    clock.advance(Duration::from_secs(1));
+    match server.handle_timeouts(server.now()) {
+        Err(Http2Error::ConnectionError(ErrorCode::NoError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // Without streams in flight, there is nothing to wait for.
+    server.send_reset(1, ErrorCode::Cancel).unwrap();
+    assert_eq!(server.poll_timeout(), None);
+    assert!(server.handle_timeouts(server.now()).unwrap().is_empty());
+}
+
+#[test]
+pub fn test_core_stream_error_reset() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new("content-length".into(), "4".into()),
+    ]);
+
+    // The content exceeds the content-length of the first request.
+    let stream_id = client.send_request(request.clone(), false).unwrap();
+    client.send_data(stream_id, b"too long", false).unwrap();
+    client.send_data(stream_id, b"too long", false).unwrap();
+    let other_stream_id = client.send_request(request, false).unwrap();
+    let events = transfer(&mut client, &mut server);
+    assert!(matches!(events[..], [Event::Headers { stream_id: 1, .. }]));
+
+    // The stream is reset, the connection goes on and ignores the frames
+    // sent on the stream before the client received the RST_STREAM frame.
+    match server.next_event() {
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(server.streams().get(stream_id).unwrap().reset_sent());
+    assert!(matches!(
+        server.next_event(),
+        Ok(Some(Event::Headers { stream_id: 3, .. }))
+    ));
+    assert_eq!(server.next_event().unwrap(), None);
+
+    let events = transfer(&mut server, &mut client);
+    assert_eq!(
+        events,
+        vec![Event::Reset {
+            stream_id,
+            error_code: ErrorCode::ProtocolError,
+        }]
+    );
+    client.send_data(other_stream_id, b"data", true).unwrap();
+    assert_eq!(
+        transfer(&mut client, &mut server),
+        vec![Event::Data {
+            stream_id: other_stream_id,
+            length: 4,
+            end_stream: true,
+        }]
+    );
+}
+
+#[test]
+pub fn test_core_reset_received() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let response = HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())]);
+
+    // The client cancels the request after part of its content.
+    let stream_id = client.send_request(request.clone(), false).unwrap();
+    client.send_data(stream_id, b"partial", false).unwrap();
+    client.send_reset(stream_id, ErrorCode::Cancel).unwrap();
+    let events = transfer(&mut client, &mut server);
+    assert_eq!(
+        events[1..],
+        [
+            Event::Data {
+                stream_id,
+                length: 7,
+                end_stream: false,
+            },
+            Event::Reset {
+                stream_id,
+                error_code: ErrorCode::Cancel,
+            }
+        ]
+    );
+    assert_eq!(
+        server.streams().get(stream_id).unwrap().reset_received(),
+        Some(ErrorCode::Cancel)
+    );
+
+    // The content received before the reset is delivered, then reading and
+    // writing fail with the error code of the client.
+    assert_eq!(server.recv_data(stream_id).unwrap(), b"partial");
+    for _ in 0..2 {
+        match server.recv_data(stream_id) {
+            Err(Http2Error::StreamError(1, ErrorCode::Cancel, _)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+    match server.send_response(stream_id, response.clone(), false) {
+        Err(Http2Error::StreamError(1, ErrorCode::Cancel, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    match server.send_data(stream_id, b"data", true) {
+        Err(Http2Error::StreamError(1, ErrorCode::Cancel, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    match server.send_trailers(stream_id, HeaderList::default()) {
+        Err(Http2Error::StreamError(1, ErrorCode::Cancel, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // A writer waiting for the flow-control window fails once the peer
+    // resets the stream.
+    let stream_id = client.send_request(request.clone(), true).unwrap();
+    transfer(&mut client, &mut server);
+    server.send_response(stream_id, response, false).unwrap();
+    let body = vec![0; 100_000];
+    let sent = server.send_data(stream_id, &body, true).unwrap();
+    assert!(sent < body.len());
+    transfer(&mut server, &mut client);
+    client
+        .send_reset(stream_id, ErrorCode::RefusedStream)
+        .unwrap();
+    transfer(&mut client, &mut server);
+    match server.send_data(stream_id, &body[sent..], true) {
+        Err(Http2Error::StreamError(3, ErrorCode::RefusedStream, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // A reset after the end of the stream does not fail reads.
+    let stream_id = client.send_request(request, true).unwrap();
+    transfer(&mut client, &mut server);
+    server
+        .send_response(
+            stream_id,
+            HeaderList::new(vec![HeaderField::new(":status".into(), "204".into())]),
+            true,
+        )
+        .unwrap();
+    transfer(&mut server, &mut client);
+    let rst_stream = [
+        0x00, 0x00, 0x04, 0x03, 0x00, 0x00, 0x00, 0x00, 0x05, // RST_STREAM on stream 5
+        0x00, 0x00, 0x00, 0x08, // Error Code = CANCEL
+    ];
+    server.handle_input(&rst_stream).unwrap();
+    assert_eq!(
+        server.streams().get(stream_id).unwrap().reset_received(),
+        None
+    );
+    assert!(server.recv_data(stream_id).unwrap().is_empty());
+}
+
+#[test]
+pub fn test_core_settings_changed() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+    let old_settings = client.peer_settings();
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request, false).unwrap();
+    assert_eq!(
+        client.send_data(stream_id, &[0x2a; 20_000], false).unwrap(),
+        20_000
+    );
+
+    // The server shrinks the stream windows, the send window of the open
+    // stream becomes negative.
+    let settings_frame =
+        SettingsFrame::new().with_parameter(SettingsParameter::InitialWindowSize(16_384));
+    let events = client.handle_input(&settings_frame.serialize()).unwrap();
+    let new_settings = client.peer_settings();
+    assert_eq!(new_settings.initial_window_size(), 16_384);
+    assert_eq!(
+        events,
+        vec![Event::SettingsChanged {
+            old_settings,
+            new_settings,
+        }]
+    );
+    assert_eq!(client.send_data(stream_id, b"data", false).unwrap(), 0);
+
+    // Settings left unchanged are not notified.
+    let events = client.handle_input(&settings_frame.serialize()).unwrap();
+    assert_eq!(events, vec![]);
+}
+
+#[test]
+pub fn test_core_max_header_list_size() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .max_header_list_size(200)
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+    assert_eq!(client.peer_settings().max_header_list_size(), Some(200));
+
+    let mut request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    request.append("user-agent", &"a".repeat(100));
+    assert!(request.size() > 200);
+
+    // The request would be refused, it is not sent.
+    match client.send_request(request.clone(), true) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(!client.has_output());
+
+    request.remove("user-agent");
+    assert_eq!(client.send_request(request, true).unwrap(), 1);
+}
+
+#[test]
+pub fn test_core_encoder_limits() {
+    let mut client = ConnectionBuilder::client()
+        .encoder_limits(EncoderLimits {
+            max_field_size: Some(100),
+            ..EncoderLimits::default()
+        })
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let mut request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    request.append("user-agent", &"a".repeat(100));
+
+    // The header field exceeds the limits, the request is not sent.
+    match client.send_request(request.clone(), true) {
+        Err(Http2Error::HeaderError(message)) => assert!(message.contains("user-agent")),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(!client.has_output());
+
+    request.remove("user-agent");
+    assert_eq!(client.send_request(request, true).unwrap(), 1);
+}
+
+#[test]
+pub fn test_core_lifecycle_events() {
+    let mut client = ConnectionBuilder::client()
+        .lifecycle_events(true)
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+
+    // The server acknowledges the client settings with its own.
+    transfer(&mut client, &mut server);
+    let events = transfer(&mut server, &mut client);
+    assert_eq!(
+        events,
+        vec![Event::SettingsAcked {
+            settings: Settings::new()
+        }]
+    );
+    transfer(&mut client, &mut server);
+
+    client
+        .send_frame(&Frame::Ping(PingFrame::new(*b"lifecycl")))
+        .unwrap();
+    transfer(&mut client, &mut server);
+    let events = transfer(&mut server, &mut client);
+    assert_eq!(
+        events,
+        vec![Event::PingAcked {
+            opaque_data: *b"lifecycl"
+        }]
+    );
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request, false).unwrap();
+    client.send_data(stream_id, &[0x2a; 100], false).unwrap();
+    transfer(&mut client, &mut server);
+    server.recv_data(stream_id).unwrap();
+
+    // The WINDOW_UPDATE frames of the connection and of the stream.
+    let events = transfer(&mut server, &mut client);
+    assert_eq!(events.len(), 2);
+    assert!(events.contains(&Event::WindowAvailable {
+        stream_id: 0,
+        window_size: 65_535,
+    }));
+    assert!(events.contains(&Event::WindowAvailable {
+        stream_id: 1,
+        window_size: 65_535,
+    }));
+
+    // Lifecycle events are not reported by default.
+    server
+        .send_frame(&Frame::Ping(PingFrame::new(*b"lifecycl")))
+        .unwrap();
+    transfer(&mut server, &mut client);
+    assert_eq!(transfer(&mut client, &mut server), vec![]);
+}
+
+#[test]
+pub fn test_core_continuation() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new("x-custom".into(), "a".repeat(64).as_str().into()),
+    ]);
+    let header_block = request
+        .encode(&mut HeaderTable::new(DEFAULT_HEADER_TABLE_SIZE))
+        .unwrap();
+
+    // The header block is split inside the literal value of x-custom,
+    // over a HEADERS frame and two CONTINUATION frames.
+    let mut input = Vec::new();
+    for (frame_type, frame_flags, fragment) in [
+        (0x1, 0x1, &header_block[..8]),
+        (0x9, 0x0, &header_block[8..header_block.len() - 10]),
+        (0x9, 0x4, &header_block[header_block.len() - 10..]),
+    ] {
+        FrameHeader::new(fragment.len() as u32, frame_type, frame_flags, false, 1)
+            .serialize_into(&mut input);
+        input.extend_from_slice(fragment);
+    }
+
+    // Nothing is decoded until the END_HEADERS flag.
+    let (head, tail) = input.split_at(input.len() - 15);
+    assert_eq!(server.handle_input(head).unwrap(), vec![]);
+    assert_eq!(
+        server.handle_input(tail).unwrap(),
+        vec![Event::Headers {
+            stream_id: 1,
+            header_list: request,
+            end_stream: true,
+        }]
+    );
+    assert_eq!(
+        server.streams().get(1).unwrap().state(),
+        StreamState::HalfClosedRemote
+    );
+}
+
+#[test]
+pub fn test_core_malformed_header_list() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    // Both requests share the dynamic table of the encoder, the first one
+    // ends with a literal header field with an uppercase name.
+    let mut header_table = HeaderTable::new(DEFAULT_HEADER_TABLE_SIZE);
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new("x-custom".into(), "value".into()),
+    ]);
+    let mut input = Vec::new();
+    for stream_id in [1, 3] {
+        let mut header_block = request.encode(&mut header_table).unwrap();
+        if stream_id == 1 {
+            header_block.extend_from_slice(b"\x00\x08X-Custom\x05value");
+        }
+        FrameHeader::new(header_block.len() as u32, 0x1, 0x5, false, stream_id)
+            .serialize_into(&mut input);
+        input.extend_from_slice(&header_block);
+    }
+    server.feed(&input);
+
+    // The malformed request resets its stream, the connection goes on.
+    match server.next_event() {
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(server.streams().get(1).unwrap().reset_sent());
+    match server.next_event() {
+        Ok(Some(Event::Headers {
+            stream_id: 3,
+            header_list,
+            ..
+        })) => assert_eq!(header_list, request),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_core_connection_error_go_away() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    // The header block references index 0, which HPACK does not use.
+    let mut input = Vec::new();
+    FrameHeader::new(1, 0x1, 0x5, false, 1).serialize_into(&mut input);
+    input.push(0x80);
+    let error = match server.handle_input(&input) {
+        Err(error) if error.error_code() == ErrorCode::CompressionError => error,
+        result => panic!("Unexpected result {:?}", result),
+    };
+
+    // The GOAWAY frame carries the error code and the error as debug data.
+    let debug_data = error.to_string().into_bytes();
+    let mut go_away = Vec::new();
+    FrameHeader::new(8 + debug_data.len() as u32, 0x7, 0x0, false, 0).serialize_into(&mut go_away);
+    go_away.extend_from_slice(&[0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x0, 0x9]);
+    go_away.extend_from_slice(&debug_data);
+    assert_eq!(server.poll_output(), go_away);
+
+    // A single GOAWAY frame is sent.
+    assert!(server.handle_input(&input).is_err());
+    assert!(!server.has_output());
+}
+
+#[test]
+pub fn test_core_send_continuation() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new("x-large".into(), "a".repeat(40_000).as_str().into()),
+    ]);
+    assert!(request.size() > 16_384);
+    client.send_request(request.clone(), true).unwrap();
+
+    // The header block is split within the SETTINGS_MAX_FRAME_SIZE of the
+    // peer, only the last frame has the END_HEADERS flag.
+    let output = client.poll_output();
+    let mut frame_headers = Vec::new();
+    let mut offset = 0;
+    while offset < output.len() {
+        let frame_header = FrameHeader::from_bytes(output[offset..].first_chunk().unwrap());
+        offset += FRAME_HEADER_LENGTH + frame_header.payload_length() as usize;
+        frame_headers.push(frame_header);
+    }
+    assert_eq!(
+        frame_headers
+            .iter()
+            .map(|frame_header| (frame_header.frame_type(), frame_header.frame_flags()))
+            .collect::<Vec<_>>(),
+        vec![(0x1, 0x1), (0x9, 0x0), (0x9, 0x4)]
+    );
+    assert_eq!(frame_headers[0].payload_length(), 16_384);
+    assert_eq!(frame_headers[1].payload_length(), 16_384);
+
+    assert_eq!(
+        server.handle_input(&output).unwrap(),
+        vec![Event::Headers {
+            stream_id: 1,
+            header_list: request,
+            end_stream: true,
+        }]
+    );
+}
+
+#[test]
+pub fn test_core_push_promise() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request, true).unwrap();
+    transfer(&mut client, &mut server);
+
+    // The promised stream is reserved by the server.
+    let promised_request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/style.css".into()),
+    ]);
+    server
+        .send_frame(&Frame::PushPromise(PushPromiseFrame::new(
+            stream_id,
+            true,
+            2,
+            promised_request.clone(),
+        )))
+        .unwrap();
+    let events = transfer(&mut server, &mut client);
+    assert_eq!(
+        events,
+        vec![Event::PushPromised {
+            stream_id: 1,
+            promised_stream_id: 2,
+            header_list: promised_request,
+        }]
+    );
+    assert_eq!(
+        client.streams().get(2).unwrap().state(),
+        StreamState::ReservedRemote
+    );
+
+    // The pushed response is received on the promised stream.
+    let response = HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())]);
+    server
+        .send_frame(&Frame::Headers(HeadersFrame::new(
+            2,
+            true,
+            true,
+            None,
+            response.clone(),
+        )))
+        .unwrap();
+    let events = transfer(&mut server, &mut client);
+    assert_eq!(
+        events,
+        vec![Event::Headers {
+            stream_id: 2,
+            header_list: response,
+            end_stream: true,
+        }]
+    );
+    assert_eq!(
+        client.streams().get(2).unwrap().state(),
+        StreamState::Closed
+    );
+
+    // Promising a stream twice is a connection error.
+    server
+        .send_frame(&Frame::PushPromise(PushPromiseFrame::new(
+            stream_id,
+            true,
+            2,
+            HeaderList::new(vec![]),
+        )))
+        .unwrap();
+    match client.handle_input(&server.poll_output()) {
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_core_push_promise_disabled() {
+    let mut client = ConnectionBuilder::client()
+        .enable_push(false)
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request.clone(), true).unwrap();
+    transfer(&mut client, &mut server);
+
+    // Push was disabled by the client.
+    server
+        .send_frame(&Frame::PushPromise(PushPromiseFrame::new(
+            stream_id,
+            true,
+            2,
+            request.clone(),
+        )))
+        .unwrap();
+    match client.handle_input(&server.poll_output()) {
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // Clients cannot push.
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+    let stream_id = client.send_request(request.clone(), false).unwrap();
+    client
+        .send_frame(&Frame::PushPromise(PushPromiseFrame::new(
+            stream_id, true, 3, request,
+        )))
+        .unwrap();
+    match server.handle_input(&client.poll_output()) {
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_core_send_push_promise() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request.clone(), true).unwrap();
+    transfer(&mut client, &mut server);
+
+    // Clients cannot push.
+    match client.send_push_promise(stream_id, request.clone()) {
+        Err(Http2Error::UsageError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // Only GET and HEAD requests are pushed.
+    let mut post_request = request.clone();
+    post_request.insert(":method", "POST");
+    match server.send_push_promise(stream_id, post_request) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    let mut promised_request = request.clone();
+    promised_request.insert(":path", "/style.css");
+    let promised_stream_id = server
+        .send_push_promise(stream_id, promised_request.clone())
+        .unwrap();
+    assert_eq!(promised_stream_id, 2);
+    assert_eq!(
+        server.streams().get(2).unwrap().state(),
+        StreamState::ReservedLocal
+    );
+    assert_eq!(
+        transfer(&mut server, &mut client),
+        vec![Event::PushPromised {
+            stream_id,
+            promised_stream_id,
+            header_list: promised_request,
+        }]
+    );
+
+    // The pushed response opens the promised stream.
+    let response = HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())]);
+    server
+        .send_response(promised_stream_id, response.clone(), false)
+        .unwrap();
+    assert_eq!(
+        server.streams().get(2).unwrap().state(),
+        StreamState::HalfClosedRemote
+    );
+    assert_eq!(
+        server.send_data(promised_stream_id, b"body", true).unwrap(),
+        4
+    );
+    assert_eq!(
+        transfer(&mut server, &mut client),
+        vec![
+            Event::Headers {
+                stream_id: promised_stream_id,
+                header_list: response,
+                end_stream: false,
+            },
+            Event::Data {
+                stream_id: promised_stream_id,
+                length: 4,
+                end_stream: true,
+            },
+        ]
+    );
+    assert_eq!(client.recv_data(promised_stream_id).unwrap(), b"body");
+
+    // Servers consult the SETTINGS_ENABLE_PUSH of the client.
+    let mut client = ConnectionBuilder::client()
+        .enable_push(false)
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+    assert!(!server.peer_settings().enable_push());
+    let stream_id = client.send_request(request.clone(), true).unwrap();
+    transfer(&mut client, &mut server);
+    match server.send_push_promise(stream_id, request) {
+        Err(Http2Error::UsageError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(!server.has_output());
+}
+
+#[test]
+pub fn test_core_interim_response() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request, true).unwrap();
+    transfer(&mut client, &mut server);
+
+    // Only 1xx statuses other than 101 are interim responses.
+    for status in ["200", "101"] {
+        let header_list = HeaderList::new(vec![HeaderField::new(":status".into(), status.into())]);
+        match server.send_interim_response(stream_id, header_list) {
+            Err(Http2Error::HeaderError(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+    assert!(!server.has_output());
+
+    // Early hints precede the final response.
+    let early_hints = HeaderList::new(vec![
+        HeaderField::new(":status".into(), "103".into()),
+        HeaderField::new("link".into(), "</style.css>; rel=preload".into()),
+    ]);
+    let response = HeaderList::new(vec![HeaderField::new(":status".into(), "204".into())]);
+    server
+        .send_interim_response(stream_id, early_hints.clone())
+        .unwrap();
+    server
+        .send_response(stream_id, response.clone(), true)
+        .unwrap();
+    assert_eq!(
+        transfer(&mut server, &mut client),
+        vec![
+            Event::InterimResponse {
+                stream_id,
+                header_list: early_hints,
+            },
+            Event::Headers {
+                stream_id,
+                header_list: response,
+                end_stream: true,
+            },
+        ]
+    );
+}
+
+#[test]
+pub fn test_core_usage_error() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let response = HeaderList::new(vec![HeaderField::new(":status".into(), "204".into())]);
+    let early_hints = HeaderList::new(vec![HeaderField::new(":status".into(), "103".into())]);
+
+    // Misusing the API is not reported to the peer.
+    match server.send_request(request.clone(), true) {
+        Err(error @ Http2Error::UsageError(_)) => assert!(!error.is_connection_error()),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    match client.send_response(1, response.clone(), true) {
+        Err(Http2Error::UsageError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    match server.send_interim_response(1, early_hints) {
+        Err(Http2Error::UsageError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(!client.has_output());
+    assert!(!server.has_output());
+
+    // The connection remains usable.
+    let stream_id = client.send_request(request.clone(), true).unwrap();
+    assert_eq!(
+        transfer(&mut client, &mut server),
+        vec![Event::Headers {
+            stream_id,
+            header_list: request,
+            end_stream: true,
+        }]
+    );
+    server
+        .send_response(stream_id, response.clone(), true)
+        .unwrap();
+    assert_eq!(
+        transfer(&mut server, &mut client),
+        vec![Event::Headers {
+            stream_id,
+            header_list: response,
+            end_stream: true,
+        }]
+    );
+}
+
+#[test]
+pub fn test_core_go_away_retryable() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    for end_stream in [false, false, true] {
+        client.send_request(request.clone(), end_stream).unwrap();
+    }
+    transfer(&mut client, &mut server);
+
+    // The server only processed the first request.
+    let go_away_frame = GoAwayFrame::new(1, ErrorCode::NoError, Vec::new());
+    server.send_frame(&Frame::GoAway(go_away_frame)).unwrap();
+    let events = transfer(&mut server, &mut client);
+    assert_eq!(
+        events,
+        vec![Event::GoAway {
+            last_stream_id: 1,
+            error_code: ErrorCode::NoError,
+            retryable_streams: vec![3, 5],
+        }]
+    );
+    assert_eq!(client.streams().local_active_streams(), 1);
+    assert!(!client.streams().get(1).unwrap().not_processed());
+    assert!(client.streams().get(3).unwrap().not_processed());
+
+    // The unprocessed streams fail with a distinct error, safe to retry.
+    match client.send_data(3, b"data", true) {
+        Err(Http2Error::StreamNotProcessed(3)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    match client.send_trailers(3, HeaderList::default()) {
+        Err(Http2Error::StreamNotProcessed(3)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    match client.recv_data(5) {
+        Err(error @ Http2Error::StreamNotProcessed(5)) => {
+            assert_eq!(error.error_code(), ErrorCode::RefusedStream);
+            assert!(error.is_stream_error());
+        }
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // The processed stream completes.
+    client.send_data(1, b"data", true).unwrap();
+    transfer(&mut client, &mut server);
+    server
+        .send_response(
+            1,
+            HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())]),
+            true,
+        )
+        .unwrap();
+    let events = transfer(&mut server, &mut client);
+    assert!(matches!(
+        events[..],
+        [Event::Headers {
+            stream_id: 1,
+            end_stream: true,
+            ..
+        }]
+    ));
+}
+
+#[test]
+pub fn test_core_encoder_header_table_size() {
+    let mut client = ConnectionBuilder::client()
+        .encoder_header_table_size(1024)
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server()
+        .header_table_size(256)
+        .build()
+        .unwrap();
+    assert_eq!(client.encoder_header_table_size(), 1024);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+
+    // The chosen size applies before the SETTINGS frame of the peer.
+    client.poll_output();
+    client.send_request(request.clone(), true).unwrap();
+    let output = client.poll_output();
+    assert_eq!(output[9..12], [0x3f, 0xe1, 0x07]); // Dynamic Table Size Update = 1024
+
+    // The table shrinks to the SETTINGS_HEADER_TABLE_SIZE of the peer.
+    transfer(&mut server, &mut client);
+    client.send_request(request.clone(), true).unwrap();
+    let output = client.poll_output();
+    assert_eq!(output[18..21], [0x3f, 0xe1, 0x01]); // Dynamic Table Size Update = 256
+
+    // A smaller size can be chosen at any time, and a larger one is still
+    // bounded by the peer.
+    client.set_encoder_header_table_size(0);
+    client.send_request(request.clone(), true).unwrap();
+    assert_eq!(client.poll_output()[9], 0x20); // Dynamic Table Size Update = 0
+    client.set_encoder_header_table_size(4096);
+    assert_eq!(client.encoder_header_table_size(), 4096);
+    client.send_request(request, true).unwrap();
+    assert_eq!(client.poll_output()[9..12], [0x3f, 0xe1, 0x01]);
+}
+
+#[test]
+pub fn test_core_header_table_size() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .header_table_size(256)
+        .build()
+        .unwrap();
+
+    // Header blocks with a Dynamic Table Size Update = 1024, then with the
+    // indexed :method GET, :scheme http and :path /.
+    let header_block = [0x3f, 0xe1, 0x07, 0x82, 0x86, 0x84];
+    let headers_frame = |stream_id| {
+        let mut input = Vec::new();
+        FrameHeader::new(header_block.len() as u32, 0x1, 0x5, false, stream_id)
+            .serialize_into(&mut input);
+        input.extend_from_slice(&header_block);
+        input
+    };
+
+    // The initial table size applies until the SETTINGS frame is
+    // acknowledged.
+    let mut input = client.poll_output();
+    input.extend(headers_frame(1));
+    assert!(matches!(
+        server.handle_input(&input).unwrap()[..],
+        [Event::Headers { stream_id: 1, .. }]
+    ));
+
+    transfer(&mut server, &mut client);
+    transfer(&mut client, &mut server);
+    match server.handle_input(&headers_frame(3)) {
+        Err(error) if error.error_code() == ErrorCode::CompressionError => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_core_memory_budget() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .memory_budget(16_384)
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+    let memory_budget = server.memory_budget().unwrap();
+    assert_eq!(memory_budget.limit(), 16_384);
+    assert_eq!(memory_budget.used(), 0);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let body = vec![0x2a; 40_000];
+    let stream_id = client.send_request(request.clone(), false).unwrap();
+    assert_eq!(client.send_data(stream_id, &body, true).unwrap(), 40_000);
+    transfer(&mut client, &mut server);
+
+    // The content exceeds the budget, the connection window is withheld
+    // once the buffered content exceeds it.
+    let memory_budget = server.memory_budget().unwrap();
+    assert_eq!(memory_budget.recv_buffers(), 40_000);
+    assert!(memory_budget.used() > memory_budget.limit());
+    transfer(&mut server, &mut client);
+    assert!(client.send_window().size() < 65_535 - 16_384);
+
+    // Reading the content releases the withheld window.
+    assert_eq!(server.recv_data(stream_id).unwrap().len(), 40_000);
+    transfer(&mut server, &mut client);
+    assert_eq!(client.send_window().size(), 65_535);
+
+    // A header block growing the decoding table beyond the budget is a
+    // connection error.
+    let mut large_request = request;
+    large_request.append("x-large", &"a".repeat(10_000));
+    let mut client = ConnectionBuilder::client()
+        .encoder_header_table_size(65_536)
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server()
+        .header_table_size(65_536)
+        .memory_budget(8_192)
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+    client.send_request(large_request, true).unwrap();
+    match server.handle_input(&client.poll_output()) {
+        Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, message)) => {
+            assert!(message.contains("memory budget"))
+        }
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_core_memory_budget_local_send() {
+    let mut client = ConnectionBuilder::client()
+        .memory_budget(16_384)
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let body = vec![0x2a; 40_000];
+    let stream_id = client.send_request(request, false).unwrap();
+    assert_eq!(client.send_data(stream_id, &body, true).unwrap(), 40_000);
+    let memory_budget = client.memory_budget().unwrap();
+    assert!(memory_budget.send_queue() > memory_budget.limit());
+    assert_eq!(memory_budget.control_queue(), 0);
+
+    // The content queued by the application does not count against the
+    // budget while the peer sends frames.
+    server
+        .send_frame(&Frame::Ping(PingFrame::new([0; 8])))
+        .unwrap();
+    assert_eq!(client.handle_input(&server.poll_output()).unwrap(), vec![]);
+    let memory_budget = client.memory_budget().unwrap();
+    assert_eq!(memory_budget.control_queue(), 17);
+    assert_eq!(memory_budget.send_queue(), client.output_len());
+}
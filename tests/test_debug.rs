@@ -0,0 +1,74 @@
+#![cfg(feature = "codec")]
+
+use http2::debug::{dump_frames, frame_flag_names};
+use http2::start::HTTP2_CONNECTION_PREFACE_SEQUENCE;
+
+#[test]
+pub fn test_dump_frames() {
+    let mut bytes: Vec<u8> = HTTP2_CONNECTION_PREFACE_SEQUENCE.to_vec();
+    bytes.extend_from_slice(&[
+        0x00, 0x00, 0x0c, // Length = 12
+        0x04, // Frame Type = SETTINGS
+        0x00, // Flags = None
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x00, 0x03, 0x00, 0x00, 0x00, 0x64, // MAX_CONCURRENT_STREAMS = 100
+        0x00, 0x10, 0x00, 0x00, 0x00, 0x01, // Unknown = 1
+        0x00, 0x00, 0x14, // Length = 20
+        0x01, // Frame Type = HEADERS
+        0x05, // Flags = [EndStream, EndHeaders]
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+        0x82, 0x86, 0x84, 0x41, 0x0f, 0x77, 0x77, 0x77, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c,
+        0x65, 0x2e, 0x63, 0x6f, 0x6d,
+        // Payload =
+        // :method: GET
+        // :scheme: http
+        // :path: /
+        // :authority: www.example.com
+        0x00, 0x00, 0x08, // Length = 8
+        0x00, // Frame Type = DATA
+        0x09, // Flags = [EndStream, Padded]
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+        0x02, // Pad Length = 2
+        0x68, 0x65, 0x6c, 0x6c, 0x6f, // Data = "hello"
+        0x00, 0x00, // Padding
+        0x00, 0x00, 0x08, // Length = 8
+        0x07, // Frame Type = GOAWAY
+        0x00, // Flags = None
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x00, 0x00, 0x00, 0x01, // Last Stream Identifier = 1
+        0x00, 0x00, 0x00, 0x01, // Error Code = PROTOCOL_ERROR
+        0x00, 0x00, 0x04, // Length = 4
+        0x08, // Frame Type = WINDOW_UPDATE
+        0x00, // Flags = None
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+    ]);
+
+    let expected = "\
+00000000  PREFACE
+00000018  SETTINGS stream=0 length=12 flags=0x00 []
+          Max Concurrent Streams: 100
+          Unknown (0x0010): 1
+0000002d  HEADERS stream=1 length=20 flags=0x05 [END_STREAM, END_HEADERS]
+          :method: GET
+          :scheme: http
+          :path: /
+          :authority: www.example.com
+0000004a  DATA stream=1 length=8 flags=0x09 [END_STREAM, PADDED]
+          0000  68 65 6c 6c 6f                                   |hello|
+0000005b  GOAWAY stream=0 length=8 flags=0x00 []
+          last stream=1 error=PROTOCOL_ERROR
+0000006c  WINDOW_UPDATE stream=1 length=4 flags=0x00 []
+          truncated payload: 0 of 4 bytes
+";
+    assert_eq!(dump_frames(&bytes), expected);
+}
+
+#[test]
+pub fn test_frame_flag_names() {
+    assert_eq!(frame_flag_names(0x4, 0x01), vec!["ACK"]);
+    assert_eq!(
+        frame_flag_names(0x1, 0x2c),
+        vec!["END_HEADERS", "PADDED", "PRIORITY"]
+    );
+    assert_eq!(frame_flag_names(0x9, 0x05), vec!["0x01", "END_HEADERS"]);
+}
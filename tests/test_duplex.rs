@@ -0,0 +1,201 @@
+#![cfg(feature = "test-support")]
+
+use std::thread;
+
+use http2::connection::builder::ConnectionBuilder;
+use http2::error::ErrorCode;
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::sync::Event;
+use http2::test_support::{duplex, duplex_with};
+
+/// Build a GET request header list.
+fn request() -> HeaderList {
+    HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ])
+}
+
+/// Build a response header list.
+fn response() -> HeaderList {
+    HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())])
+}
+
+#[test]
+pub fn test_duplex_exchange() {
+    let (mut client, mut server) = duplex().unwrap();
+
+    // Small messages fit in the windows, both ends run in the same thread.
+    let stream_id = client.send_request(request(), None).unwrap();
+    match server.next_event().unwrap() {
+        Event::Headers {
+            stream_id: 1,
+            header_list,
+            end_stream: true,
+            ..
+        } => assert_eq!(header_list, request()),
+        event => panic!("Unexpected event {:?}", event),
+    }
+    server
+        .send_response(stream_id, response(), Some(b"hello"))
+        .unwrap();
+
+    match client.next_event().unwrap() {
+        Event::Headers {
+            header_list,
+            end_stream: false,
+            ..
+        } => assert_eq!(header_list, response()),
+        event => panic!("Unexpected event {:?}", event),
+    }
+    match client.next_event().unwrap() {
+        Event::Data {
+            end_stream: true, ..
+        } => (),
+        event => panic!("Unexpected event {:?}", event),
+    }
+    assert_eq!(client.recv_data(stream_id).unwrap(), b"hello");
+}
+
+#[test]
+pub fn test_duplex_flow_control() {
+    let (mut client, mut server) = duplex_with(
+        ConnectionBuilder::client().initial_window_size(1_000),
+        ConnectionBuilder::server().max_concurrent_streams(10),
+    )
+    .unwrap();
+    assert_eq!(
+        client.get_mut().peer_settings().max_concurrent_streams(),
+        Some(10)
+    );
+
+    // The response exceeds the window of the client: the server waits for
+    // WINDOW_UPDATE frames, sent as the client reads the content.
+    let body = vec![0x2a; 100_000];
+    let expected = body.clone();
+    let server = thread::spawn(move || {
+        let stream_id = match server.next_event().unwrap() {
+            Event::Headers { stream_id, .. } => stream_id,
+            event => panic!("Unexpected event {:?}", event),
+        };
+        server
+            .send_response(stream_id, response(), Some(&body))
+            .unwrap();
+        // The server is kept open while the client reads.
+        server
+    });
+
+    let stream_id = client.send_request(request(), None).unwrap();
+    let mut content = Vec::new();
+    loop {
+        match client.next_event().unwrap() {
+            Event::Data { end_stream, .. } => {
+                content.extend(client.recv_data(stream_id).unwrap());
+                if end_stream {
+                    break;
+                }
+            }
+            Event::Headers { .. } => (),
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+    assert_eq!(content, expected);
+    server.join().unwrap();
+}
+
+#[test]
+pub fn test_duplex_write_batching() {
+    let (mut client, mut server) = duplex_with(
+        ConnectionBuilder::client()
+            .initial_window_size(1_000)
+            .max_write_batch(4_096),
+        ConnectionBuilder::server().max_write_batch(4_096),
+    )
+    .unwrap();
+
+    // Batched frames are written before either end blocks reading, the
+    // server flushes the end of the response it leaves in a batch.
+    let body = vec![0x2a; 100_000];
+    let expected = body.clone();
+    let server = thread::spawn(move || {
+        let stream_id = match server.next_event().unwrap() {
+            Event::Headers { stream_id, .. } => stream_id,
+            event => panic!("Unexpected event {:?}", event),
+        };
+        server
+            .send_response(stream_id, response(), Some(&body))
+            .unwrap();
+        server.flush().unwrap();
+        server
+    });
+
+    let stream_id = client.send_request(request(), None).unwrap();
+    let mut content = Vec::new();
+    loop {
+        match client.next_event().unwrap() {
+            Event::Data { end_stream, .. } => {
+                content.extend(client.recv_data(stream_id).unwrap());
+                if end_stream {
+                    break;
+                }
+            }
+            Event::Headers { .. } => (),
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+    assert_eq!(content, expected);
+    server.join().unwrap();
+}
+
+#[test]
+pub fn test_duplex_cancel_push() {
+    let (mut client, mut server) = duplex().unwrap();
+
+    // The server pushes a response before responding to the request.
+    let stream_id = client.send_request(request(), None).unwrap();
+    assert!(matches!(
+        server.next_event().unwrap(),
+        Event::Headers { stream_id: 1, .. }
+    ));
+    let promised_stream_id = server.send_push_promise(stream_id, request()).unwrap();
+    server
+        .send_response(promised_stream_id, response(), Some(b"pushed"))
+        .unwrap();
+    server.send_response(stream_id, response(), None).unwrap();
+
+    // The client cancels the push, the pushed response is ignored.
+    assert_eq!(
+        client.next_event().unwrap(),
+        Event::PushPromised {
+            stream_id,
+            promised_stream_id,
+            header_list: request(),
+        }
+    );
+    client.cancel_push(promised_stream_id).unwrap();
+    assert!(matches!(
+        client.next_event().unwrap(),
+        Event::Headers {
+            stream_id: 1,
+            end_stream: true,
+            ..
+        }
+    ));
+    assert!(client
+        .get_mut()
+        .core()
+        .streams()
+        .get(promised_stream_id)
+        .unwrap()
+        .reset_sent());
+
+    assert_eq!(
+        server.next_event().unwrap(),
+        Event::Reset {
+            stream_id: promised_stream_id,
+            error_code: ErrorCode::Cancel,
+        }
+    );
+}
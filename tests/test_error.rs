@@ -0,0 +1,94 @@
+use std::error::Error;
+use std::io::ErrorKind;
+
+use http2::error::{CompressionError, ErrorCode, FlowControlError, FlowControlScope, Http2Error};
+
+#[test]
+pub fn test_error_code() {
+    assert_eq!(
+        Http2Error::NotEnoughBytes("".to_string()).error_code(),
+        ErrorCode::FrameSizeError
+    );
+    assert_eq!(
+        Http2Error::FrameError("".to_string()).error_code(),
+        ErrorCode::ProtocolError
+    );
+    assert_eq!(
+        Http2Error::from(CompressionError::InvalidIndex(62)).error_code(),
+        ErrorCode::CompressionError
+    );
+    assert_eq!(
+        Http2Error::CompressionError(CompressionError::HuffmanEos).error_code(),
+        ErrorCode::CompressionError
+    );
+    assert_eq!(
+        Http2Error::IoError(ErrorKind::UnexpectedEof.into()).error_code(),
+        ErrorCode::InternalError
+    );
+    assert_eq!(
+        Http2Error::ConnectionError(ErrorCode::FlowControlError, "".to_string()).error_code(),
+        ErrorCode::FlowControlError
+    );
+    assert_eq!(
+        Http2Error::StreamError(3, ErrorCode::RefusedStream, "".to_string()).error_code(),
+        ErrorCode::RefusedStream
+    );
+}
+
+#[test]
+pub fn test_error_classification() {
+    let error = Http2Error::StreamError(3, ErrorCode::RefusedStream, "".to_string());
+    assert_eq!(error.stream_id(), Some(3));
+    assert!(error.is_stream_error());
+    assert!(!error.is_connection_error());
+
+    let error = Http2Error::ConnectionError(ErrorCode::ProtocolError, "".to_string());
+    assert_eq!(error.stream_id(), None);
+    assert!(error.is_connection_error());
+    assert!(!error.is_stream_error());
+
+    // Decoding failures desynchronize the connection state.
+    let error = Http2Error::from(CompressionError::TruncatedInteger);
+    assert!(error.is_connection_error());
+    // Flow-control failures follow the scope of their window.
+    let error = Http2Error::from(FlowControlError::WindowExceeded(
+        FlowControlScope::Stream(5),
+        100,
+        10,
+    ));
+    assert_eq!(error.stream_id(), Some(5));
+    assert_eq!(error.error_code(), ErrorCode::FlowControlError);
+    let error = Http2Error::from(FlowControlError::WindowOverflow(
+        FlowControlScope::Connection,
+        10,
+        100,
+    ));
+    assert!(error.is_connection_error());
+    let error = Http2Error::from(FlowControlError::InitialWindowSizeOverflow(5));
+    assert!(error.is_connection_error());
+
+    // Usage errors are not reported to the peer.
+    let error = Http2Error::UsageError("".to_string());
+    assert_eq!(error.stream_id(), None);
+    assert!(!error.is_connection_error());
+    assert!(!error.is_stream_error());
+}
+
+#[test]
+pub fn test_error_source() {
+    let error = Http2Error::IoError(ErrorKind::UnexpectedEof.into());
+    let source = error.source().unwrap();
+    assert_eq!(
+        source.downcast_ref::<std::io::Error>().unwrap().kind(),
+        ErrorKind::UnexpectedEof
+    );
+
+    let error = Http2Error::CompressionError(CompressionError::HuffmanInvalidPadding);
+    let source = error.source().unwrap();
+    assert_eq!(
+        source.downcast_ref::<CompressionError>(),
+        Some(&CompressionError::HuffmanInvalidPadding)
+    );
+
+    assert!(Http2Error::FrameError("".to_string()).source().is_none());
+}
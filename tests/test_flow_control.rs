@@ -0,0 +1,266 @@
+#![cfg(feature = "sync")]
+
+use http2::connection::settings::Settings;
+use http2::error::{ErrorCode, FlowControlError, FlowControlScope, Http2Error};
+use http2::flow_control::{
+    self, BdpEstimator, FlowControlWindow, WindowUpdateCoalescer, WindowUpdateStrategy,
+    DEFAULT_INITIAL_WINDOW_SIZE, MAX_BDP_WINDOW_SIZE, MAX_WINDOW_SIZE,
+};
+use http2::frame::ping::PingFrame;
+use http2::frame::settings::{SettingsFrame, SettingsParameter};
+use http2::frame::window_update::WindowUpdateFrame;
+
+#[test]
+pub fn test_window_update_zero_increment() {
+    // A 0 increment on the connection is a connection error.
+    let mut window = FlowControlWindow::new(0, DEFAULT_INITIAL_WINDOW_SIZE);
+    let frame = WindowUpdateFrame::new(0, 0);
+    assert!(matches!(
+        window.apply_window_update(&frame),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+
+    // A 0 increment on a stream is a stream error.
+    let mut window = FlowControlWindow::new(1, DEFAULT_INITIAL_WINDOW_SIZE);
+    let frame = WindowUpdateFrame::new(1, 0);
+    assert!(matches!(
+        window.apply_window_update(&frame),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+    assert_eq!(window.size(), DEFAULT_INITIAL_WINDOW_SIZE as i64);
+
+    // A valid increment is added to the window.
+    let frame = WindowUpdateFrame::new(1, 1000);
+    window.apply_window_update(&frame).unwrap();
+    assert_eq!(window.size(), DEFAULT_INITIAL_WINDOW_SIZE as i64 + 1000);
+}
+
+#[test]
+pub fn test_window_overflow() {
+    // Reaching exactly 2^31-1 is allowed.
+    let mut window = FlowControlWindow::new(0, DEFAULT_INITIAL_WINDOW_SIZE);
+    window
+        .increase(MAX_WINDOW_SIZE - DEFAULT_INITIAL_WINDOW_SIZE)
+        .unwrap();
+    assert_eq!(window.size(), MAX_WINDOW_SIZE as i64);
+
+    // Going beyond is a connection error for the connection window.
+    let frame = WindowUpdateFrame::new(0, 1);
+    assert!(matches!(
+        window.apply_window_update(&frame),
+        Err(Http2Error::FlowControlError(
+            FlowControlError::WindowOverflow(FlowControlScope::Connection, _, 1)
+        ))
+    ));
+    assert_eq!(window.size(), MAX_WINDOW_SIZE as i64);
+
+    // And a stream error for a stream window.
+    let mut window = FlowControlWindow::new(3, DEFAULT_INITIAL_WINDOW_SIZE);
+    let frame = WindowUpdateFrame::new(3, MAX_WINDOW_SIZE);
+    assert!(matches!(
+        window.apply_window_update(&frame),
+        Err(Http2Error::FlowControlError(
+            FlowControlError::WindowOverflow(FlowControlScope::Stream(3), _, _)
+        ))
+    ));
+}
+
+#[test]
+pub fn test_window_consume() {
+    let mut window = FlowControlWindow::new(1, 100);
+    window.consume(60).unwrap();
+    assert_eq!(window.size(), 40);
+
+    assert!(matches!(
+        window.consume(41),
+        Err(Http2Error::FlowControlError(
+            FlowControlError::WindowExceeded(FlowControlScope::Stream(1), 41, 40)
+        ))
+    ));
+    assert_eq!(window.size(), 40);
+}
+
+#[test]
+pub fn test_window_for_stream() {
+    // The window starts at the initial value if the receiver never sent
+    // SETTINGS_INITIAL_WINDOW_SIZE.
+    let mut settings = Settings::new();
+    let window = FlowControlWindow::for_stream(1, &settings);
+    assert_eq!(window.stream_id(), 1);
+    assert_eq!(window.size(), DEFAULT_INITIAL_WINDOW_SIZE as i64);
+
+    settings
+        .apply(&SettingsFrame::new().with_parameter(SettingsParameter::InitialWindowSize(1_000)))
+        .unwrap();
+    let window = FlowControlWindow::for_stream(3, &settings);
+    assert_eq!(window.size(), 1_000);
+}
+
+#[test]
+pub fn test_window_update_initial_size() {
+    let mut window = FlowControlWindow::new(1, DEFAULT_INITIAL_WINDOW_SIZE);
+    window.consume(60_000).unwrap();
+
+    // A smaller initial size can make the window negative.
+    window
+        .update_initial_size(DEFAULT_INITIAL_WINDOW_SIZE, 16_384)
+        .unwrap();
+    assert_eq!(window.size(), 16_384 - 60_000);
+
+    window.update_initial_size(16_384, 100_000).unwrap();
+    assert_eq!(window.size(), 40_000);
+
+    // Overflowing a stream window is a connection error.
+    let error = window.update_initial_size(0, MAX_WINDOW_SIZE).unwrap_err();
+    assert!(matches!(
+        error,
+        Http2Error::FlowControlError(FlowControlError::InitialWindowSizeOverflow(1))
+    ));
+    assert!(error.is_connection_error());
+    assert_eq!(error.error_code(), ErrorCode::FlowControlError);
+}
+
+#[test]
+pub fn test_window_update_initial_window_size() {
+    let mut windows = vec![
+        FlowControlWindow::new(1, DEFAULT_INITIAL_WINDOW_SIZE),
+        FlowControlWindow::new(3, DEFAULT_INITIAL_WINDOW_SIZE),
+    ];
+    windows[0].consume(20_000).unwrap();
+
+    flow_control::update_initial_window_size(&mut windows, DEFAULT_INITIAL_WINDOW_SIZE, 16_384)
+        .unwrap();
+    assert_eq!(windows[0].size(), 16_384 - 20_000);
+    assert_eq!(windows[1].size(), 16_384);
+
+    assert!(matches!(
+        flow_control::update_initial_window_size(&mut windows, 0, MAX_WINDOW_SIZE),
+        Err(Http2Error::FlowControlError(
+            FlowControlError::InitialWindowSizeOverflow(3)
+        ))
+    ));
+}
+
+#[test]
+pub fn test_window_consume_empty() {
+    let mut window = FlowControlWindow::new(1, DEFAULT_INITIAL_WINDOW_SIZE);
+    window.consume(1_000).unwrap();
+    window
+        .update_initial_size(DEFAULT_INITIAL_WINDOW_SIZE, 0)
+        .unwrap();
+    assert_eq!(window.size(), -1_000);
+
+    // A negative window still accepts empty frames, such as a DATA frame
+    // only carrying END_STREAM.
+    window.consume(0).unwrap();
+    assert_eq!(window.size(), -1_000);
+    assert!(matches!(
+        window.consume(1),
+        Err(Http2Error::FlowControlError(
+            FlowControlError::WindowExceeded(FlowControlScope::Stream(1), 1, -1_000)
+        ))
+    ));
+}
+
+#[test]
+pub fn test_window_update_strategy() {
+    let window_size = DEFAULT_INITIAL_WINDOW_SIZE;
+
+    assert_eq!(
+        WindowUpdateStrategy::default(),
+        WindowUpdateStrategy::Immediate
+    );
+    assert_eq!(WindowUpdateStrategy::Immediate.increment(1, window_size), 1);
+
+    let strategy = WindowUpdateStrategy::Threshold(50);
+    assert_eq!(strategy.increment(32_767, window_size), 0);
+    assert_eq!(strategy.increment(32_768, window_size), 32_768);
+    assert_eq!(WindowUpdateStrategy::Bdp.increment(32_767, window_size), 0);
+    assert_eq!(
+        WindowUpdateStrategy::Bdp.increment(32_768, window_size),
+        32_768
+    );
+
+    // Thresholds beyond the whole window wait for the whole window.
+    let strategy = WindowUpdateStrategy::Threshold(200);
+    assert_eq!(strategy.increment(window_size, window_size), window_size);
+
+    let strategy = WindowUpdateStrategy::FixedIncrement(10_000);
+    assert_eq!(strategy.increment(9_999, window_size), 0);
+    assert_eq!(strategy.increment(25_000, window_size), 20_000);
+    assert_eq!(
+        WindowUpdateStrategy::FixedIncrement(0).increment(5, window_size),
+        5
+    );
+}
+
+#[test]
+pub fn test_window_update_coalescer() {
+    let mut window_updates = WindowUpdateCoalescer::new();
+    assert!(window_updates.is_empty());
+
+    // Increments are accumulated per window, empty ones are ignored.
+    for _ in 0..10 {
+        window_updates.add(1, 100);
+        window_updates.add(0, 100);
+    }
+    window_updates.add(3, 0);
+    window_updates.add(3, 50);
+    assert_eq!(window_updates.pending(0), 1_000);
+    assert_eq!(window_updates.pending(1), 1_000);
+    assert_eq!(window_updates.pending(5), 0);
+
+    // The increment of a reset stream is discarded.
+    window_updates.add(5, 10);
+    window_updates.remove(5);
+
+    // A single WINDOW_UPDATE frame per window.
+    assert_eq!(
+        window_updates.flush(),
+        vec![
+            WindowUpdateFrame::new(1, 1_000),
+            WindowUpdateFrame::new(0, 1_000),
+            WindowUpdateFrame::new(3, 50),
+        ]
+    );
+    assert!(window_updates.is_empty());
+    assert_eq!(window_updates.flush(), vec![]);
+
+    // The increment never exceeds the maximum window size.
+    window_updates.add(0, MAX_WINDOW_SIZE);
+    window_updates.add(0, MAX_WINDOW_SIZE);
+    assert_eq!(window_updates.pending(0), MAX_WINDOW_SIZE);
+}
+
+#[test]
+pub fn test_bdp_estimator() {
+    let mut bdp_estimator = BdpEstimator::new(DEFAULT_INITIAL_WINDOW_SIZE);
+
+    // The first DATA frame starts a sample.
+    let ping_frame = bdp_estimator.recv_data(16_384).unwrap();
+    assert!(!ping_frame.is_ack());
+    assert_eq!(bdp_estimator.recv_data(16_384), None);
+
+    // A sample well within the window does not grow it.
+    let ping_ack = PingFrame::ack(*ping_frame.opaque_data());
+    assert_eq!(bdp_estimator.recv_ping_ack(&ping_ack), None);
+    assert_eq!(bdp_estimator.window_size(), DEFAULT_INITIAL_WINDOW_SIZE);
+
+    // Other PING acknowledgements are ignored.
+    bdp_estimator.recv_data(60_000).unwrap();
+    assert_eq!(bdp_estimator.recv_ping_ack(&PingFrame::ack([0; 8])), None);
+
+    // A sample nearing the window doubles it.
+    assert_eq!(bdp_estimator.recv_ping_ack(&ping_ack), Some(120_000));
+    assert_eq!(bdp_estimator.window_size(), 120_000);
+
+    // The window does not grow beyond the limit.
+    let mut bdp_estimator = BdpEstimator::new(MAX_BDP_WINDOW_SIZE / 2);
+    bdp_estimator.recv_data(MAX_BDP_WINDOW_SIZE).unwrap();
+    assert_eq!(
+        bdp_estimator.recv_ping_ack(&ping_ack),
+        Some(MAX_BDP_WINDOW_SIZE)
+    );
+    bdp_estimator.recv_data(MAX_BDP_WINDOW_SIZE).unwrap();
+    assert_eq!(bdp_estimator.recv_ping_ack(&ping_ack), None);
+}
@@ -1,10 +1,13 @@
+#![cfg(feature = "codec")]
+
 use http2::{frame::Frame, header::table::HeaderTable};
 use http2::frame::data::DataFrame;
+use http2::frame::padding::Padding;
 
 #[test]
 pub fn test_data_frame_serialize() {
     let data_frame: DataFrame = DataFrame::new(1, true, b"Hello, World!".to_vec());
-    let data_frame_bytes = data_frame.serialize(None);
+    let data_frame_bytes = data_frame.serialize(Padding::None);
 
     assert_eq!(data_frame_bytes, vec![
         0x00, 0x00, 0x0d,       // Length = 13
@@ -65,4 +68,53 @@ pub fn test_data_frame_deserialize() {
 
     let frame: Frame = Frame::Data(DataFrame::new(1, true, b"Hello, World!".to_vec()));
     assert_eq!(data_frame_deserialized, frame);
-}
\ No newline at end of file
+}
+#[test]
+pub fn test_data_frame_end_of_stream() {
+    let data_frame = DataFrame::end_of_stream(1);
+    assert!(data_frame.end_stream);
+    assert!(data_frame.data.is_empty());
+    assert_eq!(data_frame.flow_controlled_length(), 0);
+
+    let mut bytes = data_frame.serialize(Padding::None);
+    assert_eq!(bytes, vec![
+        0x00, 0x00, 0x00,       // Length = 0
+        0x00,                   // Frame Type = DATA
+        0x01,                   // Flags = EndStream
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+    ]);
+
+    let mut header_table = HeaderTable::new(4096);
+    let frame = Frame::deserialize(&mut bytes, &mut header_table).unwrap();
+    assert_eq!(frame, Frame::Data(data_frame));
+}
+
+#[test]
+pub fn test_data_frame_flow_controlled_length() {
+    // The Pad Length octet and the padding count toward flow control.
+    let mut bytes: Vec<u8> = vec![
+        0x00, 0x00, 0x0e,       // Length = 14
+        0x00,                   // Frame Type = DATA
+        0x09,                   // Flags = [EndStream, Padded]
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+        0x08,                   // Pad Length = 8
+        0x48, 0x65, 0x6c, 0x6c,
+        0x6f,                   // Payload = "Hello"
+        0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, // Padding
+    ];
+
+    let mut header_table = HeaderTable::new(4096);
+    match Frame::deserialize(&mut bytes, &mut header_table).unwrap() {
+        Frame::Data(data_frame) => {
+            assert_eq!(data_frame.data, b"Hello".to_vec());
+            assert_eq!(data_frame.flow_controlled_length(), 14);
+        }
+        frame => panic!("Unexpected frame {:?}", frame),
+    }
+
+    // A padded frame without padding still counts its Pad Length octet.
+    let mut data_frame = DataFrame::end_of_stream(1);
+    data_frame.pad_length = Some(0);
+    assert_eq!(data_frame.flow_controlled_length(), 1);
+}
@@ -0,0 +1,363 @@
+#![cfg(feature = "codec")]
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::data::DataFrame;
+use http2::frame::decoder::FrameDecoder;
+use http2::frame::headers::HeadersFrame;
+use http2::frame::window_update::WindowUpdateFrame;
+use http2::frame::{defined_flags, iter_frames, DecodeStatus, Frame};
+use http2::header::field::HeaderField;
+use http2::header::lenience::LenienceConfig;
+use http2::header::list::HeaderList;
+use http2::header::table::HeaderTable;
+
+const BYTES: [u8; 51] = [
+    0x00, 0x00, 0x14, // Length = 20
+    0x01, // Frame Type = HEADERS
+    0x05, // Flags = [EndStream, EndHeaders]
+    0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+    0x82, 0x86, 0x84, 0x41, 0x0f, 0x77, 0x77, 0x77, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c, 0x65,
+    0x2e, 0x63, 0x6f, 0x6d,
+    // Payload =
+    // :method: GET
+    // :scheme: http
+    // :path: /
+    // :authority: www.example.com
+    0x00, 0x00, 0x0d, // Length = 13
+    0x00, // Frame Type = DATA
+    0x01, // Flags = EndStream
+    0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+    0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x2c, 0x20, 0x57, 0x6f, 0x72, 0x6c, 0x64,
+    0x21, // Payload  = "Hello, World!"
+];
+
+#[test]
+pub fn test_frame_decoder_arbitrary_chunks() {
+    for chunk_size in 1..BYTES.len() {
+        let mut frame_decoder = FrameDecoder::new(4096);
+        let mut frames: Vec<Frame> = Vec::new();
+
+        for chunk in BYTES.chunks(chunk_size) {
+            frame_decoder.feed(chunk);
+            while let Some(frame) = frame_decoder.next_frame().unwrap() {
+                frames.push(frame);
+            }
+        }
+
+        assert_eq!(frames.len(), 2);
+        assert!(matches!(frames[0], Frame::Headers(_)));
+        assert!(matches!(frames[1], Frame::Data(_)));
+        assert_eq!(frame_decoder.buffered(), 0);
+    }
+}
+
+#[test]
+pub fn test_frame_decoder_partial_frame() {
+    let mut frame_decoder = FrameDecoder::new(4096);
+
+    // Header and part of the payload of the first frame.
+    frame_decoder.feed(&BYTES[..12]);
+    assert!(frame_decoder.next_frame().unwrap().is_none());
+    assert_eq!(frame_decoder.buffered(), 12);
+
+    // Rest of the first frame and header of the second one.
+    frame_decoder.feed(&BYTES[12..38]);
+    assert_eq!(frame_decoder.by_ref().count(), 1);
+    assert_eq!(frame_decoder.buffered(), 9);
+
+    frame_decoder.feed(&BYTES[38..]);
+    assert_eq!(frame_decoder.by_ref().count(), 1);
+}
+
+#[test]
+pub fn test_frame_decoder_split_points() {
+    // Every pair of read boundaries, splitting frame headers and payloads.
+    for first in 0..=BYTES.len() {
+        for second in first..=BYTES.len() {
+            let mut frame_decoder = FrameDecoder::new(4096);
+            let mut frames: Vec<Frame> = Vec::new();
+
+            for chunk in [&BYTES[..first], &BYTES[first..second], &BYTES[second..]] {
+                frame_decoder.feed(chunk);
+                frames.extend(frame_decoder.by_ref().map(Result::unwrap));
+            }
+
+            assert_eq!(frames.len(), 2);
+            assert!(matches!(frames[0], Frame::Headers(_)));
+            assert!(matches!(frames[1], Frame::Data(_)));
+        }
+    }
+}
+
+#[test]
+pub fn test_frame_decoder_need_more_data() {
+    let mut frame_decoder = FrameDecoder::new(4096);
+    assert_eq!(
+        frame_decoder.decode().unwrap(),
+        DecodeStatus::NeedMoreData(9)
+    );
+
+    // Part of the first frame header.
+    frame_decoder.feed(&BYTES[..4]);
+    assert_eq!(
+        frame_decoder.decode().unwrap(),
+        DecodeStatus::NeedMoreData(5)
+    );
+
+    // Rest of the header and part of the payload of the first frame.
+    frame_decoder.feed(&BYTES[4..12]);
+    assert_eq!(
+        frame_decoder.decode().unwrap(),
+        DecodeStatus::NeedMoreData(17)
+    );
+
+    // Rest of the first frame and part of the header of the second one.
+    frame_decoder.feed(&BYTES[12..31]);
+    match frame_decoder.decode().unwrap() {
+        DecodeStatus::Complete((frame_header, Frame::Headers(_))) => {
+            assert_eq!(frame_header.payload_length(), 20)
+        }
+        status => panic!("Unexpected status {:?}", status),
+    }
+    assert_eq!(
+        frame_decoder.decode().unwrap(),
+        DecodeStatus::NeedMoreData(7)
+    );
+
+    frame_decoder.feed(&BYTES[31..]);
+    assert!(matches!(
+        frame_decoder.decode().unwrap(),
+        DecodeStatus::Complete((_, Frame::Data(_)))
+    ));
+    assert_eq!(
+        frame_decoder.decode().unwrap(),
+        DecodeStatus::NeedMoreData(9)
+    );
+}
+
+#[test]
+pub fn test_frame_decode_need_more_data() {
+    let mut header_table = HeaderTable::new(4096);
+
+    // The stream is left untouched until the first frame is complete.
+    for length in 0..29 {
+        let mut stream = BYTES[..length].to_vec();
+        let missing = if length < 9 { 9 - length } else { 29 - length };
+        assert_eq!(
+            Frame::decode(&mut stream, &mut header_table).unwrap(),
+            DecodeStatus::NeedMoreData(missing)
+        );
+        assert_eq!(stream, BYTES[..length]);
+
+        match Frame::deserialize(&mut stream, &mut header_table) {
+            Err(Http2Error::NotEnoughBytes(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    let mut stream = BYTES.to_vec();
+    assert!(matches!(
+        Frame::decode(&mut stream, &mut header_table).unwrap(),
+        DecodeStatus::Complete(Frame::Headers(_))
+    ));
+    assert_eq!(stream, BYTES[29..]);
+
+    // Invalid frames are errors, not a lack of bytes.
+    let mut stream = vec![0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    match Frame::decode(&mut stream, &mut header_table) {
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_frame_decoder_max_frame_size() {
+    let mut frame_decoder = FrameDecoder::new(4096);
+    assert_eq!(frame_decoder.max_frame_size(), 16_384);
+
+    // A frame header announcing a payload of 16385 bytes is rejected
+    // before any of the payload is received.
+    frame_decoder.feed(&[
+        0x00, 0x40, 0x01, // Length = 16385
+        0x00, // Frame Type = DATA
+        0x00, // Flags = []
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+    ]);
+    match frame_decoder.next_frame() {
+        Err(Http2Error::ConnectionError(ErrorCode::FrameSizeError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // The limit can be raised up to 2^24-1.
+    assert!(frame_decoder.set_max_frame_size(16_383).is_err());
+    assert!(frame_decoder.set_max_frame_size(16_777_216).is_err());
+    frame_decoder.set_max_frame_size(16_777_215).unwrap();
+
+    let mut frame_decoder = FrameDecoder::new(4096);
+    frame_decoder.set_max_frame_size(32_768).unwrap();
+    frame_decoder.feed(&[0x00, 0x40, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01]);
+    frame_decoder.feed(&vec![0x00; 16_385]);
+    assert!(matches!(
+        frame_decoder.next_frame(),
+        Ok(Some(Frame::Data(_)))
+    ));
+}
+
+#[test]
+pub fn test_frame_decoder_stream_id() {
+    // Frame type, stream identifier, payload and whether it is valid.
+    let cases: [(u8, u8, &[u8], bool); 15] = [
+        (0x0, 0, b"data", false),                         // DATA
+        (0x0, 1, b"data", true),                          // DATA
+        (0x1, 0, &[0x82], false),                         // HEADERS
+        (0x2, 0, &[0x00, 0x00, 0x00, 0x00, 0x0f], false), // PRIORITY
+        (0x3, 0, &[0x00, 0x00, 0x00, 0x08], false),       // RST_STREAM
+        (0x4, 1, &[], false),                             // SETTINGS
+        (0x4, 0, &[], true),                              // SETTINGS
+        (0x5, 0, &[0x00, 0x00, 0x00, 0x02, 0x82], false), // PUSH_PROMISE
+        (0x5, 2, &[0x00, 0x00, 0x00, 0x04, 0x82], false), // PUSH_PROMISE
+        (0x5, 1, &[0x00, 0x00, 0x00, 0x02, 0x82], true),  // PUSH_PROMISE
+        (0x6, 1, &[0x00; 8], false),                      // PING
+        (0x7, 1, &[0x00; 8], false),                      // GOAWAY
+        (0x8, 0, &[0x00, 0x00, 0x00, 0x01], true),        // WINDOW_UPDATE
+        (0x8, 1, &[0x00, 0x00, 0x00, 0x01], true),        // WINDOW_UPDATE
+        (0x9, 0, &[0x82], false),                         // CONTINUATION
+    ];
+
+    for (frame_type, stream_id, payload, valid) in cases {
+        let mut frame_decoder = FrameDecoder::new(4096);
+        let flags = if frame_type == 0x5 { 0x04 } else { 0x00 };
+        frame_decoder.feed(&[0x00, 0x00, payload.len() as u8, frame_type, flags]);
+        frame_decoder.feed(&[0x00, 0x00, 0x00, stream_id]);
+        frame_decoder.feed(payload);
+
+        match frame_decoder.next_frame() {
+            Ok(Some(_)) if valid => (),
+            Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) if !valid => (),
+            result => panic!(
+                "Unexpected result {:?} for frame type {} on stream {}",
+                result, frame_type, stream_id
+            ),
+        }
+
+        // A tolerant decoder accepts frames on any stream, a CONTINUATION
+        // frame still needs a header block to continue.
+        let mut frame_decoder = FrameDecoder::new(4096);
+        frame_decoder
+            .header_table()
+            .set_lenience(LenienceConfig::tolerant());
+        frame_decoder.feed(&[0x00, 0x00, payload.len() as u8, frame_type, flags]);
+        frame_decoder.feed(&[0x00, 0x00, 0x00, stream_id]);
+        frame_decoder.feed(payload);
+        let continuation = frame_type == 0x9;
+        match frame_decoder.next_frame() {
+            Ok(Some(_)) if !continuation => (),
+            Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) if continuation => (),
+            result => panic!(
+                "Unexpected result {:?} for frame type {} on stream {}",
+                result, frame_type, stream_id
+            ),
+        }
+    }
+}
+
+#[test]
+pub fn test_frame_decoder_unknown_bits() {
+    assert_eq!(defined_flags(0x0), 0x09);
+    assert_eq!(defined_flags(0x1), 0x2d);
+    assert_eq!(defined_flags(0x8), 0x00);
+    assert_eq!(defined_flags(0xff), 0x00);
+
+    let mut frame_decoder = FrameDecoder::new(4096);
+    frame_decoder.feed(&[
+        0x00, 0x00, 0x04, // Length = 4
+        0x00, // Frame Type = DATA
+        0xa3, // Flags = [EndStream, 0x02, 0x20, 0x80]
+        0x80, 0x00, 0x00, 0x01, // Reserved = 1, Stream Identifier = 1
+        0x64, 0x61, 0x74, 0x61, // Data = "data"
+        0x00, 0x00, 0x04, // Length = 4
+        0x08, // Frame Type = WINDOW_UPDATE
+        0xff, // Flags = [0x01, ..., 0x80]
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x00, 0x00, 0x00, 0x01, // Window Size Increment = 1
+    ]);
+
+    // Unknown flags and the reserved bit are ignored by the decoded frame,
+    // and surfaced by the frame header.
+    let (frame_header, frame) = frame_decoder.next_frame_with_header().unwrap().unwrap();
+    assert_eq!(
+        frame,
+        Frame::Data(DataFrame::new(1, true, b"data".to_vec()))
+    );
+    assert_eq!(frame_header.frame_flags(), 0xa3);
+    assert_eq!(frame_header.unknown_flags(), 0xa2);
+    assert!(frame_header.reserved());
+    assert_eq!(frame_header.stream_id(), 1);
+
+    assert_eq!(
+        frame_decoder.next_frame().unwrap(),
+        Some(Frame::WindowUpdate(WindowUpdateFrame::new(0, 1)))
+    );
+}
+
+#[test]
+pub fn test_frame_decoder_serialize_into() {
+    let header_list = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new(":authority".into(), "www.example.com".into()),
+    ]);
+    let frames = [
+        Frame::Headers(HeadersFrame::new(1, true, true, None, header_list)),
+        Frame::Data(DataFrame::new(1, true, b"Hello, World!".to_vec())),
+    ];
+
+    // The frames are serialized one after the other in the same buffer.
+    let mut header_table = HeaderTable::new(4096);
+    let mut bytes: Vec<u8> = Vec::new();
+    for frame in &frames {
+        frame.serialize_into(&mut header_table, &mut bytes).unwrap();
+    }
+    assert_eq!(bytes, BYTES);
+
+    let mut frame_decoder = FrameDecoder::new(4096);
+    frame_decoder.feed(&bytes);
+    let decoded_frames: Result<Vec<Frame>, Http2Error> = frame_decoder.collect();
+    assert_eq!(decoded_frames.unwrap(), frames);
+}
+
+#[test]
+pub fn test_iter_frames() {
+    let mut header_table = HeaderTable::new(4096);
+    let frames: Vec<Frame> = iter_frames(&BYTES, &mut header_table)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(frames.len(), 2);
+    assert!(matches!(frames[0], Frame::Headers(_)));
+    assert!(matches!(&frames[1], Frame::Data(data_frame) if data_frame.data == b"Hello, World!"));
+    assert_eq!(header_table.dynamic_entries().count(), 1);
+
+    // A truncated last frame ends the iteration with an error.
+    let mut header_table = HeaderTable::new(4096);
+    let mut frames = iter_frames(&BYTES[..40], &mut header_table);
+    assert!(matches!(frames.next(), Some(Ok(Frame::Headers(_)))));
+    match frames.next() {
+        Some(Err(Http2Error::NotEnoughBytes(_))) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(frames.next().is_none());
+
+    // So does an invalid frame.
+    let mut header_table = HeaderTable::new(4096);
+    let bytes = [0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00];
+    let mut frames = iter_frames(&bytes, &mut header_table);
+    match frames.next() {
+        Some(Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(frames.next().is_none());
+
+    let mut header_table = HeaderTable::new(4096);
+    assert_eq!(iter_frames(&[], &mut header_table).count(), 0);
+}
@@ -1,3 +1,7 @@
+#![cfg(feature = "codec")]
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::go_away::{GoAwayFrame, MAX_DEBUG_DATA_LENGTH};
 use http2::{frame::Frame, header::table::HeaderTable};
 
 #[test]
@@ -7,7 +11,7 @@ pub fn test_goaway_frame() {
         0x00, 0x00, 0x15, // Length = 21
         0x07, // Frame Type = GOAWAY
         0x00, // Flags = None
-        0x00, 0x00, 0x00, 0x06, // Stream Identifier = 6
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
         0x00, 0x00, 0x00, 0x05, // Last Stream Identifier = 5
         0x00, 0x00, 0x00, 0x01, // Error Code = 1
         0x48, 0x65, 0x6c, 0x6c, 0x6f, 0x2c, 0x20, 0x57, 0x6f, 0x72, 0x6c, 0x64,
@@ -18,3 +22,65 @@ pub fn test_goaway_frame() {
     let frame = Frame::deserialize(&mut bytes, &mut header_table).unwrap();
     println!("{}", frame);
 }
+
+#[test]
+pub fn test_goaway_frame_new() {
+    let go_away_frame = GoAwayFrame::new(5, ErrorCode::EnhanceYourCalm, b"slow down".to_vec());
+    assert_eq!(go_away_frame.last_stream_id(), 5);
+    assert_eq!(go_away_frame.error_code(), 0xb);
+    assert_eq!(go_away_frame.debug_data(), b"slow down");
+
+    let mut bytes = go_away_frame.serialize();
+    assert_eq!(
+        bytes[..9],
+        [0x00, 0x00, 0x11, 0x07, 0x00, 0x00, 0x00, 0x00, 0x00]
+    );
+    assert_eq!(
+        Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)).unwrap(),
+        Frame::GoAway(go_away_frame)
+    );
+
+    // Without debug data.
+    let go_away_frame = GoAwayFrame::new(0, ErrorCode::NoError, Vec::new());
+    assert_eq!(go_away_frame.debug_data(), b"");
+    assert_eq!(go_away_frame.serialize().len(), 17);
+}
+
+#[test]
+pub fn test_goaway_frame_display() {
+    // Debug data is shown byte for byte, even when it is not UTF-8.
+    let go_away_frame = GoAwayFrame::new(1, ErrorCode::ProtocolError, vec![0x4f, 0x4b, 0xff, 0x00]);
+    assert_eq!(
+        go_away_frame.to_string(),
+        "GO_AWAY\nReserved: false\nLast Stream ID: 1\nError Code: 1\nDebug Data: 4f4bff00\n"
+    );
+
+    let go_away_frame = GoAwayFrame::new(1, ErrorCode::NoError, Vec::new());
+    assert!(go_away_frame.to_string().ends_with("Debug Data: None\n"));
+}
+
+#[test]
+pub fn test_goaway_frame_debug_data_length() {
+    // Debug data beyond the maximum length is dropped.
+    let debug_data = vec![0x2a; MAX_DEBUG_DATA_LENGTH + 100];
+    let mut bytes = GoAwayFrame::new(3, ErrorCode::NoError, debug_data).serialize();
+    match Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)).unwrap() {
+        Frame::GoAway(go_away_frame) => {
+            assert_eq!(go_away_frame.debug_data(), [0x2a; MAX_DEBUG_DATA_LENGTH])
+        }
+        frame => panic!("Unexpected frame {:?}", frame),
+    }
+
+    // A payload too short for the error code is a FRAME_SIZE_ERROR.
+    let mut bytes: Vec<u8> = vec![
+        0x00, 0x00, 0x04, // Length = 4
+        0x07, // Frame Type = GOAWAY
+        0x00, // Flags = None
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x00, 0x00, 0x00, 0x05, // Last Stream Identifier = 5
+    ];
+    match Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)) {
+        Err(Http2Error::ConnectionError(ErrorCode::FrameSizeError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
@@ -0,0 +1,106 @@
+#![cfg(feature = "codec")]
+
+use http2::error::Http2Error;
+use http2::frame::{FrameHeader, FRAME_HEADER_LENGTH, MAX_FRAME_SIZE_UPPER_BOUND, MAX_STREAM_ID};
+
+#[test]
+pub fn test_frame_header_serialize() {
+    let frame_header = FrameHeader::builder(0x1)
+        .payload_length(0x010203)
+        .frame_flags(0x25)
+        .stream_id(0x0a0b0c0d)
+        .build()
+        .unwrap();
+
+    assert_eq!(
+        frame_header.serialize(),
+        vec![
+            0x01, 0x02, 0x03, // Length = 66051
+            0x01, // Frame Type = HEADERS
+            0x25, // Flags = [EndStream, EndHeaders, Priority]
+            0x0a, 0x0b, 0x0c, 0x0d, // Stream Identifier = 168496141
+        ]
+    );
+
+    // The reserved bit is the most significant bit of the stream identifier.
+    let frame_header = FrameHeader::builder(0x8)
+        .reserved(true)
+        .stream_id(1)
+        .build()
+        .unwrap();
+    assert_eq!(frame_header.to_bytes()[5..], [0x80, 0x00, 0x00, 0x01]);
+    assert!(frame_header.reserved());
+    assert_eq!(frame_header.stream_id(), 1);
+}
+
+#[test]
+pub fn test_frame_header_round_trip() {
+    let payload_lengths = [0, 1, 16_384, MAX_FRAME_SIZE_UPPER_BOUND];
+    let stream_ids = [0, 1, 2, MAX_STREAM_ID];
+
+    for frame_type in 0..=u8::MAX {
+        for frame_flags in [0x00, 0x01, 0x08, 0xff] {
+            for payload_length in payload_lengths {
+                for stream_id in stream_ids {
+                    for reserved in [false, true] {
+                        let frame_header = FrameHeader::builder(frame_type)
+                            .payload_length(payload_length)
+                            .frame_flags(frame_flags)
+                            .reserved(reserved)
+                            .stream_id(stream_id)
+                            .build()
+                            .unwrap();
+                        assert_eq!(
+                            frame_header,
+                            FrameHeader::new(
+                                payload_length,
+                                frame_type,
+                                frame_flags,
+                                reserved,
+                                stream_id
+                            )
+                        );
+
+                        let bytes = frame_header.to_bytes();
+                        assert_eq!(FrameHeader::from_bytes(&bytes), frame_header);
+
+                        let mut bytes = frame_header.serialize();
+                        bytes.push(0x2a);
+                        assert_eq!(FrameHeader::deserialize(&mut bytes).unwrap(), frame_header);
+                        assert_eq!(bytes, vec![0x2a]);
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+pub fn test_frame_header_validation() {
+    // The payload length is encoded on 24 bits.
+    match FrameHeader::builder(0x0)
+        .payload_length(MAX_FRAME_SIZE_UPPER_BOUND + 1)
+        .stream_id(1)
+        .build()
+    {
+        Err(Http2Error::FrameError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // The stream identifier is encoded on 31 bits.
+    match FrameHeader::builder(0x0)
+        .stream_id(MAX_STREAM_ID + 1)
+        .build()
+    {
+        Err(Http2Error::FrameError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // A truncated frame header is left untouched.
+    let mut bytes = vec![0x00; FRAME_HEADER_LENGTH - 1];
+    assert!(matches!(
+        FrameHeader::deserialize(&mut bytes),
+        Err(Http2Error::NotEnoughBytes(_))
+    ));
+    assert_eq!(bytes.len(), FRAME_HEADER_LENGTH - 1);
+}
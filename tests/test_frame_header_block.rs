@@ -0,0 +1,120 @@
+#![cfg(feature = "codec")]
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::decoder::FrameDecoder;
+use http2::frame::header_block::HeaderBlockGuard;
+use http2::frame::FrameHeader;
+
+/// Build the header of a frame.
+fn frame_header(
+    payload_length: u32,
+    frame_type: u8,
+    frame_flags: u8,
+    stream_id: u32,
+) -> FrameHeader {
+    FrameHeader::new(payload_length, frame_type, frame_flags, false, stream_id)
+}
+
+#[test]
+pub fn test_header_block_guard_continuation_frames() {
+    let mut guard = HeaderBlockGuard::new(2, 1_000);
+
+    // HEADERS without END_HEADERS followed by two CONTINUATION frames.
+    guard.check(&frame_header(10, 0x1, 0x0, 1)).unwrap();
+    assert!(guard.in_progress());
+    guard.check(&frame_header(10, 0x9, 0x0, 1)).unwrap();
+    guard.check(&frame_header(10, 0x9, 0x4, 1)).unwrap();
+    assert!(!guard.in_progress());
+
+    // A third CONTINUATION frame is rejected.
+    guard.check(&frame_header(10, 0x5, 0x0, 1)).unwrap();
+    guard.check(&frame_header(10, 0x9, 0x0, 1)).unwrap();
+    guard.check(&frame_header(10, 0x9, 0x0, 1)).unwrap();
+    assert!(matches!(
+        guard.check(&frame_header(10, 0x9, 0x4, 1)),
+        Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _))
+    ));
+}
+
+#[test]
+pub fn test_header_block_guard_size() {
+    let mut guard = HeaderBlockGuard::new(10, 100);
+
+    // A single HEADERS frame may not exceed the limit.
+    assert!(matches!(
+        guard.check(&frame_header(101, 0x1, 0x4, 1)),
+        Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _))
+    ));
+
+    // Nor may the whole header block.
+    let mut guard = HeaderBlockGuard::new(10, 100);
+    guard.check(&frame_header(60, 0x1, 0x0, 1)).unwrap();
+    assert!(matches!(
+        guard.check(&frame_header(41, 0x9, 0x4, 1)),
+        Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _))
+    ));
+}
+
+#[test]
+pub fn test_header_block_guard_interleaving() {
+    // Frames other than CONTINUATION interrupt the header block.
+    let mut guard = HeaderBlockGuard::default();
+    guard.check(&frame_header(10, 0x1, 0x0, 1)).unwrap();
+    assert!(matches!(
+        guard.check(&frame_header(8, 0x6, 0x0, 0)),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+
+    // CONTINUATION frames must be on the stream of the header block.
+    let mut guard = HeaderBlockGuard::default();
+    guard.check(&frame_header(10, 0x1, 0x0, 1)).unwrap();
+    assert!(matches!(
+        guard.check(&frame_header(10, 0x9, 0x4, 3)),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+
+    // CONTINUATION frames must follow a header block.
+    let mut guard = HeaderBlockGuard::default();
+    assert!(matches!(
+        guard.check(&frame_header(10, 0x9, 0x4, 1)),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+
+    // Other frames are ignored outside of a header block.
+    let mut guard = HeaderBlockGuard::default();
+    guard.check(&frame_header(8, 0x6, 0x0, 0)).unwrap();
+    guard.check(&frame_header(10, 0x0, 0x1, 1)).unwrap();
+}
+
+#[test]
+pub fn test_frame_decoder_continuation_flood() {
+    let mut frame_decoder = FrameDecoder::new(4096);
+    frame_decoder
+        .header_block_guard()
+        .set_max_continuation_frames(1);
+
+    frame_decoder.feed(&[
+        0x00, 0x00, 0x01, // Length = 1
+        0x01, // Frame Type = HEADERS
+        0x00, // Flags = []
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+        0x82, // :method: GET
+        0x00, 0x00, 0x01, // Length = 1
+        0x09, // Frame Type = CONTINUATION
+        0x00, // Flags = []
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+        0x86, // :scheme: http
+        0x00, 0x00, 0x01, // Length = 1
+        0x09, // Frame Type = CONTINUATION
+        0x00, // Flags = []
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+    ]);
+    assert!(frame_decoder.next_frame().unwrap().is_some());
+    assert!(frame_decoder.next_frame().unwrap().is_some());
+
+    // The second CONTINUATION frame is rejected from its header.
+    assert!(matches!(
+        frame_decoder.next_frame(),
+        Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _))
+    ));
+}
@@ -1,4 +1,13 @@
-use http2::{frame::Frame, header::table::HeaderTable};
+#![cfg(feature = "codec")]
+
+use std::collections::HashSet;
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::headers::HeadersFrame;
+use http2::frame::{Frame, FrameFlag, FramePriority};
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::header::table::HeaderTable;
 
 #[test]
 pub fn test_headers_frame() {
@@ -25,3 +34,275 @@ pub fn test_headers_frame() {
     let frame = Frame::deserialize(&mut bytes, &mut header_table).unwrap();
     println!("{}", frame);
 }
+
+#[test]
+pub fn test_headers_frame_pass_through() {
+    let bytes: Vec<u8> = vec![
+        0x00, 0x00, 0x14, // Length = 20
+        0x01, // Frame Type = HEADERS
+        0x05, // Flags = [End Stream, End Headers]
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+        // Payload =
+        // :method: GET
+        // :scheme: http
+        // :path: /
+        // :authority: www.example.com (incremental indexing)
+        0x82, 0x86, 0x84, 0x41, 0x0f, 0x77, 0x77, 0x77, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c,
+        0x65, 0x2e, 0x63, 0x6f, 0x6d,
+    ];
+
+    // The header block is kept opaque and the dynamic table untouched.
+    let mut relay_table = HeaderTable::new(4096);
+    relay_table.set_pass_through(true);
+    assert!(relay_table.pass_through());
+    let frame = Frame::deserialize(&mut bytes.clone(), &mut relay_table).unwrap();
+    assert_eq!(relay_table.dynamic_entries().count(), 0);
+
+    // The frame is forwarded without re-encoding its header block.
+    let mut forward_table = HeaderTable::new(4096);
+    assert_eq!(frame.serialize(&mut forward_table).unwrap(), bytes);
+    assert_eq!(forward_table.dynamic_entries().count(), 0);
+
+    let mut headers_frame = match frame {
+        Frame::Headers(headers_frame) => headers_frame,
+        frame => panic!("Unexpected frame {:?}", frame),
+    };
+    assert_eq!(headers_frame.header_block_fragment(), Some(&bytes[9..]));
+    assert!(headers_frame.header_list().is_empty());
+
+    // The header block can be decoded later with the connection table.
+    let mut header_table = HeaderTable::new(4096);
+    let decoded = Frame::deserialize(&mut bytes.clone(), &mut header_table).unwrap();
+    let mut lazy_table = HeaderTable::new(4096);
+    headers_frame.decode_header_block(&mut lazy_table).unwrap();
+    assert_eq!(headers_frame.header_block_fragment(), None);
+    assert_eq!(Frame::Headers(headers_frame), decoded);
+    assert_eq!(lazy_table.dynamic_entries().count(), 1);
+}
+
+#[test]
+pub fn test_headers_frame_append_continuation() {
+    let header_block: Vec<u8> = vec![
+        0x82, 0x86, 0x84, 0x41, 0x0f, 0x77, 0x77, 0x77, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c,
+        0x65, 0x2e, 0x63, 0x6f, 0x6d,
+    ];
+
+    // The :authority literal is split between the HEADERS frame and the
+    // CONTINUATION frame.
+    let mut bytes: Vec<u8> = vec![
+        0x00, 0x00, 0x08, // Length = 8
+        0x01, // Frame Type = HEADERS
+        0x01, // Flags = [End Stream]
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+    ];
+    bytes.extend_from_slice(&header_block[..8]);
+    bytes.extend_from_slice(&[
+        0x00, 0x00, 0x0c, // Length = 12
+        0x09, // Frame Type = CONTINUATION
+        0x04, // Flags = [End Headers]
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+    ]);
+    bytes.extend_from_slice(&header_block[8..]);
+
+    let mut relay_table = HeaderTable::new(4096);
+    relay_table.set_pass_through(true);
+    let mut headers_frame = match Frame::deserialize(&mut bytes, &mut relay_table).unwrap() {
+        Frame::Headers(headers_frame) => headers_frame,
+        frame => panic!("Unexpected frame {:?}", frame),
+    };
+    let continuation_frame = match Frame::deserialize(&mut bytes, &mut relay_table).unwrap() {
+        Frame::Continuation(continuation_frame) => continuation_frame,
+        frame => panic!("Unexpected frame {:?}", frame),
+    };
+
+    // The header block is decoded at once.
+    assert!(!headers_frame.end_headers());
+    headers_frame
+        .append_continuation(&continuation_frame)
+        .unwrap();
+    assert!(headers_frame.end_headers());
+    assert_eq!(
+        headers_frame.header_block_fragment(),
+        Some(&header_block[..])
+    );
+    let mut header_table = HeaderTable::new(4096);
+    headers_frame
+        .decode_header_block(&mut header_table)
+        .unwrap();
+    assert_eq!(
+        headers_frame.header_list().authority(),
+        Some("www.example.com")
+    );
+
+    // The header block is already complete.
+    match headers_frame.append_continuation(&continuation_frame) {
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_headers_frame_from_header_list() {
+    let header_list = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new(":authority".into(), "www.example.com".into()),
+    ]);
+    let frame_priority = FramePriority::new(false, 1, 15);
+
+    // The header block is encoded when the frame is created.
+    let mut header_table = HeaderTable::new(4096);
+    let headers_frame = HeadersFrame::from_header_list(
+        3,
+        header_list.clone(),
+        true,
+        Some(frame_priority),
+        &mut header_table,
+    )
+    .unwrap();
+    assert_eq!(header_table.dynamic_entries().count(), 1);
+    assert_eq!(headers_frame.header_list(), &header_list);
+    assert!(headers_frame.end_stream());
+    assert!(headers_frame.end_headers());
+
+    // Serializing the frame leaves the header table untouched.
+    let expected = HeadersFrame::new(3, true, true, Some(frame_priority), header_list.clone())
+        .serialize(&mut HeaderTable::new(4096))
+        .unwrap();
+    let mut bytes = headers_frame.serialize(&mut header_table).unwrap();
+    assert_eq!(bytes, expected);
+    assert_eq!(header_table.dynamic_entries().count(), 1);
+
+    match Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)).unwrap() {
+        Frame::Headers(decoded) => {
+            assert_eq!(decoded.stream_id(), 3);
+            assert_eq!(decoded.header_list(), &header_list);
+            assert_eq!(decoded.frame_priority(), Some(&frame_priority));
+        }
+        frame => panic!("Unexpected frame {:?}", frame),
+    }
+}
+
+#[test]
+pub fn test_headers_frame_flags() {
+    // Header block fragment = :method: GET, :path: /
+    let header_block_fragment = [0x82, 0x84];
+    let header_list = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+
+    for flags in 0..16u8 {
+        let end_stream = flags & 0x1 != 0;
+        let end_headers = flags & 0x2 != 0;
+        let padded = flags & 0x4 != 0;
+        let priority = flags & 0x8 != 0;
+
+        let mut frame_flags = 0x00;
+        let mut payload: Vec<u8> = Vec::new();
+        if end_stream {
+            frame_flags |= 0x01;
+        }
+        if end_headers {
+            frame_flags |= 0x04;
+        }
+        if padded {
+            frame_flags |= 0x08;
+            payload.push(3); // Pad Length = 3
+        }
+        if priority {
+            frame_flags |= 0x20;
+            payload.extend([0x80, 0x00, 0x00, 0x01, 0x0f]); // Exclusive, Stream Dependency = 1, Weight = 15
+        }
+        payload.extend(header_block_fragment);
+        if padded {
+            payload.extend([0x00; 3]); // Padding
+        }
+        let mut bytes: Vec<u8> = vec![0x00, 0x00, payload.len() as u8, 0x01, frame_flags];
+        bytes.extend([0x00, 0x00, 0x00, 0x03]); // Stream Identifier = 3
+        bytes.extend(payload);
+
+        let frame = Frame::deserialize(&mut bytes.clone(), &mut HeaderTable::new(4096)).unwrap();
+        let headers_frame = match &frame {
+            Frame::Headers(headers_frame) => headers_frame,
+            frame => panic!("Unexpected frame {:?}", frame),
+        };
+        assert_eq!(headers_frame.stream_id(), 3);
+        assert_eq!(headers_frame.end_stream(), end_stream);
+        assert_eq!(headers_frame.end_headers(), end_headers);
+        assert_eq!(headers_frame.pad_length(), padded.then_some(3));
+        assert_eq!(
+            headers_frame.frame_priority(),
+            priority.then(|| FramePriority::new(true, 1, 15)).as_ref()
+        );
+        assert_eq!(headers_frame.header_list(), &header_list);
+        let frame_flags: HashSet<FrameFlag> = headers_frame.flags().into_iter().collect();
+        assert_eq!(frame_flags.contains(&FrameFlag::Padded), padded);
+        assert_eq!(frame_flags.contains(&FrameFlag::Priority), priority);
+        assert_eq!(frame.clone(), frame);
+        assert_eq!(frame.serialize(&mut HeaderTable::new(4096)).unwrap(), bytes);
+
+        // The fragment boundaries do not depend on the header table.
+        let mut relay_table = HeaderTable::new(4096);
+        relay_table.set_pass_through(true);
+        match Frame::deserialize(&mut bytes.clone(), &mut relay_table).unwrap() {
+            Frame::Headers(headers_frame) => assert_eq!(
+                headers_frame.header_block_fragment(),
+                Some(&header_block_fragment[..])
+            ),
+            frame => panic!("Unexpected frame {:?}", frame),
+        }
+    }
+}
+
+#[test]
+pub fn test_headers_frame_invalid_length() {
+    for (frame_flags, payload, error_code) in [
+        // Priority without room for its fields.
+        (
+            0x24,
+            vec![0x00, 0x00, 0x00, 0x01],
+            ErrorCode::FrameSizeError,
+        ),
+        (
+            0x2c,
+            vec![0x00, 0x00, 0x00, 0x00, 0x01],
+            ErrorCode::FrameSizeError,
+        ),
+        // Padding overlapping the priority.
+        (
+            0x2c,
+            vec![0x01, 0x00, 0x00, 0x00, 0x01, 0x0f],
+            ErrorCode::ProtocolError,
+        ),
+        (
+            0x2c,
+            vec![0x02, 0x00, 0x00, 0x00, 0x01, 0x0f, 0x00],
+            ErrorCode::ProtocolError,
+        ),
+    ] {
+        let mut bytes: Vec<u8> = vec![0x00, 0x00, payload.len() as u8, 0x01, frame_flags];
+        bytes.extend([0x00, 0x00, 0x00, 0x03]); // Stream Identifier = 3
+        bytes.extend(payload);
+
+        match Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)) {
+            Err(Http2Error::ConnectionError(code, _)) if code == error_code => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    // Padding filling the rest of the payload leaves an empty fragment.
+    let mut bytes: Vec<u8> = vec![
+        0x00, 0x00, 0x08, // Length = 8
+        0x01, // Frame Type = HEADERS
+        0x2c, // Flags = [End Headers, Padded, Priority]
+        0x00, 0x00, 0x00, 0x03, // Stream Identifier = 3
+        0x02, // Pad Length = 2
+        0x00, 0x00, 0x00, 0x01, 0x0f, // Stream Dependency = 1, Weight = 15
+        0x00, 0x00, // Padding
+    ];
+    match Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)).unwrap() {
+        Frame::Headers(headers_frame) => assert!(headers_frame.header_list().is_empty()),
+        frame => panic!("Unexpected frame {:?}", frame),
+    }
+}
@@ -0,0 +1,163 @@
+#![cfg(feature = "codec")]
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::data::DataFrame;
+use http2::frame::headers::HeadersFrame;
+use http2::frame::padding::Padding;
+use http2::frame::push_promise::PushPromiseFrame;
+use http2::frame::window_update::WindowUpdateFrame;
+use http2::frame::{Frame, FrameFlag, FramePriority};
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::header::table::HeaderTable;
+
+/// Build the request header list of RFC 7541 C.3.1.
+fn header_list() -> HeaderList {
+    HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new(":authority".into(), "www.example.com".into()),
+    ])
+}
+
+#[test]
+pub fn test_padding_pad_length() {
+    assert_eq!(Padding::default(), Padding::None);
+    assert_eq!(Padding::None.pad_length(), None);
+    assert_eq!(Padding::Fixed(7).pad_length(), Some(7));
+    assert_eq!(Padding::Random(0).pad_length(), Some(0));
+    for _ in 0..100 {
+        assert!(Padding::Random(16).pad_length().unwrap() <= 16);
+    }
+}
+
+#[test]
+pub fn test_padding_data_frame() {
+    let mut data_frame = DataFrame::new(1, true, b"Hello".to_vec());
+    let bytes = data_frame.serialize(Padding::Fixed(3));
+    assert_eq!(
+        bytes,
+        vec![
+            0x00, 0x00, 0x09, // Length = 9
+            0x00, // Frame Type = DATA
+            0x09, // Flags = [EndStream, Padded]
+            0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+            0x03, // Pad Length = 3
+            0x48, 0x65, 0x6c, 0x6c, 0x6f, // Data = "Hello"
+            0x00, 0x00, 0x00, // Padding
+        ]
+    );
+
+    // The decoded frame keeps its padding and is serialized as received.
+    let frame = Frame::deserialize(&mut bytes.clone(), &mut HeaderTable::new(4096)).unwrap();
+    data_frame.pad_length = Some(3);
+    assert_eq!(frame, Frame::Data(data_frame));
+    assert_eq!(frame.flags(), vec![FrameFlag::EndStream, FrameFlag::Padded]);
+    assert_eq!(frame.serialize(&mut HeaderTable::new(4096)).unwrap(), bytes);
+}
+
+#[test]
+pub fn test_padding_headers_frame() {
+    let mut headers_frame = HeadersFrame::new(
+        3,
+        false,
+        true,
+        Some(FramePriority::new(false, 1, 15)),
+        header_list(),
+    );
+    let bytes = headers_frame
+        .serialize_padded(&mut HeaderTable::new(4096), Padding::Fixed(4))
+        .unwrap();
+    assert_eq!(bytes[4], 0x2c); // Flags = [EndHeaders, Padded, Priority]
+    assert_eq!(bytes[9], 0x04); // Pad Length = 4
+    assert_eq!(bytes[10..15], [0x00, 0x00, 0x00, 0x01, 0x0f]); // Priority
+    assert!(bytes.ends_with(&[0x00; 4]));
+
+    let frame = Frame::deserialize(&mut bytes.clone(), &mut HeaderTable::new(4096)).unwrap();
+    headers_frame.set_pad_length(Some(4));
+    assert_eq!(frame, Frame::Headers(headers_frame));
+    assert_eq!(
+        frame.flags(),
+        vec![
+            FrameFlag::EndHeaders,
+            FrameFlag::Padded,
+            FrameFlag::Priority
+        ]
+    );
+    assert_eq!(frame.serialize(&mut HeaderTable::new(4096)).unwrap(), bytes);
+}
+
+#[test]
+pub fn test_padding_push_promise_frame() {
+    let mut push_promise_frame = PushPromiseFrame::new(1, true, 2, header_list());
+    let frame = Frame::PushPromise(PushPromiseFrame::new(1, true, 2, header_list()));
+    let bytes = frame
+        .serialize_padded(&mut HeaderTable::new(4096), Padding::Fixed(10))
+        .unwrap();
+    assert_eq!(bytes[4], 0x0c); // Flags = [EndHeaders, Padded]
+    assert_eq!(bytes[9], 0x0a); // Pad Length = 10
+    assert_eq!(bytes[10..14], [0x00, 0x00, 0x00, 0x02]); // Promised Stream ID = 2
+
+    let decoded = Frame::deserialize(&mut bytes.clone(), &mut HeaderTable::new(4096)).unwrap();
+    push_promise_frame.set_pad_length(Some(10));
+    assert_eq!(decoded, Frame::PushPromise(push_promise_frame));
+    assert_eq!(
+        decoded.flags(),
+        vec![FrameFlag::EndHeaders, FrameFlag::Padded]
+    );
+    assert_eq!(
+        decoded.serialize(&mut HeaderTable::new(4096)).unwrap(),
+        bytes
+    );
+
+    // Frames that cannot be padded ignore the padding policy.
+    let frame = Frame::WindowUpdate(WindowUpdateFrame::new(0, 1));
+    assert_eq!(
+        frame
+            .serialize_padded(&mut HeaderTable::new(4096), Padding::Fixed(10))
+            .unwrap(),
+        frame.serialize(&mut HeaderTable::new(4096)).unwrap()
+    );
+}
+
+#[test]
+pub fn test_padding_zero_length() {
+    let mut bytes: Vec<u8> = vec![
+        0x00, 0x00, 0x06, // Length = 6
+        0x00, // Frame Type = DATA
+        0x08, // Flags = [Padded]
+        0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+        0x00, // Pad Length = 0
+        0x48, 0x65, 0x6c, 0x6c, 0x6f, // Data = "Hello"
+    ];
+
+    let frame = Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)).unwrap();
+    let mut data_frame = DataFrame::new(1, false, b"Hello".to_vec());
+    data_frame.pad_length = Some(0);
+    assert_eq!(frame, Frame::Data(data_frame));
+}
+
+#[test]
+pub fn test_padding_invalid_length() {
+    for (frame_type, payload) in [
+        (0x00, vec![]),                             // DATA without a Pad Length
+        (0x00, vec![0x01]),                         // DATA with padding as long as the payload
+        (0x00, vec![0x05, 0x48, 0x00, 0x00]),       // DATA with padding longer than the payload
+        (0x01, vec![0x04, 0x82, 0x00, 0x00]),       // HEADERS with padding as long as the payload
+        (0x05, vec![0xff, 0x00, 0x00, 0x00, 0x02]), // PUSH_PROMISE with oversized padding
+    ] {
+        let mut bytes: Vec<u8> = vec![
+            0x00, 0x00, payload.len() as u8, // Length
+            frame_type, // Frame Type
+            0x0c,       // Flags = [EndHeaders, Padded]
+            0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+        ];
+        bytes.extend(payload);
+
+        match Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)) {
+            Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+}
@@ -1,4 +1,9 @@
-use http2::{frame::Frame, header::table::HeaderTable};
+#![cfg(feature = "codec")]
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::ping::PingFrame;
+use http2::frame::{Frame, FrameFlag};
+use http2::header::table::HeaderTable;
 
 #[test]
 pub fn test_ping_frame() {
@@ -7,7 +12,7 @@ pub fn test_ping_frame() {
         0x00, 0x00, 0x08, // Length = 8
         0x06, // Frame Type = PING
         0x01, // Flags = Ack
-        0x00, 0x00, 0x00, 0x08, // Stream Identifier = 8
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
         0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x01, // Opaque Data = 1
     ];
 
@@ -15,3 +20,51 @@ pub fn test_ping_frame() {
     let frame = Frame::deserialize(&mut bytes, &mut header_table).unwrap();
     println!("{}", frame);
 }
+
+#[test]
+pub fn test_ping_frame_new() {
+    let opaque_data = [0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08];
+
+    let ping_frame = PingFrame::new(opaque_data);
+    assert!(!ping_frame.is_ack());
+    assert!(ping_frame.flags().is_empty());
+    assert_eq!(ping_frame.opaque_data(), &opaque_data);
+    assert_eq!(
+        ping_frame.serialize(),
+        vec![
+            0x00, 0x00, 0x08, // Length = 8
+            0x06, // Frame Type = PING
+            0x00, // Flags = None
+            0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+            0x01, 0x02, 0x03, 0x04, 0x05, 0x06, 0x07, 0x08, // Opaque Data
+        ]
+    );
+
+    let ping_frame = PingFrame::ack(opaque_data);
+    assert!(ping_frame.is_ack());
+    assert_eq!(ping_frame.flags(), vec![FrameFlag::Ack]);
+    let mut bytes = ping_frame.serialize();
+    assert_eq!(bytes[4], 0x01);
+    assert_eq!(
+        Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)).unwrap(),
+        Frame::Ping(ping_frame)
+    );
+}
+
+#[test]
+pub fn test_ping_frame_length() {
+    for length in [0u8, 7, 9] {
+        let mut bytes: Vec<u8> = vec![
+            0x00, 0x00, length, // Length
+            0x06,   // Frame Type = PING
+            0x00,   // Flags = None
+            0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        ];
+        bytes.extend(vec![0x00; length as usize]);
+
+        match Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)) {
+            Err(Http2Error::ConnectionError(ErrorCode::FrameSizeError, _)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+}
@@ -1,4 +1,8 @@
-use http2::{frame::Frame, header::table::HeaderTable};
+#![cfg(feature = "codec")]
+
+use http2::frame::priority::PriorityFrame;
+use http2::frame::{Frame, FramePriority};
+use http2::header::table::HeaderTable;
 
 #[test]
 pub fn test_priority_frame() {
@@ -16,3 +20,26 @@ pub fn test_priority_frame() {
     let frame = Frame::deserialize(&mut bytes, &mut header_table).unwrap();
     println!("{}", frame);
 }
+
+#[test]
+pub fn test_priority_frame_serialize() {
+    let priority_frame = PriorityFrame::new(3, FramePriority::new(true, 5, 3));
+    let bytes = priority_frame.serialize();
+    assert_eq!(
+        bytes,
+        vec![
+            0x00, 0x00, 0x05, // Length = 5
+            0x02, // Frame Type = PRIORITY
+            0x00, // Flags = None
+            0x00, 0x00, 0x00, 0x03, // Stream Identifier = 3
+            0x80, 0x00, 0x00, 0x05, // Exclusive, Stream Dependency = 5
+            0x03, // Weight = 3
+        ]
+    );
+
+    let mut header_table = HeaderTable::new(4096);
+    assert_eq!(
+        Frame::deserialize(&mut bytes.clone(), &mut header_table).unwrap(),
+        Frame::Priority(priority_frame)
+    );
+}
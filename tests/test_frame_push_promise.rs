@@ -1,4 +1,11 @@
-use http2::{frame::Frame, header::table::HeaderTable};
+#![cfg(feature = "codec")]
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::push_promise::PushPromiseFrame;
+use http2::frame::Frame;
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::header::table::HeaderTable;
 
 #[test]
 pub fn test_push_promise_frame() {
@@ -24,3 +31,99 @@ pub fn test_push_promise_frame() {
     let frame = Frame::deserialize(&mut bytes, &mut header_table).unwrap();
     println!("{}", frame);
 }
+
+#[test]
+pub fn test_push_promise_frame_flags() {
+    let header_list = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+
+    for (frame_flags, pad_length) in [(0x00, None), (0x04, None), (0x08, Some(2)), (0x0c, Some(0))]
+    {
+        let mut payload: Vec<u8> = Vec::new();
+        if let Some(pad_length) = pad_length {
+            payload.push(pad_length);
+        }
+        payload.extend([0x00, 0x00, 0x00, 0x02]); // Promised Stream ID = 2
+        payload.extend([0x82, 0x84]); // :method: GET, :path: /
+        payload.resize(payload.len() + pad_length.unwrap_or(0) as usize, 0x00);
+        let mut bytes: Vec<u8> = vec![0x00, 0x00, payload.len() as u8, 0x05, frame_flags];
+        bytes.extend([0x00, 0x00, 0x00, 0x01]); // Stream Identifier = 1
+        bytes.extend(payload);
+
+        let frame = Frame::deserialize(&mut bytes.clone(), &mut HeaderTable::new(4096)).unwrap();
+        let mut push_promise_frame =
+            PushPromiseFrame::new(1, frame_flags & 0x04 != 0, 2, header_list.clone());
+        push_promise_frame.set_pad_length(pad_length);
+        assert_eq!(frame, Frame::PushPromise(push_promise_frame));
+        assert_eq!(frame.serialize(&mut HeaderTable::new(4096)).unwrap(), bytes);
+    }
+
+    for (frame_flags, payload, error_code) in [
+        // No room for the promised stream identifier.
+        (0x04, vec![0x00, 0x00, 0x02], ErrorCode::FrameSizeError),
+        (
+            0x0c,
+            vec![0x00, 0x00, 0x00, 0x02],
+            ErrorCode::FrameSizeError,
+        ),
+        // Padding overlapping the promised stream identifier.
+        (
+            0x0c,
+            vec![0x01, 0x00, 0x00, 0x00, 0x02],
+            ErrorCode::ProtocolError,
+        ),
+    ] {
+        let mut bytes: Vec<u8> = vec![0x00, 0x00, payload.len() as u8, 0x05, frame_flags];
+        bytes.extend([0x00, 0x00, 0x00, 0x01]); // Stream Identifier = 1
+        bytes.extend(payload);
+
+        match Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)) {
+            Err(Http2Error::ConnectionError(code, _)) if code == error_code => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+}
+
+#[test]
+pub fn test_push_promise_frame_split_header_block() {
+    let header_list = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new("x-large".into(), "a".repeat(20_000).as_str().into()),
+    ]);
+    let mut encoder_table = HeaderTable::new(4096);
+    let header_block = header_list.encode(&mut HeaderTable::new(4096)).unwrap();
+
+    // The promised stream identifier shares the payload of the first frame.
+    let mut push_promise_frame = PushPromiseFrame::new(1, true, 2, header_list.clone());
+    let continuation_frames = push_promise_frame
+        .split_header_block(16_384, &mut encoder_table)
+        .unwrap();
+    assert!(!push_promise_frame.end_headers());
+    assert_eq!(
+        push_promise_frame.header_block_fragment().unwrap().len(),
+        16_380
+    );
+    assert_eq!(continuation_frames.len(), 1);
+    assert!(continuation_frames[0].end_headers());
+
+    // The fragments join into the header block.
+    let fragments: Vec<u8> = push_promise_frame
+        .header_block_fragment()
+        .into_iter()
+        .chain(continuation_frames[0].header_block_fragment())
+        .flatten()
+        .copied()
+        .collect();
+    assert_eq!(fragments, header_block);
+
+    // A small header block fits in the frame.
+    let mut push_promise_frame = PushPromiseFrame::new(1, false, 4, HeaderList::new(vec![]));
+    assert!(push_promise_frame
+        .split_header_block(16_384, &mut encoder_table)
+        .unwrap()
+        .is_empty());
+    assert!(push_promise_frame.end_headers());
+}
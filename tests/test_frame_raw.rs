@@ -0,0 +1,66 @@
+#![cfg(feature = "codec")]
+
+use http2::error::Http2Error;
+use http2::frame::headers::HeadersFrame;
+use http2::frame::ping::PingFrame;
+use http2::frame::raw::RawFrame;
+use http2::frame::{has_header_block, DecodeStatus, Frame};
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::header::table::HeaderTable;
+
+#[test]
+pub fn test_raw_frame_decode() {
+    let mut bytes = PingFrame::new([1; 8]).serialize();
+    bytes.extend([0x00, 0x00, 0x00, 0x04]);
+
+    // The frame is split without any HPACK state.
+    let raw_frame = match RawFrame::decode(&mut bytes).unwrap() {
+        DecodeStatus::Complete(raw_frame) => raw_frame,
+        status => panic!("Unexpected status {:?}", status),
+    };
+    assert_eq!(raw_frame.frame_header().frame_type(), 0x6);
+    assert_eq!(raw_frame.payload(), [1; 8]);
+    assert!(!raw_frame.has_header_block());
+    assert_eq!(
+        raw_frame.into_frame().unwrap(),
+        Frame::Ping(PingFrame::new([1; 8]))
+    );
+
+    // Incomplete frames are left in the stream.
+    assert_eq!(
+        RawFrame::decode(&mut bytes).unwrap(),
+        DecodeStatus::NeedMoreData(5)
+    );
+    assert_eq!(bytes.len(), 4);
+    match RawFrame::deserialize(&mut bytes) {
+        Err(Http2Error::NotEnoughBytes(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_raw_frame_header_block() {
+    assert!(has_header_block(0x1) && has_header_block(0x5) && has_header_block(0x9));
+    assert!(!has_header_block(0x0) && !has_header_block(0x4));
+
+    let header_list = HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())]);
+    let headers_frame = Frame::Headers(HeadersFrame::new(1, true, true, None, header_list));
+    let bytes = headers_frame
+        .serialize(&mut HeaderTable::new(4096))
+        .unwrap();
+
+    // Header blocks need the decoding table of the connection.
+    let raw_frame = RawFrame::deserialize(&mut bytes.clone()).unwrap();
+    assert!(raw_frame.has_header_block());
+    match raw_frame.clone().into_frame() {
+        Err(Http2Error::FrameError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert_eq!(
+        raw_frame
+            .decode_header_block(&mut HeaderTable::new(4096))
+            .unwrap(),
+        headers_frame
+    );
+}
@@ -1,3 +1,7 @@
+#![cfg(feature = "codec")]
+
+use http2::error::ErrorCode;
+use http2::frame::rst_stream::RstStreamFrame;
 use http2::{frame::Frame, header::table::HeaderTable};
 
 #[test]
@@ -15,3 +19,12 @@ pub fn test_rst_stream_frame() {
     let frame = Frame::deserialize(&mut bytes, &mut header_table).unwrap();
     println!("{}", frame);
 }
+
+#[test]
+pub fn test_rst_stream_frame_new() {
+    let rst_stream_frame = RstStreamFrame::new(3, ErrorCode::Cancel);
+    assert_eq!(
+        rst_stream_frame.serialize(),
+        vec![0x00, 0x00, 0x04, 0x03, 0x00, 0x00, 0x00, 0x00, 0x03, 0x00, 0x00, 0x00, 0x08]
+    );
+}
@@ -1,3 +1,7 @@
+#![cfg(feature = "codec")]
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::settings::{SettingsFrame, SettingsParameter};
 use http2::{frame::Frame, header::table::HeaderTable};
 
 #[test]
@@ -7,7 +11,7 @@ pub fn test_settings_frame() {
         0x00, 0x00, 0x0c, // Length = 12
         0x04, // Frame Type = SETTINGS
         0x00, // Flags = None
-        0x00, 0x00, 0x00, 0x03, // Stream Identifier = 3
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
         0x00, 0x01, // Parameter Identifier = SETTINGS_HEADER_TABLE_SIZE
         0x00, 0x00, 0x00, 0xff, // Parameter Value = 255
         0x00, 0x02, // Parameter Identifier = SETTINGS_ENABLE_PUSH
@@ -18,3 +22,73 @@ pub fn test_settings_frame() {
     let frame = Frame::deserialize(&mut bytes, &mut header_table).unwrap();
     println!("{}", frame);
 }
+
+#[test]
+pub fn test_settings_frame_builder() {
+    let settings_frame = SettingsFrame::new()
+        .with_parameter(SettingsParameter::MaxConcurrentStreams(100))
+        .with_parameter(SettingsParameter::InitialWindowSize(65_535));
+    assert!(!settings_frame.is_ack());
+    assert_eq!(
+        settings_frame.serialize(),
+        vec![
+            0x00, 0x00, 0x0c, // Length = 12
+            0x04, // Frame Type = SETTINGS
+            0x00, // Flags = None
+            0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+            0x00, 0x03, // Parameter Identifier = SETTINGS_MAX_CONCURRENT_STREAMS
+            0x00, 0x00, 0x00, 0x64, // Parameter Value = 100
+            0x00, 0x04, // Parameter Identifier = SETTINGS_INITIAL_WINDOW_SIZE
+            0x00, 0x00, 0xff, 0xff, // Parameter Value = 65535
+        ]
+    );
+
+    let settings_frame = SettingsFrame::ack();
+    assert!(settings_frame.is_ack());
+    assert!(settings_frame.settings_parameters().is_empty());
+    assert_eq!(
+        settings_frame.serialize(),
+        vec![0x00, 0x00, 0x00, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00]
+    );
+}
+
+#[test]
+pub fn test_settings_frame_unknown_parameter() {
+    let mut bytes: Vec<u8> = vec![
+        0x00, 0x00, 0x0c, // Length = 12
+        0x04, // Frame Type = SETTINGS
+        0x00, // Flags = None
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x00, 0x2a, // Parameter Identifier = 42
+        0x00, 0x00, 0x00, 0x01, // Parameter Value = 1
+        0x00, 0x05, // Parameter Identifier = SETTINGS_MAX_FRAME_SIZE
+        0x00, 0x00, 0x40, 0x00, // Parameter Value = 16384
+    ];
+
+    // The unknown parameter is ignored.
+    let frame = Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)).unwrap();
+    assert_eq!(
+        frame,
+        Frame::Settings(
+            SettingsFrame::new().with_parameter(SettingsParameter::MaxFrameSize(16_384))
+        )
+    );
+    assert!(bytes.is_empty());
+}
+
+#[test]
+pub fn test_settings_frame_ack_with_payload() {
+    let mut bytes: Vec<u8> = vec![
+        0x00, 0x00, 0x06, // Length = 6
+        0x04, // Frame Type = SETTINGS
+        0x01, // Flags = Ack
+        0x00, 0x00, 0x00, 0x00, // Stream Identifier = 0
+        0x00, 0x03, // Parameter Identifier = SETTINGS_MAX_CONCURRENT_STREAMS
+        0x00, 0x00, 0x00, 0x64, // Parameter Value = 100
+    ];
+
+    match Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)) {
+        Err(Http2Error::ConnectionError(ErrorCode::FrameSizeError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
@@ -1,3 +1,5 @@
+#![cfg(feature = "codec")]
+
 use http2::{frame::Frame, header::table::HeaderTable};
 
 #[test]
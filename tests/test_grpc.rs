@@ -0,0 +1,160 @@
+#![cfg(all(feature = "grpc", feature = "test-support"))]
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::data::DataFrame;
+use http2::grpc::{self, Message, MessageDecoder, Status, DEFAULT_MAX_MESSAGE_SIZE};
+use http2::test_support::headers::header_list;
+
+#[test]
+pub fn test_grpc_message_encode() {
+    let message = Message::new(false, b"hello".to_vec());
+    assert_eq!(
+        message.encode(),
+        vec![0x00, 0x00, 0x00, 0x00, 0x05, b'h', b'e', b'l', b'l', b'o']
+    );
+}
+
+#[test]
+pub fn test_grpc_data_frames_round_trip() {
+    let messages = vec![
+        Message::new(false, vec![0xAA; 10]),
+        Message::new(true, vec![0xBB; 3]),
+        Message::new(false, Vec::new()),
+    ];
+
+    // 28 bytes of framed messages split in DATA frames of 8 bytes.
+    let data_frames = grpc::data_frames(1, &messages, 8, true);
+    assert_eq!(data_frames.len(), 4);
+    assert!(data_frames
+        .iter()
+        .all(|data_frame| data_frame.stream_id == 1));
+    assert!(data_frames[3].end_stream);
+    assert!(!data_frames[2].end_stream);
+
+    let mut decoder = MessageDecoder::new(1, DEFAULT_MAX_MESSAGE_SIZE);
+    let mut decoded: Vec<Message> = Vec::new();
+    for data_frame in &data_frames {
+        decoder.feed(data_frame);
+        for message in decoder.by_ref() {
+            decoded.push(message.unwrap());
+        }
+    }
+    assert_eq!(decoded, messages);
+    decoder.finish().unwrap();
+
+    // Ending the stream without message sends an empty DATA frame.
+    let data_frames = grpc::data_frames(3, &[], 8, true);
+    assert_eq!(data_frames, vec![DataFrame::new(3, true, Vec::new())]);
+}
+
+#[test]
+pub fn test_grpc_message_decoder_invalid() {
+    // Partial message at the end of the stream.
+    let mut decoder = MessageDecoder::new(1, DEFAULT_MAX_MESSAGE_SIZE);
+    decoder.feed(&DataFrame::new(
+        1,
+        true,
+        vec![0x00, 0x00, 0x00, 0x00, 0x02, 0xFF],
+    ));
+    assert!(decoder.next_message().unwrap().is_none());
+    assert!(matches!(
+        decoder.finish(),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+
+    // Invalid compressed flag.
+    let mut decoder = MessageDecoder::new(1, DEFAULT_MAX_MESSAGE_SIZE);
+    decoder.feed(&DataFrame::new(
+        1,
+        false,
+        vec![0x02, 0x00, 0x00, 0x00, 0x00],
+    ));
+    assert!(matches!(
+        decoder.next_message(),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+
+    // Oversized messages are rejected from their prefix.
+    let mut decoder = MessageDecoder::new(1, 16);
+    decoder.feed(&DataFrame::new(
+        1,
+        false,
+        vec![0x00, 0x00, 0x00, 0x00, 0x11],
+    ));
+    assert!(matches!(
+        decoder.next_message(),
+        Err(Http2Error::StreamError(1, ErrorCode::EnhanceYourCalm, _))
+    ));
+}
+
+#[test]
+pub fn test_grpc_check_request() {
+    let request = header_list(&[
+        (":method", "POST"),
+        (":path", "/helloworld.Greeter/SayHello"),
+        ("content-type", "application/grpc+proto"),
+        ("te", "trailers"),
+    ]);
+    grpc::check_request(1, &request).unwrap();
+
+    for invalid in [
+        header_list(&[
+            (":method", "GET"),
+            ("content-type", "application/grpc"),
+            ("te", "trailers"),
+        ]),
+        header_list(&[
+            (":method", "POST"),
+            ("content-type", "application/grpcweb"),
+            ("te", "trailers"),
+        ]),
+        header_list(&[(":method", "POST"), ("content-type", "application/grpc")]),
+    ] {
+        assert!(matches!(
+            grpc::check_request(1, &invalid),
+            Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+        ));
+    }
+}
+
+#[test]
+pub fn test_grpc_status() {
+    let status = Status::new(13, Some("déjà 100%"));
+    let trailers = grpc::trailers(&status);
+    assert_eq!(
+        trailers,
+        header_list(&[
+            ("grpc-status", "13"),
+            ("grpc-message", "d%C3%A9j%C3%A0 100%25")
+        ])
+    );
+    assert_eq!(Status::from_header_list(&trailers), Some(status));
+
+    assert_eq!(
+        Status::from_header_list(&header_list(&[("grpc-status", "0")])),
+        Some(Status::new(0, None))
+    );
+    assert_eq!(Status::from_header_list(&grpc::response_headers()), None);
+}
+
+#[test]
+pub fn test_grpc_trailers_only_response() {
+    let status = Status::new(12, Some("unimplemented"));
+    let headers_frame = grpc::trailers_only_response(1, &status);
+
+    assert!(headers_frame.end_stream());
+    assert!(grpc::is_trailers_only(&headers_frame));
+    assert_eq!(
+        headers_frame.header_list(),
+        &header_list(&[
+            (":status", "200"),
+            ("content-type", "application/grpc"),
+            ("grpc-status", "12"),
+            ("grpc-message", "unimplemented"),
+        ])
+    );
+    assert_eq!(
+        Status::from_header_list(headers_frame.header_list()),
+        Some(status)
+    );
+}
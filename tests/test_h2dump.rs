@@ -0,0 +1,51 @@
+#![cfg(feature = "cli")]
+
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+#[test]
+pub fn test_h2dump_hex_stdin() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_h2dump"))
+        .arg("--hex")
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    // SETTINGS acknowledgement followed by a PING frame.
+    child
+        .stdin
+        .take()
+        .unwrap()
+        .write_all(
+            b"0x00, 0x00, 0x00, 0x04, 0x01, 0x00, 0x00, 0x00, 0x00,\n\
+              000008 06 00 00000000 0102030405060708\n",
+        )
+        .unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout).unwrap(),
+        "\
+00000000  SETTINGS stream=0 length=0 flags=0x01 [ACK]
+00000009  PING stream=0 length=8 flags=0x00 []
+          0000  01 02 03 04 05 06 07 08                          |........|
+"
+    );
+}
+
+#[test]
+pub fn test_h2dump_invalid_hex() {
+    let mut child = Command::new(env!("CARGO_BIN_EXE_h2dump"))
+        .arg("--hex")
+        .stdin(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .unwrap();
+
+    child.stdin.take().unwrap().write_all(b"0x0g").unwrap();
+
+    let output = child.wait_with_output().unwrap();
+    assert!(!output.status.success());
+}
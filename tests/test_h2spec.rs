@@ -0,0 +1,179 @@
+#![cfg(feature = "sync")]
+
+//! Conformance of the server against h2spec.
+//!
+//! The harness is ignored by default, as it spawns the external `h2spec`
+//! tool, found in the `PATH` or at the path given by the `H2SPEC`
+//! environment variable:
+//!
+//! ```text
+//! cargo test --test test_h2spec -- --ignored --nocapture
+//! ```
+
+use std::env;
+use std::net::{TcpListener, TcpStream};
+use std::process::Command;
+use std::thread;
+use std::time::Duration;
+
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::sync::{Connection, Event};
+
+/// Time h2spec waits for a frame of the server, in seconds.
+const H2SPEC_TIMEOUT: &str = "2";
+
+/// Sections of h2spec, with whether the server passes them.
+///
+/// A section marked as passing must keep passing. Sections passing while
+/// marked as failing are reported, to be marked once fixed: the run prints
+/// the table of the sections with their actual results, to record it here.
+/// No run was recorded yet, so every section is still marked as failing.
+const SECTIONS: &[(&str, bool)] = &[
+    ("generic/1", false),
+    ("generic/2", false),
+    ("generic/3", false),
+    ("generic/4", false),
+    ("generic/5", false),
+    ("http2/3.5", false),
+    ("http2/4.1", false),
+    ("http2/4.2", false),
+    ("http2/4.3", false),
+    ("http2/5.1", false),
+    ("http2/5.1.1", false),
+    ("http2/5.1.2", false),
+    ("http2/5.3.1", false),
+    ("http2/5.4.1", false),
+    ("http2/5.5", false),
+    ("http2/6.1", false),
+    ("http2/6.2", false),
+    ("http2/6.3", false),
+    ("http2/6.4", false),
+    ("http2/6.5", false),
+    ("http2/6.5.2", false),
+    ("http2/6.5.3", false),
+    ("http2/6.7", false),
+    ("http2/6.8", false),
+    ("http2/6.9", false),
+    ("http2/6.9.1", false),
+    ("http2/6.9.2", false),
+    ("http2/6.10", false),
+    ("http2/7", false),
+    ("http2/8.1", false),
+    ("http2/8.1.2", false),
+    ("http2/8.1.2.1", false),
+    ("http2/8.1.2.2", false),
+    ("http2/8.1.2.3", false),
+    ("http2/8.1.2.6", false),
+    ("http2/8.2", false),
+    ("hpack/2.3", false),
+    ("hpack/2.3.3", false),
+    ("hpack/4.2", false),
+    ("hpack/5.2", false),
+    ("hpack/6.1", false),
+    ("hpack/6.3", false),
+];
+
+/// Serve a connection of h2spec, responding to every complete request.
+///
+/// The connection is closed once it fails with a connection error, which
+/// the driver reported with a GOAWAY frame. Stream errors already reset
+/// their stream.
+fn serve(io: TcpStream) {
+    // A connection left open by h2spec must not keep its thread forever.
+    io.set_read_timeout(Some(Duration::from_secs(10))).unwrap();
+    let mut connection = match Connection::accept(io) {
+        Ok(connection) => connection,
+        Err(_) => return,
+    };
+    let response = HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())]);
+
+    loop {
+        let result = match connection.next_event() {
+            Ok(Event::Headers {
+                stream_id,
+                end_stream,
+                ..
+            }) => {
+                if end_stream {
+                    connection.send_response(stream_id, response.clone(), Some(b"h2spec"))
+                } else {
+                    Ok(())
+                }
+            }
+            Ok(Event::Data {
+                stream_id,
+                end_stream,
+                ..
+            }) => connection.recv_data(stream_id).and_then(|_| {
+                if end_stream {
+                    connection.send_response(stream_id, response.clone(), Some(b"h2spec"))
+                } else {
+                    Ok(())
+                }
+            }),
+            Ok(Event::Trailers { stream_id, .. }) => {
+                connection.send_response(stream_id, response.clone(), Some(b"h2spec"))
+            }
+            Ok(_) => Ok(()),
+            Err(error) => Err(error),
+        };
+
+        match result {
+            Ok(()) => (),
+            Err(error) if error.is_stream_error() => (),
+            Err(_) => return,
+        }
+    }
+}
+
+#[test]
+#[ignore = "requires the h2spec conformance tool"]
+pub fn test_h2spec() {
+    // The listener is bound before h2spec starts, on a port chosen by the
+    // system, so that concurrent runs do not collide.
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let port = listener.local_addr().unwrap().port().to_string();
+    thread::spawn(move || {
+        for io in listener.incoming().flatten() {
+            thread::spawn(move || serve(io));
+        }
+    });
+
+    let h2spec = env::var("H2SPEC").unwrap_or_else(|_| "h2spec".to_string());
+    let mut regressions = Vec::new();
+    let mut matrix = Vec::new();
+    for (section, passing) in SECTIONS {
+        let status = Command::new(&h2spec)
+            .args([
+                "-h",
+                "127.0.0.1",
+                "-p",
+                &port,
+                "-o",
+                H2SPEC_TIMEOUT,
+                section,
+            ])
+            .output()
+            .unwrap_or_else(|error| panic!("Cannot run {}: {}", h2spec, error))
+            .status;
+
+        let result = match (status.success(), passing) {
+            (true, true) => "pass",
+            (true, false) => "pass (not tracked yet)",
+            (false, true) => "FAIL (regression)",
+            (false, false) => "fail",
+        };
+        println!("{:<16} {}", section, result);
+        if *passing && !status.success() {
+            regressions.push(*section);
+        }
+        matrix.push(format!("    ({:?}, {}),", section, status.success()));
+    }
+    println!(
+        "const SECTIONS: &[(&str, bool)] = &[\n{}\n];",
+        matrix.join("\n")
+    );
+
+    assert!(regressions.is_empty(), "Regressions in {:?}", regressions);
+}
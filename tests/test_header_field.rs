@@ -1,4 +1,5 @@
 use http2::header::field::{HeaderField, HeaderName, HeaderValue};
+use http2::header::limits::LiteralBudget;
 use http2::header::representation::HeaderRepresentation;
 use http2::header::table::HeaderTable;
 
@@ -39,7 +40,7 @@ pub fn test_hpack_header_field() {
 
     let header_representation = header_field.into_representation(&mut header_table);
 
-    let mut bytes = header_representation.encode(false, false);
+    let bytes = header_representation.encode(false, false).unwrap();
 
     assert!(
         bytes
@@ -50,7 +51,8 @@ pub fn test_hpack_header_field() {
     );
 
     let mut header_table = HeaderTable::new(4096);
-    let header_representation = HeaderRepresentation::decode(&mut bytes).unwrap();
+    let header_representation =
+        HeaderRepresentation::decode(&mut &bytes[..], &mut LiteralBudget::default()).unwrap();
     let header_field =
         HeaderField::from_representation(header_representation, &mut header_table).unwrap();
 
@@ -96,7 +98,7 @@ pub fn test_hpack_header_field() {
     let header_representation =
         header_field.into_representation_without_indexing(&mut header_table);
 
-    let mut bytes = header_representation.encode(false, false);
+    let bytes = header_representation.encode(false, false).unwrap();
 
     assert!(
         bytes
@@ -106,7 +108,8 @@ pub fn test_hpack_header_field() {
     );
 
     let mut header_table = HeaderTable::new(4096);
-    let header_representation = HeaderRepresentation::decode(&mut bytes).unwrap();
+    let header_representation =
+        HeaderRepresentation::decode(&mut &bytes[..], &mut LiteralBudget::default()).unwrap();
     let header_field =
         HeaderField::from_representation(header_representation, &mut header_table).unwrap();
 
@@ -153,7 +156,7 @@ pub fn test_hpack_header_field() {
 
     let header_representation = header_field.into_representation_never_index(&mut header_table);
 
-    let mut bytes = header_representation.encode(false, false);
+    let bytes = header_representation.encode(false, false).unwrap();
 
     assert!(
         bytes
@@ -165,7 +168,8 @@ pub fn test_hpack_header_field() {
 
     let mut header_table = HeaderTable::new(4096);
 
-    let header_representation = HeaderRepresentation::decode(&mut bytes).unwrap();
+    let header_representation =
+        HeaderRepresentation::decode(&mut &bytes[..], &mut LiteralBudget::default()).unwrap();
     let header_field =
         HeaderField::from_representation(header_representation, &mut header_table).unwrap();
 
@@ -207,13 +211,14 @@ pub fn test_hpack_header_field() {
 
     let header_representation = header_field.into_representation(&mut header_table);
 
-    let mut bytes = header_representation.encode(false, false);
+    let bytes = header_representation.encode(false, false).unwrap();
 
     assert!(bytes == vec![0x82]);
 
     let mut header_table = HeaderTable::new(4096);
 
-    let header_representation = HeaderRepresentation::decode(&mut bytes).unwrap();
+    let header_representation =
+        HeaderRepresentation::decode(&mut &bytes[..], &mut LiteralBudget::default()).unwrap();
     let header_field =
         HeaderField::from_representation(header_representation, &mut header_table).unwrap();
 
@@ -227,3 +232,46 @@ pub fn test_hpack_header_field() {
     );
     assert_eq!(header_table.get_dynamic_table_size(), 0);
 }
+
+#[test]
+pub fn test_hpack_header_field_sensitive() {
+    // password: secret, decoded from a literal never indexed.
+    let bytes = vec![
+        0x10, 0x08, 0x70, 0x61, 0x73, 0x73, 0x77, 0x6f, 0x72, 0x64, 0x06, 0x73, 0x65, 0x63, 0x72,
+        0x65, 0x74,
+    ];
+    let mut header_table = HeaderTable::new(4096);
+    let header_representation =
+        HeaderRepresentation::decode(&mut &bytes[..], &mut LiteralBudget::default()).unwrap();
+    let header_field = HeaderField::from_representation(header_representation, &mut header_table)
+        .unwrap()
+        .unwrap();
+    assert!(header_field.sensitive());
+    assert_eq!(
+        header_field,
+        HeaderField::new("password".into(), "secret".into())
+    );
+
+    // The protection is preserved when the header field is re-encoded.
+    let mut header_table = HeaderTable::new(4096);
+    let header_representation = header_field.into_representation(&mut header_table);
+    assert_eq!(header_representation.encode(false, false).unwrap(), bytes);
+    let header_representation =
+        header_field.into_representation_without_indexing(&mut header_table);
+    assert_eq!(header_representation.encode(false, false).unwrap(), bytes);
+    assert_eq!(header_table.get_dynamic_table_size(), 0);
+
+    // A sensitive header field is not indexed even if it is in the table.
+    let mut header_field = HeaderField::new("authorization".into(), "token".into());
+    let header_representation = header_field.into_representation(&mut header_table);
+    assert_eq!(
+        header_representation.encode(false, false).unwrap()[0],
+        0x40 | 23
+    );
+    header_field.set_sensitive(true);
+    let header_representation = header_field.into_representation(&mut header_table);
+    assert_eq!(
+        header_representation.encode(false, false).unwrap(),
+        vec![0x1f, 0x08, 0x05, 0x74, 0x6f, 0x6b, 0x65, 0x6e]
+    );
+}
@@ -0,0 +1,138 @@
+use http2::error::{CompressionError, Http2Error};
+use http2::header::huffman::{self, Tree, HPACK_HUFFMAN_CODE};
+use http2::header::limits::LiteralBudget;
+use http2::header::primitive::HpackString;
+
+#[test]
+pub fn test_huffman_decode() {
+    // RFC 7541 C.4.1: "www.example.com"
+    let bytes: Vec<u8> = vec![
+        0xf1, 0xe3, 0xc2, 0xe5, 0xf2, 0x3a, 0x6b, 0xa0,
+        0xab, 0x90, 0xf4, 0xff,
+    ];
+    assert_eq!(huffman::decode(&bytes).unwrap(), b"www.example.com");
+
+    // RFC 7541 C.6.1: "Mon, 21 Oct 2013 20:13:21 GMT"
+    let mut bytes: Vec<u8> = vec![
+        0xd0, 0x7a, 0xbe, 0x94, 0x10, 0x54, 0xd4, 0x44,
+        0xa8, 0x20, 0x05, 0x95, 0x04, 0x0b, 0x81, 0x66,
+        0xe0, 0x82, 0xa6, 0x2d, 0x1b, 0xff,
+    ];
+    assert_eq!(
+        huffman::decode(&bytes).unwrap(),
+        b"Mon, 21 Oct 2013 20:13:21 GMT"
+    );
+
+    // The state machine and the tree agree.
+    let tree = Tree::new().unwrap();
+    assert_eq!(
+        tree.decode(&mut bytes).unwrap(),
+        "Mon, 21 Oct 2013 20:13:21 GMT"
+    );
+
+    // Empty string.
+    assert_eq!(huffman::decode(&[]).unwrap(), b"");
+}
+
+#[test]
+pub fn test_huffman_decode_invalid() {
+    // Padding longer than 7 bits.
+    // "a" is 00011, followed by 11 bits set to 1.
+    let bytes: Vec<u8> = vec![0b00011111, 0b11111111];
+    assert!(matches!(
+        huffman::decode(&bytes),
+        Err(Http2Error::CompressionError(
+            CompressionError::HuffmanPaddingTooLong
+        ))
+    ));
+
+    // A full byte of padding.
+    // "a" is 00011, "b" is 100011, followed by 13 bits set to 1.
+    let bytes: Vec<u8> = vec![0b00011100, 0b01111111, 0b11111111];
+    assert!(matches!(
+        huffman::decode(&bytes),
+        Err(Http2Error::CompressionError(
+            CompressionError::HuffmanPaddingTooLong
+        ))
+    ));
+
+    // Padding not made of the EOS prefix.
+    // "a" is 00011, followed by 3 bits set to 0.
+    let bytes: Vec<u8> = vec![0b00011000];
+    assert!(matches!(
+        huffman::decode(&bytes),
+        Err(Http2Error::CompressionError(
+            CompressionError::HuffmanInvalidPadding
+        ))
+    ));
+
+    // EOS symbol (30 bits set to 1) followed by 2 bits of padding.
+    let bytes: Vec<u8> = vec![0xff, 0xff, 0xff, 0xff];
+    assert!(matches!(
+        huffman::decode(&bytes),
+        Err(Http2Error::CompressionError(CompressionError::HuffmanEos))
+    ));
+
+    // The error is propagated when decoding a string literal.
+    let bytes: Vec<u8> = vec![0x81, 0b00011000];
+    assert!(matches!(
+        HpackString::decode(&mut &bytes[..], &mut LiteralBudget::default()),
+        Err(Http2Error::CompressionError(
+            CompressionError::HuffmanInvalidPadding
+        ))
+    ));
+}
+
+#[test]
+pub fn test_huffman_encode() {
+    // RFC 7541 C.4.1: "www.example.com"
+    let bytes = huffman::encode(b"www.example.com");
+    assert_eq!(
+        bytes,
+        vec![0xf1, 0xe3, 0xc2, 0xe5, 0xf2, 0x3a, 0x6b, 0xa0, 0xab, 0x90, 0xf4, 0xff]
+    );
+    assert_eq!(huffman::encoded_len(b"www.example.com"), 12);
+
+    // RFC 7541 C.4.2: "no-cache", padded with the EOS prefix.
+    assert_eq!(
+        huffman::encode(b"no-cache"),
+        vec![0xa8, 0xeb, 0x10, 0x64, 0x9c, 0xbf]
+    );
+
+    // Every octet round-trips.
+    let octets: Vec<u8> = (0..=255).collect();
+    assert_eq!(huffman::decode(&huffman::encode(&octets)).unwrap(), octets);
+    assert_eq!(huffman::encode(&[]), vec![]);
+
+    // Codes of up to 30 bits straddle the flushes of the bit buffer.
+    let octets: Vec<u8> = [0x0a, 0x0d, 0x16, b'a', 0x00, 0xff].repeat(10);
+    let bits: String = octets
+        .iter()
+        .map(|octet| {
+            HPACK_HUFFMAN_CODE
+                .iter()
+                .find(|(_, symbol)| symbol == octet)
+                .unwrap()
+                .0
+        })
+        .collect();
+    let expected: Vec<u8> = format!("{:1<1$}", bits, bits.len().div_ceil(8) * 8)
+        .as_bytes()
+        .chunks(8)
+        .map(|chunk| u8::from_str_radix(std::str::from_utf8(chunk).unwrap(), 2).unwrap())
+        .collect();
+    assert_eq!(huffman::encode(&octets), expected);
+    assert_eq!(huffman::encoded_len(&octets), expected.len());
+
+    // A string literal is not Huffman encoded if it makes it longer.
+    let bytes = HpackString::from("no-cache").encode(true).unwrap();
+    assert_eq!(bytes, vec![0x86, 0xa8, 0xeb, 0x10, 0x64, 0x9c, 0xbf]);
+    assert_eq!(
+        HpackString::decode(&mut &bytes[..], &mut LiteralBudget::default()).unwrap(),
+        HpackString::from("no-cache")
+    );
+    assert_eq!(
+        HpackString::from("{}").encode(true).unwrap(),
+        vec![0x02, 0x7b, 0x7d]
+    );
+}
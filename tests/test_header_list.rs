@@ -1,6 +1,12 @@
+#![cfg(feature = "test-support")]
+
+use http2::error::{ErrorCode, Http2Error};
 use http2::header::field::{HeaderField, HeaderName, HeaderValue};
+use http2::header::lenience::LenienceConfig;
+use http2::header::limits::{DecoderLimits, EncoderLimits};
 use http2::header::list::HeaderList;
 use http2::header::table::HeaderTable;
+use http2::test_support::headers::header_list;
 
 #[test]
 pub fn test_header_list() {
@@ -595,3 +601,901 @@ pub fn test_header_list_eviction() {
     assert_eq!(decoded_header_list, header_list);
     assert_eq!(header_table_receiver.get_dynamic_table_size(), 215);
 }
+
+#[test]
+pub fn test_header_list_decoder_limits() {
+    // :method: GET
+    // :scheme: http
+    // :path: /
+    // :authority: www.example.com
+    let encoded_header_list: Vec<u8> = vec![
+        0x82, 0x86, 0x84, 0x41, 0x0f, 0x77, 0x77, 0x77, 0x2e, 0x65, 0x78, 0x61, 0x6d, 0x70, 0x6c,
+        0x65, 0x2e, 0x63, 0x6f, 0x6d,
+    ];
+
+    let limits = [
+        DecoderLimits {
+            max_string_length: Some(14),
+            ..DecoderLimits::default()
+        },
+        DecoderLimits {
+            max_header_list_size: Some(179),
+            ..DecoderLimits::default()
+        },
+        DecoderLimits {
+            max_header_fields: Some(3),
+            ..DecoderLimits::default()
+        },
+    ];
+
+    for decoder_limits in limits {
+        let mut header_table = HeaderTable::new(4096);
+        header_table.set_decoder_limits(decoder_limits);
+
+        match HeaderList::decode(&mut encoded_header_list.clone(), &mut header_table) {
+            Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    // The header list fits exactly in the limits.
+    let mut header_table = HeaderTable::new(4096);
+    header_table.set_decoder_limits(DecoderLimits {
+        max_header_list_size: Some(180),
+        max_string_length: Some(15),
+        max_header_fields: Some(4),
+    });
+    let header_list = HeaderList::decode(&mut encoded_header_list.clone(), &mut header_table);
+    assert!(header_list.is_ok());
+}
+
+#[test]
+pub fn test_header_list_encoder_limits() {
+    // Header field sizes: 42, 43, 38 and 57, for a header list of 180.
+    let header_list = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+        HeaderField::new(":authority".into(), "www.example.com".into()),
+    ]);
+
+    let limits = [
+        EncoderLimits {
+            max_field_size: Some(56),
+            ..EncoderLimits::default()
+        },
+        EncoderLimits {
+            max_list_size: Some(179),
+            ..EncoderLimits::default()
+        },
+    ];
+
+    for encoder_limits in limits {
+        let mut header_table = HeaderTable::new(4096);
+        header_table.set_encoder_limits(encoder_limits);
+
+        // The error names the offending field, nothing is indexed.
+        match header_list.encode(&mut header_table) {
+            Err(Http2Error::HeaderError(message)) => {
+                assert!(message.contains(":authority at position 3"))
+            }
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert_eq!(header_table.get_dynamic_table_size(), 0);
+    }
+
+    // The header list fits exactly in the limits.
+    let mut header_table = HeaderTable::new(4096);
+    header_table.set_encoder_limits(EncoderLimits {
+        max_field_size: Some(57),
+        max_list_size: Some(180),
+    });
+    assert!(header_list.encode(&mut header_table).is_ok());
+
+    // Crumbled cookies count as separate header fields of 41 octets.
+    let header_list = HeaderList::new(vec![HeaderField::new("cookie".into(), "a=b; c=d".into())]);
+    let mut header_table = HeaderTable::new(4096);
+    header_table.set_encoder_limits(EncoderLimits {
+        max_list_size: Some(81),
+        ..EncoderLimits::default()
+    });
+    match header_list.encode(&mut header_table) {
+        Err(Http2Error::HeaderError(message)) => {
+            assert!(message.contains("cookie at position 1"))
+        }
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    header_table.set_cookie_crumbling(false);
+    assert!(header_list.encode(&mut header_table).is_ok());
+}
+
+#[test]
+pub fn test_header_list_size_update() {
+    // Dynamic table size update to 4096 followed by :method: GET.
+    let mut header_table = HeaderTable::new(4096);
+    let header_list = HeaderList::decode(&mut vec![0x3f, 0xe1, 0x1f, 0x82], &mut header_table);
+    assert!(header_list.is_ok());
+
+    // Dynamic table size update above SETTINGS_HEADER_TABLE_SIZE.
+    let mut header_table = HeaderTable::new(4096);
+    header_table.set_settings_max_size(256);
+    match HeaderList::decode(&mut vec![0x3f, 0xe1, 0x1f, 0x82], &mut header_table) {
+        Err(Http2Error::ConnectionError(ErrorCode::CompressionError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // Dynamic table size update after a header field.
+    let mut header_table = HeaderTable::new(4096);
+    match HeaderList::decode(&mut vec![0x82, 0x20], &mut header_table) {
+        Err(Http2Error::ConnectionError(ErrorCode::CompressionError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_header_list_pending_size_update() {
+    let mut header_table_sender = HeaderTable::new(4096);
+    let mut header_table_receiver = HeaderTable::new(4096);
+    let header_list = HeaderList::from(vec![HeaderField::new(
+        HeaderName::from(":method"),
+        HeaderValue::from("GET"),
+    )]);
+
+    // Lower then raise the table size: both the smallest and the final
+    // size are signaled at the beginning of the next header block only.
+    header_table_sender.set_pending_size_update(0);
+    header_table_sender.set_pending_size_update(256);
+
+    let mut encoded_header_list = header_list.encode(&mut header_table_sender).unwrap();
+    assert_eq!(
+        encoded_header_list,
+        vec![
+            0x20, // Dynamic Table Size Update = 0
+            0x3f, 0xe1, 0x01, // Dynamic Table Size Update = 256
+            0x82, // :method: GET
+        ]
+    );
+
+    let decoded_header_list =
+        HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    assert_eq!(decoded_header_list, header_list);
+
+    let encoded_header_list = header_list.encode(&mut header_table_sender).unwrap();
+    assert_eq!(encoded_header_list, vec![0x82]);
+}
+
+#[test]
+pub fn test_header_list_map_accessors() {
+    let mut header_list: HeaderList = vec![
+        HeaderField::new(HeaderName::from(":method"), HeaderValue::from("GET")),
+        HeaderField::new(HeaderName::from(":scheme"), HeaderValue::from("https")),
+        HeaderField::new(
+            HeaderName::from(":authority"),
+            HeaderValue::from("example.com"),
+        ),
+        HeaderField::new(HeaderName::from(":path"), HeaderValue::from("/")),
+        HeaderField::new(HeaderName::from("accept"), HeaderValue::from("text/html")),
+        HeaderField::new(HeaderName::from("cookie"), HeaderValue::from("a=1")),
+        HeaderField::new(HeaderName::from("cookie"), HeaderValue::from("b=2")),
+    ]
+    .into_iter()
+    .collect();
+
+    assert_eq!(header_list.len(), 7);
+    assert!(!header_list.is_empty());
+    assert_eq!(header_list.method(), Some("GET"));
+    assert_eq!(header_list.scheme(), Some("https"));
+    assert_eq!(header_list.authority(), Some("example.com"));
+    assert_eq!(header_list.path(), Some("/"));
+    assert_eq!(header_list.status(), None);
+
+    // Lookups ignore ASCII case.
+    assert_eq!(header_list.get("Accept"), Some("text/html"));
+    assert!(header_list.contains("COOKIE"));
+    assert!(!header_list.contains("content-type"));
+    assert_eq!(
+        header_list.get_all("cookie").collect::<Vec<&str>>(),
+        vec!["a=1", "b=2"]
+    );
+
+    // Insert replaces every value at the position of the first one, the
+    // name is stored as given.
+    header_list.insert("Cookie", "c=3");
+    assert_eq!(header_list.len(), 6);
+    assert_eq!(
+        header_list.get_all("cookie").collect::<Vec<&str>>(),
+        vec!["c=3"]
+    );
+    assert_eq!(
+        header_list.iter().nth(5).unwrap().name(),
+        HeaderName::from("Cookie")
+    );
+
+    // Insert adds a missing header field at the end.
+    header_list.insert("content-type", "text/plain");
+    assert_eq!(
+        header_list.iter().last().unwrap(),
+        &HeaderField::new(
+            HeaderName::from("content-type"),
+            HeaderValue::from("text/plain")
+        )
+    );
+
+    // Append keeps the other values.
+    header_list.append("Accept", "application/json");
+    assert_eq!(
+        header_list.get_all("accept").collect::<Vec<&str>>(),
+        vec!["text/html", "application/json"]
+    );
+
+    // Remove returns the first value.
+    assert_eq!(
+        header_list.remove("accept"),
+        Some(HeaderValue::from("text/html"))
+    );
+    assert_eq!(header_list.remove("accept"), None);
+    assert_eq!(header_list.get("accept"), None);
+
+    let names: Vec<String> = (&header_list)
+        .into_iter()
+        .map(|header_field| header_field.name().to_string())
+        .collect();
+    assert_eq!(
+        names,
+        vec![
+            ":method",
+            ":scheme",
+            ":authority",
+            ":path",
+            "Cookie",
+            "content-type"
+        ]
+    );
+}
+
+#[test]
+pub fn test_header_list_authority() {
+    let mut header_list = HeaderList::default();
+    header_list.append(":method", "GET");
+    header_list.append("host", "example.org");
+    assert!(header_list.host_matches_authority());
+
+    header_list.append(":authority", "example.com");
+    assert!(!header_list.host_matches_authority());
+
+    // The authority supersedes the host header field.
+    header_list
+        .set_authority_from_uri("https://user@Example.com:8443/path?query#fragment")
+        .unwrap();
+    assert_eq!(header_list.authority(), Some("example.com:8443"));
+    assert_eq!(header_list.get("host"), None);
+    header_list.append("host", "example.com:8443");
+    assert!(header_list.host_matches_authority());
+
+    for uri in ["/path", "https:///path", "example.com"] {
+        match header_list.set_authority_from_uri(uri) {
+            Err(Http2Error::HeaderError(_)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+}
+
+#[test]
+pub fn test_header_list_status() {
+    let mut header_list = HeaderList::default();
+    assert!(header_list.is_empty());
+
+    header_list.insert(":status", "204");
+    assert_eq!(header_list.status(), Some(204));
+
+    header_list.insert(":status", "abc");
+    assert_eq!(header_list.status(), None);
+}
+
+#[test]
+pub fn test_header_list_size() {
+    let mut header_list = HeaderList::default();
+    assert_eq!(header_list.size(), 0);
+
+    // Each header field counts its name, its value and 32 octets.
+    header_list.append(":method", "GET");
+    header_list.append("accept", "*/*");
+    assert_eq!(header_list.size(), (7 + 3 + 32) + (6 + 3 + 32));
+}
+
+#[test]
+pub fn test_header_list_cookie_crumbling() {
+    let mut header_table_sender = HeaderTable::new(4096);
+    let mut header_table_receiver = HeaderTable::new(4096);
+    assert!(header_table_sender.cookie_crumbling());
+
+    let header_list = HeaderList::new(vec![
+        HeaderField::new(HeaderName::from(":method"), HeaderValue::from("GET")),
+        HeaderField::new(HeaderName::from("cookie"), HeaderValue::from("a=1; b=2")),
+        HeaderField::new(HeaderName::from("accept"), HeaderValue::from("*/*")),
+    ]);
+
+    // The cookie pairs are indexed separately.
+    let mut encoded_header_list = header_list.encode(&mut header_table_sender).unwrap();
+    assert_eq!(header_table_sender.get(63).unwrap().value(), "b=2".into());
+    assert_eq!(header_table_sender.get(64).unwrap().value(), "a=1".into());
+
+    // The cookie pairs are joined back after decoding.
+    let decoded_header_list =
+        HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    assert_eq!(decoded_header_list, header_list);
+
+    // A changed pair does not prevent the others from being indexed.
+    let header_list = HeaderList::new(vec![HeaderField::new(
+        HeaderName::from("cookie"),
+        HeaderValue::from("a=1; b=3"),
+    )]);
+    let mut encoded_header_list = header_list.encode(&mut header_table_sender).unwrap();
+    assert_eq!(encoded_header_list[0], 0x80 | 64);
+
+    let decoded_header_list =
+        HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    assert_eq!(decoded_header_list, header_list);
+
+    // Separate cookie header fields are reassembled at the position of the first one.
+    let mut header_table_receiver = HeaderTable::new(4096);
+    let mut header_table_sender = HeaderTable::new(4096);
+    let header_list = HeaderList::new(vec![
+        HeaderField::new(HeaderName::from("cookie"), HeaderValue::from("a=1")),
+        HeaderField::new(HeaderName::from("accept"), HeaderValue::from("*/*")),
+        HeaderField::new(HeaderName::from("cookie"), HeaderValue::from("b=2")),
+    ]);
+    let mut encoded_header_list = header_list.encode(&mut header_table_sender).unwrap();
+    let decoded_header_list =
+        HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    assert_eq!(
+        decoded_header_list,
+        HeaderList::new(vec![
+            HeaderField::new(HeaderName::from("cookie"), HeaderValue::from("a=1; b=2")),
+            HeaderField::new(HeaderName::from("accept"), HeaderValue::from("*/*")),
+        ])
+    );
+
+    // Without crumbling, cookie header fields are left as is.
+    let mut header_table_receiver = HeaderTable::new(4096);
+    header_table_receiver.set_cookie_crumbling(false);
+    let mut header_table_sender = HeaderTable::new(4096);
+    header_table_sender.set_cookie_crumbling(false);
+    let mut encoded_header_list = header_list.encode(&mut header_table_sender).unwrap();
+    assert_eq!(header_table_sender.get(62).unwrap().value(), "b=2".into());
+    assert_eq!(header_table_sender.get(64).unwrap().value(), "a=1".into());
+    let decoded_header_list =
+        HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    assert_eq!(decoded_header_list, header_list);
+}
+
+/// Check a sequence of header blocks of a connection in both directions.
+///
+/// The header blocks must decode into the header lists, and the header
+/// lists must encode into the same header blocks, the same way twice. The
+/// dynamic tables of both ends must have the given sizes after each header
+/// block.
+fn check_round_trip(
+    max_size: usize,
+    huffman_encoding: bool,
+    header_blocks: Vec<(Vec<u8>, HeaderList, usize)>,
+) {
+    let mut header_table_decoder = HeaderTable::new(max_size);
+    let mut header_table_sender = HeaderTable::new(max_size);
+    header_table_sender.set_huffman_encoding(huffman_encoding);
+    let mut header_table_other_sender = HeaderTable::new(max_size);
+    header_table_other_sender.set_huffman_encoding(huffman_encoding);
+    let mut header_table_receiver = HeaderTable::new(max_size);
+
+    for (header_block, header_list, table_size) in header_blocks {
+        let decoded_header_list =
+            HeaderList::decode(&mut header_block.clone(), &mut header_table_decoder).unwrap();
+        assert_eq!(decoded_header_list, header_list);
+        assert_eq!(header_table_decoder.get_dynamic_table_size(), table_size);
+
+        let mut encoded_header_list = header_list.encode(&mut header_table_sender).unwrap();
+        assert_eq!(encoded_header_list, header_block);
+        assert_eq!(
+            header_list.encode(&mut header_table_other_sender).unwrap(),
+            encoded_header_list
+        );
+        assert_eq!(header_table_sender.get_dynamic_table_size(), table_size);
+
+        let decoded_header_list =
+            HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+        assert_eq!(decoded_header_list, header_list);
+        assert_eq!(header_table_receiver.get_dynamic_table_size(), table_size);
+    }
+}
+
+#[test]
+pub fn test_header_list_representation_examples() {
+    // C.2.1 : Literal Header Field with Indexing
+    check_round_trip(
+        4096,
+        false,
+        vec![(
+            vec![
+                0x40, 0x0a, 0x63, 0x75, 0x73, 0x74, 0x6f, 0x6d, 0x2d, 0x6b, 0x65, 0x79, 0x0d, 0x63,
+                0x75, 0x73, 0x74, 0x6f, 0x6d, 0x2d, 0x68, 0x65, 0x61, 0x64, 0x65, 0x72,
+            ],
+            header_list(&[("custom-key", "custom-header")]),
+            55,
+        )],
+    );
+
+    // C.2.2 : Literal Header Field without Indexing
+    let mut header_table = HeaderTable::new(4096);
+    let mut header_block = vec![
+        0x04, 0x0c, 0x2f, 0x73, 0x61, 0x6d, 0x70, 0x6c, 0x65, 0x2f, 0x70, 0x61, 0x74, 0x68,
+    ];
+    let decoded_header_list = HeaderList::decode(&mut header_block, &mut header_table).unwrap();
+    assert_eq!(
+        decoded_header_list,
+        header_list(&[(":path", "/sample/path")])
+    );
+    assert_eq!(header_table.get_dynamic_table_size(), 0);
+
+    // C.2.3 : Literal Header Field Never Indexed
+    let mut header_block = vec![
+        0x10, 0x08, 0x70, 0x61, 0x73, 0x73, 0x77, 0x6f, 0x72, 0x64, 0x06, 0x73, 0x65, 0x63, 0x72,
+        0x65, 0x74,
+    ];
+    let decoded_header_list = HeaderList::decode(&mut header_block, &mut header_table).unwrap();
+    assert_eq!(decoded_header_list, header_list(&[("password", "secret")]));
+    assert!(decoded_header_list.header_fields()[0].sensitive());
+    assert_eq!(header_table.get_dynamic_table_size(), 0);
+
+    // C.2.4 : Indexed Header Field
+    check_round_trip(
+        4096,
+        false,
+        vec![(vec![0x82], header_list(&[(":method", "GET")]), 0)],
+    );
+}
+
+#[test]
+pub fn test_header_list_request_examples() {
+    let request_1 = header_list(&[
+        (":method", "GET"),
+        (":scheme", "http"),
+        (":path", "/"),
+        (":authority", "www.example.com"),
+    ]);
+    let request_2 = header_list(&[
+        (":method", "GET"),
+        (":scheme", "http"),
+        (":path", "/"),
+        (":authority", "www.example.com"),
+        ("cache-control", "no-cache"),
+    ]);
+    let request_3 = header_list(&[
+        (":method", "GET"),
+        (":scheme", "https"),
+        (":path", "/index.html"),
+        (":authority", "www.example.com"),
+        ("custom-key", "custom-value"),
+    ]);
+
+    // C.3 : Request Examples without Huffman Coding
+    check_round_trip(
+        4096,
+        false,
+        vec![
+            (
+                vec![
+                    0x82, 0x86, 0x84, 0x41, 0x0f, 0x77, 0x77, 0x77, 0x2e, 0x65, 0x78, 0x61, 0x6d,
+                    0x70, 0x6c, 0x65, 0x2e, 0x63, 0x6f, 0x6d,
+                ],
+                request_1.clone(),
+                57,
+            ),
+            (
+                vec![
+                    0x82, 0x86, 0x84, 0xbe, 0x58, 0x08, 0x6e, 0x6f, 0x2d, 0x63, 0x61, 0x63, 0x68,
+                    0x65,
+                ],
+                request_2.clone(),
+                110,
+            ),
+            (
+                vec![
+                    0x82, 0x87, 0x85, 0xbf, 0x40, 0x0a, 0x63, 0x75, 0x73, 0x74, 0x6f, 0x6d, 0x2d,
+                    0x6b, 0x65, 0x79, 0x0c, 0x63, 0x75, 0x73, 0x74, 0x6f, 0x6d, 0x2d, 0x76, 0x61,
+                    0x6c, 0x75, 0x65,
+                ],
+                request_3.clone(),
+                164,
+            ),
+        ],
+    );
+
+    // C.4 : Request Examples with Huffman Coding
+    check_round_trip(
+        4096,
+        true,
+        vec![
+            (
+                vec![
+                    0x82, 0x86, 0x84, 0x41, 0x8c, 0xf1, 0xe3, 0xc2, 0xe5, 0xf2, 0x3a, 0x6b, 0xa0,
+                    0xab, 0x90, 0xf4, 0xff,
+                ],
+                request_1,
+                57,
+            ),
+            (
+                vec![
+                    0x82, 0x86, 0x84, 0xbe, 0x58, 0x86, 0xa8, 0xeb, 0x10, 0x64, 0x9c, 0xbf,
+                ],
+                request_2,
+                110,
+            ),
+            (
+                vec![
+                    0x82, 0x87, 0x85, 0xbf, 0x40, 0x88, 0x25, 0xa8, 0x49, 0xe9, 0x5b, 0xa9, 0x7d,
+                    0x7f, 0x89, 0x25, 0xa8, 0x49, 0xe9, 0x5b, 0xb8, 0xe8, 0xb4, 0xbf,
+                ],
+                request_3,
+                164,
+            ),
+        ],
+    );
+}
+
+#[test]
+pub fn test_header_list_response_examples() {
+    let response_1 = header_list(&[
+        (":status", "302"),
+        ("cache-control", "private"),
+        ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+        ("location", "https://www.example.com"),
+    ]);
+    let response_2 = header_list(&[
+        (":status", "307"),
+        ("cache-control", "private"),
+        ("date", "Mon, 21 Oct 2013 20:13:21 GMT"),
+        ("location", "https://www.example.com"),
+    ]);
+    let response_3 = header_list(&[
+        (":status", "200"),
+        ("cache-control", "private"),
+        ("date", "Mon, 21 Oct 2013 20:13:22 GMT"),
+        ("location", "https://www.example.com"),
+        ("content-encoding", "gzip"),
+        (
+            "set-cookie",
+            "foo=ASDJKHQKBZXOQWEOPIUAXQWEOIU; max-age=3600; version=1",
+        ),
+    ]);
+
+    // C.6 : Response Examples with Huffman Coding, C.5 is covered by
+    // test_header_list_eviction.
+    check_round_trip(
+        256,
+        true,
+        vec![
+            (
+                vec![
+                    0x48, 0x82, 0x64, 0x02, 0x58, 0x85, 0xae, 0xc3, 0x77, 0x1a, 0x4b, 0x61, 0x96,
+                    0xd0, 0x7a, 0xbe, 0x94, 0x10, 0x54, 0xd4, 0x44, 0xa8, 0x20, 0x05, 0x95, 0x04,
+                    0x0b, 0x81, 0x66, 0xe0, 0x82, 0xa6, 0x2d, 0x1b, 0xff, 0x6e, 0x91, 0x9d, 0x29,
+                    0xad, 0x17, 0x18, 0x63, 0xc7, 0x8f, 0x0b, 0x97, 0xc8, 0xe9, 0xae, 0x82, 0xae,
+                    0x43, 0xd3,
+                ],
+                response_1,
+                222,
+            ),
+            (
+                vec![0x48, 0x83, 0x64, 0x0e, 0xff, 0xc1, 0xc0, 0xbf],
+                response_2,
+                222,
+            ),
+            (
+                vec![
+                    0x88, 0xc1, 0x61, 0x96, 0xd0, 0x7a, 0xbe, 0x94, 0x10, 0x54, 0xd4, 0x44, 0xa8,
+                    0x20, 0x05, 0x95, 0x04, 0x0b, 0x81, 0x66, 0xe0, 0x84, 0xa6, 0x2d, 0x1b, 0xff,
+                    0xc0, 0x5a, 0x83, 0x9b, 0xd9, 0xab, 0x77, 0xad, 0x94, 0xe7, 0x82, 0x1d, 0xd7,
+                    0xf2, 0xe6, 0xc7, 0xb3, 0x35, 0xdf, 0xdf, 0xcd, 0x5b, 0x39, 0x60, 0xd5, 0xaf,
+                    0x27, 0x08, 0x7f, 0x36, 0x72, 0xc1, 0xab, 0x27, 0x0f, 0xb5, 0x29, 0x1f, 0x95,
+                    0x87, 0x31, 0x60, 0x65, 0xc0, 0x03, 0xed, 0x4e, 0xe5, 0xb1, 0x06, 0x3d, 0x50,
+                    0x07,
+                ],
+                response_3,
+                215,
+            ),
+        ],
+    );
+}
+
+#[test]
+pub fn test_header_list_repeated_fields() {
+    let mut header_table_sender = HeaderTable::new(100);
+    let mut header_table_receiver = HeaderTable::new(100);
+
+    // Indexing x-b (s = 65) would evict x-a (s = 36), repeated after it.
+    let repeated_header_list = header_list(&[
+        ("x-a", "1"),
+        ("x-b", "012345678901234567890123456789"),
+        ("x-a", "1"),
+    ]);
+    let mut encoded_header_list = repeated_header_list
+        .encode(&mut header_table_sender)
+        .unwrap();
+    assert_eq!(
+        encoded_header_list[..7],
+        [0x40, 0x03, 0x78, 0x2d, 0x61, 0x01, 0x31]
+    );
+    assert_eq!(encoded_header_list[7], 0x00);
+    assert_eq!(
+        encoded_header_list[encoded_header_list.len() - 1],
+        0x80 | 62
+    );
+    assert_eq!(header_table_sender.get_dynamic_table_size(), 36);
+
+    let decoded_header_list =
+        HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    assert_eq!(decoded_header_list, repeated_header_list);
+    assert_eq!(header_table_receiver.get_dynamic_table_size(), 36);
+
+    // Once x-a is not referenced anymore, x-b is indexed.
+    let encoded_header_list = header_list(&[("x-b", "012345678901234567890123456789")])
+        .encode(&mut header_table_sender)
+        .unwrap();
+    assert_eq!(encoded_header_list[0], 0x40);
+    assert_eq!(header_table_sender.get_dynamic_table_size(), 65);
+
+    // A header field larger than the dynamic table is not indexed.
+    let encoded_header_list = header_list(&[("x-c", &"c".repeat(100))])
+        .encode(&mut header_table_sender)
+        .unwrap();
+    assert_eq!(encoded_header_list[0], 0x00);
+    assert_eq!(header_table_sender.get_dynamic_table_size(), 65);
+}
+
+#[test]
+pub fn test_header_list_order() {
+    let mut header_table_sender = HeaderTable::new(4096);
+    let mut header_table_receiver = HeaderTable::new(4096);
+    header_table_sender.set_cookie_crumbling(true);
+    header_table_receiver.set_cookie_crumbling(true);
+
+    // Repeated header fields keep their relative order, interleaved with
+    // other header fields, whether they are sent as literals or indexed.
+    let mut response = header_list(&[
+        (":status", "200"),
+        ("set-cookie", "a=1; Path=/"),
+        ("vary", "accept"),
+        ("set-cookie", "b=2"),
+        ("vary", "cookie"),
+        ("set-cookie", "a=1; Path=/"),
+    ]);
+    response.append("set-cookie", "c=3");
+    for _ in 0..2 {
+        let mut encoded_header_list = response.encode(&mut header_table_sender).unwrap();
+        let decoded_header_list =
+            HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+        assert_eq!(decoded_header_list, response);
+        assert_eq!(
+            decoded_header_list
+                .get_all("set-cookie")
+                .collect::<Vec<&str>>(),
+            vec!["a=1; Path=/", "b=2", "a=1; Path=/", "c=3"]
+        );
+    }
+
+    // Only the cookie header field is crumbled, its pairs keep their order.
+    let request = header_list(&[
+        (":method", "GET"),
+        ("cookie", "b=2; a=1"),
+        ("accept", "text/html"),
+        ("accept", "application/json"),
+    ]);
+    let mut encoded_header_list = request.encode(&mut header_table_sender).unwrap();
+    header_table_receiver.set_cookie_crumbling(false);
+    let decoded_header_list =
+        HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    assert_eq!(
+        decoded_header_list,
+        header_list(&[
+            (":method", "GET"),
+            ("cookie", "b=2"),
+            ("cookie", "a=1"),
+            ("accept", "text/html"),
+            ("accept", "application/json"),
+        ])
+    );
+}
+
+#[test]
+pub fn test_header_list_sensitive_cookie() {
+    let mut header_table_sender = HeaderTable::new(4096);
+    let mut header_table_receiver = HeaderTable::new(4096);
+
+    // The crumbs of a sensitive cookie are never indexed.
+    let mut cookie = HeaderField::new(HeaderName::from("cookie"), HeaderValue::from("a=1; b=2"));
+    cookie.set_sensitive(true);
+    let header_list = HeaderList::new(vec![cookie]);
+    let mut encoded_header_list = header_list.encode(&mut header_table_sender).unwrap();
+    assert_eq!(encoded_header_list[0], 0x1f);
+    assert_eq!(header_table_sender.get_dynamic_table_size(), 0);
+
+    let decoded_header_list =
+        HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    assert_eq!(decoded_header_list, header_list);
+    assert!(decoded_header_list.header_fields()[0].sensitive());
+}
+
+#[test]
+pub fn test_header_list_stats() {
+    let mut header_table_sender = HeaderTable::new(4096);
+    let mut header_table_receiver = HeaderTable::new(4096);
+    header_table_sender.set_huffman_encoding(true);
+
+    // The requests of RFC 7541 Appendix C.4.
+    let requests = [
+        header_list(&[
+            (":method", "GET"),
+            (":scheme", "http"),
+            (":path", "/"),
+            (":authority", "www.example.com"),
+        ]),
+        header_list(&[
+            (":method", "GET"),
+            (":scheme", "http"),
+            (":path", "/"),
+            (":authority", "www.example.com"),
+            ("cache-control", "no-cache"),
+        ]),
+    ];
+    for request in &requests {
+        let mut encoded_header_list = request.encode(&mut header_table_sender).unwrap();
+        HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    }
+
+    // The authority is indexed in the dynamic table by the first request,
+    // Huffman encoding saves 3 octets on it and 2 octets on no-cache.
+    let stats = header_table_sender.stats();
+    assert_eq!(stats.header_blocks(), 2);
+    assert_eq!(stats.header_fields(), 9);
+    assert_eq!(stats.dynamic_table_hits(), 1);
+    assert_eq!(stats.uncompressed_bytes(), 125);
+    assert_eq!(stats.compressed_bytes(), 17 + 12);
+    assert_eq!(stats.huffman_savings(), 5);
+    assert_eq!(stats.evictions(), 0);
+    assert_eq!(stats.dynamic_table_hit_rate(), 1.0 / 9.0);
+    assert_eq!(stats.compression_ratio(), 29.0 / 125.0);
+
+    // Huffman savings are only counted when encoding.
+    let stats = header_table_receiver.stats();
+    assert_eq!(stats.header_fields(), 9);
+    assert_eq!(stats.dynamic_table_hits(), 1);
+    assert_eq!(stats.compressed_bytes(), 29);
+    assert_eq!(stats.huffman_savings(), 0);
+
+    // Entries evicted from a small dynamic table.
+    let mut header_table_sender = HeaderTable::new(100);
+    let mut header_table_receiver = HeaderTable::new(100);
+    assert_eq!(header_table_sender.stats().compression_ratio(), 1.0);
+    let mut encoded_header_list = header_list(&[("a", "1"), ("b", "2"), ("c", "3")])
+        .encode(&mut header_table_sender)
+        .unwrap();
+    HeaderList::decode(&mut encoded_header_list, &mut header_table_receiver).unwrap();
+    assert_eq!(header_table_sender.stats().evictions(), 1);
+    assert_eq!(header_table_receiver.stats().evictions(), 1);
+}
+
+#[test]
+pub fn test_header_list_lowercase_names() {
+    let mut header_name = HeaderName::from("Content-Type");
+    assert!(!header_name.is_lowercase());
+    header_name.normalize();
+    assert_eq!(header_name, HeaderName::from("content-type"));
+    assert!(header_name.is_lowercase());
+
+    // Names are stored as given and sent lowercase.
+    let mut request = HeaderList::default();
+    request.append("Content-Type", "text/plain");
+    assert_eq!(
+        request.iter().next().unwrap().name(),
+        HeaderName::from("Content-Type")
+    );
+    let mut encoded = request.encode(&mut HeaderTable::new(4096)).unwrap();
+    let decoded = HeaderList::decode(&mut encoded, &mut HeaderTable::new(4096)).unwrap();
+    assert_eq!(decoded, header_list(&[("content-type", "text/plain")]));
+
+    // Literal without indexing "A: b", then literal with incremental
+    // indexing "c: d".
+    let block = vec![0x00, 0x01, b'A', 0x01, b'b', 0x40, 0x01, b'c', 0x01, b'd'];
+
+    // The whole header block is decoded before the uppercase name is
+    // rejected.
+    let mut header_table = HeaderTable::new(4096);
+    match HeaderList::decode(&mut block.clone(), &mut header_table) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert_eq!(
+        header_table.get(62).unwrap(),
+        HeaderField::new("c".into(), "d".into())
+    );
+
+    let mut header_table = HeaderTable::new(4096);
+    header_table.set_lenient_header_names(true);
+    assert!(header_table.lenient_header_names());
+    assert_eq!(
+        HeaderList::decode(&mut block.clone(), &mut header_table).unwrap(),
+        header_list(&[("a", "b"), ("c", "d")])
+    );
+}
+
+#[test]
+pub fn test_header_list_decode_error() {
+    // Literal with incremental indexing "c: d", then an index beyond the
+    // dynamic table.
+    let block = vec![0x40, 0x01, b'c', 0x01, b'd', 0xff, 0x00];
+
+    // A truncated header block fails before the header table is touched.
+    let mut header_table = HeaderTable::new(4096);
+    let mut bytes = block[..4].to_vec();
+    assert!(HeaderList::decode(&mut bytes, &mut header_table).is_err());
+    assert_eq!(bytes, block[..4].to_vec());
+    assert_eq!(header_table.get_dynamic_table_size(), 0);
+
+    // A header field failing to decode is a connection error, the byte
+    // vector is left as it was.
+    let mut bytes = block.clone();
+    match HeaderList::decode(&mut bytes, &mut header_table) {
+        Err(error) if error.is_connection_error() => {
+            assert_eq!(error.error_code(), ErrorCode::CompressionError)
+        }
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert_eq!(bytes, block);
+
+    // So is a header list exceeding the decoder limits.
+    header_table.set_decoder_limits(DecoderLimits {
+        max_header_fields: Some(1),
+        ..DecoderLimits::default()
+    });
+    let mut bytes = vec![0x40, 0x01, b'c', 0x01, b'd', 0x82];
+    match HeaderList::decode(&mut bytes, &mut header_table) {
+        Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert_eq!(bytes.len(), 6);
+}
+
+#[test]
+pub fn test_header_list_pseudo_header_order() {
+    // Literal without indexing "a: b", then indexed ":method: GET".
+    let block = vec![0x00, 0x01, b'a', 0x01, b'b', 0x82];
+
+    let mut header_table = HeaderTable::new(4096);
+    assert_eq!(header_table.lenience(), LenienceConfig::strict());
+    match HeaderList::decode(&mut block.clone(), &mut header_table) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // A tolerant table keeps the header fields in their order.
+    header_table.set_lenience(LenienceConfig::tolerant());
+    assert!(header_table.lenient_header_names());
+    assert_eq!(
+        HeaderList::decode(&mut block.clone(), &mut header_table).unwrap(),
+        header_list(&[("a", "b"), (":method", "GET")])
+    );
+
+    // Each violation is tolerated on its own.
+    header_table.set_lenience(LenienceConfig {
+        pseudo_header_order: true,
+        ..LenienceConfig::strict()
+    });
+    assert!(!header_table.lenient_header_names());
+    assert!(HeaderList::decode(&mut block.clone(), &mut header_table).is_ok());
+    let mut block = vec![0x00, 0x01, b'A', 0x01, b'b', 0x82];
+    match HeaderList::decode(&mut block, &mut header_table) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
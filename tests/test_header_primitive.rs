@@ -1,4 +1,6 @@
-use http2::header::primitive::HpackInteger;
+use http2::error::ErrorCode;
+use http2::header::limits::{DecoderLimits, LiteralBudget};
+use http2::header::primitive::{HpackInteger, HpackString, MAX_INTEGER_CONTINUATION_OCTETS};
 
 #[test]
 pub fn test_hpack_integer() {
@@ -9,20 +11,20 @@ pub fn test_hpack_integer() {
     // | X | X | X | 0 | 1 | 0 | 1 | 0 |   10 stored on 5 bits
     // +---+---+---+---+---+---+---+---+
     let mask: u8 = 0b10100000;
-    let integer = HpackInteger::from(10 as u128);
+    let integer = HpackInteger::from(10_u128);
 
     let mut encoded_integer = integer.encode(5).unwrap();
     assert_eq!(encoded_integer.len(), 1);
 
-    encoded_integer[0] = encoded_integer[0] + mask;
+    encoded_integer[0] += mask;
     assert_eq!(0b10101010, encoded_integer[0]);
 
     encoded_integer.push(0b00010110);
 
-    let decoded_integer = HpackInteger::decode(5, &mut encoded_integer).unwrap();
-    assert_eq!(HpackInteger::from(10 as u128), decoded_integer);
-    assert_eq!(encoded_integer.len(), 1);
-    assert_eq!(encoded_integer[0], 0b00010110);
+    let mut bytes = &encoded_integer[..];
+    let decoded_integer = HpackInteger::decode(5, &mut bytes).unwrap();
+    assert_eq!(HpackInteger::from(10_u128), decoded_integer);
+    assert_eq!(bytes, [0b00010110]);
 
     // Example 2: Encoding / Decoding 1337 Using a 5-Bit Prefix
     //
@@ -33,20 +35,20 @@ pub fn test_hpack_integer() {
     // | 0 | 0 | 0 | 0 | 1 | 0 | 1 | 0 |  10<128, encode(10), done
     // +---+---+---+---+---+---+---+---+
     let mask: u8 = 0b10100000;
-    let integer = HpackInteger::from(1337 as u128);
+    let integer = HpackInteger::from(1337_u128);
 
     let mut encoded_integer = integer.encode(5).unwrap();
     assert_eq!(encoded_integer.len(), 3);
 
-    encoded_integer[0] = encoded_integer[0] + mask;
+    encoded_integer[0] += mask;
     assert_eq!(vec![0b10111111, 0b10011010, 0b00001010], encoded_integer);
 
     encoded_integer.push(0b11111010);
 
-    let decoded_integer = HpackInteger::decode(5, &mut encoded_integer).unwrap();
-    assert_eq!(HpackInteger::from(1337 as u128), decoded_integer);
-    assert_eq!(encoded_integer.len(), 1);
-    assert_eq!(encoded_integer[0], 0b11111010);
+    let mut bytes = &encoded_integer[..];
+    let decoded_integer = HpackInteger::decode(5, &mut bytes).unwrap();
+    assert_eq!(HpackInteger::from(1337_u128), decoded_integer);
+    assert_eq!(bytes, [0b11111010]);
 
     // Example 3: Encoding / Decoding 42 starting at an Octet Boundary
     //
@@ -54,19 +56,162 @@ pub fn test_hpack_integer() {
     // +---+---+---+---+---+---+---+---+
     // | 0 | 0 | 1 | 0 | 1 | 0 | 1 | 0 |   42 stored on 8 bits
     // +---+---+---+---+---+---+---+---+
-    let integer = HpackInteger::from(42 as u128);
+    let integer = HpackInteger::from(42_u128);
 
     let mut encoded_integer = integer.encode(8).unwrap();
     assert_eq!(vec![0b00101010], encoded_integer);
 
     encoded_integer.push(0b11111010);
 
-    let decoded_integer = HpackInteger::decode(8, &mut encoded_integer).unwrap();
+    let mut bytes = &encoded_integer[..];
+    let decoded_integer = HpackInteger::decode(8, &mut bytes).unwrap();
 
-    assert_eq!(HpackInteger::from(42 as u128), decoded_integer);
-    assert_eq!(encoded_integer.len(), 1);
-    assert_eq!(encoded_integer[0], 0b11111010);
+    assert_eq!(HpackInteger::from(42_u128), decoded_integer);
+    assert_eq!(bytes, [0b11111010]);
+}
+
+#[test]
+pub fn test_hpack_integer_above_prefix() {
+    // 256 does not fit in a 5-bit prefix even though its low octet does.
+    let integer = HpackInteger::from(256_u128);
+
+    let encoded_integer = integer.encode(5).unwrap();
+    assert_eq!(vec![0b00011111, 0b11100001, 0b00000001], encoded_integer);
+
+    let mut bytes = &encoded_integer[..];
+    let decoded_integer = HpackInteger::decode(5, &mut bytes).unwrap();
+    assert_eq!(HpackInteger::from(256_u128), decoded_integer);
+    assert!(bytes.is_empty());
+}
+
+#[test]
+pub fn test_hpack_integer_continuation_octets() {
+    // The largest integer fitting in the continuation octets.
+    let mut encoded_integer = vec![0b00011111];
+    encoded_integer.extend(vec![0xff; MAX_INTEGER_CONTINUATION_OCTETS - 1]);
+    encoded_integer.push(0x7f);
+    let mut bytes = &encoded_integer[..];
+    let decoded_integer = HpackInteger::decode(5, &mut bytes).unwrap();
+    assert_eq!(
+        HpackInteger::from((1_u128 << (7 * MAX_INTEGER_CONTINUATION_OCTETS)) + 30),
+        decoded_integer
+    );
+    assert!(bytes.is_empty());
+
+    // Longer integers are rejected, even with a zero value, and so are
+    // truncated integers.
+    let cases = [
+        [
+            &[0b00011111][..],
+            &[0xff; MAX_INTEGER_CONTINUATION_OCTETS],
+            &[0x00],
+        ]
+        .concat(),
+        [&[0b00011111][..], &[0x80; 1_000], &[0x00]].concat(),
+        vec![0b00011111],
+        vec![0b00011111, 0x80],
+        vec![],
+    ];
+
+    for bytes in cases {
+        match HpackInteger::decode(5, &mut &bytes[..]) {
+            Err(error) if error.error_code() == ErrorCode::CompressionError => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+}
+
+#[test]
+pub fn test_hpack_string() {
+    // Literal string "custom-key".
+    let encoded_string = HpackString::from("custom-key").encode(false).unwrap();
+    assert_eq!(
+        encoded_string,
+        vec![0x0a, 0x63, 0x75, 0x73, 0x74, 0x6f, 0x6d, 0x2d, 0x6b, 0x65, 0x79]
+    );
+    let mut bytes = &encoded_string[..];
+    let decoded_string = HpackString::decode(&mut bytes, &mut LiteralBudget::default()).unwrap();
+    assert_eq!(decoded_string, HpackString::from("custom-key"));
+    assert!(bytes.is_empty());
+
+    // Empty strings are valid, e.g. as header values.
+    let encoded_string = HpackString::from("").encode(false).unwrap();
+    assert_eq!(encoded_string, vec![0x00]);
+    let decoded_string =
+        HpackString::decode(&mut &encoded_string[..], &mut LiteralBudget::default()).unwrap();
+    assert_eq!(decoded_string, HpackString::from(""));
+
+    // Huffman encoded string "www.example.com".
+    let encoded_string: Vec<u8> = vec![
+        0x8c, 0xf1, 0xe3, 0xc2, 0xe5, 0xf2, 0x3a, 0x6b, 0xa0, 0xab, 0x90, 0xf4, 0xff,
+    ];
+    let decoded_string =
+        HpackString::decode(&mut &encoded_string[..], &mut LiteralBudget::default()).unwrap();
+    assert_eq!(decoded_string, HpackString::from("www.example.com"));
+
+    // Truncated string.
+    let encoded_string: Vec<u8> = vec![0x03, 0x61, 0x62];
+    assert!(HpackString::decode(&mut &encoded_string[..], &mut LiteralBudget::default()).is_err());
 }
 
 #[test]
-pub fn test_hpack_string() {}
+pub fn test_hpack_string_literal_budget() {
+    let decoder_limits = DecoderLimits {
+        max_header_list_size: Some(5_000),
+        max_string_length: Some(4_096),
+        ..DecoderLimits::default()
+    };
+
+    // The length prefix is checked before the string octets are read.
+    let encoded_string: Vec<u8> = vec![0xff, 0xc1, 0x84, 0x3d, 0x61];
+    match HpackString::decode(
+        &mut &encoded_string[..],
+        &mut LiteralBudget::new(&decoder_limits),
+    ) {
+        Err(error) if error.error_code() == ErrorCode::EnhanceYourCalm => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // The string lengths of a header block add up to the header list size.
+    let mut literal_budget = LiteralBudget::new(&decoder_limits);
+    let value = "a".repeat(3_000);
+    let encoded_string = HpackString::from(value.as_str()).encode(false).unwrap();
+    let decoded_string =
+        HpackString::decode(&mut &encoded_string[..], &mut literal_budget).unwrap();
+    assert_eq!(decoded_string, HpackString::from(value.as_str()));
+    let encoded_string = HpackString::from(value.as_str()).encode(false).unwrap();
+    match HpackString::decode(&mut &encoded_string[..], &mut literal_budget) {
+        Err(error) if error.error_code() == ErrorCode::EnhanceYourCalm => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_hpack_encode_into() {
+    let mut bytes: Vec<u8> = vec![0xff];
+
+    // Encoding appends to the bytes already written.
+    HpackInteger::from(1337_u128)
+        .encode_into(5, &mut bytes)
+        .unwrap();
+    assert_eq!(bytes, vec![0xff, 0b00011111, 0b10011010, 0b00001010]);
+
+    // The H bit is set on the first octet of the string.
+    HpackString::from("no-cache")
+        .encode_into(true, &mut bytes)
+        .unwrap();
+    assert_eq!(bytes[4..], [0x86, 0xa8, 0xeb, 0x10, 0x64, 0x9c, 0xbf]);
+    HpackString::from("custom-key")
+        .encode_into(false, &mut bytes)
+        .unwrap();
+    assert_eq!(
+        bytes[11..],
+        HpackString::from("custom-key").encode(false).unwrap()
+    );
+
+    // An invalid prefix leaves the bytes unchanged.
+    assert!(HpackInteger::from(1_u128)
+        .encode_into(9, &mut bytes)
+        .is_err());
+    assert_eq!(bytes.len(), 22);
+}
@@ -0,0 +1,235 @@
+use http2::error::{CompressionError, ErrorCode, Http2Error};
+use http2::header::field::{HeaderField, HeaderName, HeaderValue};
+use http2::header::list::HeaderList;
+use http2::header::table::{
+    DynamicTable, HeaderTable, StaticTable, STATIC_HEADER_FIELDS_TABLE_CONSTANTS,
+};
+
+fn header_field(name: &str, value: &str) -> HeaderField {
+    HeaderField::new(HeaderName::from(name), HeaderValue::from(value))
+}
+
+#[test]
+pub fn test_dynamic_table_lookups() {
+    let mut dynamic_table = DynamicTable::new(4096);
+
+    dynamic_table.add_entry(header_field("custom-key", "value-1"));
+    dynamic_table.add_entry(header_field("other-key", "value"));
+    dynamic_table.add_entry(header_field("custom-key", "value-2"));
+
+    // Most recent entries have the lowest indexes.
+    assert_eq!(dynamic_table.len(), 3);
+    assert_eq!(dynamic_table.size(), 3 * 32 + 17 + 14 + 17);
+    assert_eq!(
+        dynamic_table.get(0).unwrap(),
+        header_field("custom-key", "value-2")
+    );
+    assert_eq!(
+        dynamic_table.get(2).unwrap(),
+        header_field("custom-key", "value-1")
+    );
+    match dynamic_table.get(3) {
+        Err(Http2Error::CompressionError(CompressionError::InvalidIndex(3))) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    assert_eq!(
+        dynamic_table.contains(&header_field("custom-key", "value-1")),
+        Some(2)
+    );
+    assert_eq!(
+        dynamic_table.contains(&header_field("custom-key", "value-3")),
+        None
+    );
+    assert_eq!(
+        dynamic_table.contains_name(&header_field("custom-key", "")),
+        Some(0)
+    );
+    assert_eq!(
+        dynamic_table.contains_name(&header_field("other-key", "")),
+        Some(1)
+    );
+
+    // Adding the same header field twice indexes the most recent copy.
+    dynamic_table.add_entry(header_field("custom-key", "value-1"));
+    assert_eq!(
+        dynamic_table.contains(&header_field("custom-key", "value-1")),
+        Some(0)
+    );
+}
+
+#[test]
+pub fn test_dynamic_table_eviction() {
+    // Room for exactly two entries of size 49.
+    let mut dynamic_table = DynamicTable::new(98);
+
+    dynamic_table.add_entry(header_field("custom-key", "value-1"));
+    dynamic_table.add_entry(header_field("custom-key", "value-2"));
+    dynamic_table.add_entry(header_field("custom-key", "value-3"));
+
+    assert_eq!(dynamic_table.len(), 2);
+    assert_eq!(dynamic_table.size(), 98);
+    assert_eq!(
+        dynamic_table.contains(&header_field("custom-key", "value-1")),
+        None
+    );
+    assert_eq!(
+        dynamic_table.contains(&header_field("custom-key", "value-2")),
+        Some(1)
+    );
+    assert_eq!(
+        dynamic_table.contains_name(&header_field("custom-key", "")),
+        Some(0)
+    );
+
+    // Shrinking the table evicts the oldest entries first.
+    dynamic_table.set_max_size(49);
+    assert_eq!(dynamic_table.len(), 1);
+    assert_eq!(
+        dynamic_table.contains(&header_field("custom-key", "value-2")),
+        None
+    );
+    assert_eq!(
+        dynamic_table.contains(&header_field("custom-key", "value-3")),
+        Some(0)
+    );
+
+    // An entry larger than the table empties it.
+    dynamic_table.add_entry(header_field("custom-key", "a-value-too-large"));
+    assert!(dynamic_table.is_empty());
+    assert_eq!(dynamic_table.size(), 0);
+    assert_eq!(
+        dynamic_table.contains_name(&header_field("custom-key", "")),
+        None
+    );
+}
+
+#[test]
+pub fn test_static_table_lookups() {
+    let static_table = StaticTable::from(STATIC_HEADER_FIELDS_TABLE_CONSTANTS);
+
+    // Every entry is found at its own index, names at their first index.
+    for (index, (name, value)) in STATIC_HEADER_FIELDS_TABLE_CONSTANTS.iter().enumerate() {
+        assert_eq!(
+            static_table.contains(&header_field(name, value)),
+            Some(index)
+        );
+        assert_eq!(
+            static_table
+                .contains_name(&header_field(name, "other"))
+                .map(|index| STATIC_HEADER_FIELDS_TABLE_CONSTANTS[index]),
+            STATIC_HEADER_FIELDS_TABLE_CONSTANTS
+                .iter()
+                .find(|(entry_name, _)| entry_name == name)
+                .copied()
+        );
+    }
+
+    assert_eq!(static_table.contains(&header_field(":method", "PUT")), None);
+    assert_eq!(
+        static_table.contains_name(&header_field(":method", "PUT")),
+        Some(1)
+    );
+    assert_eq!(
+        static_table.contains(&header_field(":status", "500")),
+        Some(13)
+    );
+    assert_eq!(
+        static_table.contains_name(&header_field("custom-key", "")),
+        None
+    );
+    assert_eq!(static_table.contains(&header_field("Accept", "")), None);
+    match static_table.get(STATIC_HEADER_FIELDS_TABLE_CONSTANTS.len()) {
+        Err(error) if error.error_code() == ErrorCode::CompressionError => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_header_table_index_boundaries() {
+    let mut header_table = HeaderTable::new(4096);
+
+    // Index 0 is not used by HPACK.
+    assert!(matches!(
+        header_table.get(0),
+        Err(Http2Error::CompressionError(CompressionError::ZeroIndex))
+    ));
+
+    // Indices 1 to 61 address the static table.
+    assert_eq!(header_table.get(1).unwrap(), header_field(":authority", ""));
+    assert_eq!(
+        header_table.get(61).unwrap(),
+        header_field("www-authenticate", "")
+    );
+
+    // Index 62 addresses the first entry of the dynamic table.
+    assert!(matches!(
+        header_table.get(62),
+        Err(Http2Error::CompressionError(
+            CompressionError::InvalidIndex(62)
+        ))
+    ));
+    header_table.add_entry(header_field("custom-key", "custom-value"));
+    assert_eq!(
+        header_table.get(62).unwrap(),
+        header_field("custom-key", "custom-value")
+    );
+    assert!(matches!(
+        header_table.get(63),
+        Err(Http2Error::CompressionError(
+            CompressionError::InvalidIndex(63)
+        ))
+    ));
+    assert!(matches!(
+        header_table.get(usize::MAX),
+        Err(Http2Error::CompressionError(
+            CompressionError::InvalidIndex(usize::MAX)
+        ))
+    ));
+}
+
+#[test]
+pub fn test_header_table_invalid_index_decoding() {
+    // Indexed header fields with index 0, 61, 62 and 63.
+    for (bytes, valid) in [(0x80, false), (0xbd, true), (0xbe, true), (0xbf, false)] {
+        let mut header_table = HeaderTable::new(4096);
+        header_table.add_entry(header_field("custom-key", "custom-value"));
+
+        match HeaderList::decode(&mut vec![bytes], &mut header_table) {
+            Ok(header_list) => assert!(valid && header_list.len() == 1),
+            Err(error) => {
+                assert!(!valid);
+                assert_eq!(error.error_code(), ErrorCode::CompressionError);
+                assert!(error.is_connection_error());
+            }
+        }
+    }
+}
+
+#[test]
+pub fn test_header_table_dynamic_entries() {
+    let mut header_table = HeaderTable::new(4096);
+    assert_eq!(header_table.dynamic_entries().count(), 0);
+    assert_eq!(header_table.to_string(), "      Table size:   0");
+
+    // Dynamic table of RFC 7541 appendix C.3.2.
+    header_table.add_entry(header_field(":authority", "www.example.com"));
+    header_table.add_entry(header_field("cache-control", "no-cache"));
+    let entries: Vec<(usize, &HeaderField)> = header_table.dynamic_entries().collect();
+    assert_eq!(
+        entries,
+        vec![
+            (1, &header_field("cache-control", "no-cache")),
+            (2, &header_field(":authority", "www.example.com")),
+        ]
+    );
+    assert_eq!(header_table.get(62).unwrap(), *entries[0].1);
+    assert_eq!(
+        header_table.to_string(),
+        concat!(
+            "[  1] (s =  53) cache-control: no-cache\n",
+            "[  2] (s =  57) :authority: www.example.com\n",
+            "      Table size: 110",
+        )
+    );
+}
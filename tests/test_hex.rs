@@ -0,0 +1,81 @@
+#![cfg(feature = "test-support")]
+
+use http2::header::list::HeaderList;
+use http2::header::table::HeaderTable;
+use http2::test_support::headers::header_list;
+use http2::test_support::hex;
+
+#[test]
+pub fn test_hex_parse() {
+    let dump = "\
+# RFC 7541 C.3.1 First Request
+8286 8441 0f77 7777 2e65 7861 6d70 6c65 | ...A.www.example
+2e63 6f6d                               | .com
+";
+    assert_eq!(
+        hex::parse(dump).unwrap(),
+        b"\x82\x86\x84\x41\x0fwww.example.com"
+    );
+
+    // Digits may be grouped in any way within a line.
+    assert_eq!(hex::parse("0 0 04 | ||\n\n").unwrap(), [0x00, 0x04]);
+    assert!(hex::parse("").unwrap().is_empty());
+
+    match hex::parse("00 0x04") {
+        Err(error) => assert!(error.starts_with("line 1:")),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    match hex::parse("# comment\n000") {
+        Err(error) => assert!(error.starts_with("line 2:")),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    match hex::fixture("missing") {
+        Err(error) => assert!(error.contains("missing.hex")),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_hex_fixtures() {
+    // C.4 : Request Examples with Huffman Coding
+    let mut header_table = HeaderTable::new(4096);
+    let mut requests = Vec::new();
+    for name in ["rfc7541_c4_1", "rfc7541_c4_2", "rfc7541_c4_3"] {
+        let mut header_block = hex::fixture(name).unwrap();
+        requests.push(HeaderList::decode(&mut header_block, &mut header_table).unwrap());
+    }
+    assert_eq!(
+        requests[2],
+        header_list(&[
+            (":method", "GET"),
+            (":scheme", "https"),
+            (":path", "/index.html"),
+            (":authority", "www.example.com"),
+            ("custom-key", "custom-value"),
+        ])
+    );
+    assert_eq!(header_table.get_dynamic_table_size(), 164);
+
+    // C.6 : Response Examples with Huffman Coding
+    let mut header_table = HeaderTable::new(256);
+    let mut responses = Vec::new();
+    for name in ["rfc7541_c6_1", "rfc7541_c6_2", "rfc7541_c6_3"] {
+        let mut header_block = hex::fixture(name).unwrap();
+        responses.push(HeaderList::decode(&mut header_block, &mut header_table).unwrap());
+    }
+    assert_eq!(
+        responses[2],
+        header_list(&[
+            (":status", "200"),
+            ("cache-control", "private"),
+            ("date", "Mon, 21 Oct 2013 20:13:22 GMT"),
+            ("location", "https://www.example.com"),
+            ("content-encoding", "gzip"),
+            (
+                "set-cookie",
+                "foo=ASDJKHQKBZXOQWEOPIUAXQWEOIU; max-age=3600; version=1",
+            ),
+        ])
+    );
+    assert_eq!(header_table.get_dynamic_table_size(), 215);
+}
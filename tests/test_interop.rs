@@ -0,0 +1,135 @@
+#![cfg(all(feature = "http", feature = "test-support"))]
+
+use http::{HeaderMap, Method, Request, Response, StatusCode};
+use http2::error::Http2Error;
+use http2::header::list::HeaderList;
+use http2::test_support::headers::header_list;
+
+#[test]
+pub fn test_interop_header_map() {
+    let mut header_map = HeaderMap::new();
+    header_map.append("accept", "text/html".parse().unwrap());
+    header_map.append("cookie", "a=1".parse().unwrap());
+    header_map.append("cookie", "b=2".parse().unwrap());
+    header_map.append("connection", "keep-alive".parse().unwrap());
+    header_map.append("transfer-encoding", "chunked".parse().unwrap());
+    header_map.append("te", "gzip".parse().unwrap());
+
+    // Connection-specific header fields are dropped.
+    let header_list = HeaderList::from(&header_map);
+    assert_eq!(
+        header_list,
+        self::header_list(&[
+            ("accept", "text/html"),
+            ("cookie", "a=1"),
+            ("cookie", "b=2")
+        ])
+    );
+
+    // Pseudo-header fields are skipped, repeated header fields are kept.
+    let header_list = self::header_list(&[
+        (":status", "200"),
+        ("set-cookie", "a=1"),
+        ("set-cookie", "b=2"),
+    ]);
+    let header_map = HeaderMap::try_from(&header_list).unwrap();
+    assert_eq!(header_map.len(), 2);
+    assert_eq!(
+        header_map.get_all("set-cookie").iter().collect::<Vec<_>>(),
+        vec!["a=1", "b=2"]
+    );
+
+    let header_list = self::header_list(&[("bad name", "value")]);
+    match HeaderMap::try_from(&header_list) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_interop_request() {
+    let request = Request::post("https://example.com/upload?id=1")
+        .header("content-type", "text/plain")
+        .header("te", "trailers")
+        .body(b"hello".to_vec())
+        .unwrap();
+
+    let header_list = HeaderList::try_from(&request).unwrap();
+    assert_eq!(
+        header_list,
+        self::header_list(&[
+            (":method", "POST"),
+            (":scheme", "https"),
+            (":authority", "example.com"),
+            (":path", "/upload?id=1"),
+            ("content-type", "text/plain"),
+            ("te", "trailers"),
+        ])
+    );
+
+    let converted = Request::try_from(&header_list).unwrap();
+    assert_eq!(converted.method(), Method::POST);
+    assert_eq!(converted.uri(), "https://example.com/upload?id=1");
+    assert_eq!(converted.version(), http::Version::HTTP_2);
+    assert_eq!(converted.headers(), request.headers());
+
+    // CONNECT requests only carry :method and :authority.
+    let request = Request::connect("example.com:443").body(()).unwrap();
+    assert_eq!(
+        HeaderList::try_from(&request).unwrap(),
+        self::header_list(&[(":method", "CONNECT"), (":authority", "example.com:443")])
+    );
+
+    // The host header field must match the authority of the URI.
+    let request = Request::get("https://example.com/")
+        .header("host", "EXAMPLE.com")
+        .body(())
+        .unwrap();
+    assert!(HeaderList::try_from(&request).is_ok());
+    let request = Request::get("https://example.com/")
+        .header("host", "example.org")
+        .body(())
+        .unwrap();
+    match HeaderList::try_from(&request) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // Other requests need a scheme.
+    let request = Request::get("/").body(()).unwrap();
+    match HeaderList::try_from(&request) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    let header_list = self::header_list(&[(":path", "/")]);
+    match Request::try_from(&header_list) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_interop_response() {
+    let response = Response::builder()
+        .status(StatusCode::NOT_FOUND)
+        .header("content-length", "0")
+        .body(())
+        .unwrap();
+
+    let header_list = HeaderList::from(&response);
+    assert_eq!(
+        header_list,
+        self::header_list(&[(":status", "404"), ("content-length", "0")])
+    );
+
+    let converted = Response::try_from(&header_list).unwrap();
+    assert_eq!(converted.status(), StatusCode::NOT_FOUND);
+    assert_eq!(converted.headers(), response.headers());
+
+    let header_list = self::header_list(&[(":status", "abc")]);
+    match Response::try_from(&header_list) {
+        Err(Http2Error::HeaderError(_)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
@@ -0,0 +1,40 @@
+#![cfg(feature = "test-support")]
+
+use http2::frame::{Frame, FrameHeader};
+use http2::header::list::HeaderList;
+use http2::header::table::HeaderTable;
+use http2::test_support;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_frame_header_round_trip(frame_header in test_support::frame_header()) {
+        let mut bytes = frame_header.serialize();
+        let decoded = FrameHeader::deserialize(&mut bytes).unwrap();
+        prop_assert_eq!(decoded, frame_header);
+        prop_assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_frame_round_trip(frame in test_support::frame()) {
+        let mut bytes = frame.serialize(&mut HeaderTable::new(4096)).unwrap();
+        let decoded = Frame::deserialize(&mut bytes, &mut HeaderTable::new(4096)).unwrap();
+        prop_assert_eq!(decoded, frame);
+        prop_assert!(bytes.is_empty());
+    }
+
+    #[test]
+    fn test_header_list_round_trip(
+        header_lists in proptest::collection::vec(test_support::header_list(), 1..4)
+    ) {
+        // Encode consecutive header lists to exercise the dynamic table.
+        let mut encoder_table = HeaderTable::new(4096);
+        let mut decoder_table = HeaderTable::new(4096);
+
+        for header_list in header_lists {
+            let mut bytes = header_list.encode(&mut encoder_table).unwrap();
+            let decoded = HeaderList::decode(&mut bytes, &mut decoder_table).unwrap();
+            prop_assert_eq!(decoded, header_list);
+        }
+    }
+}
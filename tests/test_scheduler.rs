@@ -0,0 +1,140 @@
+#![cfg(feature = "sync")]
+
+use http2::error::{FlowControlError, FlowControlScope, Http2Error};
+use http2::frame::data::DataFrame;
+use http2::frame::window_update::WindowUpdateFrame;
+use http2::stream::scheduler::Scheduler;
+
+/// Build DATA frames until the scheduler is blocked or empty.
+fn drain(scheduler: &mut Scheduler) -> Vec<DataFrame> {
+    std::iter::from_fn(|| scheduler.next_frame()).collect()
+}
+
+#[test]
+pub fn test_scheduler_round_robin() {
+    let mut scheduler = Scheduler::new();
+    scheduler.set_max_frame_size(10);
+    scheduler.push(1, &[1; 25], true);
+    scheduler.push(3, &[3; 15], false);
+
+    let frames = drain(&mut scheduler);
+    let order: Vec<(u32, usize, bool)> = frames
+        .iter()
+        .map(|frame| (frame.stream_id, frame.data.len(), frame.end_stream))
+        .collect();
+    assert_eq!(
+        order,
+        vec![
+            (1, 10, false),
+            (3, 10, false),
+            (1, 10, false),
+            (3, 5, false),
+            (1, 5, true),
+        ]
+    );
+
+    // Stream 1 ended and was removed, stream 3 remains without data.
+    assert_eq!(scheduler.buffered(3), 0);
+    assert!(!scheduler.is_empty());
+
+    // Ending a stream without data sends an empty DATA frame.
+    scheduler.push(3, &[], true);
+    assert_eq!(
+        scheduler.next_frame(),
+        Some(DataFrame::new(3, true, vec![]))
+    );
+    assert!(scheduler.is_empty());
+}
+
+#[test]
+pub fn test_scheduler_weights_and_urgency() {
+    let mut scheduler = Scheduler::new();
+    scheduler.set_max_frame_size(10);
+    scheduler.push(1, &[1; 200], false);
+    scheduler.push(3, &[3; 200], false);
+    scheduler.push(5, &[5; 20], false);
+    scheduler.set_priority(1, 3, 48);
+    scheduler.set_priority(3, 3, 16);
+    scheduler.set_priority(5, 0, 16);
+
+    let frames = drain(&mut scheduler);
+
+    // The most urgent stream is served first.
+    assert!(frames[..2].iter().all(|frame| frame.stream_id == 5));
+
+    // Stream 1 gets three times the bandwidth of stream 3.
+    let sent = |stream_id: u32| -> usize {
+        frames[2..18]
+            .iter()
+            .filter(|frame| frame.stream_id == stream_id)
+            .map(|frame| frame.data.len())
+            .sum()
+    };
+    assert_eq!(sent(1), 120);
+    assert_eq!(sent(3), 40);
+}
+
+#[test]
+pub fn test_scheduler_flow_control() {
+    let mut scheduler = Scheduler::new();
+    scheduler.set_initial_window_size(10);
+    scheduler.push(1, &[1; 30], false);
+    scheduler.push(3, &[3; 5], false);
+
+    // Stream 1 is blocked by its window, stream 3 is still served.
+    let frames = drain(&mut scheduler);
+    assert_eq!(frames.len(), 2);
+    assert_eq!(frames[0], DataFrame::new(1, false, vec![1; 10]));
+    assert_eq!(frames[1], DataFrame::new(3, false, vec![3; 5]));
+    assert_eq!(scheduler.buffered(1), 20);
+
+    // A WINDOW_UPDATE unblocks the stream.
+    scheduler
+        .apply_window_update(&WindowUpdateFrame::new(1, 100))
+        .unwrap();
+    assert_eq!(
+        drain(&mut scheduler),
+        vec![DataFrame::new(1, false, vec![1; 20])]
+    );
+    assert_eq!(scheduler.connection_window().size(), 65_535 - 35);
+
+    // The connection window blocks every stream.
+    scheduler.set_initial_window_size(100_000);
+    scheduler.push(5, &vec![5; 70_000], false);
+    let sent: usize = drain(&mut scheduler)
+        .iter()
+        .map(|frame| frame.data.len())
+        .sum();
+    assert_eq!(sent, 65_535 - 35);
+    assert_eq!(scheduler.connection_window().size(), 0);
+
+    scheduler
+        .apply_window_update(&WindowUpdateFrame::new(0, 10))
+        .unwrap();
+    assert_eq!(drain(&mut scheduler).len(), 1);
+
+    // Window overflows are flow control errors.
+    scheduler
+        .apply_window_update(&WindowUpdateFrame::new(0, 1))
+        .unwrap();
+    assert!(matches!(
+        scheduler.apply_window_update(&WindowUpdateFrame::new(0, 0x7FFF_FFFF)),
+        Err(Http2Error::FlowControlError(
+            FlowControlError::WindowOverflow(FlowControlScope::Connection, _, _)
+        ))
+    ));
+}
+
+#[test]
+pub fn test_scheduler_remove() {
+    let mut scheduler = Scheduler::new();
+    scheduler.push(1, &[1; 10], false);
+    scheduler.remove(1);
+    assert!(scheduler.is_empty());
+    assert_eq!(scheduler.next_frame(), None);
+
+    // Updates for unknown streams are ignored.
+    scheduler
+        .apply_window_update(&WindowUpdateFrame::new(1, 10))
+        .unwrap();
+}
@@ -0,0 +1,131 @@
+#![cfg(feature = "sync")]
+
+use http2::connection::builder::ConnectionBuilder;
+use http2::connection::send_queue::SendQueue;
+use http2::error::ErrorCode;
+use http2::frame::continuation::ContinuationFrame;
+use http2::frame::data::DataFrame;
+use http2::frame::headers::HeadersFrame;
+use http2::frame::padding::Padding;
+use http2::frame::ping::PingFrame;
+use http2::frame::rst_stream::RstStreamFrame;
+use http2::frame::window_update::WindowUpdateFrame;
+use http2::frame::Frame;
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::header::table::HeaderTable;
+use http2::start::HTTP2_CONNECTION_PREFACE_SEQUENCE;
+
+/// Split the output of a send queue into the types and stream identifiers
+/// of its frames.
+fn frames(mut bytes: &[u8]) -> Vec<(u8, u32)> {
+    let mut frames = Vec::new();
+    while !bytes.is_empty() {
+        let length = u32::from_be_bytes([0, bytes[0], bytes[1], bytes[2]]) as usize;
+        let stream_id = u32::from_be_bytes([bytes[5], bytes[6], bytes[7], bytes[8]]);
+        frames.push((bytes[3], stream_id));
+        bytes = &bytes[9 + length..];
+    }
+
+    frames
+}
+
+#[test]
+pub fn test_send_queue_control_frames_first() {
+    let mut send_queue = SendQueue::new();
+    send_queue.push(DataFrame::new(1, false, vec![0x2a; 100]).serialize(Padding::None));
+    send_queue.push(DataFrame::new(3, false, vec![0x2a; 100]).serialize(Padding::None));
+    send_queue.push(PingFrame::new([0; 8]).serialize());
+    send_queue.push(WindowUpdateFrame::new(0, 100).serialize());
+    assert_eq!(send_queue.len(), 2 * 109 + 17 + 13);
+    assert_eq!(send_queue.control_len(), 17 + 13);
+
+    // The frames of a stream keep their order.
+    send_queue.push(RstStreamFrame::new(3, ErrorCode::Cancel).serialize());
+    send_queue.push(RstStreamFrame::new(5, ErrorCode::Cancel).serialize());
+
+    assert_eq!(
+        frames(&send_queue.pop_all()),
+        vec![(0x6, 0), (0x8, 0), (0x3, 5), (0x0, 1), (0x0, 3), (0x3, 3)]
+    );
+    assert!(send_queue.is_empty());
+    assert_eq!(send_queue.control_len(), 0);
+}
+
+#[test]
+pub fn test_send_queue_limited() {
+    let mut send_queue = SendQueue::new();
+    send_queue.push_preface(HTTP2_CONNECTION_PREFACE_SEQUENCE);
+    send_queue.push(DataFrame::new(1, false, vec![0x2a; 100]).serialize(Padding::None));
+    send_queue.push(DataFrame::new(1, false, vec![0x2a; 100]).serialize(Padding::None));
+
+    // The connection preface is written first, then whole frames.
+    let bytes = send_queue.pop(150);
+    assert_eq!(bytes.len(), 24 + 109);
+    assert_eq!(&bytes[..24], HTTP2_CONNECTION_PREFACE_SEQUENCE);
+
+    // A control frame queued meanwhile jumps ahead of the rest.
+    send_queue.push(PingFrame::new([0; 8]).serialize());
+    assert_eq!(frames(&send_queue.pop(17)), vec![(0x6, 0)]);
+
+    // A frame larger than the limit is still dequeued.
+    assert_eq!(frames(&send_queue.pop(10)), vec![(0x0, 1)]);
+    assert_eq!(send_queue.pop(10), Vec::<u8>::new());
+}
+
+#[test]
+pub fn test_send_queue_header_block() {
+    let mut header_table = HeaderTable::new(4096);
+    let header_list = HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())]);
+
+    let mut send_queue = SendQueue::new();
+    send_queue.push(
+        HeadersFrame::new(1, false, false, None, header_list.clone())
+            .serialize(&mut header_table)
+            .unwrap(),
+    );
+    send_queue.push(
+        ContinuationFrame::new(1, true, header_list)
+            .serialize(&mut header_table)
+            .unwrap(),
+    );
+    assert_eq!(frames(&send_queue.pop(1)), vec![(0x1, 1)]);
+
+    // The header block is not interleaved with the control frame.
+    send_queue.push(PingFrame::new([0; 8]).serialize());
+    assert_eq!(frames(&send_queue.pop(1)), vec![(0x9, 1)]);
+    assert_eq!(frames(&send_queue.pop(1)), vec![(0x6, 0)]);
+}
+
+#[test]
+pub fn test_send_queue_core() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    server.handle_input(&client.poll_output()).unwrap();
+    client.handle_input(&server.poll_output()).unwrap();
+    server.handle_input(&client.poll_output()).unwrap();
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = client.send_request(request, false).unwrap();
+    client.send_data(stream_id, &[0x2a; 40_000], false).unwrap();
+    let queued = client.output_len();
+
+    // The transport only accepts the header block and one DATA frame.
+    let bytes = client.poll_output_limited(20_000);
+    assert_eq!(frames(&bytes), vec![(0x1, 1), (0x0, 1)]);
+
+    // The PING frame is written before the rest of the body.
+    client
+        .send_frame(&Frame::Ping(PingFrame::new([0; 8])))
+        .unwrap();
+    assert_eq!(client.output_len(), queued - bytes.len() + 17);
+    assert_eq!(
+        frames(&client.poll_output()),
+        vec![(0x6, 0), (0x0, 1), (0x0, 1)]
+    );
+    assert!(!client.has_output());
+}
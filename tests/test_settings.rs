@@ -0,0 +1,105 @@
+#![cfg(feature = "sync")]
+
+use http2::connection::settings::Settings;
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::settings::{SettingsFrame, SettingsParameter};
+
+#[test]
+pub fn test_settings_initial_values() {
+    let settings = Settings::new();
+    assert_eq!(settings.header_table_size(), 4_096);
+    assert!(settings.enable_push());
+    assert_eq!(settings.max_concurrent_streams(), None);
+    assert_eq!(settings.initial_window_size(), 65_535);
+    assert_eq!(settings.max_frame_size(), 16_384);
+    assert_eq!(settings.max_header_list_size(), None);
+    assert_eq!(settings, Settings::default());
+}
+
+#[test]
+pub fn test_settings_apply() {
+    let mut settings = Settings::new();
+
+    // The last value of a duplicated parameter wins.
+    let settings_frame = SettingsFrame::new()
+        .with_parameter(SettingsParameter::MaxConcurrentStreams(10))
+        .with_parameter(SettingsParameter::EnablePush(0))
+        .with_parameter(SettingsParameter::MaxFrameSize(32_768))
+        .with_parameter(SettingsParameter::MaxHeaderListSize(8_192))
+        .with_parameter(SettingsParameter::MaxConcurrentStreams(100));
+    settings.apply(&settings_frame).unwrap();
+    assert_eq!(settings.max_concurrent_streams(), Some(100));
+    assert!(!settings.enable_push());
+    assert_eq!(settings.max_frame_size(), 32_768);
+    assert_eq!(settings.max_header_list_size(), Some(8_192));
+
+    // Acknowledgements leave the settings unchanged.
+    let applied = settings;
+    settings.apply(&SettingsFrame::ack()).unwrap();
+    assert_eq!(settings, applied);
+}
+
+#[test]
+pub fn test_settings_apply_invalid() {
+    let cases = [
+        (SettingsParameter::EnablePush(2), ErrorCode::ProtocolError),
+        (
+            SettingsParameter::InitialWindowSize(2_147_483_648),
+            ErrorCode::FlowControlError,
+        ),
+        (
+            SettingsParameter::MaxFrameSize(16_383),
+            ErrorCode::ProtocolError,
+        ),
+        (
+            SettingsParameter::MaxFrameSize(16_777_216),
+            ErrorCode::ProtocolError,
+        ),
+        (
+            SettingsParameter::EnableConnectProtocol(2),
+            ErrorCode::ProtocolError,
+        ),
+    ];
+
+    for (settings_parameter, error_code) in cases {
+        // The valid parameters before the illegal one are not applied either.
+        let mut settings = Settings::new();
+        let settings_frame = SettingsFrame::new()
+            .with_parameter(SettingsParameter::MaxConcurrentStreams(10))
+            .with_parameter(settings_parameter);
+        match settings.apply(&settings_frame) {
+            Err(Http2Error::ConnectionError(code, _)) if code == error_code => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+        assert_eq!(settings, Settings::new());
+    }
+
+    // The boundaries are legal.
+    let mut settings = Settings::new();
+    let settings_frame = SettingsFrame::new()
+        .with_parameter(SettingsParameter::EnablePush(1))
+        .with_parameter(SettingsParameter::InitialWindowSize(2_147_483_647))
+        .with_parameter(SettingsParameter::MaxFrameSize(16_777_215));
+    settings.apply(&settings_frame).unwrap();
+    assert_eq!(settings.initial_window_size(), 2_147_483_647);
+}
+
+#[test]
+pub fn test_settings_enable_connect_protocol() {
+    let mut settings = Settings::new();
+    assert!(!settings.enable_connect_protocol());
+
+    let settings_frame =
+        SettingsFrame::new().with_parameter(SettingsParameter::EnableConnectProtocol(1));
+    settings.apply(&settings_frame).unwrap();
+    assert!(settings.enable_connect_protocol());
+
+    // Once enabled, extended CONNECT cannot be disabled.
+    let settings_frame =
+        SettingsFrame::new().with_parameter(SettingsParameter::EnableConnectProtocol(0));
+    match settings.apply(&settings_frame) {
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(settings.enable_connect_protocol());
+}
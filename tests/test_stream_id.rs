@@ -0,0 +1,64 @@
+#![cfg(feature = "sync")]
+
+use http2::connection::stream_id::{StreamIdGenerator, MAX_STREAM_ID};
+use http2::error::{ErrorCode, Http2Error};
+use http2::stream::Role;
+
+#[test]
+pub fn test_stream_id_generator_parity() {
+    let mut client = StreamIdGenerator::new(Role::Client);
+    assert_eq!(client.next_stream_id().unwrap(), 1);
+    assert_eq!(client.next_stream_id().unwrap(), 3);
+    assert_eq!(client.next_stream_id().unwrap(), 5);
+
+    let mut server = StreamIdGenerator::new(Role::Server);
+    assert_eq!(server.next_stream_id().unwrap(), 2);
+    assert_eq!(server.next_stream_id().unwrap(), 4);
+
+    // After an upgrade, stream 1 is already used by the upgrade request.
+    let mut client = StreamIdGenerator::with_next_stream_id(Role::Client, 3);
+    assert_eq!(client.next_stream_id().unwrap(), 3);
+}
+
+#[test]
+pub fn test_stream_id_generator_exhaustion() {
+    let mut client = StreamIdGenerator::with_next_stream_id(Role::Client, MAX_STREAM_ID - 2);
+    assert_eq!(client.next_stream_id().unwrap(), MAX_STREAM_ID - 2);
+    assert!(!client.is_exhausted());
+    assert_eq!(client.next_stream_id().unwrap(), MAX_STREAM_ID);
+    assert!(client.is_exhausted());
+    assert!(matches!(
+        client.next_stream_id(),
+        Err(Http2Error::ConnectionError(ErrorCode::NoError, _))
+    ));
+
+    let mut server = StreamIdGenerator::with_next_stream_id(Role::Server, MAX_STREAM_ID - 1);
+    assert_eq!(server.next_stream_id().unwrap(), MAX_STREAM_ID - 1);
+    assert!(server.is_exhausted());
+
+    // A starting identifier of the wrong parity or too large is exhausted.
+    assert!(StreamIdGenerator::with_next_stream_id(Role::Server, 3).is_exhausted());
+    assert!(StreamIdGenerator::with_next_stream_id(Role::Client, MAX_STREAM_ID + 2).is_exhausted());
+}
+
+#[test]
+pub fn test_stream_id_generator_remote() {
+    let mut server = StreamIdGenerator::new(Role::Server);
+    assert_eq!(server.last_remote_stream_id(), 0);
+    server.recv_stream_id(1).unwrap();
+    server.recv_stream_id(7).unwrap();
+    assert_eq!(server.last_remote_stream_id(), 7);
+
+    // Identifiers going backwards or of the local parity are rejected.
+    for stream_id in [0, 5, 7, 8] {
+        assert!(matches!(
+            server.recv_stream_id(stream_id),
+            Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+        ));
+    }
+    assert_eq!(server.last_remote_stream_id(), 7);
+
+    let mut client = StreamIdGenerator::new(Role::Client);
+    client.recv_stream_id(2).unwrap();
+    assert!(client.recv_stream_id(3).is_err());
+}
@@ -0,0 +1,401 @@
+#![cfg(feature = "test-support")]
+
+use std::time::{Duration, Instant};
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::continuation::ContinuationFrame;
+use http2::frame::data::DataFrame;
+use http2::frame::headers::HeadersFrame;
+use http2::stream::reset::ResetGuard;
+use http2::stream::{manager::StreamManager, Role, StreamState};
+use http2::test_support::headers::header_list;
+
+#[test]
+pub fn test_stream_manager_remote_limit() {
+    let mut manager = StreamManager::new(Role::Server);
+    manager.set_local_max_concurrent_streams(Some(2));
+
+    manager.open_remote(1).unwrap();
+    manager.open_remote(3).unwrap();
+    assert_eq!(manager.remote_active_streams(), 2);
+
+    // The third stream is refused.
+    assert!(matches!(
+        manager.open_remote(5),
+        Err(Http2Error::StreamError(5, ErrorCode::RefusedStream, _))
+    ));
+
+    // Half-closed streams still count.
+    manager.set_state(1, StreamState::HalfClosedRemote);
+    assert_eq!(manager.remote_active_streams(), 2);
+    assert!(manager.open_remote(5).is_err());
+
+    // Closing a stream makes room for a new one.
+    manager.close(1);
+    assert_eq!(manager.remote_active_streams(), 1);
+    assert_eq!(manager.open_remote(5).unwrap().state(), StreamState::Open);
+    assert_eq!(manager.remote_active_streams(), 2);
+
+    // Streams initiated by the server cannot be opened by the peer.
+    assert!(matches!(
+        manager.open_remote(2),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+}
+
+#[test]
+pub fn test_stream_manager_local_limit() {
+    let mut manager = StreamManager::new(Role::Client);
+    manager.set_remote_max_concurrent_streams(Some(1));
+
+    assert!(manager.can_open_local());
+    manager.open_local(1).unwrap();
+    assert_eq!(manager.local_active_streams(), 1);
+    assert!(!manager.can_open_local());
+
+    assert!(matches!(
+        manager.open_local(3),
+        Err(Http2Error::StreamError(3, ErrorCode::RefusedStream, _))
+    ));
+
+    // Pushed streams count once they leave the reserved state.
+    manager.set_state(2, StreamState::ReservedRemote);
+    assert_eq!(manager.remote_active_streams(), 0);
+    assert_eq!(
+        manager.open_remote(2).unwrap().state(),
+        StreamState::HalfClosedLocal
+    );
+    assert_eq!(manager.remote_active_streams(), 1);
+    assert_eq!(manager.local_active_streams(), 1);
+
+    manager.close(1);
+    assert!(manager.can_open_local());
+    manager.open_local(3).unwrap();
+}
+
+#[test]
+pub fn test_reset_guard() {
+    let mut guard = ResetGuard::new(2, Duration::from_secs(10));
+    let start = Instant::now();
+
+    guard.record(start).unwrap();
+    guard.record(start + Duration::from_secs(1)).unwrap();
+    assert!(matches!(
+        guard.record(start + Duration::from_secs(2)),
+        Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _))
+    ));
+
+    // Resets older than the interval are forgotten.
+    guard.record(start + Duration::from_secs(11)).unwrap();
+}
+
+#[test]
+pub fn test_stream_manager_rapid_reset() {
+    let mut manager = StreamManager::new(Role::Server);
+    manager.set_reset_guard(Some(ResetGuard::new(2, Duration::from_secs(10))));
+    let now = Instant::now();
+
+    // Streams finished by the server are not counted.
+    for stream_id in [1, 3, 5] {
+        manager.open_remote(stream_id).unwrap();
+        manager.set_state(stream_id, StreamState::HalfClosedLocal);
+        manager.recv_reset_at(stream_id, now).unwrap();
+        assert_eq!(manager.get(stream_id).unwrap().state(), StreamState::Closed);
+    }
+
+    // Streams opened then immediately reset are counted.
+    manager.open_remote(7).unwrap();
+    manager.recv_reset_at(7, now).unwrap();
+    manager.open_remote(9).unwrap();
+    manager.recv_reset_at(9, now).unwrap();
+    manager.open_remote(11).unwrap();
+    assert!(matches!(
+        manager.recv_reset_at(11, now),
+        Err(Http2Error::ConnectionError(ErrorCode::EnhanceYourCalm, _))
+    ));
+    assert_eq!(manager.remote_active_streams(), 0);
+
+    // The protection can be disabled.
+    manager.set_reset_guard(None);
+    manager.open_remote(13).unwrap();
+    manager.recv_reset_at(13, now).unwrap();
+}
+
+#[test]
+pub fn test_stream_manager_recv_trailers() {
+    let mut manager = StreamManager::new(Role::Server);
+
+    // Request headers open the stream.
+    let request = header_list(&[(":method", "POST"), (":path", "/")]);
+    manager
+        .recv_headers(&HeadersFrame::new(1, false, true, None, request))
+        .unwrap();
+    assert_eq!(manager.get(1).unwrap().state(), StreamState::Open);
+    assert!(manager.get(1).unwrap().headers_received());
+    assert!(manager.get(1).unwrap().trailers().is_none());
+
+    // A second header block is the trailer section and ends the stream.
+    let trailers = header_list(&[("grpc-status", "0")]);
+    manager
+        .recv_headers(&HeadersFrame::new(1, true, false, None, trailers))
+        .unwrap();
+    assert_eq!(
+        manager.get(1).unwrap().state(),
+        StreamState::HalfClosedRemote
+    );
+
+    // Trailers continued in CONTINUATION frames are appended.
+    let continued = header_list(&[("grpc-message", "OK")]);
+    manager
+        .recv_continuation(&ContinuationFrame::new(1, true, continued))
+        .unwrap();
+    assert_eq!(
+        manager.take_trailers(1),
+        Some(header_list(&[("grpc-status", "0"), ("grpc-message", "OK")]))
+    );
+    assert_eq!(manager.take_trailers(1), None);
+
+    // No header block is accepted once the peer ended the stream.
+    let trailers = header_list(&[("grpc-status", "0")]);
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(1, true, true, None, trailers)),
+        Err(Http2Error::StreamError(1, ErrorCode::StreamClosed, _))
+    ));
+}
+
+#[test]
+pub fn test_stream_manager_recv_trailers_invalid() {
+    let mut manager = StreamManager::new(Role::Client);
+    manager.open_local(1).unwrap();
+
+    // Informational responses are not the final header section.
+    let informational = header_list(&[(":status", "100")]);
+    manager
+        .recv_headers(&HeadersFrame::new(1, false, true, None, informational))
+        .unwrap();
+    assert!(!manager.get(1).unwrap().headers_received());
+
+    // Informational responses must not end the stream.
+    let informational = header_list(&[(":status", "103")]);
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(1, true, true, None, informational)),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+
+    let response = header_list(&[(":status", "200")]);
+    manager
+        .recv_headers(&HeadersFrame::new(1, false, true, None, response))
+        .unwrap();
+    assert!(manager.get(1).unwrap().headers_received());
+
+    // Trailers must end the stream.
+    let trailers = header_list(&[("grpc-status", "0")]);
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(1, false, true, None, trailers)),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+
+    // Trailers must not contain pseudo-header fields.
+    let trailers = header_list(&[(":status", "200")]);
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(1, true, true, None, trailers)),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+}
+
+#[test]
+pub fn test_stream_manager_send_trailers() {
+    let mut manager = StreamManager::new(Role::Client);
+    manager.open_local(1).unwrap();
+
+    assert!(matches!(
+        manager.send_trailers(1, &header_list(&[(":path", "/")])),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+
+    let trailers = header_list(&[("checksum", "abc")]);
+    manager.send_trailers(1, &trailers).unwrap();
+    assert_eq!(
+        manager.get(1).unwrap().state(),
+        StreamState::HalfClosedLocal
+    );
+
+    // The stream is already closed on the local side.
+    assert!(matches!(
+        manager.send_trailers(1, &trailers),
+        Err(Http2Error::StreamError(1, ErrorCode::StreamClosed, _))
+    ));
+}
+
+#[test]
+pub fn test_stream_manager_content_length() {
+    let mut manager = StreamManager::new(Role::Server);
+    let request = header_list(&[(":method", "POST"), ("content-length", "10")]);
+
+    // The DATA frames match the content-length.
+    manager
+        .recv_headers(&HeadersFrame::new(1, false, true, None, request.clone()))
+        .unwrap();
+    assert_eq!(manager.get(1).unwrap().content_length(), Some(10));
+    manager
+        .recv_data(&DataFrame::new(1, false, vec![0; 4]))
+        .unwrap();
+    manager
+        .recv_data(&DataFrame::new(1, true, vec![0; 6]))
+        .unwrap();
+    assert_eq!(manager.get(1).unwrap().data_received(), 10);
+    assert_eq!(
+        manager.get(1).unwrap().state(),
+        StreamState::HalfClosedRemote
+    );
+
+    // More DATA than announced is rejected as soon as it is received.
+    manager
+        .recv_headers(&HeadersFrame::new(3, false, true, None, request.clone()))
+        .unwrap();
+    assert!(matches!(
+        manager.recv_data(&DataFrame::new(3, false, vec![0; 11])),
+        Err(Http2Error::StreamError(3, ErrorCode::ProtocolError, _))
+    ));
+
+    // Less DATA than announced is rejected at the end of the stream,
+    // including when the stream ends with trailers.
+    manager
+        .recv_headers(&HeadersFrame::new(5, false, true, None, request.clone()))
+        .unwrap();
+    manager
+        .recv_data(&DataFrame::new(5, false, vec![0; 9]))
+        .unwrap();
+    let trailers = header_list(&[("checksum", "abc")]);
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(5, true, true, None, trailers)),
+        Err(Http2Error::StreamError(5, ErrorCode::ProtocolError, _))
+    ));
+
+    // A message without DATA frames.
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(7, true, true, None, request.clone())),
+        Err(Http2Error::StreamError(7, ErrorCode::ProtocolError, _))
+    ));
+
+    // Lenient mode accepts mismatches.
+    manager.set_enforce_content_length(false);
+    manager
+        .recv_headers(&HeadersFrame::new(9, true, true, None, request))
+        .unwrap();
+}
+
+#[test]
+pub fn test_stream_manager_content_length_invalid() {
+    let mut manager = StreamManager::new(Role::Server);
+
+    let request = header_list(&[("content-length", "+10")]);
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(1, false, true, None, request)),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+
+    let request = header_list(&[("content-length", "10"), ("content-length", "12")]);
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(3, false, true, None, request)),
+        Err(Http2Error::StreamError(3, ErrorCode::ProtocolError, _))
+    ));
+
+    // DATA is not accepted on idle streams.
+    assert!(matches!(
+        manager.recv_data(&DataFrame::new(5, true, vec![])),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+
+    // A 304 response has no content regardless of its content-length.
+    let mut manager = StreamManager::new(Role::Client);
+    manager.open_local(1).unwrap();
+    let response = header_list(&[(":status", "304"), ("content-length", "10")]);
+    manager
+        .recv_headers(&HeadersFrame::new(1, true, true, None, response))
+        .unwrap();
+}
+
+#[test]
+pub fn test_stream_manager_host_authority() {
+    let mut manager = StreamManager::new(Role::Server);
+
+    let request = header_list(&[
+        (":method", "GET"),
+        (":authority", "example.com"),
+        ("host", "Example.COM"),
+    ]);
+    manager
+        .recv_headers(&HeadersFrame::new(1, true, true, None, request))
+        .unwrap();
+
+    let request = header_list(&[
+        (":method", "GET"),
+        (":authority", "example.com"),
+        ("host", "example.org"),
+    ]);
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(3, true, true, None, request)),
+        Err(Http2Error::StreamError(3, ErrorCode::ProtocolError, _))
+    ));
+}
+
+#[test]
+pub fn test_stream_manager_closed_streams() {
+    let mut manager = StreamManager::new(Role::Server);
+    manager.set_max_closed_streams(1);
+    assert_eq!(manager.max_closed_streams(), 1);
+
+    // Frames other than HEADERS are rejected on idle streams.
+    assert!(matches!(
+        manager.recv_window_update(1),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+    assert!(matches!(
+        manager.recv_reset(1),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+
+    // Opening stream 5 implicitly closes stream 3.
+    let request = header_list(&[(":method", "GET"), (":path", "/")]);
+    for stream_id in [1, 5] {
+        manager
+            .recv_headers(&HeadersFrame::new(
+                stream_id,
+                true,
+                true,
+                None,
+                request.clone(),
+            ))
+            .unwrap();
+    }
+    assert!(matches!(
+        manager.recv_headers(&HeadersFrame::new(3, true, true, None, request.clone())),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+
+    // DATA on a closed stream is a stream error.
+    manager.close(1);
+    assert!(matches!(
+        manager.recv_data(&DataFrame::new(1, true, b"late".to_vec())),
+        Err(Http2Error::StreamError(1, ErrorCode::StreamClosed, _))
+    ));
+
+    // WINDOW_UPDATE and RST_STREAM are tolerated shortly after closing.
+    manager.recv_window_update(1).unwrap();
+    manager.recv_reset(1).unwrap();
+    assert_eq!(manager.get(1).unwrap().state(), StreamState::Closed);
+
+    // Stream 1 is forgotten once stream 5 is closed.
+    manager.close(5);
+    assert!(manager.get(1).is_none());
+    assert!(matches!(
+        manager.recv_window_update(1),
+        Err(Http2Error::ConnectionError(ErrorCode::ProtocolError, _))
+    ));
+    assert!(matches!(
+        manager.recv_data(&DataFrame::new(1, true, b"late".to_vec())),
+        Err(Http2Error::StreamError(1, ErrorCode::StreamClosed, _))
+    ));
+    manager.recv_window_update(5).unwrap();
+}
@@ -0,0 +1,165 @@
+#![cfg(feature = "test-support")]
+
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::continuation::ContinuationFrame;
+use http2::frame::data::DataFrame;
+use http2::frame::headers::HeadersFrame;
+use http2::frame::rst_stream::RstStreamFrame;
+use http2::frame::window_update::WindowUpdateFrame;
+use http2::frame::Frame;
+use http2::header::list::HeaderList;
+use http2::stream::message::{MessageAssembler, DEFAULT_MAX_BODY_SIZE};
+use http2::test_support::headers::header_list;
+
+fn headers(stream_id: u32, end_stream: bool, end_headers: bool, header_list: HeaderList) -> Frame {
+    Frame::Headers(HeadersFrame::new(
+        stream_id,
+        end_stream,
+        end_headers,
+        None,
+        header_list,
+    ))
+}
+
+fn data(stream_id: u32, end_stream: bool, data: &[u8]) -> Frame {
+    Frame::Data(DataFrame::new(stream_id, end_stream, data.to_vec()))
+}
+
+#[test]
+pub fn test_message_assembler_request() {
+    let request = header_list(&[(":method", "POST"), (":scheme", "http"), (":path", "/")]);
+    let mut assembler = MessageAssembler::new(1);
+    assert_eq!(assembler.max_body_size(), DEFAULT_MAX_BODY_SIZE);
+
+    // The header section is split into a CONTINUATION frame.
+    assert_eq!(
+        assembler
+            .push(&headers(
+                1,
+                false,
+                false,
+                header_list(&[(":method", "POST")])
+            ))
+            .unwrap(),
+        None
+    );
+    let continuation =
+        ContinuationFrame::new(1, true, header_list(&[(":scheme", "http"), (":path", "/")]));
+    assert_eq!(
+        assembler.push(&Frame::Continuation(continuation)).unwrap(),
+        None
+    );
+
+    // Frames which do not carry the message are ignored.
+    let window_update = WindowUpdateFrame::new(1, 100);
+    assert_eq!(
+        assembler.push(&Frame::WindowUpdate(window_update)).unwrap(),
+        None
+    );
+
+    assert_eq!(assembler.push(&data(1, false, b"hello ")).unwrap(), None);
+    assert_eq!(assembler.body_len(), 6);
+    let trailers = header_list(&[("grpc-status", "0")]);
+    assert_eq!(assembler.push(&data(1, false, b"world")).unwrap(), None);
+    let message = assembler
+        .push(&headers(1, true, true, trailers.clone()))
+        .unwrap()
+        .unwrap();
+    assert!(assembler.is_complete());
+    assert_eq!(message.stream_id(), 1);
+    assert!(!message.is_response());
+    assert_eq!(message.header_list(), &request);
+    assert_eq!(message.body(), b"hello world");
+    assert_eq!(message.trailers(), Some(&trailers));
+
+    // The stream is ended.
+    assert!(matches!(
+        assembler.push(&data(1, true, b"")),
+        Err(Http2Error::StreamError(1, ErrorCode::StreamClosed, _))
+    ));
+}
+
+#[test]
+pub fn test_message_assembler_response() {
+    let mut assembler = MessageAssembler::new(2);
+
+    // Interim responses are discarded.
+    let interim = header_list(&[(":status", "100")]);
+    assert_eq!(
+        assembler.push(&headers(2, false, true, interim)).unwrap(),
+        None
+    );
+
+    let response = header_list(&[(":status", "200")]);
+    assert_eq!(
+        assembler
+            .push(&headers(2, false, true, response.clone()))
+            .unwrap(),
+        None
+    );
+    let message = assembler.push(&data(2, true, b"ok")).unwrap().unwrap();
+    assert!(message.is_response());
+    assert_eq!(
+        message.into_parts(),
+        (response.clone(), b"ok".to_vec(), None)
+    );
+
+    // A message without content is complete with its header section.
+    let mut assembler = MessageAssembler::new(4);
+    let message = assembler
+        .push(&headers(4, true, true, response.clone()))
+        .unwrap()
+        .unwrap();
+    assert_eq!(message.header_list(), &response);
+    assert!(message.body().is_empty());
+}
+
+#[test]
+pub fn test_message_assembler_errors() {
+    let request = header_list(&[(":method", "GET"), (":scheme", "http"), (":path", "/")]);
+
+    // Content before the header section.
+    let mut assembler = MessageAssembler::new(1);
+    assert!(matches!(
+        assembler.push(&data(1, true, b"a")),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+
+    // Frame of another stream.
+    assert!(matches!(
+        assembler.push(&data(3, true, b"a")),
+        Err(Http2Error::StreamError(3, ErrorCode::ProtocolError, _))
+    ));
+
+    // Trailers without END_STREAM.
+    let mut assembler = MessageAssembler::new(1);
+    assembler
+        .push(&headers(1, false, true, request.clone()))
+        .unwrap();
+    assert!(matches!(
+        assembler.push(&headers(1, false, true, request.clone())),
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _))
+    ));
+
+    // Content exceeding the maximum body size.
+    let mut assembler = MessageAssembler::new(1);
+    assembler.set_max_body_size(4);
+    assembler
+        .push(&headers(1, false, true, request.clone()))
+        .unwrap();
+    assembler.push(&data(1, false, b"abcd")).unwrap();
+    assert!(matches!(
+        assembler.push(&data(1, true, b"e")),
+        Err(Http2Error::StreamError(1, ErrorCode::EnhanceYourCalm, _))
+    ));
+
+    // Stream reset by the peer.
+    let mut assembler = MessageAssembler::new(1);
+    assembler.push(&headers(1, false, true, request)).unwrap();
+    let rst_stream = RstStreamFrame::new(1, ErrorCode::Cancel);
+    assert!(matches!(
+        assembler.push(&Frame::RstStream(rst_stream)),
+        Err(Http2Error::StreamError(1, ErrorCode::Cancel, _))
+    ));
+    assert!(assembler.is_complete());
+}
@@ -0,0 +1,167 @@
+#![cfg(feature = "test-support")]
+
+use http2::connection::builder::ConnectionBuilder;
+use http2::connection::core::Event;
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::headers::HeadersFrame;
+use http2::frame::priority::PriorityFrame;
+use http2::frame::{Frame, FramePriority};
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::stream::priority::StreamPriority;
+use http2::stream::scheduler::Scheduler;
+use http2::test_support::{establish, transfer};
+
+#[test]
+pub fn test_stream_priority_field() {
+    let priority = StreamPriority::new();
+    assert_eq!(priority.urgency(), 3);
+    assert!(!priority.incremental());
+    assert_eq!(priority.weight(), 16);
+    assert_eq!(priority.stream_dependency(), 0);
+
+    let mut priority = StreamPriority::new();
+    priority.apply_priority_field("u=1, i");
+    assert_eq!(priority.urgency(), 1);
+    assert!(priority.incremental());
+
+    priority.apply_priority_field("i=?0, u=5;x=1, foo=bar");
+    assert_eq!(priority.urgency(), 5);
+    assert!(!priority.incremental());
+
+    // Invalid values are ignored.
+    priority.apply_priority_field("u=8, u=-1, u=a, i=1");
+    assert_eq!(priority.urgency(), 5);
+    assert!(!priority.incremental());
+}
+
+#[test]
+pub fn test_stream_priority_frame() {
+    let mut priority = StreamPriority::default();
+    priority.apply_frame_priority(&FramePriority::new(true, 3, 255));
+    assert_eq!(priority.weight(), 256);
+    assert_eq!(priority.stream_dependency(), 3);
+    assert_eq!(priority.urgency(), 3);
+}
+
+#[test]
+pub fn test_stream_priority_events() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+
+    // Requests without priority signals keep the defaults.
+    client.send_request(request.clone(), true).unwrap();
+    let events = transfer(&mut client, &mut server);
+    assert!(matches!(events[..], [Event::Headers { stream_id: 1, .. }]));
+    assert_eq!(
+        server.streams().get(1).unwrap().priority(),
+        StreamPriority::new()
+    );
+
+    // The priority signals of a request follow its header section.
+    let mut prioritized = request.clone();
+    prioritized.append("priority", "u=0, i");
+    let headers_frame = HeadersFrame::new(
+        3,
+        true,
+        true,
+        Some(FramePriority::new(false, 1, 31)),
+        prioritized,
+    );
+    client.send_frame(&Frame::Headers(headers_frame)).unwrap();
+    let events = transfer(&mut client, &mut server);
+    assert!(matches!(events[0], Event::Headers { stream_id: 3, .. }));
+    let priority = match events[1..] {
+        [Event::Priority {
+            stream_id: 3,
+            priority,
+        }] => priority,
+        _ => panic!("Unexpected events {:?}", events),
+    };
+    assert_eq!(priority.urgency(), 0);
+    assert!(priority.incremental());
+    assert_eq!(priority.weight(), 32);
+    assert_eq!(priority.stream_dependency(), 1);
+
+    // The scheduler serves the stream as signaled.
+    let mut scheduler = Scheduler::new();
+    scheduler.push(1, b"first", true);
+    scheduler.push(3, b"urgent", true);
+    scheduler.apply_priority(3, priority);
+    assert_eq!(scheduler.next_frame().unwrap().stream_id, 3);
+
+    // PRIORITY frames update the priority of known streams.
+    let priority_frame = [
+        0x00, 0x00, 0x05, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, // PRIORITY on stream 1
+        0x00, 0x00, 0x00, 0x03, 0xff, // Stream Dependency = 3, Weight = 256
+    ];
+    let events = server.handle_input(&priority_frame).unwrap();
+    let priority = server.streams().get(1).unwrap().priority();
+    assert_eq!(priority.weight(), 256);
+    assert_eq!(priority.stream_dependency(), 3);
+    assert_eq!(
+        events,
+        vec![Event::Priority {
+            stream_id: 1,
+            priority,
+        }]
+    );
+
+    // A stream cannot depend on itself, the stream is reset.
+    let priority_frame = [
+        0x00, 0x00, 0x05, 0x02, 0x00, 0x00, 0x00, 0x00, 0x03, // PRIORITY on stream 3
+        0x00, 0x00, 0x00, 0x03, 0x0f, // Stream Dependency = 3, Weight = 16
+    ];
+    match server.handle_input(&priority_frame) {
+        Err(Http2Error::StreamError(3, ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert!(server.streams().get(3).unwrap().reset_sent());
+}
+
+#[test]
+pub fn test_stream_priority_deprecated_frames() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .deprecate_priority_frames(true)
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    client.send_request(request, false).unwrap();
+    transfer(&mut client, &mut server);
+
+    // PRIORITY frames leave the priority of the stream untouched.
+    let priority_frame = PriorityFrame::new(1, FramePriority::new(false, 3, 255));
+    client.send_frame(&Frame::Priority(priority_frame)).unwrap();
+    assert_eq!(transfer(&mut client, &mut server), vec![]);
+    assert_eq!(
+        server.streams().get(1).unwrap().priority(),
+        StreamPriority::new()
+    );
+
+    // Their content is not checked, even a stream depending on itself.
+    let priority_frame = PriorityFrame::new(1, FramePriority::new(false, 1, 15));
+    client.send_frame(&Frame::Priority(priority_frame)).unwrap();
+    assert_eq!(transfer(&mut client, &mut server), vec![]);
+    assert!(!server.streams().get(1).unwrap().reset_sent());
+
+    // PRIORITY frames of the wrong size are still rejected.
+    let priority_frame = [
+        0x00, 0x00, 0x04, 0x02, 0x00, 0x00, 0x00, 0x00, 0x01, // PRIORITY on stream 1
+        0x00, 0x00, 0x00, 0x03, // Stream Dependency = 3
+    ];
+    assert!(server.handle_input(&priority_frame).is_err());
+}
@@ -0,0 +1,213 @@
+#![cfg(feature = "test-support")]
+
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use http2::connection::builder::ConnectionBuilder;
+use http2::connection::core::Event;
+use http2::error::{ErrorCode, Http2Error};
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::stream::timeout::{StreamTimeout, StreamTimeouts};
+use http2::stream::StreamState;
+use http2::sync::{self, Connection};
+use http2::test_support::{establish, transfer};
+
+fn request() -> HeaderList {
+    HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ])
+}
+
+#[test]
+pub fn test_stream_timeout_header() {
+    let mut client = ConnectionBuilder::client()
+        .header_timeout(Duration::from_secs(1))
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+    assert_eq!(
+        client.stream_timeouts(),
+        StreamTimeouts {
+            header_timeout: Some(Duration::from_secs(1)),
+            ..StreamTimeouts::default()
+        }
+    );
+    assert_eq!(client.poll_timeout(), None);
+
+    let start = Instant::now();
+    let stream_id = client.send_request(request(), true).unwrap();
+    transfer(&mut client, &mut server);
+    let deadline = client.poll_timeout().unwrap();
+    assert!(deadline >= start + Duration::from_secs(1));
+    assert!(client.handle_timeouts(start).unwrap().is_empty());
+
+    // The server did not respond in time, the stream is cancelled.
+    let errors = client.handle_timeouts(deadline).unwrap();
+    assert!(matches!(
+        errors[..],
+        [Http2Error::StreamTimeout(1, StreamTimeout::Headers)]
+    ));
+    assert_eq!(
+        client.streams().get(stream_id).unwrap().state(),
+        StreamState::Closed
+    );
+    assert_eq!(client.poll_timeout(), None);
+    assert_eq!(
+        transfer(&mut client, &mut server),
+        vec![Event::Reset {
+            stream_id,
+            error_code: ErrorCode::Cancel,
+        }]
+    );
+}
+
+#[test]
+pub fn test_stream_timeout_body_idle() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .header_timeout(Duration::from_secs(1))
+        .body_idle_timeout(Duration::from_secs(5))
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+
+    let stream_id = client.send_request(request(), false).unwrap();
+    transfer(&mut client, &mut server);
+    let start = Instant::now();
+
+    // The header section was received, the body idle timeout applies.
+    assert!(server
+        .handle_timeouts(start + Duration::from_secs(2))
+        .unwrap()
+        .is_empty());
+
+    // Each DATA frame postpones the deadline.
+    thread::sleep(Duration::from_millis(10));
+    client.send_data(stream_id, b"data", false).unwrap();
+    transfer(&mut client, &mut server);
+    let deadline = server.poll_timeout().unwrap();
+    assert!(deadline > start + Duration::from_secs(5));
+    assert!(server
+        .handle_timeouts(start + Duration::from_secs(5))
+        .unwrap()
+        .is_empty());
+
+    let errors = server.handle_timeouts(deadline).unwrap();
+    assert!(matches!(
+        errors[..],
+        [Http2Error::StreamTimeout(1, StreamTimeout::BodyIdle)]
+    ));
+    assert_eq!(
+        transfer(&mut server, &mut client),
+        vec![Event::Reset {
+            stream_id,
+            error_code: ErrorCode::Cancel,
+        }]
+    );
+}
+
+#[test]
+pub fn test_stream_timeout_total() {
+    let mut client = ConnectionBuilder::client()
+        .stream_timeout(Duration::from_secs(10))
+        .build()
+        .unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+
+    let start = Instant::now();
+    let stream_id = client.send_request(request(), false).unwrap();
+    let other_stream_id = client.send_request(request(), true).unwrap();
+    transfer(&mut client, &mut server);
+
+    // The second stream completes in time.
+    let response = HeaderList::new(vec![HeaderField::new(":status".into(), "204".into())]);
+    server
+        .send_response(other_stream_id, response.clone(), true)
+        .unwrap();
+    server.send_response(stream_id, response, false).unwrap();
+    transfer(&mut server, &mut client);
+
+    // The first one is still open in both directions.
+    let errors = client
+        .handle_timeouts(start + Duration::from_secs(11))
+        .unwrap();
+    assert!(matches!(
+        errors[..],
+        [Http2Error::StreamTimeout(1, StreamTimeout::Total)]
+    ));
+    assert_eq!(client.poll_timeout(), None);
+    assert!(matches!(
+        client.send_data(stream_id, b"data", true),
+        Err(Http2Error::StreamError(1, ErrorCode::StreamClosed, _))
+    ));
+}
+
+#[test]
+pub fn test_stream_timeout_error() {
+    let error = Http2Error::StreamTimeout(3, StreamTimeout::BodyIdle);
+    assert_eq!(error.error_code(), ErrorCode::Cancel);
+    assert_eq!(error.stream_id(), Some(3));
+    assert!(error.is_stream_error());
+    assert_eq!(
+        error.to_string(),
+        "Stream Timeout on stream 3: body idle timeout"
+    );
+}
+
+#[test]
+pub fn test_stream_timeout_sync() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // The server never responds.
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let mut connection = Connection::accept(io).unwrap();
+
+        assert!(matches!(
+            connection.next_event().unwrap(),
+            sync::Event::Headers {
+                stream_id: 1,
+                end_stream: true,
+                ..
+            }
+        ));
+        assert_eq!(
+            connection.next_event().unwrap(),
+            sync::Event::Reset {
+                stream_id: 1,
+                error_code: ErrorCode::Cancel,
+            }
+        );
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let inner = ConnectionBuilder::client()
+        .header_timeout(Duration::from_millis(100))
+        .handshake(io)
+        .unwrap();
+    let mut connection = Connection::from_connection(inner);
+
+    // Reads time out so that the deadlines are checked while the server is
+    // silent.
+    connection
+        .get_mut()
+        .get_mut()
+        .set_read_timeout(Some(Duration::from_millis(20)))
+        .unwrap();
+
+    let stream_id = connection.send_request(request(), None).unwrap();
+    match connection.next_event() {
+        Err(Http2Error::StreamTimeout(1, StreamTimeout::Headers)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert_eq!(stream_id, 1);
+
+    server.join().unwrap();
+}
@@ -0,0 +1,162 @@
+#![cfg(feature = "sync")]
+
+use std::net::{TcpListener, TcpStream};
+use std::sync::mpsc;
+use std::thread;
+use std::time::Duration;
+
+use http2::connection::builder::ConnectionBuilder;
+use http2::error::{ErrorCode, Http2Error};
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::sync::{Connection, Event};
+
+/// Read the events of a stream until it is ended by the peer, returning
+/// the header section and the content of the message.
+fn recv_message(connection: &mut Connection<TcpStream>) -> (u32, HeaderList, Vec<u8>) {
+    let (stream_id, header_list, mut end_stream) = match connection.next_event().unwrap() {
+        Event::Headers {
+            stream_id,
+            header_list,
+            end_stream,
+        } => (stream_id, header_list, end_stream),
+        event => panic!("Unexpected event {:?}", event),
+    };
+
+    let mut body = Vec::new();
+    while !end_stream {
+        match connection.next_event().unwrap() {
+            Event::Data {
+                stream_id: data_stream_id,
+                end_stream: end,
+                ..
+            } => {
+                body.extend(connection.recv_data(data_stream_id).unwrap());
+                end_stream = end;
+            }
+            event => panic!("Unexpected event {:?}", event),
+        }
+    }
+
+    (stream_id, header_list, body)
+}
+
+#[test]
+pub fn test_sync_connection_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // Both bodies exceed the initial flow-control windows.
+    let request_body: Vec<u8> = (0..100_000).map(|i| i as u8).collect();
+    let response_body: Vec<u8> = (0..70_000).map(|i| (i * 7) as u8).collect();
+
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "POST".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/upload".into()),
+    ]);
+    let response = HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())]);
+
+    let expected_request = (request.clone(), request_body.clone());
+    let server_response = (response.clone(), response_body.clone());
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let mut connection = Connection::accept(io).unwrap();
+
+        let (stream_id, header_list, body) = recv_message(&mut connection);
+        assert_eq!(stream_id, 1);
+        assert_eq!((header_list, body), expected_request);
+
+        let (header_list, body) = server_response;
+        connection
+            .send_response(stream_id, header_list, Some(&body))
+            .unwrap();
+
+        let (stream_id, _, body) = recv_message(&mut connection);
+        assert_eq!(stream_id, 3);
+        assert!(body.is_empty());
+        connection.send_response(stream_id, response, None).unwrap();
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let mut connection = Connection::connect(io).unwrap();
+
+    let stream_id = connection
+        .send_request(request.clone(), Some(&request_body))
+        .unwrap();
+    assert_eq!(stream_id, 1);
+    let (stream_id, header_list, body) = recv_message(&mut connection);
+    assert_eq!(stream_id, 1);
+    assert_eq!(header_list.status(), Some(200));
+    assert_eq!(body, response_body);
+
+    assert_eq!(connection.send_request(request, None).unwrap(), 3);
+    let (stream_id, _, body) = recv_message(&mut connection);
+    assert_eq!(stream_id, 3);
+    assert!(body.is_empty());
+
+    server.join().unwrap();
+}
+
+#[test]
+pub fn test_sync_connection_roles() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let mut connection = Connection::accept(io).unwrap();
+        assert!(matches!(
+            connection.send_request(HeaderList::default(), None),
+            Err(Http2Error::UsageError(_))
+        ));
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let mut connection = Connection::connect(io).unwrap();
+    assert!(matches!(
+        connection.send_response(1, HeaderList::default(), None),
+        Err(Http2Error::UsageError(_))
+    ));
+
+    server.join().unwrap();
+}
+
+#[test]
+pub fn test_sync_keep_alive() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+    let (sender, receiver) = mpsc::channel();
+
+    // The server stops reading after the handshake.
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let _connection = Connection::accept(io).unwrap();
+        receiver.recv().unwrap();
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let inner = ConnectionBuilder::client()
+        .ping_interval(Duration::from_millis(50))
+        .ping_timeout(Duration::from_millis(50))
+        .handshake(io)
+        .unwrap();
+    let mut connection = Connection::from_connection(inner);
+    connection
+        .get_mut()
+        .get_mut()
+        .set_read_timeout(Some(Duration::from_millis(10)))
+        .unwrap();
+
+    match connection.next_event() {
+        Err(Http2Error::ConnectionError(ErrorCode::NoError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+    assert_eq!(
+        connection.get_mut().stats().frames_sent().get("GOAWAY"),
+        Some(&1)
+    );
+
+    sender.send(()).unwrap();
+    server.join().unwrap();
+}
@@ -0,0 +1,101 @@
+#![cfg(feature = "tls-rustls")]
+
+use std::io::Cursor;
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::thread;
+
+use http2::error::Http2Error;
+use http2::stream::Role;
+use http2::tls::{self, ALPN_H2, ALPN_HTTP11};
+use rustls::crypto::ring;
+use rustls::pki_types::{CertificateDer, PrivateKeyDer, PrivatePkcs8KeyDer, ServerName};
+use rustls::{ClientConfig, RootCertStore, ServerConfig, ServerConnection, StreamOwned};
+
+/// Self-signed certificate for "localhost".
+const CERTIFICATE: &[u8] = include_bytes!("fixtures/localhost.cert.der");
+
+/// PKCS#8 private key of the certificate.
+const PRIVATE_KEY: &[u8] = include_bytes!("fixtures/localhost.key.der");
+
+fn server_config(alpn_protocols: &[&[u8]]) -> Arc<ServerConfig> {
+    let mut config = ServerConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_no_client_auth()
+        .with_single_cert(
+            vec![CertificateDer::from(CERTIFICATE)],
+            PrivateKeyDer::Pkcs8(PrivatePkcs8KeyDer::from(PRIVATE_KEY)),
+        )
+        .unwrap();
+    config.alpn_protocols = alpn_protocols.iter().map(|p| p.to_vec()).collect();
+    Arc::new(config)
+}
+
+fn client_config(alpn_protocols: &[&[u8]]) -> Arc<ClientConfig> {
+    let mut roots = RootCertStore::empty();
+    roots.add(CertificateDer::from(CERTIFICATE)).unwrap();
+
+    let mut config = ClientConfig::builder_with_provider(Arc::new(ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_no_client_auth();
+    config.alpn_protocols = alpn_protocols.iter().map(|p| p.to_vec()).collect();
+    Arc::new(config)
+}
+
+#[test]
+pub fn test_tls_alpn_h2() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let connection = tls::accept(server_config(&[ALPN_H2]), io).unwrap();
+        assert_eq!(connection.role(), Role::Server);
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let server_name = ServerName::try_from("localhost").unwrap();
+    let connection = tls::connect(client_config(&[ALPN_H2, ALPN_HTTP11]), server_name, io).unwrap();
+    assert_eq!(connection.role(), Role::Client);
+
+    server.join().unwrap();
+}
+
+#[test]
+pub fn test_tls_alpn_http11_rejected() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // The server only speaks HTTP/1.1.
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let tls = ServerConnection::new(server_config(&[ALPN_HTTP11])).unwrap();
+        let mut stream = StreamOwned::new(tls, io);
+        while stream.conn.is_handshaking() {
+            if stream.conn.complete_io(&mut stream.sock).is_err() {
+                break;
+            }
+        }
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let server_name = ServerName::try_from("localhost").unwrap();
+    let result = tls::connect(client_config(&[ALPN_H2, ALPN_HTTP11]), server_name, io);
+    match result {
+        Err(Http2Error::TlsError(message)) => assert!(message.contains("http/1.1")),
+        _ => panic!("expected the connection to be rejected"),
+    }
+
+    server.join().unwrap();
+
+    // A configuration that does not offer h2 is rejected before connecting.
+    let io = Cursor::new(Vec::new());
+    let server_name = ServerName::try_from("localhost").unwrap();
+    assert!(matches!(
+        tls::connect(client_config(&[ALPN_HTTP11]), server_name, io),
+        Err(Http2Error::TlsError(_))
+    ));
+}
@@ -0,0 +1,92 @@
+#![cfg(all(feature = "tracing", feature = "sync"))]
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use http2::frame::{decoder::FrameDecoder, Frame};
+use http2::header::table::HeaderTable;
+use http2::stream::{manager::StreamManager, Role};
+use tracing::field::{Field, Visit};
+use tracing::span::{Attributes, Id, Record};
+use tracing::{Event, Metadata, Subscriber};
+
+/// Subscriber recording every event as "target: field=value ...".
+#[derive(Clone, Default)]
+struct Recorder {
+    events: Arc<Mutex<Vec<String>>>,
+}
+
+struct EventVisitor(String);
+
+impl Visit for EventVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0.push_str(&format!(" {}={:?}", field.name(), value));
+    }
+}
+
+impl Subscriber for Recorder {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &Attributes<'_>) -> Id {
+        Id::from_u64(1)
+    }
+
+    fn record(&self, _: &Id, _: &Record<'_>) {}
+
+    fn record_follows_from(&self, _: &Id, _: &Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut visitor = EventVisitor(format!("{}:", event.metadata().target()));
+        event.record(&mut visitor);
+        self.events.lock().unwrap().push(visitor.0);
+    }
+
+    fn enter(&self, _: &Id) {}
+
+    fn exit(&self, _: &Id) {}
+}
+
+#[test]
+pub fn test_trace_events() {
+    let recorder = Recorder::default();
+
+    tracing::subscriber::with_default(recorder.clone(), || {
+        // A HEADERS frame adding an entry to the dynamic table.
+        let mut decoder = FrameDecoder::new(4096);
+        decoder.feed(&[
+            0x00, 0x00, 0x05, // Length = 5
+            0x01, // Frame Type = HEADERS
+            0x05, // Flags = [EndStream, EndHeaders]
+            0x00, 0x00, 0x00, 0x01, // Stream Identifier = 1
+            0x40, 0x01, 0x61, 0x01, 0x62, // a: b (incremental indexing)
+        ]);
+        let frame = decoder.next_frame().unwrap().unwrap();
+        assert!(matches!(frame, Frame::Headers(_)));
+
+        // Serialize it back.
+        let mut header_table = HeaderTable::new(4096);
+        let _ = frame.serialize(&mut header_table).unwrap();
+
+        // Open a stream.
+        let mut streams = StreamManager::new(Role::Server);
+        streams.open_remote(1).unwrap();
+    });
+
+    let events = recorder.events.lock().unwrap();
+    assert!(events.iter().any(|event| event.starts_with("http2::frame:")
+        && event.contains("direction=\"recv\"")
+        && event.contains("frame_type=\"HEADERS\"")
+        && event.contains("stream_id=1")
+        && event.contains("flags=5")
+        && event.contains("length=5")));
+    assert!(events
+        .iter()
+        .any(|event| event.starts_with("http2::hpack:") && event.contains("size=34")));
+    assert!(events
+        .iter()
+        .any(|event| event.starts_with("http2::stream:")
+            && event.contains("from=Idle")
+            && event.contains("to=Open")));
+}
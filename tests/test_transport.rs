@@ -0,0 +1,92 @@
+#![cfg(feature = "sync")]
+
+use std::io::ErrorKind;
+use std::thread;
+use std::time::Duration;
+
+use http2::error::Http2Error;
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::sync::{Connection, Event};
+use http2::transport::{duplex, ByteStream};
+
+#[test]
+pub fn test_transport_duplex() {
+    let (mut client, mut server) = duplex();
+
+    // Bytes written to an end are read from the other.
+    client.write_all(b"hello").unwrap();
+    let mut buffer = [0u8; 16];
+    assert_eq!(server.read(&mut buffer).unwrap(), 5);
+    assert_eq!(&buffer[..5], b"hello");
+
+    // Reads time out while the peer is silent.
+    server.set_read_timeout(Some(Duration::from_millis(10)));
+    assert_eq!(server.read_timeout(), Some(Duration::from_millis(10)));
+    match server.read(&mut buffer) {
+        Err(error) if error.kind() == ErrorKind::WouldBlock => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // The bytes written before the shutdown are read before its end.
+    client.write_all(b"bye").unwrap();
+    client.shutdown().unwrap();
+    assert_eq!(server.read(&mut buffer).unwrap(), 3);
+    assert_eq!(server.read(&mut buffer).unwrap(), 0);
+    match client.write(b"late") {
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+
+    // Writing to a dropped end fails.
+    drop(client);
+    match server.write(b"late") {
+        Err(error) if error.kind() == ErrorKind::BrokenPipe => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_transport_connection() {
+    let (client_io, server_io) = duplex();
+
+    let server = thread::spawn(move || {
+        let mut connection = Connection::accept(server_io).unwrap();
+        let stream_id = match connection.next_event().unwrap() {
+            Event::Headers { stream_id, .. } => stream_id,
+            event => panic!("Unexpected event {:?}", event),
+        };
+        let response = HeaderList::new(vec![HeaderField::new(":status".into(), "200".into())]);
+        connection
+            .send_response(stream_id, response, Some(b"in memory"))
+            .unwrap();
+
+        // The client shutting the transport down ends the connection.
+        match connection.next_event() {
+            Err(Http2Error::IoError(error)) if error.kind() == ErrorKind::UnexpectedEof => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    });
+
+    let mut connection = Connection::connect(client_io).unwrap();
+    let request = HeaderList::new(vec![
+        HeaderField::new(":method".into(), "GET".into()),
+        HeaderField::new(":scheme".into(), "http".into()),
+        HeaderField::new(":path".into(), "/".into()),
+    ]);
+    let stream_id = connection.send_request(request, None).unwrap();
+    loop {
+        match connection.next_event().unwrap() {
+            Event::Data {
+                stream_id: data_stream_id,
+                end_stream: true,
+                ..
+            } if data_stream_id == stream_id => break,
+            _ => (),
+        }
+    }
+    assert_eq!(connection.recv_data(stream_id).unwrap(), b"in memory");
+
+    connection.get_mut().shutdown().unwrap();
+    server.join().unwrap();
+}
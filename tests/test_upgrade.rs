@@ -0,0 +1,118 @@
+#![cfg(feature = "sync")]
+
+use http2::frame::settings::SettingsParameter;
+use http2::stream::{manager::StreamManager, Role, StreamState};
+use http2::upgrade::{
+    decode_http2_settings, encode_http2_settings, ClientUpgrade, ServerUpgrade,
+    SWITCHING_PROTOCOLS_RESPONSE,
+};
+
+#[test]
+pub fn test_http2_settings() {
+    // 0x00, 0x03, 0x00, 0x00, 0x00, 0x64,  MAX_CONCURRENT_STREAMS = 100
+    // 0x00, 0x04, 0x00, 0x00, 0xff, 0xff,  INITIAL_WINDOW_SIZE = 65535
+    let settings_parameters = vec![
+        SettingsParameter::MaxConcurrentStreams(100),
+        SettingsParameter::InitialWindowSize(65535),
+    ];
+
+    let encoded = encode_http2_settings(&settings_parameters);
+    assert_eq!(encoded, "AAMAAABkAAQAAP__");
+    assert_eq!(
+        decode_http2_settings(&encoded).unwrap(),
+        settings_parameters
+    );
+
+    // Payloads whose length is not a multiple of 3 are not padded.
+    let encoded = encode_http2_settings(&[SettingsParameter::EnablePush(0)]);
+    assert_eq!(encoded, "AAIAAAAA");
+    let encoded = encode_http2_settings(&[
+        SettingsParameter::EnablePush(0),
+        SettingsParameter::MaxFrameSize(16384),
+        SettingsParameter::HeaderTableSize(4096),
+        SettingsParameter::MaxHeaderListSize(1),
+        SettingsParameter::InitialWindowSize(1),
+    ]);
+    assert_eq!(encoded.len(), 40);
+    assert_eq!(decode_http2_settings(&encoded).unwrap().len(), 5);
+
+    // An empty payload is valid.
+    assert_eq!(decode_http2_settings("").unwrap(), vec![]);
+
+    // Invalid characters and truncated payloads are rejected.
+    assert!(decode_http2_settings("AAMAAABk+AQAAP//").is_err());
+    assert!(decode_http2_settings("AAMAAA").is_err());
+}
+
+#[test]
+pub fn test_upgrade() {
+    let mut client_streams = StreamManager::new(Role::Client);
+    let mut server_streams = StreamManager::new(Role::Server);
+
+    // The client sends the upgrade request.
+    let client = ClientUpgrade::new(
+        "GET",
+        "/",
+        "example.com",
+        vec![SettingsParameter::MaxConcurrentStreams(100)],
+    );
+    let request = client.request();
+
+    // The server parses it and accepts the upgrade.
+    let server = ServerUpgrade::from_request(&request).unwrap();
+    assert_eq!(server.method(), "GET");
+    assert_eq!(server.target(), "/");
+    assert_eq!(server.length(), request.len());
+    assert_eq!(
+        server.settings_parameters(),
+        &vec![SettingsParameter::MaxConcurrentStreams(100)]
+    );
+    let response = server.accept(&mut server_streams);
+    assert_eq!(response, SWITCHING_PROTOCOLS_RESPONSE);
+    assert_eq!(
+        server_streams.get(1).unwrap().state(),
+        StreamState::HalfClosedRemote
+    );
+    assert_eq!(server_streams.remote_active_streams(), 1);
+
+    // The client handles the response, followed by the server preface.
+    let mut bytes = response.to_vec();
+    bytes.extend_from_slice(&[0x00, 0x00, 0x00, 0x04, 0x00, 0x00, 0x00, 0x00, 0x00]);
+    let length = client.handle_response(&bytes, &mut client_streams).unwrap();
+    assert_eq!(length, Some(response.len()));
+    assert_eq!(
+        client_streams.get(1).unwrap().state(),
+        StreamState::HalfClosedLocal
+    );
+    assert_eq!(client_streams.local_active_streams(), 1);
+}
+
+#[test]
+pub fn test_upgrade_rejected() {
+    let mut streams = StreamManager::new(Role::Client);
+
+    // Missing HTTP2-Settings.
+    let request = b"GET / HTTP/1.1\r\nHost: a\r\nConnection: Upgrade\r\nUpgrade: h2c\r\n\r\n";
+    assert!(ServerUpgrade::from_request(request).is_err());
+
+    // Duplicate HTTP2-Settings.
+    let request = b"GET / HTTP/1.1\r\nHost: a\r\nConnection: Upgrade, HTTP2-Settings\r\nUpgrade: h2c\r\nHTTP2-Settings: \r\nHTTP2-Settings: \r\n\r\n";
+    assert!(ServerUpgrade::from_request(request).is_err());
+
+    // Upgrade to another protocol.
+    let request = b"GET / HTTP/1.1\r\nHost: a\r\nConnection: Upgrade, HTTP2-Settings\r\nUpgrade: websocket\r\nHTTP2-Settings: \r\n\r\n";
+    assert!(ServerUpgrade::from_request(request).is_err());
+
+    // Incomplete request head.
+    let request = b"GET / HTTP/1.1\r\nHost: a\r\n";
+    assert!(ServerUpgrade::from_request(request).is_err());
+
+    // The server ignores the upgrade.
+    let client = ClientUpgrade::new("GET", "/", "a", vec![]);
+    let response = b"HTTP/1.1 200 OK\r\nContent-Length: 0\r\n\r\n";
+    assert_eq!(
+        client.handle_response(response, &mut streams).unwrap(),
+        None
+    );
+    assert!(streams.get(1).is_none());
+}
@@ -0,0 +1,244 @@
+#![cfg(feature = "test-support")]
+
+use std::io::{Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
+
+use http2::connection::builder::ConnectionBuilder;
+use http2::error::{ErrorCode, Http2Error};
+use http2::frame::rst_stream::RstStreamFrame;
+use http2::frame::Frame;
+use http2::header::field::HeaderField;
+use http2::header::list::HeaderList;
+use http2::sync::{self, Connection};
+use http2::test_support::{establish, transfer};
+use http2::websocket;
+
+#[test]
+pub fn test_websocket_request() {
+    let request = websocket::request("https", "example.com", "/chat?room=1");
+    assert_eq!(request.method(), Some("CONNECT"));
+    assert_eq!(request.protocol(), Some("websocket"));
+    assert_eq!(request.scheme(), Some("https"));
+    assert_eq!(request.authority(), Some("example.com"));
+    assert_eq!(request.path(), Some("/chat?room=1"));
+    assert_eq!(request.get("sec-websocket-version"), Some("13"));
+    websocket::check_request(1, &request).unwrap();
+
+    assert!(websocket::is_accepted(&websocket::response()));
+    let refused = HeaderList::new(vec![HeaderField::new(":status".into(), "400".into())]);
+    assert!(!websocket::is_accepted(&refused));
+}
+
+#[test]
+pub fn test_websocket_check_request() {
+    let request = websocket::request("https", "example.com", "/chat");
+
+    let mut wrong_method = request.clone();
+    wrong_method.remove(":method");
+    wrong_method.append(":method", "GET");
+    let mut missing_path = request.clone();
+    missing_path.remove(":path");
+    let mut missing_authority = request.clone();
+    missing_authority.remove(":authority");
+    let mut wrong_version = request.clone();
+    wrong_version.remove("sec-websocket-version");
+    wrong_version.append("sec-websocket-version", "8");
+    let mut other_protocol = request.clone();
+    other_protocol.remove(":protocol");
+    other_protocol.append(":protocol", "connect-udp");
+
+    for header_list in [
+        wrong_method,
+        missing_path,
+        missing_authority,
+        wrong_version,
+        other_protocol.clone(),
+    ] {
+        match websocket::check_request(3, &header_list) {
+            Err(Http2Error::StreamError(3, ErrorCode::ProtocolError, _)) => (),
+            result => panic!("Unexpected result {:?}", result),
+        }
+    }
+
+    // Other protocols are well-formed extended CONNECT requests.
+    websocket::check_extended_connect(3, &other_protocol).unwrap();
+}
+
+#[test]
+pub fn test_websocket_settings_required() {
+    let request = websocket::request("http", "example.com", "/chat");
+
+    // The client cannot send extended CONNECT requests before the server
+    // enables them.
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server().build().unwrap();
+    establish(&mut client, &mut server);
+    assert!(!client.peer_settings().enable_connect_protocol());
+    assert!(matches!(
+        client.send_request(request.clone(), false),
+        Err(Http2Error::UsageError(_))
+    ));
+
+    // The server refuses extended CONNECT requests it did not enable, even
+    // from a client which received SETTINGS_ENABLE_CONNECT_PROTOCOL from
+    // another server.
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut enabled = ConnectionBuilder::server()
+        .enable_connect_protocol(true)
+        .build()
+        .unwrap();
+    let mut disabled = ConnectionBuilder::server().build().unwrap();
+    let preface = client.poll_output();
+    enabled.handle_input(&preface).unwrap();
+    disabled.handle_input(&preface).unwrap();
+    transfer(&mut enabled, &mut client);
+    assert!(client.peer_settings().enable_connect_protocol());
+
+    client.send_request(request, false).unwrap();
+    match disabled.handle_input(&client.poll_output()) {
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_websocket_malformed_request() {
+    let mut client = ConnectionBuilder::client().build().unwrap();
+    let mut server = ConnectionBuilder::server()
+        .enable_connect_protocol(true)
+        .build()
+        .unwrap();
+    establish(&mut client, &mut server);
+    assert!(client.peer_settings().enable_connect_protocol());
+
+    let mut request = websocket::request("http", "example.com", "/chat");
+    request.remove(":path");
+    client.send_request(request, false).unwrap();
+    match server.handle_input(&client.poll_output()) {
+        Err(Http2Error::StreamError(1, ErrorCode::ProtocolError, _)) => (),
+        result => panic!("Unexpected result {:?}", result),
+    }
+}
+
+#[test]
+pub fn test_websocket_over_tcp() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    // Messages exceed the initial flow-control windows.
+    let message: Vec<u8> = (0..100_000).map(|i| i as u8).collect();
+
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let inner = ConnectionBuilder::server()
+            .enable_connect_protocol(true)
+            .handshake(io)
+            .unwrap();
+        let mut connection = Connection::from_connection(inner);
+
+        let (stream_id, header_list) = match connection.next_event().unwrap() {
+            sync::Event::Headers {
+                stream_id,
+                header_list,
+                end_stream: false,
+            } => (stream_id, header_list),
+            event => panic!("Unexpected event {:?}", event),
+        };
+
+        // Echo the content until the client ends the stream.
+        let mut channel = connection
+            .accept_websocket(stream_id, &header_list)
+            .unwrap();
+        let mut buffer = [0u8; 4_096];
+        loop {
+            let read = channel.read(&mut buffer).unwrap();
+            if read == 0 {
+                break;
+            }
+            channel.write_all(&buffer[..read]).unwrap();
+        }
+        channel.close().unwrap();
+
+        // Keep the connection until the client closes it.
+        assert!(connection.next_event().is_err());
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let inner = ConnectionBuilder::client().handshake(io).unwrap();
+    let mut connection = Connection::from_connection(inner);
+
+    let request = websocket::request("http", &address.to_string(), "/echo");
+    let mut channel = connection.open_websocket(request).unwrap();
+    assert_eq!(channel.stream_id(), 1);
+    channel.write_all(&message).unwrap();
+    channel.flush().unwrap();
+    channel.close().unwrap();
+
+    let mut echo = Vec::new();
+    channel.read_to_end(&mut echo).unwrap();
+    assert_eq!(echo, message);
+
+    drop(connection);
+    server.join().unwrap();
+}
+
+#[test]
+pub fn test_websocket_reset() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let address = listener.local_addr().unwrap();
+
+    let server = thread::spawn(move || {
+        let (io, _) = listener.accept().unwrap();
+        let inner = ConnectionBuilder::server()
+            .enable_connect_protocol(true)
+            .handshake(io)
+            .unwrap();
+        let mut connection = Connection::from_connection(inner);
+
+        let (stream_id, header_list) = match connection.next_event().unwrap() {
+            sync::Event::Headers {
+                stream_id,
+                header_list,
+                ..
+            } => (stream_id, header_list),
+            event => panic!("Unexpected event {:?}", event),
+        };
+        let mut channel = connection
+            .accept_websocket(stream_id, &header_list)
+            .unwrap();
+
+        // The content sent before the reset is read first.
+        let mut buffer = [0u8; 5];
+        channel.read_exact(&mut buffer).unwrap();
+        assert_eq!(&buffer, b"hello");
+
+        // Reads and writes then fail with the error code of the client.
+        let error = channel.read(&mut buffer).unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::ConnectionReset);
+        assert!(matches!(
+            error.get_ref().unwrap().downcast_ref::<Http2Error>(),
+            Some(Http2Error::StreamError(1, ErrorCode::Cancel, _))
+        ));
+        let error = channel.write_all(b"late").unwrap_err();
+        assert_eq!(error.kind(), std::io::ErrorKind::ConnectionReset);
+    });
+
+    let io = TcpStream::connect(address).unwrap();
+    let inner = ConnectionBuilder::client().handshake(io).unwrap();
+    let mut connection = Connection::from_connection(inner);
+
+    let request = websocket::request("http", &address.to_string(), "/chat");
+    let mut channel = connection.open_websocket(request).unwrap();
+    channel.write_all(b"hello").unwrap();
+    let stream_id = channel.stream_id();
+    connection
+        .get_mut()
+        .send_frame(&Frame::RstStream(RstStreamFrame::new(
+            stream_id,
+            ErrorCode::Cancel,
+        )))
+        .unwrap();
+
+    server.join().unwrap();
+}